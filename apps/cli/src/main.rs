@@ -0,0 +1,286 @@
+//! Headless mITyGuitar: controller, chord mapping and audio engine without the UI
+//!
+//! Usage: mityguitar [--genre <name>] [--soundfont <file.sf2>] [--instrument <name>]
+//!                   [--buffer-size <frames>] [--host <name>] [--device <name>]
+//!                   [--backend <id>] [--stats <seconds>] [--list-devices]
+//! Settings not given on the command line come from the desktop app's config.
+//! Ctrl-C silences the guitar and prints the latency measured so far.
+
+use std::path::PathBuf;
+use std::process::ExitCode;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use anyhow::{Context, Result};
+use audio::{AudioOutput, SynthInstrumentType};
+use config::AppConfig;
+use controller::{ControllerBackend, PerformanceController, MAX_PLAYERS};
+use mapping::{ChordResolver, DynamicsMode, Genre, HarmonicMapper, MusicEvent, PalmMuteTrigger, PresetLoader, WhammyMode};
+
+/// How often the controller is read (the polling thread runs at 1000Hz)
+const POLL_INTERVAL: Duration = Duration::from_millis(1);
+/// Longest wait for a `--soundfont` to load before playing on the fallback synth
+const SOUNDFONT_LOAD_TIMEOUT: Duration = Duration::from_secs(30);
+
+const USAGE: &str = "Usage: mityguitar [--genre <name>] [--soundfont <file.sf2>] [--instrument <name>]
+                  [--buffer-size <frames>] [--host <name>] [--device <name>]
+                  [--backend <id>] [--stats <seconds>] [--list-devices]";
+
+/// Command line settings (None = use the config)
+#[derive(Debug, Default)]
+struct Options {
+    genre: Option<String>,
+    soundfont: Option<PathBuf>,
+    instrument: Option<String>,
+    buffer_size: Option<u32>,
+    host: Option<String>,
+    device: Option<String>,
+    backend: Option<String>,
+    /// Seconds between latency reports (None = no measurement)
+    stats_interval: Option<u64>,
+    list_devices: bool,
+}
+
+fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+
+    let options = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(options)) => options,
+        Ok(None) => {
+            println!("{}", USAGE);
+            return ExitCode::SUCCESS;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n{}", e, USAGE);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    match run(options) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {:#}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Parse the arguments (Ok(None) = help was asked for)
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Options>, String> {
+    let mut options = Options::default();
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("{} needs a value", arg));
+        match arg.as_str() {
+            "--genre" => options.genre = Some(value()?),
+            "--soundfont" => options.soundfont = Some(PathBuf::from(value()?)),
+            "--instrument" => options.instrument = Some(value()?),
+            "--buffer-size" => {
+                let frames = value()?;
+                options.buffer_size = Some(frames.parse().map_err(|_| format!("invalid buffer size: {}", frames))?);
+            }
+            "--host" => options.host = Some(value()?),
+            "--device" => options.device = Some(value()?),
+            "--backend" => options.backend = Some(value()?),
+            "--stats" => {
+                let seconds = value()?;
+                options.stats_interval = Some(seconds.parse().ok().filter(|&s| s > 0).ok_or_else(|| format!("invalid stats interval: {}", seconds))?);
+            }
+            "--list-devices" => options.list_devices = true,
+            "-h" | "--help" => return Ok(None),
+            _ => return Err(format!("unknown argument: {}", arg)),
+        }
+    }
+    Ok(Some(options))
+}
+
+fn run(options: Options) -> Result<()> {
+    let config = AppConfig::load().unwrap_or_else(|e| {
+        log::warn!("Failed to load config, using defaults: {}", e);
+        AppConfig::default()
+    });
+
+    // Audio output on the requested (or configured) backend and device
+    let host = options.host.as_deref().unwrap_or(config.audio.backend.as_str());
+    let device = options.device.as_deref().or(config.audio.output_device.as_deref());
+    let buffer_size = options.buffer_size.unwrap_or(config.audio.buffer_size);
+    if options.list_devices {
+        return list_devices(host);
+    }
+    let mut audio = AudioOutput::with_device(Some(host), device, Some(buffer_size))
+        .with_context(|| format!("Failed to open audio output on {}", host))?;
+    let stats = audio.get_stats();
+    log::info!("🔊 Audio: {} frames @ {} Hz (~{:.1} ms)", stats.buffer_size, stats.sample_rate, stats.estimated_latency_ms);
+    audio.set_max_voices(config.audio.max_voices)?;
+
+    if let Some(path) = options.soundfont {
+        audio.load_soundfont(path.clone())
+            .with_context(|| format!("Failed to load soundfont {}", path.display()))?;
+        // The load runs in the background; wait so the first strum uses it
+        let started = Instant::now();
+        while !audio.soundfonts_ready() && started.elapsed() < SOUNDFONT_LOAD_TIMEOUT {
+            thread::sleep(Duration::from_millis(20));
+        }
+        if let Some(error) = audio.take_loaded_soundfonts().into_iter().find_map(|loaded| loaded.error) {
+            anyhow::bail!("Failed to load soundfont {}: {}", path.display(), error);
+        }
+        log::info!("🎹 SoundFont: {}", path.display());
+    } else if let Some(name) = options.instrument {
+        let instrument = SynthInstrumentType::from_name(&name)
+            .with_context(|| format!("Unknown instrument: {}", name))?;
+        audio.set_virtual_instrument(instrument)?;
+        log::info!("🎹 Instrument: {}", name);
+    }
+
+    // One chord mapper per player, set up like the desktop app's
+    let genre_name = options.genre.as_deref().unwrap_or(config.mapping.genre.as_str());
+    let genre = parse_genre(genre_name).with_context(|| {
+        let names: Vec<_> = Genre::all().iter().map(|genre| genre.name()).collect();
+        format!("Unknown genre: {} (one of {})", genre_name, names.join(", "))
+    })?;
+    let resolver = Arc::new(load_chord_resolver());
+    let dynamics_mode = DynamicsMode::from_name(&config.mapping.dynamics_mode).unwrap_or_default();
+    let palm_mute_trigger = PalmMuteTrigger::from_name(&config.mapping.palm_mute_trigger).unwrap_or_default();
+    let whammy_mode = WhammyMode::from_name(&config.mapping.whammy_mode).unwrap_or_default();
+    let mut mappers: Vec<HarmonicMapper> = (0..MAX_PLAYERS)
+        .map(|player_index| {
+            let mut mapper = HarmonicMapper::new(Arc::clone(&resolver), genre);
+            mapper.set_dynamics_mode(dynamics_mode);
+            mapper.set_palm_mute_trigger(palm_mute_trigger);
+            mapper.set_diatonic(config.mapping.diatonic_chords);
+            mapper.set_whammy_mode(whammy_mode);
+            mapper.set_bass_mode(config.mapping.bass_players.contains(&player_index));
+            mapper.set_lead_mode(config.mapping.lead_players.contains(&player_index));
+            mapper
+        })
+        .collect();
+    log::info!("🎼 Genre: {}", genre.name());
+
+    // Controller polling (falls back to gilrs like the desktop app)
+    let mut controller = PerformanceController::new()?;
+    let backend_id = options.backend.as_deref().unwrap_or(config.controller.device_id.as_str());
+    controller.set_backend(ControllerBackend::from_device_id(backend_id));
+    if let Err(e) = controller.start_polling() {
+        if controller.backend() == ControllerBackend::Gilrs {
+            return Err(e.context("Failed to start controller polling"));
+        }
+        log::warn!("Failed to start {:?} polling ({}), falling back to gilrs", controller.backend(), e);
+        controller.set_backend(ControllerBackend::Gilrs);
+        controller.start_polling().context("Failed to start controller polling")?;
+    }
+    let _ = controller.scan_for_controllers();
+    match controller.connect_footswitch(None) {
+        Ok(Some(info)) => log::info!("🦶 Sustain pedal: {}", info.name),
+        Ok(None) => {}
+        Err(e) => log::warn!("Failed to open foot switch: {}", e),
+    }
+
+    if options.stats_interval.is_some() {
+        audio.set_latency_measurement(true);
+    }
+
+    let running = Arc::new(AtomicBool::new(true));
+    watch_ctrl_c(Arc::clone(&running));
+    log::info!("🎸 Ready, press Ctrl-C to stop");
+
+    let mut next_report = options.stats_interval.map(|seconds| Instant::now() + Duration::from_secs(seconds));
+    while running.load(Ordering::Relaxed) {
+        for (player_index, mapper) in mappers.iter_mut().enumerate() {
+            let Some(snapshot) = controller.get_player_state(player_index) else { continue };
+            if player_index > 0 && !snapshot.connected {
+                continue;
+            }
+            for event in mapper.process(&snapshot.to_controller_state()) {
+                if let Err(e) = audio.send_timed_player_event(player_index, event, snapshot.input_timestamp) {
+                    log::warn!("Failed to send audio event: {}", e);
+                }
+            }
+        }
+
+        if let (Some(report_at), Some(seconds)) = (next_report, options.stats_interval) {
+            if Instant::now() >= report_at {
+                print_stats(&audio);
+                next_report = Some(report_at + Duration::from_secs(seconds));
+            }
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+
+    // Let every note go before the stream closes
+    for (player_index, mapper) in mappers.iter_mut().enumerate() {
+        for event in mapper.panic() {
+            let _ = audio.send_timed_player_event(player_index, event, 0);
+        }
+    }
+    let _ = audio.send_event(MusicEvent::PanicAllNotesOff);
+    controller.stop_polling();
+    if options.stats_interval.is_some() {
+        print_stats(&audio);
+    }
+    Ok(())
+}
+
+/// Print the audio hosts and the devices of the selected one
+fn list_devices(host: &str) -> Result<()> {
+    println!("Hosts:");
+    for info in AudioOutput::list_hosts() {
+        println!("  {}{}", info.name, if info.is_default { " (default)" } else { "" });
+    }
+    let audio = AudioOutput::with_device(Some(host), None, None)
+        .with_context(|| format!("Failed to open audio output on {}", host))?;
+    println!("Devices on {}:", host);
+    for info in audio.list_devices()? {
+        println!("  {}{}", info.name, if info.is_default { " (default)" } else { "" });
+    }
+    Ok(())
+}
+
+/// Genre by name, ignoring case ("edm", "Punk")
+fn parse_genre(name: &str) -> Option<Genre> {
+    Genre::all().iter().copied().find(|genre| genre.name().eq_ignore_ascii_case(name))
+}
+
+/// Genre presets from assets/chordmaps, or the built-in ones
+fn load_chord_resolver() -> ChordResolver {
+    let assets_dir = ["assets", "../assets", "../../assets"]
+        .into_iter()
+        .map(PathBuf::from)
+        .find(|path| path.join("chordmaps").exists());
+    let Some(assets_dir) = assets_dir else {
+        log::info!("No chordmaps directory found, using built-in genre presets");
+        return PresetLoader::default_resolver();
+    };
+    let loaded = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .map_err(anyhow::Error::from)
+        .and_then(|runtime| runtime.block_on(PresetLoader::new(&assets_dir).load_all_presets()));
+    loaded.unwrap_or_else(|e| {
+        log::warn!("Failed to load genre presets from {:?}: {}", assets_dir, e);
+        PresetLoader::default_resolver()
+    })
+}
+
+/// Clear `running` when Ctrl-C is pressed
+fn watch_ctrl_c(running: Arc<AtomicBool>) {
+    thread::spawn(move || {
+        let Ok(runtime) = tokio::runtime::Builder::new_current_thread().enable_all().build() else {
+            log::warn!("Ctrl-C handling unavailable");
+            return;
+        };
+        if runtime.block_on(tokio::signal::ctrl_c()).is_ok() {
+            running.store(false, Ordering::Relaxed);
+        }
+    });
+}
+
+fn print_stats(audio: &AudioOutput) {
+    let stats = audio.get_stats();
+    let latency = audio.get_latency_stats();
+    println!(
+        "latency p50 {:.1} ms, p95 {:.1} ms, max {:.1} ms ({} notes) | buffer {} @ {} Hz, {} underruns, {} voices ({} stolen)",
+        latency.p50_ms, latency.p95_ms, latency.max_ms, latency.samples,
+        stats.buffer_size, stats.sample_rate, stats.underruns, stats.active_voices, stats.voices_stolen
+    );
+}
@@ -0,0 +1,21 @@
+//! Session autosave
+//! A background thread writes the song session (the chart as edited, the
+//! transport position and the score) to the recovery file every few
+//! seconds, so a crash costs at most that much. The file is removed on a
+//! clean exit; see `AppState::recover_last_session` for picking it up.
+
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::state::AppState;
+
+/// How often the session is written
+const AUTOSAVE_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Autosave the song session on a background thread
+pub fn spawn(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(AUTOSAVE_INTERVAL);
+        app.state::<AppState>().autosave_session();
+    });
+}
@@ -1,17 +1,29 @@
-use crate::state::AppState;
-use audio::AudioStats;
-use config::AppConfig;
+use crate::jobs::{JobContext, JobId, JobInfo};
+use crate::live_set::{self, LiveSetState};
+use crate::overlay::OverlayState;
+use crate::metrics::{self, CommandMetric, TimedLock};
+use crate::song_player::{LiveChange, PracticeMode, ScheduledBar};
+use crate::state::{find_assets_dir, parse_genre, AppState, ChordPlayed, RecoverableSession, SetlistPosition, TiltLayerInfo, TransposeInfo};
+use audio::{AudioDeviceInfo, AudioHostInfo, AudioMeters, AudioOutput, AudioStats, InputMonitorStats, LatencyStats, Temperament};
+use config::{AppConfig, AppPaths, BackupLocations, BackupManifest, ConfigProfile, ImportReport, InputMonitorConfig, MigrationReport, MixerConfig, OverlayConfig, SongConfig, TemperamentConfig};
 use controller::{
-    ControllerStateSnapshot, RawInputEvent, 
+    ControlId, ControllerStateSnapshot, RawInputEvent, 
     AppAction, MappingProfile, CaptureResult, CaptureState, ControllerId,
+    CalibrationStatus, WhammyCalibration, FilteredEventStats, whammy_calibration::DEFAULT_CALIBRATION_MS,
+    InputReplayer, ReplayStep, BackupVersion,
+    HardwareTestReport, hardware_test::DEFAULT_STEP_TIMEOUT_MS,
+    FootswitchInfo, FretLedInfo, RumbleCue, ControllerMacro, ResponseCurve, DemoScript,
 };
-use mapping::{LegacyGenre as Genre};
-use song::{SongChart, InstrumentRef};
+use mapping::{BendTarget, DynamicsMode, LegacyGenre as Genre, MusicEvent, PalmMuteTrigger, PluginInfo, WhammyMode, WhammyPullEffect};
+use song::{BackingTrackRef, ChartMetaEdit, ChordEvent, FretLedMode, InstrumentRef, LibrarySong, LyricEvent, Section, Setlist, SongChart, SongFilters, DEFAULT_LANE};
 use serde::{Deserialize, Serialize};
 use serde_json::Value as JsonValue;
 use tauri::{State, Manager};
 use hidapi::HidApi;
 use std::collections::HashMap;
+use std::time::{Duration, Instant};
+#[cfg(feature = "simulator")]
+use controller::simulator::InputScript;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GenreInfo {
@@ -21,10 +33,11 @@ pub struct GenreInfo {
 }
 
 /// Get current controller state (INSTANT atomic read!)
+/// `player_index` selects the guitar (defaults to player 1)
 #[tauri::command]
-pub fn get_controller_state(state: State<AppState>) -> ControllerStateSnapshot {
+pub fn get_controller_state(player_index: Option<usize>, state: State<AppState>) -> ControllerStateSnapshot {
     // Get the current state first (INSTANT!)
-    let controller_state = state.get_controller_state();
+    let controller_state = state.get_player_controller_state(player_index.unwrap_or(0));
     
     // Process input for audio using the conversion function
     let _ = state.process_controller_input();
@@ -32,11 +45,31 @@ pub fn get_controller_state(state: State<AppState>) -> ControllerStateSnapshot {
     controller_state
 }
 
+/// Get the player slots that have a connected guitar
+#[tauri::command]
+pub fn get_connected_players(state: State<AppState>) -> Vec<usize> {
+    let controller = state.controller.timed_lock();
+    controller.connected_players()
+}
+
+/// Set the instrument for a player slot
+#[cfg(feature = "soundfont")]
+#[tauri::command]
+pub fn set_player_instrument(player_index: usize, name: String, state: State<AppState>) -> Result<(), String> {
+    state.set_player_instrument(player_index, name)
+}
+
+#[cfg(not(feature = "soundfont"))]
+#[tauri::command]
+pub fn set_player_instrument(_player_index: usize, _name: String, _state: State<AppState>) -> Result<(), String> {
+    Err("SoundFont feature not enabled".to_string())
+}
+
 /// Simulator: handle key down
 #[cfg(feature = "simulator")]
 #[tauri::command]
 pub fn simulator_key_down(key: String, state: State<AppState>) -> Result<(), String> {
-    let mut sim = state.simulator.lock().unwrap();
+    let mut sim = state.simulator.timed_lock();
     sim.key_down(&key);
     drop(sim);
     
@@ -55,7 +88,7 @@ pub fn simulator_key_down(_key: String, _state: State<AppState>) -> Result<(), S
 #[cfg(feature = "simulator")]
 #[tauri::command]
 pub fn simulator_key_up(key: String, state: State<AppState>) -> Result<(), String> {
-    let mut sim = state.simulator.lock().unwrap();
+    let mut sim = state.simulator.timed_lock();
     sim.key_up(&key);
     drop(sim);
     
@@ -70,41 +103,389 @@ pub fn simulator_key_up(_key: String, _state: State<AppState>) -> Result<(), Str
     Err("Simulator not enabled".to_string())
 }
 
-/// Set the current genre
+/// Simulator: mouse wheel notches move the whammy's resting position
+/// (positive = pushed further)
+#[cfg(feature = "simulator")]
+#[tauri::command]
+pub fn simulator_wheel(notches: f32, state: State<AppState>) -> Result<(), String> {
+    state.simulator.timed_lock().wheel(notches);
+    state.process_controller_input().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[cfg(not(feature = "simulator"))]
+#[tauri::command]
+pub fn simulator_wheel(_notches: f32, _state: State<AppState>) -> Result<(), String> {
+    Err("Simulator not enabled".to_string())
+}
+
+/// Simulator: play a timed key script (JSON list of key downs and ups) through
+/// the same path as typed keys. Runs as a background job; every key is let go
+/// when it ends or is cancelled.
+#[cfg(feature = "simulator")]
+#[tauri::command]
+pub fn simulator_play_script(path: String, app_handle: tauri::AppHandle, state: State<AppState>) -> Result<JobId, String> {
+    let script = InputScript::load(std::path::Path::new(&path)).map_err(|e| format!("{:#}", e))?;
+    script.validate(&state.simulator.timed_lock()).map_err(|e| e.to_string())?;
+
+    state.jobs.spawn(&app_handle, "input-script", move |job| {
+        let state = job.app().state::<AppState>();
+        log::info!("⌨️ Input script {} ({} events, {} ms)", path, script.events.len(), script.duration_ms());
+        let result = play_input_script(&script, &state, job);
+        state.simulator.timed_lock().release_all();
+        if let Err(e) = state.process_controller_input() {
+            log::warn!("Failed to release scripted keys: {}", e);
+        }
+        result
+    })
+}
+
+#[cfg(not(feature = "simulator"))]
+#[tauri::command]
+pub fn simulator_play_script(_path: String, _state: State<AppState>) -> Result<JobId, String> {
+    Err("Simulator not enabled".to_string())
+}
+
+/// Feed each scripted key in at its time
+#[cfg(feature = "simulator")]
+fn play_input_script(script: &InputScript, state: &AppState, job: &JobContext) -> Result<String, String> {
+    let started = Instant::now();
+    for (i, event) in script.events.iter().enumerate() {
+        let due = started + Duration::from_millis(event.at_ms);
+        while let Some(wait) = due.checked_duration_since(Instant::now()) {
+            job.check_cancelled()?;
+            std::thread::sleep(wait.min(DEMO_WAIT_SLICE));
+        }
+        job.check_cancelled()?;
+        state.simulator.timed_lock().apply(event);
+        state.process_controller_input().map_err(|e| e.to_string())?;
+        job.progress(i as f32 / script.events.len() as f32, format!("Event {} of {}", i + 1, script.events.len()));
+    }
+    Ok(format!("Played {} scripted key events", script.events.len()))
+}
+
+/// Set the current genre (waits for the next bar when changes are quantized)
 #[tauri::command]
 pub fn set_genre(genre_name: String, state: State<AppState>) -> Result<(), String> {
-    let genre = match genre_name.to_lowercase().as_str() {
-        "punk" => Genre::Punk,
-        "rock" => Genre::Rock,
-        "edm" => Genre::Edm,
-        "metal" => Genre::Metal,
-        "folk" => Genre::Folk,
-        "pop" => Genre::Pop,
-        _ => return Err("Invalid genre".to_string()),
-    };
+    if parse_genre(&genre_name).is_none() {
+        return Err("Invalid genre".to_string());
+    }
+    state.request_change(LiveChange::Genre(genre_name))
+}
+
+/// Enable or disable voice leading for the current genre (remembered per genre)
+#[tauri::command]
+pub fn set_voice_leading(enabled: bool, state: State<AppState>) -> Result<(), String> {
+    state.mapper.timed_lock().set_voice_leading(enabled);
+    for player_mapper in &state.player_mappers {
+        player_mapper.timed_lock().set_voice_leading(enabled);
+    }
     
-    let mut mapper = state.mapper.lock().unwrap();
-    mapper.set_genre(genre);
+    let genre = state.active_genre().to_lowercase();
+    let mut config = state.config.timed_lock();
+    config.mapping.voice_leading.insert(genre, enabled);
+    config.save().map_err(|e| e.to_string())
+}
+
+/// Whether voice leading is enabled for the current genre
+#[tauri::command]
+pub fn get_voice_leading(state: State<AppState>) -> bool {
+    state.mapper.timed_lock().voice_leading()
+}
+
+/// Strum feel settings for the UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrumSettings {
+    pub guitar_voicing: bool,
+    pub strum_stagger: bool,
+    pub strum_spread_ms: f32,
+}
+
+/// Play chords as six-string guitar shapes, strummed string by string
+#[tauri::command]
+pub fn set_guitar_voicing(enabled: bool, strum_spread_ms: Option<f32>, state: State<AppState>) -> Result<(), String> {
+    let mut mapper = state.mapper.timed_lock();
+    let strum_spread_ms = strum_spread_ms.unwrap_or_else(|| mapper.strum_spread_ms());
+    mapper.set_guitar_voicing(enabled, strum_spread_ms);
+    let strum_spread_ms = mapper.strum_spread_ms();
+    drop(mapper);
+    for player_mapper in &state.player_mappers {
+        player_mapper.timed_lock().set_guitar_voicing(enabled, strum_spread_ms);
+    }
     
-    // Update config
-    let mut config = state.config.lock().unwrap();
-    config.mapping.genre = genre_name;
-    config.save().map_err(|e| e.to_string())?;
+    let mut config = state.config.timed_lock();
+    config.mapping.guitar_voicing = enabled;
+    config.mapping.strum_spread_ms = strum_spread_ms;
+    config.save().map_err(|e| e.to_string())
+}
+
+/// Stagger the notes of strummed chords in strum direction (spread 5-20ms between notes)
+#[tauri::command]
+pub fn set_strum_stagger(enabled: bool, spread_ms: Option<f32>, state: State<AppState>) -> Result<(), String> {
+    let mut mapper = state.mapper.timed_lock();
+    let spread_ms = spread_ms.unwrap_or_else(|| mapper.strum_spread_ms());
+    mapper.set_strum_stagger(enabled, spread_ms);
+    let spread_ms = mapper.strum_spread_ms();
+    drop(mapper);
+    for player_mapper in &state.player_mappers {
+        player_mapper.timed_lock().set_strum_stagger(enabled, spread_ms);
+    }
     
-    Ok(())
+    let mut config = state.config.timed_lock();
+    config.mapping.strum_stagger = enabled;
+    config.mapping.strum_spread_ms = spread_ms;
+    config.save().map_err(|e| e.to_string())
+}
+
+/// Current guitar voicing and strum stagger settings
+#[tauri::command]
+pub fn get_strum_settings(state: State<AppState>) -> StrumSettings {
+    let mapper = state.mapper.timed_lock();
+    StrumSettings {
+        guitar_voicing: mapper.guitar_voicing(),
+        strum_stagger: mapper.strum_stagger(),
+        strum_spread_ms: mapper.strum_spread_ms(),
+    }
+}
+
+/// Play through the legacy pattern mapper instead of the genre presets
+#[tauri::command]
+pub fn set_legacy_mapper(enabled: bool, state: State<AppState>) -> Result<(), String> {
+    state.set_legacy_mapper(enabled)
+}
+
+/// Whether the legacy pattern mapper is in use
+#[tauri::command]
+pub fn get_legacy_mapper(state: State<AppState>) -> bool {
+    state.uses_legacy_mapper()
+}
+
+/// Let a player's frets play single bass roots (strum up for the octave above)
+#[tauri::command]
+pub fn set_bass_mode(player_index: usize, enabled: bool, state: State<AppState>) -> Result<(), String> {
+    state.set_bass_mode(player_index, enabled)
+}
+
+/// Players in bass mode
+#[tauri::command]
+pub fn get_bass_players(state: State<AppState>) -> Vec<usize> {
+    state.bass_players()
+}
+
+/// Let a player's solo frets play single notes of the key's scale over the chords
+#[tauri::command]
+pub fn set_lead_mode(player_index: usize, enabled: bool, state: State<AppState>) -> Result<(), String> {
+    state.set_lead_mode(player_index, enabled)
+}
+
+/// Players in lead mode
+#[tauri::command]
+pub fn get_lead_players(state: State<AppState>) -> Vec<usize> {
+    state.lead_players()
+}
+
+/// Mapper plugins found in the plugin folder
+#[tauri::command]
+pub fn list_mapper_plugins(state: State<AppState>) -> Vec<PluginInfo> {
+    state.list_plugins()
+}
+
+/// Load plugin libraries added to the plugin folder
+#[tauri::command]
+pub fn rescan_mapper_plugins(state: State<AppState>) -> Vec<PluginInfo> {
+    state.rescan_plugins()
+}
+
+/// Let a mapper plugin play a player's frets (None = back to the chord mappers)
+#[tauri::command]
+pub fn set_player_plugin(player_index: usize, name: Option<String>, state: State<AppState>) -> Result<(), String> {
+    state.set_player_plugin(player_index, name)
+}
+
+/// Plugin each player is using (None = chord mappers)
+#[tauri::command]
+pub fn get_player_plugins(state: State<AppState>) -> Vec<Option<String>> {
+    state.player_plugins()
+}
+
+/// Correct the genre presets' chord qualities to the key and mode
+#[tauri::command]
+pub fn set_diatonic_chords(enabled: bool, state: State<AppState>) -> Result<(), String> {
+    for harmonic_mapper in &state.harmonic_mappers {
+        harmonic_mapper.timed_lock().set_diatonic(enabled);
+    }
+    let mut config = state.config.timed_lock();
+    config.mapping.diatonic_chords = enabled;
+    config.save().map_err(|e| e.to_string())
+}
+
+/// Whether chord qualities are corrected to the key
+#[tauri::command]
+pub fn get_diatonic_chords(state: State<AppState>) -> bool {
+    state.harmonic_mappers[0].timed_lock().diatonic()
+}
+
+/// Let genre presets and songs load their instrument when switching to them
+#[tauri::command]
+pub fn set_auto_instrument(enabled: bool, state: State<AppState>) -> Result<(), String> {
+    state.set_auto_instrument(enabled)
+}
+
+/// Whether genre presets and songs switch the instrument
+#[tauri::command]
+pub fn get_auto_instrument(state: State<AppState>) -> bool {
+    state.auto_instrument()
+}
+
+/// Choose what sets strum velocity ("fixed", "whammy" or "strum_speed")
+#[tauri::command]
+pub fn set_dynamics_mode(mode: String, state: State<AppState>) -> Result<(), String> {
+    let dynamics_mode = DynamicsMode::from_name(&mode)
+        .ok_or_else(|| format!("Unknown dynamics mode: {}", mode))?;
+    state.mapper.timed_lock().set_dynamics_mode(dynamics_mode);
+    for player_mapper in &state.player_mappers {
+        player_mapper.timed_lock().set_dynamics_mode(dynamics_mode);
+    }
+    for harmonic_mapper in &state.harmonic_mappers {
+        harmonic_mapper.timed_lock().set_dynamics_mode(dynamics_mode);
+    }
+
+    let mut config = state.config.timed_lock();
+    config.mapping.dynamics_mode = dynamics_mode.name().to_string();
+    config.save().map_err(|e| e.to_string())
+}
+
+/// Current dynamics mode
+#[tauri::command]
+pub fn get_dynamics_mode(state: State<AppState>) -> String {
+    state.mapper.timed_lock().dynamics_mode().name().to_string()
+}
+
+/// Choose the input that holds palm mute ("off", "select_held" or "tilt_down")
+#[tauri::command]
+pub fn set_palm_mute_trigger(trigger: String, state: State<AppState>) -> Result<(), String> {
+    let palm_mute_trigger = PalmMuteTrigger::from_name(&trigger)
+        .ok_or_else(|| format!("Unknown palm mute trigger: {}", trigger))?;
+    state.mapper.timed_lock().set_palm_mute_trigger(palm_mute_trigger);
+    for player_mapper in &state.player_mappers {
+        player_mapper.timed_lock().set_palm_mute_trigger(palm_mute_trigger);
+    }
+    for harmonic_mapper in &state.harmonic_mappers {
+        harmonic_mapper.timed_lock().set_palm_mute_trigger(palm_mute_trigger);
+    }
+
+    let mut config = state.config.timed_lock();
+    config.mapping.palm_mute_trigger = palm_mute_trigger.name().to_string();
+    config.save().map_err(|e| e.to_string())
+}
+
+/// Current palm mute trigger
+#[tauri::command]
+pub fn get_palm_mute_trigger(state: State<AppState>) -> String {
+    state.mapper.timed_lock().palm_mute_trigger().name().to_string()
+}
+
+/// Fade a virtual instrument (e.g. "Strings") in under the guitar as it tilts up
+#[tauri::command]
+pub fn set_tilt_layer(enabled: bool, instrument: String, state: State<AppState>) -> Result<(), String> {
+    state.set_tilt_layer(enabled, &instrument)
+}
+
+/// Current tilt layer settings
+#[tauri::command]
+pub fn get_tilt_layer(state: State<AppState>) -> TiltLayerInfo {
+    state.tilt_layer()
+}
+
+/// Choose what raising the neck does ("star_power", "filter_sweep", "overdrive" or "layer")
+#[tauri::command]
+pub fn set_tilt_mode(mode: String, state: State<AppState>) -> Result<(), String> {
+    state.set_tilt_mode(&mode)
+}
+
+/// What raising the neck does
+#[tauri::command]
+pub fn get_tilt_mode(state: State<AppState>) -> String {
+    state.tilt_mode().name().to_string()
+}
+
+/// Choose what the whammy bends ("all_notes" or "top_note" for lead bends)
+#[tauri::command]
+pub fn set_bend_target(target: String, state: State<AppState>) -> Result<(), String> {
+    let bend_target = BendTarget::from_name(&target)
+        .ok_or_else(|| format!("Unknown bend target: {}", target))?;
+    state.mapper.timed_lock().set_bend_target(bend_target);
+    for player_mapper in &state.player_mappers {
+        player_mapper.timed_lock().set_bend_target(bend_target);
+    }
+
+    let mut config = state.config.timed_lock();
+    config.mapping.bend_target = bend_target.name().to_string();
+    config.save().map_err(|e| e.to_string())
+}
+
+/// Current whammy bend target
+#[tauri::command]
+pub fn get_bend_target(state: State<AppState>) -> String {
+    state.mapper.timed_lock().bend_target().name().to_string()
+}
+
+/// Split whammy settings for the UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WhammySplitSettings {
+    pub enabled: bool,
+    /// "vibrato", "bend_up" or "off"
+    pub pull_effect: String,
+}
+
+/// Split the whammy at its center: push bends down, pull applies the pull effect
+#[tauri::command]
+pub fn set_whammy_split(enabled: bool, pull_effect: String, state: State<AppState>) -> Result<(), String> {
+    let pull = WhammyPullEffect::from_name(&pull_effect)
+        .ok_or_else(|| format!("Unknown whammy pull effect: {}", pull_effect))?;
+    state.mapper.timed_lock().set_whammy_split(enabled, pull);
+    for player_mapper in &state.player_mappers {
+        player_mapper.timed_lock().set_whammy_split(enabled, pull);
+    }
+
+    let mut config = state.config.timed_lock();
+    config.mapping.whammy_split = enabled;
+    config.mapping.whammy_pull_effect = pull.name().to_string();
+    config.save().map_err(|e| e.to_string())
+}
+
+/// Current split whammy settings
+#[tauri::command]
+pub fn get_whammy_split(state: State<AppState>) -> WhammySplitSettings {
+    let (enabled, pull) = state.mapper.timed_lock().whammy_split();
+    WhammySplitSettings { enabled, pull_effect: pull.name().to_string() }
+}
+
+/// Choose what the whammy controls ("pitch_bend", "vibrato", "filter_cutoff" or "volume_swell")
+#[tauri::command]
+pub fn set_whammy_mode(mode: String, state: State<AppState>) -> Result<(), String> {
+    let whammy_mode = WhammyMode::from_name(&mode)
+        .ok_or_else(|| format!("Unknown whammy mode: {}", mode))?;
+    state.set_whammy_mode(whammy_mode)
+}
+
+/// Current whammy mode
+#[tauri::command]
+pub fn get_whammy_mode(state: State<AppState>) -> String {
+    state.mapper.timed_lock().whammy_mode().name().to_string()
 }
 
 /// Next chord pattern
 #[tauri::command]
 pub fn next_pattern(state: State<AppState>) -> Result<(), String> {
-    let mut mapper = state.mapper.lock().unwrap();
+    let mut mapper = state.mapper.timed_lock();
     mapper.next_pattern();
     
     // Update config
     let pattern_index = mapper.pattern_index();
     drop(mapper);
     
-    let mut config = state.config.lock().unwrap();
+    let mut config = state.config.timed_lock();
     config.mapping.pattern_index = pattern_index;
     config.save().map_err(|e| e.to_string())?;
     
@@ -114,14 +495,14 @@ pub fn next_pattern(state: State<AppState>) -> Result<(), String> {
 /// Previous chord pattern
 #[tauri::command]
 pub fn prev_pattern(state: State<AppState>) -> Result<(), String> {
-    let mut mapper = state.mapper.lock().unwrap();
+    let mut mapper = state.mapper.timed_lock();
     mapper.prev_pattern();
     
     // Update config
     let pattern_index = mapper.pattern_index();
     drop(mapper);
     
-    let mut config = state.config.lock().unwrap();
+    let mut config = state.config.timed_lock();
     config.mapping.pattern_index = pattern_index;
     config.save().map_err(|e| e.to_string())?;
     
@@ -157,7 +538,7 @@ pub fn prev_instrument(_state: State<AppState>) -> Result<(), String> {
 /// Panic - stop all notes
 #[tauri::command]
 pub fn panic_all_notes_off(state: State<AppState>) -> Result<(), String> {
-    let mut mapper = state.mapper.lock().unwrap();
+    let mut mapper = state.mapper.timed_lock();
     let events = mapper.panic();
     drop(mapper);
     
@@ -165,6 +546,14 @@ pub fn panic_all_notes_off(state: State<AppState>) -> Result<(), String> {
         crate::state::send_audio_event(event).map_err(|e| e.to_string())?;
     }
     
+    // Reset the other players' mappers too (the audio panic already silenced their synths)
+    for player_mapper in &state.player_mappers {
+        let _ = player_mapper.timed_lock().panic();
+    }
+    for harmonic_mapper in &state.harmonic_mappers {
+        let _ = harmonic_mapper.timed_lock().panic();
+    }
+    
     Ok(())
 }
 
@@ -181,21 +570,186 @@ pub fn get_audio_stats(state: State<AppState>) -> AudioStats {
     state.get_audio_stats()
 }
 
+/// Output peak/RMS levels and clipped samples since the previous call, plus
+/// the 32-band spectrum in dBFS when `spectrum` is set (for meters polled by the UI)
+#[tauri::command]
+pub fn get_audio_meters(spectrum: Option<bool>, state: State<AppState>) -> AudioMeters {
+    state.get_audio_meters(spectrum.unwrap_or(false))
+}
+
+/// Whether the selected SoundFonts have finished loading (`soundfont-loaded`
+/// fires for each one as it does)
+#[tauri::command]
+pub fn get_soundfonts_ready(state: State<AppState>) -> bool {
+    state.soundfonts_ready()
+}
+
+/// Audio backends (cpal hosts) for the backend picker
+#[derive(Debug, Clone, Serialize)]
+pub struct AudioBackendsResponse {
+    pub backends: Vec<AudioHostInfo>,
+    /// Active backend (None = platform default)
+    pub active: Option<String>,
+}
+
+/// List available audio backends (WASAPI, ASIO, JACK, ALSA, CoreAudio, ...)
+#[tauri::command]
+pub fn get_audio_backends(state: State<AppState>) -> Result<AudioBackendsResponse, String> {
+    let configured = state.config.timed_lock().audio.backend.clone();
+    let backends = AudioOutput::list_hosts();
+    let active = backends.iter()
+        .find(|host| host.name.eq_ignore_ascii_case(&configured))
+        .map(|host| host.name.clone());
+    Ok(AudioBackendsResponse { backends, active })
+}
+
+/// Select the audio backend ("default" = platform default)
+#[tauri::command]
+pub fn set_audio_backend(backend: String, state: State<AppState>) -> Result<(), String> {
+    state.set_audio_backend(backend)
+        .map_err(|e| e.to_string())
+}
+
+/// List available audio output devices
+#[tauri::command]
+pub fn list_audio_devices(state: State<AppState>) -> Result<Vec<AudioDeviceInfo>, String> {
+    state.list_audio_devices()
+        .map_err(|e| e.to_string())
+}
+
+/// Select the audio output device (empty/none = system default)
+#[tauri::command]
+pub fn set_audio_device(name: Option<String>, state: State<AppState>) -> Result<(), String> {
+    let name = name.filter(|name| !name.is_empty());
+    state.set_audio_device(name)
+        .map_err(|e| e.to_string())
+}
+
+/// Enable or disable input-to-audio latency measurement
+#[tauri::command]
+pub fn set_latency_measurement_enabled(enabled: bool, state: State<AppState>) -> Result<(), String> {
+    state.set_latency_measurement(enabled)
+        .map_err(|e| e.to_string())
+}
+
+/// Get input-to-audio latency stats (p50/p95/max) for buffer size tuning
+#[tauri::command]
+pub fn get_latency_stats(state: State<AppState>) -> Result<LatencyStats, String> {
+    state.get_latency_stats()
+        .map_err(|e| e.to_string())
+}
+
+/// Clear collected latency samples
+#[tauri::command]
+pub fn reset_latency_stats(state: State<AppState>) -> Result<(), String> {
+    state.reset_latency_stats()
+        .map_err(|e| e.to_string())
+}
+
+/// List audio input devices for monitoring
+#[tauri::command]
+pub fn list_input_devices(state: State<AppState>) -> Result<Vec<AudioDeviceInfo>, String> {
+    state.list_input_devices()
+        .map_err(|e| e.to_string())
+}
+
+/// Monitor a guitar/mic input through the output (device none = default input,
+/// channel none = all channels mixed)
+#[tauri::command]
+pub fn start_input_monitor(
+    device: Option<String>,
+    channel: Option<u16>,
+    gain: Option<f32>,
+    state: State<AppState>,
+) -> Result<(), String> {
+    let gain = gain.unwrap_or_else(|| state.config.timed_lock().audio.input_monitor.gain);
+    state.set_input_monitor(InputMonitorConfig {
+        enabled: true,
+        device: device.filter(|name| !name.is_empty()),
+        channel,
+        gain,
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Stop input monitoring
+#[tauri::command]
+pub fn stop_input_monitor(state: State<AppState>) -> Result<(), String> {
+    let settings = state.config.timed_lock().audio.input_monitor.clone();
+    state.set_input_monitor(InputMonitorConfig { enabled: false, ..settings })
+        .map_err(|e| e.to_string())
+}
+
+/// Set the monitoring level (0.0-4.0), returns the applied value
+#[tauri::command]
+pub fn set_input_monitor_gain(gain: f32, state: State<AppState>) -> Result<f32, String> {
+    state.set_input_monitor_gain(gain)
+        .map_err(|e| e.to_string())
+}
+
+/// Input level, monitoring latency and status
+#[tauri::command]
+pub fn get_input_monitor_stats(state: State<AppState>) -> Result<InputMonitorStats, String> {
+    state.input_monitor_stats()
+        .map_err(|e| e.to_string())
+}
+
+/// Execution and lock wait times per command, slowest first
+#[tauri::command]
+pub fn get_command_metrics() -> Vec<CommandMetric> {
+    metrics::report()
+}
+
+/// Clear collected command timings
+#[tauri::command]
+pub fn reset_command_metrics() {
+    metrics::reset();
+}
+
 /// Get current configuration
 #[tauri::command]
 pub fn get_config(state: State<AppState>) -> AppConfig {
-    state.config.lock().unwrap().clone()
+    state.config.timed_lock().clone()
 }
 
 /// Save configuration
 #[tauri::command]
 pub fn save_config(config: AppConfig, state: State<AppState>) -> Result<(), String> {
-    let mut current_config = state.config.lock().unwrap();
+    let mut current_config = state.config.timed_lock();
     *current_config = config;
     current_config.save().map_err(|e| e.to_string())?;
     Ok(())
 }
 
+/// Names of the stored config profiles
+#[tauri::command]
+pub fn list_config_profiles(state: State<AppState>) -> Result<Vec<String>, String> {
+    state.list_config_profiles()
+}
+
+/// Config profile applied last (None = settings changed by hand)
+#[tauri::command]
+pub fn get_active_config_profile(state: State<AppState>) -> Option<String> {
+    state.active_config_profile()
+}
+
+/// Switch audio, mapping and instrument settings to a config profile
+#[tauri::command]
+pub fn apply_config_profile(name: String, state: State<AppState>) -> Result<ConfigProfile, String> {
+    state.apply_config_profile(&name)
+}
+
+/// Save the current settings as a config profile (replacing one with the same name)
+#[tauri::command]
+pub fn save_config_profile(name: String, state: State<AppState>) -> Result<ConfigProfile, String> {
+    state.save_config_profile(&name)
+}
+
+#[tauri::command]
+pub fn delete_config_profile(name: String, state: State<AppState>) -> Result<(), String> {
+    state.delete_config_profile(&name)
+}
+
 /// Get all available genres
 #[tauri::command]
 pub fn get_genres() -> Vec<String> {
@@ -205,7 +759,7 @@ pub fn get_genres() -> Vec<String> {
 /// Get current genre info with patterns
 #[tauri::command]
 pub fn get_current_genre_info(state: State<AppState>) -> GenreInfo {
-    let mapper = state.mapper.lock().unwrap();
+    let mapper = state.mapper.timed_lock();
     let genre = mapper.genre();
     let patterns = genre.get_patterns();
     
@@ -271,54 +825,81 @@ pub fn set_soundfont(_name: String, _state: State<AppState>) -> Result<(), Strin
     Err("SoundFont feature not enabled".to_string())
 }
 
-/// Rescan the soundfont directory
+/// Rescan the soundfont directory in the background, returns the job ID
 #[cfg(feature = "soundfont")]
 #[tauri::command]
-pub fn rescan_soundfonts(app_handle: tauri::AppHandle, state: State<AppState>) -> Result<(), String> {
+pub fn rescan_soundfonts(app_handle: tauri::AppHandle, state: State<AppState>) -> Result<JobId, String> {
     log::info!("rescan_soundfonts command called");
     
-    // Get user's app data directory for persistent uploaded soundfonts
-    let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-    let user_soundfonts_dir = app_data_dir.join("soundfonts");
+    // Uploaded soundfonts live in the app data layout
+    let user_soundfonts_dir = state.paths.soundfonts_dir.clone();
     
-    let result = state.rescan_soundfonts(Some(user_soundfonts_dir));
-    if let Err(ref e) = result {
-        log::error!("rescan_soundfonts failed: {}", e);
-    }
-    result
+    state.jobs.spawn(&app_handle, "rescan_soundfonts", move |job| {
+        job.progress(0.0, "Scanning soundfont folders");
+        let state = job.app().state::<AppState>();
+        let result = state.rescan_soundfonts(Some(user_soundfonts_dir));
+        if let Err(ref e) = result {
+            log::error!("rescan_soundfonts failed: {}", e);
+        }
+        result?;
+        Ok(format!("Found {} soundfonts", state.soundfont_manager.timed_lock().list().len()))
+    })
 }
 
 #[cfg(not(feature = "soundfont"))]
 #[tauri::command]
-pub fn rescan_soundfonts(_state: State<AppState>) -> Result<(), String> {
+pub fn rescan_soundfonts(_state: State<AppState>) -> Result<JobId, String> {
     Err("SoundFont feature not enabled".to_string())
 }
 
-/// Upload and save a soundfont file to the app data directory
+/// Copy a file in chunks, reporting progress and stopping on cancel
 #[cfg(feature = "soundfont")]
-#[tauri::command]
-pub fn upload_soundfont(file_path: String, file_name: String, app_handle: tauri::AppHandle, state: State<AppState>) -> Result<String, String> {
-    use std::fs;
-    use std::path::PathBuf;
-    
-    log::info!("upload_soundfont called with file: {}, name: {}", file_path, file_name);
+fn copy_with_progress(source: &std::path::Path, dest: &std::path::Path, job: &JobContext) -> Result<(), String> {
+    use std::io::{Read, Write};
+    
+    const CHUNK_SIZE: usize = 1024 * 1024;
+    
+    let mut input = std::fs::File::open(source)
+        .map_err(|e| format!("Failed to open soundfont file: {}", e))?;
+    let total = input.metadata().map(|m| m.len()).unwrap_or(0).max(1);
+    let mut output = std::fs::File::create(dest)
+        .map_err(|e| format!("Failed to create soundfont file: {}", e))?;
+    
+    let mut buffer = vec![0u8; CHUNK_SIZE];
+    let mut copied = 0u64;
+    loop {
+        job.check_cancelled()?;
+        let read = input.read(&mut buffer)
+            .map_err(|e| format!("Failed to read soundfont file: {}", e))?;
+        if read == 0 {
+            break;
+        }
+        output.write_all(&buffer[..read])
+            .map_err(|e| format!("Failed to copy soundfont file: {}", e))?;
+        copied += read as u64;
+        // Copying is the first 90%, the rescan the rest
+        job.progress(0.9 * copied as f32 / total as f32, format!("Copied {} MB", copied / (1024 * 1024)));
+    }
+    Ok(())
+}
+
+/// Upload and save a soundfont file to the app data directory in the
+/// background, returns the job ID
+#[cfg(feature = "soundfont")]
+#[tauri::command]
+pub fn upload_soundfont(file_path: String, file_name: String, app_handle: tauri::AppHandle, state: State<AppState>) -> Result<JobId, String> {
+    use std::fs;
+    use std::path::PathBuf;
+    
+    log::info!("upload_soundfont called with file: {}, name: {}", file_path, file_name);
     
     // Validate file extension
     if !file_name.to_lowercase().ends_with(".sf2") {
         return Err("Only .sf2 files are supported".to_string());
     }
     
-    // Get app data directory
-    let app_data_dir = app_handle
-        .path()
-        .app_data_dir()
-        .map_err(|e| format!("Failed to get app data directory: {}", e))?;
-    
-    // Create soundfonts subdirectory if it doesn't exist
-    let soundfonts_dir = app_data_dir.join("soundfonts");
+    // Create soundfonts directory if it doesn't exist
+    let soundfonts_dir = state.paths.soundfonts_dir.clone();
     fs::create_dir_all(&soundfonts_dir)
         .map_err(|e| format!("Failed to create soundfonts directory: {}", e))?;
     
@@ -330,21 +911,247 @@ pub fn upload_soundfont(file_path: String, file_name: String, app_handle: tauri:
         return Err(format!("A soundfont with the name '{}' already exists", file_name));
     }
     
-    // Copy the file
-    fs::copy(&file_path, &dest_path)
-        .map_err(|e| format!("Failed to copy soundfont file: {}", e))?;
-    
-    log::info!("Soundfont saved to: {:?}", dest_path);
-    
-    // Rescan soundfonts to include the new file
-    state.rescan_soundfonts(Some(soundfonts_dir))?;
-    
-    Ok(format!("Soundfont '{}' uploaded successfully", file_name))
+    state.jobs.spawn(&app_handle, "upload_soundfont", move |job| {
+        let source = PathBuf::from(&file_path);
+        if let Err(e) = copy_with_progress(&source, &dest_path, job) {
+            // Don't leave a partial file behind
+            let _ = fs::remove_file(&dest_path);
+            return Err(e);
+        }
+        log::info!("Soundfont saved to: {:?}", dest_path);
+        
+        // Rescan soundfonts to include the new file
+        job.progress(0.9, "Rescanning soundfonts");
+        job.app().state::<AppState>().rescan_soundfonts(Some(soundfonts_dir))?;
+        
+        Ok(format!("Soundfont '{}' uploaded successfully", file_name))
+    })
+}
+
+#[cfg(not(feature = "soundfont"))]
+#[tauri::command]
+pub fn upload_soundfont(_file_path: String, _file_name: String, _app_handle: tauri::AppHandle, _state: State<AppState>) -> Result<JobId, String> {
+    Err("SoundFont feature not enabled".to_string())
+}
+
+// ============================================================================
+// App Data Commands
+// ============================================================================
+
+/// Resolved storage locations and the legacy migration outcome
+#[derive(Debug, Clone, Serialize)]
+pub struct AppPathsInfo {
+    pub paths: AppPaths,
+    pub migration: MigrationReport,
+}
+
+/// Where config, profiles, songs and soundfonts are stored (for troubleshooting)
+#[tauri::command]
+pub fn get_app_paths(state: State<AppState>) -> AppPathsInfo {
+    AppPathsInfo {
+        paths: state.paths.clone(),
+        migration: state.migration_report().clone(),
+    }
+}
+
+/// Where each part of a backup lives (the genre presets if the assets were found)
+fn backup_locations(state: &AppState) -> BackupLocations {
+    BackupLocations::new(&state.paths, find_assets_dir().map(|assets| assets.join("chordmaps")))
+}
+
+/// Bundle config, profiles, genre presets and the song library into a zip archive
+#[tauri::command]
+pub fn export_user_data(path: String, state: State<AppState>) -> Result<BackupManifest, String> {
+    let manifest = config::export_user_data(&backup_locations(&state), std::path::Path::new(&path))
+        .map_err(|e| format!("Failed to export to {}: {:#}", path, e))?;
+    log::info!("📦 Exported {} files to {}", manifest.files.len(), path);
+    Ok(manifest)
+}
+
+/// Restore a backup made by `export_user_data`, then reload what changed
+#[tauri::command]
+pub fn import_user_data(path: String, state: State<AppState>) -> Result<ImportReport, String> {
+    let report = config::import_user_data(&backup_locations(&state), std::path::Path::new(&path))
+        .map_err(|e| format!("Failed to import {}: {:#}", path, e))?;
+    log::info!("📦 Imported {} files from {} (mITyGuitar {})", report.restored.len(), path, report.app_version);
+    state.reload_config()?;
+    state.reload_chordmaps();
+    Ok(report)
+}
+
+// ============================================================================
+// Transpose Commands
+// ============================================================================
+
+/// Move the capo by a number of semitones (the D-pad moves it by one)
+#[tauri::command]
+pub fn transpose(semitones: i8, state: State<AppState>) -> TransposeInfo {
+    state.transpose(semitones)
+}
+
+#[tauri::command]
+pub fn get_transpose(state: State<AppState>) -> TransposeInfo {
+    state.transpose_info()
+}
+
+/// Limit how many semitones the capo may move the key either way (0-12)
+#[tauri::command]
+pub fn set_transpose_range(range: u8, state: State<AppState>) -> Result<TransposeInfo, String> {
+    state.set_transpose_range(range)
+}
+
+// ============================================================================
+// Menu Navigation Commands
+// ============================================================================
+
+/// Enter or leave guitar menu navigation ("menu-navigate" events carry up,
+/// down, select and back while a menu is open; nothing plays meanwhile)
+#[tauri::command]
+pub fn set_menu_mode(open: bool, state: State<AppState>) -> Result<(), String> {
+    state.set_menu_mode(open)
+}
+
+#[tauri::command]
+pub fn get_menu_mode(state: State<AppState>) -> bool {
+    state.menu_mode()
+}
+
+// ============================================================================
+// Live Set Commands
+// ============================================================================
+
+/// Start or stop streaming the stage display state ("live-set-state" events)
+#[tauri::command]
+pub fn set_live_set_mode(enabled: bool, state: State<AppState>) {
+    state.set_live_set_mode(enabled);
+}
+
+#[tauri::command]
+pub fn get_live_set_mode(state: State<AppState>) -> bool {
+    state.live_set_mode()
+}
+
+/// Current stage display state (for the first frame before an event arrives)
+#[tauri::command]
+pub fn get_live_set_state(state: State<AppState>) -> LiveSetState {
+    LiveSetState::collect(&state)
+}
+
+/// Open the stage display window (always on top) and start live-set mode
+#[tauri::command]
+pub fn open_live_set_window(fullscreen: Option<bool>, app_handle: tauri::AppHandle, state: State<AppState>) -> Result<(), String> {
+    live_set::open_window(&app_handle, fullscreen.unwrap_or(false)).map_err(|e| e.to_string())?;
+    state.set_live_set_mode(true);
+    Ok(())
+}
+
+// ============================================================================
+// Stream Overlay Commands
+// ============================================================================
+
+/// Start or stop the overlay feed ("overlay-state" events and the local browser-source stream)
+#[tauri::command]
+pub fn set_overlay(enabled: bool, port: Option<u16>, state: State<AppState>) -> Result<OverlayConfig, String> {
+    state.set_overlay(enabled, port)
+}
+
+/// Current overlay state (for the first frame before an event arrives)
+#[tauri::command]
+pub fn get_overlay_state(state: State<AppState>) -> OverlayState {
+    OverlayState::collect(&state)
+}
+
+// ============================================================================
+// Background Job Commands
+// ============================================================================
+
+/// Get the state of a background job
+#[tauri::command]
+pub fn get_job(job_id: JobId, state: State<AppState>) -> Result<JobInfo, String> {
+    state.jobs.get(job_id)
+        .ok_or_else(|| format!("Unknown job: {}", job_id))
+}
+
+/// List running and recently finished background jobs
+#[tauri::command]
+pub fn list_jobs(state: State<AppState>) -> Vec<JobInfo> {
+    state.jobs.list()
+}
+
+/// Ask a running background job to stop
+#[tauri::command]
+pub fn cancel_job(job_id: JobId, state: State<AppState>) -> Result<bool, String> {
+    Ok(state.jobs.cancel(job_id))
+}
+
+// ============================================================================
+// Demo Commands
+// ============================================================================
+
+/// Longest sleep between cancellation checks while waiting for a demo step
+const DEMO_WAIT_SLICE: Duration = Duration::from_millis(10);
+
+/// Play a scripted performance through the full input pipeline, in place of
+/// player 1's guitar (the built-in demo when no script is given). Runs as a
+/// background job that fails if no note sounded, so it doubles as an audio
+/// smoke test.
+#[tauri::command]
+pub fn run_demo(script: Option<DemoScript>, app_handle: tauri::AppHandle, state: State<AppState>) -> Result<JobId, String> {
+    let script = script.unwrap_or_else(DemoScript::builtin);
+    script.validate().map_err(|e| e.to_string())?;
+    if state.demo_running() {
+        return Err("A demo is already running".to_string());
+    }
+    // Take the guitar over right away so a second demo can't start
+    state.set_demo_input(Some(ControllerStateSnapshot { connected: true, ..Default::default() }));
+    
+    state.jobs.spawn(&app_handle, "demo", move |job| {
+        let state = job.app().state::<AppState>();
+        log::info!("🎬 Demo '{}' ({} steps, {} ms)", script.name, script.steps.len(), script.duration_ms());
+        let result = play_demo(&script, &state, job);
+        // Hand the guitar back, releasing whatever the demo held
+        state.set_demo_input(None);
+        if let Err(e) = state.process_controller_input() {
+            log::warn!("Failed to release demo input: {}", e);
+        }
+        result
+    })
+}
+
+/// Feed each demo step in at its time, tracking whether anything sounded
+fn play_demo(script: &DemoScript, state: &AppState, job: &JobContext) -> Result<String, String> {
+    let started = Instant::now();
+    let mut peak_voices = 0;
+    for (i, step) in script.steps.iter().enumerate() {
+        let due = started + Duration::from_millis(step.at_ms);
+        while let Some(wait) = due.checked_duration_since(Instant::now()) {
+            job.check_cancelled()?;
+            std::thread::sleep(wait.min(DEMO_WAIT_SLICE));
+            peak_voices = peak_voices.max(state.get_audio_stats().active_voices);
+        }
+        job.check_cancelled()?;
+        state.set_demo_input(Some(step.snapshot()));
+        state.process_controller_input().map_err(|e| e.to_string())?;
+        job.progress(i as f32 / script.steps.len() as f32, format!("Step {} of {}", i + 1, script.steps.len()));
+    }
+    if peak_voices == 0 {
+        return Err("The demo played but no notes sounded, check the audio output".to_string());
+    }
+    Ok(format!("Demo '{}' played {} steps (up to {} voices)", script.name, script.steps.len(), peak_voices))
+}
+
+/// Render a short strummed-chord WAV preview of an instrument (offline, cached)
+/// Async so loading a large SoundFont doesn't block the main thread
+#[cfg(feature = "soundfont")]
+#[tauri::command]
+pub async fn render_instrument_preview(name: String, state: State<'_, AppState>) -> Result<tauri::ipc::Response, String> {
+    let wav = state.render_instrument_preview(&name)?;
+    Ok(tauri::ipc::Response::new(wav))
 }
 
 #[cfg(not(feature = "soundfont"))]
 #[tauri::command]
-pub fn upload_soundfont(_file_path: String, _file_name: String, _app_handle: tauri::AppHandle, _state: State<AppState>) -> Result<String, String> {
+pub async fn render_instrument_preview(_name: String, _state: State<'_, AppState>) -> Result<tauri::ipc::Response, String> {
     Err("SoundFont feature not enabled".to_string())
 }
 
@@ -353,7 +1160,7 @@ pub fn upload_soundfont(_file_path: String, _file_name: String, _app_handle: tau
 pub fn check_hardware_controller(state: State<AppState>) -> Result<String, String> {
     // First, process gilrs events to detect any newly connected controllers
     {
-        let controller = state.controller.lock().unwrap();
+        let controller = state.controller.timed_lock();
         let _ = controller.process_events();
         drop(controller);
     }
@@ -364,7 +1171,7 @@ pub fn check_hardware_controller(state: State<AppState>) -> Result<String, Strin
     
     // Check gilrs gamepads
     {
-        let controller = state.controller.lock().unwrap();
+        let controller = state.controller.timed_lock();
         if controller.find_device().unwrap_or(false) {
             devices.push("✅ Gilrs detected gamepad(s):".to_string());
             // The find_device logs will show details
@@ -428,7 +1235,7 @@ pub fn check_hardware_controller(state: State<AppState>) -> Result<String, Strin
 /// Get controller debug information
 #[tauri::command]
 pub fn get_controller_debug_info(state: State<AppState>) -> Result<String, String> {
-    let controller = state.controller.lock().unwrap();
+    let controller = state.controller.timed_lock();
     Ok(controller.get_debug_info())
 }
 
@@ -511,7 +1318,7 @@ pub fn update_chord_override(
     Ok(())
 }
 
-/// Update chord mapping settings
+/// Update chord mapping settings (waits for the next bar when changes are quantized)
 #[tauri::command]
 pub fn update_chord_mapping_settings(
     settings: ChordMappingSettings,
@@ -519,48 +1326,106 @@ pub fn update_chord_mapping_settings(
 ) -> Result<(), String> {
     log::info!("Updating chord mapping settings: {:?}", settings);
     
-    // Update the mapper with new genre, key, and mode
-    let mut mapper = state.mapper.lock().unwrap();
-    
-    // Update genre
-    let genre = match settings.genre.to_lowercase().as_str() {
-        "punk" => Genre::Punk,
-        "rock" => Genre::Rock,
-        "edm" => Genre::Edm,
-        "metal" => Genre::Metal,
-        "folk" => Genre::Folk,
-        "pop" => Genre::Pop,
-        _ => {
-            log::warn!("Unknown genre '{}', keeping current", settings.genre);
-            return Err(format!("Invalid genre: {}", settings.genre));
-        }
-    };
-    mapper.set_genre(genre);
-    
-    // Update key root
-    if let Some(key_note) = parse_note(&settings.key_root) {
-        mapper.set_key_root(key_note as u8);
-    } else {
+    if parse_genre(&settings.genre).is_none() {
+        log::warn!("Unknown genre '{}', keeping current", settings.genre);
+        return Err(format!("Invalid genre: {}", settings.genre));
+    }
+    if parse_note(&settings.key_root).is_none() {
         log::warn!("Invalid key root '{}', keeping current", settings.key_root);
         return Err(format!("Invalid key: {}", settings.key_root));
     }
     
-    // Update mode
-    let is_major = settings.mode.to_lowercase() == "major";
-    mapper.set_mode(is_major);
-    
-    // Update config
-    let mut config = state.config.lock().unwrap();
-    config.mapping.genre = settings.genre.clone();
+    state.request_change(LiveChange::ChordMapping {
+        genre: settings.genre,
+        key_root: settings.key_root,
+        mode: settings.mode,
+    })?;
     
     log::info!("Chord mapping settings updated successfully");
     Ok(())
 }
 
+/// A fret combination that plays a different chord after a proposed change
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FretChordChange {
+    /// Fret colors ("green", "red", ...)
+    pub frets: Vec<String>,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// What a genre/key/mode change would do to the chord mapping
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChordMappingPreview {
+    pub changes: Vec<FretChordChange>,
+    /// Mapped fret combinations that keep their chord
+    pub unchanged: usize,
+}
+
+/// Preview which frets change chords under a proposed genre/key/mode, without applying it
+#[tauri::command]
+pub fn preview_chord_mapping_change(
+    genre: String,
+    key_root: String,
+    mode: String,
+    state: State<AppState>,
+) -> Result<ChordMappingPreview, String> {
+    let genre = Genre::all()
+        .iter()
+        .copied()
+        .find(|g| g.name().eq_ignore_ascii_case(&genre))
+        .ok_or_else(|| format!("Invalid genre: {}", genre))?;
+    let key_root = parse_note(&key_root).ok_or_else(|| format!("Invalid key: {}", key_root))?;
+    let is_major = mode.to_lowercase() == "major";
+
+    let mapper = state.mapper.timed_lock();
+    let proposed = mapper.with_settings(genre, key_root as u8, is_major);
+    let changes = mapper.diff(&proposed);
+    let unchanged = proposed.chord_names()
+        .iter()
+        .filter(|(frets, name)| mapper.chord_name(frets).as_ref() == Some(name))
+        .count();
+
+    Ok(ChordMappingPreview {
+        changes: changes
+            .into_iter()
+            .map(|change| FretChordChange {
+                frets: change.frets.iter().map(|&fret| fret_color(fret)).collect(),
+                from: change.from,
+                to: change.to,
+            })
+            .collect(),
+        unchanged,
+    })
+}
+
+/// Chord struck most recently (also sent as the "chord-played" event)
+#[tauri::command]
+pub fn get_last_chord(state: State<AppState>) -> Option<ChordPlayed> {
+    state.last_chord()
+}
+
+/// Fret color name for the UI ("green", "solo_red", ...)
+fn fret_color(fret: ControlId) -> String {
+    match fret {
+        ControlId::FretGreen => "green".to_string(),
+        ControlId::FretRed => "red".to_string(),
+        ControlId::FretYellow => "yellow".to_string(),
+        ControlId::FretBlue => "blue".to_string(),
+        ControlId::FretOrange => "orange".to_string(),
+        ControlId::SoloGreen => "solo_green".to_string(),
+        ControlId::SoloRed => "solo_red".to_string(),
+        ControlId::SoloYellow => "solo_yellow".to_string(),
+        ControlId::SoloBlue => "solo_blue".to_string(),
+        ControlId::SoloOrange => "solo_orange".to_string(),
+        other => format!("{:?}", other).to_lowercase(),
+    }
+}
+
 /// Get current app config including soundfont info
 #[tauri::command]
 pub fn get_app_config(state: State<AppState>) -> Result<JsonValue, String> {
-    let config = state.config.lock().unwrap();
+    let config = state.config.timed_lock();
     let soundfont_current = config.soundfonts.current.clone();
     
     Ok(serde_json::json!({
@@ -729,7 +1594,7 @@ fn generate_metal_chords(key_root: &str) -> HashMap<String, String> {
     result
 }
 
-fn parse_note(note: &str) -> Option<usize> {
+pub(crate) fn parse_note(note: &str) -> Option<usize> {
     match note.to_uppercase().as_str() {
         "C" => Some(0),
         "C#" | "DB" => Some(1),
@@ -794,6 +1659,87 @@ pub fn set_sustain_release_time(time_ms: f32, state: State<AppState>) -> Result<
         .map_err(|e| e.to_string())
 }
 
+/// Set the A4 reference frequency in Hz (432-446), returns the applied value
+#[tauri::command]
+pub fn set_a4_frequency(frequency: f32, state: State<AppState>) -> Result<f32, String> {
+    state.set_a4_frequency(frequency)
+        .map_err(|e| e.to_string())
+}
+
+/// Get the A4 reference frequency in Hz
+#[tauri::command]
+pub fn get_a4_frequency(state: State<AppState>) -> Result<f32, String> {
+    Ok(state.config.timed_lock().audio.a4_frequency)
+}
+
+/// Set the chord stereo spread width (0.0 = mono, 1.0 = full), returns the applied value
+#[tauri::command]
+pub fn set_stereo_spread(width: f32, state: State<AppState>) -> Result<f32, String> {
+    state.set_stereo_spread(width)
+        .map_err(|e| e.to_string())
+}
+
+/// Get the chord stereo spread width
+#[tauri::command]
+pub fn get_stereo_spread(state: State<AppState>) -> Result<f32, String> {
+    Ok(state.config.timed_lock().audio.stereo_spread)
+}
+
+/// Set how many notes a virtual instrument plays at once (4-64), returns the applied value
+#[tauri::command]
+pub fn set_max_voices(voices: usize, state: State<AppState>) -> Result<usize, String> {
+    state.set_max_voices(voices)
+        .map_err(|e| e.to_string())
+}
+
+/// Get the virtual instrument polyphony
+#[tauri::command]
+pub fn get_max_voices(state: State<AppState>) -> Result<usize, String> {
+    Ok(state.config.timed_lock().audio.max_voices)
+}
+
+/// Set a mixer fader ("master", "synth", "backing_track", "metronome" or "drums",
+/// 0.0 to 2.0), returns the applied gain
+#[tauri::command]
+pub fn mixer_set_level(channel: String, gain: f32, state: State<AppState>) -> Result<f32, String> {
+    state.set_mixer_level(&channel, gain)
+        .map_err(|e| e.to_string())
+}
+
+/// Get the mixer fader gains
+#[tauri::command]
+pub fn mixer_get_levels(state: State<AppState>) -> MixerConfig {
+    state.mixer_levels()
+}
+
+/// Select a temperament ("equal", "just", "19-tet" or "custom" with 12 cent offsets)
+#[tauri::command]
+pub fn set_temperament(
+    preset: String,
+    root: Option<u8>,
+    custom_cents: Option<Vec<f32>>,
+    state: State<AppState>,
+) -> Result<(), String> {
+    state.set_temperament(TemperamentConfig {
+        preset,
+        root: root.unwrap_or(0),
+        custom_cents: custom_cents.unwrap_or_default(),
+    })
+    .map_err(|e| e.to_string())
+}
+
+/// Get the current temperament selection
+#[tauri::command]
+pub fn get_temperament(state: State<AppState>) -> Result<TemperamentConfig, String> {
+    Ok(state.config.timed_lock().audio.temperament.clone())
+}
+
+/// List the built-in temperament names
+#[tauri::command]
+pub fn list_temperaments() -> Vec<String> {
+    Temperament::all().iter().map(|t| t.name().to_string()).collect()
+}
+
 // ============================================================================
 // Raw Input Diagnostics Commands
 // ============================================================================
@@ -801,7 +1747,7 @@ pub fn set_sustain_release_time(time_ms: f32, state: State<AppState>) -> Result<
 /// Enable or disable raw input diagnostics
 #[tauri::command]
 pub fn set_raw_diagnostics_enabled(enabled: bool, state: State<AppState>) -> Result<(), String> {
-    let controller = state.controller.lock().unwrap();
+    let controller = state.controller.timed_lock();
     let diagnostics = controller.raw_diagnostics();
     diagnostics.set_enabled(enabled);
     Ok(())
@@ -810,7 +1756,7 @@ pub fn set_raw_diagnostics_enabled(enabled: bool, state: State<AppState>) -> Res
 /// Get raw input diagnostics events
 #[tauri::command]
 pub fn get_raw_diagnostics(state: State<AppState>) -> Result<Vec<RawInputEvent>, String> {
-    let controller = state.controller.lock().unwrap();
+    let controller = state.controller.timed_lock();
     let diagnostics = controller.raw_diagnostics();
     Ok(diagnostics.get_events())
 }
@@ -818,7 +1764,7 @@ pub fn get_raw_diagnostics(state: State<AppState>) -> Result<Vec<RawInputEvent>,
 /// Clear raw input diagnostics
 #[tauri::command]
 pub fn clear_raw_diagnostics(state: State<AppState>) -> Result<(), String> {
-    let controller = state.controller.lock().unwrap();
+    let controller = state.controller.timed_lock();
     let diagnostics = controller.raw_diagnostics();
     diagnostics.clear();
     Ok(())
@@ -827,18 +1773,87 @@ pub fn clear_raw_diagnostics(state: State<AppState>) -> Result<(), String> {
 /// Get raw diagnostics status
 #[tauri::command]
 pub fn get_raw_diagnostics_status(state: State<AppState>) -> Result<(bool, usize), String> {
-    let controller = state.controller.lock().unwrap();
+    let controller = state.controller.timed_lock();
     let diagnostics = controller.raw_diagnostics();
     Ok((diagnostics.is_enabled(), diagnostics.event_count()))
 }
 
+/// One replayed raw event with the actions and music events it produced
+#[derive(Debug, Clone, Serialize)]
+pub struct ReplayReportStep {
+    #[serde(flatten)]
+    pub step: ReplayStep,
+    pub music_events: Vec<MusicEvent>,
+}
+
+/// Save the recorded raw events as a capture file (JSON)
+#[tauri::command]
+pub fn export_raw_capture(path: String, state: State<AppState>) -> Result<usize, String> {
+    let events = state.controller.timed_lock().raw_diagnostics().get_events();
+    let json = serde_json::to_string_pretty(&events).map_err(|e| e.to_string())?;
+    std::fs::write(&path, json).map_err(|e| format!("Failed to write capture: {}", e))?;
+    log::info!("🔍 Exported {} raw events to {}", events.len(), path);
+    Ok(events.len())
+}
+
+/// Replay a capture file (or the current recording when no path is given)
+/// through the active mapping profile and the current chord mapper, without hardware
+#[tauri::command]
+pub fn replay_raw_capture(capture_path: Option<String>, state: State<AppState>) -> Result<Vec<ReplayReportStep>, String> {
+    let profile = state.profile_manager.timed_lock().active_profile().cloned();
+    let steps = match capture_path {
+        Some(path) => {
+            let json = std::fs::read_to_string(&path).map_err(|e| format!("Failed to read capture: {}", e))?;
+            let events: Vec<RawInputEvent> = serde_json::from_str(&json)
+                .map_err(|e| format!("Invalid capture file: {}", e))?;
+            InputReplayer::replay(&events, profile.as_ref())
+        }
+        None => state.controller.timed_lock().raw_diagnostics().replay(profile.as_ref()),
+    };
+
+    let mut mapper = state.mapper.timed_lock().replay_copy();
+    let report: Vec<ReplayReportStep> = steps
+        .into_iter()
+        .map(|step| {
+            let music_events = mapper.process(&step.state);
+            ReplayReportStep { step, music_events }
+        })
+        .collect();
+    log::info!("🔍 Replayed {} raw events", report.len());
+    Ok(report)
+}
+
+/// Get per-action counts of bounces dropped by the debounce layer
+#[tauri::command]
+pub fn get_debounce_stats(state: State<AppState>) -> Result<Vec<FilteredEventStats>, String> {
+    let controller = state.controller.timed_lock();
+    Ok(controller.raw_diagnostics().filtered_stats())
+}
+
+/// Set the debounce window (ms) for an action in the active mapping profile (0 disables)
+#[tauri::command]
+pub fn set_debounce_ms(action: String, window_ms: u32, state: State<AppState>) -> Result<(), String> {
+    let app_action = serde_json::from_str::<AppAction>(&format!("\"{}\"", action))
+        .map_err(|e| format!("Invalid action: {}", e))?;
+    
+    let mut manager = state.profile_manager.timed_lock();
+    let profile = manager.active_profile_mut()
+        .ok_or_else(|| "No active mapping profile".to_string())?;
+    profile.set_debounce_ms(app_action, window_ms);
+    manager.save_active_profile().map_err(|e| e.to_string())?;
+    drop(manager);
+    
+    state.apply_active_mapping_profile();
+    Ok(())
+}
+
 // Mapping Wizard Commands
 // ============================================================================
 
 /// Start capturing for a specific app action
 #[tauri::command]
 pub fn wizard_start_capture(action: String, state: State<AppState>) -> Result<(), String> {
-    let controller = state.controller.lock().unwrap();
+    let controller = state.controller.timed_lock();
     let wizard = controller.mapping_wizard();
     
     // Parse action string to AppAction enum
@@ -852,7 +1867,7 @@ pub fn wizard_start_capture(action: String, state: State<AppState>) -> Result<()
 /// Stop current capture
 #[tauri::command]
 pub fn wizard_stop_capture(state: State<AppState>) -> Result<(), String> {
-    let controller = state.controller.lock().unwrap();
+    let controller = state.controller.timed_lock();
     let wizard = controller.mapping_wizard();
     wizard.stop_capture();
     Ok(())
@@ -861,7 +1876,7 @@ pub fn wizard_stop_capture(state: State<AppState>) -> Result<(), String> {
 /// Finalize capture and get result
 #[tauri::command]
 pub fn wizard_finalize_capture(state: State<AppState>) -> Result<CaptureResult, String> {
-    let controller = state.controller.lock().unwrap();
+    let controller = state.controller.timed_lock();
     let wizard = controller.mapping_wizard();
     Ok(wizard.finalize_capture())
 }
@@ -869,7 +1884,7 @@ pub fn wizard_finalize_capture(state: State<AppState>) -> Result<CaptureResult,
 /// Get current wizard state
 #[tauri::command]
 pub fn wizard_get_state(state: State<AppState>) -> Result<String, String> {
-    let controller = state.controller.lock().unwrap();
+    let controller = state.controller.timed_lock();
     let wizard = controller.mapping_wizard();
     let capture_state = wizard.get_state();
     // Serialize to JSON for frontend
@@ -880,7 +1895,7 @@ pub fn wizard_get_state(state: State<AppState>) -> Result<String, String> {
 /// Set auto-capture mode
 #[tauri::command]
 pub fn wizard_set_auto_capture(enabled: bool, state: State<AppState>) -> Result<(), String> {
-    let controller = state.controller.lock().unwrap();
+    let controller = state.controller.timed_lock();
     let wizard = controller.mapping_wizard();
     wizard.set_auto_capture(enabled);
     Ok(())
@@ -889,93 +1904,353 @@ pub fn wizard_set_auto_capture(enabled: bool, state: State<AppState>) -> Result<
 /// Clear wizard state
 #[tauri::command]
 pub fn wizard_clear(state: State<AppState>) -> Result<(), String> {
-    let controller = state.controller.lock().unwrap();
+    let controller = state.controller.timed_lock();
     let wizard = controller.mapping_wizard();
     wizard.clear_events();
     Ok(())
 }
 
-// Mapping Profile Commands
+// Whammy Calibration Commands
 // ============================================================================
 
-/// List all available mapping profiles
+/// Start recording whammy values (leave the bar at rest for the first half second)
 #[tauri::command]
-pub fn list_mapping_profiles(state: State<AppState>) -> Result<Vec<String>, String> {
-    let manager = state.profile_manager.lock().unwrap();
-    manager.list_profiles()
-        .map_err(|e| e.to_string())
+pub fn whammy_calibration_start(duration_ms: Option<u64>, state: State<AppState>) -> Result<(), String> {
+    let controller = state.controller.timed_lock();
+    controller.whammy_calibrator()
+        .start(duration_ms.unwrap_or(DEFAULT_CALIBRATION_MS));
+    Ok(())
 }
 
-/// Load a mapping profile by name
+/// Get calibration progress
 #[tauri::command]
-pub fn load_mapping_profile(name: String, state: State<AppState>) -> Result<MappingProfile, String> {
-    let mut manager = state.profile_manager.lock().unwrap();
-    manager.load_profile(&name)
-        .map_err(|e| e.to_string())?;
-    // Return the loaded profile
-    manager.active_profile()
-        .cloned()
-        .ok_or_else(|| "Profile loaded but not found".to_string())
+pub fn whammy_calibration_status(state: State<AppState>) -> Result<CalibrationStatus, String> {
+    let controller = state.controller.timed_lock();
+    Ok(controller.whammy_calibrator().status())
 }
 
-/// Save a mapping profile
+/// Finish calibration and store the result in the active mapping profile.
+/// `split` calibrates a two-way bar around its resting position.
 #[tauri::command]
-pub fn save_mapping_profile(profile: MappingProfile, state: State<AppState>) -> Result<(), String> {
-    let mut manager = state.profile_manager.lock().unwrap();
-    manager.set_active_profile(profile);
-    manager.save_active_profile()
-        .map_err(|e| e.to_string())
+pub fn whammy_calibration_finish(split: Option<bool>, state: State<AppState>) -> Result<WhammyCalibration, String> {
+    let controller = state.controller.timed_lock();
+    let calibration = if split.unwrap_or(false) {
+        controller.whammy_calibrator().finish_split()
+    } else {
+        controller.whammy_calibrator().finish()
+    }.map_err(|e| e.to_string())?;
+    drop(controller);
+    
+    let mut manager = state.profile_manager.timed_lock();
+    let profile = manager.active_profile_mut()
+        .ok_or_else(|| "No active mapping profile to store the calibration in".to_string())?;
+    profile.set_whammy_calibration(Some(calibration));
+    manager.save_active_profile().map_err(|e| e.to_string())?;
+    drop(manager);
+    
+    state.apply_active_mapping_profile();
+    Ok(calibration)
 }
 
-/// Create a new mapping profile
+/// Abort a running calibration
 #[tauri::command]
-pub fn create_mapping_profile(name: String, controller_name: String, state: State<AppState>) -> Result<MappingProfile, String> {
-    let manager = state.profile_manager.lock().unwrap();
-    let controller_id = ControllerId {
-        name: controller_name.clone(),
-        label: Some(controller_name),
-        vendor_id: None,
-        product_id: None,
-    };
-    let mut profile = manager.create_default_profile(controller_id);
-    profile.name = name;
+pub fn whammy_calibration_cancel(state: State<AppState>) -> Result<(), String> {
+    state.controller.timed_lock().whammy_calibrator().cancel();
+    Ok(())
+}
+
+/// Remove the whammy calibration from the active mapping profile
+#[tauri::command]
+pub fn whammy_calibration_clear(state: State<AppState>) -> Result<(), String> {
+    let mut manager = state.profile_manager.timed_lock();
+    let profile = manager.active_profile_mut()
+        .ok_or_else(|| "No active mapping profile".to_string())?;
+    profile.set_whammy_calibration(None);
+    manager.save_active_profile().map_err(|e| e.to_string())?;
     drop(manager);
     
-    // Save the profile
-    let mut manager = state.profile_manager.lock().unwrap();
-    manager.set_active_profile(profile.clone());
-    manager.save_active_profile()
-        .map_err(|e| e.to_string())?;
-    Ok(profile)
+    state.apply_active_mapping_profile();
+    Ok(())
 }
 
-/// Delete a mapping profile
+// Hardware Test Commands
+// ============================================================================
+
+/// Start the guided hardware test (frets, strum, whammy, tilt)
 #[tauri::command]
-pub fn delete_mapping_profile(name: String, state: State<AppState>) -> Result<(), String> {
-    let manager = state.profile_manager.lock().unwrap();
-    manager.delete_profile(&name)
-        .map_err(|e| e.to_string())
+pub fn hardware_test_start(timeout_ms: Option<u64>, state: State<AppState>) -> Result<(), String> {
+    let controller = state.controller.timed_lock();
+    controller.hardware_tester()
+        .start(timeout_ms.unwrap_or(DEFAULT_STEP_TIMEOUT_MS));
+    Ok(())
 }
 
-/// Set the active mapping profile
+/// Get the current prompt and per-control results (None if no test was started)
 #[tauri::command]
-pub fn set_active_profile(name: String, state: State<AppState>) -> Result<(), String> {
-    let mut manager = state.profile_manager.lock().unwrap();
-    manager.load_profile(&name)
-        .map_err(|e| e.to_string())
+pub fn hardware_test_status(state: State<AppState>) -> Result<Option<HardwareTestReport>, String> {
+    let controller = state.controller.timed_lock();
+    Ok(controller.hardware_tester().report())
 }
 
-/// Get the currently active profile name
+/// Skip the control currently being tested
 #[tauri::command]
-pub fn get_active_profile(state: State<AppState>) -> Result<Option<String>, String> {
-    let manager = state.profile_manager.lock().unwrap();
-    Ok(manager.active_profile().map(|p| p.name.clone()))
+pub fn hardware_test_skip(state: State<AppState>) -> Result<(), String> {
+    state.controller.timed_lock().hardware_tester().skip();
+    Ok(())
 }
 
-/// Update a specific mapping in the active profile
+/// Abort the hardware test
+#[tauri::command]
+pub fn hardware_test_cancel(state: State<AppState>) -> Result<(), String> {
+    state.controller.timed_lock().hardware_tester().cancel();
+    Ok(())
+}
+
+// Foot Switch Commands
+// ============================================================================
+
+/// List connected USB foot switches
+#[tauri::command]
+pub fn list_footswitches() -> Result<Vec<FootswitchInfo>, String> {
+    controller::list_footswitches().map_err(|e| e.to_string())
+}
+
+/// Use a USB foot switch as sustain pedal (by device path, or the first one found)
+#[tauri::command]
+pub fn connect_footswitch(path: Option<String>, state: State<AppState>) -> Result<Option<FootswitchInfo>, String> {
+    state.controller.timed_lock()
+        .connect_footswitch(path.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Stop using the foot switch
+#[tauri::command]
+pub fn disconnect_footswitch(state: State<AppState>) -> Result<(), String> {
+    state.controller.timed_lock().disconnect_footswitch();
+    Ok(())
+}
+
+/// The foot switch in use, if any
+#[tauri::command]
+pub fn get_footswitch(state: State<AppState>) -> Option<FootswitchInfo> {
+    state.controller.timed_lock().footswitch().cloned()
+}
+
+// Fret LED Commands
+// ============================================================================
+
+/// List connected guitars with fret LEDs
+#[tauri::command]
+pub fn list_led_guitars() -> Result<Vec<FretLedInfo>, String> {
+    controller::list_led_guitars().map_err(|e| e.to_string())
+}
+
+/// Light the frets of a guitar with fret LEDs (by device path, or the first one found)
+#[tauri::command]
+pub fn connect_fret_leds(path: Option<String>, state: State<AppState>) -> Result<Option<FretLedInfo>, String> {
+    state.controller.timed_lock()
+        .connect_fret_leds(path.as_deref())
+        .map_err(|e| e.to_string())
+}
+
+/// Stop using the fret LEDs (switches them off)
+#[tauri::command]
+pub fn disconnect_fret_leds(state: State<AppState>) -> Result<(), String> {
+    state.controller.timed_lock().disconnect_fret_leds();
+    Ok(())
+}
+
+/// The guitar whose fret LEDs are in use, if any
+#[tauri::command]
+pub fn get_fret_leds(state: State<AppState>) -> Option<FretLedInfo> {
+    state.controller.timed_lock().fret_leds().cloned()
+}
+
+/// What the fret LEDs show in song mode where the chart doesn't say
+/// ("off", "next_chord" or "held_chord")
+#[tauri::command]
+pub fn set_fret_led_mode(mode: String, state: State<AppState>) -> Result<(), String> {
+    let mode = FretLedMode::from_name(&mode).ok_or_else(|| format!("Invalid fret LED mode: {}", mode))?;
+    let mut config = state.config.timed_lock();
+    config.song.fret_leds = mode.name().to_string();
+    config.save().map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+pub fn get_fret_led_mode(state: State<AppState>) -> String {
+    state.config.timed_lock().song.fret_leds.clone()
+}
+
+// Rumble Commands
+// ============================================================================
+
+/// Rumble on missed notes and when star power activates
+#[tauri::command]
+pub fn set_rumble_enabled(enabled: bool, state: State<AppState>) -> Result<(), String> {
+    state.set_rumble_enabled(enabled)
+}
+
+#[tauri::command]
+pub fn get_rumble_enabled(state: State<AppState>) -> bool {
+    state.controller.timed_lock().rumble_enabled()
+}
+
+// Macro Commands
+// ============================================================================
+
+/// Macros of the active mapping profile
+#[tauri::command]
+pub fn macro_list(state: State<AppState>) -> Vec<ControllerMacro> {
+    state.list_macros()
+}
+
+/// Add or replace a macro in the active mapping profile
+#[tauri::command]
+pub fn macro_save(controller_macro: ControllerMacro, state: State<AppState>) -> Result<(), String> {
+    state.save_macro(controller_macro)
+}
+
+/// Delete a macro from the active mapping profile
+#[tauri::command]
+pub fn macro_delete(name: String, state: State<AppState>) -> Result<(), String> {
+    state.delete_macro(&name)
+}
+
+/// Start recording what player 1 plays into a macro
+#[tauri::command]
+pub fn macro_record_start(state: State<AppState>) -> Result<(), String> {
+    state.start_macro_recording();
+    Ok(())
+}
+
+/// Finish recording and save the macro with its trigger combo (e.g. ["Select", "FretOrange"])
+#[tauri::command]
+pub fn macro_record_stop(name: String, trigger: Vec<AppAction>, state: State<AppState>) -> Result<ControllerMacro, String> {
+    state.stop_macro_recording(name, trigger)
+}
+
+/// Play a macro now (as if its combo was pressed)
+#[tauri::command]
+pub fn macro_trigger(name: String, state: State<AppState>) -> Result<(), String> {
+    state.trigger_macro(&name)
+}
+
+/// Stop all running macros
+#[tauri::command]
+pub fn macro_stop_all(state: State<AppState>) -> Result<(), String> {
+    state.stop_macros();
+    Ok(())
+}
+
+// Mapping Profile Commands
+// ============================================================================
+
+/// List all available mapping profiles
+#[tauri::command]
+pub fn list_mapping_profiles(state: State<AppState>) -> Result<Vec<String>, String> {
+    let manager = state.profile_manager.timed_lock();
+    manager.list_profiles()
+        .map_err(|e| e.to_string())
+}
+
+/// Load a mapping profile by name
+#[tauri::command]
+pub fn load_mapping_profile(name: String, state: State<AppState>) -> Result<MappingProfile, String> {
+    let mut manager = state.profile_manager.timed_lock();
+    manager.load_profile(&name)
+        .map_err(|e| e.to_string())?;
+    // Return the loaded profile
+    let profile = manager.active_profile()
+        .cloned()
+        .ok_or_else(|| "Profile loaded but not found".to_string())?;
+    drop(manager);
+    state.apply_active_mapping_profile();
+    Ok(profile)
+}
+
+/// Save a mapping profile
+#[tauri::command]
+pub fn save_mapping_profile(profile: MappingProfile, state: State<AppState>) -> Result<(), String> {
+    let mut manager = state.profile_manager.timed_lock();
+    manager.set_active_profile(profile);
+    manager.save_active_profile()
+        .map_err(|e| e.to_string())?;
+    drop(manager);
+    state.apply_active_mapping_profile();
+    Ok(())
+}
+
+/// List backed-up versions of a mapping profile (newest first)
+#[tauri::command]
+pub fn list_profile_versions(name: String, state: State<AppState>) -> Result<Vec<BackupVersion>, String> {
+    let manager = state.profile_manager.timed_lock();
+    manager.list_profile_versions(&name)
+        .map_err(|e| e.to_string())
+}
+
+/// Restore a mapping profile to a backed-up version
+#[tauri::command]
+pub fn restore_profile_version(name: String, timestamp: u64, state: State<AppState>) -> Result<(), String> {
+    let mut manager = state.profile_manager.timed_lock();
+    manager.restore_profile_version(&name, timestamp)
+        .map_err(|e| e.to_string())?;
+    drop(manager);
+    state.apply_active_mapping_profile();
+    Ok(())
+}
+
+/// Create a new mapping profile
+#[tauri::command]
+pub fn create_mapping_profile(name: String, controller_name: String, state: State<AppState>) -> Result<MappingProfile, String> {
+    let manager = state.profile_manager.timed_lock();
+    let controller_id = ControllerId {
+        name: controller_name.clone(),
+        label: Some(controller_name),
+        vendor_id: None,
+        product_id: None,
+    };
+    let mut profile = manager.create_default_profile(controller_id);
+    profile.name = name;
+    drop(manager);
+    
+    // Save the profile
+    let mut manager = state.profile_manager.timed_lock();
+    manager.set_active_profile(profile.clone());
+    manager.save_active_profile()
+        .map_err(|e| e.to_string())?;
+    Ok(profile)
+}
+
+/// Delete a mapping profile
+#[tauri::command]
+pub fn delete_mapping_profile(name: String, state: State<AppState>) -> Result<(), String> {
+    let manager = state.profile_manager.timed_lock();
+    manager.delete_profile(&name)
+        .map_err(|e| e.to_string())
+}
+
+/// Set the active mapping profile
+#[tauri::command]
+pub fn set_active_profile(name: String, state: State<AppState>) -> Result<(), String> {
+    let mut manager = state.profile_manager.timed_lock();
+    manager.load_profile(&name)
+        .map_err(|e| e.to_string())?;
+    drop(manager);
+    state.apply_active_mapping_profile();
+    Ok(())
+}
+
+/// Get the currently active profile name
+#[tauri::command]
+pub fn get_active_profile(state: State<AppState>) -> Result<Option<String>, String> {
+    let manager = state.profile_manager.timed_lock();
+    Ok(manager.active_profile().map(|p| p.name.clone()))
+}
+
+/// Update a specific mapping in the active profile
 #[tauri::command]
 pub fn update_profile_mapping(action: String, binding: String, state: State<AppState>) -> Result<(), String> {
-    let mut manager = state.profile_manager.lock().unwrap();
+    let mut manager = state.profile_manager.timed_lock();
     
     // Parse action and binding
     let app_action = serde_json::from_str::<AppAction>(&format!("\"{}\"", action))
@@ -993,6 +2268,25 @@ pub fn update_profile_mapping(action: String, binding: String, state: State<AppS
     }
 }
 
+/// Set the response curve of an axis in the active profile (e.g. a whammy
+/// that reacts more at the start of its travel)
+#[tauri::command]
+pub fn set_axis_curve(action: String, curve: ResponseCurve, state: State<AppState>) -> Result<(), String> {
+    let app_action = serde_json::from_str::<AppAction>(&format!("\"{}\"", action))
+        .map_err(|e| format!("Invalid action: {}", e))?;
+    let mut manager = state.profile_manager.timed_lock();
+    let profile = manager.active_profile_mut()
+        .ok_or_else(|| "No active profile".to_string())?;
+    if !profile.set_axis_curve(app_action, curve) {
+        return Err(format!("{} is not bound to an axis", app_action.display_name()));
+    }
+    manager.save_active_profile()
+        .map_err(|e| e.to_string())?;
+    drop(manager);
+    state.apply_active_mapping_profile();
+    Ok(())
+}
+
 // ============================================================================
 // Song Play Commands
 // ============================================================================
@@ -1016,6 +2310,22 @@ pub struct TransportState {
     pub time_sig: [u32; 2],
     pub speed_multiplier: f64,
     pub is_in_count_in: bool,
+    pub bar: i32,
+    pub beat_in_bar: f64,
+    pub accent: song::AccentLevel,
+    pub accent_grouping: Vec<u32>,
+    /// Click for a beat crossed since the previous poll (None if no new beat)
+    pub metronome_click: Option<song::MetronomeClick>,
+    /// Drum and bass accompaniment for a bar started since the previous poll
+    pub accompaniment: Option<ScheduledBar>,
+    /// Practice loop, if one is set
+    pub loop_region: Option<song::LoopRegion>,
+    /// Times the practice loop has repeated
+    pub loop_passes: u32,
+    pub loop_scoring: song::LoopScoring,
+    pub practice_mode: PracticeMode,
+    /// Stopped at a chord until it is played (wait-for-note practice)
+    pub waiting_for_note: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1027,6 +2337,9 @@ pub struct ScoreData {
     pub misses: u32,
     pub accuracy: f64,
     pub grade: String,
+    pub star_power: song::StarPowerState,
+    /// Misses don't break the combo
+    pub no_fail: bool,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -1034,22 +2347,23 @@ pub struct HitResultData {
     pub is_hit: bool,
     pub chord: Option<String>,
     pub accuracy: Option<f64>,
+    pub timing: Option<song::HitTiming>,
+    /// Strum time relative to the note (ms, negative = early)
+    pub offset_ms: Option<f64>,
     pub miss_reason: Option<String>,
 }
 
 /// Load a song chart from JSON string
 #[tauri::command]
 pub fn song_load_chart(json: String, state: State<AppState>) -> Result<(), String> {
-    let mut player = state.song_player.lock().unwrap();
-    player.load_chart(&json).map_err(|e| e.to_string())
+    state.load_song_chart(&json)
 }
 
 /// Load the default Greensleeves chart
 #[tauri::command]
 pub fn song_load_default_chart(state: State<AppState>) -> Result<(), String> {
     let json = include_str!("../../../../assets/songs/greensleeves.mitychart.json");
-    let mut player = state.song_player.lock().unwrap();
-    player.load_chart(json).map_err(|e| e.to_string())
+    state.load_song_chart(json)
 }
 
 /// Load a song chart from a path in the assets directory
@@ -1066,14 +2380,74 @@ pub fn song_load_chart_from_path(path: String, state: State<AppState>) -> Result
         _ => return Err(format!("Unknown asset path: {}", path)),
     };
     
-    let mut player = state.song_player.lock().unwrap();
-    player.load_chart(json).map_err(|e| e.to_string())
+    state.load_song_chart(json)
+}
+
+/// Import a Guitar Hero / Clone Hero .chart file and load it (the hardest
+/// guitar difficulty unless one is named); save it with the library commands.
+/// Runs as a background job.
+#[tauri::command]
+pub fn song_import_chart_file(path: String, difficulty: Option<String>, app_handle: tauri::AppHandle, state: State<AppState>) -> Result<JobId, String> {
+    state.jobs.spawn(&app_handle, "chart-import", move |job| {
+        job.progress(0.0, "Converting chart");
+        let chart = song::convert_clone_hero_file(std::path::Path::new(&path), difficulty.as_deref())
+            .map_err(|e| format!("Failed to import {}: {:#}", path, e))?;
+        job.check_cancelled()?;
+        load_imported_chart(job, chart)
+    })
+}
+
+/// Load a converted chart from an import job, naming it in the job result
+fn load_imported_chart(job: &JobContext, chart: SongChart) -> Result<String, String> {
+    let chords = chart.lanes.iter().map(|lane| lane.events.len()).sum::<usize>();
+    let title = chart.meta.title.clone();
+    log::info!("📥 Imported '{}' ({} chords)", title, chords);
+    job.progress(0.9, "Loading chart");
+    job.app().state::<AppState>().new_song_chart(chart)?;
+    Ok(format!("Imported '{}' ({} chords)", title, chords))
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MidiTrackInfo {
+    pub index: usize,
+    pub name: Option<String>,
+    pub notes: usize,
+}
+
+/// Tracks of a MIDI file, to pick one for `song_import_midi`
+#[tauri::command]
+pub fn song_list_midi_tracks(path: String) -> Result<Vec<MidiTrackInfo>, String> {
+    let data = std::fs::read(&path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let midi = song::parse_midi(&data).map_err(|e| e.to_string())?;
+    Ok(midi.tracks.iter().enumerate()
+        .map(|(index, track)| MidiTrackInfo { index, name: track.name.clone(), notes: track.notes.len() })
+        .collect())
+}
+
+/// Import a MIDI file (one track, or all of them) and load it; save it with
+/// the library commands. Runs as a background job.
+#[tauri::command]
+pub fn song_import_midi(path: String, track_index: Option<usize>, app_handle: tauri::AppHandle, state: State<AppState>) -> Result<JobId, String> {
+    state.jobs.spawn(&app_handle, "midi-import", move |job| {
+        job.progress(0.0, "Converting MIDI");
+        let options = song::MidiConvertOptions { track: track_index, ..Default::default() };
+        let chart = song::convert_midi_file(std::path::Path::new(&path), &options)
+            .map_err(|e| format!("Failed to import {}: {:#}", path, e))?;
+        job.check_cancelled()?;
+        load_imported_chart(job, chart)
+    })
+}
+
+/// Unload the song and go back to free play (restores the key a chart switched to)
+#[tauri::command]
+pub fn song_unload_chart(state: State<AppState>) -> Result<(), String> {
+    state.unload_song_chart()
 }
 
 /// Get current chart data
 #[tauri::command]
 pub fn song_get_chart(state: State<AppState>) -> Result<Option<String>, String> {
-    let player = state.song_player.lock().unwrap();
+    let player = state.song_player.timed_lock();
     if let Some(chart) = player.get_chart() {
         serde_json::to_string(chart).map(Some).map_err(|e| e.to_string())
     } else {
@@ -1084,48 +2458,52 @@ pub fn song_get_chart(state: State<AppState>) -> Result<Option<String>, String>
 /// Play/resume song
 #[tauri::command]
 pub fn song_play(state: State<AppState>) -> Result<(), String> {
-    let mut player = state.song_player.lock().unwrap();
-    player.play();
+    state.song_player.timed_lock().play();
+    state.sync_backing_track();
     Ok(())
 }
 
 /// Pause song
 #[tauri::command]
 pub fn song_pause(state: State<AppState>) -> Result<(), String> {
-    let mut player = state.song_player.lock().unwrap();
-    player.pause();
+    state.song_player.timed_lock().pause();
+    state.sync_backing_track();
     Ok(())
 }
 
 /// Stop song and reset
 #[tauri::command]
 pub fn song_stop(state: State<AppState>) -> Result<(), String> {
-    let mut player = state.song_player.lock().unwrap();
-    player.stop();
+    state.song_player.timed_lock().stop();
+    state.sync_backing_track();
     Ok(())
 }
 
 /// Seek to beat
 #[tauri::command]
 pub fn song_seek(beat: f64, state: State<AppState>) -> Result<(), String> {
-    let mut player = state.song_player.lock().unwrap();
-    player.seek(beat);
+    state.song_player.timed_lock().seek(beat);
+    state.sync_backing_track();
     Ok(())
 }
 
 /// Set playback speed
 #[tauri::command]
 pub fn song_set_speed(multiplier: f64, state: State<AppState>) -> Result<(), String> {
-    let mut player = state.song_player.lock().unwrap();
-    player.set_speed(multiplier);
+    state.song_player.timed_lock().set_speed(multiplier);
+    state.sync_backing_track();
     Ok(())
 }
 
 /// Get transport state
 #[tauri::command]
 pub fn song_get_transport_state(state: State<AppState>) -> Result<TransportState, String> {
-    let mut player = state.song_player.lock().unwrap();
+    let mut player = state.song_player.timed_lock();
     let current_beat = player.get_current_beat();
+    let metronome_click = player.poll_metronome();
+    let accompaniment = player.poll_accompaniment();
+    let loop_scoring = player.loop_scoring();
+    let practice_mode = player.practice_mode();
     let transport = player.get_transport_state();
     Ok(TransportState {
         is_playing: transport.is_playing,
@@ -1134,21 +2512,217 @@ pub fn song_get_transport_state(state: State<AppState>) -> Result<TransportState
         time_sig: transport.time_sig,
         speed_multiplier: transport.speed_multiplier,
         is_in_count_in: transport.is_in_count_in(),
+        bar: transport.get_current_bar(),
+        beat_in_bar: transport.get_beat_in_bar(),
+        accent: transport.get_current_accent(),
+        accent_grouping: transport.meter.grouping.clone(),
+        metronome_click,
+        accompaniment,
+        loop_region: transport.loop_region(),
+        loop_passes: transport.loop_passes(),
+        loop_scoring,
+        practice_mode,
+        waiting_for_note: transport.is_waiting(),
     })
 }
 
+/// Set the accent grouping for the loaded song (e.g. "2+2+3")
+#[tauri::command]
+pub fn song_set_accent_grouping(grouping: String, state: State<AppState>) -> Result<(), String> {
+    let grouping = song::parse_grouping(&grouping).map_err(|e| e.to_string())?;
+    let mut player = state.song_player.timed_lock();
+    player.set_accent_grouping(grouping).map_err(|e| e.to_string())
+}
+
+/// Enable or disable metronome clicks in the transport state
+#[tauri::command]
+pub fn song_set_metronome_enabled(enabled: bool, state: State<AppState>) -> Result<(), String> {
+    let mut player = state.song_player.timed_lock();
+    player.set_metronome_enabled(enabled);
+    Ok(())
+}
+
+/// Repeat a section of the song (A/B loop); the score adds up over the
+/// passes unless `scoring` is "reset"
+#[tauri::command]
+pub fn song_set_loop(start_beat: f64, end_beat: f64, scoring: Option<song::LoopScoring>, state: State<AppState>) -> Result<(), String> {
+    state.song_player.timed_lock()
+        .set_loop(start_beat, end_beat, scoring.unwrap_or_default())
+        .map_err(|e| e.to_string())?;
+    log::info!("🔁 Practice loop: beats {} - {}", start_beat, end_beat);
+    state.sync_backing_track();
+    Ok(())
+}
+
+/// Stop repeating the practice loop
+#[tauri::command]
+pub fn song_clear_loop(state: State<AppState>) -> Result<(), String> {
+    state.song_player.timed_lock().clear_loop();
+    Ok(())
+}
+
+/// Play songs in time ("normal") or have the transport stop at every chord
+/// until the right frets are strummed ("wait_for_note")
+#[tauri::command]
+pub fn song_set_practice_mode(mode: PracticeMode, state: State<AppState>) -> Result<(), String> {
+    state.set_practice_mode(mode);
+    Ok(())
+}
+
+/// Speed trainer ramp, and the speed reached while it runs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedTrainerState {
+    pub active: bool,
+    pub speed: Option<f64>,
+    pub settings: song::SpeedTrainerSettings,
+}
+
+/// Play the practice loop slowed down and speed it up a step after every
+/// pass at or above the accuracy threshold (settings are remembered; None =
+/// the last ones used)
+#[tauri::command]
+pub fn song_start_speed_trainer(settings: Option<song::SpeedTrainerSettings>, state: State<AppState>) -> Result<(), String> {
+    state.start_speed_trainer(settings)
+}
+
+/// Stop raising the speed (the loop keeps the speed reached)
+#[tauri::command]
+pub fn song_stop_speed_trainer(state: State<AppState>) -> Result<(), String> {
+    state.song_player.timed_lock().stop_speed_trainer();
+    Ok(())
+}
+
+#[tauri::command]
+pub fn song_get_speed_trainer(state: State<AppState>) -> Result<SpeedTrainerState, String> {
+    if let Some(trainer) = state.song_player.timed_lock().speed_trainer() {
+        return Ok(SpeedTrainerState { active: true, speed: Some(trainer.speed()), settings: trainer.settings() });
+    }
+    Ok(SpeedTrainerState { active: false, speed: None, settings: state.speed_trainer_settings() })
+}
+
+/// Play songs at a difficulty (easy, medium, hard or expert = as charted);
+/// applies to the loaded song and every song loaded after it
+#[tauri::command]
+pub fn song_set_difficulty(level: String, state: State<AppState>) -> Result<(), String> {
+    let difficulty = song::Difficulty::from_name(&level)
+        .ok_or_else(|| format!("Unknown difficulty: {}", level))?;
+    state.set_song_difficulty(difficulty)
+}
+
+/// Get the difficulty songs are played at
+#[tauri::command]
+pub fn song_get_difficulty(state: State<AppState>) -> Result<String, String> {
+    Ok(state.song_player.timed_lock().difficulty().name().to_string())
+}
+
+/// Set the count-in for every song (bars None = each chart's own) and whether it clicks
+#[tauri::command]
+pub fn song_set_count_in(bars: Option<u32>, click: Option<bool>, state: State<AppState>) -> Result<(), String> {
+    let click = click.unwrap_or_else(|| state.count_in().count_in_click);
+    state.set_count_in(bars, click)
+}
+
+/// Get the count-in settings
+#[tauri::command]
+pub fn song_get_count_in(state: State<AppState>) -> Result<SongConfig, String> {
+    Ok(state.count_in())
+}
+
+/// Enable the drum and bass accompaniment (intensity 0.0 = as written, 1.0 = busy fills)
+#[tauri::command]
+pub fn song_set_accompaniment(enabled: bool, intensity: Option<f32>, state: State<AppState>) -> Result<(), String> {
+    let mut player = state.song_player.timed_lock();
+    player.set_accompaniment(enabled, intensity);
+    Ok(())
+}
+
+/// Quantize setting and changes waiting for their beat
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PendingChanges {
+    pub quantize: song::QuantizeGrid,
+    pub changes: Vec<song::ScheduledChange<LiveChange>>,
+}
+
+/// Defer genre/key/instrument changes to the next "beat" or "bar" while a song plays ("off" = instant)
+#[tauri::command]
+pub fn set_change_quantize(grid: String, state: State<AppState>) -> Result<(), String> {
+    let grid = song::QuantizeGrid::from_name(&grid)
+        .ok_or_else(|| format!("Unknown quantize grid: {}", grid))?;
+    state.song_player.timed_lock().set_change_quantize(grid);
+    
+    let mut config = state.config.timed_lock();
+    config.mapping.change_quantize = grid.name().to_string();
+    config.save().map_err(|e| e.to_string())
+}
+
+/// Get the quantize setting and queued changes
+#[tauri::command]
+pub fn get_pending_changes(state: State<AppState>) -> PendingChanges {
+    let player = state.song_player.timed_lock();
+    PendingChanges {
+        quantize: player.change_quantize(),
+        changes: player.pending_changes().to_vec(),
+    }
+}
+
+/// Drop queued changes without applying them
+#[tauri::command]
+pub fn cancel_pending_changes(state: State<AppState>) {
+    state.song_player.timed_lock().cancel_pending_changes();
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TapTempoState {
+    /// Tapped tempo (None until two taps)
+    pub bpm: Option<f64>,
+    pub taps: usize,
+    pub rounding: song::TempoRounding,
+}
+
+fn tap_tempo_state(player: &crate::song_player::SongPlayer) -> TapTempoState {
+    let tap_tempo = player.get_tap_tempo();
+    TapTempoState {
+        bpm: tap_tempo.bpm(),
+        taps: tap_tempo.tap_count(),
+        rounding: tap_tempo.rounding,
+    }
+}
+
+/// Tap tempo: successive taps set the transport BPM
+#[tauri::command]
+pub fn song_tap_tempo(state: State<AppState>) -> Result<TapTempoState, String> {
+    let mut player = state.song_player.timed_lock();
+    player.tap_tempo(std::time::Instant::now());
+    Ok(tap_tempo_state(&player))
+}
+
+/// Set how tapped tempos are rounded ("none", "half" or "whole")
+#[tauri::command]
+pub fn song_set_tap_tempo_rounding(rounding: song::TempoRounding, state: State<AppState>) -> Result<TapTempoState, String> {
+    let mut player = state.song_player.timed_lock();
+    player.set_tap_tempo_rounding(rounding);
+    Ok(tap_tempo_state(&player))
+}
+
 /// Check strum for hit detection
 #[tauri::command]
 pub fn song_check_strum(pressed_frets: Vec<String>, state: State<AppState>) -> Result<HitResultData, String> {
-    let mut player = state.song_player.lock().unwrap();
+    let result = state.song_player.timed_lock().check_strum(pressed_frets);
     
-    if let Some(result) = player.check_strum(pressed_frets) {
+    if let Some(result) = result {
+        state.record_practice_hit(&result);
+        if matches!(result, song::HitResult::Miss { .. }) {
+            state.rumble(RumbleCue::MissedNote);
+        }
         match result {
-            song::HitResult::Hit { event, accuracy } => {
+            song::HitResult::Hit { event, accuracy, timing, offset_ms } => {
                 Ok(HitResultData {
                     is_hit: true,
                     chord: Some(event.chord),
                     accuracy: Some(accuracy),
+                    timing: Some(timing),
+                    offset_ms: Some(offset_ms),
                     miss_reason: None,
                 })
             }
@@ -1162,6 +2736,8 @@ pub fn song_check_strum(pressed_frets: Vec<String>, state: State<AppState>) -> R
                     is_hit: false,
                     chord: None,
                     accuracy: None,
+                    timing: None,
+                    offset_ms: None,
                     miss_reason: Some(reason_str.to_string()),
                 })
             }
@@ -1171,17 +2747,137 @@ pub fn song_check_strum(pressed_frets: Vec<String>, state: State<AppState>) -> R
     }
 }
 
+/// Switch star power on (as Select or tilting the guitar does); None if the
+/// meter isn't full enough or no song is playing
+#[tauri::command]
+pub fn song_activate_star_power(state: State<AppState>) -> Result<Option<song::StarPowerActivation>, String> {
+    Ok(state.activate_star_power())
+}
+
+/// Hit-detection timing: the windows and the input offset taken off strums
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SongTiming {
+    pub windows: song::HitWindows,
+    pub input_offset_ms: f64,
+}
+
+/// Get the hit windows and input offset
+#[tauri::command]
+pub fn song_get_timing(state: State<AppState>) -> Result<SongTiming, String> {
+    let player = state.song_player.timed_lock();
+    Ok(SongTiming {
+        windows: player.hit_windows(),
+        input_offset_ms: player.input_offset_ms(),
+    })
+}
+
+/// Set the perfect/good/miss hit windows (ms either side of a note)
+#[tauri::command]
+pub fn song_set_hit_windows(windows: song::HitWindows, state: State<AppState>) -> Result<(), String> {
+    state.set_hit_windows(windows)
+}
+
+/// Set the latency taken off every strum by hand (ms)
+#[tauri::command]
+pub fn song_set_input_offset(offset_ms: f64, state: State<AppState>) -> Result<(), String> {
+    state.set_input_offset_ms(offset_ms)
+}
+
+/// Longest sleep between cancellation checks while calibrating
+const CALIBRATION_WAIT_SLICE: Duration = Duration::from_millis(5);
+
+/// Measure the input offset: play a click loop, listen to the player strum
+/// along and apply the median offset. Runs as a background job; the result
+/// message carries the offset.
+#[tauri::command]
+pub fn song_calibrate_latency(bpm: Option<f64>, beats: Option<u32>, app_handle: tauri::AppHandle, state: State<AppState>) -> Result<JobId, String> {
+    let calibration = song::LatencyCalibration::new(
+        bpm.unwrap_or(song::DEFAULT_CALIBRATION_BPM),
+        beats.unwrap_or(song::DEFAULT_CALIBRATION_BEATS),
+    ).map_err(|e| e.to_string())?;
+    if state.song_player.timed_lock().is_calibrating() {
+        return Err("A latency calibration is already running".to_string());
+    }
+
+    state.jobs.spawn(&app_handle, "latency-calibration", move |job| {
+        let state = job.app().state::<AppState>();
+        let result = run_latency_calibration(calibration, &state, job);
+        if result.is_err() {
+            state.song_player.timed_lock().cancel_calibration();
+        }
+        result
+    })
+}
+
+/// Click each beat on time while strums are collected, then apply the result
+fn run_latency_calibration(calibration: song::LatencyCalibration, state: &AppState, job: &JobContext) -> Result<String, String> {
+    let started = Instant::now();
+    let beats = calibration.beats();
+    let clicks: Vec<f64> = (0..beats).map(|index| calibration.click_ms(index)).collect();
+    let duration_ms = calibration.duration_ms();
+    state.song_player.timed_lock().start_calibration(calibration, controller::high_performance::timestamp_nanos());
+    log::info!("⏱️ Latency calibration: {} clicks", beats);
+
+    let wait_until = |due_ms: f64| -> Result<(), String> {
+        let due = started + Duration::from_secs_f64(due_ms / 1000.0);
+        while let Some(wait) = due.checked_duration_since(Instant::now()) {
+            job.check_cancelled()?;
+            std::thread::sleep(wait.min(CALIBRATION_WAIT_SLICE));
+        }
+        Ok(())
+    };
+    for (index, click_ms) in clicks.iter().enumerate() {
+        wait_until(*click_ms)?;
+        state.play_click(index % 4 == 0);
+        job.progress(index as f32 / beats as f32, format!("Strum with click {} of {}", index + 1, beats));
+    }
+    wait_until(duration_ms)?;
+
+    let (offset_ms, strums) = state.song_player.timed_lock().finish_calibration().map_err(|e| e.to_string())?;
+    let offset_ms = offset_ms.round();
+    state.set_input_offset_ms(offset_ms)?;
+    Ok(format!("Input offset set to {} ms (from {} strums)", offset_ms, strums))
+}
+
+/// Minimum progress change reported while bouncing
+const BOUNCE_PROGRESS_STEP: f32 = 0.01;
+
+/// Render the loaded chart with the current instrument to a WAV file, at file
+/// speed instead of live. Runs as a background job; the result message names
+/// the file.
+#[tauri::command]
+pub fn song_render_to_wav(filename: String, app_handle: tauri::AppHandle, state: State<AppState>) -> Result<JobId, String> {
+    if state.song_player.timed_lock().get_chart().is_none() {
+        return Err("No song loaded".to_string());
+    }
+    let path = std::path::PathBuf::from(filename);
+
+    state.jobs.spawn(&app_handle, "song-bounce", move |job| {
+        let state = job.app().state::<AppState>();
+        let mut reported = 0.0;
+        let chords = state.render_song_to_wav(&path, |done| {
+            if done - reported >= BOUNCE_PROGRESS_STEP {
+                reported = done;
+                job.progress(done, "Rendering");
+            }
+            job.check_cancelled().map_err(anyhow::Error::msg)
+        })?;
+        Ok(format!("Rendered {} chords to {}", chords, path.display()))
+    })
+}
+
 /// Update sustain state
 #[tauri::command]
 pub fn song_update_sustain(pressed_frets: Vec<String>, state: State<AppState>) -> Result<bool, String> {
-    let mut player = state.song_player.lock().unwrap();
+    let mut player = state.song_player.timed_lock();
     Ok(player.update_sustain(pressed_frets))
 }
 
 /// Get current score
 #[tauri::command]
 pub fn song_get_score(state: State<AppState>) -> Result<ScoreData, String> {
-    let player = state.song_player.lock().unwrap();
+    let mut player = state.song_player.timed_lock();
+    let star_power = player.star_power_state();
     let scorer = player.get_score();
     Ok(ScoreData {
         score: scorer.score,
@@ -1191,57 +2887,274 @@ pub fn song_get_score(state: State<AppState>) -> Result<ScoreData, String> {
         misses: scorer.misses,
         accuracy: scorer.get_accuracy(),
         grade: scorer.get_grade().to_string(),
+        star_power,
+        no_fail: scorer.no_fail(),
     })
 }
 
+/// Play without failing: misses are counted but don't break the combo.
+/// Play-throughs are saved with the flag and don't set high scores.
+#[tauri::command]
+pub fn song_set_no_fail(enabled: bool, state: State<AppState>) -> Result<(), String> {
+    state.set_no_fail(enabled)
+}
+
+/// Best results per song and difficulty (of one song, or of all of them)
+#[tauri::command]
+pub fn song_get_highscores(song_id: Option<String>, state: State<AppState>) -> Result<Vec<song::HighScore>, String> {
+    Ok(state.song_stats.timed_lock().high_scores(song_id.as_deref()))
+}
+
+/// Finished play-throughs, newest first
+#[tauri::command]
+pub fn song_get_history(song_id: Option<String>, limit: Option<usize>, state: State<AppState>) -> Result<Vec<song::PlayRecord>, String> {
+    Ok(state.song_stats.timed_lock().history(song_id.as_deref(), limit))
+}
+
+/// Strums, chords and timing tendencies of the practice session
+#[tauri::command]
+pub fn get_practice_report(state: State<AppState>) -> Result<song::PracticeReport, String> {
+    Ok(state.practice_report())
+}
+
+/// Start a new practice session
+#[tauri::command]
+pub fn reset_practice_session(state: State<AppState>) -> Result<(), String> {
+    state.reset_practice_session();
+    Ok(())
+}
+
 /// Set user override instrument
 #[tauri::command]
 pub fn song_set_instrument(instrument_type: String, label: String, state: State<AppState>) -> Result<(), String> {
-    let mut player = state.song_player.lock().unwrap();
-    player.set_user_instrument(Some(InstrumentRef {
+    state.song_player.timed_lock().set_user_instrument(Some(InstrumentRef {
         instrument_type,
         label,
     }));
+    state.apply_song_instrument();
     Ok(())
 }
 
 /// Clear user override instrument
 #[tauri::command]
 pub fn song_clear_instrument_override(state: State<AppState>) -> Result<(), String> {
-    let mut player = state.song_player.lock().unwrap();
-    player.set_user_instrument(None);
+    state.song_player.timed_lock().set_user_instrument(None);
+    state.apply_song_instrument();
     Ok(())
 }
 
 // ============================================================================
-// Song Library Management
+// Song Editor Commands
 // ============================================================================
+// Edits apply to the loaded chart; save it with `song_get_chart` and
+// `song_save_to_library`.
 
-use std::fs;
-use std::path::PathBuf;
+/// Get the loaded chart as written (song_get_chart returns it reduced to
+/// the difficulty); event indices of the edit commands refer to this one
+#[tauri::command]
+pub fn song_edit_get_chart(state: State<AppState>) -> Result<Option<String>, String> {
+    let player = state.song_player.timed_lock();
+    player.get_source_chart()
+        .map(|chart| serde_json::to_string(chart).map_err(|e| e.to_string()))
+        .transpose()
+}
+
+/// Start a new, empty chart (4/4 unless given) and load it for editing
+#[tauri::command]
+pub fn song_edit_new_chart(title: String, artist: String, bpm: f64, time_sig: Option<[u32; 2]>, state: State<AppState>) -> Result<(), String> {
+    let chart = SongChart::new(&title, &artist, bpm, time_sig.unwrap_or([4, 4]))
+        .map_err(|e| e.to_string())?;
+    state.new_song_chart(chart)
+}
+
+/// Change the title, artist, tempo, meter, key or genre of the loaded chart
+#[tauri::command]
+pub fn song_edit_set_meta(meta: ChartMetaEdit, state: State<AppState>) -> Result<(), String> {
+    state.edit_song_chart(|chart| chart.apply_meta(&meta))
+}
+
+/// Set the loaded chart's backing track (None removes it); `offset_seconds`
+/// is the time in the file where beat 0 falls
+#[tauri::command]
+pub fn song_edit_set_backing_track(path: Option<String>, offset_seconds: Option<f64>, state: State<AppState>) -> Result<(), String> {
+    let backing_track = path.map(|path| BackingTrackRef { path, offset_seconds: offset_seconds.unwrap_or(0.0) });
+    state.edit_song_chart(|chart| {
+        chart.meta.backing_track = backing_track;
+        Ok(())
+    })?;
+    state.load_backing_track();
+    Ok(())
+}
+
+/// Map a chord name to the frets that play it
+#[tauri::command]
+pub fn song_edit_set_chord(chord: String, frets: Vec<String>, state: State<AppState>) -> Result<(), String> {
+    state.edit_song_chart(|chart| chart.set_chord(&chord, frets))
+}
+
+/// Insert a chord event (into the main lane unless named), returning its index
+#[tauri::command]
+pub fn song_edit_add_event(event: ChordEvent, lane: Option<String>, state: State<AppState>) -> Result<usize, String> {
+    let lane = lane.unwrap_or_else(|| DEFAULT_LANE.to_string());
+    state.edit_song_chart(|chart| chart.add_event(&lane, event))
+}
+
+/// Remove a chord event by its index in the lane
+#[tauri::command]
+pub fn song_edit_remove_event(index: usize, lane: Option<String>, state: State<AppState>) -> Result<(), String> {
+    let lane = lane.unwrap_or_else(|| DEFAULT_LANE.to_string());
+    state.edit_song_chart(|chart| chart.remove_event(&lane, index).map(|_| ()))
+}
+
+/// Move a chord event to another beat, returning its new index
+#[tauri::command]
+pub fn song_edit_move_event(index: usize, beat: f64, lane: Option<String>, state: State<AppState>) -> Result<usize, String> {
+    let lane = lane.unwrap_or_else(|| DEFAULT_LANE.to_string());
+    state.edit_song_chart(|chart| chart.move_event(&lane, index, beat))
+}
+
+/// Add a section, or replace the one with the same name
+#[tauri::command]
+pub fn song_edit_set_section(section: Section, state: State<AppState>) -> Result<(), String> {
+    state.edit_song_chart(|chart| chart.set_section(section))
+}
+
+/// Remove a section by name (false if there was none)
+#[tauri::command]
+pub fn song_edit_remove_section(name: String, state: State<AppState>) -> Result<bool, String> {
+    state.edit_song_chart(|chart| Ok(chart.remove_section(&name)))
+}
+
+/// Add a lyric line, or replace the one on the same beat
+#[tauri::command]
+pub fn song_edit_set_lyric(lyric: LyricEvent, state: State<AppState>) -> Result<(), String> {
+    state.edit_song_chart(|chart| chart.set_lyric(lyric))
+}
+
+/// Remove the lyric line on a beat (false if there was none)
+#[tauri::command]
+pub fn song_edit_remove_lyric(beat: f64, state: State<AppState>) -> Result<bool, String> {
+    state.edit_song_chart(|chart| Ok(chart.remove_lyric(beat)))
+}
+
+// ============================================================================
+// Song Recording Commands
+// ============================================================================
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct SongLibraryEntry {
-    pub id: String,
-    pub title: String,
-    pub artist: String,
-    pub filename: String,
+pub struct RecordingStatus {
+    /// Quantization grid in beats
+    pub grid: f64,
+    /// Chords recorded so far
+    pub events: usize,
+}
+
+/// Record chords played while the transport runs, snapped to `grid` beats
+#[tauri::command]
+pub fn song_record_start(grid: Option<f64>, state: State<AppState>) -> Result<(), String> {
+    state.start_chart_recording(grid);
+    Ok(())
+}
+
+/// Stop recording, load the recorded chart and save it to the library.
+/// Returns the library filename (named after the title unless given).
+#[tauri::command]
+pub fn song_record_stop(title: String, artist: Option<String>, filename: Option<String>, state: State<AppState>) -> Result<String, String> {
+    let chart = state.stop_chart_recording(&title, artist.as_deref().unwrap_or(""))?;
+    let json = serde_json::to_string(&chart).map_err(|e| e.to_string())?;
+    let filename = filename.unwrap_or_else(|| library_filename(&title));
+    song_save_to_library(json, filename)
+}
+
+/// Throw the recording away
+#[tauri::command]
+pub fn song_record_cancel(state: State<AppState>) -> Result<(), String> {
+    state.song_player.timed_lock().cancel_recording();
+    Ok(())
+}
+
+/// Recording progress (None when not recording)
+#[tauri::command]
+pub fn song_record_status(state: State<AppState>) -> Result<Option<RecordingStatus>, String> {
+    Ok(state.song_player.timed_lock().recorder().map(|recorder| RecordingStatus {
+        grid: recorder.grid(),
+        events: recorder.event_count(),
+    }))
+}
+
+// ============================================================================
+// Chord Trainer Commands
+// ============================================================================
+
+/// Start training the chords of the current mapping; returns the first prompt
+#[tauri::command]
+pub fn trainer_start(seed: Option<u64>, state: State<AppState>) -> Result<song::TrainerPrompt, String> {
+    let chords = state.trainer_chords();
+    let seed = seed.unwrap_or_else(crate::state::unix_seconds);
+    let mut trainer = song::ChordTrainer::new(chords, seed).map_err(|e| e.to_string())?;
+    let prompt = trainer.next_prompt(Instant::now());
+    log::info!("🎯 Chord trainer started ({} chords)", trainer.max_level() + 1);
+    *state.trainer.timed_lock() = Some(trainer);
+    Ok(prompt)
+}
+
+/// Chord being asked for (None when not training)
+#[tauri::command]
+pub fn trainer_get_prompt(state: State<AppState>) -> Result<Option<song::TrainerPrompt>, String> {
+    Ok(state.trainer.timed_lock().as_ref().and_then(|trainer| trainer.prompt().cloned()))
 }
 
+/// Answer the prompt with the frets held on a strum; the right frets move on to the next prompt
+#[tauri::command]
+pub fn trainer_submit_result(pressed_frets: Vec<String>, state: State<AppState>) -> Result<song::TrainerResult, String> {
+    let mut trainer = state.trainer.timed_lock();
+    let trainer = trainer.as_mut().ok_or("Chord trainer not started")?;
+    trainer.submit(&pressed_frets, Instant::now()).ok_or_else(|| "No chord prompted".to_string())
+}
+
+/// Level, streak and reaction times of the session
+#[tauri::command]
+pub fn trainer_get_stats(state: State<AppState>) -> Result<Option<song::TrainerStats>, String> {
+    Ok(state.trainer.timed_lock().as_ref().map(|trainer| trainer.stats()))
+}
+
+/// End the session, returning how it went
+#[tauri::command]
+pub fn trainer_stop(state: State<AppState>) -> Result<Option<song::TrainerStats>, String> {
+    Ok(state.trainer.timed_lock().take().map(|trainer| trainer.stats()))
+}
+
+// ============================================================================
+// Drum Machine Commands
+// ============================================================================
+
+/// Start the drums on a genre groove ("rock", "punk", "edm", "metal"): at
+/// `bpm`, or following the loaded song's transport when no tempo is given
+#[tauri::command]
+pub fn drums_start(bpm: Option<f64>, pattern: String, state: State<AppState>) -> Result<(), String> {
+    state.start_drums(bpm, &pattern)
+}
+
+#[tauri::command]
+pub fn drums_stop(state: State<AppState>) -> Result<(), String> {
+    state.stop_drums()
+}
+
+/// Drum level (0.0-1.0)
+#[tauri::command]
+pub fn drums_set_volume(volume: f32, state: State<AppState>) -> Result<(), String> {
+    state.set_drums_volume(volume)
+}
+
+// ============================================================================
+// Song Library Management
+// ============================================================================
+
+use std::fs;
+use std::path::PathBuf;
+
 fn get_songs_directory() -> Result<PathBuf, String> {
-    let exe_path = std::env::current_exe().map_err(|e| e.to_string())?;
-    let exe_dir = exe_path.parent().ok_or("Failed to get exe directory")?;
-    
-    // In development: use workspace root
-    // In production: use app data directory
-    let songs_dir = if cfg!(debug_assertions) {
-        // Development: use workspace assets/songs
-        exe_dir.join("../../../../assets/songs")
-    } else {
-        // Production: use app data
-        let app_data = dirs::data_dir().ok_or("Failed to get app data directory")?;
-        app_data.join("mityguitar").join("songs")
-    };
+    let songs_dir = AppPaths::resolve().map_err(|e| e.to_string())?.songs_dir;
     
     // Create directory if it doesn't exist
     fs::create_dir_all(&songs_dir).map_err(|e| e.to_string())?;
@@ -1250,9 +3163,19 @@ fn get_songs_directory() -> Result<PathBuf, String> {
     songs_dir.canonicalize().map_err(|e| e.to_string())
 }
 
+/// Library filename for a song title ("My Song!" -> "my-song")
+fn library_filename(title: &str) -> String {
+    let slug: String = title.to_lowercase()
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+        .collect();
+    let slug = slug.split('-').filter(|part| !part.is_empty()).collect::<Vec<_>>().join("-");
+    if slug.is_empty() { "recording".to_string() } else { slug }
+}
+
 /// Save a song to the library
 #[tauri::command]
-pub fn song_save_to_library(json: String, filename: String) -> Result<String, String> {
+pub fn song_save_to_library(json: String, filename: String, state: State<AppState>) -> Result<String, String> {
     // Validate JSON first
     let chart: SongChart = serde_json::from_str(&json).map_err(|e| format!("Invalid song JSON: {}", e))?;
     
@@ -1273,50 +3196,46 @@ pub fn song_save_to_library(json: String, filename: String) -> Result<String, St
     let pretty_json = serde_json::to_string_pretty(&chart).map_err(|e| e.to_string())?;
     fs::write(&file_path, pretty_json).map_err(|e| format!("Failed to save song: {}", e))?;
     
+    // Saving the loaded chart as edited leaves nothing to recover
+    let mut player = state.song_player.timed_lock();
+    let saved = serde_json::to_value(&chart).ok();
+    if player.get_source_chart().is_some_and(|loaded| serde_json::to_value(loaded).ok() == saved) {
+        player.mark_chart_saved();
+    }
+    
     Ok(filename)
 }
 
-/// List all songs in the library
+/// List all songs in the library (from the index, see `AppState::rescan_library`)
 #[tauri::command]
-pub fn song_list_library() -> Result<Vec<SongLibraryEntry>, String> {
-    let songs_dir = get_songs_directory()?;
-    
-    let mut entries = Vec::new();
-    
-    // Read directory
-    let dir_entries = fs::read_dir(&songs_dir).map_err(|e| format!("Failed to read songs directory: {}", e))?;
-    
-    for entry in dir_entries {
-        let entry = entry.map_err(|e| e.to_string())?;
-        let path = entry.path();
-        
-        // Only process .mitychart.json files
-        if !path.is_file() || !path.to_string_lossy().ends_with(".mitychart.json") {
-            continue;
-        }
-        
-        let filename = path.file_name()
-            .and_then(|n| n.to_str())
-            .unwrap_or("")
-            .to_string();
-        
-        // Try to read and parse the file to get metadata
-        if let Ok(content) = fs::read_to_string(&path) {
-            if let Ok(chart) = serde_json::from_str::<SongChart>(&content) {
-                entries.push(SongLibraryEntry {
-                    id: filename.clone(),
-                    title: chart.meta.title.clone(),
-                    artist: chart.meta.artist.clone(),
-                    filename,
-                });
-            }
-        }
-    }
-    
-    // Sort by title
-    entries.sort_by(|a, b| a.title.cmp(&b.title));
-    
-    Ok(entries)
+pub fn song_list_library(state: State<AppState>) -> Result<Vec<LibrarySong>, String> {
+    state.library_songs()
+}
+
+/// Song session a crash left behind (None = the last session exited cleanly)
+#[tauri::command]
+pub fn get_recoverable_session(state: State<AppState>) -> Option<RecoverableSession> {
+    state.recoverable_session()
+}
+
+/// Load the song of the session a crash left behind with its unsaved edits,
+/// position and score
+#[tauri::command]
+pub fn recover_last_session(state: State<AppState>) -> Result<Option<RecoverableSession>, String> {
+    state.recover_last_session()
+}
+
+/// Forget the session a crash left behind
+#[tauri::command]
+pub fn discard_recovered_session(state: State<AppState>) {
+    state.discard_recovered_session();
+}
+
+/// Search the library by title or artist, with optional genre, BPM range and
+/// difficulty filters and a sort order
+#[tauri::command]
+pub fn song_search(query: String, filters: Option<SongFilters>, state: State<AppState>) -> Vec<LibrarySong> {
+    state.search_library(&query, &filters.unwrap_or_default())
 }
 
 /// Load a song from the library
@@ -1332,8 +3251,7 @@ pub fn song_load_from_library(filename: String, state: State<AppState>) -> Resul
     let json = fs::read_to_string(&file_path)
         .map_err(|e| format!("Failed to read song file: {}", e))?;
     
-    let mut player = state.song_player.lock().unwrap();
-    player.load_chart(&json).map_err(|e| e.to_string())
+    state.load_song_chart(&json)
 }
 
 /// Delete a song from the library
@@ -1349,3 +3267,63 @@ pub fn song_delete_from_library(filename: String) -> Result<(), String> {
     fs::remove_file(&file_path)
         .map_err(|e| format!("Failed to delete song: {}", e))
 }
+
+// ============================================================================
+// Setlist Commands
+// ============================================================================
+// Setlists are stored in the songs directory next to the songs they list.
+
+/// List the setlists in the library
+#[tauri::command]
+pub fn setlist_list(state: State<AppState>) -> Result<Vec<Setlist>, String> {
+    song::list_setlists(&state.paths.songs_dir).map_err(|e| e.to_string())
+}
+
+/// Create a setlist or save an edit of one (entries, overrides, auto-advance)
+#[tauri::command]
+pub fn setlist_save(setlist: Setlist, state: State<AppState>) -> Result<(), String> {
+    setlist.save(&state.paths.songs_dir).map_err(|e| e.to_string())?;
+    state.refresh_setlist(&setlist);
+    Ok(())
+}
+
+/// Delete a setlist
+#[tauri::command]
+pub fn setlist_delete(name: String, state: State<AppState>) -> Result<(), String> {
+    song::delete_setlist(&state.paths.songs_dir, &name).map_err(|e| e.to_string())
+}
+
+/// Move a setlist entry to another place, returning the reordered setlist
+#[tauri::command]
+pub fn setlist_move_entry(name: String, from: usize, to: usize, state: State<AppState>) -> Result<Setlist, String> {
+    let mut setlist = song::load_setlist(&state.paths.songs_dir, &name).map_err(|e| e.to_string())?;
+    setlist.move_entry(from, to).map_err(|e| e.to_string())?;
+    setlist.save(&state.paths.songs_dir).map_err(|e| e.to_string())?;
+    state.refresh_setlist(&setlist);
+    Ok(setlist)
+}
+
+/// Start a setlist, loading its song at `index` (default the first)
+#[tauri::command]
+pub fn setlist_play(name: String, index: Option<usize>, state: State<AppState>) -> Result<SetlistPosition, String> {
+    let setlist = song::load_setlist(&state.paths.songs_dir, &name).map_err(|e| e.to_string())?;
+    state.play_setlist(setlist, index.unwrap_or(0))
+}
+
+/// Load the next song of the setlist being played (None = it was the last)
+#[tauri::command]
+pub fn setlist_next(state: State<AppState>) -> Result<Option<SetlistPosition>, String> {
+    state.next_setlist_song()
+}
+
+/// Setlist being played and its loaded song
+#[tauri::command]
+pub fn setlist_get_position(state: State<AppState>) -> Option<SetlistPosition> {
+    state.setlist_position()
+}
+
+/// Leave the setlist, keeping the loaded song
+#[tauri::command]
+pub fn setlist_stop(state: State<AppState>) {
+    state.stop_setlist();
+}
@@ -0,0 +1,63 @@
+//! Config hot-reload
+//! A background thread watches the config file and the genre presets
+//! (`chordmaps/`) for edits made outside the app, by hand or by a sync tool.
+//! A changed config is applied like a config profile and a changed preset
+//! directory is loaded again; either way the frontend gets a
+//! `config-changed` event with the config now in use, so no restart is
+//! needed. The app's own saves show up as changes too, but a config that
+//! matches the one in use is ignored.
+
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use config::{AppConfig, FileWatcher};
+
+use crate::metrics::TimedLock;
+use crate::state::{find_assets_dir, AppState};
+
+/// Event carrying a `ConfigChanged`
+pub const CONFIG_CHANGED_EVENT: &str = "config-changed";
+/// How often the files are checked
+const POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// What changed on disk, and the config in use afterwards
+#[derive(Debug, Clone, Serialize)]
+pub struct ConfigChanged {
+    /// "config" or "chordmaps"
+    pub source: &'static str,
+    pub config: AppConfig,
+}
+
+/// Watch the config file and genre presets on a background thread
+pub fn spawn(app: AppHandle) {
+    let config_file = app.state::<AppState>().paths.config_file.clone();
+    let chordmaps_dir = find_assets_dir().map(|assets| assets.join("chordmaps"));
+    let mut watcher = FileWatcher::new(std::iter::once(config_file.clone()).chain(chordmaps_dir));
+
+    std::thread::spawn(move || loop {
+        std::thread::sleep(POLL_INTERVAL);
+        for path in watcher.poll() {
+            let state = app.state::<AppState>();
+            let changed = if path == config_file {
+                if !path.exists() {
+                    continue;
+                }
+                match state.reload_config() {
+                    Ok(Some(config)) => ConfigChanged { source: "config", config },
+                    Ok(None) => continue,
+                    Err(e) => {
+                        log::warn!("Ignoring edited config file: {}", e);
+                        continue;
+                    }
+                }
+            } else {
+                state.reload_chordmaps();
+                ConfigChanged { source: "chordmaps", config: state.config.timed_lock().clone() }
+            };
+            if let Err(e) = app.emit(CONFIG_CHANGED_EVENT, &changed) {
+                log::warn!("Failed to emit {}: {}", CONFIG_CHANGED_EVENT, e);
+            }
+        }
+    });
+}
@@ -0,0 +1,73 @@
+//! Fret LED feedback
+//! On guitars with fret LEDs (see `controller::fret_leds`) a background
+//! thread decides about 30 times per second which frets to light. While a
+//! song plays, the mode comes from the chart section being played, falling
+//! back to `song.fret_leds` in the config: the frets of the next chord, or the
+//! held frets once they match the chord due now. In free play the held frets
+//! light up whenever they play a chord. The controller only sends a report
+//! when the lit frets change.
+
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use controller::{ControllerStateSnapshot, FretLeds};
+use song::{FretLedMode, SongChart};
+
+use crate::metrics::TimedLock;
+use crate::state::{held_fret_names, AppState};
+
+/// ~30 Hz
+const UPDATE_INTERVAL: Duration = Duration::from_millis(33);
+
+/// Frets to light for a playing chart at a beat
+fn song_leds(chart: &SongChart, beat: f64, mode: FretLedMode, snapshot: &ControllerStateSnapshot) -> FretLeds {
+    let chord_frets = |chord: &str| chart.mapping.chords.get(chord).map(|mapping| FretLeds::from_fret_names(&mapping.frets));
+    let events = chart.get_all_chord_events();
+    match mode {
+        FretLedMode::Off => FretLeds::NONE,
+        FretLedMode::NextChord => events.iter()
+            .find(|event| event.beat > beat)
+            .and_then(|event| chord_frets(&event.chord))
+            .unwrap_or_default(),
+        FretLedMode::HeldChord => {
+            let due = events.iter()
+                .rev()
+                .find(|event| event.beat <= beat && beat < event.beat + event.dur)
+                .and_then(|event| chord_frets(&event.chord));
+            let held = FretLeds::from_fret_names(&held_fret_names(snapshot));
+            if due == Some(held) { held } else { FretLeds::NONE }
+        }
+    }
+}
+
+/// Frets player 1's guitar should light now
+pub fn wanted_leds(state: &AppState) -> FretLeds {
+    let snapshot = state.get_controller_state();
+    let default_mode = FretLedMode::from_name(&state.config.timed_lock().song.fret_leds).unwrap_or(FretLedMode::NextChord);
+    {
+        let mut player = state.song_player.timed_lock();
+        let beat = player.get_current_beat();
+        let is_playing = player.get_transport_state().is_playing;
+        if let Some(chart) = player.get_chart().filter(|_| is_playing) {
+            let mode = chart.fret_led_mode_at(beat, default_mode);
+            return song_leds(chart, beat, mode, &snapshot);
+        }
+    }
+    match state.held_chord_name(&snapshot) {
+        Some(_) => FretLeds::from_fret_names(&held_fret_names(&snapshot)),
+        None => FretLeds::NONE,
+    }
+}
+
+/// Drive the fret LEDs on a background thread while a guitar with LEDs is connected
+pub fn spawn(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(UPDATE_INTERVAL);
+        let state = app.state::<AppState>();
+        if state.controller.timed_lock().fret_leds().is_none() {
+            continue;
+        }
+        let leds = wanted_leds(&state);
+        state.controller.timed_lock().set_fret_leds(leds);
+    });
+}
@@ -0,0 +1,198 @@
+//! Background jobs for long-running commands
+//! Commands that touch large files (soundfont upload, rescans, imports) start a
+//! job and return its ID right away. Progress is reported with `job-progress`
+//! events and the outcome with `job-finished`; `cancel_job` asks the job to stop
+//! at its next checkpoint.
+
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tauri::{AppHandle, Emitter};
+
+use crate::metrics::TimedLock;
+
+pub type JobId = u64;
+
+/// Finished jobs kept around for `get_job`
+const MAX_FINISHED_JOBS: usize = 50;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JobStatus {
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+/// Job state as sent to the frontend
+#[derive(Debug, Clone, Serialize)]
+pub struct JobInfo {
+    pub id: JobId,
+    pub kind: String,
+    pub status: JobStatus,
+    /// 0.0-1.0
+    pub progress: f32,
+    pub message: Option<String>,
+    pub result: Option<String>,
+    pub error: Option<String>,
+}
+
+struct JobEntry {
+    info: JobInfo,
+    cancelled: Arc<AtomicBool>,
+}
+
+/// Handed to the job body for progress reporting and cancellation checks
+pub struct JobContext {
+    id: JobId,
+    cancelled: Arc<AtomicBool>,
+    manager: Arc<JobManager>,
+    app: AppHandle,
+}
+
+impl JobContext {
+    pub fn app(&self) -> &AppHandle {
+        &self.app
+    }
+
+    /// Report progress (0.0-1.0) with a short status message
+    pub fn progress(&self, progress: f32, message: impl Into<String>) {
+        let info = self.manager.update(self.id, |info| {
+            info.progress = progress.clamp(0.0, 1.0);
+            info.message = Some(message.into());
+        });
+        if let Some(info) = info {
+            let _ = self.app.emit("job-progress", &info);
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Bail out of the job body if cancellation was requested
+    pub fn check_cancelled(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err("Cancelled".to_string())
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct JobManager {
+    next_id: AtomicU64,
+    jobs: Mutex<HashMap<JobId, JobEntry>>,
+}
+
+impl JobManager {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run `work` on a background thread, returning the job ID immediately
+    pub fn spawn<F>(self: &Arc<Self>, app: &AppHandle, kind: &str, work: F) -> Result<JobId, String>
+    where
+        F: FnOnce(&JobContext) -> Result<String, String> + Send + 'static,
+    {
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed) + 1;
+        let cancelled = Arc::new(AtomicBool::new(false));
+        self.jobs.timed_lock().insert(id, JobEntry {
+            info: JobInfo {
+                id,
+                kind: kind.to_string(),
+                status: JobStatus::Running,
+                progress: 0.0,
+                message: None,
+                result: None,
+                error: None,
+            },
+            cancelled: Arc::clone(&cancelled),
+        });
+
+        let context = JobContext {
+            id,
+            cancelled,
+            manager: Arc::clone(self),
+            app: app.clone(),
+        };
+        log::info!("⏳ Job {} ({}) started", id, kind);
+        std::thread::Builder::new()
+            .name(format!("job-{}-{}", id, kind))
+            .spawn(move || {
+                let outcome = work(&context);
+                context.manager.finish(&context, outcome);
+            })
+            .map_err(|e| format!("Failed to start {} job: {}", kind, e))?;
+        Ok(id)
+    }
+
+    fn update(&self, id: JobId, apply: impl FnOnce(&mut JobInfo)) -> Option<JobInfo> {
+        let mut jobs = self.jobs.timed_lock();
+        let entry = jobs.get_mut(&id)?;
+        apply(&mut entry.info);
+        Some(entry.info.clone())
+    }
+
+    fn finish(&self, context: &JobContext, outcome: Result<String, String>) {
+        let cancelled = context.is_cancelled();
+        let info = self.update(context.id, |info| {
+            match outcome {
+                Ok(result) => {
+                    info.status = JobStatus::Completed;
+                    info.progress = 1.0;
+                    info.result = Some(result);
+                }
+                Err(_) if cancelled => info.status = JobStatus::Cancelled,
+                Err(error) => {
+                    info.status = JobStatus::Failed;
+                    info.error = Some(error);
+                }
+            }
+        });
+        if let Some(info) = info {
+            log::info!("⏳ Job {} ({}) {:?}", info.id, info.kind, info.status);
+            let _ = context.app.emit("job-finished", &info);
+        }
+        self.prune();
+    }
+
+    /// Drop the oldest finished jobs beyond `MAX_FINISHED_JOBS`
+    fn prune(&self) {
+        let mut jobs = self.jobs.timed_lock();
+        let mut finished: Vec<JobId> = jobs.values()
+            .filter(|entry| entry.info.status != JobStatus::Running)
+            .map(|entry| entry.info.id)
+            .collect();
+        if finished.len() > MAX_FINISHED_JOBS {
+            finished.sort_unstable();
+            for id in &finished[..finished.len() - MAX_FINISHED_JOBS] {
+                jobs.remove(id);
+            }
+        }
+    }
+
+    pub fn get(&self, id: JobId) -> Option<JobInfo> {
+        self.jobs.timed_lock().get(&id).map(|entry| entry.info.clone())
+    }
+
+    pub fn list(&self) -> Vec<JobInfo> {
+        let mut jobs: Vec<JobInfo> = self.jobs.timed_lock().values().map(|entry| entry.info.clone()).collect();
+        jobs.sort_by_key(|info| info.id);
+        jobs
+    }
+
+    /// Request cancellation, returns false if the job isn't running
+    pub fn cancel(&self, id: JobId) -> bool {
+        match self.jobs.timed_lock().get(&id) {
+            Some(entry) if entry.info.status == JobStatus::Running => {
+                entry.cancelled.store(true, Ordering::Relaxed);
+                true
+            }
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,49 @@
+//! Background song library scanning
+//! The song library is indexed on a background thread at start and
+//! rescanned whenever a file in the songs directory changes, so listing the
+//! library is a lookup instead of parsing every chart. A rescan that finds
+//! new, changed or removed charts sends a `library-changed` event with the
+//! updated listing.
+
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use config::FileWatcher;
+
+use crate::state::AppState;
+
+/// Event carrying the library listing (`Vec<LibrarySong>`)
+pub const LIBRARY_CHANGED_EVENT: &str = "library-changed";
+/// How often the songs directory is checked
+const POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Index the song library and follow changes to it on a background thread
+pub fn spawn(app: AppHandle) {
+    let songs_dir = app.state::<AppState>().paths.songs_dir.clone();
+    let mut watcher = FileWatcher::new([songs_dir]);
+
+    std::thread::spawn(move || {
+        // The saved index may be out of date with the directory
+        let mut dirty = true;
+        loop {
+            if dirty {
+                let state = app.state::<AppState>();
+                match state.rescan_library() {
+                    Ok(true) => match state.library_songs() {
+                        Ok(songs) => {
+                            log::info!("📚 Song library indexed: {} songs", songs.len());
+                            if let Err(e) = app.emit(LIBRARY_CHANGED_EVENT, &songs) {
+                                log::warn!("Failed to emit {}: {}", LIBRARY_CHANGED_EVENT, e);
+                            }
+                        }
+                        Err(e) => log::warn!("Failed to list song library: {}", e),
+                    },
+                    Ok(false) => {}
+                    Err(e) => log::warn!("Failed to scan song library: {}", e),
+                }
+            }
+            std::thread::sleep(POLL_INTERVAL);
+            dirty = !watcher.poll().is_empty();
+        }
+    });
+}
@@ -0,0 +1,141 @@
+//! Live-set mode
+//! On stage the player glances at a second screen rather than the settings
+//! window, so the state that matters mid-song (song and section, current and
+//! next chord, key, instrument, guitar battery) is gathered into one
+//! `live-set-state` event emitted a few times per second while live-set mode
+//! is on. The stage window only listens to that event and never polls
+//! commands, keeping it cheap to leave open next to the main window.
+
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, WebviewUrl, WebviewWindowBuilder};
+
+use song::SongChart;
+
+use crate::metrics::TimedLock;
+use crate::state::AppState;
+
+/// Event carrying a `LiveSetState`
+pub const LIVE_SET_EVENT: &str = "live-set-state";
+/// Label of the stage display window
+pub const LIVE_SET_WINDOW: &str = "live-set";
+/// How often the state is emitted while live-set mode is on
+const UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Everything the stage display shows
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveSetState {
+    /// Loaded song (None in free play)
+    pub song: Option<LiveSetSong>,
+    /// Key being played, e.g. "A major"
+    pub key: String,
+    pub genre: String,
+    pub instrument: Option<String>,
+    pub controller_connected: bool,
+    /// Player 1's guitar battery in percent (None = unknown or wired)
+    pub battery: Option<u8>,
+}
+
+/// Position in the loaded song
+#[derive(Debug, Clone, Serialize)]
+pub struct LiveSetSong {
+    pub title: String,
+    pub artist: String,
+    pub section: Option<String>,
+    pub chord: Option<String>,
+    pub next_chord: Option<String>,
+    /// Beats until the next chord starts
+    pub beats_to_next_chord: Option<f64>,
+    pub beat: f64,
+    pub bpm: f64,
+    pub is_playing: bool,
+}
+
+impl LiveSetSong {
+    /// Section and chords at a beat of a chart
+    fn at(chart: &SongChart, beat: f64, bpm: f64, is_playing: bool) -> Self {
+        let section = chart.sections.iter()
+            .find(|section| beat >= section.from_beat && beat < section.to_beat)
+            .map(|section| section.name.clone());
+        let events = chart.get_all_chord_events();
+        let chord = events.iter()
+            .rev()
+            .find(|event| event.beat <= beat && beat < event.beat + event.dur)
+            .map(|event| event.chord.clone());
+        let next = events.iter().find(|event| event.beat > beat);
+        Self {
+            title: chart.meta.title.clone(),
+            artist: chart.meta.artist.clone(),
+            section,
+            chord,
+            next_chord: next.map(|event| event.chord.clone()),
+            beats_to_next_chord: next.map(|event| event.beat - beat),
+            beat,
+            bpm,
+            is_playing,
+        }
+    }
+}
+
+impl LiveSetState {
+    /// Gather the current state
+    pub fn collect(state: &AppState) -> Self {
+        let song = {
+            let mut player = state.song_player.timed_lock();
+            let beat = player.get_current_beat();
+            let transport = player.get_transport_state();
+            let (bpm, is_playing) = (transport.bpm * transport.speed_multiplier, transport.is_playing);
+            player.get_chart().map(|chart| LiveSetSong::at(chart, beat, bpm, is_playing))
+        };
+
+        let key = state.current_key();
+        let genre = if state.uses_legacy_mapper() {
+            state.mapper.timed_lock().genre().name().to_string()
+        } else {
+            state.harmonic_mappers[0].timed_lock().genre().name().to_string()
+        };
+
+        let instrument = state.config.timed_lock().soundfonts.current.clone();
+        let controller = state.get_controller_state();
+        Self {
+            song,
+            key,
+            genre,
+            instrument,
+            controller_connected: controller.connected,
+            battery: controller.battery,
+        }
+    }
+}
+
+/// Open (or focus) the stage display window
+pub fn open_window(app: &AppHandle, fullscreen: bool) -> tauri::Result<()> {
+    if let Some(window) = app.get_webview_window(LIVE_SET_WINDOW) {
+        window.set_fullscreen(fullscreen)?;
+        window.show()?;
+        return window.set_focus();
+    }
+    WebviewWindowBuilder::new(app, LIVE_SET_WINDOW, WebviewUrl::App("index.html#/live-set".into()))
+        .title("mITyGuitar Live Set")
+        .inner_size(1280.0, 720.0)
+        .fullscreen(fullscreen)
+        .always_on_top(true)
+        .build()?;
+    log::info!("🎤 Live set window opened");
+    Ok(())
+}
+
+/// Emit the live-set state on a background thread while live-set mode is on
+pub fn spawn(app: AppHandle) {
+    std::thread::spawn(move || loop {
+        std::thread::sleep(UPDATE_INTERVAL);
+        let state = app.state::<AppState>();
+        if !state.live_set_mode() {
+            continue;
+        }
+        let live_set = LiveSetState::collect(&state);
+        if let Err(e) = app.emit(LIVE_SET_EVENT, &live_set) {
+            log::warn!("Failed to emit {}: {}", LIVE_SET_EVENT, e);
+        }
+    });
+}
@@ -4,9 +4,20 @@
 mod state;
 mod commands;
 mod song_player;
+mod metrics;
+mod jobs;
+mod media_session;
+mod live_set;
+mod overlay;
+mod fret_leds;
+mod config_watch;
+mod library_scan;
+mod autosave;
+mod ticker;
 
+use metrics::TimedLock;
 use state::AppState;
-use tauri::{Manager, menu::{Menu, MenuItem}, tray::{TrayIconBuilder, TrayIconEvent}};
+use tauri::{Emitter, Manager, menu::{Menu, MenuItem}, tray::{TrayIconBuilder, TrayIconEvent}};
 
 fn main() {
     env_logger::init();
@@ -18,8 +29,100 @@ fn main() {
         .setup(|app| {
             // Initialize application state
             let state = AppState::new()?;
+            
+            // Forward controller hot-plug events to the frontend
+            let controller_events = state.controller.timed_lock().subscribe_events();
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                for event in controller_events {
+                    if let Err(e) = app_handle.emit(event.event_name(), &event) {
+                        log::warn!("Failed to emit {}: {}", event.event_name(), e);
+                    }
+                }
+            });
+            
+            // Tell the frontend where the D-pad capo put the key
+            let transpose_events = state.subscribe_transpose();
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                for info in transpose_events {
+                    if let Err(e) = app_handle.emit("transpose-changed", &info) {
+                        log::warn!("Failed to emit transpose-changed: {}", e);
+                    }
+                }
+            });
+            
+            // Tell the frontend when a config profile is applied (e.g. from the guitar)
+            let profile_events = state.subscribe_config_profiles();
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                for name in profile_events {
+                    if let Err(e) = app_handle.emit("config-profile-applied", &name) {
+                        log::warn!("Failed to emit config-profile-applied: {}", e);
+                    }
+                }
+            });
+            
+            // Tell the frontend which chord each strum played
+            let chord_events = state.subscribe_chords();
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                for chord in chord_events {
+                    if let Err(e) = app_handle.emit("chord-played", &chord) {
+                        log::warn!("Failed to emit chord-played: {}", e);
+                    }
+                }
+            });
+            
+            // Tell the frontend when star power kicks in
+            let star_power_events = state.subscribe_star_power();
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                for activation in star_power_events {
+                    if let Err(e) = app_handle.emit("star-power-activated", &activation) {
+                        log::warn!("Failed to emit star-power-activated: {}", e);
+                    }
+                }
+            });
+            
+            // Guitar presses while a menu is open
+            let menu_events = state.subscribe_menu_navigation();
+            let app_handle = app.handle().clone();
+            std::thread::spawn(move || {
+                for action in menu_events {
+                    if let Err(e) = app_handle.emit("menu-navigate", &action) {
+                        log::warn!("Failed to emit menu-navigate: {}", e);
+                    }
+                }
+            });
+            
             app.manage(state);
             
+            // Publish song mode to the OS media session and follow media keys
+            media_session::spawn(app.handle().clone());
+            
+            // Stream the stage display state while live-set mode is on
+            live_set::spawn(app.handle().clone());
+            
+            // Publish chord, frets and score to stream overlays while enabled
+            overlay::spawn(app.handle().clone());
+            
+            // Light the chords on guitars with fret LEDs
+            fret_leds::spawn(app.handle().clone());
+            
+            // Pick up config and genre preset edits made outside the app
+            config_watch::spawn(app.handle().clone());
+            
+            // Index the song library and keep the index up to date
+            library_scan::spawn(app.handle().clone());
+            
+            // Keep the song session recoverable after a crash
+            autosave::spawn(app.handle().clone());
+            
+            // Play controller macros, follow the song transport and report SoundFont
+            // loads and audio underruns on one clock
+            ticker::spawn(app.handle().clone());
+            
             // Create system tray menu
             let show = MenuItem::with_id(app, "show", "Show", true, None::<&str>)?;
             let quit = MenuItem::with_id(app, "quit", "Quit", true, None::<&str>)?;
@@ -58,11 +161,48 @@ fn main() {
             log::info!("mITyGuitar initialized with system tray");
             Ok(())
         })
-        .invoke_handler(tauri::generate_handler![
+        .invoke_handler(metrics::instrument(tauri::generate_handler![
             commands::get_controller_state,
+            commands::get_connected_players,
+            commands::set_player_instrument,
             commands::simulator_key_down,
             commands::simulator_key_up,
+            commands::simulator_play_script,
+            commands::simulator_wheel,
             commands::set_genre,
+            commands::set_voice_leading,
+            commands::get_voice_leading,
+            commands::set_guitar_voicing,
+            commands::set_strum_stagger,
+            commands::get_strum_settings,
+            commands::set_legacy_mapper,
+            commands::get_legacy_mapper,
+            commands::set_diatonic_chords,
+            commands::set_bass_mode,
+            commands::get_bass_players,
+            commands::set_lead_mode,
+            commands::get_lead_players,
+            commands::list_mapper_plugins,
+            commands::rescan_mapper_plugins,
+            commands::set_player_plugin,
+            commands::get_player_plugins,
+            commands::get_diatonic_chords,
+            commands::set_auto_instrument,
+            commands::get_auto_instrument,
+            commands::set_dynamics_mode,
+            commands::get_dynamics_mode,
+            commands::set_palm_mute_trigger,
+            commands::get_palm_mute_trigger,
+            commands::set_tilt_layer,
+            commands::get_tilt_layer,
+            commands::set_tilt_mode,
+            commands::get_tilt_mode,
+            commands::set_bend_target,
+            commands::get_bend_target,
+            commands::set_whammy_split,
+            commands::get_whammy_split,
+            commands::set_whammy_mode,
+            commands::get_whammy_mode,
             commands::next_pattern,
             commands::prev_pattern,
             commands::next_instrument,
@@ -70,7 +210,26 @@ fn main() {
             commands::panic_all_notes_off,
             commands::quit_app,
             commands::get_audio_stats,
+            commands::get_audio_meters,
+            commands::get_soundfonts_ready,
+            commands::get_audio_backends,
+            commands::set_audio_backend,
+            commands::list_audio_devices,
+            commands::set_audio_device,
+            commands::set_latency_measurement_enabled,
+            commands::get_latency_stats,
+            commands::reset_latency_stats,
+            commands::list_input_devices,
+            commands::start_input_monitor,
+            commands::stop_input_monitor,
+            commands::set_input_monitor_gain,
+            commands::get_input_monitor_stats,
             commands::get_config,
+            commands::list_config_profiles,
+            commands::get_active_config_profile,
+            commands::apply_config_profile,
+            commands::save_config_profile,
+            commands::delete_config_profile,
             commands::save_config,
             commands::get_genres,
             commands::get_current_genre_info,
@@ -79,42 +238,95 @@ fn main() {
             commands::set_soundfont,
             commands::rescan_soundfonts,
             commands::upload_soundfont,
+            commands::render_instrument_preview,
             commands::check_hardware_controller,
             commands::get_controller_debug_info,
             commands::check_audio_health,
             commands::set_release_multiplier,
             commands::set_sustain_enabled,
             commands::set_sustain_release_time,
+            commands::set_a4_frequency,
+            commands::get_a4_frequency,
+            commands::set_stereo_spread,
+            commands::get_stereo_spread,
+            commands::set_max_voices,
+            commands::get_max_voices,
+            commands::mixer_set_level,
+            commands::mixer_get_levels,
+            commands::set_temperament,
+            commands::get_temperament,
+            commands::list_temperaments,
             // New chord mapping commands
             commands::get_chord_mapping,
             commands::update_chord_override,
             commands::update_chord_mapping_settings,
+            commands::preview_chord_mapping_change,
+            commands::get_last_chord,
             commands::get_app_config,
             // Raw diagnostics commands
             commands::set_raw_diagnostics_enabled,
             commands::get_raw_diagnostics,
             commands::clear_raw_diagnostics,
             commands::get_raw_diagnostics_status,
+            commands::export_raw_capture,
+            commands::replay_raw_capture,
             // Mapping wizard commands
             commands::wizard_start_capture,
             commands::wizard_stop_capture,
             commands::wizard_finalize_capture,
             commands::wizard_get_state,
             commands::wizard_set_auto_capture,
+            commands::get_debounce_stats,
+            commands::set_debounce_ms,
+            commands::whammy_calibration_start,
+            commands::whammy_calibration_status,
+            commands::whammy_calibration_finish,
+            commands::whammy_calibration_cancel,
+            commands::whammy_calibration_clear,
+            commands::hardware_test_start,
+            commands::hardware_test_status,
+            commands::hardware_test_skip,
+            commands::hardware_test_cancel,
+            commands::list_footswitches,
+            commands::connect_footswitch,
+            commands::disconnect_footswitch,
+            commands::get_footswitch,
+            commands::list_led_guitars,
+            commands::connect_fret_leds,
+            commands::disconnect_fret_leds,
+            commands::get_fret_leds,
+            commands::set_fret_led_mode,
+            commands::get_fret_led_mode,
+            commands::set_rumble_enabled,
+            commands::get_rumble_enabled,
+            commands::macro_list,
+            commands::macro_save,
+            commands::macro_delete,
+            commands::macro_record_start,
+            commands::macro_record_stop,
+            commands::macro_trigger,
+            commands::macro_stop_all,
             commands::wizard_clear,
             // Mapping profile commands
             commands::list_mapping_profiles,
             commands::load_mapping_profile,
             commands::save_mapping_profile,
+            commands::list_profile_versions,
+            commands::restore_profile_version,
             commands::create_mapping_profile,
             commands::delete_mapping_profile,
             commands::set_active_profile,
             commands::get_active_profile,
             commands::update_profile_mapping,
+            commands::set_axis_curve,
             // Song play commands
             commands::song_load_chart,
             commands::song_load_default_chart,
             commands::song_load_chart_from_path,
+            commands::song_import_chart_file,
+            commands::song_list_midi_tracks,
+            commands::song_import_midi,
+            commands::song_unload_chart,
             commands::song_get_chart,
             commands::song_play,
             commands::song_pause,
@@ -122,17 +334,115 @@ fn main() {
             commands::song_seek,
             commands::song_set_speed,
             commands::song_get_transport_state,
+            commands::song_set_accent_grouping,
+            commands::song_set_metronome_enabled,
+            commands::song_set_count_in,
+            commands::song_set_difficulty,
+            commands::song_get_difficulty,
+            commands::song_set_loop,
+            commands::song_clear_loop,
+            commands::song_get_count_in,
+            commands::song_set_accompaniment,
+            commands::set_change_quantize,
+            commands::get_pending_changes,
+            commands::cancel_pending_changes,
+            commands::song_tap_tempo,
+            commands::song_set_tap_tempo_rounding,
             commands::song_check_strum,
+            commands::song_activate_star_power,
+            commands::song_get_timing,
+            commands::song_set_hit_windows,
+            commands::song_set_practice_mode,
+            commands::song_set_no_fail,
+            commands::song_start_speed_trainer,
+            commands::song_stop_speed_trainer,
+            commands::song_get_speed_trainer,
+            commands::song_set_input_offset,
+            commands::song_calibrate_latency,
+            commands::song_render_to_wav,
             commands::song_update_sustain,
             commands::song_get_score,
+            commands::song_get_highscores,
+            commands::song_get_history,
+            commands::get_practice_report,
+            commands::reset_practice_session,
             commands::song_set_instrument,
             commands::song_clear_instrument_override,
+            // Song editor commands
+            commands::song_edit_get_chart,
+            commands::song_edit_new_chart,
+            commands::song_edit_set_meta,
+            commands::song_edit_set_backing_track,
+            commands::song_edit_set_chord,
+            commands::song_edit_add_event,
+            commands::song_edit_remove_event,
+            commands::song_edit_move_event,
+            commands::song_edit_set_section,
+            commands::song_edit_remove_section,
+            commands::song_edit_set_lyric,
+            commands::song_edit_remove_lyric,
+            // Song recording commands
+            commands::song_record_start,
+            commands::song_record_stop,
+            commands::song_record_cancel,
+            commands::song_record_status,
+            // Chord trainer commands
+            commands::trainer_start,
+            commands::trainer_get_prompt,
+            commands::trainer_submit_result,
+            commands::trainer_get_stats,
+            commands::trainer_stop,
+            // Drum machine commands
+            commands::drums_start,
+            commands::drums_stop,
+            commands::drums_set_volume,
             // Song library commands
             commands::song_save_to_library,
             commands::song_list_library,
+            commands::song_search,
+            commands::get_recoverable_session,
+            commands::recover_last_session,
+            commands::discard_recovered_session,
             commands::song_load_from_library,
             commands::song_delete_from_library,
-        ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+            commands::setlist_list,
+            commands::setlist_save,
+            commands::setlist_delete,
+            commands::setlist_move_entry,
+            commands::setlist_play,
+            commands::setlist_next,
+            commands::setlist_get_position,
+            commands::setlist_stop,
+            commands::get_command_metrics,
+            commands::get_app_paths,
+            commands::export_user_data,
+            commands::import_user_data,
+            // Transpose commands
+            commands::transpose,
+            commands::get_transpose,
+            commands::set_transpose_range,
+            commands::set_menu_mode,
+            commands::get_menu_mode,
+            // Live set commands
+            commands::set_live_set_mode,
+            commands::get_live_set_mode,
+            commands::get_live_set_state,
+            commands::open_live_set_window,
+            // Stream overlay commands
+            commands::set_overlay,
+            commands::get_overlay_state,
+            commands::get_job,
+            commands::list_jobs,
+            commands::cancel_job,
+            commands::run_demo,
+            commands::reset_command_metrics,
+        ]))
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // A clean exit leaves no session to recover
+            if let tauri::RunEvent::Exit = event {
+                app.state::<AppState>().clear_recovery();
+            }
+        });
 }
@@ -0,0 +1,217 @@
+//! OS media session (SMTC on Windows, MPRIS on Linux)
+//! Song mode publishes the loaded song and transport state like a regular
+//! media app, and the media keys (play/pause/stop, seeking from the OS
+//! overlay) drive the song transport. The session lives on its own thread and
+//! polls the song player a few times per second; the OS extrapolates the
+//! position while playing, so it is only re-sent when playback state changes
+//! or the position jumps (seek, tempo change).
+
+use anyhow::{anyhow, Result};
+use souvlaki::{MediaControlEvent, MediaControls, MediaMetadata, MediaPlayback, MediaPosition, PlatformConfig, SeekDirection};
+use std::sync::mpsc::{self, Receiver};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Manager};
+
+use crate::metrics::TimedLock;
+use crate::song_player::SongPlayer;
+use crate::state::AppState;
+
+/// How often the session follows the song player
+const UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+/// Position drift before the OS is told the position again
+const POSITION_TOLERANCE: Duration = Duration::from_secs(1);
+/// Seek step when the OS asks to seek without an amount
+const DEFAULT_SEEK: Duration = Duration::from_secs(10);
+
+/// What the OS shows as playing
+#[derive(Debug, Clone, PartialEq)]
+pub struct NowPlaying {
+    pub title: String,
+    pub artist: String,
+    pub duration: Option<Duration>,
+    /// Position from the first beat (zero during the count-in)
+    pub position: Duration,
+    pub is_playing: bool,
+}
+
+impl NowPlaying {
+    /// Current song and transport state (None when no song is loaded)
+    pub fn from_player(player: &mut SongPlayer) -> Option<Self> {
+        let beat = player.get_current_beat();
+        let transport = player.get_transport_state();
+        let (speed, is_playing) = (transport.speed_multiplier, transport.is_playing);
+        let chart = player.get_chart()?;
+        let seconds = |beats: f64| Duration::from_secs_f64(chart.beat_to_seconds(beats.max(0.0), speed).max(0.0));
+        Some(Self {
+            title: chart.meta.title.clone(),
+            artist: chart.meta.artist.clone(),
+            duration: Some(seconds(chart.total_beats())).filter(|duration| !duration.is_zero()),
+            position: seconds(beat),
+            is_playing,
+        })
+    }
+}
+
+/// Registration with the OS media session
+pub struct MediaSession {
+    controls: MediaControls,
+    events: Receiver<MediaControlEvent>,
+    /// Song last published (title, artist, duration)
+    metadata: Option<(String, String, Option<Duration>)>,
+    /// Playback last published, with when it was sent
+    playback: Option<(bool, Duration, Instant)>,
+}
+
+impl MediaSession {
+    /// Register with the OS. Windows needs the main window handle.
+    pub fn new(hwnd: Option<*mut std::ffi::c_void>) -> Result<Self> {
+        let config = PlatformConfig {
+            dbus_name: "mityguitar",
+            display_name: "mITyGuitar",
+            hwnd,
+        };
+        let mut controls = MediaControls::new(config)
+            .map_err(|e| anyhow!("Failed to create media session: {:?}", e))?;
+
+        let (sender, events) = mpsc::channel();
+        controls.attach(move |event| {
+            let _ = sender.send(event);
+        }).map_err(|e| anyhow!("Failed to attach media key handler: {:?}", e))?;
+
+        Ok(Self {
+            controls,
+            events,
+            metadata: None,
+            playback: None,
+        })
+    }
+
+    /// Media key presses since the last call
+    pub fn take_events(&self) -> Vec<MediaControlEvent> {
+        self.events.try_iter().collect()
+    }
+
+    /// Publish the current song and transport state
+    pub fn update(&mut self, now_playing: Option<&NowPlaying>) -> Result<()> {
+        let metadata = now_playing.map(|song| (song.title.clone(), song.artist.clone(), song.duration));
+        if metadata != self.metadata {
+            let (title, artist, duration) = metadata.clone().unwrap_or_default();
+            self.controls.set_metadata(MediaMetadata {
+                title: now_playing.map(|_| title.as_str()),
+                artist: now_playing.map(|_| artist.as_str()),
+                duration,
+                ..Default::default()
+            }).map_err(|e| anyhow!("Failed to set media metadata: {:?}", e))?;
+            self.metadata = metadata;
+            self.playback = None;
+        }
+
+        let Some(song) = now_playing else {
+            if self.playback.is_some() {
+                self.controls.set_playback(MediaPlayback::Stopped)
+                    .map_err(|e| anyhow!("Failed to set media playback: {:?}", e))?;
+                self.playback = None;
+            }
+            return Ok(());
+        };
+        if !self.playback_changed(song) {
+            return Ok(());
+        }
+        let progress = Some(MediaPosition(song.position));
+        let playback = if song.is_playing {
+            MediaPlayback::Playing { progress }
+        } else {
+            MediaPlayback::Paused { progress }
+        };
+        self.controls.set_playback(playback)
+            .map_err(|e| anyhow!("Failed to set media playback: {:?}", e))?;
+        self.playback = Some((song.is_playing, song.position, Instant::now()));
+        Ok(())
+    }
+
+    /// Whether the OS's idea of the playback state is off
+    fn playback_changed(&self, song: &NowPlaying) -> bool {
+        let Some((was_playing, position, sent)) = self.playback else {
+            return true;
+        };
+        let expected = if was_playing { position + sent.elapsed() } else { position };
+        let drift = expected.abs_diff(song.position);
+        was_playing != song.is_playing || drift > POSITION_TOLERANCE
+    }
+}
+
+/// Apply a media key press to the song transport
+fn handle_event(player: &mut SongPlayer, event: MediaControlEvent) {
+    let seek_by = |player: &mut SongPlayer, direction: SeekDirection, seconds: f64| {
+        let beat = player.get_current_beat();
+        let transport = player.get_transport_state();
+        let offset = match direction {
+            SeekDirection::Forward => transport.seconds_to_beats(seconds),
+            SeekDirection::Backward => -transport.seconds_to_beats(seconds),
+        };
+        let target = (beat + offset).max(-transport.count_in_beats());
+        player.seek(target);
+    };
+    match event {
+        MediaControlEvent::Play => player.play(),
+        MediaControlEvent::Pause => player.pause(),
+        MediaControlEvent::Toggle => {
+            if player.get_transport_state().is_playing {
+                player.pause();
+            } else {
+                player.play();
+            }
+        }
+        MediaControlEvent::Stop => player.stop(),
+        MediaControlEvent::SetPosition(MediaPosition(position)) => {
+            let beat = player.get_transport_state().seconds_to_beats(position.as_secs_f64());
+            player.seek(beat);
+        }
+        MediaControlEvent::Seek(direction) => seek_by(player, direction, DEFAULT_SEEK.as_secs_f64()),
+        MediaControlEvent::SeekBy(direction, amount) => seek_by(player, direction, amount.as_secs_f64()),
+        _ => {}
+    }
+}
+
+/// Window handle the Windows media session attaches to
+#[cfg(target_os = "windows")]
+fn window_handle(app: &AppHandle) -> Option<usize> {
+    let window = app.get_webview_window("main")?;
+    window.hwnd().ok().map(|hwnd| hwnd.0 as usize)
+}
+
+#[cfg(not(target_os = "windows"))]
+fn window_handle(_app: &AppHandle) -> Option<usize> {
+    None
+}
+
+/// Run the media session on a background thread
+pub fn spawn(app: AppHandle) {
+    let hwnd = window_handle(&app);
+    std::thread::spawn(move || {
+        let mut session = match MediaSession::new(hwnd.map(|hwnd| hwnd as *mut std::ffi::c_void)) {
+            Ok(session) => session,
+            Err(e) => {
+                log::warn!("Media keys unavailable: {}", e);
+                return;
+            }
+        };
+        log::info!("⏯️ Media session registered");
+
+        loop {
+            let now_playing = {
+                let state = app.state::<AppState>();
+                let mut player = state.song_player.timed_lock();
+                for event in session.take_events() {
+                    log::info!("⏯️ Media key: {:?}", event);
+                    handle_event(&mut player, event);
+                }
+                NowPlaying::from_player(&mut player)
+            };
+            if let Err(e) = session.update(now_playing.as_ref()) {
+                log::warn!("{}", e);
+            }
+            std::thread::sleep(UPDATE_INTERVAL);
+        }
+    });
+}
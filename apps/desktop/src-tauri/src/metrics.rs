@@ -0,0 +1,136 @@
+//! Command execution timing
+//! Every Tauri command goes through `instrument`, which records how long the
+//! handler ran and how much of that was spent waiting on `timed_lock` calls.
+//! Async commands only show their dispatch time (they don't block the UI).
+
+use once_cell::sync::Lazy;
+use serde::Serialize;
+use std::cell::Cell;
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+use tauri::ipc::Invoke;
+
+/// Commands slower than this are logged
+const SLOW_COMMAND: Duration = Duration::from_millis(100);
+
+static METRICS: Lazy<Mutex<HashMap<String, CommandStats>>> = Lazy::new(Default::default);
+
+thread_local! {
+    /// Lock wait of the command running on this thread (None = not in a command)
+    static LOCK_WAIT: Cell<Option<Duration>> = const { Cell::new(None) };
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+struct CommandStats {
+    calls: u64,
+    total: Duration,
+    max: Duration,
+    lock_wait_total: Duration,
+    lock_wait_max: Duration,
+}
+
+/// Timing summary for one command
+#[derive(Debug, Clone, Serialize)]
+pub struct CommandMetric {
+    pub command: String,
+    pub calls: u64,
+    pub avg_ms: f64,
+    pub max_ms: f64,
+    pub total_ms: f64,
+    pub lock_wait_avg_ms: f64,
+    pub lock_wait_max_ms: f64,
+}
+
+/// Measures one command invocation, recorded on drop
+pub struct CommandTimer {
+    command: String,
+    started: Instant,
+    outer_wait: Option<Duration>,
+}
+
+impl CommandTimer {
+    pub fn start(command: &str) -> Self {
+        let outer_wait = LOCK_WAIT.with(|wait| wait.replace(Some(Duration::ZERO)));
+        Self {
+            command: command.to_string(),
+            started: Instant::now(),
+            outer_wait,
+        }
+    }
+}
+
+impl Drop for CommandTimer {
+    fn drop(&mut self) {
+        let elapsed = self.started.elapsed();
+        let lock_wait = LOCK_WAIT.with(|wait| wait.replace(self.outer_wait)).unwrap_or_default();
+        if elapsed >= SLOW_COMMAND {
+            log::warn!(
+                "🐢 Slow command {}: {:.1} ms (lock wait {:.1} ms)",
+                self.command,
+                elapsed.as_secs_f64() * 1000.0,
+                lock_wait.as_secs_f64() * 1000.0
+            );
+        }
+
+        let mut metrics = METRICS.lock().unwrap();
+        let stats = metrics.entry(std::mem::take(&mut self.command)).or_default();
+        stats.calls += 1;
+        stats.total += elapsed;
+        stats.max = stats.max.max(elapsed);
+        stats.lock_wait_total += lock_wait;
+        stats.lock_wait_max = stats.lock_wait_max.max(lock_wait);
+    }
+}
+
+/// `Mutex::lock().unwrap()` that counts the wait towards the running command
+pub trait TimedLock<T> {
+    fn timed_lock(&self) -> MutexGuard<'_, T>;
+}
+
+impl<T> TimedLock<T> for Mutex<T> {
+    fn timed_lock(&self) -> MutexGuard<'_, T> {
+        let started = Instant::now();
+        let guard = self.lock().unwrap();
+        LOCK_WAIT.with(|wait| {
+            if let Some(total) = wait.get() {
+                wait.set(Some(total + started.elapsed()));
+            }
+        });
+        guard
+    }
+}
+
+/// Wrap a Tauri invoke handler so every command is timed
+pub fn instrument<R: tauri::Runtime>(
+    handler: impl Fn(Invoke<R>) -> bool + Send + Sync + 'static,
+) -> impl Fn(Invoke<R>) -> bool + Send + Sync + 'static {
+    move |invoke| {
+        let _timer = CommandTimer::start(invoke.message.command());
+        handler(invoke)
+    }
+}
+
+/// Per-command timings, slowest (by max) first
+pub fn report() -> Vec<CommandMetric> {
+    let ms = |d: Duration| d.as_secs_f64() * 1000.0;
+    let metrics = METRICS.lock().unwrap();
+    let mut report: Vec<CommandMetric> = metrics
+        .iter()
+        .map(|(command, stats)| CommandMetric {
+            command: command.clone(),
+            calls: stats.calls,
+            avg_ms: ms(stats.total) / stats.calls.max(1) as f64,
+            max_ms: ms(stats.max),
+            total_ms: ms(stats.total),
+            lock_wait_avg_ms: ms(stats.lock_wait_total) / stats.calls.max(1) as f64,
+            lock_wait_max_ms: ms(stats.lock_wait_max),
+        })
+        .collect();
+    report.sort_by(|a, b| b.max_ms.total_cmp(&a.max_ms));
+    report
+}
+
+pub fn reset() {
+    METRICS.lock().unwrap().clear();
+}
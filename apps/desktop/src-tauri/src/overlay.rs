@@ -0,0 +1,176 @@
+//! Stream overlay feed
+//! Streamers put what is being played on screen with a browser source, so
+//! the chord, pressed frets, score and combo are gathered about 30 times per
+//! second into a compact `OverlayState`. While the overlay is enabled in the
+//! config, every change is emitted as an `overlay-state` event for the app's
+//! own windows and served on `http://127.0.0.1:<port>/` as a Server-Sent
+//! Events stream (`/state` returns a single JSON frame). OBS and friends can
+//! read it with a plain `EventSource`, no plugin or websocket library needed.
+
+use serde::Serialize;
+use std::io::{ErrorKind, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::metrics::TimedLock;
+use crate::state::{held_fret_names, AppState};
+
+/// Event carrying an `OverlayState`
+pub const OVERLAY_EVENT: &str = "overlay-state";
+/// ~30 Hz
+const UPDATE_INTERVAL: Duration = Duration::from_millis(33);
+/// A browser source that stops reading is dropped after this
+const WRITE_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// What the overlay shows, kept small since it goes out 30 times per second
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct OverlayState {
+    /// Chord the held frets play (None when nothing or an unmapped shape is held)
+    pub chord: Option<String>,
+    /// Held main frets, e.g. ["GREEN", "RED"]
+    pub frets: Vec<String>,
+    pub score: u32,
+    pub combo: u32,
+    /// Star power is running
+    pub star: bool,
+}
+
+impl OverlayState {
+    /// Gather the current state
+    pub fn collect(state: &AppState) -> Self {
+        let snapshot = state.get_controller_state();
+        let (score, combo, star) = {
+            let mut player = state.song_player.timed_lock();
+            let star = player.star_power_state().active;
+            let scorer = player.get_score();
+            (scorer.score, scorer.combo, star)
+        };
+        Self {
+            chord: state.held_chord_name(&snapshot),
+            frets: held_fret_names(&snapshot),
+            score,
+            combo,
+            star,
+        }
+    }
+}
+
+/// Browser sources connected to the feed
+struct OverlayFeed {
+    listener: TcpListener,
+    port: u16,
+    clients: Vec<TcpStream>,
+}
+
+impl OverlayFeed {
+    fn bind(port: u16) -> std::io::Result<Self> {
+        let listener = TcpListener::bind(("127.0.0.1", port))?;
+        listener.set_nonblocking(true)?;
+        log::info!("📺 Overlay feed on http://127.0.0.1:{}/", port);
+        Ok(Self {
+            listener,
+            port,
+            clients: Vec::new(),
+        })
+    }
+
+    /// Answer new connections: `/state` gets one frame, anything else the event stream
+    fn accept(&mut self, json: &str) {
+        loop {
+            let mut stream = match self.listener.accept() {
+                Ok((stream, _)) => stream,
+                Err(e) if e.kind() == ErrorKind::WouldBlock => return,
+                Err(e) => {
+                    log::warn!("Overlay feed accept failed: {}", e);
+                    return;
+                }
+            };
+            if stream.set_nonblocking(false).is_err()
+                || stream.set_read_timeout(Some(WRITE_TIMEOUT)).is_err()
+                || stream.set_write_timeout(Some(WRITE_TIMEOUT)).is_err()
+            {
+                continue;
+            }
+
+            let mut request = [0u8; 1024];
+            let Ok(read) = stream.read(&mut request) else { continue };
+            let path = String::from_utf8_lossy(&request[..read])
+                .split_whitespace()
+                .nth(1)
+                .unwrap_or("/")
+                .to_string();
+
+            if path == "/state" {
+                let _ = write!(
+                    stream,
+                    "HTTP/1.1 200 OK\r\nContent-Type: application/json\r\nAccess-Control-Allow-Origin: *\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    json.len(),
+                    json
+                );
+                continue;
+            }
+            let headers = "HTTP/1.1 200 OK\r\nContent-Type: text/event-stream\r\nCache-Control: no-cache\r\nAccess-Control-Allow-Origin: *\r\nConnection: keep-alive\r\n\r\n";
+            let first = format!("{}data: {}\n\n", headers, json);
+            if stream.write_all(first.as_bytes()).is_ok() {
+                self.clients.push(stream);
+            }
+        }
+    }
+
+    /// Send a frame to every client, dropping the ones that went away
+    fn broadcast(&mut self, json: &str) {
+        let frame = format!("data: {}\n\n", json);
+        self.clients.retain_mut(|client| client.write_all(frame.as_bytes()).is_ok());
+    }
+}
+
+/// Publish the overlay state on a background thread
+pub fn spawn(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut feed: Option<OverlayFeed> = None;
+        let mut failed_port = None;
+        let mut last = None;
+        loop {
+            std::thread::sleep(UPDATE_INTERVAL);
+            let state = app.state::<AppState>();
+            let settings = state.config.timed_lock().overlay.clone();
+
+            if !settings.enabled {
+                if feed.take().is_some() {
+                    log::info!("📺 Overlay feed stopped");
+                }
+                failed_port = None;
+                last = None;
+                continue;
+            }
+            if feed.as_ref().map(|feed| feed.port) != Some(settings.port) && failed_port != Some(settings.port) {
+                feed = match OverlayFeed::bind(settings.port) {
+                    Ok(feed) => Some(feed),
+                    Err(e) => {
+                        log::warn!("Failed to start overlay feed on port {}: {}", settings.port, e);
+                        failed_port = Some(settings.port);
+                        None
+                    }
+                };
+            }
+
+            let overlay = OverlayState::collect(&state);
+            let Ok(json) = serde_json::to_string(&overlay) else { continue };
+            if let Some(feed) = feed.as_mut() {
+                feed.accept(&json);
+            }
+            // Only changes go out; new browser sources got the current frame on connect
+            if last.as_ref() == Some(&overlay) {
+                continue;
+            }
+            if let Err(e) = app.emit(OVERLAY_EVENT, &overlay) {
+                log::warn!("Failed to emit {}: {}", OVERLAY_EVENT, e);
+            }
+            if let Some(feed) = feed.as_mut() {
+                feed.broadcast(&json);
+            }
+            last = Some(overlay);
+        }
+    });
+}
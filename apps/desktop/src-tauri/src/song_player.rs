@@ -1,15 +1,107 @@
+use audio::{AccompanimentBar, Accompanist, BarContext, GroovePattern};
+use serde::{Deserialize, Serialize};
 use song::*;
 use std::sync::{Arc, Mutex};
 use std::time::Instant;
 
+use crate::state::unix_seconds;
+
+/// How song playback waits for the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PracticeMode {
+    /// The song plays on in time
+    #[default]
+    Normal,
+    /// The transport stops at every chord until it is played
+    WaitForNote,
+}
+
+/// Seed of the accompaniment, so every playthrough of a song sounds the same
+const ACCOMPANIMENT_SEED: u64 = 0x6d49_7479;
+/// Bass root when no chord is playing (E2)
+const DEFAULT_BASS_ROOT: u8 = 40;
+
+/// Accompaniment generated for the bar that just started
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduledBar {
+    pub start_beat: f64,
+    /// Length of the bar in beats (the bar's sixteen steps are spread over it)
+    pub beats: f64,
+    pub bar: AccompanimentBar,
+}
+
+/// A live change that can wait for the next bar of the transport
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub enum LiveChange {
+    Genre(String),
+    ChordMapping { genre: String, key_root: String, mode: String },
+    Instrument(String),
+}
+
+/// Where the backing track and transport-following drums belong
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TransportSync {
+    pub beat: f64,
+    /// Time of the beat in the backing track (None = the chart has none)
+    pub backing_track_seconds: Option<f64>,
+    /// Drum tempo: the song tempo at the playback speed
+    pub bpm: f64,
+    pub speed: f64,
+    /// Both hold while paused or waiting for a note
+    pub playing: bool,
+}
+
 /// Song playback state manager
 pub struct SongPlayer {
+    /// Chart being played (reduced to the difficulty)
     chart: Option<SongChart>,
+    /// Chart as written, which edits apply to
+    source_chart: Option<SongChart>,
+    /// The chart was edited since it was loaded or saved
+    unsaved_edits: bool,
+    difficulty: Difficulty,
     transport: Transport,
+    metronome: Metronome,
+    tap_tempo: TapTempo,
     hit_detector: HitDetector,
     scorer: Scorer,
     instrument_resolver: InstrumentResolver,
     user_override_instrument: Option<InstrumentRef>,
+    accompanist: Accompanist,
+    accompaniment_enabled: bool,
+    /// Bar the accompaniment was last generated for
+    accompaniment_bar: Option<i64>,
+    change_quantize: QuantizeGrid,
+    pending_changes: ChangeScheduler<LiveChange>,
+    /// Performance being recorded into a new chart
+    recorder: Option<ChartRecorder>,
+    /// Count-in beats (kept apart from the metronome, which the UI polls)
+    count_in_clicks: Metronome,
+    /// Count-in bars replacing the chart's own (None = as charted)
+    count_in_override: Option<u32>,
+    loop_scoring: LoopScoring,
+    /// Loop passes already handled (hit detection reset, score kept or reset)
+    loop_passes: u32,
+    /// Playback jumped back to the loop start since `take_loop_jump`
+    loop_jumped: bool,
+    /// Hits and misses when the current loop pass started
+    loop_pass_start: (u32, u32),
+    /// Speed ramp over the loop passes
+    speed_trainer: Option<SpeedTrainer>,
+    /// Outcome of the last finished pass, until `take_speed_trainer_pass`
+    speed_trainer_pass: Option<SpeedTrainerPass>,
+    practice_mode: PracticeMode,
+    /// Waiting for a note as of the last `poll_note_wait`
+    note_waiting: bool,
+    hit_windows: HitWindows,
+    /// Latency taken off every strum (ms)
+    input_offset_ms: f64,
+    /// Latency calibration in progress, with its start (ns since epoch)
+    calibration: Option<(u64, LatencyCalibration)>,
+    star_power: StarPower,
+    /// The end of the song was reached (the play-through is over)
+    finished: bool,
 }
 
 impl SongPlayer {
@@ -17,40 +109,217 @@ impl SongPlayer {
         let global_default = ("virtual".to_string(), "Basic Guitar".to_string());
         Self {
             chart: None,
+            source_chart: None,
+            unsaved_edits: false,
+            difficulty: Difficulty::default(),
             transport: Transport::new(120.0, [4, 4], 2),
+            metronome: Metronome::new(),
+            tap_tempo: TapTempo::default(),
             hit_detector: HitDetector::new(&std::collections::HashMap::new()),
             scorer: Scorer::new(),
             instrument_resolver: InstrumentResolver::new(available_instruments, global_default),
             user_override_instrument: None,
+            accompanist: Accompanist::new(GroovePattern::rock(), ACCOMPANIMENT_SEED),
+            accompaniment_enabled: false,
+            accompaniment_bar: None,
+            change_quantize: QuantizeGrid::Off,
+            pending_changes: ChangeScheduler::new(),
+            recorder: None,
+            count_in_clicks: Metronome::new(),
+            count_in_override: None,
+            loop_scoring: LoopScoring::default(),
+            loop_passes: 0,
+            loop_jumped: false,
+            loop_pass_start: (0, 0),
+            speed_trainer: None,
+            speed_trainer_pass: None,
+            practice_mode: PracticeMode::default(),
+            note_waiting: false,
+            hit_windows: HitWindows::default(),
+            input_offset_ms: 0.0,
+            calibration: None,
+            star_power: StarPower::new(&[], &[]),
+            finished: false,
         }
     }
 
     /// Load a song chart
     pub fn load_chart(&mut self, json: &str) -> anyhow::Result<()> {
-        let chart = SongChart::from_json(json)?;
+        let source_chart = SongChart::from_json(json)?;
+        let chart = reduce_chart(&source_chart, self.difficulty)?;
         
         // Initialize transport from chart
         self.transport = Transport::new(
             chart.clock.bpm,
             chart.clock.time_sig,
-            chart.clock.count_in_bars,
+            self.count_in_override.unwrap_or(chart.clock.count_in_bars),
         );
+        self.transport.meter = chart.clock.meter()?;
+        // Wait at the start of the count-in
+        self.transport.stop();
+        self.metronome.reset();
+        self.count_in_clicks.reset();
+        self.reset_accompaniment();
+        self.loop_passes = 0;
+        self.speed_trainer = None;
+        self.finished = false;
 
         // Initialize hit detector with chart mappings
-        self.hit_detector = HitDetector::new(&chart.mapping.chords);
+        self.hit_detector = self.new_hit_detector(&chart);
+        self.star_power = new_star_power(&chart);
 
         // Reset scoring
         self.scorer.reset();
 
         self.chart = Some(chart);
+        self.source_chart = Some(source_chart);
+        self.unsaved_edits = false;
         Ok(())
     }
 
+    fn new_hit_detector(&self, chart: &SongChart) -> HitDetector {
+        let mut hit_detector = HitDetector::new(&chart.mapping.chords);
+        hit_detector.set_windows(self.hit_windows);
+        hit_detector.set_window_scale(self.difficulty.hit_window_scale());
+        hit_detector.set_input_offset_ms(self.input_offset_ms);
+        hit_detector
+    }
+
+    /// Timing windows for hit detection (before the difficulty widens them)
+    pub fn set_hit_windows(&mut self, windows: HitWindows) -> anyhow::Result<()> {
+        windows.validate()?;
+        self.hit_windows = windows;
+        self.hit_detector.set_windows(windows);
+        Ok(())
+    }
+
+    pub fn hit_windows(&self) -> HitWindows {
+        self.hit_windows
+    }
+
+    /// Latency to take off every strum (ms, positive = strums register late)
+    pub fn set_input_offset_ms(&mut self, offset_ms: f64) {
+        self.input_offset_ms = offset_ms;
+        self.hit_detector.set_input_offset_ms(offset_ms);
+    }
+
+    pub fn input_offset_ms(&self) -> f64 {
+        self.input_offset_ms
+    }
+
+    /// Start measuring strums against a click track starting at `started_nanos`
+    pub fn start_calibration(&mut self, calibration: LatencyCalibration, started_nanos: u64) {
+        self.calibration = Some((started_nanos, calibration));
+    }
+
+    pub fn is_calibrating(&self) -> bool {
+        self.calibration.is_some()
+    }
+
+    /// Feed the strum bar to the calibration (`at_nanos` = input time)
+    pub fn calibrate_input(&mut self, at_nanos: u64, strum: bool) {
+        if let Some((started_nanos, calibration)) = &mut self.calibration {
+            let at_ms = at_nanos.saturating_sub(*started_nanos) as f64 / 1_000_000.0;
+            calibration.process(at_ms, strum);
+        }
+    }
+
+    /// End the calibration, returning the measured offset and the strums it is based on
+    pub fn finish_calibration(&mut self) -> anyhow::Result<(f64, usize)> {
+        let (_, calibration) = self.calibration.take().ok_or_else(|| anyhow::anyhow!("No calibration running"))?;
+        Ok((calibration.result()?, calibration.strum_count()))
+    }
+
+    pub fn cancel_calibration(&mut self) {
+        self.calibration = None;
+    }
+
+    /// Play songs at a difficulty; the loaded song starts over at it
+    pub fn set_difficulty(&mut self, difficulty: Difficulty) -> anyhow::Result<()> {
+        if let Some(source_chart) = &self.source_chart {
+            let chart = reduce_chart(source_chart, difficulty)?;
+            self.difficulty = difficulty;
+            self.hit_detector = self.new_hit_detector(&chart);
+            self.star_power = new_star_power(&chart);
+            self.chart = Some(chart);
+            self.stop();
+        } else {
+            self.difficulty = difficulty;
+        }
+        Ok(())
+    }
+
+    pub fn difficulty(&self) -> Difficulty {
+        self.difficulty
+    }
+
+    /// Stop and leave song mode (back to free play)
+    pub fn unload_chart(&mut self) {
+        self.stop();
+        self.cancel_pending_changes();
+        self.chart = None;
+        self.source_chart = None;
+    }
+
     /// Get current chart
     pub fn get_chart(&self) -> Option<&SongChart> {
         self.chart.as_ref()
     }
 
+    /// Chart as written (before reducing it to the difficulty)
+    pub fn get_source_chart(&self) -> Option<&SongChart> {
+        self.source_chart.as_ref()
+    }
+
+    /// Edit the loaded chart in place, keeping the transport position. The
+    /// chart is left as it was if the edit fails or leaves it invalid. Edits
+    /// apply to the chart as written; the difficulty is derived again.
+    pub fn edit_chart<R>(&mut self, edit: impl FnOnce(&mut SongChart) -> anyhow::Result<R>) -> anyhow::Result<R> {
+        let source_chart = self.source_chart.as_ref().ok_or_else(|| anyhow::anyhow!("No song loaded"))?;
+        let mut edited = source_chart.clone();
+        let result = edit(&mut edited)?;
+        edited.validate()?;
+        let played = reduce_chart(&edited, self.difficulty)?;
+
+        self.transport.set_bpm(edited.clock.bpm);
+        self.transport.time_sig = edited.clock.time_sig;
+        self.transport.set_count_in_bars(self.count_in_override.unwrap_or(edited.clock.count_in_bars));
+        self.transport.meter = edited.clock.meter()?;
+        self.hit_detector = self.new_hit_detector(&played);
+        self.star_power = new_star_power(&played);
+        self.chart = Some(played);
+        self.source_chart = Some(edited);
+        self.unsaved_edits = true;
+        Ok(result)
+    }
+
+    /// The loaded chart was saved as it is
+    pub fn mark_chart_saved(&mut self) {
+        self.unsaved_edits = false;
+    }
+
+    /// The session to autosave (None = no song loaded)
+    pub fn session_snapshot(&mut self) -> Option<SessionSnapshot> {
+        let chart = self.source_chart.clone()?;
+        Some(SessionSnapshot {
+            saved_at: unix_seconds(),
+            chart,
+            unsaved_edits: self.unsaved_edits,
+            difficulty: self.difficulty,
+            beat: self.transport.get_current_beat(),
+            speed: self.transport.speed_multiplier,
+            score: self.scorer.snapshot(),
+        })
+    }
+
+    /// Put the loaded chart back where a recovered session was (paused)
+    pub fn resume_session(&mut self, snapshot: &SessionSnapshot) {
+        self.seek(snapshot.beat);
+        self.set_speed(snapshot.speed);
+        self.scorer.restore(&snapshot.score);
+        self.unsaved_edits = snapshot.unsaved_edits;
+    }
+
     /// Play
     pub fn play(&mut self) {
         self.transport.play();
@@ -58,19 +327,34 @@ impl SongPlayer {
 
     /// Pause
     pub fn pause(&mut self) {
+        self.release_recorded_chord();
         self.transport.pause();
     }
 
     /// Stop
     pub fn stop(&mut self) {
+        self.release_recorded_chord();
         self.transport.stop();
+        self.metronome.reset();
+        self.count_in_clicks.reset();
+        self.reset_accompaniment();
         self.hit_detector.reset();
         self.scorer.reset();
+        self.star_power.reset();
+        self.finished = false;
     }
 
     /// Seek to beat
     pub fn seek(&mut self, beat: f64) {
         self.transport.seek(beat);
+        self.finished = false;
+        self.metronome.reset();
+        self.count_in_clicks.reset();
+        self.accompaniment_bar = None;
+        // Queued changes wait for the next grid line from the new position
+        if let Some(due_beat) = self.change_quantize.next_boundary(&self.transport.meter, beat) {
+            self.pending_changes.retime(due_beat);
+        }
     }
 
     /// Set speed
@@ -80,17 +364,301 @@ impl SongPlayer {
 
     /// Get current beat
     pub fn get_current_beat(&mut self) -> f64 {
-        self.transport.get_current_beat()
+        self.current_beat()
+    }
+
+    /// Sync for the backing track and drums (after play, pause, seek, speed
+    /// or tempo changes)
+    pub fn transport_sync(&mut self) -> TransportSync {
+        let beat = self.current_beat();
+        let transport = &self.transport;
+        TransportSync {
+            beat,
+            backing_track_seconds: self.chart.as_ref()
+                .and_then(|chart| chart.meta.backing_track.as_ref())
+                .map(|backing_track| backing_track.offset_seconds + transport.song_seconds(beat)),
+            bpm: transport.bpm * transport.speed_multiplier,
+            speed: transport.speed_multiplier,
+            playing: transport.is_playing && !transport.is_waiting(),
+        }
+    }
+
+    /// Current beat, starting a new pass when the practice loop jumped back
+    fn current_beat(&mut self) -> f64 {
+        let beat = self.transport.get_current_beat();
+        let passes = self.transport.loop_passes();
+        if passes != self.loop_passes {
+            let jumped = passes > self.loop_passes;
+            self.loop_jumped |= jumped;
+            self.loop_passes = passes;
+            if jumped {
+                self.finish_speed_trainer_pass();
+            }
+            // The looped notes can be hit again
+            self.metronome.reset();
+            self.accompaniment_bar = None;
+            self.hit_detector.reset();
+            if self.loop_scoring == LoopScoring::Reset {
+                self.scorer.reset();
+                self.star_power.reset();
+            } else {
+                self.star_power.reset_phrases();
+            }
+            self.loop_pass_start = (self.scorer.hits, self.scorer.misses);
+        }
+        self.update_note_wait(beat);
+        beat
+    }
+
+    /// In wait-for-note practice, stop the transport at the next chord that
+    /// wasn't played yet (within the practice loop, if one is set)
+    fn update_note_wait(&mut self, beat: f64) {
+        if self.practice_mode != PracticeMode::WaitForNote {
+            return;
+        }
+        let end_beat = self.transport.loop_region()
+            .filter(|region| region.contains(beat))
+            .map_or(f64::INFINITY, |region| region.end_beat);
+        let next_note = self.chart.as_ref().and_then(|chart| {
+            chart.get_chord_events_in_range(beat, end_beat)
+                .into_iter()
+                .filter(|event| self.hit_detector.is_pending(event))
+                .map(|event| event.beat)
+                .min_by(f64::total_cmp)
+        });
+        self.transport.wait_at(next_note);
+    }
+
+    /// Play on in time, or stop at every chord until it is played
+    pub fn set_practice_mode(&mut self, mode: PracticeMode) {
+        self.practice_mode = mode;
+        match mode {
+            PracticeMode::Normal => self.transport.wait_at(None),
+            PracticeMode::WaitForNote => {
+                self.current_beat();
+            }
+        }
+    }
+
+    pub fn practice_mode(&self) -> PracticeMode {
+        self.practice_mode
+    }
+
+    /// Whether the transport is waiting for a note, if that changed since the last call
+    pub fn poll_note_wait(&mut self) -> Option<bool> {
+        self.current_beat();
+        let waiting = self.transport.is_waiting();
+        (waiting != self.note_waiting).then(|| {
+            self.note_waiting = waiting;
+            waiting
+        })
+    }
+
+    /// Raise the speed if the pass that just ended was played well enough
+    fn finish_speed_trainer_pass(&mut self) {
+        let Some(trainer) = self.speed_trainer.as_mut() else {
+            return;
+        };
+        let (hits, misses) = self.loop_pass_start;
+        let pass = trainer.finish_pass(self.scorer.hits.saturating_sub(hits), self.scorer.misses.saturating_sub(misses));
+        self.transport.set_speed(pass.speed);
+        self.speed_trainer_pass = Some(pass);
+    }
+
+    /// Practice a section over and over; the score keeps adding up or
+    /// starts again on every pass. Playback outside the loop moves to its start.
+    pub fn set_loop(&mut self, start_beat: f64, end_beat: f64, scoring: LoopScoring) -> anyhow::Result<()> {
+        self.transport.set_loop(start_beat, end_beat)?;
+        self.loop_scoring = scoring;
+        self.loop_passes = 0;
+        self.loop_pass_start = (self.scorer.hits, self.scorer.misses);
+        let current_beat = self.current_beat();
+        if !self.transport.loop_region().is_some_and(|region| region.contains(current_beat)) {
+            self.seek(start_beat);
+            self.hit_detector.reset();
+        }
+        Ok(())
+    }
+
+    /// Stop looping (playback carries on from where it is)
+    pub fn clear_loop(&mut self) {
+        self.transport.clear_loop();
+        self.loop_passes = 0;
+        self.speed_trainer = None;
+    }
+
+    /// Ramp the practice loop up from the start speed, a step for every pass
+    /// played accurately enough. Playback goes back to the loop start.
+    pub fn start_speed_trainer(&mut self, settings: SpeedTrainerSettings) -> anyhow::Result<()> {
+        let Some(region) = self.transport.loop_region() else {
+            anyhow::bail!("Set a practice loop before starting the speed trainer");
+        };
+        let trainer = SpeedTrainer::new(settings)?;
+        self.transport.set_speed(trainer.speed());
+        self.speed_trainer = Some(trainer);
+        self.speed_trainer_pass = None;
+        self.seek(region.start_beat);
+        self.hit_detector.reset();
+        self.loop_pass_start = (self.scorer.hits, self.scorer.misses);
+        Ok(())
+    }
+
+    /// Stop ramping (the loop carries on at the speed reached)
+    pub fn stop_speed_trainer(&mut self) {
+        self.speed_trainer = None;
+    }
+
+    /// Speed trainer ramp and current speed, while it runs
+    pub fn speed_trainer(&self) -> Option<&SpeedTrainer> {
+        self.speed_trainer.as_ref()
+    }
+
+    /// Outcome of the last speed trainer pass, if one finished since the last call
+    pub fn take_speed_trainer_pass(&mut self) -> Option<SpeedTrainerPass> {
+        self.speed_trainer_pass.take()
+    }
+
+    /// Loop passes, if playback jumped back to the loop start since the last call
+    pub fn take_loop_jump(&mut self) -> Option<u32> {
+        self.current_beat();
+        std::mem::take(&mut self.loop_jumped).then_some(self.loop_passes)
+    }
+
+    /// How the score treats practice loop passes
+    pub fn loop_scoring(&self) -> LoopScoring {
+        self.loop_scoring
+    }
+
+    /// Metronome click for a beat crossed since the last poll (while playing)
+    pub fn poll_metronome(&mut self) -> Option<MetronomeClick> {
+        if !self.transport.is_playing {
+            return None;
+        }
+        let current_beat = self.current_beat();
+        self.metronome.tick(&self.transport.meter, current_beat)
+    }
+
+    /// The play-through, once playback passes the last note of the song.
+    /// Practice loops never finish, nor does a song where nothing was played.
+    pub fn poll_song_end(&mut self) -> Option<PlayRecord> {
+        if self.finished || !self.transport.is_playing || self.transport.loop_region().is_some() {
+            return None;
+        }
+        let current_beat = self.current_beat();
+        let chart = self.chart.as_ref()?;
+        if current_beat < chart.total_beats() {
+            return None;
+        }
+        self.finished = true;
+        if self.scorer.hits + self.scorer.misses == 0 {
+            return None;
+        }
+        Some(PlayRecord::new(chart, self.difficulty, &self.scorer, unix_seconds()))
+    }
+
+    /// Count-in beat crossed since the last poll (while playing before beat 0)
+    pub fn poll_count_in(&mut self) -> Option<CountInBeat> {
+        if !self.transport.is_playing {
+            return None;
+        }
+        let current_beat = self.current_beat();
+        if current_beat >= 0.0 {
+            return None;
+        }
+        self.count_in_clicks.tick(&self.transport.meter, current_beat)
+            .and_then(CountInBeat::from_click)
+    }
+
+    /// Override the count-in length of every song (None = use each chart's)
+    pub fn set_count_in_bars(&mut self, bars: Option<u32>) {
+        self.count_in_override = bars;
+        let charted = self.chart.as_ref().map(|chart| chart.clock.count_in_bars);
+        if let Some(bars) = bars.or(charted) {
+            self.transport.set_count_in_bars(bars);
+        }
+    }
+
+    /// Accompaniment for a bar started since the last poll, with a fill
+    /// before each section change and a crash where a section starts
+    pub fn poll_accompaniment(&mut self) -> Option<ScheduledBar> {
+        if !self.accompaniment_enabled || !self.transport.is_playing {
+            return None;
+        }
+        let current_beat = self.current_beat();
+        let chart = self.chart.as_ref()?;
+        if current_beat < 0.0 {
+            // Count-in
+            return None;
+        }
+        let beats = self.transport.meter.beats_per_bar() as f64;
+        let bar = (current_beat / beats).floor() as i64;
+        if self.accompaniment_bar == Some(bar) {
+            return None;
+        }
+        self.accompaniment_bar = Some(bar);
+
+        let start_beat = bar as f64 * beats;
+        let context = BarContext::at(chart.sections.iter().map(|section| section.from_beat), start_beat, beats);
+        let events = chart.get_all_chord_events();
+        let root = events.iter()
+            .find(|event| event.beat <= start_beat && start_beat < event.beat + event.dur)
+            .or_else(|| events.iter().find(|event| event.beat >= start_beat && event.beat < start_beat + beats))
+            .and_then(|event| chord_root(&event.chord))
+            .unwrap_or(DEFAULT_BASS_ROOT);
+        Some(ScheduledBar { start_beat, beats, bar: self.accompanist.next_bar(root, context) })
+    }
+
+    /// Play the drum and bass accompaniment while the song runs
+    pub fn set_accompaniment(&mut self, enabled: bool, intensity: Option<f32>) {
+        self.accompaniment_enabled = enabled;
+        if let Some(intensity) = intensity {
+            self.accompanist.set_intensity(intensity);
+        }
+        self.reset_accompaniment();
+    }
+
+    /// Start the accompaniment over from its first bar
+    fn reset_accompaniment(&mut self) {
+        self.accompanist.reseed(ACCOMPANIMENT_SEED);
+        self.accompaniment_bar = None;
+    }
+
+    /// Override the accent grouping of the loaded song
+    pub fn set_accent_grouping(&mut self, grouping: Vec<u32>) -> anyhow::Result<()> {
+        self.transport.set_accent_grouping(grouping)
+    }
+
+    /// Enable or disable metronome clicks
+    pub fn set_metronome_enabled(&mut self, enabled: bool) {
+        self.metronome.enabled = enabled;
+    }
+
+    /// Register a tap and apply the tapped tempo to the transport
+    pub fn tap_tempo(&mut self, at: Instant) -> Option<f64> {
+        let bpm = self.tap_tempo.tap(at)?;
+        self.transport.set_bpm(bpm);
+        Some(bpm)
+    }
+
+    /// Set how tapped tempos are rounded
+    pub fn set_tap_tempo_rounding(&mut self, rounding: TempoRounding) {
+        self.tap_tempo.rounding = rounding;
+    }
+
+    /// Get tap tempo state
+    pub fn get_tap_tempo(&self) -> &TapTempo {
+        &self.tap_tempo
     }
 
     /// Check strum
     pub fn check_strum(&mut self, pressed_frets: Vec<String>) -> Option<HitResult> {
+        let current_beat = self.current_beat();
         let chart = self.chart.as_ref()?;
-        let current_beat = self.transport.get_current_beat();
+        // Windows are in ms, so they follow tempo and practice speed
+        self.hit_detector.set_tempo(self.transport.bpm, self.transport.speed_multiplier);
         
         // Get events in window
-        let window_start = current_beat - HIT_WINDOW;
-        let window_end = current_beat + HIT_WINDOW;
+        let (window_start, window_end) = self.hit_detector.strum_range(current_beat);
         let events = chart.get_chord_events_in_range(window_start, window_end);
 
         let result = self.hit_detector.check_strum(
@@ -100,17 +668,45 @@ impl SongPlayer {
         );
 
         // Update scoring
+        let bonus = if self.star_power.is_active(current_beat) { STAR_POWER_MULTIPLIER } else { 1 };
+        self.scorer.set_bonus_multiplier(bonus);
         self.scorer.register_hit(&result);
+        if self.star_power.register(current_beat, &result) {
+            log::info!("⭐ Star power phrase complete");
+        }
+        // A played note lets the transport move on to the next one
+        if matches!(result, HitResult::Hit { .. }) {
+            self.update_note_wait(current_beat);
+        }
 
         Some(result)
     }
 
+    /// Switch star power on (while the song plays and the meter is full enough)
+    pub fn activate_star_power(&mut self) -> Option<StarPowerActivation> {
+        if !self.transport.is_playing || self.chart.is_none() {
+            return None;
+        }
+        let current_beat = self.current_beat();
+        self.star_power.activate(current_beat)
+    }
+
+    pub fn star_power_state(&mut self) -> StarPowerState {
+        let current_beat = self.current_beat();
+        self.star_power.state(current_beat)
+    }
+
     /// Update sustain
     pub fn update_sustain(&mut self, pressed_frets: Vec<String>) -> bool {
-        let current_beat = self.transport.get_current_beat();
+        let current_beat = self.current_beat();
         self.hit_detector.update_sustain(current_beat, &pressed_frets)
     }
 
+    /// Keep the combo going through misses (the chart plays and highlights as usual)
+    pub fn set_no_fail(&mut self, enabled: bool) {
+        self.scorer.set_no_fail(enabled);
+    }
+
     /// Get score
     pub fn get_score(&self) -> &Scorer {
         &self.scorer
@@ -136,8 +732,210 @@ impl SongPlayer {
         ))
     }
 
+    /// Instrument to load with the loaded song: the user's pick when the chart
+    /// allows one, else the one its meta names (None = no preference)
+    pub fn song_instrument(&self) -> Option<String> {
+        let chart = self.chart.as_ref()?;
+        if self.user_override_instrument.is_some() && chart.playback.allow_user_override_instrument {
+            return self.get_resolved_instrument().map(|resolved| resolved.label);
+        }
+        chart.meta.instrument.clone()
+    }
+
     /// Get available instruments
     pub fn get_available_instruments(&self) -> &[(String, String)] {
         self.instrument_resolver.get_available_instruments()
     }
+
+    /// Defer live changes to the next beat or bar while playing
+    pub fn set_change_quantize(&mut self, grid: QuantizeGrid) {
+        self.change_quantize = grid;
+    }
+
+    pub fn change_quantize(&self) -> QuantizeGrid {
+        self.change_quantize
+    }
+
+    /// Queue a change for the next grid line. None means apply it now
+    /// (quantizing is off or the transport isn't running).
+    pub fn defer_change(&mut self, change: LiveChange) -> Option<f64> {
+        if !self.transport.is_playing {
+            return None;
+        }
+        let current_beat = self.current_beat();
+        let due_beat = self.change_quantize.next_boundary(&self.transport.meter, current_beat)?;
+        self.pending_changes.schedule(change, due_beat);
+        Some(due_beat)
+    }
+
+    /// Queued changes whose beat has come (all of them once the transport stops)
+    pub fn take_due_changes(&mut self) -> Vec<LiveChange> {
+        if !self.transport.is_playing {
+            return self.pending_changes.take_all();
+        }
+        let current_beat = self.current_beat();
+        self.pending_changes.take_due(current_beat)
+    }
+
+    pub fn pending_changes(&self) -> &[ScheduledChange<LiveChange>] {
+        self.pending_changes.pending()
+    }
+
+    pub fn cancel_pending_changes(&mut self) {
+        self.pending_changes.clear();
+    }
+
+    /// Start recording what is played while the transport runs
+    pub fn start_recording(&mut self, grid: f64) {
+        self.recorder = Some(ChartRecorder::new(grid));
+    }
+
+    pub fn recorder(&self) -> Option<&ChartRecorder> {
+        self.recorder.as_ref()
+    }
+
+    /// Feed the guitar to the recorder (ignored unless recording past the count-in)
+    pub fn record_input(&mut self, pressed_frets: &[String], strum: bool, chord: Option<&str>) {
+        if self.recorder.is_none() || !self.transport.is_playing {
+            return;
+        }
+        let current_beat = self.current_beat();
+        if current_beat < 0.0 {
+            return;
+        }
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.process(current_beat, pressed_frets, strum, chord);
+        }
+    }
+
+    /// Stop recording and build the chart at the transport's tempo and meter
+    pub fn stop_recording(&mut self, title: &str, artist: &str) -> anyhow::Result<SongChart> {
+        let recorder = self.recorder.take().ok_or_else(|| anyhow::anyhow!("Not recording"))?;
+        let current_beat = self.current_beat();
+        recorder.finish(current_beat, title, artist, self.transport.bpm, self.transport.time_sig)
+    }
+
+    pub fn cancel_recording(&mut self) {
+        self.recorder = None;
+    }
+
+    /// End a recorded chord where the transport stops
+    fn release_recorded_chord(&mut self) {
+        if self.recorder.is_none() || !self.transport.is_playing {
+            return;
+        }
+        let current_beat = self.current_beat();
+        if let Some(recorder) = self.recorder.as_mut() {
+            recorder.process(current_beat, &[], false, None);
+        }
+    }
+}
+
+/// Bass root (MIDI note from E2 up) of a chord symbol like "F#m7"
+fn chord_root(chord: &str) -> Option<u8> {
+    let mut chars = chord.chars();
+    let semitone: i8 = match chars.next()?.to_ascii_uppercase() {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return None,
+    };
+    let semitone = match chars.next() {
+        Some('#') => semitone + 1,
+        Some('b') => semitone - 1,
+        _ => semitone,
+    };
+    Some(DEFAULT_BASS_ROOT + (semitone - 4).rem_euclid(12) as u8)
+}
+
+/// Star power for a chart's phrases and notes
+fn new_star_power(chart: &SongChart) -> StarPower {
+    let note_beats: Vec<f64> = chart.get_all_chord_events().iter().map(|event| event.beat).collect();
+    StarPower::new(&chart.star_phrases, &note_beats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    /// A 4/4 chart with one C chord per bar for four bars
+    fn player(bpm: f64, backing_track: Option<BackingTrackRef>) -> SongPlayer {
+        let mut chart = SongChart::new("Test", "Me", bpm, [4, 4]).unwrap();
+        chart.meta.backing_track = backing_track;
+        chart.mapping.chords.insert("C".to_string(), ChordMapping { frets: vec!["GREEN".to_string()] });
+        chart.lanes = vec![Lane {
+            name: DEFAULT_LANE.to_string(),
+            events: (0..4).map(|bar| ChordEvent { beat: bar as f64 * 4.0, dur: 4.0, chord: "C".to_string(), section: None }).collect(),
+        }];
+        let mut player = SongPlayer::new(Vec::new());
+        player.load_chart(&serde_json::to_string(&chart).unwrap()).unwrap();
+        player
+    }
+
+    #[test]
+    fn test_mid_bar_change_applies_at_next_downbeat() {
+        // 240 BPM: a beat every 250 ms
+        let mut player = player(240.0, None);
+        player.set_change_quantize(QuantizeGrid::Bar);
+        player.play();
+        player.seek(1.5);
+        assert_eq!(player.defer_change(LiveChange::Genre("punk".to_string())), Some(4.0));
+        assert!(player.take_due_changes().is_empty());
+
+        // Seeking later in the bar keeps the downbeat; the change waits for it
+        player.seek(3.5);
+        assert_eq!(player.pending_changes()[0].due_beat, 4.0);
+        assert!(player.take_due_changes().is_empty());
+        std::thread::sleep(Duration::from_millis(200));
+        assert!(matches!(player.take_due_changes().as_slice(), [LiveChange::Genre(genre)] if genre == "punk"));
+
+        // Seeking past the downbeat moves a queued change to the next one
+        player.defer_change(LiveChange::Genre("rock".to_string()));
+        player.seek(9.0);
+        assert_eq!(player.pending_changes()[0].due_beat, 12.0);
+    }
+
+    #[test]
+    fn test_seek_speed_and_pause_sync_backing_track_and_drums() {
+        let mut player = player(120.0, Some(BackingTrackRef { path: "song.ogg".to_string(), offset_seconds: 1.5 }));
+
+        player.seek(8.0);
+        let sync = player.transport_sync();
+        assert_eq!((sync.beat, sync.backing_track_seconds, sync.playing), (8.0, Some(5.5), false));
+
+        // The backing track plays slower, the drums follow the slower tempo
+        player.set_speed(0.5);
+        let sync = player.transport_sync();
+        assert_eq!((sync.bpm, sync.speed, sync.backing_track_seconds), (60.0, 0.5, Some(5.5)));
+
+        player.play();
+        let sync = player.transport_sync();
+        assert!(sync.playing);
+        assert!(sync.beat >= 8.0 && sync.beat < 8.5);
+
+        player.pause();
+        let paused = player.transport_sync();
+        assert!(!paused.playing);
+        let seconds = paused.backing_track_seconds.unwrap();
+        assert!((seconds - (1.5 + paused.beat * 0.5)).abs() < 1e-9);
+        assert_eq!(player.transport_sync(), paused);
+    }
+
+    #[test]
+    fn test_backing_track_and_drums_hold_while_waiting_for_a_note() {
+        let mut player = player(120.0, None);
+        player.set_practice_mode(PracticeMode::WaitForNote);
+        player.seek(0.0);
+        player.play();
+        let sync = player.transport_sync();
+        assert_eq!((sync.beat, sync.backing_track_seconds, sync.playing), (0.0, None, false));
+
+        player.set_practice_mode(PracticeMode::Normal);
+        assert!(player.transport_sync().playing);
+    }
 }
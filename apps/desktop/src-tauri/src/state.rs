@@ -1,27 +1,48 @@
 use anyhow::Result;
-use audio::{AudioOutput, AudioStats};
+use audio::{AudioDeviceInfo, AudioMeters, AudioOutput, AudioStats, BackingTrack, GroovePattern, InputMonitorStats, InstrumentLayer, LatencyStats, MixerChannel, RockOutEffect, SoundFontLoaded, Temperament, TuningTable};
 #[cfg(feature = "soundfont")]
-use audio::{SoundFontInfo, InstrumentInfo, SoundFontInstrumentType as InstrumentType, SoundFontManager};
+use audio::{SoundFontInfo, InstrumentInfo, SoundFontInstrumentType as InstrumentType, SoundFontManager, load_sample_instrument};
+#[cfg(feature = "soundfont")]
+use audio::{PreviewOptions, render_preview_wav};
+use audio::{BounceChord, BounceOptions, PreviewSource, render_bounce_wav};
 use audio::synth::InstrumentType as SynthInstrumentType;
-use config::AppConfig;
-use controller::{PerformanceController, ControllerStateSnapshot, ControllerState, ControlId, MappingProfileManager};
-use mapping::{LegacyGenre as Genre, Mapper, MusicEvent};
-use std::sync::{Arc, Mutex};
+use config::{AppConfig, AppPaths, AudioConfig, ConfigProfile, ConfigProfileStore, HitWindowConfig, InputMonitorConfig, MigrationReport, MixerConfig, OverlayConfig, SongConfig, SpeedTrainerConfig, TemperamentConfig};
+use controller::high_performance::timestamp_nanos;
+use controller::{PerformanceController, ControllerBackend, ControllerStateSnapshot, ControllerState, ControlId, MappingProfileManager, MAX_PLAYERS};
+use controller::{AppAction, ControllerMacro, MacroAction, MacroPlayer, MacroRecorder, MenuNavigator, NavAction, RumbleCue, TiltTrigger};
+use mapping::{BendTarget, DynamicsMode, LegacyGenre as Genre, Mapper, MusicEvent, PalmMuteTrigger, TiltAction, WhammyMode, WhammyPullEffect, ROCK_OUT_CC};
+use mapping::{Capo, ChordResolver, FretButton, FretRow, HarmonicMapper, LayerDefaults, MapperPlugin, Mode, Note, PluginInfo, PluginRegistry, PresetLoader, TiltLayer};
+use mapping::transpose::transpose_root;
+use serde::Serialize;
+use song::{ChordTrainer, CountInBeat, Difficulty, FinishedPlay, HitResult, HitWindows, LibraryIndex, LibrarySong, PracticeReport, PracticeSession, QuantizeGrid, SessionSnapshot, Setlist, SongChart, SongFilters, SongKey, SongStats, SpeedTrainerPass, SpeedTrainerSettings, StarPowerActivation, TrainerChord, DEFAULT_RECORD_GRID};
+use std::sync::{mpsc, Arc, Mutex};
+use std::time::{Duration, Instant};
 use once_cell::sync::OnceCell;
-#[cfg(feature = "soundfont")]
 use std::path::PathBuf;
+#[cfg(feature = "soundfont")]
+use std::collections::HashMap;
 
 #[cfg(feature = "simulator")]
 use controller::simulator::ControllerSimulator;
 
-use crate::song_player::SongPlayer;
+use crate::commands::parse_note;
+use crate::jobs::JobManager;
+use crate::metrics::TimedLock;
+use crate::song_player::{LiveChange, PracticeMode, SongPlayer};
 
 // Global audio output - initialized once at startup
 static AUDIO: OnceCell<Mutex<AudioOutput>> = OnceCell::new();
 
-/// Initialize the global audio output
-pub fn init_audio(buffer_size: Option<u32>) -> Result<()> {
-    let audio = AudioOutput::new(buffer_size)?;
+/// Initialize the global audio output on the configured backend and device
+/// (falls back to the platform defaults)
+pub fn init_audio(buffer_size: Option<u32>, host_name: Option<&str>, device_name: Option<&str>) -> Result<()> {
+    let audio = AudioOutput::with_device(host_name, device_name, buffer_size).or_else(|e| {
+        if host_name.is_none() && device_name.is_none() {
+            return Err(e);
+        }
+        log::warn!("Configured audio backend/device unavailable ({}), using defaults", e);
+        AudioOutput::new(buffer_size)
+    })?;
     AUDIO.set(Mutex::new(audio))
         .map_err(|_| anyhow::anyhow!("Audio already initialized"))?;
     Ok(())
@@ -34,21 +55,125 @@ where
 {
     let audio_mutex = AUDIO.get()
         .ok_or_else(|| anyhow::anyhow!("Audio not initialized"))?;
-    let mut audio = audio_mutex.lock().unwrap();
+    let mut audio = audio_mutex.timed_lock();
     f(&mut *audio)
 }
 
+/// Capo position and the key it puts the guitar in ("transpose-changed" event)
+#[derive(Debug, Clone, Serialize)]
+pub struct TransposeInfo {
+    /// Semitones from the key chosen in the settings
+    pub offset: i8,
+    pub range: u8,
+    /// Key being played, e.g. "B major"
+    pub key: String,
+}
+
+/// Chord a mapper struck ("chord-played" event)
+#[derive(Debug, Clone, Serialize)]
+pub struct ChordPlayed {
+    pub player: usize,
+    /// Display name, e.g. "Am7"
+    pub name: String,
+    /// MIDI notes, low to high
+    pub notes: Vec<u8>,
+    /// Frets held for it ("GREEN", "RED", ...)
+    pub frets: Vec<String>,
+}
+
+/// Tilt layer settings (see `AppState::set_tilt_layer`)
+#[derive(Debug, Clone, Serialize)]
+pub struct TiltLayerInfo {
+    /// Tilt fades the layer in (`tilt_mode` "layer")
+    pub enabled: bool,
+    pub instrument: String,
+}
+
+/// Song of a setlist being played (sent with "setlist-advanced")
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetlistPosition {
+    pub name: String,
+    /// Entry playing (0-based)
+    pub index: usize,
+    pub count: usize,
+    /// Library file of the song
+    pub song: String,
+}
+
+/// A song session left behind by a crash (see `AppState::recover_last_session`)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RecoverableSession {
+    pub title: String,
+    pub artist: String,
+    /// Seconds since the Unix epoch
+    pub saved_at: u64,
+    pub unsaved_edits: bool,
+    pub beat: f64,
+    pub score: u32,
+}
+
+impl RecoverableSession {
+    fn of(snapshot: &SessionSnapshot) -> Self {
+        Self {
+            title: snapshot.chart.meta.title.clone(),
+            artist: snapshot.chart.meta.artist.clone(),
+            saved_at: snapshot.saved_at,
+            unsaved_edits: snapshot.unsaved_edits,
+            beat: snapshot.beat,
+            score: snapshot.score.score,
+        }
+    }
+}
+
 /// Shared application state
 pub struct AppState {
     pub config: Arc<Mutex<AppConfig>>,
     pub mapper: Arc<Mutex<Mapper>>,
+    /// Mappers for players 2..MAX_PLAYERS (player 1 uses `mapper`)
+    pub player_mappers: Vec<Arc<Mutex<Mapper>>>,
+    /// Genre preset pipeline, one per player slot
+    pub harmonic_mappers: Vec<Arc<Mutex<HarmonicMapper>>>,
+    /// Play through the legacy mappers instead of `harmonic_mappers`
+    legacy_mapper: Arc<Mutex<bool>>,
+    /// Mapper plugins found in `paths.plugins_dir`
+    plugins: Arc<Mutex<PluginRegistry>>,
+    /// Plugin playing in place of the chord mappers, one per player slot
+    player_plugins: Vec<Arc<Mutex<Option<Box<dyn MapperPlugin>>>>>,
+    /// Emit the stage display state (see `live_set`)
+    live_set_mode: Arc<Mutex<bool>>,
+    /// Guitar menu navigation (takes the guitar over while a menu is open)
+    menu_navigator: Arc<Mutex<MenuNavigator>>,
+    /// Receivers of menu navigation actions
+    menu_listeners: Arc<Mutex<Vec<mpsc::Sender<NavAction>>>>,
     pub controller: Arc<Mutex<PerformanceController>>, // New high-performance controller
     pub profile_manager: Arc<Mutex<MappingProfileManager>>,
     pub song_player: Arc<Mutex<SongPlayer>>,
+    /// High scores and play history (saved to `paths.stats_file`)
+    pub song_stats: Arc<Mutex<SongStats>>,
+    /// Session found in the recovery file at start, until it's recovered or discarded
+    recovered_session: Arc<Mutex<Option<SessionSnapshot>>>,
+    /// Listing entries of the song library (see `rescan_library`)
+    library: Arc<Mutex<LibraryIndex>>,
+    /// Practice analytics since the app started (or the session was reset)
+    practice: Arc<Mutex<PracticeSession>>,
+    /// Drum machine follows the song transport (instead of its own tempo)
+    drums_follow_transport: Arc<Mutex<bool>>,
+    /// Chord trainer session (None when not training)
+    pub trainer: Arc<Mutex<Option<ChordTrainer>>>,
+    /// Strum bar held on the last poll (a strum counts on the edge)
+    prev_strum: Arc<Mutex<bool>>,
+    /// Background jobs for long-running commands
+    pub jobs: Arc<JobManager>,
     
     #[cfg(feature = "soundfont")]
     pub soundfont_manager: Arc<Mutex<SoundFontManager>>,
     
+    /// Rendered instrument previews (WAV bytes) keyed by instrument name
+    #[cfg(feature = "soundfont")]
+    preview_cache: Arc<Mutex<HashMap<String, Vec<u8>>>>,
+    
     #[cfg(feature = "simulator")]
     pub simulator: Arc<Mutex<ControllerSimulator>>,
     
@@ -58,17 +183,75 @@ pub struct AppState {
     // Track previous button states for detecting button presses
     prev_dpad_left: Arc<Mutex<bool>>,
     prev_dpad_right: Arc<Mutex<bool>>,
+    prev_dpad_up: Arc<Mutex<bool>>,
+    prev_dpad_down: Arc<Mutex<bool>>,
+    /// Free-play genre, key and mode to restore when the song that set its own key is unloaded
+    free_play_mapping: Arc<Mutex<Option<(String, String, String)>>>,
+    /// Instrument a song's instrument replaced, restored when it's unloaded
+    free_play_instrument: Arc<Mutex<Option<String>>>,
+    /// Setlist being played and the entry that's loaded
+    active_setlist: Arc<Mutex<Option<(Setlist, usize)>>>,
+    /// Genre declared by the loaded song, played without touching the saved config
+    song_genre: Arc<Mutex<Option<String>>>,
+    /// D-pad capo (key offset from the chosen key)
+    capo: Arc<Mutex<Capo>>,
+    /// Player 1 input played by a running demo, in place of the hardware
+    demo_input: Arc<Mutex<Option<ControllerStateSnapshot>>>,
+    /// Tilt to layer level for player 1 (None = tilt doesn't drive a layer)
+    tilt_layer: Arc<Mutex<Option<TiltLayer>>>,
+    /// Receivers of capo changes
+    transpose_listeners: Arc<Mutex<Vec<mpsc::Sender<TransposeInfo>>>>,
+    /// Last tap tempo press already applied (nanoseconds since epoch)
+    last_tap_tempo: Arc<Mutex<u64>>,
+    /// Select on the last poll (star power fires on the edge)
+    prev_star_power_trigger: Arc<Mutex<bool>>,
+    /// What raising the neck does, and the detector deciding when it's raised
+    tilt_action: Arc<Mutex<TiltAction>>,
+    tilt_trigger: Arc<Mutex<TiltTrigger>>,
+    /// Receivers of star power activations
+    star_power_listeners: Arc<Mutex<Vec<mpsc::Sender<StarPowerActivation>>>>,
+    /// Chord struck most recently by any player, and the receivers of new ones
+    last_chord: Arc<Mutex<Option<ChordPlayed>>>,
+    chord_listeners: Arc<Mutex<Vec<mpsc::Sender<ChordPlayed>>>>,
+    /// Macros of the active mapping profile
+    macro_player: Arc<Mutex<MacroPlayer>>,
+    /// Macro recording in progress (player 1's mapper output)
+    macro_recorder: Arc<Mutex<Option<MacroRecorder>>>,
+    /// Named audio/mapping/instrument setups
+    config_profiles: Arc<Mutex<ConfigProfileStore>>,
+    /// Next config profile action on the last poll (switches on the edge)
+    prev_next_config_profile: Arc<Mutex<bool>>,
+    /// Receivers of applied config profiles
+    config_profile_listeners: Arc<Mutex<Vec<mpsc::Sender<String>>>>,
+    /// Where config, profiles, songs and soundfonts are stored
+    pub paths: AppPaths,
+    /// Legacy files brought into `paths` at startup
+    migration: MigrationReport,
 }
 
 impl AppState {
     pub fn new() -> Result<Self> {
+        // Resolve the app data layout and bring over files from older builds
+        let paths = AppPaths::resolve()?;
+        let migration = paths.migrate().unwrap_or_else(|e| {
+            log::warn!("Failed to migrate legacy data: {}", e);
+            MigrationReport::default()
+        });
+        if !migration.migrated.is_empty() {
+            log::info!("📦 Moved {} legacy files into {:?}", migration.migrated.len(), paths.root);
+        }
+        
         // Load configuration
         let config = AppConfig::load()?;
         log::info!("Config loaded: sample_rate={}, buffer_size={}", 
             config.audio.sample_rate, config.audio.buffer_size);
         
         // Initialize audio (global, not in state)
-        init_audio(Some(config.audio.buffer_size))?;
+        init_audio(
+            Some(config.audio.buffer_size),
+            Some(config.audio.backend.as_str()),
+            config.audio.output_device.as_deref(),
+        )?;
         log::info!("Audio output initialized");
         
         // Initialize SoundFont manager
@@ -94,7 +277,7 @@ impl AppState {
             
             log::info!("Looking for soundfont directory at: {:?}", soundfont_dir);
             
-            let manager = SoundFontManager::new(&soundfont_dir)
+            let manager = SoundFontManager::with_additional_dirs(&soundfont_dir, vec![paths.soundfonts_dir.clone()])
                 .unwrap_or_else(|e| {
                     log::warn!("Failed to initialize SoundFont manager: {}. Continuing without SoundFonts.", e);
                     // Create an empty manager by using a non-existent directory
@@ -115,7 +298,66 @@ impl AppState {
             "pop" => Genre::Pop,
             _ => Genre::Rock,
         };
-        let mut mapper = Mapper::new(genre);
+        let voice_leading = config.mapping.voice_leading.get(&config.mapping.genre.to_lowercase()).copied();
+        let dynamics_mode = DynamicsMode::from_name(&config.mapping.dynamics_mode).unwrap_or_default();
+        let palm_mute_trigger = PalmMuteTrigger::from_name(&config.mapping.palm_mute_trigger).unwrap_or_default();
+        let bend_target = BendTarget::from_name(&config.mapping.bend_target).unwrap_or_default();
+        let whammy_pull = WhammyPullEffect::from_name(&config.mapping.whammy_pull_effect).unwrap_or_default();
+        let whammy_mode = WhammyMode::from_name(&config.mapping.whammy_mode).unwrap_or_default();
+        let new_mapper = || {
+            let mut mapper = Mapper::new(genre);
+            if let Some(enabled) = voice_leading {
+                mapper.set_voice_leading(enabled);
+            }
+            mapper.set_guitar_voicing(config.mapping.guitar_voicing, config.mapping.strum_spread_ms);
+            if config.mapping.strum_stagger {
+                mapper.set_strum_stagger(true, config.mapping.strum_spread_ms);
+            }
+            mapper.set_dynamics_mode(dynamics_mode);
+            mapper.set_palm_mute_trigger(palm_mute_trigger);
+            mapper.set_bend_target(bend_target);
+            mapper.set_whammy_split(config.mapping.whammy_split, whammy_pull);
+            mapper.set_whammy_mode(whammy_mode);
+            mapper
+        };
+        let mut mapper = new_mapper();
+        let player_mappers = (1..MAX_PLAYERS)
+            .map(|_| Arc::new(Mutex::new(new_mapper())))
+            .collect();
+        
+        // Genre preset pipeline (chord qualities, whammy and sustain from assets/chordmaps)
+        let chord_resolver = Arc::new(load_chord_resolver());
+        let harmonic_mappers = (0..MAX_PLAYERS)
+            .map(|player_index| {
+                let mut mapper = HarmonicMapper::new(Arc::clone(&chord_resolver), genre.into());
+                mapper.set_dynamics_mode(dynamics_mode);
+                mapper.set_palm_mute_trigger(palm_mute_trigger);
+                mapper.set_diatonic(config.mapping.diatonic_chords);
+                mapper.set_whammy_mode(whammy_mode);
+                mapper.set_bass_mode(config.mapping.bass_players.contains(&player_index));
+                mapper.set_lead_mode(config.mapping.lead_players.contains(&player_index));
+                Arc::new(Mutex::new(mapper))
+            })
+            .collect();
+        let legacy_mapper = config.mapping.legacy_mapper;
+        
+        // Mapper plugins, and the players that picked one
+        let mut plugins = PluginRegistry::new();
+        plugins.load_dir(&paths.plugins_dir);
+        let player_plugins = (0..MAX_PLAYERS)
+            .map(|player_index| {
+                let plugin = config.mapping.player_plugins.get(&player_index).and_then(|name| {
+                    let plugin = plugins.create(name);
+                    if plugin.is_none() {
+                        log::warn!("Mapper plugin '{}' of player {} not found", name, player_index + 1);
+                    }
+                    plugin
+                });
+                Arc::new(Mutex::new(plugin))
+            })
+            .collect();
+        let transpose_range = config.mapping.transpose_range;
+        log::info!("🎼 Chord mapping: {}", if legacy_mapper { "legacy patterns" } else { "genre presets" });
         
         // Set pattern index from config
         for _ in 0..config.mapping.pattern_index {
@@ -138,15 +380,41 @@ impl AppState {
         // let audio_callback = Arc::new(InstantAudioCallback::new());
         // controller.set_audio_callback(audio_callback);
         
+        // Select input backend from config ("auto" = gilrs, "xinput[:slot]" = XInput)
+        controller.set_backend(ControllerBackend::from_device_id(&config.controller.device_id));
+        
         // Start high-frequency polling (1000Hz) for instant response
-        controller.start_polling()
-            .unwrap_or_else(|e| {
-                log::error!("Failed to start high-performance polling: {}", e);
-            });
+        if let Err(e) = controller.start_polling() {
+            log::error!("Failed to start high-performance polling: {}", e);
+            if controller.backend() != ControllerBackend::Gilrs {
+                log::warn!("Falling back to gilrs controller backend");
+                controller.set_backend(ControllerBackend::Gilrs);
+                controller.start_polling()
+                    .unwrap_or_else(|e| {
+                        log::error!("Failed to start high-performance polling: {}", e);
+                    });
+            }
+        }
 
         // Scan for existing controllers
         let _ = controller.scan_for_controllers();
         
+        // A USB foot switch, if plugged in, acts as sustain pedal
+        match controller.connect_footswitch(None) {
+            Ok(Some(info)) => log::info!("🦶 Sustain pedal: {}", info.name),
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to open foot switch: {}", e),
+        }
+        
+        controller.set_rumble_enabled(config.controller.rumble);
+        
+        // Rock Band 3 guitars with fret LEDs light the chords to play
+        match controller.connect_fret_leds(None) {
+            Ok(Some(info)) => log::info!("💡 Fret LEDs: {}", info.name),
+            Ok(None) => {}
+            Err(e) => log::warn!("Failed to open fret LEDs: {}", e),
+        }
+        
         log::info!("✅ High-performance controller initialized (1000Hz polling)");
         
         #[cfg(feature = "simulator")]
@@ -161,29 +429,67 @@ impl AppState {
                 let path = soundfont.path.clone();
                 drop(manager); // Release lock before calling into audio
                 
+                // Loads in the background; the fallback synth plays until it is ready
                 if let Err(e) = with_audio(|audio| audio.load_soundfont(path)) {
                     log::error!("Failed to load soundfont on startup: {}", e);
                 } else {
-                    log::info!("✅ Soundfont loaded on startup: {}", soundfont_name);
+                    log::info!("🎹 Soundfont loading on startup: {}", soundfont_name);
+                }
+            } else if let Some(path) = manager.get_instrument_by_name(soundfont_name)
+                .filter(|instrument| matches!(instrument.instrument_type, InstrumentType::Sampler | InstrumentType::Sfz))
+                .and_then(|instrument| instrument.path.clone())
+            {
+                drop(manager);
+                match load_sample_instrument(&path).and_then(|zones| with_audio(|audio| audio.load_sampler(0, zones))) {
+                    Ok(()) => log::info!("✅ Sample instrument loaded on startup: {}", soundfont_name),
+                    Err(e) => log::error!("Failed to load sample instrument on startup: {}", e),
                 }
             } else {
                 log::warn!("Configured soundfont '{}' not found in directory", soundfont_name);
             }
         }
         
-        // Apply release time multiplier from config
-        let release_multiplier = config.audio.release_time_multiplier;
-        if let Err(e) = with_audio(|audio| audio.set_release_multiplier(release_multiplier)) {
-            log::error!("Failed to set release multiplier: {}", e);
+        apply_audio_settings(&config.audio);
+        if let Some(preset) = chord_resolver.get_preset(genre.into()) {
+            let smoothing = preset.whammy_defaults.smoothing_factor;
+            controller.set_whammy_filter(smoothing, preset.whammy_defaults.deadzone);
+            if let Err(e) = with_audio(|audio| audio.set_whammy_smoothing(smoothing)) {
+                log::error!("Failed to set whammy smoothing: {}", e);
+            }
+            let layer = instrument_layer(preset.layer.as_ref());
+            if let Err(e) = with_audio(|audio| audio.set_preset_layer(layer)) {
+                log::error!("Failed to set preset layer: {}", e);
+            }
+        }
+        let tilt_action = TiltAction::from_name(&config.mapping.tilt_mode).unwrap_or_else(|| {
+            log::warn!("Unknown tilt mode in config: {}", config.mapping.tilt_mode);
+            TiltAction::default()
+        });
+        if let Err(e) = with_audio(|audio| audio.set_rock_out_effect(rock_out_effect(tilt_action))) {
+            log::error!("Failed to set rock-out effect: {}", e);
+        }
+        let tilt_trigger = TiltTrigger::new(config.mapping.tilt_threshold, controller::tilt_trigger::DEFAULT_TILT_HYSTERESIS);
+        let tilt_layer = if tilt_action == TiltAction::Layer {
+            let instrument = SynthInstrumentType::from_name(&config.mapping.tilt_layer_instrument);
+            if instrument.is_none() {
+                log::warn!("Unknown tilt layer instrument in config: {}", config.mapping.tilt_layer_instrument);
+            }
+            if let Err(e) = with_audio(|audio| audio.set_layer_instrument(instrument)) {
+                log::error!("Failed to set tilt layer instrument: {}", e);
+            }
+            instrument.map(|_| TiltLayer::default())
         } else {
-            log::info!("✅ Release time multiplier set to: {}", release_multiplier);
+            None
+        };
+        let monitor = config.audio.input_monitor.clone();
+        if monitor.enabled {
+            if let Err(e) = with_audio(|audio| audio.start_input_monitor(monitor.device.as_deref(), monitor.channel, monitor.gain)) {
+                log::warn!("Failed to start input monitoring: {}", e);
+            }
         }
         
         // Initialize profile manager
-        let profiles_dir = std::env::current_dir()
-            .unwrap_or_default()
-            .join("mapping_profiles");
-        let profile_manager = MappingProfileManager::new(profiles_dir.clone())
+        let profile_manager = MappingProfileManager::new(paths.profiles_dir.clone())
             .unwrap_or_else(|e| {
                 log::warn!("Failed to initialize profile manager: {}. Using temp directory.", e);
                 // Fallback to temp directory
@@ -191,113 +497,1764 @@ impl AppState {
                 MappingProfileManager::new(temp_dir).unwrap()
             });
         
+        // Config profiles, with the built-in ones on first start
+        let config_profiles = ConfigProfileStore::new(paths.config_profiles_dir.clone())
+            .unwrap_or_else(|e| {
+                log::warn!("Failed to initialize config profiles: {}. Using temp directory.", e);
+                ConfigProfileStore::new(std::env::temp_dir().join("mityguitar_config_profiles")).unwrap()
+            });
+        if let Err(e) = config_profiles.seed_defaults(&config) {
+            log::warn!("Failed to write the default config profiles: {}", e);
+        }
+        
         // Initialize song player with available instruments
         let available_instruments = vec![
             ("virtual".to_string(), "Basic Guitar".to_string()),
             #[cfg(feature = "soundfont")]
             ("soundfont".to_string(), "Clean Guitar".to_string()),
         ];
-        let song_player = SongPlayer::new(available_instruments);
+        let mut song_player = SongPlayer::new(available_instruments);
+        song_player.set_change_quantize(QuantizeGrid::from_name(&config.mapping.change_quantize).unwrap_or_default());
+        song_player.set_count_in_bars(config.song.count_in_bars);
+        song_player.set_input_offset_ms(config.song.input_offset_ms);
+        song_player.set_no_fail(config.song.no_fail);
+        let windows = &config.song.hit_windows;
+        if let Err(e) = song_player.set_hit_windows(HitWindows { perfect_ms: windows.perfect_ms, good_ms: windows.good_ms, miss_ms: windows.miss_ms }) {
+            log::warn!("Ignoring hit windows in config: {}", e);
+        }
+        if let Err(e) = song_player.set_difficulty(Difficulty::from_name(&config.song.difficulty).unwrap_or_default()) {
+            log::warn!("Failed to set song difficulty: {}", e);
+        }
+        let song_stats = SongStats::load(&paths.stats_file).unwrap_or_else(|e| {
+            log::warn!("Failed to load song stats: {:#}. Starting over.", e);
+            SongStats::default()
+        });
+        // Left behind when the last session didn't exit cleanly
+        let recovered_session = SessionSnapshot::load(&paths.recovery_file).unwrap_or_else(|e| {
+            log::warn!("Ignoring recovery file: {:#}", e);
+            None
+        });
+        if let Some(snapshot) = &recovered_session {
+            log::info!("🩹 Last session ended unexpectedly with '{}' loaded", snapshot.chart.meta.title);
+        }
         
         Ok(Self {
             config: Arc::new(Mutex::new(config)),
             mapper: Arc::new(Mutex::new(mapper)),
+            player_mappers,
+            harmonic_mappers,
+            legacy_mapper: Arc::new(Mutex::new(legacy_mapper)),
+            plugins: Arc::new(Mutex::new(plugins)),
+            player_plugins,
+            live_set_mode: Arc::new(Mutex::new(false)),
+            menu_navigator: Arc::new(Mutex::new(MenuNavigator::new())),
+            menu_listeners: Arc::new(Mutex::new(Vec::new())),
             controller: Arc::new(Mutex::new(controller)),
             profile_manager: Arc::new(Mutex::new(profile_manager)),
             song_player: Arc::new(Mutex::new(song_player)),
+            song_stats: Arc::new(Mutex::new(song_stats)),
+            recovered_session: Arc::new(Mutex::new(recovered_session)),
+            library: Arc::new(Mutex::new(LibraryIndex::load(&paths.library_index_file))),
+            practice: Arc::new(Mutex::new(PracticeSession::new(unix_seconds()))),
+            prev_strum: Arc::new(Mutex::new(false)),
+            trainer: Arc::new(Mutex::new(None)),
+            drums_follow_transport: Arc::new(Mutex::new(false)),
             #[cfg(feature = "soundfont")]
             soundfont_manager,
+            #[cfg(feature = "soundfont")]
+            preview_cache: Arc::new(Mutex::new(HashMap::new())),
             #[cfg(feature = "simulator")]
             simulator: Arc::new(Mutex::new(simulator)),
             hw_controller_enabled: Arc::new(Mutex::new(true)), // Enabled by default, will work if available
             prev_dpad_left: Arc::new(Mutex::new(false)),
             prev_dpad_right: Arc::new(Mutex::new(false)),
+            prev_dpad_up: Arc::new(Mutex::new(false)),
+            prev_dpad_down: Arc::new(Mutex::new(false)),
+            free_play_mapping: Arc::new(Mutex::new(None)),
+            free_play_instrument: Arc::new(Mutex::new(None)),
+            active_setlist: Arc::new(Mutex::new(None)),
+            song_genre: Arc::new(Mutex::new(None)),
+            capo: Arc::new(Mutex::new(Capo::new(transpose_range))),
+            tilt_layer: Arc::new(Mutex::new(tilt_layer)),
+            demo_input: Arc::new(Mutex::new(None)),
+            transpose_listeners: Arc::new(Mutex::new(Vec::new())),
+            last_tap_tempo: Arc::new(Mutex::new(0)),
+            prev_star_power_trigger: Arc::new(Mutex::new(false)),
+            tilt_action: Arc::new(Mutex::new(tilt_action)),
+            tilt_trigger: Arc::new(Mutex::new(tilt_trigger)),
+            star_power_listeners: Arc::new(Mutex::new(Vec::new())),
+            last_chord: Arc::new(Mutex::new(None)),
+            chord_listeners: Arc::new(Mutex::new(Vec::new())),
+            macro_player: Arc::new(Mutex::new(MacroPlayer::new())),
+            macro_recorder: Arc::new(Mutex::new(None)),
+            config_profiles: Arc::new(Mutex::new(config_profiles)),
+            prev_next_config_profile: Arc::new(Mutex::new(false)),
+            config_profile_listeners: Arc::new(Mutex::new(Vec::new())),
+            paths,
+            migration,
+            jobs: Arc::new(JobManager::new()),
         })
     }
     
     /// Get current controller state (INSTANT - just atomic reads!)
     pub fn get_controller_state(&self) -> ControllerStateSnapshot {
+        self.get_player_controller_state(0)
+    }
+    
+    /// Get current controller state for a player slot
+    pub fn get_player_controller_state(&self, player_index: usize) -> ControllerStateSnapshot {
+        // A running demo plays player 1
+        if player_index == 0 {
+            if let Some(demo) = self.demo_input.timed_lock().clone() {
+                return demo;
+            }
+        }
+        
         // Hardware enabled check
-        let hw_enabled = *self.hw_controller_enabled.lock().unwrap();
+        let hw_enabled = *self.hw_controller_enabled.timed_lock();
 
-        if hw_enabled {
+        let snapshot = if hw_enabled {
             // Get atomic state snapshot - this is INSTANT! No polling overhead.
-            let controller = self.controller.lock().unwrap();
-            controller.get_state() // This just reads atomics - microsecond access!
+            let controller = self.controller.timed_lock();
+            controller.get_player_state(player_index) // This just reads atomics - microsecond access!
+        } else {
+            None
+        };
+        
+        // Hardware disabled or unknown player, return empty state
+        let snapshot = snapshot.unwrap_or_else(|| ControllerStateSnapshot {
+            player_index,
+            ..Default::default()
+        });
+        self.with_simulator_keys(player_index, snapshot)
+    }
+
+    /// Simulator keys (typed or scripted) play alongside player 1's guitar
+    #[cfg(feature = "simulator")]
+    fn with_simulator_keys(&self, player_index: usize, mut snapshot: ControllerStateSnapshot) -> ControllerStateSnapshot {
+        if player_index == 0 {
+            let mut simulator = self.simulator.timed_lock();
+            simulator.tick(Instant::now());
+            simulator.merge_into(&mut snapshot);
+        }
+        snapshot
+    }
+
+    #[cfg(not(feature = "simulator"))]
+    fn with_simulator_keys(&self, _player_index: usize, snapshot: ControllerStateSnapshot) -> ControllerStateSnapshot {
+        snapshot
+    }
+    
+    /// Legacy files brought into the app data layout at startup
+    pub fn migration_report(&self) -> &MigrationReport {
+        &self.migration
+    }
+    
+    /// Push the active mapping profile's axis bindings to the polling thread
+    /// and load its macros
+    pub fn apply_active_mapping_profile(&self) {
+        let manager = self.profile_manager.timed_lock();
+        self.controller.timed_lock().set_mapping_profile(manager.active_profile());
+        let macros = manager.active_profile().map(|profile| profile.macros.clone()).unwrap_or_default();
+        drop(manager);
+        let released = self.macro_player.timed_lock().set_macros(macros);
+        self.play_macro_actions(released);
+    }
+    
+    /// Fire macros whose combo is held and play their due steps.
+    /// Called every millisecond so recorded timing is kept.
+    pub fn run_macros(&self) {
+        let actions = {
+            let mut player = self.macro_player.timed_lock();
+            if player.is_idle() {
+                return;
+            }
+            let state = self.get_controller_state().to_controller_state();
+            player.update(&state, Instant::now())
+        };
+        self.play_macro_actions(actions);
+    }
+    
+    /// Start a macro by name
+    pub fn trigger_macro(&self, name: &str) -> Result<(), String> {
+        let released = self.macro_player.timed_lock().trigger(name, Instant::now())
+            .ok_or_else(|| format!("Macro '{}' not found", name))?;
+        self.play_macro_actions(released);
+        Ok(())
+    }
+    
+    /// Stop all running macros (releasing their notes)
+    pub fn stop_macros(&self) {
+        let released = self.macro_player.timed_lock().stop_all();
+        self.play_macro_actions(released);
+    }
+    
+    fn play_macro_actions(&self, actions: Vec<MacroAction>) {
+        for action in actions {
+            let result = match MusicEvent::from_macro_action(&action) {
+                Some(event) => send_audio_event(event).map_err(|e| e.to_string()),
+                None => match action {
+                    MacroAction::SetInstrument { name } => self.request_change(LiveChange::Instrument(name)),
+                    MacroAction::SetGenre { genre } => self.request_change(LiveChange::Genre(genre)),
+                    _ => Ok(()),
+                },
+            };
+            if let Err(e) = result {
+                log::warn!("Macro step failed: {}", e);
+            }
+        }
+    }
+    
+    /// Start recording player 1's notes into a macro
+    pub fn start_macro_recording(&self) {
+        log::info!("⏺️ Macro recording started");
+        *self.macro_recorder.timed_lock() = Some(MacroRecorder::new(Instant::now()));
+    }
+    
+    /// Finish recording and store the macro in the active mapping profile
+    pub fn stop_macro_recording(&self, name: String, trigger: Vec<AppAction>) -> Result<ControllerMacro, String> {
+        let recorder = self.macro_recorder.timed_lock().take()
+            .ok_or_else(|| "No macro recording in progress".to_string())?;
+        if recorder.step_count() == 0 {
+            return Err("Nothing was played while recording".to_string());
+        }
+        let recorded = recorder.finish(name, trigger);
+        log::info!("⏹️ Recorded macro '{}': {} steps, {}ms", recorded.name, recorded.steps.len(), recorded.duration_ms());
+        self.save_macro(recorded.clone())?;
+        Ok(recorded)
+    }
+    
+    /// Add or replace a macro in the active mapping profile
+    pub fn save_macro(&self, controller_macro: ControllerMacro) -> Result<(), String> {
+        let mut manager = self.profile_manager.timed_lock();
+        let profile = manager.active_profile_mut()
+            .ok_or_else(|| "No active mapping profile".to_string())?;
+        profile.set_macro(controller_macro);
+        manager.save_active_profile().map_err(|e| e.to_string())?;
+        drop(manager);
+        
+        self.apply_active_mapping_profile();
+        Ok(())
+    }
+    
+    /// Remove a macro from the active mapping profile
+    pub fn delete_macro(&self, name: &str) -> Result<(), String> {
+        let mut manager = self.profile_manager.timed_lock();
+        let profile = manager.active_profile_mut()
+            .ok_or_else(|| "No active mapping profile".to_string())?;
+        if !profile.remove_macro(name) {
+            return Err(format!("Macro '{}' not found", name));
+        }
+        manager.save_active_profile().map_err(|e| e.to_string())?;
+        drop(manager);
+        
+        self.apply_active_mapping_profile();
+        Ok(())
+    }
+    
+    /// Macros of the active mapping profile
+    pub fn list_macros(&self) -> Vec<ControllerMacro> {
+        self.macro_player.timed_lock().macros().to_vec()
+    }
+    
+    /// Mapper for a player slot (player 1 uses the main mapper)
+    pub fn mapper_for(&self, player_index: usize) -> Option<Arc<Mutex<Mapper>>> {
+        if player_index == 0 {
+            Some(Arc::clone(&self.mapper))
+        } else {
+            self.player_mappers.get(player_index - 1).cloned()
+        }
+    }
+    
+    /// Whether input goes through the legacy pattern mapper
+    pub fn uses_legacy_mapper(&self) -> bool {
+        *self.legacy_mapper.timed_lock()
+    }
+    
+    /// Switch between the genre preset pipeline and the legacy mapper
+    /// (silences whatever the other one left sounding)
+    pub fn set_legacy_mapper(&self, enabled: bool) -> Result<(), String> {
+        let mut legacy_mapper = self.legacy_mapper.timed_lock();
+        if *legacy_mapper != enabled {
+            let events = if *legacy_mapper {
+                self.mapper.timed_lock().panic()
+            } else {
+                self.harmonic_mappers[0].timed_lock().panic()
+            };
+            for player_index in 1..MAX_PLAYERS {
+                if *legacy_mapper {
+                    if let Some(mapper) = self.mapper_for(player_index) {
+                        let _ = mapper.timed_lock().panic();
+                    }
+                } else if let Some(mapper) = self.harmonic_mappers.get(player_index) {
+                    let _ = mapper.timed_lock().panic();
+                }
+            }
+            // The panic at the end silences every player's synth
+            for event in events {
+                send_audio_event(event).map_err(|e| e.to_string())?;
+            }
+            *legacy_mapper = enabled;
+        }
+        drop(legacy_mapper);
+        
+        let mut config = self.config.timed_lock();
+        config.mapping.legacy_mapper = enabled;
+        config.save().map_err(|e| e.to_string())
+    }
+    
+    /// Let a player's frets play single bass roots instead of chords and remember it
+    pub fn set_bass_mode(&self, player_index: usize, enabled: bool) -> Result<(), String> {
+        let mapper = self.harmonic_mappers.get(player_index)
+            .ok_or_else(|| format!("Invalid player index: {}", player_index))?;
+        let events = mapper.timed_lock().set_bass_mode(enabled);
+        for event in events {
+            send_timed_audio_event(player_index, event, 0).map_err(|e| e.to_string())?;
+        }
+        log::info!("🎸 Player {} plays {}", player_index + 1, if enabled { "bass" } else { "chords" });
+        
+        let mut config = self.config.timed_lock();
+        config.mapping.bass_players.retain(|&player| player != player_index);
+        if enabled {
+            config.mapping.bass_players.push(player_index);
+            config.mapping.bass_players.sort_unstable();
+        }
+        config.save().map_err(|e| e.to_string())
+    }
+    
+    /// Players in bass mode
+    pub fn bass_players(&self) -> Vec<usize> {
+        self.harmonic_mappers.iter().enumerate()
+            .filter(|(_, mapper)| mapper.timed_lock().bass_mode())
+            .map(|(player_index, _)| player_index)
+            .collect()
+    }
+    
+    /// Let a player's solo frets (or main frets with Select held) play the
+    /// key's scale and remember it
+    pub fn set_lead_mode(&self, player_index: usize, enabled: bool) -> Result<(), String> {
+        let mapper = self.harmonic_mappers.get(player_index)
+            .ok_or_else(|| format!("Invalid player index: {}", player_index))?;
+        let events = mapper.timed_lock().set_lead_mode(enabled);
+        for event in events {
+            send_timed_audio_event(player_index, event, 0).map_err(|e| e.to_string())?;
+        }
+        log::info!("🎸 Player {} lead mode {}", player_index + 1, if enabled { "on" } else { "off" });
+        
+        let mut config = self.config.timed_lock();
+        config.mapping.lead_players.retain(|&player| player != player_index);
+        if enabled {
+            config.mapping.lead_players.push(player_index);
+            config.mapping.lead_players.sort_unstable();
+        }
+        config.save().map_err(|e| e.to_string())
+    }
+    
+    /// Players in lead mode
+    pub fn lead_players(&self) -> Vec<usize> {
+        self.harmonic_mappers.iter().enumerate()
+            .filter(|(_, mapper)| mapper.timed_lock().lead_mode())
+            .map(|(player_index, _)| player_index)
+            .collect()
+    }
+    
+    /// Mapper plugins that can be picked for a player
+    pub fn list_plugins(&self) -> Vec<PluginInfo> {
+        self.plugins.timed_lock().list()
+    }
+    
+    /// Load plugin libraries added to the plugin folder since startup
+    pub fn rescan_plugins(&self) -> Vec<PluginInfo> {
+        let mut plugins = self.plugins.timed_lock();
+        plugins.load_dir(&self.paths.plugins_dir);
+        plugins.list()
+    }
+    
+    /// Let a mapper plugin play a player's frets instead of the chord mappers
+    /// (None = back to chords) and remember it
+    pub fn set_player_plugin(&self, player_index: usize, name: Option<String>) -> Result<(), String> {
+        let slot = self.player_plugins.get(player_index)
+            .ok_or_else(|| format!("Invalid player index: {}", player_index))?;
+        let plugin = match &name {
+            Some(name) => Some(self.plugins.timed_lock().create(name).ok_or_else(|| format!("Unknown mapper plugin: {}", name))?),
+            None => None,
+        };
+        let previous = std::mem::replace(&mut *slot.timed_lock(), plugin);
+        if let Some(mut previous) = previous {
+            for event in previous.panic() {
+                send_timed_audio_event(player_index, event, 0).map_err(|e| e.to_string())?;
+            }
+        }
+        log::info!("🧩 Player {} plays {}", player_index + 1, name.as_deref().unwrap_or("chords"));
+        
+        let mut config = self.config.timed_lock();
+        match name {
+            Some(name) => config.mapping.player_plugins.insert(player_index, name),
+            None => config.mapping.player_plugins.remove(&player_index),
+        };
+        config.save().map_err(|e| e.to_string())
+    }
+    
+    /// Plugin each player is using (None = chord mappers)
+    pub fn player_plugins(&self) -> Vec<Option<String>> {
+        self.player_plugins.iter()
+            .map(|plugin| plugin.timed_lock().as_ref().map(|plugin| plugin.name().to_string()))
+            .collect()
+    }
+    
+    /// Whether the stage display state is being emitted
+    pub fn live_set_mode(&self) -> bool {
+        *self.live_set_mode.timed_lock()
+    }
+    
+    /// Start or stop emitting the stage display state
+    pub fn set_live_set_mode(&self, enabled: bool) {
+        *self.live_set_mode.timed_lock() = enabled;
+        log::info!("🎤 Live set mode {}", if enabled { "on" } else { "off" });
+    }
+    
+    /// Serve the stream overlay feed (see `overlay`); None keeps the port
+    pub fn set_overlay(&self, enabled: bool, port: Option<u16>) -> Result<OverlayConfig, String> {
+        let mut config = self.config.timed_lock();
+        config.overlay.enabled = enabled;
+        if let Some(port) = port {
+            config.overlay.port = port;
+        }
+        log::info!("📺 Stream overlay {} (port {})", if enabled { "on" } else { "off" }, config.overlay.port);
+        config.save().map_err(|e| e.to_string())?;
+        Ok(config.overlay.clone())
+    }
+    
+    /// Whether the frontend has a menu open
+    pub fn menu_mode(&self) -> bool {
+        self.menu_navigator.timed_lock().is_open()
+    }
+    
+    /// Open or close guitar menu navigation. While open, presses are sent as
+    /// navigation actions and nothing plays.
+    pub fn set_menu_mode(&self, open: bool) -> Result<(), String> {
+        let mut navigator = self.menu_navigator.timed_lock();
+        if navigator.is_open() == open {
+            return Ok(());
+        }
+        if open {
+            navigator.open(&self.get_controller_state());
+        } else {
+            navigator.close();
+        }
+        drop(navigator);
+        log::info!("🧭 Menu navigation {}", if open { "on" } else { "off" });
+        if open {
+            // Silence whatever was ringing when the menu came up
+            send_audio_event(MusicEvent::PanicAllNotesOff).map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+    
+    /// Receive menu navigation actions (forwarded to the frontend as "menu-navigate")
+    pub fn subscribe_menu_navigation(&self) -> mpsc::Receiver<NavAction> {
+        let (sender, receiver) = mpsc::channel();
+        self.menu_listeners.timed_lock().push(sender);
+        receiver
+    }
+    
+    /// Play player 1 from a demo step instead of the hardware (None = back to the hardware)
+    pub fn set_demo_input(&self, snapshot: Option<ControllerStateSnapshot>) {
+        *self.demo_input.timed_lock() = snapshot.map(|snapshot| ControllerStateSnapshot {
+            timestamp: timestamp_nanos(),
+            input_timestamp: timestamp_nanos(),
+            ..snapshot
+        });
+    }
+    
+    /// Whether a demo is playing player 1
+    pub fn demo_running(&self) -> bool {
+        self.demo_input.timed_lock().is_some()
+    }
+    
+    /// Map a player's controller state through the active pipeline
+    fn map_input(&self, player_index: usize, snapshot: &ControllerStateSnapshot, state: &ControllerState) -> Vec<MusicEvent> {
+        if let Some(plugin) = self.player_plugins.get(player_index) {
+            if let Some(plugin) = plugin.timed_lock().as_mut() {
+                return plugin.process(snapshot);
+            }
+        }
+        let (events, played) = if self.uses_legacy_mapper() {
+            self.mapper_for(player_index)
+                .map(|mapper| {
+                    let mut mapper = mapper.timed_lock();
+                    (mapper.process(state), mapper.take_played_chord())
+                })
+                .unwrap_or_default()
         } else {
-            // Hardware disabled, return empty state  
-            ControllerStateSnapshot {
-                fret_green: false,
-                fret_red: false,
-                fret_blue: false,
-                fret_yellow: false,
-                fret_orange: false,
-                strum_up: false,
-                strum_down: false,
-                dpad_up: false,
-                dpad_down: false,
-                dpad_left: false,
-                dpad_right: false,
-                start: false,
-                select: false,
-                whammy_bar: 0.0,
-                connected: false,
-                timestamp: 0,
+            self.harmonic_mappers.get(player_index)
+                .map(|mapper| {
+                    let mut mapper = mapper.timed_lock();
+                    (mapper.process(state), mapper.take_played_chord())
+                })
+                .unwrap_or_default()
+        };
+        if let Some(played) = played {
+            self.chord_played(ChordPlayed {
+                player: player_index,
+                name: played.name,
+                notes: played.notes,
+                frets: held_fret_names(snapshot),
+            });
+        }
+        events
+    }
+    
+    /// Remember a struck chord and tell the listeners
+    fn chord_played(&self, chord: ChordPlayed) {
+        self.chord_listeners.timed_lock().retain(|listener| listener.send(chord.clone()).is_ok());
+        *self.last_chord.timed_lock() = Some(chord);
+    }
+    
+    /// Chord struck most recently by any player (None before the first strum)
+    pub fn last_chord(&self) -> Option<ChordPlayed> {
+        self.last_chord.timed_lock().clone()
+    }
+    
+    /// Receive struck chords (forwarded to the frontend as "chord-played")
+    pub fn subscribe_chords(&self) -> mpsc::Receiver<ChordPlayed> {
+        let (sender, receiver) = mpsc::channel();
+        self.chord_listeners.timed_lock().push(sender);
+        receiver
+    }
+    
+    /// Set the genre for all players (keeps per-genre voice leading)
+    pub fn set_genre(&self, genre_name: &str) -> Result<(), String> {
+        let genre = parse_genre(genre_name).ok_or_else(|| "Invalid genre".to_string())?;
+        
+        let mut config = self.config.timed_lock();
+        let voice_leading = config.mapping.voice_leading.get(&genre_name.to_lowercase()).copied();
+        // Picking a genre by hand replaces the one the song declared
+        *self.song_genre.timed_lock() = None;
+        
+        let mut mapper = self.mapper.timed_lock();
+        mapper.set_genre(genre);
+        if let Some(enabled) = voice_leading {
+            mapper.set_voice_leading(enabled);
+        }
+        for player_mapper in &self.player_mappers {
+            let mut player_mapper = player_mapper.timed_lock();
+            player_mapper.set_genre(genre);
+            if let Some(enabled) = voice_leading {
+                player_mapper.set_voice_leading(enabled);
+            }
+        }
+        for harmonic_mapper in &self.harmonic_mappers {
+            harmonic_mapper.timed_lock().set_genre(genre.into());
+        }
+        self.apply_whammy_smoothing();
+        self.apply_preset_layer();
+        
+        // Update config
+        config.mapping.genre = genre_name.to_string();
+        config.save().map_err(|e| e.to_string())?;
+        drop(config);
+        self.apply_preset_instrument();
+        Ok(())
+    }
+    
+    /// Tilt layer settings
+    pub fn tilt_layer(&self) -> TiltLayerInfo {
+        let config = self.config.timed_lock();
+        TiltLayerInfo {
+            enabled: config.mapping.tilt_mode == "layer",
+            instrument: config.mapping.tilt_layer_instrument.clone(),
+        }
+    }
+    
+    /// Fade a virtual instrument in under player 1 as the guitar tilts up, or
+    /// stop (tilt goes back to star power)
+    pub fn set_tilt_layer(&self, enabled: bool, instrument: &str) -> Result<(), String> {
+        let synth_instrument = SynthInstrumentType::from_name(instrument)
+            .ok_or_else(|| format!("Not a virtual instrument: {}", instrument))?;
+        with_audio(|audio| audio.set_layer_instrument(enabled.then_some(synth_instrument)))
+            .map_err(|e| e.to_string())?;
+        *self.tilt_layer.timed_lock() = enabled.then(TiltLayer::default);
+        log::info!("🎻 Tilt layer {} ({})", if enabled { "on" } else { "off" }, instrument);
+        let action = if enabled { TiltAction::Layer } else { TiltAction::StarPower };
+        self.apply_tilt_action(action)?;
+        
+        let mut config = self.config.timed_lock();
+        config.mapping.tilt_mode = action.name().to_string();
+        config.mapping.tilt_layer_instrument = instrument.to_string();
+        config.save().map_err(|e| e.to_string())
+    }
+    
+    /// What raising the neck does
+    pub fn tilt_mode(&self) -> TiltAction {
+        *self.tilt_action.timed_lock()
+    }
+    
+    /// Choose what raising the neck does ("star_power", "filter_sweep",
+    /// "overdrive" or "layer")
+    pub fn set_tilt_mode(&self, mode: &str) -> Result<(), String> {
+        let action = TiltAction::from_name(mode).ok_or_else(|| format!("Unknown tilt mode: {}", mode))?;
+        if action == TiltAction::Layer {
+            let instrument = self.config.timed_lock().mapping.tilt_layer_instrument.clone();
+            return self.set_tilt_layer(true, &instrument);
+        }
+        if self.tilt_layer.timed_lock().take().is_some() {
+            with_audio(|audio| audio.set_layer_instrument(None)).map_err(|e| e.to_string())?;
+        }
+        self.apply_tilt_action(action)?;
+        
+        let mut config = self.config.timed_lock();
+        config.mapping.tilt_mode = action.name().to_string();
+        config.save().map_err(|e| e.to_string())
+    }
+    
+    /// Switch the tilt action, letting go of a boost that is engaged
+    fn apply_tilt_action(&self, action: TiltAction) -> Result<(), String> {
+        *self.tilt_action.timed_lock() = action;
+        self.tilt_trigger.timed_lock().reset();
+        send_audio_event(MusicEvent::ControlChange { cc: ROCK_OUT_CC, value: 0 }).map_err(|e| e.to_string())?;
+        with_audio(|audio| audio.set_rock_out_effect(rock_out_effect(action))).map_err(|e| e.to_string())?;
+        log::info!("🤘 Tilt: {}", action.name());
+        Ok(())
+    }
+    
+    /// Smooth the whammy (in the polling loop and the synth) as the genre's preset asks
+    fn apply_whammy_smoothing(&self) {
+        let (smoothing, deadzone) = {
+            let mapper = self.harmonic_mappers[0].timed_lock();
+            let defaults = mapper.whammy_defaults();
+            (defaults.smoothing_factor, defaults.deadzone)
+        };
+        self.controller.timed_lock().set_whammy_filter(smoothing, deadzone);
+        if let Err(e) = with_audio(|audio| audio.set_whammy_smoothing(smoothing)) {
+            log::error!("Failed to set whammy smoothing: {}", e);
+        }
+    }
+
+    /// Layer the genre preset's instrument under the guitar (or none)
+    fn apply_preset_layer(&self) {
+        let layer = instrument_layer(self.harmonic_mappers[0].timed_lock().layer_defaults());
+        if let Err(e) = with_audio(|audio| audio.set_preset_layer(layer)) {
+            log::error!("Failed to set preset layer: {}", e);
+        }
+    }
+
+    /// Load the genre preset's instrument, unless switching is turned off
+    fn apply_preset_instrument(&self) {
+        if !self.config.timed_lock().mapping.auto_instrument {
+            return;
+        }
+        let instrument = self.harmonic_mappers[0].timed_lock().preset_instrument().map(str::to_string);
+        if let Some(name) = instrument {
+            self.switch_instrument(&name);
+        }
+    }
+    
+    /// Load an instrument unless it's the one playing (a missing instrument
+    /// only costs the switch)
+    fn switch_instrument(&self, name: &str) {
+        if self.config.timed_lock().soundfonts.current.as_deref() == Some(name) {
+            return;
+        }
+        log::info!("🎸 Instrument: {}", name);
+        if let Err(e) = self.request_change(LiveChange::Instrument(name.to_string())) {
+            log::warn!("Failed to load instrument '{}': {}", name, e);
+        }
+    }
+    
+    /// Whether genre presets and songs switch the instrument
+    pub fn auto_instrument(&self) -> bool {
+        self.config.timed_lock().mapping.auto_instrument
+    }
+    
+    /// Let genre presets and songs switch the instrument (off = it stays as picked by hand)
+    pub fn set_auto_instrument(&self, enabled: bool) -> Result<(), String> {
+        let config = {
+            let mut config = self.config.timed_lock();
+            config.mapping.auto_instrument = enabled;
+            config.clone()
+        };
+        config.save().map_err(|e| e.to_string())?;
+        if enabled {
+            if self.song_player.timed_lock().get_chart().is_some() {
+                self.apply_song_instrument();
+            } else {
+                self.apply_preset_instrument();
+            }
+        }
+        Ok(())
+    }
+    
+    /// Choose what the whammy controls for all players
+    pub fn set_whammy_mode(&self, mode: WhammyMode) -> Result<(), String> {
+        self.mapper.timed_lock().set_whammy_mode(mode);
+        for player_mapper in &self.player_mappers {
+            player_mapper.timed_lock().set_whammy_mode(mode);
+        }
+        for harmonic_mapper in &self.harmonic_mappers {
+            harmonic_mapper.timed_lock().set_whammy_mode(mode);
+        }
+
+        let mut config = self.config.timed_lock();
+        config.mapping.whammy_mode = mode.name().to_string();
+        config.save().map_err(|e| e.to_string())
+    }
+
+    /// Set genre, key and mode of the chord mapping
+    pub fn set_chord_mapping(&self, genre_name: &str, key_root: &str, mode: &str) -> Result<(), String> {
+        self.apply_chord_mapping(genre_name, key_root, mode)?;
+        *self.song_genre.timed_lock() = None;
+        self.config.timed_lock().mapping.genre = genre_name.to_string();
+        self.apply_preset_instrument();
+        Ok(())
+    }
+    
+    /// Switch every player's mappers to a genre, key and mode (config untouched)
+    fn apply_chord_mapping(&self, genre_name: &str, key_root: &str, mode: &str) -> Result<(), String> {
+        let genre = parse_genre(genre_name).ok_or_else(|| format!("Invalid genre: {}", genre_name))?;
+        let key_note = parse_note(key_root).ok_or_else(|| format!("Invalid key: {}", key_root))?;
+        let is_major = mode.to_lowercase() == "major";
+        let voice_leading = self.config.timed_lock().mapping.voice_leading.get(&genre_name.to_lowercase()).copied();
+        
+        for mapper in std::iter::once(&self.mapper).chain(self.player_mappers.iter()) {
+            let mut mapper = mapper.timed_lock();
+            mapper.set_genre(genre);
+            mapper.set_key_root(key_note as u8);
+            mapper.set_mode(is_major);
+            if let Some(enabled) = voice_leading {
+                mapper.set_voice_leading(enabled);
+            }
+        }
+        
+        let mode = if is_major { Mode::Major } else { Mode::Minor };
+        for harmonic_mapper in &self.harmonic_mappers {
+            let mut harmonic_mapper = harmonic_mapper.timed_lock();
+            harmonic_mapper.set_genre(genre.into());
+            harmonic_mapper.set_key(Some(Note::from_semitone(key_note as u8)), Some(mode));
+        }
+        self.apply_whammy_smoothing();
+        self.apply_preset_layer();
+        
+        // The chosen key is the capo's new starting point
+        self.capo.timed_lock().reset();
+        self.notify_transpose();
+        Ok(())
+    }
+    
+    /// Load a song chart, switching free play to the key and genre it declares
+    /// (a song loaded this way leaves the setlist being played)
+    pub fn load_song_chart(&self, json: &str) -> Result<(), String> {
+        self.song_player.timed_lock().load_chart(json).map_err(|e| e.to_string())?;
+        *self.active_setlist.timed_lock() = None;
+        self.load_backing_track();
+        self.apply_song_key()?;
+        self.apply_song_instrument();
+        Ok(())
+    }
+    
+    /// Load the loaded chart's backing track into the engine (a missing or
+    /// unreadable file only costs the backing track, not the song)
+    pub fn load_backing_track(&self) {
+        let backing_track = self.song_player.timed_lock().get_chart()
+            .and_then(|chart| chart.meta.backing_track.clone());
+        let track = backing_track.and_then(|backing_track| {
+            let path = self.paths.songs_dir.join(&backing_track.path);
+            match BackingTrack::load(&path) {
+                Ok(track) => {
+                    match track.duration_seconds() {
+                        Some(seconds) => log::info!("🎶 Backing track: {} ({:.0}s)", path.display(), seconds),
+                        None => log::info!("🎶 Backing track: {}", path.display()),
+                    }
+                    Some(Arc::new(track))
+                }
+                Err(e) => {
+                    log::warn!("Backing track not loaded: {:#}", e);
+                    None
+                }
+            }
+        });
+        if let Err(e) = with_audio(|audio| audio.set_backing_track(track)) {
+            log::warn!("Failed to set backing track: {}", e);
+        }
+        self.sync_backing_track();
+    }
+    
+    /// Point the backing track at the transport (after play, pause, seek, speed
+    /// or tempo changes); drums following the transport move along
+    pub fn sync_backing_track(&self) {
+        self.sync_drums();
+        let sync = self.song_player.timed_lock().transport_sync();
+        let Some(seconds) = sync.backing_track_seconds else {
+            return;
+        };
+        if let Err(e) = with_audio(|audio| audio.sync_backing_track(seconds, sync.playing, sync.speed)) {
+            log::warn!("Failed to sync backing track: {}", e);
+        }
+    }
+    
+    /// Start the drum machine on a groove ("rock", "punk", "edm", "metal"), at
+    /// its own tempo or (without one) following the loaded song's transport
+    pub fn start_drums(&self, bpm: Option<f64>, pattern: &str) -> Result<(), String> {
+        let groove = GroovePattern::from_name(pattern)
+            .ok_or_else(|| format!("Unknown drum pattern '{}' (try {})", pattern, GroovePattern::names().join(", ")))?;
+        match bpm {
+            Some(bpm) if bpm > 0.0 => {
+                *self.drums_follow_transport.timed_lock() = false;
+                with_audio(|audio| audio.start_drums(groove, bpm, 0.0)).map_err(|e| e.to_string())?;
+                log::info!("🥁 Drums: {} at {} BPM", pattern, bpm);
+            }
+            Some(bpm) => return Err(format!("Invalid tempo: {}", bpm)),
+            None => {
+                if self.song_player.timed_lock().get_chart().is_none() {
+                    return Err("Give a tempo or load a song for the drums to follow".to_string());
+                }
+                *self.drums_follow_transport.timed_lock() = true;
+                with_audio(|audio| audio.start_drums(groove, 120.0, 0.0)).map_err(|e| e.to_string())?;
+                self.sync_drums();
+                log::info!("🥁 Drums: {} following the song", pattern);
+            }
+        }
+        Ok(())
+    }
+
+    pub fn stop_drums(&self) -> Result<(), String> {
+        *self.drums_follow_transport.timed_lock() = false;
+        with_audio(|audio| audio.stop_drums()).map_err(|e| e.to_string())
+    }
+
+    pub fn set_drums_volume(&self, volume: f32) -> Result<(), String> {
+        with_audio(|audio| audio.set_drums_volume(volume)).map_err(|e| e.to_string())
+    }
+
+    /// Move transport-following drums to the song's beat, tempo and play state
+    fn sync_drums(&self) {
+        if !*self.drums_follow_transport.timed_lock() {
+            return;
+        }
+        let sync = self.song_player.timed_lock().transport_sync();
+        if let Err(e) = with_audio(|audio| audio.sync_drums(sync.beat, sync.bpm, sync.playing)) {
+            log::warn!("Failed to sync drums: {}", e);
+        }
+    }
+
+    /// Count-in beat crossed since the last poll, clicked through the engine
+    /// unless the count-in click is turned off
+    pub fn poll_count_in(&self) -> Option<CountInBeat> {
+        let beat = self.song_player.timed_lock().poll_count_in()?;
+        if self.config.timed_lock().song.count_in_click {
+            self.play_click(beat.click.pulse_in_bar == 0);
+        }
+        Some(beat)
+    }
+
+    /// Sound a metronome click (higher on the downbeat)
+    pub fn play_click(&self, downbeat: bool) {
+        if let Err(e) = with_audio(|audio| audio.play_click(downbeat)) {
+            log::warn!("Failed to play click: {}", e);
+        }
+    }
+
+    /// Set the song hit windows and remember them
+    pub fn set_hit_windows(&self, windows: HitWindows) -> Result<(), String> {
+        self.song_player.timed_lock().set_hit_windows(windows).map_err(|e| e.to_string())?;
+        let mut config = self.config.timed_lock();
+        config.song.hit_windows = HitWindowConfig { perfect_ms: windows.perfect_ms, good_ms: windows.good_ms, miss_ms: windows.miss_ms };
+        config.save().map_err(|e| e.to_string())
+    }
+
+    /// Set the latency taken off song strums and remember it
+    pub fn set_input_offset_ms(&self, offset_ms: f64) -> Result<(), String> {
+        self.song_player.timed_lock().set_input_offset_ms(offset_ms);
+        log::info!("⏱️ Song input offset: {} ms", offset_ms);
+        let mut config = self.config.timed_lock();
+        config.song.input_offset_ms = offset_ms;
+        config.save().map_err(|e| e.to_string())
+    }
+
+    /// Loop passes if the practice loop jumped back since the last poll; the
+    /// backing track follows the jump
+    pub fn poll_song_loop(&self) -> Option<u32> {
+        let passes = self.song_player.timed_lock().take_loop_jump()?;
+        self.sync_backing_track();
+        Some(passes)
+    }
+
+    /// Play songs in time or stop at every chord until it is played
+    pub fn set_practice_mode(&self, mode: PracticeMode) {
+        self.song_player.timed_lock().set_practice_mode(mode);
+        log::info!("🎓 Practice mode: {:?}", mode);
+        self.sync_backing_track();
+    }
+
+    /// Whether the transport waits for a note, if that changed since the last
+    /// poll; the backing track and drums hold while it waits
+    pub fn poll_note_wait(&self) -> Option<bool> {
+        let waiting = self.song_player.timed_lock().poll_note_wait()?;
+        self.sync_backing_track();
+        Some(waiting)
+    }
+
+    /// Speed trainer ramp remembered in the config
+    pub fn speed_trainer_settings(&self) -> SpeedTrainerSettings {
+        let ramp = self.config.timed_lock().song.speed_trainer;
+        SpeedTrainerSettings {
+            start_speed: ramp.start_speed,
+            increment: ramp.increment,
+            target_speed: ramp.target_speed,
+            accuracy_threshold: ramp.accuracy_threshold,
+        }
+    }
+
+    /// Start the speed trainer on the practice loop, with the given ramp
+    /// (remembered) or the last one used
+    pub fn start_speed_trainer(&self, settings: Option<SpeedTrainerSettings>) -> Result<(), String> {
+        let ramp = settings.unwrap_or_else(|| self.speed_trainer_settings());
+        self.song_player.timed_lock().start_speed_trainer(ramp).map_err(|e| e.to_string())?;
+        log::info!("🐢 Speed trainer: {:.0}% up to {:.0}% in {:.0}% steps at {:.0}% accuracy",
+            ramp.start_speed * 100.0, ramp.target_speed * 100.0, ramp.increment * 100.0, ramp.accuracy_threshold);
+        self.sync_backing_track();
+        if settings.is_some() {
+            let mut config = self.config.timed_lock();
+            config.song.speed_trainer = SpeedTrainerConfig {
+                start_speed: ramp.start_speed,
+                increment: ramp.increment,
+                target_speed: ramp.target_speed,
+                accuracy_threshold: ramp.accuracy_threshold,
+            };
+            config.save().map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+
+    /// Outcome of the speed trainer pass that ended since the last poll
+    pub fn poll_speed_trainer(&self) -> Option<SpeedTrainerPass> {
+        let pass = self.song_player.timed_lock().take_speed_trainer_pass()?;
+        if pass.passed {
+            log::info!("🐢 Loop pass at {:.1}% accuracy, speed up to {:.0}%", pass.accuracy, pass.speed * 100.0);
+        }
+        Some(pass)
+    }
+
+    /// Record the play-through once the song has ended, updating the high scores
+    pub fn poll_song_end(&self) -> Option<FinishedPlay> {
+        let play = self.song_player.timed_lock().poll_song_end()?;
+        let mut stats = self.song_stats.timed_lock();
+        let finished = stats.record(play);
+        if let Err(e) = stats.save(&self.paths.stats_file) {
+            log::warn!("Failed to save song stats: {:#}", e);
+        }
+        log::info!("🏁 '{}' finished: {} points, {:.1}%{}", finished.play.title, finished.play.score, finished.play.accuracy,
+            if finished.new_bests.score { " (new high score)" } else { "" });
+        Some(finished)
+    }
+
+    /// Bring the library listing up to date (only new and changed charts are
+    /// parsed), saving it when something changed. Returns whether it did.
+    pub fn rescan_library(&self) -> Result<bool, String> {
+        std::fs::create_dir_all(&self.paths.songs_dir).map_err(|e| e.to_string())?;
+        let mut library = self.library.timed_lock();
+        let changed = library.rescan(&self.paths.songs_dir).map_err(|e| e.to_string())?;
+        if changed {
+            if let Err(e) = library.save(&self.paths.library_index_file) {
+                log::warn!("Failed to save library index: {:#}", e);
+            }
+        }
+        Ok(changed)
+    }
+    
+    /// The charts in the song library, by title
+    pub fn library_songs(&self) -> Result<Vec<LibrarySong>, String> {
+        self.rescan_library()?;
+        Ok(self.library.timed_lock().songs())
+    }
+    
+    /// Search the library listing; the index is kept up to date in the
+    /// background, so no chart is read
+    pub fn search_library(&self, query: &str, filters: &SongFilters) -> Vec<LibrarySong> {
+        self.library.timed_lock().search(query, filters)
+    }
+    
+    /// Write the song session to the recovery file. Without a song loaded the
+    /// file is removed, unless a crashed session still waits to be recovered.
+    pub fn autosave_session(&self) {
+        let snapshot = self.song_player.timed_lock().session_snapshot();
+        let result = match snapshot {
+            Some(snapshot) => snapshot.save(&self.paths.recovery_file),
+            None if self.recovered_session.timed_lock().is_some() => Ok(()),
+            None => SessionSnapshot::clear(&self.paths.recovery_file),
+        };
+        if let Err(e) = result {
+            log::warn!("Failed to autosave session: {:#}", e);
+        }
+    }
+    
+    /// The session a crash left behind, if it wasn't recovered or discarded yet
+    pub fn recoverable_session(&self) -> Option<RecoverableSession> {
+        self.recovered_session.timed_lock().as_ref().map(RecoverableSession::of)
+    }
+    
+    /// Load the song of the session a crash left behind, with its edits,
+    /// difficulty, position, speed and score (paused). None = nothing to recover.
+    pub fn recover_last_session(&self) -> Result<Option<RecoverableSession>, String> {
+        let Some(snapshot) = self.recovered_session.timed_lock().take() else {
+            return Ok(None);
+        };
+        let json = serde_json::to_string(&snapshot.chart).map_err(|e| e.to_string())?;
+        self.song_player.timed_lock().set_difficulty(snapshot.difficulty).map_err(|e| e.to_string())?;
+        self.load_song_chart(&json)?;
+        self.song_player.timed_lock().resume_session(&snapshot);
+        self.sync_backing_track();
+        log::info!("🩹 Recovered '{}' at beat {:.1}", snapshot.chart.meta.title, snapshot.beat);
+        Ok(Some(RecoverableSession::of(&snapshot)))
+    }
+    
+    /// Forget the session a crash left behind
+    pub fn discard_recovered_session(&self) {
+        self.recovered_session.timed_lock().take();
+        self.autosave_session();
+    }
+    
+    /// Remove the recovery file on a clean exit
+    pub fn clear_recovery(&self) {
+        if let Err(e) = SessionSnapshot::clear(&self.paths.recovery_file) {
+            log::warn!("{:#}", e);
+        }
+    }
+    
+    /// Play songs at a difficulty (the loaded song starts over) and remember it
+    pub fn set_song_difficulty(&self, difficulty: Difficulty) -> Result<(), String> {
+        self.song_player.timed_lock().set_difficulty(difficulty).map_err(|e| e.to_string())?;
+        self.sync_backing_track();
+        log::info!("🎚️ Song difficulty: {}", difficulty.name());
+
+        let mut config = self.config.timed_lock();
+        config.song.difficulty = difficulty.name().to_string();
+        config.save().map_err(|e| e.to_string())
+    }
+
+    /// Turn no-fail on or off and remember it
+    pub fn set_no_fail(&self, enabled: bool) -> Result<(), String> {
+        self.song_player.timed_lock().set_no_fail(enabled);
+        log::info!("🛟 No-fail: {}", if enabled { "on" } else { "off" });
+        let mut config = self.config.timed_lock();
+        config.song.no_fail = enabled;
+        config.save().map_err(|e| e.to_string())
+    }
+
+    /// Count a song strum's hit or miss in the practice analytics
+    pub fn record_practice_hit(&self, result: &HitResult) {
+        self.practice.timed_lock().record_hit(result);
+    }
+    
+    /// Rumble player 1's guitar for a cue (when rumble is on)
+    pub fn rumble(&self, cue: RumbleCue) {
+        if let Err(e) = self.controller.timed_lock().rumble(0, cue) {
+            log::warn!("Rumble failed: {}", e);
+        }
+    }
+    
+    /// Switch rumble cues on or off and save the choice
+    pub fn set_rumble_enabled(&self, enabled: bool) -> Result<(), String> {
+        self.controller.timed_lock().set_rumble_enabled(enabled);
+        let mut config = self.config.timed_lock();
+        config.controller.rumble = enabled;
+        config.save().map_err(|e| e.to_string())
+    }
+
+    /// Practice analytics of the session so far, with chords named by degree in the current key
+    pub fn practice_report(&self) -> PracticeReport {
+        let key = SongKey::parse(&self.current_key());
+        self.practice.timed_lock().report(key.as_ref(), unix_seconds())
+    }
+
+    /// Start a new practice session
+    pub fn reset_practice_session(&self) {
+        *self.practice.timed_lock() = PracticeSession::new(unix_seconds());
+        log::info!("📊 Practice session reset");
+    }
+
+    /// Count-in settings
+    pub fn count_in(&self) -> SongConfig {
+        self.config.timed_lock().song.clone()
+    }
+
+    /// Set the count-in length for every song (None = as charted) and whether it clicks
+    pub fn set_count_in(&self, bars: Option<u32>, click: bool) -> Result<(), String> {
+        self.song_player.timed_lock().set_count_in_bars(bars);
+        log::info!("🥁 Count-in: {} bars, click {}", bars.map_or("charted".to_string(), |bars| bars.to_string()), if click { "on" } else { "off" });
+
+        let mut config = self.config.timed_lock();
+        config.song.count_in_bars = bars;
+        config.song.count_in_click = click;
+        config.save().map_err(|e| e.to_string())
+    }
+
+    /// Start editing a new, empty chart (replaces the loaded song)
+    pub fn new_song_chart(&self, chart: SongChart) -> Result<(), String> {
+        let json = serde_json::to_string(&chart).map_err(|e| e.to_string())?;
+        self.load_song_chart(&json)
+    }
+    
+    /// Edit the loaded chart, following a changed key or genre
+    pub fn edit_song_chart<R>(&self, edit: impl FnOnce(&mut SongChart) -> anyhow::Result<R>) -> Result<R, String> {
+        let declared = |player: &SongPlayer| player.get_chart()
+            .map(|chart| (chart.mapping.key.clone(), chart.mapping.genre.clone()));
+        let mut player = self.song_player.timed_lock();
+        let before = declared(&player);
+        let result = player.edit_chart(edit).map_err(|e| e.to_string())?;
+        let changed = declared(&player) != before;
+        drop(player);
+        // Tempo edits move the track time under the current beat
+        self.sync_backing_track();
+        if changed {
+            self.apply_song_key()?;
+        }
+        Ok(result)
+    }
+    
+    /// Record what is played while the transport runs into a new chart
+    pub fn start_chart_recording(&self, grid: Option<f64>) {
+        log::info!("⏺️ Recording chart");
+        self.song_player.timed_lock().start_recording(grid.unwrap_or(DEFAULT_RECORD_GRID));
+    }
+    
+    /// Stop recording and load the recorded chart so it can be played back
+    pub fn stop_chart_recording(&self, title: &str, artist: &str) -> Result<SongChart, String> {
+        let chart = self.song_player.timed_lock().stop_recording(title, artist).map_err(|e| e.to_string())?;
+        log::info!("⏹️ Recorded {} chords into '{}'", chart.lanes.iter().map(|lane| lane.events.len()).sum::<usize>(), title);
+        self.new_song_chart(chart.clone())?;
+        Ok(chart)
+    }
+    
+    /// Chord symbol the held main frets play for player 1 (None when unknown)
+    pub fn held_chord_name(&self, snapshot: &ControllerStateSnapshot) -> Option<String> {
+        let held: Vec<(ControlId, FretButton)> = [
+            (snapshot.fret_green, ControlId::FretGreen, FretButton::Green),
+            (snapshot.fret_red, ControlId::FretRed, FretButton::Red),
+            (snapshot.fret_yellow, ControlId::FretYellow, FretButton::Yellow),
+            (snapshot.fret_blue, ControlId::FretBlue, FretButton::Blue),
+            (snapshot.fret_orange, ControlId::FretOrange, FretButton::Orange),
+        ].into_iter().filter(|(pressed, _, _)| *pressed).map(|(_, control, button)| (control, button)).collect();
+        self.chord_for_frets(&held)
+    }
+    
+    /// Chord symbol a set of main frets plays for player 1 (None when unknown)
+    fn chord_for_frets(&self, held: &[(ControlId, FretButton)]) -> Option<String> {
+        if self.uses_legacy_mapper() {
+            let frets: Vec<ControlId> = held.iter().map(|(control, _)| *control).collect();
+            return self.mapper.timed_lock().chord_name(&frets);
+        }
+        // Genre presets name single frets only
+        match held {
+            [(_, button)] => self.harmonic_mappers[0].timed_lock()
+                .chords(FretRow::Main)
+                .get(button)
+                .map(|chord| chord.display_name()),
+            _ => None,
+        }
+    }
+    
+    /// Chords of the single main frets in the current mapping, for the chord trainer
+    pub fn trainer_chords(&self) -> Vec<TrainerChord> {
+        [
+            (ControlId::FretGreen, FretButton::Green, "GREEN"),
+            (ControlId::FretRed, FretButton::Red, "RED"),
+            (ControlId::FretYellow, FretButton::Yellow, "YELLOW"),
+            (ControlId::FretBlue, FretButton::Blue, "BLUE"),
+            (ControlId::FretOrange, FretButton::Orange, "ORANGE"),
+        ].into_iter()
+            .filter_map(|(control, button, fret)| self.chord_for_frets(&[(control, button)])
+                .map(|name| TrainerChord { name, frets: vec![fret.to_string()] }))
+            .collect()
+    }
+    
+    /// Leave song mode, restoring the free-play key the song replaced
+    pub fn unload_song_chart(&self) -> Result<(), String> {
+        self.song_player.timed_lock().unload_chart();
+        *self.active_setlist.timed_lock() = None;
+        if let Err(e) = with_audio(|audio| audio.set_backing_track(None)) {
+            log::warn!("Failed to remove backing track: {}", e);
+        }
+        self.restore_free_play_instrument();
+        self.restore_free_play_mapping()
+    }
+    
+    /// Play the loaded chart's key and genre while it stays loaded, so
+    /// noodling along (e.g. in the intro) fits the song
+    fn apply_song_key(&self) -> Result<(), String> {
+        let declared = self.song_player.timed_lock().get_chart()
+            .and_then(|chart| chart.mapping.song_key().map(|key| (key, chart.mapping.genre.clone())));
+        let Some((key, genre)) = declared else {
+            return self.restore_free_play_mapping();
+        };
+        
+        let mut free_play = self.free_play_mapping.timed_lock();
+        if free_play.is_none() {
+            *free_play = Some(self.active_chord_mapping());
+        }
+        drop(free_play);
+        
+        let genre = genre.filter(|genre| parse_genre(genre).is_some());
+        *self.song_genre.timed_lock() = genre.clone();
+        let genre = genre.unwrap_or_else(|| self.active_genre());
+        log::info!("🎼 Song key: {} {} ({})", key.root, key.mode_name(), genre);
+        self.apply_chord_mapping(&genre, &key.root, key.mode_name())
+    }
+    
+    fn restore_free_play_mapping(&self) -> Result<(), String> {
+        let Some((genre, key_root, mode)) = self.free_play_mapping.timed_lock().take() else {
+            return Ok(());
+        };
+        *self.song_genre.timed_lock() = None;
+        log::info!("🎼 Free play key restored: {} {} ({})", key_root, mode, genre);
+        self.apply_chord_mapping(&genre, &key_root, &mode)
+    }
+    
+    /// Load the instrument the loaded song asks for (else the preset's of the
+    /// genre it declares), keeping the free-play one to go back to
+    pub fn apply_song_instrument(&self) {
+        if !self.config.timed_lock().mapping.auto_instrument {
+            return;
+        }
+        let song_instrument = self.song_player.timed_lock().song_instrument();
+        let declared_genre = self.song_genre.timed_lock().is_some();
+        let instrument = song_instrument.or_else(|| {
+            declared_genre.then(|| self.harmonic_mappers[0].timed_lock().preset_instrument().map(str::to_string)).flatten()
+        });
+        match instrument {
+            Some(name) => self.use_song_instrument(&name),
+            None => self.restore_free_play_instrument(),
+        }
+    }
+    
+    /// Load an instrument for the loaded song, keeping the free-play one to go back to
+    fn use_song_instrument(&self, name: &str) {
+        let current = self.config.timed_lock().soundfonts.current.clone();
+        let mut free_play = self.free_play_instrument.timed_lock();
+        if free_play.is_none() {
+            *free_play = current;
+        }
+        drop(free_play);
+        self.switch_instrument(name);
+    }
+    
+    fn restore_free_play_instrument(&self) {
+        let instrument = self.free_play_instrument.timed_lock().take();
+        if let Some(name) = instrument {
+            self.switch_instrument(&name);
+        }
+    }
+    
+    /// Load a setlist's song at `index` with its speed and instrument
+    pub fn play_setlist(&self, setlist: Setlist, index: usize) -> Result<SetlistPosition, String> {
+        let entry = setlist.entries.get(index).cloned()
+            .ok_or_else(|| format!("Setlist '{}' has no entry {}", setlist.name, index + 1))?;
+        let path = self.paths.songs_dir.join(&entry.song);
+        let json = std::fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read song {}: {}", entry.song, e))?;
+        self.load_song_chart(&json)?;
+        self.song_player.timed_lock().set_speed(entry.speed.unwrap_or(1.0));
+        self.sync_backing_track();
+        if let Some(instrument) = &entry.instrument {
+            self.use_song_instrument(instrument);
+        }
+        
+        let position = SetlistPosition { name: setlist.name.clone(), index, count: setlist.entries.len(), song: entry.song };
+        log::info!("📜 Setlist '{}': song {} of {} ({})", position.name, index + 1, position.count, position.song);
+        *self.active_setlist.timed_lock() = Some((setlist, index));
+        Ok(position)
+    }
+    
+    /// Load the next song of the setlist being played (None = it was the last)
+    pub fn next_setlist_song(&self) -> Result<Option<SetlistPosition>, String> {
+        let Some((setlist, index)) = self.active_setlist.timed_lock().clone() else {
+            return Err("No setlist is playing".to_string());
+        };
+        if index + 1 >= setlist.entries.len() {
+            log::info!("📜 Setlist '{}' finished", setlist.name);
+            *self.active_setlist.timed_lock() = None;
+            return Ok(None);
+        }
+        self.play_setlist(setlist, index + 1).map(Some)
+    }
+    
+    /// After a song finished: start the setlist's next song if it auto-advances
+    pub fn advance_setlist(&self) -> Option<SetlistPosition> {
+        let auto_advance = self.active_setlist.timed_lock().as_ref().is_some_and(|(setlist, _)| setlist.auto_advance);
+        if !auto_advance {
+            return None;
+        }
+        match self.next_setlist_song() {
+            Ok(position) => {
+                let position = position?;
+                self.song_player.timed_lock().play();
+                self.sync_backing_track();
+                Some(position)
+            }
+            Err(e) => {
+                log::warn!("Failed to advance setlist: {}", e);
+                None
+            }
+        }
+    }
+    
+    /// Setlist being played and its loaded song
+    pub fn setlist_position(&self) -> Option<SetlistPosition> {
+        self.active_setlist.timed_lock().as_ref().map(|(setlist, index)| SetlistPosition {
+            name: setlist.name.clone(),
+            index: *index,
+            count: setlist.entries.len(),
+            song: setlist.entries[*index].song.clone(),
+        })
+    }
+    
+    /// Take in an edit of the setlist being played, following its loaded song
+    /// to its new place (a setlist without it is left)
+    pub fn refresh_setlist(&self, setlist: &Setlist) {
+        let mut active = self.active_setlist.timed_lock();
+        let Some((playing, index)) = active.as_ref() else {
+            return;
+        };
+        if playing.name != setlist.name {
+            return;
+        }
+        let song = &playing.entries[*index].song;
+        *active = setlist.entries.iter().position(|entry| &entry.song == song)
+            .map(|index| (setlist.clone(), index));
+    }
+    
+    /// Keep playing the loaded song but leave the setlist
+    pub fn stop_setlist(&self) {
+        *self.active_setlist.timed_lock() = None;
+    }
+    
+    /// Genre being played: the loaded song's, else the configured one
+    pub fn active_genre(&self) -> String {
+        self.song_genre.timed_lock().clone()
+            .unwrap_or_else(|| self.config.timed_lock().mapping.genre.clone())
+    }
+    
+    /// Genre, key root and mode being played (as `set_chord_mapping` takes them)
+    fn active_chord_mapping(&self) -> (String, String, String) {
+        let genre = self.active_genre();
+        let (key_root, mode) = if self.uses_legacy_mapper() {
+            let mapper = self.mapper.timed_lock();
+            (Note::from_semitone(mapper.key_root()), if mapper.is_major() { Mode::Major } else { Mode::Minor })
+        } else {
+            self.harmonic_mappers[0].timed_lock().key()
+        };
+        let mode = if mode == Mode::Major { "major" } else { "minor" };
+        (genre, key_root.name().to_string(), mode.to_string())
+    }
+    
+    /// Key player 1 is playing, e.g. "A major"
+    pub fn current_key(&self) -> String {
+        let (_, key_root, mode) = self.active_chord_mapping();
+        format!("{} {}", key_root, mode)
+    }
+    
+    /// Capo position and the resulting key
+    pub fn transpose_info(&self) -> TransposeInfo {
+        let capo = *self.capo.timed_lock();
+        TransposeInfo {
+            offset: capo.offset(),
+            range: capo.range(),
+            key: self.current_key(),
+        }
+    }
+    
+    /// Receive star power activations (forwarded to the frontend as "star-power-activated")
+    pub fn subscribe_star_power(&self) -> mpsc::Receiver<StarPowerActivation> {
+        let (sender, receiver) = mpsc::channel();
+        self.star_power_listeners.timed_lock().push(sender);
+        receiver
+    }
+    
+    /// Switch star power on if the meter allows it, telling the listeners
+    pub fn activate_star_power(&self) -> Option<StarPowerActivation> {
+        let activation = self.song_player.timed_lock().activate_star_power()?;
+        log::info!("⭐ Star power until beat {:.1}", activation.until_beat);
+        self.rumble(RumbleCue::StarPower);
+        self.star_power_listeners.timed_lock().retain(|listener| listener.send(activation).is_ok());
+        Some(activation)
+    }
+    
+    /// Receive capo changes (forwarded to the frontend as "transpose-changed")
+    pub fn subscribe_transpose(&self) -> mpsc::Receiver<TransposeInfo> {
+        let (sender, receiver) = mpsc::channel();
+        self.transpose_listeners.timed_lock().push(sender);
+        receiver
+    }
+    
+    /// Send the capo position to the listeners
+    fn notify_transpose(&self) -> TransposeInfo {
+        let info = self.transpose_info();
+        self.transpose_listeners.timed_lock().retain(|listener| listener.send(info.clone()).is_ok());
+        info
+    }
+    
+    /// Move the capo by `semitones` (stops at the configured range)
+    pub fn transpose(&self, semitones: i8) -> TransposeInfo {
+        let moved = self.capo.timed_lock().shift(semitones);
+        self.move_key(moved);
+        self.transpose_info()
+    }
+    
+    /// Limit how far the capo may move the key, then save the config
+    pub fn set_transpose_range(&self, range: u8) -> Result<TransposeInfo, String> {
+        let moved = self.capo.timed_lock().set_range(range);
+        self.move_key(moved);
+        let mut config = self.config.timed_lock();
+        config.mapping.transpose_range = self.capo.timed_lock().range();
+        config.save().map_err(|e| e.to_string())?;
+        drop(config);
+        Ok(self.transpose_info())
+    }
+    
+    /// Shift every mapper's key root (sounding chords are released by the mappers as usual)
+    fn move_key(&self, semitones: i8) {
+        if semitones == 0 {
+            return;
+        }
+        let shift_legacy = |mapper: &mut Mapper| {
+            let key_root = transpose_root(mapper.key_root(), semitones);
+            mapper.set_key_root(key_root);
+        };
+        shift_legacy(&mut self.mapper.timed_lock());
+        for mapper in &self.player_mappers {
+            shift_legacy(&mut mapper.timed_lock());
+        }
+        for mapper in &self.harmonic_mappers {
+            let mut mapper = mapper.timed_lock();
+            let (key_root, mode) = mapper.key();
+            mapper.set_key(Some(Note::from_semitone(transpose_root(key_root.semitone(), semitones))), Some(mode));
+        }
+        let info = self.notify_transpose();
+        log::info!("🎚️ Capo {:+} ({})", info.offset, info.key);
+    }
+    
+    /// Apply a live change now, or queue it for the next beat/bar when quantizing
+    pub fn request_change(&self, change: LiveChange) -> Result<(), String> {
+        if let Some(due_beat) = self.song_player.timed_lock().defer_change(change.clone()) {
+            log::info!("⏱️ {:?} queued for beat {}", change, due_beat);
+            return Ok(());
+        }
+        self.apply_change(change)
+    }
+    
+    /// Apply queued changes whose beat has come (polled with the song transport)
+    pub fn apply_due_changes(&self) {
+        let changes = self.song_player.timed_lock().take_due_changes();
+        for change in changes {
+            log::info!("⏱️ Applying queued {:?}", change);
+            if let Err(e) = self.apply_change(change) {
+                log::warn!("Failed to apply queued change: {}", e);
+            }
+        }
+    }
+    
+    fn apply_change(&self, change: LiveChange) -> Result<(), String> {
+        match change {
+            LiveChange::Genre(genre) => self.set_genre(&genre),
+            LiveChange::ChordMapping { genre, key_root, mode } => self.set_chord_mapping(&genre, &key_root, &mode),
+            #[cfg(feature = "soundfont")]
+            LiveChange::Instrument(name) => self.set_instrument(name),
+            #[cfg(not(feature = "soundfont"))]
+            LiveChange::Instrument(_) => Err("SoundFont feature not enabled".to_string()),
+        }
+    }
+    
+    pub fn process_controller_input(&self) -> Result<()> {
+        let state = self.get_controller_state();
+        
+        // An open menu takes the guitar over: presses navigate instead of playing
+        {
+            let mut navigator = self.menu_navigator.timed_lock();
+            let actions = navigator.update(&state);
+            if navigator.is_open() {
+                drop(navigator);
+                self.menu_listeners.timed_lock()
+                    .retain(|listener| actions.iter().all(|action| listener.send(*action).is_ok()));
+                // Keep the D-pad edges current so closing the menu doesn't fire them
+                *self.prev_dpad_left.timed_lock() = state.dpad_left;
+                *self.prev_dpad_right.timed_lock() = state.dpad_right;
+                *self.prev_dpad_up.timed_lock() = state.dpad_up;
+                *self.prev_dpad_down.timed_lock() = state.dpad_down;
+                return Ok(());
+            }
+        }
+        
+        // Check for d-pad button presses to switch instruments
+        #[cfg(feature = "soundfont")]
+        {
+            let mut prev_left = self.prev_dpad_left.timed_lock();
+            let mut prev_right = self.prev_dpad_right.timed_lock();
+            
+            // Detect d-pad left press (transition from false to true)
+            if state.dpad_left && !*prev_left {
+                log::info!("🎸 D-Pad Left: switching to previous instrument");
+                if let Err(e) = self.prev_instrument_internal() {
+                    log::warn!("Failed to switch to previous instrument: {}", e);
+                }
+            }
+            
+            // Detect d-pad right press (transition from false to true)
+            if state.dpad_right && !*prev_right {
+                log::info!("🎸 D-Pad Right: switching to next instrument");
+                if let Err(e) = self.next_instrument_internal() {
+                    log::warn!("Failed to switch to next instrument: {}", e);
+                }
+            }
+            
+            // Update previous states
+            *prev_left = state.dpad_left;
+            *prev_right = state.dpad_right;
+        }
+        
+        // D-pad up/down moves the capo (only reported when not used for strum)
+        {
+            let mut prev_up = self.prev_dpad_up.timed_lock();
+            let mut prev_down = self.prev_dpad_down.timed_lock();
+            if state.dpad_up && !*prev_up {
+                self.transpose(1);
+            }
+            if state.dpad_down && !*prev_down {
+                self.transpose(-1);
+            }
+            *prev_up = state.dpad_up;
+            *prev_down = state.dpad_down;
+        }
+        
+        // Tap tempo presses are timestamped by the polling thread
+        {
+            let mut last_tap = self.last_tap_tempo.timed_lock();
+            if state.tap_tempo_timestamp != 0 && state.tap_tempo_timestamp != *last_tap {
+                *last_tap = state.tap_tempo_timestamp;
+                let age = Duration::from_nanos(timestamp_nanos().saturating_sub(state.tap_tempo_timestamp));
+                let at = Instant::now().checked_sub(age).unwrap_or_else(Instant::now);
+                if let Some(bpm) = self.song_player.timed_lock().tap_tempo(at) {
+                    log::info!("🥁 Tap tempo: {} BPM", bpm);
+                }
+            }
+        }
+        
+        // The quick-switch binding cycles config profiles
+        {
+            let mut prev_next_profile = self.prev_next_config_profile.timed_lock();
+            if state.next_config_profile && !*prev_next_profile {
+                if let Err(e) = self.cycle_config_profile() {
+                    log::warn!("Failed to switch config profile: {}", e);
+                }
+            }
+            *prev_next_profile = state.next_config_profile;
+        }
+        
+        // Select releases star power (so does raising the neck, as a tilt action)
+        {
+            let trigger = state.select;
+            let mut prev_trigger = self.prev_star_power_trigger.timed_lock();
+            if trigger && !*prev_trigger {
+                self.activate_star_power();
+            }
+            *prev_trigger = trigger;
+        }
+        
+        // Convert ControllerStateSnapshot to old ControllerState format for mapper
+        let old_state = state.to_controller_state();
+        
+        // Process through the genre presets (or the legacy mapper)
+        let mut events = self.map_input(0, &state, &old_state);
+        if let Some(layer) = self.tilt_layer.timed_lock().as_mut() {
+            events.extend(layer.process(&old_state));
+        }
+        
+        // Raising the neck past the threshold runs the tilt action
+        let tilt_change = self.tilt_trigger.timed_lock().update(state.tilt);
+        if let Some(engaged) = tilt_change {
+            let action = *self.tilt_action.timed_lock();
+            match action {
+                TiltAction::StarPower if engaged => {
+                    self.activate_star_power();
+                }
+                TiltAction::FilterSweep | TiltAction::Overdrive => {
+                    events.push(MusicEvent::ControlChange { cc: ROCK_OUT_CC, value: if engaged { 127 } else { 0 } });
+                }
+                // The layer follows the tilt all the way, not just past the threshold
+                _ => {}
             }
         }
-    }
-    
-    pub fn process_controller_input(&self) -> Result<()> {
-        let state = self.get_controller_state();
         
-        // Check for d-pad button presses to switch instruments
-        #[cfg(feature = "soundfont")]
+        // Latency calibration listens to the strum bar (at the time it moved)
         {
-            let mut prev_left = self.prev_dpad_left.lock().unwrap();
-            let mut prev_right = self.prev_dpad_right.lock().unwrap();
-            
-            // Detect d-pad left press (transition from false to true)
-            if state.dpad_left && !*prev_left {
-                log::info!("🎸 D-Pad Left: switching to previous instrument");
-                if let Err(e) = self.prev_instrument_internal() {
-                    log::warn!("Failed to switch to previous instrument: {}", e);
-                }
+            let mut player = self.song_player.timed_lock();
+            if player.is_calibrating() {
+                let at_nanos = if state.input_timestamp > 0 { state.input_timestamp } else { timestamp_nanos() };
+                player.calibrate_input(at_nanos, state.strum_up || state.strum_down);
             }
-            
-            // Detect d-pad right press (transition from false to true)
-            if state.dpad_right && !*prev_right {
-                log::info!("🎸 D-Pad Right: switching to next instrument");
-                if let Err(e) = self.next_instrument_internal() {
-                    log::warn!("Failed to switch to next instrument: {}", e);
-                }
+        }
+        
+        // Practice analytics count strums and the chord each one played
+        {
+            let strum = state.strum_up || state.strum_down;
+            let strummed = strum && !std::mem::replace(&mut *self.prev_strum.timed_lock(), strum);
+            if strummed {
+                let chord = self.held_chord_name(&state);
+                self.practice.timed_lock().record_strum(chord.as_deref());
             }
-            
-            // Update previous states
-            *prev_left = state.dpad_left;
-            *prev_right = state.dpad_right;
         }
         
-        // Convert ControllerStateSnapshot to old ControllerState format for mapper
-        let old_state = controller_snapshot_to_state(&state);
+        // Record-to-chart follows the guitar while the transport runs
+        if self.song_player.timed_lock().recorder().is_some() {
+            let chord = self.held_chord_name(&state);
+            self.song_player.timed_lock()
+                .record_input(&held_fret_names(&state), state.strum_up || state.strum_down, chord.as_deref());
+        }
         
-        // Process through mapper
-        let events = {
-            let mut mapper = self.mapper.lock().unwrap();
-            mapper.process(&old_state)
-        };
+        if let Some(recorder) = self.macro_recorder.timed_lock().as_mut() {
+            let now = Instant::now();
+            for (action, delay_ms) in events.iter().filter_map(MusicEvent::to_macro_action) {
+                recorder.record(action, now, delay_ms);
+            }
+        }
         
-        // Send events to audio (global)
+        // Send events to audio (global), tagged with the input time for latency stats
         for event in events {
-            send_audio_event(event)?;
+            send_timed_audio_event(0, event, state.input_timestamp)?;
+        }
+        
+        // Additional players route to their own synth
+        for player_index in 1..MAX_PLAYERS {
+            let player_state = self.get_player_controller_state(player_index);
+            if !player_state.connected {
+                continue;
+            }
+            let events = self.map_input(player_index, &player_state, &player_state.to_controller_state());
+            for event in events {
+                send_timed_audio_event(player_index, event, player_state.input_timestamp)?;
+            }
         }
         
         Ok(())
     }
     
+    /// Set the instrument for a player slot (player 1 uses `set_instrument`)
+    #[cfg(feature = "soundfont")]
+    pub fn set_player_instrument(&self, player_index: usize, name: String) -> Result<(), String> {
+        if player_index == 0 {
+            return self.set_instrument(name);
+        }
+        if player_index >= MAX_PLAYERS {
+            return Err(format!("Invalid player index: {}", player_index));
+        }
+        
+        let instrument = {
+            let manager = self.soundfont_manager.timed_lock();
+            manager.get_instrument_by_name(&name)
+                .cloned()
+                .ok_or_else(|| format!("Instrument '{}' not found", name))?
+        };
+        
+        log::info!("🎸 Player {} instrument: {}", player_index + 1, name);
+        match instrument.instrument_type {
+            InstrumentType::SoundFont => {
+                let path = instrument.path.clone()
+                    .ok_or_else(|| "SoundFont instrument missing path".to_string())?;
+                with_audio(|audio| audio.load_player_soundfont(player_index, path))
+                    .map_err(|e| format!("Failed to load soundfont: {}", e))
+            }
+            InstrumentType::Sampler | InstrumentType::Sfz => {
+                let path = instrument.path.clone()
+                    .ok_or_else(|| "Sample instrument missing path".to_string())?;
+                let zones = load_sample_instrument(&path)
+                    .map_err(|e| format!("Failed to load sample instrument: {}", e))?;
+                with_audio(|audio| audio.load_sampler(player_index, zones))
+                    .map_err(|e| format!("Failed to load sample instrument: {}", e))
+            }
+            InstrumentType::Virtual => {
+                let synth_instrument = instrument.get_synth_instrument_type()
+                    .unwrap_or(SynthInstrumentType::CleanElectricGuitar);
+                with_audio(|audio| audio.set_player_virtual_instrument(player_index, synth_instrument))
+                    .map_err(|e| format!("Failed to set virtual instrument: {}", e))
+            }
+        }
+    }
+    
     pub fn get_audio_stats(&self) -> AudioStats {
         with_audio(|audio| Ok(audio.get_stats())).unwrap()
     }
     
+    /// Output levels since the previous read (band levels too if `spectrum`)
+    pub fn get_audio_meters(&self, spectrum: bool) -> AudioMeters {
+        with_audio(|audio| Ok(audio.meters(spectrum))).unwrap_or_default()
+    }
+    
+    /// Whether every SoundFont asked for has finished loading
+    pub fn soundfonts_ready(&self) -> bool {
+        with_audio(|audio| Ok(audio.soundfonts_ready())).unwrap_or(true)
+    }
+    
+    /// SoundFonts that finished loading in the background since the last call
+    pub fn take_loaded_soundfonts(&self) -> Vec<SoundFontLoaded> {
+        with_audio(|audio| Ok(audio.take_loaded_soundfonts())).unwrap_or_default()
+    }
+    
+    /// Enable or disable input latency measurement
+    pub fn set_latency_measurement(&self, enabled: bool) -> Result<()> {
+        with_audio(|audio| {
+            audio.set_latency_measurement(enabled);
+            Ok(())
+        })
+    }
+
+    /// Input-to-audio latency histogram summary
+    pub fn get_latency_stats(&self) -> Result<LatencyStats> {
+        with_audio(|audio| Ok(audio.get_latency_stats()))
+    }
+
+    pub fn reset_latency_stats(&self) -> Result<()> {
+        with_audio(|audio| {
+            audio.reset_latency_stats();
+            Ok(())
+        })
+    }
+
+    /// List audio output devices
+    pub fn list_audio_devices(&self) -> Result<Vec<AudioDeviceInfo>> {
+        with_audio(|audio| audio.list_devices())
+    }
+
+    /// Switch the audio backend (cpal host) and save it
+    pub fn set_audio_backend(&self, backend: String) -> Result<()> {
+        with_audio(|audio| audio.set_host(Some(backend.clone())))?;
+
+        let mut config = self.config.timed_lock();
+        config.audio.backend = backend;
+        // Device names are per backend
+        config.audio.output_device = None;
+        let config_clone = config.clone();
+        drop(config);
+
+        if let Err(e) = config_clone.save() {
+            log::warn!("Failed to save config after changing audio backend: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Route audio to another output device (None = system default) and save it
+    pub fn set_audio_device(&self, device_name: Option<String>) -> Result<()> {
+        with_audio(|audio| audio.set_device(device_name.clone()))?;
+
+        let mut config = self.config.timed_lock();
+        config.audio.output_device = device_name;
+        let config_clone = config.clone();
+        drop(config);
+
+        if let Err(e) = config_clone.save() {
+            log::warn!("Failed to save config after changing audio device: {}", e);
+        }
+        Ok(())
+    }
+
     /// Check audio health and attempt reconnection if needed
     pub fn check_and_reconnect_audio(&self) -> Result<bool> {
         check_audio_health()
@@ -306,7 +2263,7 @@ impl AppState {
     /// Set the release time multiplier
     pub fn set_release_multiplier(&self, multiplier: f32) -> Result<()> {
         // Update config
-        let mut config = self.config.lock().unwrap();
+        let mut config = self.config.timed_lock();
         config.audio.release_time_multiplier = multiplier;
         let config_clone = config.clone();
         drop(config);
@@ -320,6 +2277,144 @@ impl AppState {
         with_audio(|audio| audio.set_release_multiplier(multiplier))
     }
     
+    /// Set the A4 reference frequency (clamped to 432-446 Hz), returns the applied value
+    pub fn set_a4_frequency(&self, frequency: f32) -> Result<f32> {
+        let frequency = audio::synth::clamp_a4_frequency(frequency);
+        let mut config = self.config.timed_lock();
+        config.audio.a4_frequency = frequency;
+        let config_clone = config.clone();
+        drop(config);
+
+        if let Err(e) = config_clone.save() {
+            log::warn!("Failed to save config after setting A4 frequency: {}", e);
+        }
+
+        with_audio(|audio| audio.set_a4_frequency(frequency))?;
+        Ok(frequency)
+    }
+
+    /// Set the chord stereo spread (clamped to 0.0-1.0), returns the applied value
+    pub fn set_stereo_spread(&self, width: f32) -> Result<f32> {
+        let width = audio::synth::clamp_stereo_spread(width);
+        let mut config = self.config.timed_lock();
+        config.audio.stereo_spread = width;
+        let config_clone = config.clone();
+        drop(config);
+
+        if let Err(e) = config_clone.save() {
+            log::warn!("Failed to save config after setting stereo spread: {}", e);
+        }
+
+        with_audio(|audio| audio.set_stereo_spread(width))?;
+        Ok(width)
+    }
+
+    /// Set the virtual instrument polyphony (clamped), returns the applied value
+    pub fn set_max_voices(&self, voices: usize) -> Result<usize> {
+        let voices = audio::synth::clamp_max_voices(voices);
+        let mut config = self.config.timed_lock();
+        config.audio.max_voices = voices;
+        let config_clone = config.clone();
+        drop(config);
+
+        if let Err(e) = config_clone.save() {
+            log::warn!("Failed to save config after setting polyphony: {}", e);
+        }
+
+        with_audio(|audio| audio.set_max_voices(voices))?;
+        Ok(voices)
+    }
+
+    /// Set a mixer fader ("master", "synth", "backing_track", "metronome" or
+    /// "drums"), returns the applied gain
+    pub fn set_mixer_level(&self, channel: &str, gain: f32) -> Result<f32> {
+        let channel = MixerChannel::from_name(channel)
+            .ok_or_else(|| anyhow::anyhow!("Unknown mixer channel: {}", channel))?;
+        let gain = with_audio(|audio| audio.set_mixer_level(channel, gain))?;
+        let mut config = self.config.timed_lock();
+        if let Some(level) = config.audio.mixer.level_mut(channel.name()) {
+            *level = gain;
+        }
+        let config_clone = config.clone();
+        drop(config);
+
+        if let Err(e) = config_clone.save() {
+            log::warn!("Failed to save config after setting the {} level: {}", channel.name(), e);
+        }
+        Ok(gain)
+    }
+
+    /// Mixer fader gains
+    pub fn mixer_levels(&self) -> MixerConfig {
+        self.config.timed_lock().audio.mixer.clone()
+    }
+
+    /// List input devices for monitoring
+    pub fn list_input_devices(&self) -> Result<Vec<AudioDeviceInfo>> {
+        with_audio(|audio| audio.list_input_devices())
+    }
+
+    /// Start or stop input monitoring and save the settings
+    pub fn set_input_monitor(&self, settings: InputMonitorConfig) -> Result<()> {
+        with_audio(|audio| {
+            if settings.enabled {
+                audio.start_input_monitor(settings.device.as_deref(), settings.channel, settings.gain)
+            } else {
+                audio.stop_input_monitor();
+                Ok(())
+            }
+        })?;
+
+        let mut config = self.config.timed_lock();
+        config.audio.input_monitor = InputMonitorConfig {
+            gain: audio::monitor::clamp_monitor_gain(settings.gain),
+            ..settings
+        };
+        let config_clone = config.clone();
+        drop(config);
+
+        if let Err(e) = config_clone.save() {
+            log::warn!("Failed to save config after changing input monitoring: {}", e);
+        }
+        Ok(())
+    }
+
+    /// Set the monitoring level and save it
+    pub fn set_input_monitor_gain(&self, gain: f32) -> Result<f32> {
+        let gain = with_audio(|audio| Ok(audio.set_input_monitor_gain(gain)))?;
+        let mut config = self.config.timed_lock();
+        config.audio.input_monitor.gain = gain;
+        let config_clone = config.clone();
+        drop(config);
+
+        if let Err(e) = config_clone.save() {
+            log::warn!("Failed to save config after setting monitor gain: {}", e);
+        }
+        Ok(gain)
+    }
+
+    /// Input monitoring level and latency
+    pub fn input_monitor_stats(&self) -> Result<InputMonitorStats> {
+        with_audio(|audio| Ok(audio.input_monitor_stats()))
+    }
+
+    /// Select the temperament for virtual instruments and save it
+    pub fn set_temperament(&self, temperament: TemperamentConfig) -> Result<()> {
+        let tuning = tuning_table(&temperament)?;
+        with_audio(|audio| audio.set_tuning(tuning))?;
+        log::info!("🎼 Temperament: {} (root {})", temperament.preset, temperament.root);
+
+        let mut config = self.config.timed_lock();
+        config.audio.temperament = temperament;
+        let config_clone = config.clone();
+        drop(config);
+
+        if let Err(e) = config_clone.save() {
+            log::warn!("Failed to save config after setting temperament: {}", e);
+        }
+        Ok(())
+    }
+
     /// Enable or disable sustain mode
     pub fn set_sustain_enabled(&self, enabled: bool) -> Result<()> {
         with_audio(|audio| audio.set_sustain_enabled(enabled))
@@ -330,21 +2425,161 @@ impl AppState {
         with_audio(|audio| audio.set_sustain_release_time(time_seconds))
     }
     
+    /// Names of the stored config profiles
+    pub fn list_config_profiles(&self) -> Result<Vec<String>, String> {
+        self.config_profiles.timed_lock().list().map_err(|e| e.to_string())
+    }
+    
+    /// Save the current audio, mapping and instrument settings as a profile
+    pub fn save_config_profile(&self, name: &str) -> Result<ConfigProfile, String> {
+        let profile = ConfigProfile::capture(name.trim(), &self.config.timed_lock());
+        self.config_profiles.timed_lock().save(&profile).map_err(|e| e.to_string())?;
+        log::info!("💾 Saved config profile: {}", profile.name);
+        Ok(profile)
+    }
+    
+    pub fn delete_config_profile(&self, name: &str) -> Result<(), String> {
+        self.config_profiles.timed_lock().delete(name).map_err(|e| e.to_string())?;
+        let mut config = self.config.timed_lock();
+        if config.active_profile.as_deref() == Some(name) {
+            config.active_profile = None;
+            config.save().map_err(|e| e.to_string())?;
+        }
+        Ok(())
+    }
+    
+    /// Switch every setting a config profile holds: the audio stream (a new
+    /// buffer size reopens it), engine, mappers and instrument
+    pub fn apply_config_profile(&self, name: &str) -> Result<ConfigProfile, String> {
+        let profile = self.config_profiles.timed_lock().load(name).map_err(|e| e.to_string())?;
+        let (previous, config) = {
+            let mut config = self.config.timed_lock();
+            let previous = config.clone();
+            profile.apply_to(&mut config);
+            (previous, config.clone())
+        };
+        if let Err(e) = config.save() {
+            log::warn!("Failed to save config after applying profile {}: {}", profile.name, e);
+        }
+        self.apply_settings(&previous, &config)?;
+        
+        log::info!("🎛️ Config profile: {}", profile.name);
+        self.config_profile_listeners.timed_lock()
+            .retain(|listener| listener.send(profile.name.clone()).is_ok());
+        Ok(profile)
+    }
+    
+    /// Push a config's audio, mapping and instrument settings to the running
+    /// app (`previous` tells what needs a restart, such as the buffer size)
+    fn apply_settings(&self, previous: &AppConfig, config: &AppConfig) -> Result<(), String> {
+        if config.audio.buffer_size != previous.audio.buffer_size {
+            if let Err(e) = with_audio(|audio| audio.set_buffer_size(Some(config.audio.buffer_size))) {
+                log::warn!("Failed to switch the audio buffer size: {}", e);
+            }
+        }
+        apply_audio_settings(&config.audio);
+        self.set_sustain_enabled(config.audio.sustain_enabled).map_err(|e| e.to_string())?;
+        self.set_sustain_release_time(config.audio.sustain_release_time_ms / 1000.0).map_err(|e| e.to_string())?;
+        
+        let mapping = &config.mapping;
+        self.set_legacy_mapper(mapping.legacy_mapper)?;
+        self.set_genre(&mapping.genre)?;
+        let dynamics_mode = DynamicsMode::from_name(&mapping.dynamics_mode).unwrap_or_default();
+        let palm_mute_trigger = PalmMuteTrigger::from_name(&mapping.palm_mute_trigger).unwrap_or_default();
+        let bend_target = BendTarget::from_name(&mapping.bend_target).unwrap_or_default();
+        let whammy_pull = WhammyPullEffect::from_name(&mapping.whammy_pull_effect).unwrap_or_default();
+        for mapper in std::iter::once(&self.mapper).chain(self.player_mappers.iter()) {
+            let mut mapper = mapper.timed_lock();
+            mapper.set_strum_stagger(false, mapping.strum_spread_ms);
+            mapper.set_guitar_voicing(mapping.guitar_voicing, mapping.strum_spread_ms);
+            if mapping.strum_stagger {
+                mapper.set_strum_stagger(true, mapping.strum_spread_ms);
+            }
+            mapper.set_dynamics_mode(dynamics_mode);
+            mapper.set_palm_mute_trigger(palm_mute_trigger);
+            mapper.set_bend_target(bend_target);
+            mapper.set_whammy_split(mapping.whammy_split, whammy_pull);
+        }
+        for harmonic_mapper in &self.harmonic_mappers {
+            let mut harmonic_mapper = harmonic_mapper.timed_lock();
+            harmonic_mapper.set_dynamics_mode(dynamics_mode);
+            harmonic_mapper.set_palm_mute_trigger(palm_mute_trigger);
+            harmonic_mapper.set_diatonic(mapping.diatonic_chords);
+        }
+        self.set_whammy_mode(WhammyMode::from_name(&mapping.whammy_mode).unwrap_or_default())?;
+        self.set_tilt_mode(&mapping.tilt_mode)?;
+        
+        #[cfg(feature = "soundfont")]
+        if config.soundfonts.current != previous.soundfonts.current {
+            if let Some(instrument) = config.soundfonts.current.clone() {
+                self.set_instrument(instrument)?;
+            }
+        }
+        Ok(())
+    }
+    
+    /// Take over the config file after an edit outside the app. Returns the
+    /// new config, or None when it matches the one in use (e.g. our own save).
+    pub fn reload_config(&self) -> Result<Option<AppConfig>, String> {
+        let config = AppConfig::load_from(&self.paths.config_file).map_err(|e| format!("{:#}", e))?;
+        let previous = {
+            let mut current = self.config.timed_lock();
+            if serde_json::to_value(&*current).ok() == serde_json::to_value(&config).ok() {
+                return Ok(None);
+            }
+            std::mem::replace(&mut *current, config.clone())
+        };
+        log::info!("🔄 Config file changed on disk, applying it");
+        self.apply_settings(&previous, &config)?;
+        self.controller.timed_lock().set_rumble_enabled(config.controller.rumble);
+        Ok(Some(self.config.timed_lock().clone()))
+    }
+    
+    /// Load the genre presets again after an edit to the chordmaps directory
+    pub fn reload_chordmaps(&self) {
+        let resolver = Arc::new(load_chord_resolver());
+        for harmonic_mapper in &self.harmonic_mappers {
+            harmonic_mapper.timed_lock().set_resolver(Arc::clone(&resolver));
+        }
+        self.apply_whammy_smoothing();
+        self.apply_preset_layer();
+        log::info!("🔄 Genre presets reloaded");
+    }
+    
+    /// Apply the profile after the active one (name order, wrapping around)
+    pub fn cycle_config_profile(&self) -> Result<Option<ConfigProfile>, String> {
+        let current = self.config.timed_lock().active_profile.clone();
+        let next = self.config_profiles.timed_lock().next_after(current.as_deref()).map_err(|e| e.to_string())?;
+        next.map(|name| self.apply_config_profile(&name)).transpose()
+    }
+    
+    /// Config profile applied last (None = settings changed by hand)
+    pub fn active_config_profile(&self) -> Option<String> {
+        self.config.timed_lock().active_profile.clone()
+    }
+    
+    /// Receive the name of each config profile applied
+    pub fn subscribe_config_profiles(&self) -> mpsc::Receiver<String> {
+        let (sender, receiver) = mpsc::channel();
+        self.config_profile_listeners.timed_lock().push(sender);
+        receiver
+    }
+    
     #[cfg(feature = "soundfont")]
     pub fn get_available_instruments(&self) -> Result<Vec<InstrumentInfo>, String> {
-        let manager = self.soundfont_manager.lock().unwrap();
+        let manager = self.soundfont_manager.timed_lock();
         Ok(manager.list_instruments().to_vec())
     }
 
     #[cfg(feature = "soundfont")]
     pub fn get_available_soundfonts(&self) -> Result<Vec<SoundFontInfo>, String> {
-        let manager = self.soundfont_manager.lock().unwrap();
+        let manager = self.soundfont_manager.timed_lock();
         Ok(manager.list().to_vec())
     }
     
     #[cfg(feature = "soundfont")]
     pub fn next_instrument_internal(&self) -> Result<(), String> {
-        let manager = self.soundfont_manager.lock().unwrap();
+        let manager = self.soundfont_manager.timed_lock();
         let instruments = manager.list_instruments();
         
         if instruments.is_empty() {
@@ -352,7 +2587,7 @@ impl AppState {
         }
         
         // Get current instrument name from config
-        let config = self.config.lock().unwrap();
+        let config = self.config.timed_lock();
         let current_name = config.soundfonts.current.clone();
         drop(config);
         
@@ -372,12 +2607,12 @@ impl AppState {
         drop(manager);
         
         // Load the next instrument
-        self.set_instrument(next_name)
+        self.request_change(LiveChange::Instrument(next_name))
     }
     
     #[cfg(feature = "soundfont")]
     pub fn prev_instrument_internal(&self) -> Result<(), String> {
-        let manager = self.soundfont_manager.lock().unwrap();
+        let manager = self.soundfont_manager.timed_lock();
         let instruments = manager.list_instruments();
         
         if instruments.is_empty() {
@@ -385,7 +2620,7 @@ impl AppState {
         }
         
         // Get current instrument name from config
-        let config = self.config.lock().unwrap();
+        let config = self.config.timed_lock();
         let current_name = config.soundfonts.current.clone();
         drop(config);
         
@@ -405,14 +2640,14 @@ impl AppState {
         drop(manager);
         
         // Load the previous instrument
-        self.set_instrument(prev_name)
+        self.request_change(LiveChange::Instrument(prev_name))
     }
 
-    /// Set instrument (handles both SoundFonts and Virtual instruments)
+    /// Set instrument (handles SoundFonts, sample folders and Virtual instruments)
     #[cfg(feature = "soundfont")]
     pub fn set_instrument(&self, name: String) -> Result<(), String> {
         let (instrument_type, instrument_path, instrument_info) = {
-            let manager = self.soundfont_manager.lock().unwrap();
+            let manager = self.soundfont_manager.timed_lock();
             let instrument = manager.get_instrument_by_name(&name)
                 .ok_or_else(|| format!("Instrument '{}' not found", name))?;
             
@@ -425,7 +2660,7 @@ impl AppState {
                 if let Some(path) = instrument_path {
                     // Update config
                     {
-                        let mut config = self.config.lock().unwrap();
+                        let mut config = self.config.timed_lock();
                         config.soundfonts.current = Some(name.clone());
                         let _ = config.save(); // Don't fail on save errors
                     }
@@ -441,10 +2676,25 @@ impl AppState {
                     Err("SoundFont instrument missing path".to_string())
                 }
             },
+            InstrumentType::Sampler | InstrumentType::Sfz => {
+                let path = instrument_path
+                    .ok_or_else(|| "Sample instrument missing path".to_string())?;
+                {
+                    let mut config = self.config.timed_lock();
+                    config.soundfonts.current = Some(name.clone());
+                    let _ = config.save(); // Don't fail on save errors
+                }
+                
+                log::info!("Set sample instrument to: {}", name);
+                let zones = load_sample_instrument(&path)
+                    .map_err(|e| format!("Failed to load sample instrument: {}", e))?;
+                with_audio(|audio| audio.load_sampler(0, zones))
+                    .map_err(|e| format!("Failed to load sample instrument: {}", e))
+            }
             InstrumentType::Virtual => {
                 // Update config to mark as virtual instrument
                 {
-                    let mut config = self.config.lock().unwrap();
+                    let mut config = self.config.timed_lock();
                     config.soundfonts.current = Some(name.clone());
                     let _ = config.save(); // Don't fail on save errors
                 }
@@ -467,9 +2717,78 @@ impl AppState {
         }
     }
     
+    /// Render (or fetch from cache) a short WAV preview of an instrument.
+    /// Uses a private offline engine, the live audio stream is untouched.
+    #[cfg(feature = "soundfont")]
+    pub fn render_instrument_preview(&self, name: &str) -> Result<Vec<u8>, String> {
+        if let Some(wav) = self.preview_cache.timed_lock().get(name) {
+            return Ok(wav.clone());
+        }
+        
+        let instrument = {
+            let manager = self.soundfont_manager.timed_lock();
+            manager.get_instrument_by_name(name)
+                .cloned()
+                .ok_or_else(|| format!("Instrument '{}' not found", name))?
+        };
+        
+        let source = offline_source(&instrument)?;
+        
+        let wav = render_preview_wav(&source, &PreviewOptions::default())
+            .map_err(|e| format!("Failed to render preview: {}", e))?;
+        log::info!("🎧 Rendered preview for {} ({} bytes)", name, wav.len());
+        
+        self.preview_cache.timed_lock().insert(name.to_string(), wav.clone());
+        Ok(wav)
+    }
+    
+    /// Bounce the loaded chart to a WAV file with the current instrument, at
+    /// file speed. Returns the number of chords rendered; `progress` gets the
+    /// fraction done and can stop the render by returning an error.
+    pub fn render_song_to_wav(&self, path: &std::path::Path, progress: impl FnMut(f32) -> anyhow::Result<()>) -> Result<usize, String> {
+        let chart = self.song_player.timed_lock().get_chart().cloned()
+            .ok_or_else(|| "No song loaded".to_string())?;
+        let seconds_per_beat = 60.0 / chart.clock.bpm;
+        let mut skipped = 0;
+        let chords: Vec<BounceChord> = chart.lanes.iter()
+            .flat_map(|lane| &lane.events)
+            .filter_map(|event| {
+                let chord = BounceChord::from_symbol(&event.chord, event.beat * seconds_per_beat, event.dur * seconds_per_beat, mapping::dynamics::DEFAULT_VELOCITY);
+                skipped += usize::from(chord.is_none());
+                chord
+            })
+            .collect();
+        if skipped > 0 {
+            log::warn!("Bounce skips {} chords with unknown symbols", skipped);
+        }
+        if chords.is_empty() {
+            return Err("The chart has no chords to render".to_string());
+        }
+
+        let source = self.current_offline_source();
+        let wav = render_bounce_wav(&source, &chords, &BounceOptions::default(), progress)
+            .map_err(|e| format!("Failed to render '{}': {}", chart.meta.title, e))?;
+        std::fs::write(path, wav).map_err(|e| format!("Failed to write {}: {}", path.display(), e))?;
+        log::info!("💿 Bounced '{}' ({} chords) to {}", chart.meta.title, chords.len(), path.display());
+        Ok(chords.len())
+    }
+
+    /// The current instrument for an offline engine (the basic guitar if it can't be used)
+    fn current_offline_source(&self) -> PreviewSource {
+        #[cfg(feature = "soundfont")]
+        {
+            let current = self.config.timed_lock().soundfonts.current.clone();
+            let instrument = current.and_then(|name| self.soundfont_manager.timed_lock().get_instrument_by_name(&name).cloned());
+            if let Some(source) = instrument.and_then(|instrument| offline_source(&instrument).ok()) {
+                return source;
+            }
+        }
+        PreviewSource::Virtual(SynthInstrumentType::CleanElectricGuitar)
+    }
+    
     #[cfg(feature = "soundfont")]
     pub fn set_soundfont(&self, name: String) -> Result<(), String> {
-        let manager = self.soundfont_manager.lock().unwrap();
+        let manager = self.soundfont_manager.timed_lock();
         let soundfont = manager.get_by_name(&name)
             .ok_or_else(|| format!("SoundFont not found: {}", name))?;
         
@@ -481,7 +2800,7 @@ impl AppState {
             .map_err(|e| format!("Failed to load soundfont: {}", e))?;
         
         // Update config
-        let mut config = self.config.lock().unwrap();
+        let mut config = self.config.timed_lock();
         config.soundfonts.current = Some(name.clone());
         config.save().map_err(|e| e.to_string())?;
         
@@ -521,7 +2840,8 @@ impl AppState {
         .map_err(|e| format!("Failed to scan soundfonts: {}", e))?;
         
         let count = new_manager.list().len();
-        *self.soundfont_manager.lock().unwrap() = new_manager;
+        *self.soundfont_manager.timed_lock() = new_manager;
+        self.preview_cache.timed_lock().clear();
         
         log::info!("Rescanned soundfonts: found {} files", count);
         Ok(())
@@ -533,28 +2853,147 @@ pub fn send_audio_event(event: MusicEvent) -> Result<()> {
     with_audio(|audio| audio.send_event(event))
 }
 
-/// Convert new ControllerStateSnapshot to old ControllerState format for mapper compatibility
-fn controller_snapshot_to_state(snapshot: &ControllerStateSnapshot) -> ControllerState {
-    let mut state = ControllerState::default();
-    
-    // Map buttons
-    state.buttons.insert(ControlId::FretGreen, snapshot.fret_green);
-    state.buttons.insert(ControlId::FretRed, snapshot.fret_red);
-    state.buttons.insert(ControlId::FretBlue, snapshot.fret_blue);
-    state.buttons.insert(ControlId::FretYellow, snapshot.fret_yellow);
-    state.buttons.insert(ControlId::FretOrange, snapshot.fret_orange);
-    state.buttons.insert(ControlId::StrumUp, snapshot.strum_up);
-    state.buttons.insert(ControlId::StrumDown, snapshot.strum_down);
-    // Skip dpad_up and dpad_down as they're not in the original ControlId enum
-    state.buttons.insert(ControlId::DPadLeft, snapshot.dpad_left);
-    state.buttons.insert(ControlId::DPadRight, snapshot.dpad_right);
-    state.buttons.insert(ControlId::Start, snapshot.start);
-    state.buttons.insert(ControlId::Select, snapshot.select);
-    
-    // Map axes
-    state.axes.insert(ControlId::WhammyBar, snapshot.whammy_bar);
-    
-    state
+/// Send an event to a specific player's synth, with the timestamp of the
+/// controller input that caused it (0 = untimed)
+pub fn send_timed_audio_event(player_index: usize, event: MusicEvent, input_ns: u64) -> Result<()> {
+    with_audio(|audio| audio.send_timed_player_event(player_index, event, input_ns))
+}
+
+/// Offline engine source (previews and bounces) for an instrument
+#[cfg(feature = "soundfont")]
+fn offline_source(instrument: &InstrumentInfo) -> Result<PreviewSource, String> {
+    Ok(match instrument.instrument_type {
+        InstrumentType::SoundFont => PreviewSource::SoundFont(
+            instrument.path.clone()
+                .ok_or_else(|| "SoundFont instrument missing path".to_string())?,
+        ),
+        InstrumentType::Virtual => PreviewSource::Virtual(
+            instrument.get_synth_instrument_type()
+                .unwrap_or(SynthInstrumentType::CleanElectricGuitar),
+        ),
+        InstrumentType::Sampler | InstrumentType::Sfz => PreviewSource::Sampler(
+            instrument.path.clone()
+                .ok_or_else(|| "Sample instrument missing path".to_string())?,
+        ),
+    })
+}
+
+/// Build the synth tuning table for a temperament selection
+fn tuning_table(temperament: &TemperamentConfig) -> Result<TuningTable> {
+    match Temperament::from_name(&temperament.preset)? {
+        Temperament::Custom => TuningTable::custom(temperament.root, &temperament.custom_cents),
+        preset => Ok(TuningTable::preset(preset, temperament.root)),
+    }
+}
+
+/// Push the engine settings of an audio config (tuning, release, spread,
+/// polyphony and mixer) to the audio output
+fn apply_audio_settings(settings: &AudioConfig) {
+    let release_multiplier = settings.release_time_multiplier;
+    if let Err(e) = with_audio(|audio| audio.set_release_multiplier(release_multiplier)) {
+        log::error!("Failed to set release multiplier: {}", e);
+    } else {
+        log::info!("✅ Release time multiplier set to: {}", release_multiplier);
+    }
+
+    let a4_frequency = settings.a4_frequency;
+    if let Err(e) = with_audio(|audio| audio.set_a4_frequency(a4_frequency)) {
+        log::error!("Failed to set A4 frequency: {}", e);
+    }
+    match tuning_table(&settings.temperament) {
+        Ok(tuning) => {
+            if let Err(e) = with_audio(|audio| audio.set_tuning(tuning)) {
+                log::error!("Failed to set temperament: {}", e);
+            }
+        }
+        Err(e) => log::warn!("Invalid temperament in config, using equal temperament: {}", e),
+    }
+    let stereo_spread = settings.stereo_spread;
+    if let Err(e) = with_audio(|audio| audio.set_stereo_spread(stereo_spread)) {
+        log::error!("Failed to set stereo spread: {}", e);
+    }
+    let max_voices = settings.max_voices;
+    if let Err(e) = with_audio(|audio| audio.set_max_voices(max_voices)) {
+        log::error!("Failed to set polyphony: {}", e);
+    }
+    for &channel in MixerChannel::all() {
+        let gain = settings.mixer.level(channel.name()).unwrap_or(1.0);
+        if let Err(e) = with_audio(|audio| audio.set_mixer_level(channel, gain)) {
+            log::error!("Failed to set {} level: {}", channel.name(), e);
+        }
+    }
+}
+
+/// Genre by its config name
+pub fn parse_genre(name: &str) -> Option<Genre> {
+    match name.to_lowercase().as_str() {
+        "punk" => Some(Genre::Punk),
+        "rock" => Some(Genre::Rock),
+        "edm" => Some(Genre::Edm),
+        "metal" => Some(Genre::Metal),
+        "folk" => Some(Genre::Folk),
+        "pop" => Some(Genre::Pop),
+        _ => None,
+    }
+}
+
+/// Assets directory holding the genre presets (`chordmaps/`), if any
+pub fn find_assets_dir() -> Option<PathBuf> {
+    ["assets", "../assets", "../../assets", "../../../assets"]
+        .into_iter()
+        .map(PathBuf::from)
+        .find(|path| path.join("chordmaps").exists())
+}
+
+/// Genre presets from the workspace assets, or the built-in ones when they
+/// can't be found (installed builds)
+fn load_chord_resolver() -> ChordResolver {
+    let Some(assets_dir) = find_assets_dir() else {
+        log::info!("No chordmaps directory found, using built-in genre presets");
+        return PresetLoader::default_resolver();
+    };
+    tauri::async_runtime::block_on(PresetLoader::new(&assets_dir).load_all_presets())
+        .unwrap_or_else(|e| {
+            log::warn!("Failed to load genre presets from {:?}: {}", assets_dir, e);
+            PresetLoader::default_resolver()
+        })
+}
+
+/// Seconds since the Unix epoch
+pub fn unix_seconds() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// Effect the audio engine switches on for a tilt action
+fn rock_out_effect(action: TiltAction) -> Option<RockOutEffect> {
+    match action {
+        TiltAction::FilterSweep => Some(RockOutEffect::FilterSweep),
+        TiltAction::Overdrive => Some(RockOutEffect::Overdrive),
+        TiltAction::StarPower | TiltAction::Layer => None,
+    }
+}
+
+/// Engine layer for a preset's layer settings (None for an unknown instrument)
+fn instrument_layer(defaults: Option<&LayerDefaults>) -> Option<InstrumentLayer> {
+    let defaults = defaults?;
+    let Some(instrument) = SynthInstrumentType::from_name(&defaults.instrument) else {
+        log::warn!("Unknown preset layer instrument: {}", defaults.instrument);
+        return None;
+    };
+    Some(InstrumentLayer { instrument, gain: defaults.gain, octave: defaults.octave })
+}
+
+/// Main frets held in a snapshot, named as charts name them
+pub fn held_fret_names(snapshot: &ControllerStateSnapshot) -> Vec<String> {
+    [
+        (snapshot.fret_green, "GREEN"),
+        (snapshot.fret_red, "RED"),
+        (snapshot.fret_yellow, "YELLOW"),
+        (snapshot.fret_blue, "BLUE"),
+        (snapshot.fret_orange, "ORANGE"),
+    ].into_iter().filter(|(pressed, _)| *pressed).map(|(_, name)| name.to_string()).collect()
 }
 
 /// Check audio health and reconnect if needed
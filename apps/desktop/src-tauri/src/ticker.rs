@@ -0,0 +1,78 @@
+//! Clock-driven background work
+//! One thread wakes every millisecond and runs everything that has to happen
+//! on time rather than in answer to a command: controller macro steps every
+//! tick, the song transport (queued changes, count-in clicks, practice loop
+//! jumps, note waits, the end of the song) every other tick, finished
+//! SoundFont loads every 50 ms and the audio underrun check once a second.
+
+use serde::Serialize;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::state::AppState;
+
+/// Macro steps keep millisecond timing
+const TICK: Duration = Duration::from_millis(1);
+/// Ticks between polls of the slower jobs
+const SONG_TICKS: u64 = 2;
+const SOUNDFONT_TICKS: u64 = 50;
+const UNDERRUN_TICKS: u64 = 1000;
+
+/// Run the clock-driven work on a background thread
+pub fn spawn(app: AppHandle) {
+    std::thread::spawn(move || {
+        let mut underruns = audio::UnderrunMonitor::new();
+        for tick in 1u64.. {
+            std::thread::sleep(TICK);
+            let state = app.state::<AppState>();
+            state.run_macros();
+            if tick % SONG_TICKS == 0 {
+                poll_song(&app, &state);
+            }
+            if tick % SOUNDFONT_TICKS == 0 {
+                for loaded in state.take_loaded_soundfonts() {
+                    emit(&app, "soundfont-loaded", &loaded);
+                }
+            }
+            if tick % UNDERRUN_TICKS == 0 {
+                // Warn the frontend when audio underruns pile up
+                let stats = state.get_audio_stats();
+                if let Some(alert) = underruns.update(stats.underruns, stats.buffer_size, Instant::now()) {
+                    log::warn!("⚠️ {} audio underruns in the last few seconds (buffer {})", alert.recent, alert.buffer_size);
+                    emit(&app, "audio-underruns", &alert);
+                }
+            }
+        }
+    });
+}
+
+/// Apply bar-quantized changes on their beat, click the count-in and flash it
+/// in the UI; follow practice loop jumps, record finished songs and move on
+/// to the setlist's next one
+fn poll_song(app: &AppHandle, state: &AppState) {
+    state.apply_due_changes();
+    if let Some(beat) = state.poll_count_in() {
+        emit(app, "count-in-beat", &beat);
+    }
+    if let Some(passes) = state.poll_song_loop() {
+        emit(app, "song-loop-pass", passes);
+        if let Some(pass) = state.poll_speed_trainer() {
+            emit(app, "speed-trainer-pass", &pass);
+        }
+    }
+    if let Some(waiting) = state.poll_note_wait() {
+        emit(app, "song-waiting-for-note", waiting);
+    }
+    if let Some(finished) = state.poll_song_end() {
+        emit(app, "song-finished", &finished);
+        if let Some(position) = state.advance_setlist() {
+            emit(app, "setlist-advanced", &position);
+        }
+    }
+}
+
+fn emit<S: Serialize + Clone>(app: &AppHandle, event: &str, payload: S) {
+    if let Err(e) = app.emit(event, payload) {
+        log::warn!("Failed to emit {}: {}", event, e);
+    }
+}
@@ -1,5 +1,6 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { runJob } from "./jobs";
 import "./App.css";
 import MenuBar from "./components/MenuBar";
 import LiveView from "./components/LiveView";
@@ -17,6 +18,15 @@ import "./components/FretButton.css";
 import "./components/FretBoard.css";
 import "./components/ChordMappingControls.css";
 
+interface RecoverableSession {
+  title: string;
+  artist: string;
+  savedAt: number;
+  unsavedEdits: boolean;
+  beat: number;
+  score: number;
+}
+
 type View = "live" | "diagnostics" | "mapping-wizard" | "profile-manager" | "song-play" | "song-library" | "audio-settings";
 
 function App() {
@@ -27,11 +37,16 @@ function App() {
   const [showVirtualInstrumentSelector, setShowVirtualInstrumentSelector] = useState(false);
   const [showAboutDialog, setShowAboutDialog] = useState(false);
   const [isRescanningSoundFonts, setIsRescanningSoundFonts] = useState(false);
+  const [recoverableSession, setRecoverableSession] = useState<RecoverableSession | null>(null);
 
   useEffect(() => {
     // Only load genre info and start audio health check if startup menu is not shown
     if (!showStartupMenu) {
       loadGenreInfo();
+      // Offer the song session the last run left behind when it crashed
+      invoke<RecoverableSession | null>("get_recoverable_session")
+        .then(setRecoverableSession)
+        .catch((error) => console.error("Failed to check for a session to recover:", error));
       
       // Periodically check audio health and reconnect if needed
       const audioHealthCheck = setInterval(async () => {
@@ -49,6 +64,20 @@ function App() {
     }
   }, [showStartupMenu]);
 
+  const handleRecoverSession = async (recover: boolean) => {
+    setRecoverableSession(null);
+    try {
+      if (recover) {
+        await invoke("recover_last_session");
+        setCurrentView("song-play");
+      } else {
+        await invoke("discard_recovered_session");
+      }
+    } catch (error) {
+      console.error("Failed to recover session:", error);
+    }
+  };
+
   const loadGenreInfo = async () => {
     try {
       const info = await invoke("get_current_genre_info");
@@ -62,6 +91,8 @@ function App() {
     try {
       switch (action) {
         case "view_live":
+          // Back to free play: drops the song and the key it switched to
+          await invoke("song_unload_chart");
           setCurrentView("live");
           break;
         case "view_diagnostics":
@@ -88,7 +119,7 @@ function App() {
         case "rescan_soundfonts":
           setIsRescanningSoundFonts(true);
           try {
-            await invoke("rescan_soundfonts");
+            await runJob("rescan_soundfonts");
             console.log("SoundFonts rescanned");
             // Keep the loading state visible for a moment so user sees feedback
             setTimeout(() => setIsRescanningSoundFonts(false), 800);
@@ -199,6 +230,21 @@ function App() {
         />
       )}
 
+      {recoverableSession && (
+        <div className="loading-overlay">
+          <div className="loading-content">
+            <h2>Recover last session?</h2>
+            <p>
+              mITyGuitar closed unexpectedly while playing "{recoverableSession.title}" by {recoverableSession.artist}
+              {recoverableSession.unsavedEdits && " with unsaved chart edits"}
+              {" "}({new Date(recoverableSession.savedAt * 1000).toLocaleString()}).
+            </p>
+            <button onClick={() => handleRecoverSession(true)}>Recover</button>
+            <button onClick={() => handleRecoverSession(false)}>Discard</button>
+          </div>
+        </div>
+      )}
+
       {showAboutDialog && (
         <AboutDialog onClose={() => setShowAboutDialog(false)} />
       )}
@@ -1,15 +1,38 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import "./AudioSettings.css";
 
 interface AudioStats {
   sample_rate: number;
+  render_sample_rate: number;
   buffer_size: number;
   underruns: number;
   active_voices: number;
+  voices_stolen: number;
   estimated_latency_ms: number;
 }
 
+interface AudioMeters {
+  peak: [number, number];
+  rms: [number, number];
+  clipped_samples: number;
+  spectrum: number[];
+}
+
+const METER_FLOOR_DB = -60;
+
+// Bar height (0-100%) for a linear level or a dBFS value
+const levelToPercent = (level: number) => dbToPercent(level > 0 ? 20 * Math.log10(level) : METER_FLOOR_DB);
+const dbToPercent = (db: number) => Math.max(0, Math.min(100, (1 - db / METER_FLOOR_DB) * 100));
+
+interface UnderrunAlert {
+  recent: number;
+  total: number;
+  buffer_size: number;
+  suggested_buffer_size: number;
+}
+
 export default function AudioSettings() {
   const [releaseMultiplier, setReleaseMultiplier] = useState<number>(1.0);
   const [sustainEnabled, setSustainEnabled] = useState<boolean>(false);
@@ -17,6 +40,11 @@ export default function AudioSettings() {
   const [audioStats, setAudioStats] = useState<AudioStats | null>(null);
   const [error, setError] = useState<string | null>(null);
   const [isSaving, setIsSaving] = useState(false);
+  const [stereoSpread, setStereoSpread] = useState<number>(0);
+  const [maxVoices, setMaxVoices] = useState<number>(16);
+  const [underrunAlert, setUnderrunAlert] = useState<UnderrunAlert | null>(null);
+  const [meters, setMeters] = useState<AudioMeters | null>(null);
+  const [lastClip, setLastClip] = useState<number | null>(null);
 
   useEffect(() => {
     loadSettings();
@@ -27,12 +55,49 @@ export default function AudioSettings() {
     return () => clearInterval(interval);
   }, []);
 
+  // Output meters, ~10 times per second
+  useEffect(() => {
+    const interval = setInterval(async () => {
+      try {
+        const result = await invoke<AudioMeters>("get_audio_meters", { spectrum: true });
+        setMeters(result);
+        if (result.clipped_samples > 0) {
+          setLastClip(Date.now());
+        }
+      } catch (err: any) {
+        console.error("Failed to read audio meters:", err);
+      }
+    }, 100);
+    return () => clearInterval(interval);
+  }, []);
+
+  useEffect(() => {
+    const unlisten = listen<UnderrunAlert>("audio-underruns", (event) => {
+      setUnderrunAlert(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Follow config edits made outside the app and config profile switches
+  useEffect(() => {
+    const unlistenConfig = listen("config-changed", () => loadSettings());
+    const unlistenProfile = listen("config-profile-applied", () => loadSettings());
+    return () => {
+      unlistenConfig.then((fn) => fn());
+      unlistenProfile.then((fn) => fn());
+    };
+  }, []);
+
   const loadSettings = async () => {
     try {
       const config = await invoke<any>("get_config");
       setReleaseMultiplier(config.audio.release_time_multiplier || 1.0);
       setSustainEnabled(config.audio.sustain_enabled || false);
       setSustainReleaseTime(config.audio.sustain_release_time_ms || 500);
+      setStereoSpread(config.audio.stereo_spread || 0);
+      setMaxVoices(config.audio.max_voices || 16);
     } catch (err: any) {
       console.error("Failed to load audio settings:", err);
       setError("Failed to load settings");
@@ -48,6 +113,26 @@ export default function AudioSettings() {
     }
   };
 
+  const handleStereoSpreadChange = async (value: number) => {
+    setStereoSpread(value);
+    try {
+      await invoke("set_stereo_spread", { width: value });
+    } catch (err: any) {
+      console.error("Failed to set stereo spread:", err);
+      setError("Failed to update stereo spread");
+    }
+  };
+
+  const handleMaxVoicesChange = async (value: number) => {
+    setMaxVoices(value);
+    try {
+      await invoke("set_max_voices", { voices: value });
+    } catch (err: any) {
+      console.error("Failed to set polyphony:", err);
+      setError("Failed to update polyphony");
+    }
+  };
+
   const handleReleaseMultiplierChange = async (value: number) => {
     setReleaseMultiplier(value);
     try {
@@ -127,6 +212,12 @@ export default function AudioSettings() {
                   <span className="stat-label">Sample Rate:</span>
                   <span className="stat-value">{audioStats.sample_rate} Hz</span>
                 </div>
+                {audioStats.render_sample_rate !== audioStats.sample_rate && (
+                  <div className="stat-row">
+                    <span className="stat-label">Synth Rate:</span>
+                    <span className="stat-value">{audioStats.render_sample_rate} Hz (resampled)</span>
+                  </div>
+                )}
                 <div className="stat-row">
                   <span className="stat-label">Buffer Size:</span>
                   <span className="stat-value">{audioStats.buffer_size} samples</span>
@@ -139,12 +230,52 @@ export default function AudioSettings() {
                   <span className="stat-label">Active Voices:</span>
                   <span className="stat-value">{audioStats.active_voices}</span>
                 </div>
+                <div className="stat-row">
+                  <span className="stat-label">Voices Stolen:</span>
+                  <span className={`stat-value ${audioStats.voices_stolen > 0 ? "warning" : ""}`}>
+                    {audioStats.voices_stolen}
+                  </span>
+                </div>
                 <div className="stat-row">
                   <span className="stat-label">Buffer Underruns:</span>
                   <span className={`stat-value ${audioStats.underruns > 0 ? "warning" : ""}`}>
                     {audioStats.underruns}
                   </span>
                 </div>
+                {underrunAlert && (
+                  <div className="stat-row warning">
+                    ⚠️ {underrunAlert.recent} underruns recently at {underrunAlert.buffer_size} samples.
+                    Try a buffer size of {underrunAlert.suggested_buffer_size}.
+                  </div>
+                )}
+              </div>
+            </div>
+          )}
+
+          {/* Output Meters */}
+          {meters && (
+            <div className="setting-group">
+              <label>Output Level</label>
+              <div className="output-meters">
+                {["L", "R"].map((side, i) => (
+                  <div className="meter-row" key={side}>
+                    <span className="meter-label">{side}</span>
+                    <div className="meter-track">
+                      <div className="meter-rms" style={{ width: `${levelToPercent(meters.rms[i])}%` }} />
+                      <div className="meter-peak" style={{ left: `${levelToPercent(meters.peak[i])}%` }} />
+                    </div>
+                  </div>
+                ))}
+                <div className="meter-spectrum">
+                  {meters.spectrum.map((db, band) => (
+                    <div key={band} className="meter-band" style={{ height: `${dbToPercent(db)}%` }} />
+                  ))}
+                </div>
+                {lastClip !== null && Date.now() - lastClip < 3000 && (
+                  <div className="stat-row warning">
+                    ⚠️ Output is clipping. Lower the master or synth level in the mixer.
+                  </div>
+                )}
               </div>
             </div>
           )}
@@ -175,6 +306,50 @@ export default function AudioSettings() {
             </div>
           </div>
 
+          {/* Stereo Spread */}
+          <div className="setting-group">
+            <label>
+              Stereo Spread
+              <span className="setting-description">
+                Spreads chord notes across the stereo field, low strings left and high strings right
+              </span>
+            </label>
+            <div className="slider-container">
+              <input
+                type="range"
+                min="0"
+                max="1"
+                step="0.05"
+                value={stereoSpread}
+                onChange={(e) => handleStereoSpreadChange(parseFloat(e.target.value))}
+                className="slider"
+              />
+              <div className="slider-value">{Math.round(stereoSpread * 100)}%</div>
+            </div>
+          </div>
+
+          {/* Polyphony */}
+          <div className="setting-group">
+            <label>
+              Polyphony
+              <span className="setting-description">
+                Notes a virtual instrument plays at once; beyond that the quietest fading note makes room
+              </span>
+            </label>
+            <div className="slider-container">
+              <input
+                type="range"
+                min="4"
+                max="64"
+                step="4"
+                value={maxVoices}
+                onChange={(e) => handleMaxVoicesChange(parseInt(e.target.value))}
+                className="slider"
+              />
+              <div className="slider-value">{maxVoices} voices</div>
+            </div>
+          </div>
+
           {/* Sustain Mode */}
           <div className="setting-group">
             <label>
@@ -3,10 +3,13 @@ import { invoke } from "@tauri-apps/api/core";
 
 interface AudioStats {
   sample_rate: number;
+  render_sample_rate: number;
   buffer_size: number;
   underruns: number;
   active_voices: number;
   estimated_latency_ms: number;
+  first_callback_ms: number | null;
+  denormals_flushed: boolean;
 }
 
 interface AppConfig {
@@ -51,6 +54,14 @@ interface RawInputEvent {
   raw_code: string;
 }
 
+interface ReplayReportStep {
+  timestamp_ms: number;
+  raw_code: string;
+  actions: { action: string; pressed: boolean }[];
+  whammy: number;
+  music_events: unknown[];
+}
+
 export default function DiagnosticsView() {
   const [stats, setStats] = useState<AudioStats | null>(null);
   const [currentSoundfont, setCurrentSoundfont] = useState<string | null>(null);
@@ -62,6 +73,7 @@ export default function DiagnosticsView() {
   const [filterEventType, setFilterEventType] = useState<string>("All");
   const [filterText, setFilterText] = useState<string>("");
   const [showOnlyChanges, setShowOnlyChanges] = useState<boolean>(false);
+  const [replaySteps, setReplaySteps] = useState<ReplayReportStep[] | null>(null);
 
   useEffect(() => {
     const loadStats = async () => {
@@ -167,6 +179,16 @@ export default function DiagnosticsView() {
     }
   };
 
+  // Run the recorded events through the mapping layer (no hardware needed)
+  const replayRawDiagnostics = async () => {
+    try {
+      const steps = await invoke<ReplayReportStep[]>("replay_raw_capture", { capturePath: null });
+      setReplaySteps(steps.filter(step => step.actions.length > 0 || step.music_events.length > 0));
+    } catch (error) {
+      console.error("Failed to replay raw diagnostics:", error);
+    }
+  };
+
   const copyToClipboard = () => {
     const text = filteredEvents.map(e => 
       `[${e.timestamp_ms}ms] ${e.event_type} | ${e.button || e.axis || 'N/A'} | ${e.raw_code}`
@@ -305,6 +327,16 @@ export default function DiagnosticsView() {
               {stats.underruns}
             </span>
           </div>
+          <div className="info-row">
+            <span className="info-label">First Callback:</span>
+            <span style={{ fontWeight: 600 }}>
+              {stats.first_callback_ms !== null ? `${stats.first_callback_ms.toFixed(1)} ms` : "—"}
+            </span>
+          </div>
+          <div className="info-row">
+            <span className="info-label">Denormal Flush:</span>
+            <span style={{ fontWeight: 600 }}>{stats.denormals_flushed ? "FTZ/DAZ" : "Software"}</span>
+          </div>
           <div className="info-row">
             <span className="info-label">Status:</span>
             <span style={{ fontWeight: 600, color: stats.underruns === 0 ? "#4ade80" : "#f87171" }}>
@@ -476,6 +508,21 @@ export default function DiagnosticsView() {
             >
               📋 Copy
             </button>
+            <button
+              onClick={replayRawDiagnostics}
+              disabled={eventCount === 0}
+              style={{
+                padding: '6px 12px',
+                borderRadius: '4px',
+                border: '1px solid rgba(255, 255, 255, 0.2)',
+                background: 'rgba(255, 255, 255, 0.1)',
+                color: eventCount === 0 ? '#666' : '#fff',
+                cursor: eventCount === 0 ? 'not-allowed' : 'pointer',
+                fontSize: '13px'
+              }}
+            >
+              🔁 Replay
+            </button>
           </div>
         </div>
 
@@ -600,6 +647,17 @@ export default function DiagnosticsView() {
           )}
         </div>
 
+        {replaySteps && (
+          <div style={{ marginTop: '12px' }}>
+            <strong>Replay ({replaySteps.length} steps with output)</strong>
+            <pre style={{ maxHeight: '240px', overflow: 'auto', fontSize: '12px', background: 'rgba(0, 0, 0, 0.3)', padding: '8px', borderRadius: '4px' }}>
+              {replaySteps.map(step =>
+                `[${step.timestamp_ms}ms] ${step.actions.map(a => `${a.action} ${a.pressed ? "↓" : "↑"}`).join(", ") || "-"} → ${step.music_events.map(e => JSON.stringify(e)).join(" ") || "no music events"}`
+              ).join("\n")}
+            </pre>
+          </div>
+        )}
+
         <div style={{ marginTop: '12px', fontSize: '12px', color: 'rgba(255, 255, 255, 0.6)', lineHeight: '1.6' }}>
           <strong>💡 Tip:</strong> Raw diagnostics show both the logical button mapping (e.g., "DPadUp") and the raw event code. 
           Use this to identify which physical controls map to the same logical inputs.
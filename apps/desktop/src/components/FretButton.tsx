@@ -35,6 +35,13 @@ const VALID_CHORDS = [
   'C7', 'C#7', 'D7', 'D#7', 'E7', 'F7', 'F#7', 'G7', 'G#7', 'A7', 'A#7', 'B7',
   'Cmaj7', 'C#maj7', 'Dmaj7', 'D#maj7', 'Emaj7', 'Fmaj7', 'F#maj7', 'Gmaj7', 'G#maj7', 'Amaj7', 'A#maj7', 'Bmaj7',
   'Cm7', 'C#m7', 'Dm7', 'D#m7', 'Em7', 'Fm7', 'F#m7', 'Gm7', 'G#m7', 'Am7', 'A#m7', 'Bm7',
+  // 9th chords
+  'C9', 'C#9', 'D9', 'D#9', 'E9', 'F9', 'F#9', 'G9', 'G#9', 'A9', 'A#9', 'B9',
+  'Cmaj9', 'C#maj9', 'Dmaj9', 'D#maj9', 'Emaj9', 'Fmaj9', 'F#maj9', 'Gmaj9', 'G#maj9', 'Amaj9', 'A#maj9', 'Bmaj9',
+  'Cm9', 'C#m9', 'Dm9', 'D#m9', 'Em9', 'Fm9', 'F#m9', 'Gm9', 'G#m9', 'Am9', 'A#m9', 'Bm9',
+  // Diminished and augmented chords
+  'Cdim', 'C#dim', 'Ddim', 'D#dim', 'Edim', 'Fdim', 'F#dim', 'Gdim', 'G#dim', 'Adim', 'A#dim', 'Bdim',
+  'Caug', 'C#aug', 'Daug', 'D#aug', 'Eaug', 'Faug', 'F#aug', 'Gaug', 'G#aug', 'Aaug', 'A#aug', 'Baug',
 ];
 
 export default function FretButton({ 
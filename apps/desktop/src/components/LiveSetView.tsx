@@ -0,0 +1,71 @@
+import { useState, useEffect } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+import "./LiveSetView.css";
+
+interface LiveSetSong {
+  title: string;
+  artist: string;
+  section: string | null;
+  chord: string | null;
+  next_chord: string | null;
+  beats_to_next_chord: number | null;
+  beat: number;
+  bpm: number;
+  is_playing: boolean;
+}
+
+interface LiveSetState {
+  song: LiveSetSong | null;
+  key: string;
+  genre: string;
+  instrument: string | null;
+  controller_connected: boolean;
+  battery: number | null;
+}
+
+// Stage display window: big, glanceable state streamed from the backend
+export default function LiveSetView() {
+  const [liveSet, setLiveSet] = useState<LiveSetState | null>(null);
+
+  useEffect(() => {
+    invoke<LiveSetState>("get_live_set_state").then(setLiveSet).catch(console.error);
+    const unlisten = listen<LiveSetState>("live-set-state", (event) => {
+      setLiveSet(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  if (!liveSet) {
+    return <div className="live-set-view" />;
+  }
+
+  const song = liveSet.song;
+  const battery = liveSet.battery;
+  return (
+    <div className="live-set-view">
+      <div className="live-set-header">
+        <span className="live-set-song">{song ? `${song.title} — ${song.artist}` : "Free play"}</span>
+        <span className={`live-set-battery ${battery !== null && battery <= 20 ? "low" : ""}`}>
+          {!liveSet.controller_connected ? "🎸 ✕" : battery === null ? "🎸" : `🔋 ${battery}%`}
+        </span>
+      </div>
+      <div className="live-set-section">{song?.section ?? ""}</div>
+      <div className="live-set-chords">
+        <div className="live-set-chord">{song?.chord ?? "–"}</div>
+        <div className="live-set-next-chord">
+          {song?.next_chord ? `→ ${song.next_chord}` : ""}
+          {song?.beats_to_next_chord != null && <small> in {Math.ceil(song.beats_to_next_chord)}</small>}
+        </div>
+      </div>
+      <div className="live-set-footer">
+        <span>{liveSet.key}</span>
+        <span>{liveSet.genre}</span>
+        <span>{liveSet.instrument ?? ""}</span>
+        {song && <span>{Math.round(song.bpm)} BPM {song.is_playing ? "▶" : "⏸"}</span>}
+      </div>
+    </div>
+  );
+}
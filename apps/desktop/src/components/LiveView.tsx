@@ -1,7 +1,9 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import FretBoard from "./FretBoard";
 import ChordMappingControls from "./ChordMappingControls";
+import { useMenuNavigation } from "../menuNavigation";
 
 interface LiveViewProps {
   genreInfo: any;
@@ -58,16 +60,37 @@ interface ChordMappingSettings {
   whammy_filter_cutoff_enabled: boolean;
 }
 
+interface TransposeInfo {
+  offset: number;
+  range: number;
+  key: string;
+}
+
+interface ChordPlayed {
+  player: number;
+  name: string;
+  notes: number[];
+  frets: string[];
+}
+
+interface SoundFontLoaded {
+  player: number;
+  path: string;
+  error: string | null;
+}
+
 interface InstrumentInfo {
   name: string;
   path?: string;
   size_bytes?: number;
-  instrument_type: 'SoundFont' | 'Virtual';
+  instrument_type: 'SoundFont' | 'Virtual' | 'Sampler' | 'Sfz';
 }
 
 export default function LiveView({ genreInfo: _genreInfo, onAction: _onAction }: LiveViewProps) {
   const [controllerState, setControllerState] = useState<ControllerState | null>(null);
   const [currentSoundfont, setCurrentSoundfont] = useState<string | null>(null);
+  const [soundfontsReady, setSoundfontsReady] = useState<boolean>(true);
+  const [lastChord, setLastChord] = useState<ChordPlayed | null>(null);
   const [simulatorEnabled, setSimulatorEnabled] = useState<boolean>(false);
   const [mainChords, setMainChords] = useState<ChordMapState>({
     green: 'E5', red: 'A5', yellow: 'B5', blue: 'D5', orange: 'C#5'
@@ -87,11 +110,54 @@ export default function LiveView({ genreInfo: _genreInfo, onAction: _onAction }:
     whammy_filter_cutoff_enabled: false
   });  const [availableInstruments, setAvailableInstruments] = useState<InstrumentInfo[]>([]);
   const [showInstrumentDropdown, setShowInstrumentDropdown] = useState<boolean>(false);
-  const [previousDpadUp, setPreviousDpadUp] = useState<boolean>(false);
-  const [previousDpadDown, setPreviousDpadDown] = useState<boolean>(false);
+  const [highlightedInstrument, setHighlightedInstrument] = useState<number>(0);
+  const [capoOffset, setCapoOffset] = useState<number>(0);
   const [keyboardShortcutsExpanded, setKeyboardShortcutsExpanded] = useState<boolean>(false);
 
-  const availableGenres = ['EDM', 'Folk', 'Metal', 'Pop', 'Punk', 'Rock'];
+  // D-pad up/down moves the capo in the backend; follow the key it lands on
+  useEffect(() => {
+    const unlisten = listen<TransposeInfo>("transpose-changed", (event) => {
+      setCapoOffset(event.payload.offset);
+      const keyRoot = event.payload.key.split(" ")[0];
+      setChordMappingSettings(prev => ({ ...prev, key_root: keyRoot }));
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // Show the chord the backend mapper struck, as it names it
+  useEffect(() => {
+    invoke<ChordPlayed | null>("get_last_chord").then(setLastChord).catch(console.error);
+    const unlisten = listen<ChordPlayed>("chord-played", (event) => {
+      setLastChord(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  // SoundFonts load in the background; show when the selected one can play
+  const refreshSoundfontsReady = async () => {
+    try {
+      setSoundfontsReady(await invoke<boolean>("get_soundfonts_ready"));
+    } catch (error) {
+      console.error("Failed to get soundfont readiness:", error);
+    }
+  };
+
+  useEffect(() => {
+    refreshSoundfontsReady();
+    const unlisten = listen<SoundFontLoaded>("soundfont-loaded", (event) => {
+      if (event.payload.error) {
+        console.error(`Failed to load soundfont ${event.payload.path}:`, event.payload.error);
+      }
+      refreshSoundfontsReady();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
 
   // Load sustain settings from config on mount
   useEffect(() => {
@@ -115,27 +181,6 @@ export default function LiveView({ genreInfo: _genreInfo, onAction: _onAction }:
     const interval = setInterval(async () => {
       try {
         const state = await invoke<ControllerState>("get_controller_state");
-        
-        // Detect D-pad up press (edge detection)
-        if (state.dpad_up && !previousDpadUp) {
-          // Cycle to next genre
-          const currentIndex = availableGenres.indexOf(chordMappingSettings.genre);
-          const nextIndex = (currentIndex + 1) % availableGenres.length;
-          const newSettings = { ...chordMappingSettings, genre: availableGenres[nextIndex] };
-          handleSettingsChange(newSettings);
-        }
-        
-        // Detect D-pad down press (edge detection)
-        if (state.dpad_down && !previousDpadDown) {
-          // Cycle to previous genre
-          const currentIndex = availableGenres.indexOf(chordMappingSettings.genre);
-          const prevIndex = (currentIndex - 1 + availableGenres.length) % availableGenres.length;
-          const newSettings = { ...chordMappingSettings, genre: availableGenres[prevIndex] };
-          handleSettingsChange(newSettings);
-        }
-        
-        setPreviousDpadUp(state.dpad_up);
-        setPreviousDpadDown(state.dpad_down);
         setControllerState(state);
         
         // Auto-disable simulator if hardware controller is connected
@@ -156,7 +201,7 @@ export default function LiveView({ genreInfo: _genreInfo, onAction: _onAction }:
     loadAvailableInstruments();
 
     return () => clearInterval(interval);
-  }, [simulatorEnabled, previousDpadUp, previousDpadDown, chordMappingSettings.genre]);
+  }, [simulatorEnabled, chordMappingSettings.genre]);
 
   // Load chord mapping when settings change
   useEffect(() => {
@@ -214,6 +259,7 @@ export default function LiveView({ genreInfo: _genreInfo, onAction: _onAction }:
     try {
       await invoke("set_instrument", { name: instrumentName });
       setCurrentSoundfont(instrumentName);
+      refreshSoundfontsReady();
       setShowInstrumentDropdown(false);
     } catch (error) {
       // Fallback to set_soundfont if set_instrument doesn't exist
@@ -227,6 +273,22 @@ export default function LiveView({ genreInfo: _genreInfo, onAction: _onAction }:
     }
   };
 
+  // The instrument list can be browsed from the guitar while it is open
+  useMenuNavigation(showInstrumentDropdown, (action) => {
+    const count = availableInstruments.length;
+    if (action === "back") {
+      setShowInstrumentDropdown(false);
+    } else if (count === 0) {
+      return;
+    } else if (action === "up") {
+      setHighlightedInstrument((index) => (index + count - 1) % count);
+    } else if (action === "down") {
+      setHighlightedInstrument((index) => (index + 1) % count);
+    } else if (action === "select") {
+      handleInstrumentSelect(availableInstruments[Math.min(highlightedInstrument, count - 1)].name);
+    }
+  });
+
   const handleChordEdit = async (fret: keyof ChordMapState, row: 'main' | 'solo', newChord: string) => {
     try {
       await invoke("update_chord_override", {
@@ -269,6 +331,13 @@ export default function LiveView({ genreInfo: _genreInfo, onAction: _onAction }:
       }
     };
 
+    // Mouse wheel moves the simulated whammy (scrolling down pushes it)
+    const handleWheel = (e: WheelEvent) => {
+      if (simulatorEnabled && e.deltaY !== 0) {
+        invoke("simulator_wheel", { notches: Math.sign(e.deltaY) }).catch(console.error);
+      }
+    };
+
     // Close instrument dropdown when clicking outside
     const handleClickOutside = (e: MouseEvent) => {
       if (showInstrumentDropdown) {
@@ -281,11 +350,13 @@ export default function LiveView({ genreInfo: _genreInfo, onAction: _onAction }:
 
     window.addEventListener("keydown", handleKeyDown);
     window.addEventListener("keyup", handleKeyUp);
+    window.addEventListener("wheel", handleWheel);
     window.addEventListener("click", handleClickOutside);
 
     return () => {
       window.removeEventListener("keydown", handleKeyDown);
       window.removeEventListener("keyup", handleKeyUp);
+      window.removeEventListener("wheel", handleWheel);
       window.removeEventListener("click", handleClickOutside);
     };
   }, [simulatorEnabled, showInstrumentDropdown]);
@@ -307,13 +378,12 @@ export default function LiveView({ genreInfo: _genreInfo, onAction: _onAction }:
       case "DPadDown": return controllerState.dpad_down;
       case "Start": return controllerState.start;
       case "Select": return controllerState.select;
-      // Solo buttons might not be supported yet
-      case "SoloGreen":
-      case "SoloRed":
-      case "SoloYellow":
-      case "SoloBlue":
-      case "SoloOrange":
-        return false; // Not implemented in new controller yet
+      // Solo buttons (keyboard simulator; guitars report them as main frets)
+      case "SoloGreen": return !!controllerState.solo_green;
+      case "SoloRed": return !!controllerState.solo_red;
+      case "SoloYellow": return !!controllerState.solo_yellow;
+      case "SoloBlue": return !!controllerState.solo_blue;
+      case "SoloOrange": return !!controllerState.solo_orange;
       default: return false;
     }
   };
@@ -362,6 +432,11 @@ export default function LiveView({ genreInfo: _genreInfo, onAction: _onAction }:
                 onMouseLeave={(e) => e.currentTarget.style.background = 'rgba(255, 255, 255, 0.1)'}
               >
                 {currentSoundfont || "Fallback Synth"}
+                {!soundfontsReady && (
+                  <span style={{ fontSize: '12px', color: 'rgba(255, 255, 255, 0.7)' }} title="Loading, the previous instrument plays until it is ready">
+                    ⏳ loading
+                  </span>
+                )}
                 <span style={{ fontSize: '12px', color: 'rgba(255, 255, 255, 0.7)' }}>▼</span>
               </span>
             </div>
@@ -380,11 +455,12 @@ export default function LiveView({ genreInfo: _genreInfo, onAction: _onAction }:
                 overflowY: 'auto',
                 boxShadow: '0 4px 12px rgba(0, 0, 0, 0.3)'
               }}>
-                {availableInstruments.map((instrument) => (
+                {availableInstruments.map((instrument, index) => (
                   <div
                     key={instrument.name}
                     style={{
                       padding: '8px 12px',
+                      background: index === highlightedInstrument ? 'rgba(255, 255, 255, 0.1)' : 'transparent',
                       cursor: 'pointer',
                       borderBottom: '1px solid rgba(255, 255, 255, 0.1)',
                       display: 'flex',
@@ -393,7 +469,7 @@ export default function LiveView({ genreInfo: _genreInfo, onAction: _onAction }:
                     }}
                     onClick={() => handleInstrumentSelect(instrument.name)}
                     onMouseEnter={(e) => e.currentTarget.style.background = 'rgba(255, 255, 255, 0.1)'}
-                    onMouseLeave={(e) => e.currentTarget.style.background = 'transparent'}
+                    onMouseLeave={(e) => e.currentTarget.style.background = index === highlightedInstrument ? 'rgba(255, 255, 255, 0.1)' : 'transparent'}
                   >
                     <span style={{ color: '#fff' }}>{instrument.name}</span>
                     <span style={{ 
@@ -403,12 +479,18 @@ export default function LiveView({ genreInfo: _genreInfo, onAction: _onAction }:
                       padding: '2px 6px',
                       borderRadius: '3px'
                     }}>
-                      {instrument.instrument_type === 'Virtual' ? 'Virtual' : 'SoundFont'}
+                      {instrument.instrument_type === 'Sampler' ? 'Samples' : instrument.instrument_type === 'Sfz' ? 'SFZ' : instrument.instrument_type}
                     </span>
                   </div>
                 ))}
               </div>
             )}
+            <div className="info-row">
+              <span className="info-label">Last chord:</span>
+              <span style={{ fontWeight: 600 }} title={lastChord ? `${lastChord.frets.join(" + ")} → MIDI ${lastChord.notes.join(", ")}` : undefined}>
+                {lastChord ? lastChord.name : "—"}
+              </span>
+            </div>
           </div>
 
           {/* New Fret Board */}
@@ -509,8 +591,8 @@ export default function LiveView({ genreInfo: _genreInfo, onAction: _onAction }:
               <span style={{ opacity: simulatorEnabled ? 1 : 0.5 }}>Space (down), Arrow Up (up)</span>
             </div>
             <div className="info-row">
-              <span className="info-label">Genre:</span>
-              <span>D-Pad Up/Down (cycle genres)</span>
+              <span className="info-label">Capo:</span>
+              <span>D-Pad Up/Down (transpose, now {capoOffset > 0 ? `+${capoOffset}` : capoOffset})</span>
             </div>
           </>
         )}
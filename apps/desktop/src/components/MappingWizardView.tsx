@@ -46,6 +46,10 @@ const APP_ACTIONS = [
   { name: "Start", display: "Start", category: "Menu" },
   { name: "Select", display: "Select/Back", category: "Menu" },
   
+  // Tools
+  { name: "TapTempo", display: "Tap Tempo", category: "Tools" },
+  { name: "NextConfigProfile", display: "Next Config Profile", category: "Tools" },
+  
   // Analog
   { name: "WhammyBar", display: "Whammy Bar", category: "Analog" },
   { name: "TouchStrip", display: "Touch Strip", category: "Analog" },
@@ -1,5 +1,6 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
 import { open as openUrl } from "@tauri-apps/plugin-shell";
 import { open as openDialog } from "@tauri-apps/plugin-dialog";
 import { readTextFile } from "@tauri-apps/plugin-fs";
@@ -11,14 +12,22 @@ interface SongLibraryEntry {
   title: string;
   artist: string;
   filename: string;
+  duration_seconds: number;
+  bpm: number;
+  difficulty: string;
+  chord_count: number;
 }
 
+const formatDuration = (seconds: number) =>
+  `${Math.floor(seconds / 60)}:${Math.round(seconds % 60).toString().padStart(2, "0")}`;
+
 interface SongChart {
   meta: {
     title: string;
     artist: string;
     youtube?: string;
     spotify?: string;
+    backingTrack?: { path: string; offsetSeconds?: number };
   };
   clock: {
     bpm: number;
@@ -74,15 +83,37 @@ export default function SongLibraryView() {
   const [editingTimeSig, setEditingTimeSig] = useState(false);
   const [timeSigNumerator, setTimeSigNumerator] = useState<string>('4');
   const [timeSigDenominator, setTimeSigDenominator] = useState<string>('4');
+  const [searchQuery, setSearchQuery] = useState("");
+  const [sortKey, setSortKey] = useState("title");
 
   useEffect(() => {
-    loadSongLibrary();
-  }, []);
+    searchSongs();
+  }, [searchQuery, sortKey]);
 
-  const loadSongLibrary = async () => {
+  // The library is rescanned in the background when songs are added or changed
+  useEffect(() => {
+    const unlisten = listen("library-changed", () => searchSongs());
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [searchQuery, sortKey]);
+
+  // Searches the index without reading any chart
+  const searchSongs = async () => {
     try {
-      const library = await invoke<SongLibraryEntry[]>("song_list_library");
+      const library = await invoke<SongLibraryEntry[]>("song_search", { query: searchQuery, filters: { sort: sortKey } });
       setSongLibrary(library);
+    } catch (err) {
+      console.error("Failed to search song library:", err);
+      setError("Failed to load song library");
+    }
+  };
+
+  // Rescans the library first, so songs just added or deleted show up right away
+  const loadSongLibrary = async () => {
+    try {
+      await invoke("song_list_library");
+      await searchSongs();
     } catch (err) {
       console.error("Failed to load song library:", err);
       setError("Failed to load song library");
@@ -599,6 +630,22 @@ export default function SongLibraryView() {
           <div className="library-header">
             <h2>Song Library</h2>
             <span className="song-count">{songLibrary.length} songs</span>
+            <div className="library-search">
+              <input
+                type="search"
+                placeholder="Search title or artist"
+                value={searchQuery}
+                onChange={(e) => setSearchQuery(e.target.value)}
+              />
+              <select value={sortKey} onChange={(e) => setSortKey(e.target.value)} title="Sort by">
+                <option value="title">Title</option>
+                <option value="artist">Artist</option>
+                <option value="bpm">BPM</option>
+                <option value="duration">Length</option>
+                <option value="difficulty">Difficulty</option>
+                <option value="chord_count">Chords</option>
+              </select>
+            </div>
             <button className="upload-button" onClick={handleUploadSong} title="Upload Song">
               ⬆ Upload Song
             </button>
@@ -617,6 +664,9 @@ export default function SongLibraryView() {
                 >
                   <div className="song-item-title">{song.title}</div>
                   <div className="song-item-artist">{song.artist}</div>
+                  <div className="song-item-details">
+                    {formatDuration(song.duration_seconds)} · {Math.round(song.bpm)} BPM · {song.difficulty} · {song.chord_count} chords
+                  </div>
                 </div>
                 {!isDefaultSong(song.filename) && (
                   <button
@@ -1,11 +1,23 @@
 import { useState, useEffect, useRef } from "react";
 import { invoke } from "@tauri-apps/api/core";
-import { open as openDialog } from "@tauri-apps/plugin-dialog";
+import { listen } from "@tauri-apps/api/event";
+import { open as openDialog, save as saveDialog } from "@tauri-apps/plugin-dialog";
 import { readTextFile } from "@tauri-apps/plugin-fs";
 import { open as openUrl } from "@tauri-apps/plugin-shell";
 import SongUploadDialog from "./SongUploadDialog";
+import { runJob } from "../jobs";
 import "./SongPlayView.css";
 
+interface CountInBeat {
+  click: {
+    beat: number;
+    bar: number;
+    pulse_in_bar: number;
+    accent: string;
+  };
+  beats_left: number;
+}
+
 interface SongLibraryEntry {
   id: string;
   title: string;
@@ -19,6 +31,7 @@ interface SongChart {
     artist: string;
     youtube?: string;
     spotify?: string;
+    backingTrack?: { path: string; offsetSeconds?: number };
   };
   clock: {
     bpm: number;
@@ -63,6 +76,8 @@ interface TransportState {
   time_sig: [number, number];
   speed_multiplier: number;
   is_in_count_in: boolean;
+  practice_mode: "normal" | "wait_for_note";
+  waiting_for_note: boolean;
 }
 
 interface ScoreData {
@@ -73,6 +88,7 @@ interface ScoreData {
   misses: number;
   accuracy: number;
   grade: string;
+  no_fail: boolean;
 }
 
 interface ControllerState {
@@ -116,9 +132,11 @@ export default function SongPlayView() {
   const [songLibrary, setSongLibrary] = useState<SongLibraryEntry[]>([]);
   const [showLibrary, setShowLibrary] = useState(false);
   const [countdown, setCountdown] = useState<number | null>(null);
+  const [countInBeat, setCountInBeat] = useState<CountInBeat | null>(null);
   const [showUploadDialog, setShowUploadDialog] = useState(false);
   const [uploadResult, setUploadResult] = useState<{ songName: string; isError: boolean; errorMessage?: string } | null>(null);
   const [timelineMode, setTimelineMode] = useState<'beats' | 'seconds'>('beats');
+  const [bounceProgress, setBounceProgress] = useState<number | null>(null);
   
   const prevStrumRef = useRef({ up: false, down: false });
   const animationRef = useRef<number | null>(null);
@@ -210,16 +228,8 @@ export default function SongPlayView() {
       if (chartJson) {
         const parsedChart = JSON.parse(chartJson);
         setChart(parsedChart);
-        
-        // Calculate count-in starting beat
-        const countInBars = parsedChart.clock.countInBars || 0;
-        const beatsPerBar = parsedChart.clock.timeSig[0];
-        const countInBeats = countInBars * beatsPerBar;
-        
-        // Seek to beginning of count-in (negative beat)
-        if (countInBeats > 0) {
-          await invoke("song_seek", { beat: -countInBeats });
-        }
+
+        // The backend waits at the start of the count-in after loading
       }
       setShowLibrary(false);
       setError(null);
@@ -278,6 +288,23 @@ export default function SongPlayView() {
     }
   };
 
+  useEffect(() => {
+    // Flash each count-in beat as the backend clicks it
+    const unlisten = listen<CountInBeat>("count-in-beat", (event) => {
+      setCountInBeat(event.payload);
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, []);
+
+  useEffect(() => {
+    // The count is over once the song starts (or is stopped)
+    if (countInBeat && transport && (transport.current_beat >= 0 || !transport.is_playing)) {
+      setCountInBeat(null);
+    }
+  }, [transport, countInBeat]);
+
   useEffect(() => {
     // Start update loop
     const update = async () => {
@@ -327,16 +354,8 @@ export default function SongPlayView() {
       if (chartJson) {
         const parsedChart = JSON.parse(chartJson);
         setChart(parsedChart);
-        
-        // Calculate count-in starting beat
-        const countInBars = parsedChart.clock.countInBars || 0;
-        const beatsPerBar = parsedChart.clock.timeSig[0];
-        const countInBeats = countInBars * beatsPerBar;
-        
-        // Seek to beginning of count-in (negative beat)
-        if (countInBeats > 0) {
-          await invoke("song_seek", { beat: -countInBeats });
-        }
+
+        // The backend waits at the start of the count-in after loading
       }
     } catch (error) {
       setError(`Failed to load chart: ${error}`);
@@ -394,6 +413,52 @@ export default function SongPlayView() {
     }
   };
 
+  // Stop at every chord until it is played, or play on in time
+  const handleToggleWaitForNote = async () => {
+    const mode = transport?.practice_mode === "wait_for_note" ? "normal" : "wait_for_note";
+    try {
+      await invoke("song_set_practice_mode", { mode });
+    } catch (error) {
+      console.error("Practice mode error:", error);
+    }
+  };
+
+  // Misses don't break the combo (beginners playing along)
+  const handleToggleNoFail = async () => {
+    try {
+      await invoke("song_set_no_fail", { enabled: !score?.no_fail });
+    } catch (error) {
+      console.error("No-fail error:", error);
+    }
+  };
+
+  // Render the chart to a WAV file with the current instrument
+  const handleBounce = async () => {
+    if (!chart || bounceProgress !== null) return;
+    try {
+      const filename = await saveDialog({
+        defaultPath: `${chart.meta.title}.wav`,
+        filters: [{ name: "WAV Audio", extensions: ["wav"] }]
+      });
+      if (!filename) return;
+      setBounceProgress(0);
+      await runJob("song_render_to_wav", { filename }, (job) => setBounceProgress(job.progress));
+    } catch (error) {
+      console.error("Bounce error:", error);
+      setError(`Failed to export WAV: ${error}`);
+    } finally {
+      setBounceProgress(null);
+    }
+  };
+
+  const handleTapTempo = async () => {
+    try {
+      await invoke("song_tap_tempo");
+    } catch (error) {
+      console.error("Tap tempo error:", error);
+    }
+  };
+
   const getPressedFrets = (state: ControllerState): string[] => {
     const frets: string[] = [];
     if (state.fret_green) frets.push("GREEN");
@@ -586,11 +651,29 @@ export default function SongPlayView() {
             >
               1.25x
             </button>
+            <button
+              onClick={handleToggleWaitForNote}
+              className={transport.practice_mode === "wait_for_note" ? "active" : ""}
+              title="Stop at every chord until it is played"
+            >
+              🎓 Wait for note
+            </button>
+            <button
+              onClick={handleToggleNoFail}
+              className={score.no_fail ? "active" : ""}
+              title="Misses don't break the combo (no high scores)"
+            >
+              🛟 No fail
+            </button>
           </div>
 
           <div className="beat-display">
             Beat: {transport.current_beat.toFixed(1)} / {transport.bpm} BPM
+            {transport.waiting_for_note && " ⏳ Play the chord"}
           </div>
+          <button onClick={handleTapTempo} className="control-btn" title="Tap repeatedly to set the tempo">
+            🥁 Tap
+          </button>
 
           <button onClick={() => setShowLibrary(!showLibrary)} className="control-btn">
             📁 Song Library ({songLibrary.length})
@@ -598,6 +681,9 @@ export default function SongPlayView() {
           <button onClick={handleUploadSong} className="control-btn">
             ⬆ Upload Song
           </button>
+          <button onClick={handleBounce} className="control-btn" disabled={bounceProgress !== null} title="Render the chart to a WAV file">
+            {bounceProgress !== null ? `💿 Exporting ${Math.round(bounceProgress * 100)}%` : "💿 Export WAV"}
+          </button>
         </div>
 
         <div className="song-info-row">
@@ -691,6 +777,16 @@ export default function SongPlayView() {
             chordMappings={chart.mapping.chords}
             controllerState={controllerState}
           />
+          {countInBeat !== null && (
+            <div className="count-in-overlay">
+              <div
+                key={countInBeat.click.beat}
+                className={`count-in-beat${countInBeat.click.pulse_in_bar === 0 ? " downbeat" : ""}`}
+              >
+                {countInBeat.beats_left}
+              </div>
+            </div>
+          )}
           {countdown !== null && (
             <div className="countdown-overlay">
               <div key={countdown} className="countdown-number">{countdown}</div>
@@ -1,6 +1,7 @@
 import { useState, useEffect } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
+import { runJob } from "../jobs";
 
 interface SoundFontInfo {
   name: string;
@@ -60,8 +61,8 @@ export default function SoundFontSelector({ onClose, onSelect }: SoundFontSelect
       // Extract filename from path
       const fileName = selected.split(/[/\\]/).pop() || "unknown.sf2";
       
-      // Call backend to upload/save the file
-      const result = await invoke<string>("upload_soundfont", {
+      // Call backend to upload/save the file (runs as a background job)
+      const result = await runJob("upload_soundfont", {
         filePath: selected,
         fileName: fileName
       });
@@ -0,0 +1,55 @@
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+export interface JobInfo {
+  id: number;
+  kind: string;
+  status: "running" | "completed" | "failed" | "cancelled";
+  progress: number;
+  message: string | null;
+  result: string | null;
+  error: string | null;
+}
+
+/**
+ * Start a background job command and wait for it to finish.
+ * Resolves with the job result, rejects with its error (or "Cancelled").
+ */
+export async function runJob(
+  command: string,
+  args?: Record<string, unknown>,
+  onProgress?: (job: JobInfo) => void,
+): Promise<string> {
+  const jobId = await invoke<number>(command, args);
+
+  let settle: (job: JobInfo) => void = () => {};
+  const finished = new Promise<JobInfo>((resolve) => {
+    settle = resolve;
+  });
+
+  const unlistenProgress = await listen<JobInfo>("job-progress", (event) => {
+    if (event.payload.id === jobId) onProgress?.(event.payload);
+  });
+  const unlistenFinished = await listen<JobInfo>("job-finished", (event) => {
+    if (event.payload.id === jobId) settle(event.payload);
+  });
+
+  try {
+    // The job may have finished before the listeners were registered
+    const current = await invoke<JobInfo>("get_job", { jobId });
+    if (current.status !== "running") settle(current);
+
+    const job = await finished;
+    if (job.status !== "completed") {
+      throw job.error ?? "Cancelled";
+    }
+    return job.result ?? "";
+  } finally {
+    unlistenProgress();
+    unlistenFinished();
+  }
+}
+
+export function cancelJob(jobId: number): Promise<boolean> {
+  return invoke<boolean>("cancel_job", { jobId });
+}
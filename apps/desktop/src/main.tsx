@@ -1,9 +1,10 @@
 import { StrictMode } from "react";
 import ReactDOM from "react-dom/client";
 import App from "./App";
+import LiveSetView from "./components/LiveSetView";
 
 ReactDOM.createRoot(document.getElementById("root")!).render(
   <StrictMode>
-    <App />
+    {window.location.hash === "#/live-set" ? <LiveSetView /> : <App />}
   </StrictMode>
 );
@@ -0,0 +1,25 @@
+import { useEffect, useRef } from "react";
+import { invoke } from "@tauri-apps/api/core";
+import { listen } from "@tauri-apps/api/event";
+
+export type NavAction = "up" | "down" | "select" | "back";
+
+/**
+ * Drive a menu from the guitar while it is mounted and `open`.
+ * The backend stops playing notes and sends D-pad/strum, Start/green and
+ * Select/red presses as navigation actions instead.
+ */
+export function useMenuNavigation(open: boolean, onAction: (action: NavAction) => void) {
+  const handler = useRef(onAction);
+  handler.current = onAction;
+
+  useEffect(() => {
+    if (!open) return;
+    invoke("set_menu_mode", { open: true }).catch((e) => console.error("Failed to enter menu mode:", e));
+    const unlisten = listen<NavAction>("menu-navigate", (event) => handler.current(event.payload));
+    return () => {
+      unlisten.then((fn) => fn());
+      invoke("set_menu_mode", { open: false }).catch((e) => console.error("Failed to leave menu mode:", e));
+    };
+  }, [open]);
+}
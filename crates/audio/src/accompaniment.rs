@@ -0,0 +1,385 @@
+//! Humanized drum/bass accompaniment patterns
+//! Generates one bar at a time from a base groove: occasional mutations and
+//! ghost notes keep repeats from sounding like a static loop, and fills are
+//! written into the bar before a section boundary. Randomness comes from a
+//! seeded RNG so a given seed always produces the same performance.
+
+use serde::{Deserialize, Serialize};
+
+/// Steps per bar (sixteenth notes in 4/4)
+pub const STEPS_PER_BAR: usize = 16;
+
+/// Drum kit piece
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DrumVoice {
+    Kick,
+    Snare,
+    ClosedHat,
+    OpenHat,
+    Crash,
+    HighTom,
+    LowTom,
+}
+
+impl DrumVoice {
+    /// General MIDI percussion note for this voice
+    pub fn gm_note(&self) -> u8 {
+        match self {
+            DrumVoice::Kick => 36,
+            DrumVoice::Snare => 38,
+            DrumVoice::ClosedHat => 42,
+            DrumVoice::OpenHat => 46,
+            DrumVoice::Crash => 49,
+            DrumVoice::HighTom => 48,
+            DrumVoice::LowTom => 45,
+        }
+    }
+}
+
+/// A drum hit at a step inside the bar
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct DrumHit {
+    pub step: usize,
+    pub voice: DrumVoice,
+    pub velocity: u8,
+}
+
+/// A bass note at a step inside the bar
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct BassNote {
+    pub step: usize,
+    pub note: u8,
+    pub velocity: u8,
+    /// Length in steps
+    pub length: usize,
+}
+
+/// Base groove that variations are generated from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GroovePattern {
+    pub name: String,
+    pub drums: Vec<DrumHit>,
+    /// Steps where the bass plays the chord root
+    pub bass_steps: Vec<usize>,
+}
+
+impl GroovePattern {
+    /// Straight rock beat: kick on 1 and 3, snare on 2 and 4, eighth-note hats
+    pub fn rock() -> Self {
+        let mut drums = Vec::new();
+        for step in (0..STEPS_PER_BAR).step_by(2) {
+            drums.push(DrumHit { step, voice: DrumVoice::ClosedHat, velocity: if step % 4 == 0 { 90 } else { 70 } });
+        }
+        for step in [0, 8, 10] {
+            drums.push(DrumHit { step, voice: DrumVoice::Kick, velocity: 110 });
+        }
+        for step in [4, 12] {
+            drums.push(DrumHit { step, voice: DrumVoice::Snare, velocity: 110 });
+        }
+        Self {
+            name: "Rock".to_string(),
+            drums,
+            bass_steps: vec![0, 6, 8, 10],
+        }
+    }
+
+    /// Fast punk beat: kick and snare trading eighths, hats on every eighth
+    pub fn punk() -> Self {
+        let mut drums = Vec::new();
+        for step in (0..STEPS_PER_BAR).step_by(2) {
+            drums.push(DrumHit { step, voice: DrumVoice::ClosedHat, velocity: 95 });
+            let voice = if step % 4 == 0 { DrumVoice::Kick } else { DrumVoice::Snare };
+            drums.push(DrumHit { step, voice, velocity: 110 });
+        }
+        Self {
+            name: "Punk".to_string(),
+            drums,
+            bass_steps: (0..STEPS_PER_BAR).step_by(2).collect(),
+        }
+    }
+
+    /// Four on the floor with off-beat open hats and a snare on 2 and 4
+    pub fn edm() -> Self {
+        let mut drums = Vec::new();
+        for step in (0..STEPS_PER_BAR).step_by(4) {
+            drums.push(DrumHit { step, voice: DrumVoice::Kick, velocity: 120 });
+            drums.push(DrumHit { step: step + 2, voice: DrumVoice::OpenHat, velocity: 85 });
+        }
+        for step in (1..STEPS_PER_BAR).step_by(2) {
+            drums.push(DrumHit { step, voice: DrumVoice::ClosedHat, velocity: 55 });
+        }
+        for step in [4, 12] {
+            drums.push(DrumHit { step, voice: DrumVoice::Snare, velocity: 105 });
+        }
+        Self {
+            name: "EDM".to_string(),
+            drums,
+            bass_steps: vec![2, 6, 10, 14],
+        }
+    }
+
+    /// Double kick on every sixteenth under a half-time snare
+    pub fn metal() -> Self {
+        let mut drums = Vec::new();
+        for step in 0..STEPS_PER_BAR {
+            drums.push(DrumHit { step, voice: DrumVoice::Kick, velocity: if step % 4 == 0 { 115 } else { 95 } });
+        }
+        for step in (0..STEPS_PER_BAR).step_by(4) {
+            drums.push(DrumHit { step, voice: DrumVoice::ClosedHat, velocity: 90 });
+        }
+        drums.push(DrumHit { step: 0, voice: DrumVoice::Crash, velocity: 110 });
+        drums.push(DrumHit { step: 8, voice: DrumVoice::Snare, velocity: 120 });
+        Self {
+            name: "Metal".to_string(),
+            drums,
+            bass_steps: (0..STEPS_PER_BAR).step_by(2).collect(),
+        }
+    }
+
+    /// Names `from_name` knows
+    pub fn names() -> &'static [&'static str] {
+        &["rock", "punk", "edm", "metal"]
+    }
+
+    /// Groove by genre name (case-insensitive)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "rock" => Some(Self::rock()),
+            "punk" => Some(Self::punk()),
+            "edm" => Some(Self::edm()),
+            "metal" => Some(Self::metal()),
+            _ => None,
+        }
+    }
+}
+
+/// Where the generated bar sits relative to the song structure
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct BarContext {
+    /// Last bar before a section change (play a fill)
+    pub before_section_change: bool,
+    /// First bar of a new section (crash on the downbeat)
+    pub section_start: bool,
+}
+
+impl BarContext {
+    /// Context of the bar starting at `bar_start` from the beats the song's sections start on
+    pub fn at(section_starts: impl IntoIterator<Item = f64>, bar_start: f64, beats_per_bar: f64) -> Self {
+        let bar_end = bar_start + beats_per_bar;
+        let mut context = Self::default();
+        for start in section_starts {
+            context.section_start |= start >= bar_start && start < bar_end;
+            context.before_section_change |= start >= bar_end && start < bar_end + beats_per_bar;
+        }
+        context
+    }
+}
+
+/// Generated accompaniment for one bar
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct AccompanimentBar {
+    pub drums: Vec<DrumHit>,
+    pub bass: Vec<BassNote>,
+}
+
+/// Seeded generator turning a groove into varied bars
+pub struct Accompanist {
+    pattern: GroovePattern,
+    rng: fastrand::Rng,
+    /// 0.0 = play the groove as written, 1.0 = busy fills and heavy variation
+    intensity: f32,
+}
+
+impl Accompanist {
+    pub fn new(pattern: GroovePattern, seed: u64) -> Self {
+        Self {
+            pattern,
+            rng: fastrand::Rng::with_seed(seed),
+            intensity: 0.5,
+        }
+    }
+
+    pub fn set_intensity(&mut self, intensity: f32) {
+        self.intensity = intensity.clamp(0.0, 1.0);
+    }
+
+    pub fn intensity(&self) -> f32 {
+        self.intensity
+    }
+
+    /// Restart the random sequence (same seed = same performance)
+    pub fn reseed(&mut self, seed: u64) {
+        self.rng.seed(seed);
+    }
+
+    /// Generate the next bar for a chord root (MIDI note) in the given context
+    pub fn next_bar(&mut self, root_note: u8, context: BarContext) -> AccompanimentBar {
+        let fill_start = if context.before_section_change {
+            // Higher intensity = longer fill (one to two beats)
+            STEPS_PER_BAR - 4 - (self.intensity * 4.0).round() as usize
+        } else {
+            STEPS_PER_BAR
+        };
+
+        let mut drums = self.mutate_groove(fill_start);
+        self.add_ghost_notes(&mut drums, fill_start);
+        if context.before_section_change {
+            self.add_fill(&mut drums, fill_start);
+        }
+        if context.section_start {
+            drums.retain(|hit| !(hit.step == 0 && hit.voice == DrumVoice::ClosedHat));
+            drums.push(DrumHit { step: 0, voice: DrumVoice::Crash, velocity: 115 });
+        }
+        drums.sort_by_key(|hit| hit.step);
+
+        let bass = self.bass_line(root_note, fill_start);
+        AccompanimentBar { drums, bass }
+    }
+
+    /// Copy the groove up to the fill, occasionally dropping or moving hits
+    fn mutate_groove(&mut self, fill_start: usize) -> Vec<DrumHit> {
+        let mutation_chance = 0.05 + 0.15 * self.intensity;
+        let mut drums = Vec::with_capacity(self.pattern.drums.len() + 4);
+        for hit in &self.pattern.drums {
+            if hit.step >= fill_start {
+                continue;
+            }
+            let mut hit = *hit;
+            match hit.voice {
+                // Backbeat snare and downbeat kick always stay put
+                DrumVoice::Snare => {}
+                DrumVoice::Kick if hit.step == 0 => {}
+                DrumVoice::Kick if self.rng.f32() < mutation_chance => {
+                    // Push the kick a sixteenth late or early
+                    let shifted = if self.rng.bool() { hit.step + 1 } else { hit.step.saturating_sub(1) };
+                    hit.step = shifted.min(fill_start.saturating_sub(1));
+                }
+                DrumVoice::ClosedHat if self.rng.f32() < mutation_chance => {
+                    if self.rng.bool() {
+                        continue; // Drop the hat
+                    }
+                    hit.voice = DrumVoice::OpenHat;
+                }
+                _ => {}
+            }
+            hit.velocity = humanize_velocity(&mut self.rng, self.intensity, hit.velocity);
+            drums.push(hit);
+        }
+        drums
+    }
+
+    /// Quiet snare hits on off-beat sixteenths
+    fn add_ghost_notes(&mut self, drums: &mut Vec<DrumHit>, fill_start: usize) {
+        let ghost_chance = 0.1 * self.intensity;
+        for step in (1..fill_start).step_by(2) {
+            let occupied = drums.iter().any(|hit| hit.step == step && hit.voice == DrumVoice::Snare);
+            if !occupied && self.rng.f32() < ghost_chance {
+                let velocity = 25 + self.rng.u8(0..15);
+                drums.push(DrumHit { step, voice: DrumVoice::Snare, velocity });
+            }
+        }
+    }
+
+    /// Snare/tom run from `fill_start` to the end of the bar, crescendoing
+    fn add_fill(&mut self, drums: &mut Vec<DrumHit>, fill_start: usize) {
+        let voices = [DrumVoice::Snare, DrumVoice::HighTom, DrumVoice::LowTom];
+        let length = STEPS_PER_BAR - fill_start;
+        for (i, step) in (fill_start..STEPS_PER_BAR).enumerate() {
+            // Low intensity fills leave some sixteenths out
+            if self.intensity < 0.5 && step % 2 == 1 && self.rng.f32() > self.intensity * 2.0 {
+                continue;
+            }
+            // Move down the kit as the fill progresses
+            let voice = voices[(i * voices.len() / length.max(1)).min(voices.len() - 1)];
+            let velocity = 80 + (40 * i / length.max(1)) as u8;
+            drums.push(DrumHit { step, voice, velocity: humanize_velocity(&mut self.rng, self.intensity, velocity) });
+        }
+    }
+
+    /// Root notes on the groove's bass steps, with octave jumps and a
+    /// chromatic approach note leading into a section change
+    fn bass_line(&mut self, root_note: u8, fill_start: usize) -> Vec<BassNote> {
+        let variation_chance = 0.1 + 0.2 * self.intensity;
+        let mut steps: Vec<usize> = self.pattern.bass_steps.iter().copied().filter(|&s| s < fill_start).collect();
+        steps.sort_unstable();
+
+        let mut bass = Vec::with_capacity(steps.len() + 1);
+        for (i, &step) in steps.iter().enumerate() {
+            let next = steps.get(i + 1).copied().unwrap_or(fill_start);
+            let mut note = root_note;
+            if step != 0 && self.rng.f32() < variation_chance {
+                note = note.saturating_add(12).min(127);
+            }
+            bass.push(BassNote {
+                step,
+                note,
+                velocity: humanize_velocity(&mut self.rng, self.intensity, 100),
+                length: (next - step).max(1),
+            });
+        }
+        if fill_start < STEPS_PER_BAR {
+            // Half-step approach (from above or below) into the next section
+            let approach = if self.rng.bool() { root_note.saturating_sub(1) } else { root_note.saturating_add(1).min(127) };
+            bass.push(BassNote {
+                step: STEPS_PER_BAR - 2,
+                note: approach,
+                velocity: humanize_velocity(&mut self.rng, self.intensity, 95),
+                length: 2,
+            });
+        }
+        bass
+    }
+}
+
+/// Random velocity offset scaled by intensity
+fn humanize_velocity(rng: &mut fastrand::Rng, intensity: f32, velocity: u8) -> u8 {
+    let spread = 4 + (8.0 * intensity) as i32;
+    (velocity as i32 + rng.i32(-spread..=spread)).clamp(1, 127) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_same_seed_same_performance() {
+        let mut a = Accompanist::new(GroovePattern::rock(), 42);
+        let mut b = Accompanist::new(GroovePattern::rock(), 42);
+        for _ in 0..8 {
+            assert_eq!(a.next_bar(40, BarContext::default()), b.next_bar(40, BarContext::default()));
+        }
+    }
+
+    #[test]
+    fn test_fill_before_section_change() {
+        let mut accompanist = Accompanist::new(GroovePattern::rock(), 7);
+        accompanist.set_intensity(1.0);
+        let bar = accompanist.next_bar(40, BarContext { before_section_change: true, section_start: false });
+
+        let toms = bar.drums.iter().filter(|hit| matches!(hit.voice, DrumVoice::HighTom | DrumVoice::LowTom)).count();
+        assert!(toms > 0);
+        // No groove hats inside the fill
+        assert!(!bar.drums.iter().any(|hit| hit.step >= 8 && hit.voice == DrumVoice::ClosedHat));
+        assert!(bar.bass.iter().any(|note| note.note == 39 || note.note == 41));
+    }
+
+    #[test]
+    fn test_bar_context_from_sections() {
+        let sections = [0.0, 16.0];
+        assert_eq!(BarContext::at(sections, 12.0, 4.0), BarContext { before_section_change: true, section_start: false });
+        assert_eq!(BarContext::at(sections, 16.0, 4.0), BarContext { before_section_change: false, section_start: true });
+        assert_eq!(BarContext::at(sections, 4.0, 4.0), BarContext::default());
+    }
+
+    #[test]
+    fn test_section_start_crash_and_backbeat() {
+        let mut accompanist = Accompanist::new(GroovePattern::rock(), 1);
+        accompanist.set_intensity(0.0);
+        let bar = accompanist.next_bar(40, BarContext { before_section_change: false, section_start: true });
+
+        assert!(bar.drums.iter().any(|hit| hit.step == 0 && hit.voice == DrumVoice::Crash));
+        for step in [4, 12] {
+            assert!(bar.drums.iter().any(|hit| hit.step == step && hit.voice == DrumVoice::Snare));
+        }
+    }
+}
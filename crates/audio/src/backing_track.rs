@@ -0,0 +1,520 @@
+//! Backing track playback
+//! A song can come with a recording (WAV, MP3 or Ogg Vorbis) to play along to.
+//! The track streams from disk: a decoder thread keeps a ring buffer a couple
+//! of seconds ahead of the playhead and the engine mixes from it, so a long
+//! track never sits in memory whole. The song transport drives it by sending
+//! its position, play state and speed whenever they change (play, pause, seek,
+//! tempo). Between syncs the track advances with the audio clock itself, so it
+//! stays sample-locked to what is heard; a sync that moves the position makes
+//! the decoder seek, and the track is silent until it has caught up. Speed
+//! changes play the track faster or slower (pitch follows, there is no
+//! time-stretch).
+
+use anyhow::{Context, Result};
+use ringbuf::traits::{Consumer, Observer, Producer, Split};
+use ringbuf::HeapRb;
+use std::fs::File;
+use std::io::{Cursor, ErrorKind};
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use symphonia::core::audio::SampleBuffer;
+use symphonia::core::codecs::{Decoder, DecoderOptions, CODEC_TYPE_NULL};
+use symphonia::core::errors::Error as SymphoniaError;
+use symphonia::core::formats::{FormatOptions, FormatReader, SeekMode, SeekTo};
+use symphonia::core::io::{MediaSource, MediaSourceStream};
+use symphonia::core::meta::MetadataOptions;
+use symphonia::core::probe::Hint;
+
+/// Default backing track level under the guitar
+pub const DEFAULT_BACKING_TRACK_GAIN: f32 = 0.8;
+/// Decoded audio kept ahead of the playhead (seconds)
+const STREAM_BUFFER_SECONDS: usize = 2;
+/// A sync closer than this to the track's own position doesn't seek (seconds)
+const SEEK_TOLERANCE_SECONDS: f64 = 0.05;
+/// Decoder thread sleep while the ring buffer is full or a seek waits for it to drain
+const DECODER_IDLE: Duration = Duration::from_millis(5);
+
+/// Seek requests and end-of-track, shared by the decoder thread and the engine
+#[derive(Debug, Default)]
+struct StreamShared {
+    /// Seeks asked for so far; the decoder carries each out by seeking to `seek_frame`
+    seek_requested: AtomicU64,
+    /// Last seek the decoder has carried out
+    seek_done: AtomicU64,
+    seek_frame: AtomicU64,
+    /// Everything up to the end of the track is in the ring buffer
+    finished: AtomicBool,
+    /// The track was dropped: the decoder thread exits
+    stopped: AtomicBool,
+}
+
+/// Engine side of the stream: the ring buffer and the two frames the playhead
+/// sits between
+struct TrackReader {
+    consumer: ringbuf::HeapCons<f32>,
+    /// Track frame the next frame taken from the ring buffer is
+    next_frame: u64,
+    /// Frames `next_frame - 2` and `next_frame - 1`
+    previous: [f32; 2],
+    current: [f32; 2],
+}
+
+impl TrackReader {
+    /// Drop audio from before a seek until the decoder has carried it out
+    /// (true once the ring buffer plays from where it was asked to)
+    fn settle(&mut self, shared: &StreamShared) -> bool {
+        if shared.seek_done.load(Ordering::Acquire) == shared.seek_requested.load(Ordering::Relaxed) {
+            return true;
+        }
+        self.consumer.clear();
+        false
+    }
+
+    /// The frames at `index` and after it (the last frame twice at the end of
+    /// the track); None if the decoder hasn't got that far or the track is over
+    fn frames_at(&mut self, index: u64, shared: &StreamShared) -> Option<([f32; 2], [f32; 2])> {
+        while self.next_frame < index + 2 {
+            if self.consumer.occupied_len() < 2 {
+                let at_end = shared.finished.load(Ordering::Acquire) && self.consumer.occupied_len() < 2;
+                return (at_end && self.next_frame == index + 1).then_some((self.current, self.current));
+            }
+            let mut frame = [0.0; 2];
+            self.consumer.pop_slice(&mut frame);
+            self.previous = self.current;
+            self.current = frame;
+            self.next_frame += 1;
+        }
+        (self.next_frame == index + 2).then_some((self.previous, self.current))
+    }
+}
+
+/// A backing track streaming from disk, decoded to stereo
+pub struct BackingTrack {
+    sample_rate: u32,
+    /// Length, if the file declares it
+    duration_seconds: Option<f64>,
+    shared: Arc<StreamShared>,
+    /// Only the audio thread reads; the lock makes the track shareable
+    reader: Mutex<TrackReader>,
+}
+
+impl std::fmt::Debug for BackingTrack {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BackingTrack")
+            .field("sample_rate", &self.sample_rate)
+            .field("duration_seconds", &self.duration_seconds)
+            .finish_non_exhaustive()
+    }
+}
+
+impl BackingTrack {
+    /// Open a backing track and start streaming it from disk
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("Failed to read {}", path.display()))?;
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        Self::open(Box::new(file), extension).with_context(|| format!("Failed to decode {}", path.display()))
+    }
+
+    /// Stream a backing track held in memory
+    pub fn decode(bytes: Vec<u8>) -> Result<Self> {
+        Self::open(Box::new(Cursor::new(bytes)), None)
+    }
+
+    fn open(source: Box<dyn MediaSource>, extension: Option<&str>) -> Result<Self> {
+        let mut hint = Hint::new();
+        if let Some(extension) = extension {
+            hint.with_extension(extension);
+        }
+        let stream = MediaSourceStream::new(source, Default::default());
+        let format = symphonia::default::get_probe()
+            .format(&hint, stream, &FormatOptions::default(), &MetadataOptions::default())
+            .context("Unsupported backing track format (WAV, MP3 and Ogg Vorbis play)")?
+            .format;
+        let track = format.tracks().iter()
+            .find(|track| track.codec_params.codec != CODEC_TYPE_NULL)
+            .context("Backing track has no audio")?;
+        let sample_rate = track.codec_params.sample_rate
+            .filter(|rate| *rate > 0)
+            .context("Backing track has no sample rate")?;
+        let duration_seconds = track.codec_params.n_frames.map(|frames| frames as f64 / sample_rate as f64);
+        let decoder = symphonia::default::get_codecs()
+            .make(&track.codec_params, &DecoderOptions::default())
+            .context("Unsupported backing track codec")?;
+
+        let shared = Arc::new(StreamShared::default());
+        let (producer, consumer) = HeapRb::<f32>::new(sample_rate as usize * 2 * STREAM_BUFFER_SECONDS).split();
+        let stream = DecoderStream {
+            track_id: track.id,
+            format,
+            decoder,
+            producer,
+            shared: Arc::clone(&shared),
+            sample_buffer: None,
+            pending: Vec::new(),
+            pending_pos: 0,
+            skip_frames: 0,
+            finished: false,
+        };
+        std::thread::Builder::new()
+            .name("backing-track".to_string())
+            .spawn(move || stream.run())
+            .context("Failed to start the backing track decoder")?;
+
+        Ok(Self {
+            sample_rate,
+            duration_seconds,
+            shared,
+            reader: Mutex::new(TrackReader {
+                consumer,
+                next_frame: 0,
+                previous: [0.0; 2],
+                current: [0.0; 2],
+            }),
+        })
+    }
+
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Length in seconds, if the file declares it
+    pub fn duration_seconds(&self) -> Option<f64> {
+        self.duration_seconds
+    }
+
+    /// Have the decoder restart at a frame (negative = from the start); RT-safe
+    fn seek(&self, position: f64) {
+        let Ok(mut reader) = self.reader.try_lock() else { return };
+        let frame = position.max(0.0) as u64;
+        reader.next_frame = frame;
+        self.shared.seek_frame.store(frame, Ordering::Relaxed);
+        self.shared.seek_requested.fetch_add(1, Ordering::Release);
+        reader.settle(&self.shared);
+    }
+}
+
+impl Drop for BackingTrack {
+    fn drop(&mut self) {
+        self.shared.stopped.store(true, Ordering::Relaxed);
+    }
+}
+
+/// Decoder thread: decodes ahead into the ring buffer and carries out seeks
+struct DecoderStream {
+    format: Box<dyn FormatReader>,
+    decoder: Box<dyn Decoder>,
+    track_id: u32,
+    producer: ringbuf::HeapProd<f32>,
+    shared: Arc<StreamShared>,
+    sample_buffer: Option<SampleBuffer<f32>>,
+    /// Decoded stereo samples not in the ring buffer yet, from `pending_pos`
+    pending: Vec<f32>,
+    pending_pos: usize,
+    /// Frames still to drop to land exactly on a seek target
+    skip_frames: u64,
+    /// No more packets until the next seek
+    finished: bool,
+}
+
+impl DecoderStream {
+    fn run(mut self) {
+        let mut seek_done = 0;
+        while !self.shared.stopped.load(Ordering::Relaxed) {
+            let requested = self.shared.seek_requested.load(Ordering::Acquire);
+            if requested != seek_done {
+                // The engine drops what is still buffered; seeking once it is
+                // gone means nothing from before the seek follows it
+                if self.producer.occupied_len() > 0 {
+                    std::thread::sleep(DECODER_IDLE);
+                    continue;
+                }
+                self.seek(self.shared.seek_frame.load(Ordering::Relaxed));
+                seek_done = requested;
+                self.shared.seek_done.store(requested, Ordering::Release);
+                continue;
+            }
+
+            if self.pending_pos == self.pending.len() {
+                if self.finished {
+                    self.shared.finished.store(true, Ordering::Release);
+                    std::thread::sleep(DECODER_IDLE);
+                } else {
+                    self.decode_packet();
+                }
+                continue;
+            }
+
+            // Whole frames only, so the engine always reads a frame at once
+            let count = self.producer.vacant_len().min(self.pending.len() - self.pending_pos) & !1;
+            let pushed = self.producer.push_slice(&self.pending[self.pending_pos..self.pending_pos + count]);
+            self.pending_pos += pushed;
+            if pushed == 0 {
+                std::thread::sleep(DECODER_IDLE);
+            }
+        }
+    }
+
+    fn seek(&mut self, frame: u64) {
+        self.pending.clear();
+        self.pending_pos = 0;
+        self.skip_frames = 0;
+        self.shared.finished.store(false, Ordering::Relaxed);
+        // WAV, MP3 and Vorbis timestamps count frames
+        let to = SeekTo::TimeStamp { ts: frame, track_id: self.track_id };
+        match self.format.seek(SeekMode::Accurate, to) {
+            Ok(seeked) => {
+                self.skip_frames = seeked.required_ts.saturating_sub(seeked.actual_ts);
+                self.finished = false;
+            }
+            // Past the end of the track (or a stream that can't seek): silence
+            Err(e) => {
+                log::debug!("Backing track seek to frame {} failed: {}", frame, e);
+                self.finished = true;
+            }
+        }
+        self.decoder.reset();
+    }
+
+    /// Decode the next packet of the track into `pending`
+    fn decode_packet(&mut self) {
+        loop {
+            let packet = match self.format.next_packet() {
+                Ok(packet) => packet,
+                Err(SymphoniaError::IoError(e)) if e.kind() == ErrorKind::UnexpectedEof => {
+                    self.finished = true;
+                    return;
+                }
+                Err(e) => {
+                    log::warn!("Backing track stopped: {}", e);
+                    self.finished = true;
+                    return;
+                }
+            };
+            if packet.track_id() != self.track_id {
+                continue;
+            }
+            let decoded = match self.decoder.decode(&packet) {
+                Ok(decoded) => decoded,
+                // A damaged packet is skipped
+                Err(SymphoniaError::DecodeError(e)) => {
+                    log::debug!("Backing track packet skipped: {}", e);
+                    continue;
+                }
+                Err(e) => {
+                    log::warn!("Backing track stopped: {}", e);
+                    self.finished = true;
+                    return;
+                }
+            };
+
+            let spec = *decoded.spec();
+            let channels = spec.channels.count();
+            if channels == 0 {
+                continue;
+            }
+            let needed = decoded.capacity() * channels;
+            let buffer = match &mut self.sample_buffer {
+                Some(buffer) if buffer.capacity() >= needed => buffer,
+                buffer => buffer.insert(SampleBuffer::new(decoded.capacity() as u64, spec)),
+            };
+            buffer.copy_interleaved_ref(decoded);
+
+            let frames = buffer.samples().chunks_exact(channels);
+            let skip = (self.skip_frames as usize).min(frames.len());
+            self.skip_frames -= skip as u64;
+            self.pending.clear();
+            self.pending_pos = 0;
+            // Mono is doubled; channels past the first two are dropped
+            self.pending.extend(frames.skip(skip).flat_map(|frame| [frame[0], frame[1.min(channels - 1)]]));
+            return;
+        }
+    }
+}
+
+/// Plays a backing track inside the engine (RT-safe: no allocation in `render`)
+#[derive(Debug)]
+pub struct BackingTrackPlayer {
+    track: Option<Arc<BackingTrack>>,
+    output_rate: u32,
+    /// Read position in track frames (negative = still counting in)
+    position: f64,
+    playing: bool,
+    speed: f64,
+    gain: f32,
+}
+
+impl BackingTrackPlayer {
+    pub fn new(output_rate: u32) -> Self {
+        Self {
+            track: None,
+            output_rate,
+            position: 0.0,
+            playing: false,
+            speed: 1.0,
+            gain: DEFAULT_BACKING_TRACK_GAIN,
+        }
+    }
+
+    /// Swap the track (None removes it); playback stops until the next sync
+    pub fn set_track(&mut self, track: Option<Arc<BackingTrack>>) {
+        // A track replayed after a stream rebuild may have played on already
+        if let Some(track) = &track {
+            track.seek(0.0);
+        }
+        self.track = track;
+        self.position = 0.0;
+        self.playing = false;
+    }
+
+    pub fn has_track(&self) -> bool {
+        self.track.is_some()
+    }
+
+    /// Follow the transport: position in seconds of the track, play state and speed
+    pub fn sync(&mut self, seconds: f64, playing: bool, speed: f64) {
+        let Some(track) = &self.track else { return };
+        let position = seconds * track.sample_rate as f64;
+        // Small corrections aren't worth the gap while the decoder seeks
+        if (position - self.position).abs() > SEEK_TOLERANCE_SECONDS * track.sample_rate as f64 {
+            track.seek(position);
+            self.position = position;
+        }
+        self.playing = playing;
+        self.speed = if speed > 0.0 { speed } else { 1.0 };
+    }
+
+    pub fn set_gain(&mut self, gain: f32) {
+        self.gain = gain.clamp(0.0, 2.0);
+    }
+
+    /// Track position in seconds
+    pub fn position_seconds(&self) -> f64 {
+        self.track.as_ref().map(|track| self.position / track.sample_rate as f64).unwrap_or(0.0)
+    }
+
+    /// Mix the track into an interleaved stereo buffer
+    pub fn render(&mut self, buffer: &mut [f32]) {
+        let Some(track) = &self.track else { return };
+        let Ok(mut reader) = track.reader.try_lock() else { return };
+        let settled = reader.settle(&track.shared);
+        if !self.playing {
+            return;
+        }
+        let step = track.sample_rate as f64 / self.output_rate as f64 * self.speed;
+        for out in buffer.chunks_exact_mut(2) {
+            let position = self.position;
+            self.position += step;
+            // Until a seek is done the position runs on and is caught up with after
+            if !settled || position < 0.0 {
+                continue;
+            }
+            let index = position as u64;
+            let Some((a, b)) = reader.frames_at(index, &track.shared) else { continue };
+            let fraction = (position - index as f64) as f32;
+            for (channel, out) in out.iter_mut().enumerate() {
+                *out = (*out + (a[channel] + (b[channel] - a[channel]) * fraction) * self.gain).clamp(-1.0, 1.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::preview::{encode_wav, encode_wav_channels};
+
+    fn ramp_track(frames: usize, sample_rate: u32) -> Arc<BackingTrack> {
+        let samples: Vec<f32> = (0..frames).flat_map(|i| {
+            let value = i as f32 / frames as f32;
+            [value, -value]
+        }).collect();
+        Arc::new(BackingTrack::decode(encode_wav_channels(&samples, 2, sample_rate)).unwrap())
+    }
+
+    /// Wait for the decoder to carry out seeks and fill the ring buffer
+    fn wait_for_decoder(player: &BackingTrackPlayer) {
+        let track = player.track.as_ref().unwrap();
+        for _ in 0..2000 {
+            let mut reader = track.reader.lock().unwrap();
+            if reader.settle(&track.shared)
+                && (reader.consumer.occupied_len() >= 2048 || track.shared.finished.load(Ordering::Acquire))
+            {
+                return;
+            }
+            drop(reader);
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        panic!("Backing track decoder didn't catch up");
+    }
+
+    #[test]
+    fn test_sync_sets_position_and_speed() {
+        let mut player = BackingTrackPlayer::new(48000);
+        player.set_gain(1.0);
+        player.set_track(Some(ramp_track(48000, 48000)));
+
+        // Stopped: nothing is mixed
+        let mut buffer = vec![0.0; 64];
+        player.render(&mut buffer);
+        assert!(buffer.iter().all(|s| *s == 0.0));
+
+        player.sync(0.5, true, 1.0);
+        wait_for_decoder(&player);
+        player.render(&mut buffer);
+        assert!((buffer[0] - 0.5).abs() < 1e-3);
+        assert!((buffer[1] + 0.5).abs() < 1e-3);
+        assert!((player.position_seconds() - (0.5 + 32.0 / 48000.0)).abs() < 1e-9);
+
+        // Double speed covers twice the track per output frame
+        player.sync(0.0, true, 2.0);
+        wait_for_decoder(&player);
+        buffer.fill(0.0);
+        player.render(&mut buffer);
+        assert!(buffer[0].abs() < 1e-3);
+        assert!((buffer[62] - 62.0 / 48000.0).abs() < 1e-3);
+        assert!((player.position_seconds() - 64.0 / 48000.0).abs() < 1e-9);
+
+        // A sync close to where the track is doesn't seek
+        player.sync(65.0 / 48000.0, true, 1.0);
+        assert!((player.position_seconds() - 64.0 / 48000.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_count_in_and_end_are_silent() {
+        let mut player = BackingTrackPlayer::new(48000);
+        player.set_track(Some(ramp_track(100, 48000)));
+        player.sync(-1.0, true, 1.0);
+        wait_for_decoder(&player);
+        let mut buffer = vec![0.0; 64];
+        player.render(&mut buffer);
+        assert!(buffer.iter().all(|s| *s == 0.0));
+
+        player.sync(10.0, true, 1.0);
+        wait_for_decoder(&player);
+        player.render(&mut buffer);
+        assert!(buffer.iter().all(|s| *s == 0.0));
+    }
+
+    #[test]
+    fn test_decode_wav_and_reject_unknown_formats() {
+        let wav = encode_wav(&[0.5, -0.5], 8000);
+        let track = Arc::new(BackingTrack::decode(wav).unwrap());
+        assert_eq!(track.sample_rate(), 8000);
+        assert_eq!(track.duration_seconds(), Some(2.0 / 8000.0));
+        let mut player = BackingTrackPlayer::new(8000);
+        player.set_gain(1.0);
+        player.set_track(Some(track));
+        player.sync(0.0, true, 1.0);
+        wait_for_decoder(&player);
+        let mut buffer = vec![0.0; 4];
+        player.render(&mut buffer);
+        // Mono is doubled to both channels
+        for (sample, expected) in buffer.iter().zip([0.5, 0.5, -0.5, -0.5]) {
+            assert!((sample - expected).abs() < 1e-3);
+        }
+
+        assert!(BackingTrack::decode(b"not audio at all".to_vec()).is_err());
+    }
+}
@@ -0,0 +1,166 @@
+//! Offline chart bounce
+//! Plays timed chords through a private engine at file speed (the live RT
+//! stream is untouched) and encodes the result as a stereo WAV, so a chart can
+//! be exported as a demo or backing track without playing it live. Chords are
+//! strummed down on the guitar voicing of their symbol.
+
+use anyhow::Result;
+use mapping::guitar_voicing::StrumDirection;
+use mapping::{ChordSpec, MusicEvent};
+
+use crate::engine::AudioEngine;
+use crate::preview::{encode_wav_channels, load_source, PreviewSource};
+
+const RENDER_BLOCK_FRAMES: usize = 256;
+/// Longest bounce rendered (keeps a broken chart from filling the memory)
+pub const MAX_BOUNCE_SECS: f64 = 30.0 * 60.0;
+
+/// A chord to bounce
+#[derive(Debug, Clone, PartialEq)]
+pub struct BounceChord {
+    pub start_secs: f64,
+    pub duration_secs: f64,
+    /// MIDI notes, strummed low to high
+    pub notes: Vec<u8>,
+    pub velocity: u8,
+}
+
+impl BounceChord {
+    /// Chord from a symbol such as "Am7" (None when it can't be parsed)
+    pub fn from_symbol(symbol: &str, start_secs: f64, duration_secs: f64, velocity: u8) -> Option<Self> {
+        let spec = ChordSpec::parse(symbol)?;
+        Some(Self {
+            start_secs,
+            duration_secs,
+            notes: spec.guitar_voicing().notes(),
+            velocity,
+        })
+    }
+}
+
+/// Bounce rendering options
+#[derive(Debug, Clone)]
+pub struct BounceOptions {
+    pub sample_rate: u32,
+    /// Delay between successive strings (ms)
+    pub strum_delay_ms: f32,
+    /// Release tail rendered after the last chord (seconds)
+    pub tail_secs: f32,
+}
+
+impl Default for BounceOptions {
+    fn default() -> Self {
+        Self {
+            sample_rate: 48000,
+            strum_delay_ms: 15.0,
+            tail_secs: 2.0,
+        }
+    }
+}
+
+/// Render chords, returning interleaved stereo samples. `progress` gets the
+/// fraction done after each block; an error from it stops the render.
+pub fn render_bounce(
+    source: &PreviewSource,
+    chords: &[BounceChord],
+    options: &BounceOptions,
+    mut progress: impl FnMut(f32) -> Result<()>,
+) -> Result<Vec<f32>> {
+    let sample_rate = options.sample_rate.max(8000);
+    let end_secs = chords.iter().map(|chord| chord.start_secs + chord.duration_secs.max(0.0)).fold(0.0, f64::max);
+    if end_secs > MAX_BOUNCE_SECS {
+        anyhow::bail!("Chart is {:.0} minutes long, bounces stop at {:.0}", end_secs / 60.0, MAX_BOUNCE_SECS / 60.0);
+    }
+    let mut engine = AudioEngine::new(sample_rate);
+    load_source(&mut engine, source)?;
+
+    let to_frame = |secs: f64| (secs.max(0.0) * sample_rate as f64).round() as usize;
+    let strum_secs = options.strum_delay_ms.max(0.0) as f64 / 1000.0;
+    // Note-offs sort before note-ons on the same frame, so a repeated chord restrikes
+    let mut events: Vec<(usize, bool, MusicEvent)> = Vec::new();
+    for chord in chords {
+        let end = to_frame(chord.start_secs + chord.duration_secs.max(0.0));
+        for (note, delay_ms) in mapping::guitar_voicing::strum_order(&chord.notes, StrumDirection::Down, strum_secs as f32 * 1000.0) {
+            let start = to_frame(chord.start_secs + delay_ms as f64 / 1000.0);
+            events.push((start.min(end), true, MusicEvent::NoteOn { note, velocity: chord.velocity }));
+            events.push((end, false, MusicEvent::NoteOff { note }));
+        }
+    }
+    events.sort_by_key(|(frame, on, _)| (*frame, *on));
+
+    let total_frames = to_frame(end_secs) + to_frame(options.tail_secs.max(0.0) as f64);
+    let mut output = Vec::with_capacity(total_frames * 2);
+    let mut next_event = 0;
+    let mut frame = 0;
+    while frame < total_frames {
+        while let Some((_, _, event)) = events.get(next_event).filter(|(at, _, _)| *at <= frame) {
+            engine.handle_event(event.clone());
+            next_event += 1;
+        }
+        // Stop at the next event so strums stay sample-accurate
+        let mut frames = RENDER_BLOCK_FRAMES.min(total_frames - frame);
+        if let Some((at, _, _)) = events.get(next_event) {
+            frames = frames.min(at - frame);
+        }
+        let start = output.len();
+        output.resize(start + frames * 2, 0.0);
+        engine.render(&mut output[start..]);
+        frame += frames;
+        progress(frame as f32 / total_frames as f32)?;
+    }
+    Ok(output)
+}
+
+/// Render chords and encode them as a 16-bit stereo WAV in one step
+pub fn render_bounce_wav(
+    source: &PreviewSource,
+    chords: &[BounceChord],
+    options: &BounceOptions,
+    progress: impl FnMut(f32) -> Result<()>,
+) -> Result<Vec<u8>> {
+    let samples = render_bounce(source, chords, options, progress)?;
+    Ok(encode_wav_channels(&samples, 2, options.sample_rate.max(8000)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::synth::InstrumentType as SynthInstrumentType;
+
+    #[test]
+    fn test_bounce_renders_chords_in_place() {
+        let options = BounceOptions { sample_rate: 8000, tail_secs: 0.5, ..Default::default() };
+        let chords = vec![
+            BounceChord::from_symbol("E", 0.5, 0.5, 100).unwrap(),
+            BounceChord::from_symbol("Am", 1.0, 0.5, 100).unwrap(),
+        ];
+        assert!(BounceChord::from_symbol("H7", 0.0, 1.0, 100).is_none());
+
+        let mut reports = 0;
+        let source = PreviewSource::Virtual(SynthInstrumentType::CleanElectricGuitar);
+        let samples = render_bounce(&source, &chords, &options, |_| {
+            reports += 1;
+            Ok(())
+        })
+        .unwrap();
+        // Chords end at 1.5 s, plus the tail
+        assert_eq!(samples.len(), 2 * 8000 * 2);
+        assert!(reports > 0);
+        // Silent until the first chord, sounding after it
+        assert!(samples[..2 * 4000].iter().all(|s| *s == 0.0));
+        assert!(samples[2 * 4000..2 * 6000].iter().any(|s| s.abs() > 0.0));
+
+        let wav = encode_wav_channels(&samples, 2, 8000);
+        assert_eq!(u16::from_le_bytes([wav[22], wav[23]]), 2);
+    }
+
+    #[test]
+    fn test_bounce_can_be_cancelled() {
+        let chords = vec![BounceChord::from_symbol("G", 0.0, 10.0, 100).unwrap()];
+        let source = PreviewSource::Virtual(SynthInstrumentType::CleanElectricGuitar);
+        let result = render_bounce(&source, &chords, &BounceOptions::default(), |done| {
+            if done > 0.1 { anyhow::bail!("cancelled") } else { Ok(()) }
+        });
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,95 @@
+//! Metronome click
+//! A short decaying sine blip mixed over everything else, used for the song
+//! count-in. The downbeat is pitched higher so the bar start can be heard.
+
+use std::f32::consts::TAU;
+
+const DOWNBEAT_FREQUENCY: f32 = 1760.0;
+const BEAT_FREQUENCY: f32 = 1320.0;
+const CLICK_SECONDS: f32 = 0.04;
+const CLICK_GAIN: f32 = 0.5;
+
+/// Click generator (RT-safe)
+#[derive(Debug)]
+pub struct Click {
+    sample_rate: u32,
+    frequency: f32,
+    phase: f32,
+    /// Frames left in the current click (0 = silent)
+    remaining: u32,
+    length: u32,
+}
+
+impl Click {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            frequency: BEAT_FREQUENCY,
+            phase: 0.0,
+            remaining: 0,
+            length: ((sample_rate as f32 * CLICK_SECONDS) as u32).max(1),
+        }
+    }
+
+    /// Start a click, restarting one still sounding
+    pub fn trigger(&mut self, downbeat: bool) {
+        self.frequency = if downbeat { DOWNBEAT_FREQUENCY } else { BEAT_FREQUENCY };
+        self.phase = 0.0;
+        self.remaining = self.length;
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.remaining > 0
+    }
+
+    /// Mix the click into an interleaved stereo buffer
+    pub fn render(&mut self, buffer: &mut [f32]) {
+        let step = self.frequency / self.sample_rate as f32;
+        for frame in buffer.chunks_exact_mut(2) {
+            if self.remaining == 0 {
+                return;
+            }
+            let envelope = self.remaining as f32 / self.length as f32;
+            let sample = (self.phase * TAU).sin() * envelope * envelope * CLICK_GAIN;
+            self.phase = (self.phase + step).fract();
+            self.remaining -= 1;
+            for out in frame {
+                *out = (*out + sample).clamp(-1.0, 1.0);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_click_decays_to_silence() {
+        let mut click = Click::new(48000);
+        let mut buffer = vec![0.0; 256];
+        click.render(&mut buffer);
+        assert!(buffer.iter().all(|s| *s == 0.0));
+
+        click.trigger(true);
+        let mut buffer = vec![0.0; 48000 / 10 * 2];
+        click.render(&mut buffer);
+        assert!(buffer.iter().any(|s| s.abs() > 0.1));
+        assert!(!click.is_active());
+        // Past the click length everything is silent
+        assert!(buffer[(48000.0 * CLICK_SECONDS) as usize * 2..].iter().all(|s| *s == 0.0));
+    }
+
+    #[test]
+    fn test_downbeat_is_higher() {
+        let crossings = |downbeat: bool| {
+            let mut click = Click::new(48000);
+            click.trigger(downbeat);
+            let mut buffer = vec![0.0; 1024];
+            click.render(&mut buffer);
+            buffer.chunks(2).map(|f| f[0]).collect::<Vec<_>>()
+                .windows(2).filter(|w| w[0] <= 0.0 && w[1] > 0.0).count()
+        };
+        assert!(crossings(true) > crossings(false));
+    }
+}
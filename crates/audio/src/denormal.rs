@@ -0,0 +1,100 @@
+//! Denormal protection and stream warm-up
+//! Decaying feedback (the pluck delay line, the voice low-pass, reverb-like
+//! tails in SoundFonts) ends in denormal floats, which some CPUs process up
+//! to a hundred times slower; a note fading out could then blow the callback
+//! deadline. The audio thread turns on flush-to-zero / denormals-are-zero on
+//! its first callback, and the synth's recursive state is flushed explicitly
+//! for CPUs without those modes. Before the stream starts the engine renders
+//! a short silent block so first-use costs (page faults, cold caches, lazy
+//! allocations) don't land on the first note.
+
+use std::time::Duration;
+
+use crate::engine::AudioEngine;
+
+/// Magnitude below which recursive synth state is treated as silence
+/// (about -300 dB, far above the denormal range of 1e-38)
+const DENORMAL_THRESHOLD: f32 = 1e-15;
+
+/// Length of the silent warm-up render
+pub const WARM_UP_DURATION: Duration = Duration::from_millis(50);
+
+/// Frames rendered per warm-up block (a typical callback size)
+const WARM_UP_BLOCK_FRAMES: usize = 256;
+
+/// Flush a recursive state value that has decayed to (near) denormal range
+#[inline]
+pub fn flush_denormal(value: f32) -> f32 {
+    if value.abs() < DENORMAL_THRESHOLD {
+        0.0
+    } else {
+        value
+    }
+}
+
+/// Turn on flush-to-zero and denormals-are-zero for the calling thread.
+/// Returns false on CPUs without those modes (the explicit flushing still applies).
+#[cfg(any(target_arch = "x86", target_arch = "x86_64"))]
+pub fn enable_flush_to_zero() -> bool {
+    /// MXCSR flush-to-zero (bit 15) and denormals-are-zero (bit 6)
+    const FTZ_DAZ: u32 = (1 << 15) | (1 << 6);
+    let mut csr: u32 = 0;
+    // SAFETY: stmxcsr/ldmxcsr only read and write the SSE control register of this thread
+    unsafe {
+        std::arch::asm!("stmxcsr [{}]", in(reg) &mut csr, options(nostack));
+        csr |= FTZ_DAZ;
+        std::arch::asm!("ldmxcsr [{}]", in(reg) &csr, options(nostack));
+    }
+    true
+}
+
+/// Turn on flush-to-zero for the calling thread (FPCR.FZ, which also treats denormal inputs as zero)
+#[cfg(target_arch = "aarch64")]
+pub fn enable_flush_to_zero() -> bool {
+    const FPCR_FZ: u64 = 1 << 24;
+    // SAFETY: only the floating point control register of this thread is changed
+    unsafe {
+        let mut fpcr: u64;
+        std::arch::asm!("mrs {}, fpcr", out(reg) fpcr, options(nomem, nostack));
+        fpcr |= FPCR_FZ;
+        std::arch::asm!("msr fpcr, {}", in(reg) fpcr, options(nomem, nostack));
+    }
+    true
+}
+
+#[cfg(not(any(target_arch = "x86", target_arch = "x86_64", target_arch = "aarch64")))]
+pub fn enable_flush_to_zero() -> bool {
+    false
+}
+
+/// Render `WARM_UP_DURATION` of silence through the engine and discard it
+pub fn warm_up(engine: &mut AudioEngine, sample_rate: u32) {
+    let mut buffer = vec![0.0f32; WARM_UP_BLOCK_FRAMES * 2];
+    let frames = (WARM_UP_DURATION.as_secs_f64() * sample_rate as f64) as usize;
+    for _ in 0..frames.div_ceil(WARM_UP_BLOCK_FRAMES) {
+        engine.render(&mut buffer);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flush_denormal() {
+        assert_eq!(flush_denormal(1e-30), 0.0);
+        assert_eq!(flush_denormal(-f32::MIN_POSITIVE / 2.0), 0.0);
+        assert_eq!(flush_denormal(0.25), 0.25);
+        assert_eq!(flush_denormal(-1e-6), -1e-6);
+    }
+
+    #[test]
+    fn test_flush_to_zero_on_this_thread() {
+        if !enable_flush_to_zero() {
+            return;
+        }
+        // A denormal result is flushed once the mode is on
+        let tiny = std::hint::black_box(f32::MIN_POSITIVE);
+        assert_eq!(tiny / std::hint::black_box(4.0), 0.0);
+    }
+}
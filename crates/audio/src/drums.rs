@@ -0,0 +1,264 @@
+//! Drum machine
+//! Steps through a genre groove (see `GroovePattern`) and plays it on a small
+//! synthesized kit, inside the audio callback so every hit lands on its
+//! sample. It keeps its own tempo, or follows the song transport, which sends
+//! its beat, tempo and play state the way it does for the backing track.
+
+use std::f32::consts::TAU;
+use crate::accompaniment::{DrumVoice, GroovePattern, STEPS_PER_BAR};
+
+/// Default drum level under the guitar
+pub const DEFAULT_DRUM_VOLUME: f32 = 0.7;
+/// Steps per beat (the groove is in sixteenths)
+const STEPS_PER_BEAT: f64 = 4.0;
+/// Drum sounds ringing at once (the oldest is cut for a new one)
+const MAX_DRUM_SOUNDS: usize = 12;
+
+/// Decay time (seconds to fall by 1/e) of a kit piece
+fn decay_seconds(voice: DrumVoice) -> f32 {
+    match voice {
+        DrumVoice::Kick => 0.12,
+        DrumVoice::Snare => 0.07,
+        DrumVoice::ClosedHat => 0.02,
+        DrumVoice::OpenHat => 0.12,
+        DrumVoice::Crash => 0.45,
+        DrumVoice::HighTom | DrumVoice::LowTom => 0.1,
+    }
+}
+
+/// A kit piece that is sounding
+#[derive(Debug, Clone, Copy)]
+struct DrumSound {
+    voice: DrumVoice,
+    gain: f32,
+    /// Frames since the hit
+    age: u32,
+    phase: f32,
+    /// High-pass state of the cymbal noise
+    last_noise: f32,
+}
+
+/// Step sequencer and kit (RT-safe: `render` doesn't allocate)
+pub struct DrumMachine {
+    sample_rate: u32,
+    pattern: GroovePattern,
+    bpm: f64,
+    /// Position in steps from beat 0
+    position: f64,
+    /// Next step to play
+    next_step: i64,
+    running: bool,
+    /// Stopped by the transport (paused), not by the user
+    held: bool,
+    volume: f32,
+    sounds: [Option<DrumSound>; MAX_DRUM_SOUNDS],
+    noise: u32,
+}
+
+impl DrumMachine {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            pattern: GroovePattern::rock(),
+            bpm: 120.0,
+            position: 0.0,
+            next_step: 0,
+            running: false,
+            held: false,
+            volume: DEFAULT_DRUM_VOLUME,
+            sounds: [None; MAX_DRUM_SOUNDS],
+            noise: 0x1234_5678,
+        }
+    }
+
+    /// Play a groove from a beat (negative beats count in silently)
+    pub fn start(&mut self, pattern: GroovePattern, bpm: f64, beat: f64) {
+        self.pattern = pattern;
+        self.running = true;
+        self.held = false;
+        self.sync(beat, bpm, true);
+    }
+
+    pub fn stop(&mut self) {
+        self.running = false;
+    }
+
+    pub fn is_running(&self) -> bool {
+        self.running
+    }
+
+    /// Move to the transport's beat, tempo and play state
+    pub fn sync(&mut self, beat: f64, bpm: f64, playing: bool) {
+        if bpm > 0.0 {
+            self.bpm = bpm;
+        }
+        self.position = beat * STEPS_PER_BEAT;
+        self.next_step = self.position.ceil() as i64;
+        self.held = !playing;
+    }
+
+    pub fn set_volume(&mut self, volume: f32) {
+        self.volume = volume.clamp(0.0, 1.0);
+    }
+
+    pub fn volume(&self) -> f32 {
+        self.volume
+    }
+
+    /// Mix the drums into an interleaved stereo buffer
+    pub fn render(&mut self, buffer: &mut [f32]) {
+        let advancing = self.running && !self.held;
+        if !advancing && self.sounds.iter().all(Option::is_none) {
+            return;
+        }
+        let step_per_frame = self.bpm / 60.0 * STEPS_PER_BEAT / self.sample_rate as f64;
+        for frame in buffer.chunks_exact_mut(2) {
+            if advancing {
+                while self.position >= self.next_step as f64 {
+                    self.play_step(self.next_step);
+                    self.next_step += 1;
+                }
+                self.position += step_per_frame;
+            }
+            let sample = self.render_sounds() * self.volume;
+            for out in frame {
+                *out = (*out + sample).clamp(-1.0, 1.0);
+            }
+        }
+    }
+
+    /// Trigger the groove's hits at an absolute step
+    fn play_step(&mut self, step: i64) {
+        if step < 0 {
+            return;
+        }
+        let step = step as usize % STEPS_PER_BAR;
+        for index in 0..self.pattern.drums.len() {
+            let hit = self.pattern.drums[index];
+            if hit.step == step {
+                self.trigger(hit.voice, hit.velocity);
+            }
+        }
+    }
+
+    /// Sound a kit piece
+    pub fn trigger(&mut self, voice: DrumVoice, velocity: u8) {
+        // A hi-hat chokes the open one
+        if matches!(voice, DrumVoice::ClosedHat | DrumVoice::OpenHat) {
+            for sound in self.sounds.iter_mut() {
+                if sound.is_some_and(|sound| sound.voice == DrumVoice::OpenHat) {
+                    *sound = None;
+                }
+            }
+        }
+        let sound = DrumSound { voice, gain: velocity as f32 / 127.0, age: 0, phase: 0.0, last_noise: 0.0 };
+        let slot = self.sounds.iter().position(Option::is_none)
+            .unwrap_or_else(|| {
+                // Cut the oldest sound
+                self.sounds.iter().enumerate()
+                    .max_by_key(|(_, sound)| sound.map_or(0, |sound| sound.age))
+                    .map_or(0, |(index, _)| index)
+            });
+        self.sounds[slot] = Some(sound);
+    }
+
+    fn render_sounds(&mut self) -> f32 {
+        let sample_rate = self.sample_rate as f32;
+        let mut mix = 0.0;
+        for slot in self.sounds.iter_mut() {
+            let Some(sound) = slot else { continue };
+            let t = sound.age as f32 / sample_rate;
+            let decay = decay_seconds(sound.voice);
+            if t > decay * 8.0 {
+                *slot = None;
+                continue;
+            }
+            let envelope = (-t / decay).exp();
+            self.noise ^= self.noise << 13;
+            self.noise ^= self.noise >> 17;
+            self.noise ^= self.noise << 5;
+            let noise = self.noise as f32 / u32::MAX as f32 * 2.0 - 1.0;
+
+            let value = match sound.voice {
+                DrumVoice::Kick => tone(sound, 50.0 + 110.0 * (-t / 0.03).exp(), sample_rate),
+                DrumVoice::HighTom => tone(sound, 140.0 + 80.0 * (-t / 0.05).exp(), sample_rate),
+                DrumVoice::LowTom => tone(sound, 90.0 + 50.0 * (-t / 0.05).exp(), sample_rate),
+                DrumVoice::Snare => tone(sound, 185.0, sample_rate) * 0.4 + noise * 0.6,
+                DrumVoice::ClosedHat | DrumVoice::OpenHat | DrumVoice::Crash => {
+                    // First-difference high-pass keeps the noise bright
+                    let bright = noise - sound.last_noise;
+                    sound.last_noise = noise;
+                    bright * 0.35
+                }
+            };
+            mix += value * envelope * sound.gain;
+            sound.age += 1;
+        }
+        mix
+    }
+}
+
+/// Next sample of a sine at `frequency`
+fn tone(sound: &mut DrumSound, frequency: f32, sample_rate: f32) -> f32 {
+    let value = (sound.phase * TAU).sin();
+    sound.phase = (sound.phase + frequency / sample_rate).fract();
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accompaniment::DrumHit;
+
+    /// One kick on the downbeat of every bar
+    fn kick_pattern() -> GroovePattern {
+        GroovePattern {
+            name: "Kick".to_string(),
+            drums: vec![DrumHit { step: 0, voice: DrumVoice::Kick, velocity: 127 }],
+            bass_steps: vec![],
+        }
+    }
+
+    /// First frame (after `from`) louder than a threshold
+    fn onset(buffer: &[f32], from: usize) -> Option<usize> {
+        buffer.chunks(2).skip(from).position(|frame| frame[0].abs() > 0.01).map(|frame| frame + from)
+    }
+
+    /// A hit sounds at `frame` (the kick's sine starts from zero, give it a few frames)
+    fn hit_at(buffer: &[f32], from: usize, frame: usize) -> bool {
+        onset(buffer, from).is_some_and(|onset| onset >= frame && onset < frame + 4)
+    }
+
+    #[test]
+    fn test_hits_land_on_the_beat() {
+        // 120 BPM at 8 kHz: a beat is 4000 frames, a 4/4 bar 16000
+        let mut drums = DrumMachine::new(8000);
+        drums.start(kick_pattern(), 120.0, 0.0);
+        let mut buffer = vec![0.0; 20000 * 2];
+        drums.render(&mut buffer);
+        assert!(hit_at(&buffer, 0, 0));
+        // The kick dies away, and the next one comes a bar later
+        assert!(buffer[8000 * 2..16000 * 2].iter().all(|s| s.abs() < 0.01));
+        assert!(hit_at(&buffer, 8000, 16000));
+    }
+
+    #[test]
+    fn test_count_in_stop_and_transport_hold() {
+        let mut drums = DrumMachine::new(8000);
+        // Start a beat before the downbeat: silent until beat 0
+        drums.start(kick_pattern(), 120.0, -1.0);
+        let mut buffer = vec![0.0; 6000 * 2];
+        drums.render(&mut buffer);
+        assert!(hit_at(&buffer, 0, 4000));
+
+        // Paused by the transport: nothing new plays
+        drums.sync(4.0, 120.0, false);
+        let mut buffer = vec![0.0; 30000 * 2];
+        drums.render(&mut buffer);
+        assert_eq!(onset(&buffer, 20000), None);
+
+        drums.stop();
+        assert!(!drums.is_running());
+        assert!(GroovePattern::names().iter().all(|name| GroovePattern::from_name(name).is_some()));
+    }
+}
@@ -1,79 +1,308 @@
-use mapping::MusicEvent;
-use crate::synth::{FallbackSynth, InstrumentType as SynthInstrumentType};
+use mapping::{MusicEvent, EXPRESSION_CC, FILTER_CUTOFF_CC, LAYER_CC, MODULATION_CC, ROCK_OUT_CC, SUSTAIN_PEDAL_CC};
+use crate::synth::{FallbackSynth, InstrumentType as SynthInstrumentType, DEFAULT_A4_FREQUENCY};
+use crate::sampler::{SampleZone, Sampler};
+use crate::temperament::TuningTable;
+use crate::backing_track::{BackingTrack, BackingTrackPlayer};
+use crate::click::Click;
+use crate::drums::DrumMachine;
+use crate::rock_out::{RockOut, RockOutEffect};
+use crate::mixer::{Mixer, MixerChannel};
+use crate::accompaniment::GroovePattern;
 
 #[cfg(feature = "soundfont")]
-use crate::soundfont::SoundFontSynth;
+use crate::soundfont::{SoundFontLoad, SoundFontLoadState, SoundFontSynth};
 
 enum SynthEngine {
     Fallback(FallbackSynth),
+    Sampler(Box<Sampler>),
     #[cfg(feature = "soundfont")]
     SoundFont(SoundFontSynth),
 }
 
+/// Maximum number of players routed to separate synths
+pub const MAX_PLAYERS: usize = controller::high_performance::MAX_PLAYERS;
+
+/// Scratch buffer size for mixing additional players (interleaved samples)
+const MIX_BUFFER_SIZE: usize = 16384;
+
+/// Most strummed notes that can be waiting to sound at once
+const MAX_PENDING_NOTES: usize = 64;
+
+/// Most note-offs the sustain pedal can hold back at once
+const MAX_HELD_NOTES: usize = 128;
+
+/// A strummed string waiting for its delay to pass
+#[derive(Debug, Clone, Copy)]
+struct PendingNote {
+    player: usize,
+    note: u8,
+    velocity: u8,
+    frames_left: usize,
+}
+
+/// Instrument played under player 1 at a fixed level, set by the genre preset
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InstrumentLayer {
+    pub instrument: SynthInstrumentType,
+    /// Level relative to the guitar (0.0..1.0)
+    pub gain: f32,
+    /// Octaves above (positive) or below (negative) the guitar
+    pub octave: i8,
+}
+
 /// Main audio engine that processes events and renders audio
 pub struct AudioEngine {
     synth: SynthEngine,
+    /// Synths for players 2..MAX_PLAYERS (player 1 uses `synth`)
+    player_synths: Vec<SynthEngine>,
+    /// Whether each additional player has played anything yet
+    player_active: Vec<bool>,
+    mix_buffer: Vec<f32>,
     sample_rate: u32,
     release_multiplier: f32,
+    /// A4 reference frequency in Hz
+    a4_frequency: f32,
+    /// Temperament for the fallback synths (SoundFonts stay in 12-TET)
+    tuning: TuningTable,
+    /// Chord spread width for the fallback synths (0.0 = mono)
+    stereo_spread: f32,
+    /// Glide of the whammy filter and volume in the fallback synths
+    whammy_smoothing: f32,
+    /// Polyphony of each fallback synth
+    max_voices: usize,
+    /// Delayed strum notes (preallocated, never grows in the audio thread)
+    pending_notes: Vec<PendingNote>,
+    /// Sustain pedal (CC64) held, per player
+    sustain_pedal: [bool; MAX_PLAYERS],
+    /// Note-offs held back by the sustain pedal as (player, note) (preallocated)
+    held_notes: Vec<(usize, u8)>,
+    /// Genre preset's instrument doubling player 1 at a fixed level
+    preset_layer: Option<SynthEngine>,
+    /// Settings `preset_layer` was built with
+    preset_layer_settings: Option<InstrumentLayer>,
+    /// Second instrument doubling player 1, faded in by `LAYER_CC` (tilt)
+    layer: Option<SynthEngine>,
+    /// Layer level requested by the last `LAYER_CC` (0.0..1.0)
+    layer_level: f32,
+    /// Layer gain reached at the end of the last block, ramped towards `layer_level`
+    layer_gain: f32,
+    /// Tilt effect on the guitars, switched by `ROCK_OUT_CC`
+    rock_out: RockOut,
+    /// Song backing track, mixed under everything else
+    backing_track: BackingTrackPlayer,
+    /// Drum machine, mixed with the backing track
+    drums: DrumMachine,
+    /// Count-in click, mixed over everything else
+    click: Click,
+    /// Faders per source and the master limiter
+    mixer: Mixer,
+    /// SoundFonts loading in the background as (player, load), installed once ready
+    #[cfg(feature = "soundfont")]
+    pending_soundfonts: Vec<(usize, SoundFontLoad)>,
 }
 
 impl AudioEngine {
     pub fn new(sample_rate: u32) -> Self {
         Self {
             synth: SynthEngine::Fallback(FallbackSynth::new(sample_rate)),
+            player_synths: (1..MAX_PLAYERS)
+                .map(|_| SynthEngine::Fallback(FallbackSynth::new(sample_rate)))
+                .collect(),
+            player_active: vec![false; MAX_PLAYERS.saturating_sub(1)],
+            mix_buffer: vec![0.0; MIX_BUFFER_SIZE],
             sample_rate,
             release_multiplier: 1.0,
+            a4_frequency: DEFAULT_A4_FREQUENCY,
+            tuning: TuningTable::equal(),
+            stereo_spread: 0.0,
+            whammy_smoothing: crate::synth::DEFAULT_WHAMMY_SMOOTHING,
+            max_voices: crate::synth::DEFAULT_MAX_VOICES,
+            pending_notes: Vec::with_capacity(MAX_PENDING_NOTES),
+            sustain_pedal: [false; MAX_PLAYERS],
+            held_notes: Vec::with_capacity(MAX_HELD_NOTES),
+            preset_layer: None,
+            preset_layer_settings: None,
+            layer: None,
+            layer_level: 0.0,
+            layer_gain: 0.0,
+            rock_out: RockOut::new(sample_rate),
+            backing_track: BackingTrackPlayer::new(sample_rate),
+            drums: DrumMachine::new(sample_rate),
+            click: Click::new(sample_rate),
+            mixer: Mixer::new(sample_rate),
+            #[cfg(feature = "soundfont")]
+            pending_soundfonts: Vec::with_capacity(MAX_PLAYERS),
         }
     }
     
+    /// Main synth followed by additional player synths and the layers
+    fn all_synths_mut(&mut self) -> impl Iterator<Item = &mut SynthEngine> {
+        std::iter::once(&mut self.synth)
+            .chain(self.player_synths.iter_mut())
+            .chain(self.preset_layer.iter_mut())
+            .chain(self.layer.iter_mut())
+    }
+    
     /// Set the release time multiplier for all instruments
     pub fn set_release_multiplier(&mut self, multiplier: f32) {
         self.release_multiplier = multiplier;
-        match &mut self.synth {
-            SynthEngine::Fallback(synth) => synth.set_release_multiplier(multiplier),
-            #[cfg(feature = "soundfont")]
-            SynthEngine::SoundFont(_) => {
-                // SoundFont uses its own envelope, can't modify easily
+        for synth in self.all_synths_mut() {
+            match synth {
+                SynthEngine::Fallback(synth) => synth.set_release_multiplier(multiplier),
+                // Samples use the release time of their zone
+                SynthEngine::Sampler(_) => {}
+                #[cfg(feature = "soundfont")]
+                SynthEngine::SoundFont(_) => {
+                    // SoundFont uses its own envelope, can't modify easily
+                }
             }
         }
     }
     
+    /// Set the A4 reference frequency (master tuning) for all synths
+    pub fn set_a4_frequency(&mut self, frequency: f32) {
+        self.a4_frequency = crate::synth::clamp_a4_frequency(frequency);
+        log::info!("🎼 Master tuning: A4 = {:.1} Hz", self.a4_frequency);
+        let a4_frequency = self.a4_frequency;
+        for synth in self.all_synths_mut() {
+            Self::apply_tuning(synth, a4_frequency);
+        }
+    }
+
+    pub fn a4_frequency(&self) -> f32 {
+        self.a4_frequency
+    }
+
+    /// Set the temperament for all fallback synths
+    pub fn set_tuning(&mut self, tuning: TuningTable) {
+        self.tuning = tuning;
+        for synth in self.all_synths_mut() {
+            if let SynthEngine::Fallback(synth) = synth {
+                synth.set_tuning(tuning);
+            }
+        }
+    }
+
+    /// Set the chord stereo spread for all fallback synths
+    pub fn set_stereo_spread(&mut self, width: f32) {
+        self.stereo_spread = crate::synth::clamp_stereo_spread(width);
+        let width = self.stereo_spread;
+        for synth in self.all_synths_mut() {
+            if let SynthEngine::Fallback(synth) = synth {
+                synth.set_stereo_spread(width);
+            }
+        }
+    }
+
+    /// Set the polyphony of all fallback synths
+    pub fn set_max_voices(&mut self, voices: usize) {
+        self.max_voices = crate::synth::clamp_max_voices(voices);
+        let voices = self.max_voices;
+        for synth in self.all_synths_mut() {
+            if let SynthEngine::Fallback(synth) = synth {
+                synth.set_max_voices(voices);
+            }
+        }
+    }
+
+    fn apply_tuning(synth: &mut SynthEngine, a4_frequency: f32) {
+        match synth {
+            SynthEngine::Fallback(synth) => synth.set_a4_frequency(a4_frequency),
+            SynthEngine::Sampler(sampler) => sampler.set_tuning_cents(crate::synth::a4_offset_cents(a4_frequency)),
+            #[cfg(feature = "soundfont")]
+            SynthEngine::SoundFont(synth) => synth.set_tuning_cents(crate::synth::a4_offset_cents(a4_frequency)),
+        }
+    }
+
     /// Enable or disable sustain mode
     pub fn set_sustain_enabled(&mut self, enabled: bool) {
-        match &mut self.synth {
-            SynthEngine::Fallback(synth) => synth.set_sustain_enabled(enabled),
-            #[cfg(feature = "soundfont")]
-            SynthEngine::SoundFont(_) => {
-                // SoundFont doesn't support this yet
+        for synth in self.all_synths_mut() {
+            match synth {
+                SynthEngine::Fallback(synth) => synth.set_sustain_enabled(enabled),
+                SynthEngine::Sampler(_) => {}
+                #[cfg(feature = "soundfont")]
+                SynthEngine::SoundFont(_) => {
+                    // SoundFont doesn't support this yet
+                }
             }
         }
     }
     
     /// Set sustain release time in seconds
     pub fn set_sustain_release_time(&mut self, time_seconds: f32) {
-        match &mut self.synth {
-            SynthEngine::Fallback(synth) => synth.set_sustain_release_time(time_seconds),
-            #[cfg(feature = "soundfont")]
-            SynthEngine::SoundFont(_) => {
-                // SoundFont doesn't support this yet
+        for synth in self.all_synths_mut() {
+            match synth {
+                SynthEngine::Fallback(synth) => synth.set_sustain_release_time(time_seconds),
+                SynthEngine::Sampler(_) => {}
+                #[cfg(feature = "soundfont")]
+                SynthEngine::SoundFont(_) => {
+                    // SoundFont doesn't support this yet
+                }
             }
         }
     }
     
     #[cfg(feature = "soundfont")]
     pub fn load_soundfont(&mut self, path: &std::path::Path) -> anyhow::Result<()> {
+        self.cancel_pending_soundfont(0);
         log::info!("Loading soundfont: {:?}", path);
         let mut sf_synth = SoundFontSynth::new(self.sample_rate as f32)?;
         sf_synth.load_soundfont(path)?;
+        sf_synth.set_tuning_cents(crate::synth::a4_offset_cents(self.a4_frequency));
         self.synth = SynthEngine::SoundFont(sf_synth);
         log::info!("Soundfont loaded successfully");
         Ok(())
     }
 
+    /// Switch player 1 to a sample instrument
+    pub fn load_sampler(&mut self, zones: Vec<SampleZone>) {
+        self.cancel_pending_soundfont(0);
+        self.synth = self.new_sampler(zones);
+        log::info!("Sample instrument loaded successfully");
+    }
+
+    /// Load a sample instrument for a player (player 0 uses `load_sampler`)
+    pub fn load_player_sampler(&mut self, player: usize, zones: Vec<SampleZone>) -> anyhow::Result<()> {
+        if player == 0 {
+            self.load_sampler(zones);
+            return Ok(());
+        }
+        self.cancel_pending_soundfont(player);
+        let sampler = self.new_sampler(zones);
+        let slot = self.player_synths.get_mut(player - 1)
+            .ok_or_else(|| anyhow::anyhow!("Invalid player index: {}", player))?;
+        *slot = sampler;
+        Ok(())
+    }
+
+    fn new_sampler(&self, zones: Vec<SampleZone>) -> SynthEngine {
+        let mut sampler = Sampler::new(self.sample_rate, zones);
+        sampler.set_tuning_cents(crate::synth::a4_offset_cents(self.a4_frequency));
+        SynthEngine::Sampler(Box::new(sampler))
+    }
+
+    /// Set the glide of whammy filter and volume changes for all fallback synths
+    /// (SoundFonts smooth controllers themselves)
+    pub fn set_whammy_smoothing(&mut self, factor: f32) {
+        self.whammy_smoothing = factor;
+        for synth in self.all_synths_mut() {
+            if let SynthEngine::Fallback(synth) = synth {
+                synth.set_whammy_smoothing(factor);
+            }
+        }
+    }
+
     /// Switch to using the fallback synthesizer (for virtual instruments)
     pub fn use_fallback_synth(&mut self) -> anyhow::Result<()> {
         log::info!("Switching to fallback synth for virtual instrument");
-        self.synth = SynthEngine::Fallback(FallbackSynth::new(self.sample_rate));
+        self.cancel_pending_soundfont(0);
+        let mut synth = FallbackSynth::new(self.sample_rate);
+        synth.set_a4_frequency(self.a4_frequency);
+        synth.set_tuning(self.tuning);
+        synth.set_stereo_spread(self.stereo_spread);
+        synth.set_whammy_smoothing(self.whammy_smoothing);
+        synth.set_max_voices(self.max_voices);
+        self.synth = SynthEngine::Fallback(synth);
         log::info!("Switched to fallback synth successfully");
         Ok(())
     }
@@ -81,47 +310,331 @@ impl AudioEngine {
     /// Set virtual instrument type (when using fallback synth)
     pub fn set_virtual_instrument(&mut self, instrument: SynthInstrumentType) -> anyhow::Result<()> {
         log::info!("Setting virtual instrument: {:?}", instrument);
-        match &mut self.synth {
-            SynthEngine::Fallback(synth) => {
-                synth.set_instrument(instrument);
-                log::info!("Virtual instrument set successfully");
-                Ok(())
+        self.cancel_pending_soundfont(0);
+        // SoundFonts and sample instruments switch to the fallback synth first
+        if !matches!(self.synth, SynthEngine::Fallback(_)) {
+            self.use_fallback_synth()?;
+        }
+        if let SynthEngine::Fallback(synth) = &mut self.synth {
+            synth.set_instrument(instrument);
+            log::info!("Virtual instrument set successfully");
+        }
+        Ok(())
+    }
+
+    /// Load a SoundFont for an additional player (player 0 uses `load_soundfont`)
+    #[cfg(feature = "soundfont")]
+    pub fn load_player_soundfont(&mut self, player: usize, path: &std::path::Path) -> anyhow::Result<()> {
+        if player == 0 {
+            return self.load_soundfont(path);
+        }
+        self.cancel_pending_soundfont(player);
+        let a4_frequency = self.a4_frequency;
+        let slot = self.player_synths.get_mut(player - 1)
+            .ok_or_else(|| anyhow::anyhow!("Invalid player index: {}", player))?;
+        log::info!("Loading soundfont for player {}: {:?}", player + 1, path);
+        let mut sf_synth = SoundFontSynth::new(self.sample_rate as f32)?;
+        sf_synth.load_soundfont(path)?;
+        sf_synth.set_tuning_cents(crate::synth::a4_offset_cents(a4_frequency));
+        *slot = SynthEngine::SoundFont(sf_synth);
+        Ok(())
+    }
+
+    /// Set virtual instrument for a player (player 0 uses `set_virtual_instrument`)
+    pub fn set_player_virtual_instrument(&mut self, player: usize, instrument: SynthInstrumentType) -> anyhow::Result<()> {
+        if player == 0 {
+            return self.set_virtual_instrument(instrument);
+        }
+        self.cancel_pending_soundfont(player);
+        let synth = self.new_fallback_synth(instrument);
+        let slot = self.player_synths.get_mut(player - 1)
+            .ok_or_else(|| anyhow::anyhow!("Invalid player index: {}", player))?;
+        log::info!("Setting virtual instrument for player {}: {:?}", player + 1, instrument);
+        *slot = SynthEngine::Fallback(synth);
+        Ok(())
+    }
+
+    /// Switch a player to a SoundFont loading in the background; the player
+    /// keeps its current instrument until the SoundFont is ready
+    #[cfg(feature = "soundfont")]
+    pub fn load_soundfont_async(&mut self, player: usize, load: SoundFontLoad) {
+        self.cancel_pending_soundfont(player);
+        if player < MAX_PLAYERS && self.pending_soundfonts.len() < MAX_PLAYERS {
+            self.pending_soundfonts.push((player, load));
+        }
+    }
+
+    /// Forget a background SoundFont a newer instrument choice replaced
+    #[cfg_attr(not(feature = "soundfont"), allow(unused_variables))]
+    fn cancel_pending_soundfont(&mut self, player: usize) {
+        #[cfg(feature = "soundfont")]
+        self.pending_soundfonts.retain(|(pending, _)| *pending != player);
+    }
+
+    /// Swap in the background SoundFonts that finished loading
+    #[cfg(feature = "soundfont")]
+    fn install_ready_soundfonts(&mut self) {
+        let mut i = 0;
+        while i < self.pending_soundfonts.len() {
+            let (player, state) = (self.pending_soundfonts[i].0, self.pending_soundfonts[i].1.take());
+            match state {
+                SoundFontLoadState::Loading => {
+                    i += 1;
+                    continue;
+                }
+                SoundFontLoadState::Ready(mut synth) => {
+                    synth.set_tuning_cents(crate::synth::a4_offset_cents(self.a4_frequency));
+                    let slot = if player == 0 { Some(&mut self.synth) } else { self.player_synths.get_mut(player - 1) };
+                    if let Some(slot) = slot {
+                        *slot = SynthEngine::SoundFont(*synth);
+                    }
+                }
+                SoundFontLoadState::Failed | SoundFontLoadState::Taken => {}
             }
-            #[cfg(feature = "soundfont")]
-            SynthEngine::SoundFont(_) => {
-                // Switch to fallback first, then set instrument
-                self.use_fallback_synth()?;
-                if let SynthEngine::Fallback(synth) = &mut self.synth {
-                    synth.set_instrument(instrument);
+            self.pending_soundfonts.swap_remove(i);
+        }
+    }
+
+    /// Fallback synth playing `instrument` with the current engine settings
+    fn new_fallback_synth(&self, instrument: SynthInstrumentType) -> FallbackSynth {
+        let mut synth = FallbackSynth::new(self.sample_rate);
+        synth.set_release_multiplier(self.release_multiplier);
+        synth.set_a4_frequency(self.a4_frequency);
+        synth.set_tuning(self.tuning);
+        synth.set_stereo_spread(self.stereo_spread);
+        synth.set_whammy_smoothing(self.whammy_smoothing);
+        synth.set_max_voices(self.max_voices);
+        synth.set_instrument(instrument);
+        synth
+    }
+
+    /// Set the instrument faded in by tilt under player 1 (None = no layer)
+    pub fn set_layer_instrument(&mut self, instrument: Option<SynthInstrumentType>) {
+        log::info!("🎻 Tilt layer instrument: {:?}", instrument);
+        self.layer = instrument.map(|instrument| SynthEngine::Fallback(self.new_fallback_synth(instrument)));
+        self.layer_gain = 0.0;
+    }
+
+    /// Set the instrument the genre preset layers under player 1 (None = no
+    /// layer). The same settings again keep the layer's notes sounding.
+    pub fn set_preset_layer(&mut self, layer: Option<InstrumentLayer>) {
+        let layer = layer.map(|layer| InstrumentLayer {
+            gain: layer.gain.clamp(0.0, 1.0),
+            octave: layer.octave.clamp(-3, 3),
+            ..layer
+        });
+        if layer == self.preset_layer_settings {
+            return;
+        }
+        log::info!("🎻 Preset layer: {:?}", layer);
+        self.preset_layer = layer.map(|layer| SynthEngine::Fallback(self.new_fallback_synth(layer.instrument)));
+        self.preset_layer_settings = layer;
+    }
+
+    /// Set a mixer fader, returns the applied gain
+    pub fn set_mixer_level(&mut self, channel: MixerChannel, gain: f32) -> f32 {
+        self.mixer.set_level(channel, gain)
+    }
+
+    pub fn mixer_level(&self, channel: MixerChannel) -> f32 {
+        self.mixer.level(channel)
+    }
+
+    /// Set the effect tilting switches on (None = no effect)
+    pub fn set_rock_out_effect(&mut self, effect: Option<RockOutEffect>) {
+        log::info!("🤘 Rock-out effect: {:?}", effect);
+        self.rock_out.set_effect(effect);
+    }
+
+    /// Set the song's backing track (None removes it)
+    pub fn set_backing_track(&mut self, track: Option<std::sync::Arc<BackingTrack>>) {
+        self.backing_track.set_track(track);
+    }
+
+    /// Move the backing track to the transport's position, play state and speed
+    pub fn sync_backing_track(&mut self, seconds: f64, playing: bool, speed: f64) {
+        self.backing_track.sync(seconds, playing, speed);
+    }
+
+    pub fn set_backing_track_gain(&mut self, gain: f32) {
+        self.backing_track.set_gain(gain);
+    }
+
+    /// Play a drum groove from a beat
+    pub fn start_drums(&mut self, pattern: GroovePattern, bpm: f64, beat: f64) {
+        self.drums.start(pattern, bpm, beat);
+    }
+
+    pub fn stop_drums(&mut self) {
+        self.drums.stop();
+    }
+
+    /// Move the drums to the transport's beat, tempo and play state
+    pub fn sync_drums(&mut self, beat: f64, bpm: f64, playing: bool) {
+        self.drums.sync(beat, bpm, playing);
+    }
+
+    pub fn set_drums_volume(&mut self, volume: f32) {
+        self.drums.set_volume(volume);
+    }
+
+    /// Sound a metronome click (count-in)
+    pub fn play_click(&mut self, downbeat: bool) {
+        self.click.trigger(downbeat);
+    }
+
+    /// Handle a music event for a specific player (called in audio thread, must be RT-safe)
+    pub fn handle_player_event(&mut self, player: usize, event: MusicEvent) {
+        let event = match event {
+            MusicEvent::StrumNoteOn { note, velocity, delay_ms } => {
+                let frames_left = (delay_ms.max(0.0) * self.sample_rate as f32 / 1000.0) as usize;
+                if frames_left == 0 {
+                    MusicEvent::NoteOn { note, velocity }
+                } else {
+                    // Drop the delay rather than allocate if the queue is full
+                    if self.pending_notes.len() < MAX_PENDING_NOTES {
+                        self.pending_notes.push(PendingNote { player, note, velocity, frames_left });
+                        return;
+                    }
+                    MusicEvent::NoteOn { note, velocity }
+                }
+            }
+            // The layer level only sets the mix, no synth sees it
+            MusicEvent::ControlChange { cc: LAYER_CC, value } => {
+                if player == 0 {
+                    self.layer_level = value.min(127) as f32 / 127.0;
+                }
+                return;
+            }
+            // Any player's tilt switches the effect on the guitars
+            MusicEvent::ControlChange { cc: ROCK_OUT_CC, value } => {
+                self.rock_out.set_engaged(value >= 64);
+                return;
+            }
+            // Sustain pedal holds releases back like MIDI CC64 (value >= 64 = down)
+            MusicEvent::ControlChange { cc: SUSTAIN_PEDAL_CC, value } => {
+                self.set_sustain_pedal(player, value >= 64);
+                return;
+            }
+            MusicEvent::NoteOff { note } if self.sustain_pedal.get(player).copied().unwrap_or(false) => {
+                // A release before the string sounded cancels it, pedal or not
+                self.pending_notes.retain(|pending| pending.player != player || pending.note != note);
+                if self.held_notes.len() < MAX_HELD_NOTES {
+                    if !self.held_notes.contains(&(player, note)) {
+                        self.held_notes.push((player, note));
+                    }
+                    return;
                 }
-                Ok(())
+                event
+            }
+            // A release before the string sounded cancels it
+            MusicEvent::NoteOff { note } => {
+                self.pending_notes.retain(|pending| pending.player != player || pending.note != note);
+                event
+            }
+            // Replaying a held note takes it over from the pedal
+            MusicEvent::NoteOn { note, .. } => {
+                self.held_notes.retain(|&held| held != (player, note));
+                event
+            }
+            MusicEvent::PanicAllNotesOff => {
+                self.pending_notes.clear();
+                self.held_notes.clear();
+                event
+            }
+            _ => event,
+        };
+        if player == 0 || matches!(event, MusicEvent::PanicAllNotesOff) {
+            if matches!(event, MusicEvent::PanicAllNotesOff) {
+                for synth in &mut self.player_synths {
+                    Self::dispatch_event(synth, MusicEvent::PanicAllNotesOff);
+                }
+            }
+            if let (Some(layer), Some(settings)) = (&mut self.preset_layer, self.preset_layer_settings) {
+                if let Some(event) = transpose_event(event.clone(), settings.octave * 12) {
+                    Self::dispatch_event(layer, event);
+                }
+            }
+            if let Some(layer) = &mut self.layer {
+                Self::dispatch_event(layer, event.clone());
+            }
+            Self::dispatch_event(&mut self.synth, event);
+            return;
+        }
+        if let Some(synth) = self.player_synths.get_mut(player - 1) {
+            if matches!(event, MusicEvent::NoteOn { .. }) {
+                self.player_active[player - 1] = true;
+            }
+            Self::dispatch_event(synth, event);
+        }
+    }
+
+    /// Press or release a player's sustain pedal; releasing sends the held note-offs
+    fn set_sustain_pedal(&mut self, player: usize, down: bool) {
+        let Some(pedal) = self.sustain_pedal.get_mut(player) else {
+            return;
+        };
+        *pedal = down;
+        if down {
+            return;
+        }
+        let mut i = 0;
+        while i < self.held_notes.len() {
+            if self.held_notes[i].0 == player {
+                let (_, note) = self.held_notes.swap_remove(i);
+                self.handle_player_event(player, MusicEvent::NoteOff { note });
+            } else {
+                i += 1;
             }
         }
     }
 
     /// Handle a music event (called in audio thread, must be RT-safe)
     pub fn handle_event(&mut self, event: MusicEvent) {
-        match &mut self.synth {
+        self.handle_player_event(0, event);
+    }
+
+    fn dispatch_event(synth: &mut SynthEngine, event: MusicEvent) {
+        match synth {
             SynthEngine::Fallback(synth) => {
                 match event {
-                    MusicEvent::NoteOn { note, velocity } => synth.note_on(note, velocity),
+                    MusicEvent::NoteOn { note, velocity } | MusicEvent::StrumNoteOn { note, velocity, .. } => synth.note_on(note, velocity),
                     MusicEvent::NoteOff { note } => synth.note_off(note),
                     MusicEvent::PitchBend(amount) => synth.set_pitch_bend(amount),
+                    MusicEvent::NotePitchBend { note, amount } => synth.set_note_pitch_bend(note, amount),
                     MusicEvent::PanicAllNotesOff => synth.all_notes_off(),
+                    MusicEvent::PalmMute(enabled) => synth.set_palm_mute(enabled),
+                    MusicEvent::ControlChange { cc: MODULATION_CC, value } => synth.set_modulation(value),
+                    MusicEvent::ControlChange { cc: FILTER_CUTOFF_CC, value } => synth.set_filter_cutoff(value),
+                    MusicEvent::ControlChange { cc: EXPRESSION_CC, value } => synth.set_expression(value),
+                    _ => {}
+                }
+            }
+            SynthEngine::Sampler(sampler) => {
+                match event {
+                    MusicEvent::NoteOn { note, velocity } | MusicEvent::StrumNoteOn { note, velocity, .. } => sampler.note_on(note, velocity),
+                    MusicEvent::NoteOff { note } => sampler.note_off(note),
+                    // Samples only bend as a whole
+                    MusicEvent::PitchBend(amount) | MusicEvent::NotePitchBend { amount, .. } => sampler.set_pitch_bend(amount),
+                    MusicEvent::PanicAllNotesOff => sampler.all_notes_off(),
                     _ => {}
                 }
             }
             #[cfg(feature = "soundfont")]
             SynthEngine::SoundFont(synth) => {
                 match event {
-                    MusicEvent::NoteOn { note, velocity } => synth.note_on(0, note, velocity),
+                    MusicEvent::NoteOn { note, velocity } | MusicEvent::StrumNoteOn { note, velocity, .. } => synth.note_on(0, note, velocity),
                     MusicEvent::NoteOff { note } => synth.note_off(0, note),
-                    MusicEvent::PitchBend(amount) => {
+                    // The SoundFont synth only bends the whole channel
+                    MusicEvent::PitchBend(amount) | MusicEvent::NotePitchBend { amount, .. } => {
                         // Convert i16 (-8192..8191) to f32 (-1.0..1.0)
                         let normalized = (amount as f32) / 8192.0;
                         synth.set_pitch_bend(normalized);
                     },
                     MusicEvent::PanicAllNotesOff => synth.all_notes_off(),
+                    // SoundFonts map the modulation wheel, expression and cutoff themselves
+                    MusicEvent::ControlChange { cc, value } => synth.control_change(0, cc, value),
+                    // SoundFont doesn't support palm mute yet
                     _ => {}
                 }
             }
@@ -130,8 +643,114 @@ impl AudioEngine {
 
     /// Render audio into the output buffer (RT-safe)
     pub fn render(&mut self, buffer: &mut [f32]) {
-        match &mut self.synth {
+        #[cfg(feature = "soundfont")]
+        self.install_ready_soundfonts();
+
+        // Split the buffer at each delayed strum note so it starts on time
+        let mut offset = 0;
+        while offset < buffer.len() {
+            self.start_due_notes();
+            let remaining_frames = (buffer.len() - offset).div_ceil(2);
+            let frames = self.pending_notes.iter()
+                .map(|pending| pending.frames_left)
+                .min()
+                .unwrap_or(remaining_frames)
+                .min(remaining_frames);
+            let end = (offset + frames * 2).min(buffer.len());
+            self.render_block(&mut buffer[offset..end]);
+            for pending in &mut self.pending_notes {
+                pending.frames_left -= frames;
+            }
+            offset = end;
+        }
+    }
+
+    /// Start delayed notes whose time has come
+    fn start_due_notes(&mut self) {
+        let mut i = 0;
+        while i < self.pending_notes.len() {
+            if self.pending_notes[i].frames_left == 0 {
+                let pending = self.pending_notes.swap_remove(i);
+                self.handle_player_event(pending.player, MusicEvent::NoteOn { note: pending.note, velocity: pending.velocity });
+            } else {
+                i += 1;
+            }
+        }
+    }
+
+    fn render_block(&mut self, buffer: &mut [f32]) {
+        Self::render_synth(&mut self.synth, buffer);
+
+        // Mix in additional players that have been used
+        for (synth, active) in self.player_synths.iter_mut().zip(self.player_active.iter()) {
+            if !*active {
+                continue;
+            }
+            for chunk in buffer.chunks_mut(MIX_BUFFER_SIZE) {
+                let scratch = &mut self.mix_buffer[..chunk.len()];
+                Self::render_synth(synth, scratch);
+                for (out, sample) in chunk.iter_mut().zip(scratch.iter()) {
+                    *out = (*out + *sample).clamp(-1.0, 1.0);
+                }
+            }
+        }
+
+        // Mix in the preset layer at its fixed level
+        if let (Some(layer), Some(settings)) = (&mut self.preset_layer, self.preset_layer_settings) {
+            for chunk in buffer.chunks_mut(MIX_BUFFER_SIZE) {
+                let scratch = &mut self.mix_buffer[..chunk.len()];
+                Self::render_synth(layer, scratch);
+                for (out, sample) in chunk.iter_mut().zip(scratch.iter()) {
+                    *out = (*out + *sample * settings.gain).clamp(-1.0, 1.0);
+                }
+            }
+        }
+
+        // Mix in the tilt layer, ramping the gain across the block so it doesn't click.
+        // The layer keeps rendering while silent so its notes stay in step.
+        if let Some(layer) = &mut self.layer {
+            let frames = buffer.len().div_ceil(2).max(1);
+            let step = (self.layer_level - self.layer_gain) / frames as f32;
+            let mut gain = self.layer_gain;
+            for chunk in buffer.chunks_mut(MIX_BUFFER_SIZE) {
+                let scratch = &mut self.mix_buffer[..chunk.len()];
+                Self::render_synth(layer, scratch);
+                for (out, frame) in chunk.chunks_mut(2).zip(scratch.chunks(2)) {
+                    gain += step;
+                    for (out, sample) in out.iter_mut().zip(frame.iter()) {
+                        *out = (*out + *sample * gain).clamp(-1.0, 1.0);
+                    }
+                }
+            }
+            self.layer_gain = self.layer_level;
+        }
+
+        self.rock_out.process(buffer);
+        self.mixer.apply(MixerChannel::Synth, buffer);
+
+        // The other sources render on their own so each fader only scales its source
+        for chunk in buffer.chunks_mut(MIX_BUFFER_SIZE) {
+            let scratch = &mut self.mix_buffer[..chunk.len()];
+            for channel in [MixerChannel::BackingTrack, MixerChannel::Drums, MixerChannel::Metronome] {
+                scratch.fill(0.0);
+                match channel {
+                    MixerChannel::BackingTrack => self.backing_track.render(scratch),
+                    MixerChannel::Drums => self.drums.render(scratch),
+                    _ => self.click.render(scratch),
+                }
+                self.mixer.apply(channel, scratch);
+                for (out, sample) in chunk.iter_mut().zip(scratch.iter()) {
+                    *out += *sample;
+                }
+            }
+        }
+        self.mixer.master(buffer);
+    }
+
+    fn render_synth(synth: &mut SynthEngine, buffer: &mut [f32]) {
+        match synth {
             SynthEngine::Fallback(synth) => synth.render(buffer),
+            SynthEngine::Sampler(sampler) => sampler.render(buffer),
             #[cfg(feature = "soundfont")]
             SynthEngine::SoundFont(synth) => synth.render(buffer),
         }
@@ -139,10 +758,173 @@ impl AudioEngine {
 
     /// Get count of active voices
     pub fn active_voice_count(&self) -> usize {
-        match &self.synth {
-            SynthEngine::Fallback(synth) => synth.active_voice_count(),
-            #[cfg(feature = "soundfont")]
-            SynthEngine::SoundFont(_) => 0, // TODO: implement for soundfont
-        }
+        std::iter::once(&self.synth)
+            .chain(self.player_synths.iter())
+            .chain(self.preset_layer.iter())
+            .chain(self.layer.iter())
+            .map(|synth| match synth {
+                SynthEngine::Fallback(synth) => synth.active_voice_count(),
+                SynthEngine::Sampler(sampler) => sampler.active_voice_count(),
+                #[cfg(feature = "soundfont")]
+                SynthEngine::SoundFont(_) => 0, // TODO: implement for soundfont
+            })
+            .sum()
     }
+
+    /// Notes that had to take over a sounding voice since the last call
+    pub fn take_stolen_voices(&mut self) -> u64 {
+        self.all_synths_mut()
+            .map(|synth| match synth {
+                SynthEngine::Fallback(synth) => synth.take_stolen_voices(),
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+/// Move a note event by some semitones (None when the note leaves the MIDI range)
+fn transpose_event(event: MusicEvent, semitones: i8) -> Option<MusicEvent> {
+    let shift = |note: u8| u8::try_from(note as i16 + semitones as i16).ok().filter(|note| *note <= 127);
+    Some(match event {
+        MusicEvent::NoteOn { note, velocity } => MusicEvent::NoteOn { note: shift(note)?, velocity },
+        MusicEvent::StrumNoteOn { note, velocity, delay_ms } => MusicEvent::StrumNoteOn { note: shift(note)?, velocity, delay_ms },
+        MusicEvent::NoteOff { note } => MusicEvent::NoteOff { note: shift(note)? },
+        MusicEvent::NotePitchBend { note, amount } => MusicEvent::NotePitchBend { note: shift(note)?, amount },
+        event => event,
+    })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_player_events_route_to_own_synth() {
+        let mut engine = AudioEngine::new(48000);
+        engine.handle_player_event(0, MusicEvent::NoteOn { note: 60, velocity: 100 });
+        engine.handle_player_event(1, MusicEvent::NoteOn { note: 64, velocity: 100 });
+        assert_eq!(engine.active_voice_count(), 2);
+
+        // Unknown players are ignored
+        engine.handle_player_event(MAX_PLAYERS, MusicEvent::NoteOn { note: 67, velocity: 100 });
+        assert_eq!(engine.active_voice_count(), 2);
+    }
+
+    #[test]
+    fn test_render_mixes_players() {
+        let mut engine = AudioEngine::new(48000);
+        engine.handle_player_event(1, MusicEvent::NoteOn { note: 64, velocity: 127 });
+
+        let mut buffer = vec![0.0f32; 512];
+        engine.render(&mut buffer);
+        assert!(buffer.iter().any(|s| s.abs() > 0.0));
+        assert!(buffer.iter().all(|s| s.abs() <= 1.0));
+    }
+
+    #[test]
+    fn test_strum_notes_start_after_delay() {
+        let mut engine = AudioEngine::new(48000);
+        engine.handle_event(MusicEvent::NoteOn { note: 40, velocity: 100 });
+        // 10ms = 480 frames
+        engine.handle_event(MusicEvent::StrumNoteOn { note: 47, velocity: 100, delay_ms: 10.0 });
+        engine.handle_event(MusicEvent::StrumNoteOn { note: 52, velocity: 100, delay_ms: 20.0 });
+        assert_eq!(engine.active_voice_count(), 1);
+
+        let mut buffer = vec![0.0f32; 2 * 600];
+        engine.render(&mut buffer);
+        assert_eq!(engine.active_voice_count(), 2);
+
+        // Releasing a string before it sounds cancels it
+        engine.handle_event(MusicEvent::NoteOff { note: 52 });
+        let mut buffer = vec![0.0f32; 2 * 600];
+        engine.render(&mut buffer);
+        assert_eq!(engine.active_voice_count(), 2);
+        assert!(engine.pending_notes.is_empty());
+    }
+
+    #[test]
+    fn test_tilt_layer_fades_in_with_level() {
+        let mut engine = AudioEngine::new(48000);
+        engine.set_layer_instrument(Some(SynthInstrumentType::Strings));
+        engine.handle_event(MusicEvent::NoteOn { note: 60, velocity: 100 });
+        // The layer doubles player 1 only
+        engine.handle_player_event(1, MusicEvent::NoteOn { note: 64, velocity: 100 });
+        assert_eq!(engine.active_voice_count(), 3);
+
+        let mut buffer = vec![0.0f32; 2 * 256];
+        engine.render(&mut buffer);
+        assert_eq!(engine.layer_gain, 0.0);
+
+        // The level glides in over the next block
+        engine.handle_event(MusicEvent::ControlChange { cc: LAYER_CC, value: 127 });
+        engine.render(&mut buffer);
+        assert_eq!(engine.layer_gain, 1.0);
+        assert!(buffer.iter().all(|s| s.abs() <= 1.0));
+
+        engine.set_layer_instrument(None);
+        assert_eq!(engine.active_voice_count(), 2);
+    }
+
+    #[test]
+    fn test_preset_layer_doubles_player_one_shifted() {
+        let mut engine = AudioEngine::new(48000);
+        let layer = InstrumentLayer { instrument: SynthInstrumentType::Strings, gain: 0.4, octave: -1 };
+        engine.set_preset_layer(Some(layer));
+        engine.handle_event(MusicEvent::NoteOn { note: 60, velocity: 100 });
+        engine.handle_player_event(1, MusicEvent::NoteOn { note: 64, velocity: 100 });
+        assert_eq!(engine.active_voice_count(), 3);
+
+        // Setting the same layer again keeps its notes
+        engine.set_preset_layer(Some(layer));
+        assert_eq!(engine.active_voice_count(), 3);
+
+        let mut buffer = vec![0.0f32; 2 * 256];
+        engine.render(&mut buffer);
+        assert!(buffer.iter().all(|s| s.abs() <= 1.0));
+
+        // Notes pushed out of the MIDI range only sound on the guitar
+        engine.handle_event(MusicEvent::NoteOn { note: 5, velocity: 100 });
+        assert_eq!(engine.active_voice_count(), 4);
+
+        engine.set_preset_layer(None);
+        assert_eq!(engine.active_voice_count(), 3);
+    }
+
+    #[test]
+    fn test_transpose_event() {
+        assert!(matches!(transpose_event(MusicEvent::NoteOff { note: 60 }, -12), Some(MusicEvent::NoteOff { note: 48 })));
+        assert!(transpose_event(MusicEvent::NoteOn { note: 120, velocity: 1 }, 12).is_none());
+        assert!(matches!(transpose_event(MusicEvent::PitchBend(100), 12), Some(MusicEvent::PitchBend(100))));
+    }
+
+    #[test]
+    fn test_sustain_pedal_holds_note_offs() {
+        let mut engine = AudioEngine::new(48000);
+        let pedal = |value| MusicEvent::ControlChange { cc: SUSTAIN_PEDAL_CC, value };
+        engine.handle_event(pedal(127));
+        engine.handle_event(MusicEvent::NoteOn { note: 60, velocity: 100 });
+        engine.handle_event(MusicEvent::NoteOff { note: 60 });
+        engine.handle_event(MusicEvent::NoteOff { note: 60 });
+        assert_eq!(engine.held_notes, vec![(0, 60)]);
+
+        // Player 2's pedal is up, so its releases go straight through
+        engine.handle_player_event(1, MusicEvent::NoteOn { note: 64, velocity: 100 });
+        engine.handle_player_event(1, MusicEvent::NoteOff { note: 64 });
+        assert_eq!(engine.held_notes.len(), 1);
+
+        // Restriking a held note takes it back from the pedal
+        engine.handle_event(MusicEvent::NoteOn { note: 60, velocity: 100 });
+        assert!(engine.held_notes.is_empty());
+        engine.handle_event(MusicEvent::NoteOff { note: 60 });
+        engine.handle_event(pedal(0));
+        assert!(engine.held_notes.is_empty());
+        assert!(!engine.sustain_pedal[0]);
+
+        // A string released before it sounded stays silent with the pedal down
+        engine.handle_event(pedal(127));
+        engine.handle_event(MusicEvent::StrumNoteOn { note: 67, velocity: 100, delay_ms: 10.0 });
+        engine.handle_event(MusicEvent::NoteOff { note: 67 });
+        assert!(engine.pending_notes.is_empty());
+    }
+}
+
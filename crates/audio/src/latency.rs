@@ -0,0 +1,157 @@
+//! End-to-end input latency measurement
+//! The polling thread timestamps fret/strum changes, the timestamp travels with
+//! the resulting NoteOn through the event queue, and the audio callback records
+//! how long it took to get there. Recording only touches atomics (RT-safe).
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+/// Histogram bucket width in microseconds
+const BUCKET_US: u64 = 250;
+/// Number of buckets (last bucket collects everything >= 100 ms)
+const BUCKET_COUNT: usize = 400;
+
+/// Latency summary for the UI
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct LatencyStats {
+    pub enabled: bool,
+    pub samples: u64,
+    pub p50_ms: f32,
+    pub p95_ms: f32,
+    pub max_ms: f32,
+    pub mean_ms: f32,
+}
+
+/// Lock-free latency histogram shared with the audio callback
+pub struct LatencyHistogram {
+    enabled: AtomicBool,
+    buckets: Vec<AtomicU64>,
+    samples: AtomicU64,
+    total_us: AtomicU64,
+    max_us: AtomicU64,
+    /// Input timestamp of the last recorded event (chords record once)
+    last_input: AtomicU64,
+}
+
+impl LatencyHistogram {
+    pub fn new() -> Self {
+        Self {
+            enabled: AtomicBool::new(false),
+            buckets: (0..BUCKET_COUNT).map(|_| AtomicU64::new(0)).collect(),
+            samples: AtomicU64::new(0),
+            total_us: AtomicU64::new(0),
+            max_us: AtomicU64::new(0),
+            last_input: AtomicU64::new(0),
+        }
+    }
+
+    pub fn set_enabled(&self, enabled: bool) {
+        self.enabled.store(enabled, Ordering::Relaxed);
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled.load(Ordering::Relaxed)
+    }
+
+    /// Record the latency between an input timestamp and `now_ns` (both
+    /// nanoseconds since epoch). Ignored when disabled, untimed (0) or
+    /// already recorded for this input.
+    pub fn record(&self, input_ns: u64, now_ns: u64) {
+        if input_ns == 0 || !self.is_enabled() {
+            return;
+        }
+        if self.last_input.swap(input_ns, Ordering::Relaxed) == input_ns {
+            return;
+        }
+        let latency_us = now_ns.saturating_sub(input_ns) / 1000;
+        let bucket = ((latency_us / BUCKET_US) as usize).min(BUCKET_COUNT - 1);
+        self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        self.samples.fetch_add(1, Ordering::Relaxed);
+        self.total_us.fetch_add(latency_us, Ordering::Relaxed);
+        self.max_us.fetch_max(latency_us, Ordering::Relaxed);
+    }
+
+    /// Upper edge of the bucket containing the given percentile (0.0-1.0)
+    fn percentile_ms(&self, samples: u64, percentile: f64) -> f32 {
+        if samples == 0 {
+            return 0.0;
+        }
+        let target = ((samples as f64 * percentile).ceil() as u64).max(1);
+        let mut seen = 0;
+        for (i, bucket) in self.buckets.iter().enumerate() {
+            seen += bucket.load(Ordering::Relaxed);
+            if seen >= target {
+                return ((i as u64 + 1) * BUCKET_US) as f32 / 1000.0;
+            }
+        }
+        (BUCKET_COUNT as u64 * BUCKET_US) as f32 / 1000.0
+    }
+
+    pub fn stats(&self) -> LatencyStats {
+        let samples = self.samples.load(Ordering::Relaxed);
+        let total_us = self.total_us.load(Ordering::Relaxed);
+        LatencyStats {
+            enabled: self.is_enabled(),
+            samples,
+            p50_ms: self.percentile_ms(samples, 0.50),
+            p95_ms: self.percentile_ms(samples, 0.95),
+            max_ms: self.max_us.load(Ordering::Relaxed) as f32 / 1000.0,
+            mean_ms: if samples > 0 { total_us as f32 / samples as f32 / 1000.0 } else { 0.0 },
+        }
+    }
+
+    pub fn reset(&self) {
+        for bucket in &self.buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.samples.store(0, Ordering::Relaxed);
+        self.total_us.store(0, Ordering::Relaxed);
+        self.max_us.store(0, Ordering::Relaxed);
+        self.last_input.store(0, Ordering::Relaxed);
+    }
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: u64 = 1_000_000;
+
+    #[test]
+    fn test_percentiles() {
+        let histogram = LatencyHistogram::new();
+        histogram.set_enabled(true);
+        for i in 1..=100u64 {
+            histogram.record(i * 1000 * MS, i * 1000 * MS + i * MS / 10);
+        }
+        let stats = histogram.stats();
+        assert_eq!(stats.samples, 100);
+        assert!((stats.p50_ms - 5.0).abs() <= 0.25);
+        assert!((stats.p95_ms - 9.5).abs() <= 0.25);
+        assert!((stats.max_ms - 10.0).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_disabled_and_duplicates_ignored() {
+        let histogram = LatencyHistogram::new();
+        histogram.record(MS, 2 * MS);
+        assert_eq!(histogram.stats().samples, 0);
+
+        histogram.set_enabled(true);
+        // Three notes of one chord share the input timestamp
+        for _ in 0..3 {
+            histogram.record(MS, 3 * MS);
+        }
+        histogram.record(0, 3 * MS);
+        assert_eq!(histogram.stats().samples, 1);
+
+        histogram.reset();
+        assert_eq!(histogram.stats().samples, 0);
+    }
+}
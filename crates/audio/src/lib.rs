@@ -1,6 +1,23 @@
 pub mod synth;
 pub mod engine;
+pub mod sampler;
+pub mod sfz;
+pub mod preview;
+pub mod bounce;
+pub mod accompaniment;
+pub mod latency;
+pub mod temperament;
+pub mod resample;
+pub mod underrun;
+pub mod monitor;
+pub mod meter;
 pub mod instant_callback;
+pub mod denormal;
+pub mod backing_track;
+pub mod click;
+pub mod drums;
+pub mod rock_out;
+pub mod mixer;
 
 #[cfg(feature = "soundfont")]
 pub mod soundfont;
@@ -12,7 +29,7 @@ use mapping::MusicEvent;
 use ringbuf::{HeapRb, traits::Split};
 use ringbuf::traits::{Consumer, Producer};
 use std::sync::Arc;
-use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
 
 /// Engine control commands
 #[derive(Debug, Clone)]
@@ -22,25 +39,146 @@ enum EngineControl {
     SetReleaseMultiplier(f32),
     SetSustainEnabled(bool),
     SetSustainReleaseTime(f32),
+    SetA4Frequency(f32),
+    SetTuning(TuningTable),
+    SetStereoSpread(f32),
+    SetMaxVoices(usize),
+    SetWhammySmoothing(f32),
+    SetPlayerVirtualInstrument(usize, SynthInstrumentType),
+    LoadSampler(usize, Vec<SampleZone>),
+    SetLayerInstrument(Option<SynthInstrumentType>),
+    SetPresetLayer(Option<InstrumentLayer>),
+    SetBackingTrack(Option<Arc<BackingTrack>>),
+    SyncBackingTrack { seconds: f64, playing: bool, speed: f64 },
+    SetBackingTrackGain(f32),
+    Click { downbeat: bool },
+    StartDrums { pattern: GroovePattern, bpm: f64, beat: f64 },
+    StopDrums,
+    SyncDrums { beat: f64, bpm: f64, playing: bool },
+    SetDrumsVolume(f32),
+    SetRockOutEffect(Option<RockOutEffect>),
+    SetMixerLevel(MixerChannel, f32),
+    /// Switch a player to a SoundFont loading in the background
     #[cfg(feature = "soundfont")]
-    LoadSoundFont(std::path::PathBuf),
+    LoadSoundFont(usize, SoundFontLoad),
 }
 
+impl EngineControl {
+    /// Commands with the same key replace each other when settings are replayed
+    fn replay_key(&self) -> (std::mem::Discriminant<Self>, usize) {
+        let player = match self {
+            EngineControl::SetPlayerVirtualInstrument(player, _) => *player,
+            EngineControl::LoadSampler(0, _) => {
+                return (std::mem::discriminant(&EngineControl::UseFallbackSynth), 0);
+            }
+            EngineControl::LoadSampler(player, _) => *player,
+            // Each fader keeps its own setting
+            EngineControl::SetMixerLevel(channel, _) => *channel as usize,
+            #[cfg(feature = "soundfont")]
+            EngineControl::LoadSoundFont(player, _) if *player > 0 => *player,
+            // Instrument selections for player 1 all share one slot
+            EngineControl::UseFallbackSynth | EngineControl::SetVirtualInstrument(_) => {
+                return (std::mem::discriminant(&EngineControl::UseFallbackSynth), 0);
+            }
+            #[cfg(feature = "soundfont")]
+            EngineControl::LoadSoundFont(..) => {
+                return (std::mem::discriminant(&EngineControl::UseFallbackSynth), 0);
+            }
+            _ => 0,
+        };
+        (std::mem::discriminant(self), player)
+    }
+}
+
+/// Music event tagged with the player it belongs to and the input
+/// timestamp that caused it (nanoseconds since epoch, 0 = untimed)
+type PlayerEvent = (u8, MusicEvent, u64);
+
 pub use synth::{FallbackSynth, InstrumentType as SynthInstrumentType};
-pub use engine::AudioEngine;
+pub use engine::{AudioEngine, InstrumentLayer, MAX_PLAYERS};
+pub use sampler::{Sampler, SampleData, SampleZone, LoopMode, RoundRobinMode, decode_wav, is_sample_folder, load_sample_folder, load_sample_instrument};
+pub use sfz::{load_sfz, parse_sfz, SfzFile, SfzRegion};
+pub use preview::{PreviewSource, PreviewOptions, render_preview_wav};
+pub use bounce::{BounceChord, BounceOptions, render_bounce_wav};
+pub use accompaniment::{Accompanist, AccompanimentBar, BarContext, GroovePattern};
 pub use instant_callback::InstantAudioCallback;
+pub use latency::{LatencyHistogram, LatencyStats};
+pub use temperament::{Temperament, TuningTable};
+pub use resample::{nearest_supported_rate, StereoResampler, PREFERRED_SAMPLE_RATE};
+pub use underrun::{UnderrunAlert, UnderrunDetector, UnderrunMonitor};
+pub use monitor::{InputMonitor, InputMonitorStats, MonitorTap};
+pub use meter::{AudioMeters, MeterTap, SPECTRUM_BANDS};
+pub use denormal::{enable_flush_to_zero, flush_denormal};
+pub use backing_track::{BackingTrack, BackingTrackPlayer, DEFAULT_BACKING_TRACK_GAIN};
+pub use click::Click;
+pub use drums::{DrumMachine, DEFAULT_DRUM_VOLUME};
+pub use rock_out::{RockOut, RockOutEffect};
+pub use mixer::{Mixer, MixerChannel, MAX_MIXER_GAIN};
+
+#[cfg(feature = "soundfont")]
+pub use soundfont::{SoundFontInfo, InstrumentInfo, InstrumentType as SoundFontInstrumentType, SoundFontLoad, SoundFontManager, SoundFontSynth};
+
+/// Audio host (cpal backend) for the backend picker
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AudioHostInfo {
+    /// Host name as stored in `AudioConfig.backend` (e.g. "WASAPI", "ASIO", "JACK")
+    pub name: String,
+    pub is_default: bool,
+}
 
+/// Backend names that select the platform default host
+fn is_default_host_name(name: &str) -> bool {
+    matches!(name.to_lowercase().as_str(), "" | "default" | "fallback")
+}
+
+/// Resolve a host by name (None / "default" = platform default)
+fn host_by_name(host_name: Option<&str>) -> Result<cpal::Host> {
+    let Some(name) = host_name.filter(|name| !is_default_host_name(name)) else {
+        return Ok(cpal::default_host());
+    };
+    let id = cpal::available_hosts()
+        .into_iter()
+        .find(|id| id.name().eq_ignore_ascii_case(name))
+        .with_context(|| format!("Audio backend '{}' is not available on this system", name))?;
+    cpal::host_from_id(id).with_context(|| format!("Failed to open audio backend '{}'", name))
+}
+
+/// Output device for the device picker
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct AudioDeviceInfo {
+    pub name: String,
+    pub is_default: bool,
+}
+
+/// A SoundFont that finished loading in the background
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct SoundFontLoaded {
+    pub player: usize,
+    pub path: std::path::PathBuf,
+    /// Why it couldn't be loaded (None = it is playing now)
+    pub error: Option<String>,
+}
+
+/// Finished loads kept for `take_loaded_soundfonts` (oldest dropped first)
 #[cfg(feature = "soundfont")]
-pub use soundfont::{SoundFontInfo, InstrumentInfo, InstrumentType as SoundFontInstrumentType, SoundFontManager, SoundFontSynth};
+const MAX_LOADED_SOUNDFONTS: usize = 16;
 
 /// Audio statistics for diagnostics
 #[derive(Debug, Clone, serde::Serialize)]
 pub struct AudioStats {
     pub sample_rate: u32,
+    /// Rate the synth renders at (differs from `sample_rate` when resampling)
+    pub render_sample_rate: u32,
     pub buffer_size: u32,
     pub underruns: u64,
     pub active_voices: usize,
+    /// Notes that took over a sounding voice because the polyphony was used up
+    pub voices_stolen: u64,
     pub estimated_latency_ms: f32,
+    /// Time from stream creation to the first audio callback (None = not called yet)
+    pub first_callback_ms: Option<f32>,
+    /// Flush-to-zero / denormals-are-zero active on the audio thread
+    pub denormals_flushed: bool,
 }
 
 // Wrapper to make Stream Send+Sync
@@ -53,37 +191,139 @@ unsafe impl Sync for StreamWrapper {}
 /// Audio output manager
 pub struct AudioOutput {
     _stream: StreamWrapper,
-    event_producer: ringbuf::HeapProd<MusicEvent>,
+    event_producer: ringbuf::HeapProd<PlayerEvent>,
     stats: Arc<AudioStatsInner>,
+    latency: Arc<LatencyHistogram>,
     engine_control_tx: std::sync::mpsc::Sender<EngineControl>,
     stream_error: Arc<std::sync::atomic::AtomicBool>,
     buffer_size: Option<u32>,
+    /// Selected host (None = platform default)
+    host_name: Option<String>,
+    /// Selected output device (None = system default)
+    device_name: Option<String>,
+    /// Last engine settings, replayed when the stream is rebuilt
+    engine_settings: std::sync::Mutex<Vec<EngineControl>>,
+    /// Input monitor tap mixed in by the output callback
+    monitor_tap: Arc<std::sync::Mutex<Option<MonitorTap>>>,
+    /// Running input monitor (None = monitoring off)
+    input_monitor: Option<InputMonitor>,
+    /// Background SoundFont loads that finished since the last `take_loaded_soundfonts`
+    loaded_soundfonts: Arc<std::sync::Mutex<Vec<SoundFontLoaded>>>,
+    /// Output levels measured by the callback
+    meters: Arc<MeterTap>,
 }
 
 struct AudioStatsInner {
     sample_rate: u32,
+    render_sample_rate: u32,
     buffer_size: u32,
     underruns: AtomicU64,
     active_voices: AtomicUsize,
+    voices_stolen: AtomicU64,
+    /// Microseconds from stream creation to the first callback (0 = not called yet)
+    first_callback_us: AtomicU64,
+    denormals_flushed: AtomicBool,
 }
 
 impl AudioOutput {
     /// Create a new audio output with specified buffer size
     pub fn new(buffer_size: Option<u32>) -> Result<Self> {
-        Self::create_with_device(None, buffer_size)
+        Self::create_with_device(None, None, buffer_size)
+    }
+
+    /// Create an audio output on a named host and device (None = defaults)
+    pub fn with_device(host_name: Option<&str>, device_name: Option<&str>, buffer_size: Option<u32>) -> Result<Self> {
+        Self::create_with_device(host_name, device_name, buffer_size)
+    }
+
+    /// List the audio hosts compiled in and available on this system
+    pub fn list_hosts() -> Vec<AudioHostInfo> {
+        let default_id = cpal::default_host().id();
+        cpal::available_hosts()
+            .into_iter()
+            .map(|id| AudioHostInfo {
+                name: id.name().to_string(),
+                is_default: id == default_id,
+            })
+            .collect()
+    }
+
+    /// Selected host name (None = platform default)
+    pub fn host_name(&self) -> Option<&str> {
+        self.host_name.as_deref()
+    }
+
+    /// Switch to another audio host, rebuilding the stream on its default device
+    pub fn set_host(&mut self, host_name: Option<String>) -> Result<()> {
+        let host_name = host_name.filter(|name| !is_default_host_name(name));
+        log::info!("🔊 Switching audio backend to {}", host_name.as_deref().unwrap_or("default"));
+        let new_output = Self::create_with_device(host_name.as_deref(), None, self.buffer_size)?;
+        self.replace_stream(new_output);
+        self.host_name = host_name;
+        self.device_name = None;
+        Ok(())
+    }
+
+    /// List available output devices of the current host
+    pub fn list_devices(&self) -> Result<Vec<AudioDeviceInfo>> {
+        let host = host_by_name(self.host_name.as_deref())?;
+        let default_name = host.default_output_device().and_then(|d| d.name().ok());
+        let devices = host.output_devices()?
+            .filter_map(|device| device.name().ok())
+            .map(|name| AudioDeviceInfo {
+                is_default: default_name.as_deref() == Some(name.as_str()),
+                name,
+            })
+            .collect();
+        Ok(devices)
+    }
+
+    /// Selected output device (None = system default)
+    pub fn device_name(&self) -> Option<&str> {
+        self.device_name.as_deref()
+    }
+
+    /// Switch to another output device, rebuilding the stream
+    pub fn set_device(&mut self, device_name: Option<String>) -> Result<()> {
+        log::info!("🔊 Switching audio output to {}", device_name.as_deref().unwrap_or("system default"));
+        let new_output = Self::create_with_device(self.host_name.as_deref(), device_name.as_deref(), self.buffer_size)?;
+        self.replace_stream(new_output);
+        self.device_name = device_name;
+        Ok(())
+    }
+
+    /// Requested buffer size in frames (None = device default)
+    pub fn buffer_size(&self) -> Option<u32> {
+        self.buffer_size
+    }
+
+    /// Switch to another buffer size, rebuilding the stream on the same device
+    pub fn set_buffer_size(&mut self, buffer_size: Option<u32>) -> Result<()> {
+        if buffer_size == self.buffer_size {
+            return Ok(());
+        }
+        log::info!("🔊 Switching audio buffer to {} frames", buffer_size.map_or("default".to_string(), |size| size.to_string()));
+        let new_output = Self::create_with_device(self.host_name.as_deref(), self.device_name.as_deref(), buffer_size)?;
+        self.replace_stream(new_output);
+        self.buffer_size = buffer_size;
+        Ok(())
     }
 
     /// Try to reconnect to an available audio device
     pub fn try_reconnect(&mut self) -> Result<()> {
         log::info!("Attempting to reconnect to audio device...");
-        match Self::create_with_device(None, self.buffer_size) {
+        let result = Self::create_with_device(self.host_name.as_deref(), self.device_name.as_deref(), self.buffer_size)
+            .or_else(|e| {
+                if self.device_name.is_none() && self.host_name.is_none() {
+                    return Err(e);
+                }
+                // Selected device is gone (e.g. USB interface unplugged), use the default
+                log::warn!("Selected audio device unavailable ({}), trying default device", e);
+                Self::create_with_device(None, None, self.buffer_size)
+            });
+        match result {
             Ok(new_output) => {
-                // Replace the current output with the new one
-                self._stream = new_output._stream;
-                self.event_producer = new_output.event_producer;
-                self.stats = new_output.stats;
-                self.engine_control_tx = new_output.engine_control_tx;
-                self.stream_error.store(false, std::sync::atomic::Ordering::Relaxed);
+                self.replace_stream(new_output);
                 log::info!("Successfully reconnected to audio device");
                 Ok(())
             }
@@ -94,14 +334,61 @@ impl AudioOutput {
         }
     }
 
+    /// Take over the stream of a freshly created output and restore engine settings
+    fn replace_stream(&mut self, new_output: AudioOutput) {
+        self._stream = new_output._stream;
+        self.event_producer = new_output.event_producer;
+        self.stats = new_output.stats;
+        new_output.latency.set_enabled(self.latency.is_enabled());
+        self.latency = new_output.latency;
+        self.engine_control_tx = new_output.engine_control_tx;
+        self.stream_error.store(false, std::sync::atomic::Ordering::Relaxed);
+        self.monitor_tap = new_output.monitor_tap;
+        self.meters = new_output.meters;
+
+        // The new stream has a fresh engine: replay instrument, tuning, etc.
+        // SoundFonts were handed to the old engine, so they load again
+        let mut settings = self.engine_settings.lock().unwrap();
+        for command in settings.iter_mut() {
+            #[cfg(feature = "soundfont")]
+            if let EngineControl::LoadSoundFont(player, load) = command {
+                *load = self.spawn_soundfont_load(*player, load.path().to_path_buf());
+            }
+            let _ = self.engine_control_tx.send(command.clone());
+        }
+        drop(settings);
+
+        // Reopen input monitoring at the new stream's sample rate
+        if let Some(monitor) = self.input_monitor.take() {
+            let device = monitor.requested_device().map(|name| name.to_string());
+            let (channel, gain) = (monitor.channel(), monitor.gain());
+            drop(monitor);
+            if let Err(e) = self.open_input_monitor(new_output.host_name.as_deref(), device.as_deref(), channel, gain) {
+                log::warn!("🎤 Input monitoring stopped after audio device change: {}", e);
+            }
+        }
+    }
+
+    /// Send an engine command and remember it for stream rebuilds
+    fn send_control(&self, command: EngineControl) -> std::result::Result<(), std::sync::mpsc::SendError<EngineControl>> {
+        {
+            let mut settings = self.engine_settings.lock().unwrap();
+            let key = command.replay_key();
+            settings.retain(|existing| existing.replay_key() != key);
+            settings.push(command.clone());
+        }
+        self.engine_control_tx.send(command)
+    }
+
     /// Check if there was a stream error
     pub fn has_stream_error(&self) -> bool {
         self.stream_error.load(std::sync::atomic::Ordering::Relaxed)
     }
 
     /// Create audio output with a specific device (or find available one)
-    fn create_with_device(device_name: Option<&str>, buffer_size: Option<u32>) -> Result<Self> {
-        let host = cpal::default_host();
+    fn create_with_device(host_name: Option<&str>, device_name: Option<&str>, buffer_size: Option<u32>) -> Result<Self> {
+        let host = host_by_name(host_name)?;
+        log::info!("Using audio host: {}", host.id().name());
         
         // Try to get the specified device or find an available one
         let device = if let Some(name) = device_name {
@@ -126,22 +413,35 @@ impl AudioOutput {
 
         let config = Self::get_config(&device, buffer_size)?;
         let sample_rate = config.sample_rate.0;
-        
+        let render_sample_rate = PREFERRED_SAMPLE_RATE;
+        let mut resampler = (sample_rate != render_sample_rate).then(|| {
+            log::warn!("🔁 Device runs at {}Hz, resampling from {}Hz", sample_rate, render_sample_rate);
+            StereoResampler::new(render_sample_rate, sample_rate)
+        });
+
         // Create ring buffer for events (lock-free, RT-safe)
-        let ring_buffer = HeapRb::<MusicEvent>::new(1024);
+        let ring_buffer = HeapRb::<PlayerEvent>::new(1024);
         let (event_producer, mut event_consumer) = ring_buffer.split(); // mutable for Consumer trait
 
         let stats = Arc::new(AudioStatsInner {
             sample_rate,
+            render_sample_rate,
             buffer_size: buffer_size.unwrap_or(256),
             underruns: AtomicU64::new(0),
             active_voices: AtomicUsize::new(0),
+            voices_stolen: AtomicU64::new(0),
+            first_callback_us: AtomicU64::new(0),
+            denormals_flushed: AtomicBool::new(false),
         });
 
         let stats_clone = Arc::clone(&stats);
 
-        // Create audio engine
-        let mut engine = AudioEngine::new(sample_rate);
+        let latency = Arc::new(LatencyHistogram::new());
+        let latency_clone = Arc::clone(&latency);
+
+        // Create audio engine and get its first-use costs out of the way
+        let mut engine = AudioEngine::new(render_sample_rate);
+        denormal::warm_up(&mut engine, render_sample_rate);
         
         // Create channel for engine control
         let (engine_control_tx, engine_control_rx) = std::sync::mpsc::channel::<EngineControl>();
@@ -152,10 +452,33 @@ impl AudioOutput {
         let stream_error = Arc::new(std::sync::atomic::AtomicBool::new(false));
         let stream_error_clone = Arc::clone(&stream_error);
 
+        // Underrun detection from render time and callback timestamps
+        let underrun_detector = UnderrunDetector::new(sample_rate);
+        let channels = config.channels as usize;
+        let mut last_callback: Option<cpal::StreamInstant> = None;
+
+        let monitor_tap: Arc<std::sync::Mutex<Option<MonitorTap>>> = Arc::new(std::sync::Mutex::new(None));
+        let monitor_tap_clone = Arc::clone(&monitor_tap);
+
+        let meters = Arc::new(MeterTap::new(sample_rate));
+        let meters_clone = Arc::clone(&meters);
+
         // Build the audio stream
+        let stream_created = std::time::Instant::now();
+        let mut first_callback = true;
         let stream = device.build_output_stream(
             &config,
-            move |data: &mut [f32], _: &cpal::OutputCallbackInfo| {
+            move |data: &mut [f32], info: &cpal::OutputCallbackInfo| {
+                let callback_started = std::time::Instant::now();
+
+                // FTZ/DAZ is per thread, so it is set from the audio thread itself
+                if first_callback {
+                    first_callback = false;
+                    let micros = callback_started.duration_since(stream_created).as_micros() as u64;
+                    stats_clone.first_callback_us.store(micros.max(1), Ordering::Relaxed);
+                    stats_clone.denormals_flushed.store(denormal::enable_flush_to_zero(), Ordering::Relaxed);
+                }
+
                 // Check for engine control commands
                 if let Ok(rx) = engine_control_rx_clone.try_lock() {
                     while let Ok(command) = rx.try_recv() {
@@ -179,17 +502,91 @@ impl AudioOutput {
                             EngineControl::SetSustainReleaseTime(time) => {
                                 engine.set_sustain_release_time(time);
                             }
-                            #[cfg(feature = "soundfont")]
-                            EngineControl::LoadSoundFont(path) => {
-                                if let Err(e) = engine.load_soundfont(&path) {
-                                    log::error!("Failed to load soundfont: {}", e);
+                            EngineControl::SetA4Frequency(frequency) => {
+                                engine.set_a4_frequency(frequency);
+                            }
+                            EngineControl::SetTuning(tuning) => {
+                                engine.set_tuning(tuning);
+                            }
+                            EngineControl::SetStereoSpread(width) => {
+                                engine.set_stereo_spread(width);
+                            }
+                            EngineControl::SetMaxVoices(voices) => {
+                                engine.set_max_voices(voices);
+                            }
+                            EngineControl::SetWhammySmoothing(factor) => {
+                                engine.set_whammy_smoothing(factor);
+                            }
+                            EngineControl::SetPlayerVirtualInstrument(player, instrument) => {
+                                if let Err(e) = engine.set_player_virtual_instrument(player, instrument) {
+                                    log::error!("Failed to set player virtual instrument: {}", e);
+                                }
+                            }
+                            EngineControl::SetLayerInstrument(instrument) => {
+                                engine.set_layer_instrument(instrument);
+                            }
+                            EngineControl::SetPresetLayer(layer) => {
+                                engine.set_preset_layer(layer);
+                            }
+                            EngineControl::SetBackingTrack(track) => {
+                                engine.set_backing_track(track);
+                            }
+                            EngineControl::SyncBackingTrack { seconds, playing, speed } => {
+                                engine.sync_backing_track(seconds, playing, speed);
+                            }
+                            EngineControl::SetBackingTrackGain(gain) => {
+                                engine.set_backing_track_gain(gain);
+                            }
+                            EngineControl::Click { downbeat } => {
+                                engine.play_click(downbeat);
+                            }
+                            EngineControl::StartDrums { pattern, bpm, beat } => {
+                                engine.start_drums(pattern, bpm, beat);
+                            }
+                            EngineControl::StopDrums => {
+                                engine.stop_drums();
+                            }
+                            EngineControl::SyncDrums { beat, bpm, playing } => {
+                                engine.sync_drums(beat, bpm, playing);
+                            }
+                            EngineControl::SetDrumsVolume(volume) => {
+                                engine.set_drums_volume(volume);
+                            }
+                            EngineControl::SetRockOutEffect(effect) => {
+                                engine.set_rock_out_effect(effect);
+                            }
+                            EngineControl::SetMixerLevel(channel, gain) => {
+                                engine.set_mixer_level(channel, gain);
+                            }
+                            EngineControl::LoadSampler(player, zones) => {
+                                if let Err(e) = engine.load_player_sampler(player, zones) {
+                                    log::error!("Failed to load sample instrument: {}", e);
                                 }
                             }
+                            #[cfg(feature = "soundfont")]
+                            EngineControl::LoadSoundFont(player, load) => {
+                                engine.load_soundfont_async(player, load);
+                            }
                         }
                     }
                 }
                 
-                Self::audio_callback(data, &mut engine, &mut event_consumer, &stats_clone);
+                Self::audio_callback(data, &mut engine, resampler.as_mut(), &mut event_consumer, &stats_clone, &latency_clone);
+
+                // Mix in the monitored input (only swapped by the UI when starting/stopping)
+                if let Ok(mut tap) = monitor_tap_clone.try_lock() {
+                    if let Some(tap) = tap.as_mut() {
+                        tap.mix_into(data, channels);
+                    }
+                }
+                meters_clone.measure(data, channels);
+
+                let callback = info.timestamp().callback;
+                let gap = last_callback.and_then(|last| callback.duration_since(&last));
+                last_callback = Some(callback);
+                if underrun_detector.check(data.len() / channels, callback_started.elapsed(), gap) {
+                    stats_clone.underruns.fetch_add(1, Ordering::Relaxed);
+                }
             },
             move |err| {
                 log::error!("Audio stream error: {}", err);
@@ -210,54 +607,282 @@ impl AudioOutput {
             _stream: StreamWrapper(stream),
             event_producer,
             stats,
+            latency,
             engine_control_tx,
             stream_error,
             buffer_size,
+            host_name: host_name.filter(|name| !is_default_host_name(name)).map(|name| name.to_string()),
+            device_name: device_name.map(|name| name.to_string()),
+            engine_settings: std::sync::Mutex::new(Vec::new()),
+            monitor_tap,
+            input_monitor: None,
+            loaded_soundfonts: Arc::new(std::sync::Mutex::new(Vec::new())),
+            meters,
         })
     }
     
+    /// Load a SoundFont for player 1 in the background (the current
+    /// instrument keeps playing until it is ready)
     #[cfg(feature = "soundfont")]
     pub fn load_soundfont(&self, path: std::path::PathBuf) -> Result<()> {
-        self.engine_control_tx.send(EngineControl::LoadSoundFont(path))
+        self.load_player_soundfont(0, path)
+    }
+
+    /// Load a SoundFont for a specific player in the background
+    #[cfg(feature = "soundfont")]
+    pub fn load_player_soundfont(&self, player: usize, path: std::path::PathBuf) -> Result<()> {
+        if player >= MAX_PLAYERS {
+            anyhow::bail!("Invalid player index: {}", player);
+        }
+        let load = self.spawn_soundfont_load(player, path);
+        self.send_control(EngineControl::LoadSoundFont(player, load))
             .context("Failed to send soundfont load message")?;
         Ok(())
     }
 
+    /// Start loading a SoundFont, reporting to `take_loaded_soundfonts` when done
+    #[cfg(feature = "soundfont")]
+    fn spawn_soundfont_load(&self, player: usize, path: std::path::PathBuf) -> SoundFontLoad {
+        let loaded_soundfonts = Arc::clone(&self.loaded_soundfonts);
+        let reported_path = path.clone();
+        SoundFontLoad::spawn(path, self.stats.render_sample_rate as f32, move |result| {
+            let mut loaded = loaded_soundfonts.lock().unwrap();
+            if loaded.len() >= MAX_LOADED_SOUNDFONTS {
+                loaded.remove(0);
+            }
+            loaded.push(SoundFontLoaded {
+                player,
+                path: reported_path,
+                error: result.err().map(|e| format!("{:#}", e)),
+            });
+        })
+    }
+
+    /// Whether every SoundFont asked for has finished loading
+    pub fn soundfonts_ready(&self) -> bool {
+        self.engine_settings.lock().unwrap().iter().all(|command| match command {
+            #[cfg(feature = "soundfont")]
+            EngineControl::LoadSoundFont(_, load) => load.is_finished(),
+            _ => true,
+        })
+    }
+
+    /// SoundFonts that finished loading since the last call
+    pub fn take_loaded_soundfonts(&self) -> Vec<SoundFontLoaded> {
+        std::mem::take(&mut *self.loaded_soundfonts.lock().unwrap())
+    }
+
+    /// Switch a player to a sample instrument. Decode the zones with
+    /// `load_sample_folder` first so the audio thread never touches the disk.
+    pub fn load_sampler(&self, player: usize, zones: Vec<SampleZone>) -> Result<()> {
+        self.send_control(EngineControl::LoadSampler(player, zones))
+            .context("Failed to send sample instrument load message")?;
+        Ok(())
+    }
+
     /// Switch to using fallback synth for virtual instruments
     pub fn use_fallback_synth(&self) -> Result<()> {
-        self.engine_control_tx.send(EngineControl::UseFallbackSynth)
+        self.send_control(EngineControl::UseFallbackSynth)
             .context("Failed to send fallback synth message")?;
         Ok(())
     }
 
     /// Set virtual instrument type
     pub fn set_virtual_instrument(&self, instrument: SynthInstrumentType) -> Result<()> {
-        self.engine_control_tx.send(EngineControl::SetVirtualInstrument(instrument))
+        self.send_control(EngineControl::SetVirtualInstrument(instrument))
             .context("Failed to send virtual instrument message")?;
         Ok(())
     }
     
+    /// Set virtual instrument type for a specific player
+    pub fn set_player_virtual_instrument(&self, player: usize, instrument: SynthInstrumentType) -> Result<()> {
+        self.send_control(EngineControl::SetPlayerVirtualInstrument(player, instrument))
+            .context("Failed to send player virtual instrument message")?;
+        Ok(())
+    }
+
+    /// Set the instrument the tilt fades in under player 1 (None = no layer)
+    pub fn set_layer_instrument(&self, instrument: Option<SynthInstrumentType>) -> Result<()> {
+        self.send_control(EngineControl::SetLayerInstrument(instrument))
+            .context("Failed to send layer instrument message")?;
+        Ok(())
+    }
+
+    /// Set the instrument the genre preset layers under player 1 (None = no layer)
+    pub fn set_preset_layer(&self, layer: Option<InstrumentLayer>) -> Result<()> {
+        self.send_control(EngineControl::SetPresetLayer(layer))
+            .context("Failed to send preset layer message")?;
+        Ok(())
+    }
+    
+    /// Set the song's backing track (None removes it)
+    pub fn set_backing_track(&self, track: Option<Arc<BackingTrack>>) -> Result<()> {
+        self.send_control(EngineControl::SetBackingTrack(track))
+            .context("Failed to send backing track message")?;
+        Ok(())
+    }
+
+    /// Move the backing track to a position (seconds), play state and speed.
+    /// Not replayed after a stream rebuild: the position would be stale.
+    pub fn sync_backing_track(&self, seconds: f64, playing: bool, speed: f64) -> Result<()> {
+        self.engine_control_tx.send(EngineControl::SyncBackingTrack { seconds, playing, speed })
+            .context("Failed to send backing track sync message")?;
+        Ok(())
+    }
+
+    pub fn set_backing_track_gain(&self, gain: f32) -> Result<()> {
+        self.send_control(EngineControl::SetBackingTrackGain(gain))
+            .context("Failed to send backing track gain message")?;
+        Ok(())
+    }
+
+    /// Play a metronome click (higher on the downbeat); not replayed
+    pub fn play_click(&self, downbeat: bool) -> Result<()> {
+        self.engine_control_tx.send(EngineControl::Click { downbeat })
+            .context("Failed to send click message")?;
+        Ok(())
+    }
+
+    /// Play a drum groove from a beat. Like the backing track position, the
+    /// drums are not replayed after a stream rebuild.
+    pub fn start_drums(&self, pattern: GroovePattern, bpm: f64, beat: f64) -> Result<()> {
+        self.engine_control_tx.send(EngineControl::StartDrums { pattern, bpm, beat })
+            .context("Failed to send drums start message")?;
+        Ok(())
+    }
+
+    pub fn stop_drums(&self) -> Result<()> {
+        self.engine_control_tx.send(EngineControl::StopDrums)
+            .context("Failed to send drums stop message")?;
+        Ok(())
+    }
+
+    /// Move the drums to the transport's beat, tempo and play state; not replayed
+    pub fn sync_drums(&self, beat: f64, bpm: f64, playing: bool) -> Result<()> {
+        self.engine_control_tx.send(EngineControl::SyncDrums { beat, bpm, playing })
+            .context("Failed to send drums sync message")?;
+        Ok(())
+    }
+
+    pub fn set_drums_volume(&self, volume: f32) -> Result<()> {
+        self.send_control(EngineControl::SetDrumsVolume(volume))
+            .context("Failed to send drums volume message")?;
+        Ok(())
+    }
+
+    /// Set the effect tilting the guitar up switches on (None = no effect)
+    pub fn set_rock_out_effect(&self, effect: Option<RockOutEffect>) -> Result<()> {
+        self.send_control(EngineControl::SetRockOutEffect(effect))
+            .context("Failed to send rock-out effect message")?;
+        Ok(())
+    }
+
+    /// Set a mixer fader (0.0..MAX_MIXER_GAIN), returns the applied gain
+    pub fn set_mixer_level(&self, channel: MixerChannel, gain: f32) -> Result<f32> {
+        let gain = mixer::clamp_mixer_gain(gain);
+        self.send_control(EngineControl::SetMixerLevel(channel, gain))
+            .context("Failed to send mixer level message")?;
+        Ok(gain)
+    }
+
     /// Set release time multiplier (affects how long notes fade out)
     pub fn set_release_multiplier(&self, multiplier: f32) -> Result<()> {
-        self.engine_control_tx.send(EngineControl::SetReleaseMultiplier(multiplier))
+        self.send_control(EngineControl::SetReleaseMultiplier(multiplier))
             .context("Failed to send release multiplier message")?;
         Ok(())
     }
     
     /// Enable or disable sustain mode
     pub fn set_sustain_enabled(&self, enabled: bool) -> Result<()> {
-        self.engine_control_tx.send(EngineControl::SetSustainEnabled(enabled))
+        self.send_control(EngineControl::SetSustainEnabled(enabled))
             .context("Failed to send sustain enabled message")?;
         Ok(())
     }
     
     /// Set sustain release time in seconds
     pub fn set_sustain_release_time(&self, time_seconds: f32) -> Result<()> {
-        self.engine_control_tx.send(EngineControl::SetSustainReleaseTime(time_seconds))
+        self.send_control(EngineControl::SetSustainReleaseTime(time_seconds))
             .context("Failed to send sustain release time message")?;
         Ok(())
     }
 
+    /// Set the A4 reference frequency in Hz (master tuning, 432-446)
+    pub fn set_a4_frequency(&self, frequency: f32) -> Result<()> {
+        self.send_control(EngineControl::SetA4Frequency(frequency))
+            .context("Failed to send A4 frequency message")?;
+        Ok(())
+    }
+
+    /// Set the temperament used by the fallback synth
+    pub fn set_tuning(&self, tuning: TuningTable) -> Result<()> {
+        self.send_control(EngineControl::SetTuning(tuning))
+            .context("Failed to send tuning message")?;
+        Ok(())
+    }
+
+    /// Set how wide chord notes are spread across the stereo field (0.0-1.0)
+    pub fn set_stereo_spread(&self, width: f32) -> Result<()> {
+        self.send_control(EngineControl::SetStereoSpread(width))
+            .context("Failed to send stereo spread message")?;
+        Ok(())
+    }
+
+    /// Set how many notes each virtual instrument plays at once
+    /// (`synth::MIN_VOICES`-`synth::MAX_VOICES`)
+    pub fn set_max_voices(&self, voices: usize) -> Result<()> {
+        self.send_control(EngineControl::SetMaxVoices(voices))
+            .context("Failed to send max voices message")?;
+        Ok(())
+    }
+
+    /// Set how smoothly whammy filter and volume changes glide (0.0 = jump, up to 0.99)
+    pub fn set_whammy_smoothing(&self, factor: f32) -> Result<()> {
+        self.send_control(EngineControl::SetWhammySmoothing(factor))
+            .context("Failed to send whammy smoothing message")?;
+        Ok(())
+    }
+
+    /// List input devices of the current host (for input monitoring)
+    pub fn list_input_devices(&self) -> Result<Vec<AudioDeviceInfo>> {
+        monitor::list_input_devices(&host_by_name(self.host_name.as_deref())?)
+    }
+
+    /// Monitor an input device through the output (None = default input,
+    /// channel None = all channels mixed). Restarts monitoring if already running.
+    pub fn start_input_monitor(&mut self, device_name: Option<&str>, channel: Option<u16>, gain: f32) -> Result<()> {
+        self.stop_input_monitor();
+        let host_name = self.host_name.clone();
+        self.open_input_monitor(host_name.as_deref(), device_name, channel, gain)
+    }
+
+    fn open_input_monitor(&mut self, host_name: Option<&str>, device_name: Option<&str>, channel: Option<u16>, gain: f32) -> Result<()> {
+        let host = host_by_name(host_name)?;
+        let (monitor, tap) = InputMonitor::start(&host, device_name, channel, gain, self.stats.sample_rate, self.buffer_size)?;
+        *self.monitor_tap.lock().unwrap() = Some(tap);
+        self.input_monitor = Some(monitor);
+        Ok(())
+    }
+
+    pub fn stop_input_monitor(&mut self) {
+        if self.input_monitor.take().is_some() {
+            log::info!("🎤 Input monitoring stopped");
+        }
+        *self.monitor_tap.lock().unwrap() = None;
+    }
+
+    /// Set the monitoring gain (0.0-4.0), returns the applied value
+    pub fn set_input_monitor_gain(&self, gain: f32) -> f32 {
+        match &self.input_monitor {
+            Some(monitor) => monitor.set_gain(gain),
+            None => monitor::clamp_monitor_gain(gain),
+        }
+    }
+
+    /// Input monitoring level and latency (inactive when monitoring is off)
+    pub fn input_monitor_stats(&self) -> InputMonitorStats {
+        self.input_monitor.as_ref().map(|monitor| monitor.stats()).unwrap_or_default()
+    }
+
     fn get_config(device: &Device, buffer_size: Option<u32>) -> Result<StreamConfig> {
         let default_config = device.default_output_config()?;
         
@@ -271,9 +896,19 @@ impl AudioOutput {
             },
         };
 
-        // Prefer 48kHz if available
-        if config.sample_rate.0 != 48000 {
-            config.sample_rate = cpal::SampleRate(48000);
+        // Prefer 48kHz, otherwise the nearest rate the device supports for stereo f32
+        let ranges: Vec<(u32, u32)> = match device.supported_output_configs() {
+            Ok(configs) => configs
+                .filter(|range| range.channels() == config.channels && range.sample_format() == cpal::SampleFormat::F32)
+                .map(|range| (range.min_sample_rate().0, range.max_sample_rate().0))
+                .collect(),
+            Err(e) => {
+                log::warn!("Could not query supported configs, using default rate: {}", e);
+                Vec::new()
+            }
+        };
+        if let Some(rate) = nearest_supported_rate(&ranges, PREFERRED_SAMPLE_RATE) {
+            config.sample_rate = cpal::SampleRate(rate);
         }
 
         Ok(config)
@@ -283,42 +918,92 @@ impl AudioOutput {
     fn audio_callback(
         data: &mut [f32],
         engine: &mut AudioEngine,
-        event_consumer: &mut ringbuf::HeapCons<MusicEvent>,
+        resampler: Option<&mut StereoResampler>,
+        event_consumer: &mut ringbuf::HeapCons<PlayerEvent>,
         stats: &AudioStatsInner,
+        latency: &LatencyHistogram,
     ) {
         // Process all pending events
-        while let Some(event) = Consumer::try_pop(event_consumer) {
-            engine.handle_event(event);
+        while let Some((player, event, input_ns)) = Consumer::try_pop(event_consumer) {
+            if input_ns != 0 && matches!(event, MusicEvent::NoteOn { .. }) && latency.is_enabled() {
+                latency.record(input_ns, controller::high_performance::timestamp_nanos());
+            }
+            engine.handle_player_event(player as usize, event);
         }
 
         // Generate audio
-        engine.render(data);
+        match resampler {
+            Some(resampler) => resampler.process(data, |buffer| engine.render(buffer)),
+            None => engine.render(data),
+        }
 
         // Update stats (atomic operations are RT-safe)
         stats.active_voices.store(engine.active_voice_count(), Ordering::Relaxed);
+        stats.voices_stolen.fetch_add(engine.take_stolen_voices(), Ordering::Relaxed);
     }
 
     /// Send a music event to the audio thread (RT-safe, lock-free)
     pub fn send_event(&mut self, event: MusicEvent) -> Result<()> {
-        Producer::try_push(&mut self.event_producer, event)
+        self.send_player_event(0, event)
+    }
+
+    /// Send a music event for a specific player (routed to that player's synth)
+    pub fn send_player_event(&mut self, player: usize, event: MusicEvent) -> Result<()> {
+        self.send_timed_player_event(player, event, 0)
+    }
+
+    /// Send a music event carrying the timestamp of the input that caused it
+    /// (nanoseconds since epoch, see `controller::high_performance::timestamp_nanos`)
+    pub fn send_timed_player_event(&mut self, player: usize, event: MusicEvent, input_ns: u64) -> Result<()> {
+        if player >= MAX_PLAYERS {
+            anyhow::bail!("Invalid player index: {}", player);
+        }
+        Producer::try_push(&mut self.event_producer, (player as u8, event, input_ns))
             .map_err(|_| anyhow::anyhow!("Audio event queue full"))?;
         Ok(())
     }
 
+    /// Enable or disable input-to-audio latency measurement
+    pub fn set_latency_measurement(&self, enabled: bool) {
+        self.latency.set_enabled(enabled);
+    }
+
+    /// Input-to-audio latency histogram summary
+    pub fn get_latency_stats(&self) -> LatencyStats {
+        self.latency.stats()
+    }
+
+    pub fn reset_latency_stats(&self) {
+        self.latency.reset();
+    }
+
     /// Get current audio statistics
     pub fn get_stats(&self) -> AudioStats {
         let buffer_size = self.stats.buffer_size;
         let sample_rate = self.stats.sample_rate;
+        let render_sample_rate = self.stats.render_sample_rate;
         
         AudioStats {
             sample_rate,
+            render_sample_rate,
             buffer_size,
             underruns: self.stats.underruns.load(Ordering::Relaxed),
             active_voices: self.stats.active_voices.load(Ordering::Relaxed),
+            voices_stolen: self.stats.voices_stolen.load(Ordering::Relaxed),
             estimated_latency_ms: (buffer_size as f32 / sample_rate as f32) * 1000.0,
+            first_callback_ms: match self.stats.first_callback_us.load(Ordering::Relaxed) {
+                0 => None,
+                micros => Some(micros as f32 / 1000.0),
+            },
+            denormals_flushed: self.stats.denormals_flushed.load(Ordering::Relaxed),
         }
     }
 
+    /// Output levels since the previous call, with the band levels if `spectrum`
+    pub fn meters(&self, spectrum: bool) -> AudioMeters {
+        self.meters.read(spectrum)
+    }
+
     /// Send panic/all notes off
     pub fn panic(&mut self) -> Result<()> {
         self.send_event(MusicEvent::PanicAllNotesOff)
@@ -333,13 +1018,42 @@ mod tests {
     fn test_audio_stats() {
         let stats = AudioStats {
             sample_rate: 48000,
+            render_sample_rate: 48000,
             buffer_size: 256,
             underruns: 0,
             active_voices: 0,
+            voices_stolen: 0,
             estimated_latency_ms: 5.33,
+            first_callback_ms: Some(12.5),
+            denormals_flushed: true,
         };
         
         assert_eq!(stats.sample_rate, 48000);
         assert!(stats.estimated_latency_ms < 10.0);
     }
+
+    #[test]
+    fn test_default_host_names() {
+        assert!(is_default_host_name("fallback"));
+        assert!(is_default_host_name("Default"));
+        assert!(!is_default_host_name("ASIO"));
+        assert!(host_by_name(Some("NoSuchBackend")).is_err());
+    }
+
+    #[test]
+    fn test_engine_control_replay_key() {
+        // Instrument selections for player 1 replace each other
+        assert_eq!(
+            EngineControl::UseFallbackSynth.replay_key(),
+            EngineControl::SetVirtualInstrument(SynthInstrumentType::Piano).replay_key()
+        );
+        assert_ne!(
+            EngineControl::SetPlayerVirtualInstrument(1, SynthInstrumentType::Piano).replay_key(),
+            EngineControl::SetVirtualInstrument(SynthInstrumentType::Piano).replay_key()
+        );
+        assert_ne!(
+            EngineControl::SetA4Frequency(440.0).replay_key(),
+            EngineControl::SetSustainEnabled(true).replay_key()
+        );
+    }
 }
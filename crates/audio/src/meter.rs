@@ -0,0 +1,224 @@
+//! Output level meters
+//! The output callback measures every buffer it hands to the device: peak and
+//! RMS per channel and samples at full scale. It also keeps the latest
+//! `SPECTRUM_WINDOW` frames (mixed to mono) in a ring of atomics. Reading the
+//! meters swaps the peaks and clip count back to zero. The optional 32-band
+//! spectrum is computed from the ring on the reader's thread, so the audio
+//! thread only pays for a few adds and relaxed stores per frame.
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+
+/// Bands in `AudioMeters::spectrum`
+pub const SPECTRUM_BANDS: usize = 32;
+/// Frames the spectrum is computed over (a power of two, ~21ms at 48kHz)
+const SPECTRUM_WINDOW: usize = 1024;
+/// Band edges (the top one is capped at Nyquist)
+const SPECTRUM_LOW_HZ: f32 = 20.0;
+const SPECTRUM_HIGH_HZ: f32 = 20000.0;
+/// Quietest level reported, in dBFS
+pub const METER_FLOOR_DB: f32 = -96.0;
+/// Samples at or above this count as clipped
+const CLIP_LEVEL: f32 = 0.999;
+
+/// Output levels for the UI (linear, 1.0 = full scale)
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct AudioMeters {
+    /// Left/right peak since the previous read
+    pub peak: [f32; 2],
+    /// Left/right RMS of the latest buffer
+    pub rms: [f32; 2],
+    /// Samples at full scale since the previous read
+    pub clipped_samples: u64,
+    /// Level per band in dBFS, low to high (empty unless asked for)
+    pub spectrum: Vec<f32>,
+}
+
+/// Shared between the output callback (`measure`) and the UI (`read`)
+pub struct MeterTap {
+    sample_rate: u32,
+    peak_bits: [AtomicU32; 2],
+    rms_bits: [AtomicU32; 2],
+    clipped: AtomicU64,
+    /// Latest mono frames (f32 bits), `write` is the next slot
+    window: Box<[AtomicU32]>,
+    write: AtomicUsize,
+}
+
+impl MeterTap {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            sample_rate,
+            peak_bits: [AtomicU32::new(0), AtomicU32::new(0)],
+            rms_bits: [AtomicU32::new(0), AtomicU32::new(0)],
+            clipped: AtomicU64::new(0),
+            window: (0..SPECTRUM_WINDOW).map(|_| AtomicU32::new(0)).collect(),
+            write: AtomicUsize::new(0),
+        }
+    }
+
+    /// Measure an interleaved output buffer (RT-safe, no allocations)
+    pub fn measure(&self, data: &[f32], channels: usize) {
+        let channels = channels.max(1);
+        let frames = data.len() / channels;
+        if frames == 0 {
+            return;
+        }
+        let mut peak = [0.0f32; 2];
+        let mut squares = [0.0f32; 2];
+        let mut clipped = 0;
+        let mut write = self.write.load(Ordering::Relaxed);
+        for frame in data.chunks_exact(channels) {
+            // Mono devices show the same level on both sides
+            let left = frame[0];
+            let right = frame[1.min(channels - 1)];
+            for (side, sample) in [left, right].into_iter().enumerate() {
+                peak[side] = peak[side].max(sample.abs());
+                squares[side] += sample * sample;
+            }
+            clipped += frame.iter().filter(|sample| sample.abs() >= CLIP_LEVEL).count() as u64;
+            self.window[write].store((0.5 * (left + right)).to_bits(), Ordering::Relaxed);
+            write = (write + 1) % SPECTRUM_WINDOW;
+        }
+        self.write.store(write, Ordering::Relaxed);
+        for side in 0..2 {
+            self.peak_bits[side].fetch_max(peak[side].to_bits(), Ordering::Relaxed);
+            self.rms_bits[side].store((squares[side] / frames as f32).sqrt().to_bits(), Ordering::Relaxed);
+        }
+        if clipped > 0 {
+            self.clipped.fetch_add(clipped, Ordering::Relaxed);
+        }
+    }
+
+    /// Current levels, resetting the peaks and clip count; `spectrum` adds the band levels
+    pub fn read(&self, spectrum: bool) -> AudioMeters {
+        AudioMeters {
+            peak: [0, 1].map(|side| f32::from_bits(self.peak_bits[side].swap(0, Ordering::Relaxed))),
+            rms: [0, 1].map(|side| f32::from_bits(self.rms_bits[side].load(Ordering::Relaxed))),
+            clipped_samples: self.clipped.swap(0, Ordering::Relaxed),
+            spectrum: if spectrum { self.spectrum() } else { Vec::new() },
+        }
+    }
+
+    /// Band levels of the latest window (Hann-windowed FFT, log-spaced bands)
+    fn spectrum(&self) -> Vec<f32> {
+        let start = self.write.load(Ordering::Relaxed);
+        let mut bins: Vec<(f32, f32)> = (0..SPECTRUM_WINDOW)
+            .map(|i| {
+                let sample = f32::from_bits(self.window[(start + i) % SPECTRUM_WINDOW].load(Ordering::Relaxed));
+                let hann = 0.5 - 0.5 * (std::f32::consts::TAU * i as f32 / SPECTRUM_WINDOW as f32).cos();
+                (sample * hann, 0.0)
+            })
+            .collect();
+        fft(&mut bins);
+
+        // A full-scale sine reads 0 dB: undo the window's gain of 0.5 and the one-sided split
+        let scale = 4.0 / SPECTRUM_WINDOW as f32;
+        let bin_hz = self.sample_rate.max(1) as f32 / SPECTRUM_WINDOW as f32;
+        let high = SPECTRUM_HIGH_HZ.min(self.sample_rate as f32 / 2.0).max(SPECTRUM_LOW_HZ * 2.0);
+        let ratio = (high / SPECTRUM_LOW_HZ).powf(1.0 / SPECTRUM_BANDS as f32);
+        (0..SPECTRUM_BANDS)
+            .map(|band| {
+                let from = SPECTRUM_LOW_HZ * ratio.powi(band as i32);
+                let to = from * ratio;
+                // Low bands narrower than a bin read the bin they fall in
+                let first = ((from / bin_hz).round() as usize).clamp(1, SPECTRUM_WINDOW / 2);
+                let last = ((to / bin_hz).round() as usize).clamp(first + 1, SPECTRUM_WINDOW / 2 + 1);
+                let magnitude = bins[first..last]
+                    .iter()
+                    .map(|(re, im)| (re * re + im * im).sqrt())
+                    .fold(0.0f32, f32::max);
+                to_db(magnitude * scale)
+            })
+            .collect()
+    }
+}
+
+/// Linear level in dBFS, floored at `METER_FLOOR_DB`
+pub fn to_db(level: f32) -> f32 {
+    if level > 0.0 {
+        (20.0 * level.log10()).max(METER_FLOOR_DB)
+    } else {
+        METER_FLOOR_DB
+    }
+}
+
+/// In-place radix-2 FFT of (re, im) pairs (length must be a power of two)
+fn fft(data: &mut [(f32, f32)]) {
+    let n = data.len();
+    let mut j = 0;
+    for i in 1..n {
+        let mut bit = n >> 1;
+        while j & bit != 0 {
+            j ^= bit;
+            bit >>= 1;
+        }
+        j |= bit;
+        if i < j {
+            data.swap(i, j);
+        }
+    }
+    let mut len = 2;
+    while len <= n {
+        let angle = -std::f32::consts::TAU / len as f32;
+        for chunk in data.chunks_exact_mut(len) {
+            for k in 0..len / 2 {
+                let (sin, cos) = (angle * k as f32).sin_cos();
+                let (re, im) = chunk[k + len / 2];
+                let twiddled = (re * cos - im * sin, re * sin + im * cos);
+                let even = chunk[k];
+                chunk[k] = (even.0 + twiddled.0, even.1 + twiddled.1);
+                chunk[k + len / 2] = (even.0 - twiddled.0, even.1 - twiddled.1);
+            }
+        }
+        len <<= 1;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_levels_and_clipping() {
+        let tap = MeterTap::new(48000);
+        // Left at half scale, right silent, then one clipped sample on the right
+        let mut data: Vec<f32> = (0..256).flat_map(|_| [0.5, 0.0]).collect();
+        data[3] = 1.0;
+        tap.measure(&data, 2);
+
+        let meters = tap.read(false);
+        assert_eq!(meters.peak, [0.5, 1.0]);
+        assert!((meters.rms[0] - 0.5).abs() < 1e-4);
+        assert_eq!(meters.clipped_samples, 1);
+        assert!(meters.spectrum.is_empty());
+
+        // Peaks and clips reset on read, RMS stays until the next buffer
+        let meters = tap.read(false);
+        assert_eq!(meters.peak, [0.0, 0.0]);
+        assert_eq!(meters.clipped_samples, 0);
+        assert!((meters.rms[0] - 0.5).abs() < 1e-4);
+    }
+
+    #[test]
+    fn test_spectrum_finds_a_sine() {
+        let tap = MeterTap::new(48000);
+        let data: Vec<f32> = (0..SPECTRUM_WINDOW)
+            .flat_map(|i| {
+                let sample = 0.5 * (std::f32::consts::TAU * 1000.0 * i as f32 / 48000.0).sin();
+                [sample, sample]
+            })
+            .collect();
+        tap.measure(&data, 2);
+
+        let spectrum = tap.read(true).spectrum;
+        assert_eq!(spectrum.len(), SPECTRUM_BANDS);
+        let loudest = (0..SPECTRUM_BANDS).max_by(|&a, &b| spectrum[a].total_cmp(&spectrum[b])).unwrap();
+        let ratio = (SPECTRUM_HIGH_HZ / SPECTRUM_LOW_HZ).powf(1.0 / SPECTRUM_BANDS as f32);
+        let from = SPECTRUM_LOW_HZ * ratio.powi(loudest as i32);
+        assert!(from <= 1000.0 && 1000.0 <= from * ratio * ratio, "loudest band starts at {} Hz", from);
+        // Half scale is about -6 dB
+        assert!((spectrum[loudest] + 6.0).abs() < 3.0, "{} dB", spectrum[loudest]);
+        assert!(spectrum[0] < -40.0);
+    }
+}
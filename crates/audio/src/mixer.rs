@@ -0,0 +1,151 @@
+//! Mixer
+//! Faders for the engine's sources (the synths, backing track, metronome and
+//! drums) and the master, followed by a peak limiter so a loud mix squashes
+//! smoothly instead of clipping.
+
+use serde::{Deserialize, Serialize};
+
+/// Highest fader gain (+6 dB)
+pub const MAX_MIXER_GAIN: f32 = 2.0;
+/// Output ceiling of the limiter
+const LIMITER_CEILING: f32 = 0.95;
+/// Seconds for the limiter to recover by 1/e once peaks pass
+const LIMITER_RELEASE_SECONDS: f32 = 0.1;
+
+/// A mixer fader
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MixerChannel {
+    Master,
+    /// Every player's instrument and the tilt layer
+    Synth,
+    BackingTrack,
+    /// Count-in click
+    Metronome,
+    Drums,
+}
+
+impl MixerChannel {
+    pub fn all() -> &'static [MixerChannel] {
+        &[Self::Master, Self::Synth, Self::BackingTrack, Self::Metronome, Self::Drums]
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|channel| channel.name() == name)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Master => "master",
+            Self::Synth => "synth",
+            Self::BackingTrack => "backing_track",
+            Self::Metronome => "metronome",
+            Self::Drums => "drums",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// Fader gains and the master limiter (RT-safe)
+#[derive(Debug, Clone)]
+pub struct Mixer {
+    gains: [f32; 5],
+    /// Gain reduction the limiter is applying (1.0 = none)
+    limiter_gain: f32,
+    limiter_release: f32,
+}
+
+impl Mixer {
+    pub fn new(sample_rate: u32) -> Self {
+        Self {
+            gains: [1.0; 5],
+            limiter_gain: 1.0,
+            limiter_release: (-1.0 / (LIMITER_RELEASE_SECONDS * sample_rate.max(1) as f32)).exp(),
+        }
+    }
+
+    /// Set a fader (clamped to 0.0..MAX_MIXER_GAIN), returns the applied gain
+    pub fn set_level(&mut self, channel: MixerChannel, gain: f32) -> f32 {
+        let gain = clamp_mixer_gain(gain);
+        self.gains[channel.index()] = gain;
+        gain
+    }
+
+    pub fn level(&self, channel: MixerChannel) -> f32 {
+        self.gains[channel.index()]
+    }
+
+    /// Scale a source's buffer by its fader
+    pub fn apply(&self, channel: MixerChannel, buffer: &mut [f32]) {
+        let gain = self.level(channel);
+        if gain != 1.0 {
+            for sample in buffer.iter_mut() {
+                *sample *= gain;
+            }
+        }
+    }
+
+    /// Master gain and limiting on the final mix (interleaved stereo)
+    pub fn master(&mut self, buffer: &mut [f32]) {
+        let master = self.level(MixerChannel::Master);
+        for frame in buffer.chunks_mut(2) {
+            let peak = frame.iter().fold(0.0f32, |peak, sample| peak.max((sample * master).abs()));
+            // Clamp down at once on a peak, let go slowly
+            let needed = if peak > LIMITER_CEILING { LIMITER_CEILING / peak } else { 1.0 };
+            self.limiter_gain = if needed < self.limiter_gain {
+                needed
+            } else {
+                needed + (self.limiter_gain - needed) * self.limiter_release
+            };
+            for sample in frame.iter_mut() {
+                *sample = (*sample * master * self.limiter_gain).clamp(-1.0, 1.0);
+            }
+        }
+    }
+}
+
+/// Fader range the mixer accepts
+pub fn clamp_mixer_gain(gain: f32) -> f32 {
+    if gain.is_finite() { gain.clamp(0.0, MAX_MIXER_GAIN) } else { 1.0 }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faders_scale_and_clamp() {
+        let mut mixer = Mixer::new(48000);
+        assert_eq!(mixer.set_level(MixerChannel::Drums, 0.5), 0.5);
+        assert_eq!(mixer.set_level(MixerChannel::Metronome, 5.0), MAX_MIXER_GAIN);
+        assert_eq!(mixer.set_level(MixerChannel::Synth, f32::NAN), 1.0);
+
+        let mut buffer = vec![0.4, -0.4];
+        mixer.apply(MixerChannel::Drums, &mut buffer);
+        assert_eq!(buffer, vec![0.2, -0.2]);
+
+        assert_eq!(MixerChannel::from_name("backing_track"), Some(MixerChannel::BackingTrack));
+        assert_eq!(MixerChannel::from_name("vocals"), None);
+    }
+
+    #[test]
+    fn test_limiter_holds_peaks_under_ceiling() {
+        let mut mixer = Mixer::new(48000);
+        mixer.set_level(MixerChannel::Master, 2.0);
+        // Quiet material passes at the master gain
+        let mut buffer = vec![0.25; 64];
+        mixer.master(&mut buffer);
+        assert!(buffer.iter().all(|&sample| (sample - 0.5).abs() < 1e-6));
+
+        // A loud burst is held at the ceiling, and the gain recovers slowly after it
+        let mut buffer = vec![0.9; 64];
+        mixer.master(&mut buffer);
+        assert!(buffer.iter().all(|&sample| sample <= LIMITER_CEILING + 1e-6));
+        let mut buffer = vec![0.25; 2];
+        mixer.master(&mut buffer);
+        assert!(buffer[0] < 0.5);
+    }
+}
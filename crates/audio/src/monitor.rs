@@ -0,0 +1,319 @@
+//! Audio input monitoring
+//! Plays a guitar or mic plugged into the audio interface through the output
+//! stream, so mITyGuitar can be the only audio app running during practice.
+//! The input callback pushes a mono signal into a ring buffer that the output
+//! callback mixes in. Input and output clocks drift and callbacks arrive in
+//! bursts, so the output side trims the ring back whenever it grows past one
+//! buffer plus some slack, keeping monitoring latency near two device buffers.
+
+use anyhow::{Context, Result};
+use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+use ringbuf::traits::{Consumer, Observer, Producer, Split};
+use ringbuf::HeapRb;
+use serde::Serialize;
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use crate::StreamWrapper;
+
+/// Highest monitoring gain (+12 dB)
+pub const MAX_MONITOR_GAIN: f32 = 4.0;
+/// Ring buffer capacity in frames (~340ms at 48kHz)
+const MONITOR_RING_FRAMES: usize = 16384;
+/// Buffered frames tolerated on top of one output buffer before trimming
+const MONITOR_SLACK_FRAMES: usize = 128;
+
+/// Clamp a monitoring gain to 0..MAX_MONITOR_GAIN (non-finite = unity)
+pub fn clamp_monitor_gain(gain: f32) -> f32 {
+    if gain.is_finite() {
+        gain.clamp(0.0, MAX_MONITOR_GAIN)
+    } else {
+        1.0
+    }
+}
+
+/// Input monitoring status for the UI
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct InputMonitorStats {
+    pub active: bool,
+    pub device: Option<String>,
+    /// Channels the input device delivers
+    pub channels: u16,
+    /// Monitored channel (None = all channels mixed)
+    pub channel: Option<u16>,
+    pub gain: f32,
+    /// Input peak (0.0-1.0, before gain) since the last status read
+    pub input_peak: f32,
+    /// Input buffer + queued input + output buffer
+    pub estimated_latency_ms: f32,
+    /// Input dropped to keep latency down (drift or bursty callbacks)
+    pub trimmed_ms: f32,
+    /// The input stream reported an error (e.g. device unplugged)
+    pub stream_error: bool,
+}
+
+/// Counters shared by the input callback, output callback and UI
+struct MonitorShared {
+    gain_bits: AtomicU32,
+    peak_bits: AtomicU32,
+    input_frames: AtomicUsize,
+    output_frames: AtomicUsize,
+    buffered_frames: AtomicUsize,
+    trimmed_frames: AtomicU64,
+    stream_error: AtomicBool,
+}
+
+impl MonitorShared {
+    fn new(gain: f32) -> Self {
+        Self {
+            gain_bits: AtomicU32::new(clamp_monitor_gain(gain).to_bits()),
+            peak_bits: AtomicU32::new(0),
+            input_frames: AtomicUsize::new(0),
+            output_frames: AtomicUsize::new(0),
+            buffered_frames: AtomicUsize::new(0),
+            trimmed_frames: AtomicU64::new(0),
+            stream_error: AtomicBool::new(false),
+        }
+    }
+
+    fn gain(&self) -> f32 {
+        f32::from_bits(self.gain_bits.load(Ordering::Relaxed))
+    }
+
+    /// Raise the peak meter (bit order matches float order for positive values)
+    fn record_peak(&self, peak: f32) {
+        self.peak_bits.fetch_max(peak.abs().min(1.0).to_bits(), Ordering::Relaxed);
+    }
+}
+
+/// Mono sample from one interleaved input frame
+fn input_sample(frame: &[f32], channel: Option<u16>) -> f32 {
+    match channel.map(usize::from).and_then(|channel| frame.get(channel)) {
+        Some(&sample) => sample,
+        None if frame.is_empty() => 0.0,
+        None => frame.iter().sum::<f32>() / frame.len() as f32,
+    }
+}
+
+/// Output side of the monitor, mixed in by the output callback
+pub struct MonitorTap {
+    consumer: ringbuf::HeapCons<f32>,
+    shared: Arc<MonitorShared>,
+}
+
+impl MonitorTap {
+    /// Mix queued input into an interleaved output buffer (RT-safe)
+    pub fn mix_into(&mut self, data: &mut [f32], channels: usize) {
+        let channels = channels.max(1);
+        let frames = data.len() / channels;
+        let target = frames + MONITOR_SLACK_FRAMES;
+        let available = self.consumer.occupied_len();
+        if available > target + frames {
+            let trimmed = self.consumer.skip(available - target);
+            self.shared.trimmed_frames.fetch_add(trimmed as u64, Ordering::Relaxed);
+        }
+        self.shared.output_frames.store(frames, Ordering::Relaxed);
+        self.shared.buffered_frames.store(self.consumer.occupied_len(), Ordering::Relaxed);
+
+        let gain = self.shared.gain();
+        for frame in data.chunks_mut(channels) {
+            // Running dry just means silence until the input catches up
+            let sample = self.consumer.try_pop().unwrap_or(0.0) * gain;
+            for out in frame {
+                *out = (*out + sample).clamp(-1.0, 1.0);
+            }
+        }
+    }
+}
+
+/// Create the shared counters and ring buffer for a monitor
+fn monitor_channel(gain: f32) -> (ringbuf::HeapProd<f32>, MonitorTap, Arc<MonitorShared>) {
+    let shared = Arc::new(MonitorShared::new(gain));
+    let (producer, consumer) = HeapRb::<f32>::new(MONITOR_RING_FRAMES).split();
+    let tap = MonitorTap {
+        consumer,
+        shared: Arc::clone(&shared),
+    };
+    (producer, tap, shared)
+}
+
+/// Running input stream (dropping it stops monitoring)
+pub struct InputMonitor {
+    _stream: StreamWrapper,
+    shared: Arc<MonitorShared>,
+    device_name: String,
+    /// Device name as requested (None = system default input)
+    requested_device: Option<String>,
+    channels: u16,
+    channel: Option<u16>,
+    sample_rate: u32,
+}
+
+impl InputMonitor {
+    /// Open an input device at the output's sample rate and start capturing.
+    /// The returned tap goes to the output callback.
+    pub fn start(
+        host: &cpal::Host,
+        device_name: Option<&str>,
+        channel: Option<u16>,
+        gain: f32,
+        sample_rate: u32,
+        buffer_size: Option<u32>,
+    ) -> Result<(Self, MonitorTap)> {
+        let device = match device_name {
+            Some(name) => host.input_devices()?
+                .find(|d| d.name().map(|n| n == name).unwrap_or(false))
+                .with_context(|| format!("Input device '{}' not found", name))?,
+            None => host.default_input_device().context("No audio input device available")?,
+        };
+        let name = device.name()?;
+
+        // The output doesn't resample input, so it has to run at the output rate
+        let default_channels = device.default_input_config().map(|config| config.channels()).ok();
+        let ranges: Vec<_> = device.supported_input_configs()?
+            .filter(|range| range.sample_format() == cpal::SampleFormat::F32)
+            .filter(|range| range.min_sample_rate().0 <= sample_rate && range.max_sample_rate().0 >= sample_rate)
+            .collect();
+        let range = ranges.iter()
+            .find(|range| Some(range.channels()) == default_channels)
+            .or_else(|| ranges.first())
+            .with_context(|| format!("Input device '{}' doesn't support {} Hz", name, sample_rate))?;
+        let channels = range.channels();
+        if let Some(channel) = channel.filter(|&channel| channel >= channels) {
+            anyhow::bail!("Input device '{}' has {} channel(s), can't monitor channel {}", name, channels, channel + 1);
+        }
+
+        let config = cpal::StreamConfig {
+            channels,
+            sample_rate: cpal::SampleRate(sample_rate),
+            buffer_size: match buffer_size {
+                Some(size) => cpal::BufferSize::Fixed(size),
+                None => cpal::BufferSize::Default,
+            },
+        };
+
+        let (mut producer, tap, shared) = monitor_channel(gain);
+        let input_shared = Arc::clone(&shared);
+        let error_shared = Arc::clone(&shared);
+        let frame_channels = channels as usize;
+        let stream = device.build_input_stream(
+            &config,
+            move |data: &[f32], _: &cpal::InputCallbackInfo| {
+                let mut peak = 0.0f32;
+                for frame in data.chunks(frame_channels) {
+                    let sample = input_sample(frame, channel);
+                    peak = peak.max(sample.abs());
+                    // A full ring means the output stalled; drop rather than block
+                    let _ = producer.try_push(sample);
+                }
+                input_shared.record_peak(peak);
+                input_shared.input_frames.store(data.len() / frame_channels, Ordering::Relaxed);
+            },
+            move |err| {
+                log::error!("Audio input stream error: {}", err);
+                error_shared.stream_error.store(true, Ordering::Relaxed);
+            },
+            None,
+        )?;
+        stream.play()?;
+
+        log::info!("🎤 Input monitoring started: {} ({} ch, {}Hz)", name, channels, sample_rate);
+        Ok((
+            Self {
+                _stream: StreamWrapper(stream),
+                shared,
+                device_name: name,
+                requested_device: device_name.map(|name| name.to_string()),
+                channels,
+                channel,
+                sample_rate,
+            },
+            tap,
+        ))
+    }
+
+    pub fn set_gain(&self, gain: f32) -> f32 {
+        let gain = clamp_monitor_gain(gain);
+        self.shared.gain_bits.store(gain.to_bits(), Ordering::Relaxed);
+        gain
+    }
+
+    pub fn gain(&self) -> f32 {
+        self.shared.gain()
+    }
+
+    /// Device name as requested when monitoring started (None = default input)
+    pub fn requested_device(&self) -> Option<&str> {
+        self.requested_device.as_deref()
+    }
+
+    pub fn channel(&self) -> Option<u16> {
+        self.channel
+    }
+
+    /// Current status (resets the peak meter)
+    pub fn stats(&self) -> InputMonitorStats {
+        let rate = self.sample_rate.max(1) as f32;
+        let frames = self.shared.input_frames.load(Ordering::Relaxed)
+            + self.shared.buffered_frames.load(Ordering::Relaxed)
+            + self.shared.output_frames.load(Ordering::Relaxed);
+        InputMonitorStats {
+            active: true,
+            device: Some(self.device_name.clone()),
+            channels: self.channels,
+            channel: self.channel,
+            gain: self.gain(),
+            input_peak: f32::from_bits(self.shared.peak_bits.swap(0, Ordering::Relaxed)),
+            estimated_latency_ms: frames as f32 / rate * 1000.0,
+            trimmed_ms: self.shared.trimmed_frames.load(Ordering::Relaxed) as f32 / rate * 1000.0,
+            stream_error: self.shared.stream_error.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// List input devices of a host
+pub fn list_input_devices(host: &cpal::Host) -> Result<Vec<crate::AudioDeviceInfo>> {
+    let default_name = host.default_input_device().and_then(|d| d.name().ok());
+    let devices = host.input_devices()?
+        .filter_map(|device| device.name().ok())
+        .map(|name| crate::AudioDeviceInfo {
+            is_default: default_name.as_deref() == Some(name.as_str()),
+            name,
+        })
+        .collect();
+    Ok(devices)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_input_sample_channel_selection() {
+        let frame = [0.2, 0.6];
+        assert_eq!(input_sample(&frame, Some(1)), 0.6);
+        assert!((input_sample(&frame, None) - 0.4).abs() < 1e-6);
+        // Out-of-range channel falls back to the mix
+        assert!((input_sample(&frame, Some(5)) - 0.4).abs() < 1e-6);
+        assert_eq!(input_sample(&[], None), 0.0);
+    }
+
+    #[test]
+    fn test_tap_mixes_with_gain_and_trims_backlog() {
+        let (mut producer, mut tap, shared) = monitor_channel(2.0);
+        producer.try_push(0.25).unwrap();
+        let mut data = [0.1f32; 4];
+        tap.mix_into(&mut data, 2);
+        // First frame gets the input on both channels, second runs dry
+        assert_eq!(data, [0.6, 0.6, 0.1, 0.1]);
+
+        // A backlog far past one buffer is trimmed back to buffer + slack
+        for _ in 0..2000 {
+            producer.try_push(0.0).unwrap();
+        }
+        let mut data = [0.0f32; 2 * 64];
+        tap.mix_into(&mut data, 2);
+        assert_eq!(shared.trimmed_frames.load(Ordering::Relaxed), (2000 - 64 - MONITOR_SLACK_FRAMES) as u64);
+        assert_eq!(shared.buffered_frames.load(Ordering::Relaxed), 64 + MONITOR_SLACK_FRAMES);
+    }
+}
@@ -0,0 +1,168 @@
+//! Offline instrument previews
+//! Renders a short strummed chord with a private engine (never touches the live
+//! RT stream) and encodes it as a small WAV clip for the instrument picker.
+
+use anyhow::Result;
+use mapping::MusicEvent;
+use crate::engine::AudioEngine;
+use crate::synth::InstrumentType as SynthInstrumentType;
+
+const RENDER_BLOCK_FRAMES: usize = 256;
+
+/// Instrument to preview
+#[derive(Debug, Clone)]
+pub enum PreviewSource {
+    Virtual(SynthInstrumentType),
+    /// Sample instrument folder or SFZ file
+    Sampler(std::path::PathBuf),
+    #[cfg(feature = "soundfont")]
+    SoundFont(std::path::PathBuf),
+}
+
+/// Preview rendering options
+#[derive(Debug, Clone)]
+pub struct PreviewOptions {
+    pub sample_rate: u32,
+    /// MIDI notes strummed low to high
+    pub notes: Vec<u8>,
+    /// Delay between successive strings (ms)
+    pub strum_delay_ms: f32,
+    /// How long the chord is held before release (seconds)
+    pub hold_secs: f32,
+    /// Release tail rendered after note off (seconds)
+    pub tail_secs: f32,
+}
+
+impl Default for PreviewOptions {
+    fn default() -> Self {
+        Self {
+            sample_rate: 24000,
+            notes: vec![40, 47, 52, 56, 59, 64], // Open E major
+            strum_delay_ms: 15.0,
+            hold_secs: 1.0,
+            tail_secs: 0.5,
+        }
+    }
+}
+
+/// Render a strummed chord preview, returning mono samples
+pub fn render_preview(source: &PreviewSource, options: &PreviewOptions) -> Result<Vec<f32>> {
+    let sample_rate = options.sample_rate.max(8000);
+    let mut engine = AudioEngine::new(sample_rate);
+    load_source(&mut engine, source)?;
+
+    let strum_frames = (options.strum_delay_ms.max(0.0) / 1000.0 * sample_rate as f32) as usize;
+    let hold_frames = (options.hold_secs.max(0.0) * sample_rate as f32) as usize;
+    let total_frames = hold_frames + (options.tail_secs.max(0.0) * sample_rate as f32) as usize;
+
+    let mut output = Vec::with_capacity(total_frames);
+    let mut block = vec![0.0f32; RENDER_BLOCK_FRAMES * 2];
+    let mut next_note = 0;
+    let mut released = false;
+    let mut frame = 0;
+
+    while frame < total_frames {
+        // Trigger strings whose strum offset falls at or before this block
+        while next_note < options.notes.len() && next_note * strum_frames <= frame {
+            engine.handle_event(MusicEvent::NoteOn { note: options.notes[next_note], velocity: 100 });
+            next_note += 1;
+        }
+        if !released && frame >= hold_frames {
+            for &note in &options.notes {
+                engine.handle_event(MusicEvent::NoteOff { note });
+            }
+            released = true;
+        }
+
+        // Stop at the next trigger point so strum timing stays block-accurate
+        let mut frames = RENDER_BLOCK_FRAMES.min(total_frames - frame);
+        if next_note < options.notes.len() {
+            frames = frames.min((next_note * strum_frames).saturating_sub(frame).max(1));
+        }
+        if !released {
+            frames = frames.min(hold_frames.saturating_sub(frame).max(1));
+        }
+
+        let buffer = &mut block[..frames * 2];
+        engine.render(buffer);
+        output.extend(buffer.chunks(2).map(|lr| (lr[0] + lr[1]) * 0.5));
+        frame += frames;
+    }
+
+    Ok(output)
+}
+
+/// Switch an offline engine to the instrument
+pub(crate) fn load_source(engine: &mut AudioEngine, source: &PreviewSource) -> Result<()> {
+    match source {
+        PreviewSource::Virtual(instrument) => engine.set_virtual_instrument(*instrument)?,
+        PreviewSource::Sampler(path) => engine.load_sampler(crate::sampler::load_sample_instrument(path)?),
+        #[cfg(feature = "soundfont")]
+        PreviewSource::SoundFont(path) => engine.load_soundfont(path)?,
+    }
+    Ok(())
+}
+
+/// Encode mono f32 samples as a 16-bit PCM WAV file
+pub fn encode_wav(samples: &[f32], sample_rate: u32) -> Vec<u8> {
+    encode_wav_channels(samples, 1, sample_rate)
+}
+
+/// Encode interleaved f32 samples as a 16-bit PCM WAV file
+pub fn encode_wav_channels(samples: &[f32], channels: u16, sample_rate: u32) -> Vec<u8> {
+    let bits_per_sample: u16 = 16;
+    let block_align = channels * bits_per_sample / 8;
+    let byte_rate = sample_rate * block_align as u32;
+    let data_len = (samples.len() * block_align as usize) as u32;
+
+    let mut wav = Vec::with_capacity(44 + data_len as usize);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + data_len).to_le_bytes());
+    wav.extend_from_slice(b"WAVE");
+    wav.extend_from_slice(b"fmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&channels.to_le_bytes());
+    wav.extend_from_slice(&sample_rate.to_le_bytes());
+    wav.extend_from_slice(&byte_rate.to_le_bytes());
+    wav.extend_from_slice(&block_align.to_le_bytes());
+    wav.extend_from_slice(&bits_per_sample.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&data_len.to_le_bytes());
+    for sample in samples {
+        let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+        wav.extend_from_slice(&value.to_le_bytes());
+    }
+    wav
+}
+
+/// Render a preview and encode it as WAV in one step
+pub fn render_preview_wav(source: &PreviewSource, options: &PreviewOptions) -> Result<Vec<u8>> {
+    let samples = render_preview(source, options)?;
+    Ok(encode_wav(&samples, options.sample_rate.max(8000)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_preview_length() {
+        let options = PreviewOptions::default();
+        let samples = render_preview(&PreviewSource::Virtual(SynthInstrumentType::CleanElectricGuitar), &options).unwrap();
+        let expected = ((options.hold_secs + options.tail_secs) * options.sample_rate as f32) as usize;
+        assert_eq!(samples.len(), expected);
+        assert!(samples.iter().any(|s| s.abs() > 0.0));
+    }
+
+    #[test]
+    fn test_encode_wav_header() {
+        let wav = encode_wav(&[0.0, 1.0, -1.0], 24000);
+        assert_eq!(&wav[0..4], b"RIFF");
+        assert_eq!(&wav[8..12], b"WAVE");
+        assert_eq!(u32::from_le_bytes(wav[24..28].try_into().unwrap()), 24000);
+        assert_eq!(u32::from_le_bytes(wav[40..44].try_into().unwrap()), 6);
+        assert_eq!(wav.len(), 44 + 6);
+        assert_eq!(i16::from_le_bytes([wav[46], wav[47]]), i16::MAX);
+    }
+}
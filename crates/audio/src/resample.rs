@@ -0,0 +1,121 @@
+//! Sample-rate negotiation and resampling
+//! The synth prefers to render at 48kHz. Devices that can't open a stream at
+//! that rate get the nearest rate they support, and the callback resamples the
+//! synth output to it with linear interpolation.
+
+/// Rate the synth engine renders at
+pub const PREFERRED_SAMPLE_RATE: u32 = 48000;
+
+/// Frames rendered per engine call while resampling (keeps added latency low)
+const RESAMPLE_BLOCK_FRAMES: usize = 64;
+const CHANNELS: usize = 2;
+
+/// Pick the supported rate closest to `preferred` from (min, max) rate ranges
+pub fn nearest_supported_rate(ranges: &[(u32, u32)], preferred: u32) -> Option<u32> {
+    ranges
+        .iter()
+        .map(|&(min, max)| preferred.clamp(min, max.max(min)))
+        .min_by_key(|&rate| (rate.abs_diff(preferred), std::cmp::Reverse(rate)))
+}
+
+/// Streaming linear resampler for interleaved stereo. Pulls source frames from
+/// a render callback in small blocks; buffers are allocated up front so
+/// `process` is RT-safe.
+pub struct StereoResampler {
+    /// Source frames per output frame
+    step: f64,
+    /// Position between `previous` and `next` (0.0-1.0)
+    position: f64,
+    previous: [f32; CHANNELS],
+    next: [f32; CHANNELS],
+    source: Vec<f32>,
+    read: usize,
+    filled: usize,
+}
+
+impl StereoResampler {
+    pub fn new(source_rate: u32, output_rate: u32) -> Self {
+        Self {
+            step: source_rate as f64 / output_rate as f64,
+            // Start past the end so the first output frame pulls in real samples
+            position: 2.0,
+            previous: [0.0; CHANNELS],
+            next: [0.0; CHANNELS],
+            source: vec![0.0; RESAMPLE_BLOCK_FRAMES * CHANNELS],
+            read: 0,
+            filled: 0,
+        }
+    }
+
+    fn next_source_frame(&mut self, render: &mut impl FnMut(&mut [f32])) -> [f32; CHANNELS] {
+        if self.read >= self.filled {
+            self.source.fill(0.0);
+            render(&mut self.source);
+            self.read = 0;
+            self.filled = RESAMPLE_BLOCK_FRAMES;
+        }
+        let offset = self.read * CHANNELS;
+        self.read += 1;
+        [self.source[offset], self.source[offset + 1]]
+    }
+
+    /// Fill `output` (interleaved stereo at the output rate) from `render`
+    /// (interleaved stereo at the source rate)
+    pub fn process(&mut self, output: &mut [f32], mut render: impl FnMut(&mut [f32])) {
+        for frame in output.chunks_exact_mut(CHANNELS) {
+            while self.position >= 1.0 {
+                self.previous = self.next;
+                self.next = self.next_source_frame(&mut render);
+                self.position -= 1.0;
+            }
+            let t = self.position as f32;
+            for (channel, sample) in frame.iter_mut().enumerate() {
+                *sample = self.previous[channel] + (self.next[channel] - self.previous[channel]) * t;
+            }
+            self.position += self.step;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nearest_supported_rate() {
+        assert_eq!(nearest_supported_rate(&[(44100, 44100), (8000, 96000)], 48000), Some(48000));
+        assert_eq!(nearest_supported_rate(&[(44100, 44100), (88200, 96000)], 48000), Some(44100));
+        assert_eq!(nearest_supported_rate(&[(50000, 50000), (46000, 46000)], 48000), Some(50000));
+        assert_eq!(nearest_supported_rate(&[], 48000), None);
+    }
+
+    #[test]
+    fn test_resampler_output_length_and_ramp() {
+        // A ramp rendered at 48kHz stays a (denser) ramp at 96kHz
+        let mut counter = 0.0;
+        let mut render = |buffer: &mut [f32]| {
+            for frame in buffer.chunks_exact_mut(2) {
+                frame[0] = counter;
+                frame[1] = -counter;
+                counter += 1.0;
+            }
+        };
+        let mut resampler = StereoResampler::new(48000, 96000);
+        let mut output = vec![0.0; 200 * 2];
+        resampler.process(&mut output, &mut render);
+        for pair in output.chunks_exact(4).skip(1) {
+            assert!((pair[2] - pair[0] - 0.5).abs() < 1e-4);
+            assert_eq!(pair[1], -pair[0]);
+        }
+    }
+
+    #[test]
+    fn test_resampler_consumes_source_at_ratio() {
+        let mut frames_rendered = 0;
+        let mut resampler = StereoResampler::new(48000, 44100);
+        let mut output = vec![0.0; 44100 * 2];
+        resampler.process(&mut output, |buffer: &mut [f32]| frames_rendered += buffer.len() / 2);
+        // One second out needs one second of source (plus at most a block)
+        assert!((48000..=48000 + RESAMPLE_BLOCK_FRAMES * 2).contains(&frames_rendered));
+    }
+}
@@ -0,0 +1,166 @@
+//! Rock-out boost
+//! An effect on the guitars that tilting the neck up switches on: an overdrive
+//! boost, or a resonant filter sweeping up and down like an auto-wah. It is
+//! engaged by `ROCK_OUT_CC` (so it lands with the input that caused it) and
+//! fades in and out over a few milliseconds instead of clicking.
+
+use serde::{Deserialize, Serialize};
+
+use crate::denormal::flush_denormal;
+
+/// Seconds to fade the effect fully in or out
+const FADE_SECONDS: f32 = 0.03;
+/// Drive of the overdrive boost at full level
+const OVERDRIVE_GAIN: f32 = 8.0;
+/// Sweep range and speed of the filter
+const SWEEP_LOW_HZ: f32 = 300.0;
+const SWEEP_HIGH_HZ: f32 = 3500.0;
+const SWEEP_RATE_HZ: f32 = 0.8;
+/// Filter damping (lower = more resonant)
+const SWEEP_DAMPING: f32 = 0.4;
+
+/// Effect the tilt switches on
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RockOutEffect {
+    FilterSweep,
+    Overdrive,
+}
+
+/// The boost on the guitar mix (RT-safe)
+#[derive(Debug, Clone)]
+pub struct RockOut {
+    sample_rate: u32,
+    effect: Option<RockOutEffect>,
+    engaged: bool,
+    /// Effect level, fading towards 1.0 while engaged and 0.0 when not
+    level: f32,
+    sweep_phase: f32,
+    /// State-variable filter state per channel (low-pass, band-pass)
+    filter: [(f32, f32); 2],
+}
+
+impl RockOut {
+    pub fn new(sample_rate: u32) -> Self {
+        Self { sample_rate, effect: None, engaged: false, level: 0.0, sweep_phase: 0.0, filter: [(0.0, 0.0); 2] }
+    }
+
+    /// Choose the effect (None = tilting does nothing here)
+    pub fn set_effect(&mut self, effect: Option<RockOutEffect>) {
+        if effect != self.effect {
+            self.effect = effect;
+            self.level = 0.0;
+            self.filter = [(0.0, 0.0); 2];
+        }
+    }
+
+    pub fn effect(&self) -> Option<RockOutEffect> {
+        self.effect
+    }
+
+    pub fn set_engaged(&mut self, engaged: bool) {
+        if engaged && !self.engaged {
+            // Every sweep starts from the bottom
+            self.sweep_phase = 0.0;
+        }
+        self.engaged = engaged;
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.engaged
+    }
+
+    /// Apply the effect to an interleaved stereo buffer
+    pub fn process(&mut self, buffer: &mut [f32]) {
+        let Some(effect) = self.effect else { return };
+        if !self.engaged && self.level == 0.0 {
+            return;
+        }
+        let sample_rate = self.sample_rate.max(1) as f32;
+        let fade_step = 1.0 / (FADE_SECONDS * sample_rate);
+        let target = if self.engaged { 1.0 } else { 0.0 };
+        for frame in buffer.chunks_exact_mut(2) {
+            self.level = if self.level < target {
+                (self.level + fade_step).min(target)
+            } else {
+                (self.level - fade_step).max(target)
+            };
+            match effect {
+                RockOutEffect::Overdrive => {
+                    let drive = 1.0 + OVERDRIVE_GAIN;
+                    for sample in frame.iter_mut() {
+                        let driven = (*sample * drive).tanh() / drive.tanh();
+                        *sample = (*sample + (driven - *sample) * self.level).clamp(-1.0, 1.0);
+                    }
+                }
+                RockOutEffect::FilterSweep => {
+                    // Triangle LFO, swept on an exponential (musical) scale
+                    let sweep = 1.0 - (2.0 * self.sweep_phase - 1.0).abs();
+                    self.sweep_phase = (self.sweep_phase + SWEEP_RATE_HZ / sample_rate).fract();
+                    let cutoff = (SWEEP_LOW_HZ * (SWEEP_HIGH_HZ / SWEEP_LOW_HZ).powf(sweep)).min(sample_rate / 6.0);
+                    let f = 2.0 * (std::f32::consts::PI * cutoff / sample_rate).sin();
+                    for (sample, (low, band)) in frame.iter_mut().zip(self.filter.iter_mut()) {
+                        *low = flush_denormal(*low + f * *band);
+                        let high = *sample - *low - SWEEP_DAMPING * *band;
+                        *band = flush_denormal(*band + f * high);
+                        *sample = (*sample + (*low - *sample) * self.level).clamp(-1.0, 1.0);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sine(frequency: f32, amplitude: f32, frames: usize) -> Vec<f32> {
+        (0..frames)
+            .flat_map(|frame| {
+                let sample = amplitude * (std::f32::consts::TAU * frequency * frame as f32 / 48000.0).sin();
+                [sample, sample]
+            })
+            .collect()
+    }
+
+    fn peak(buffer: &[f32]) -> f32 {
+        buffer.iter().fold(0.0, |peak, sample| peak.max(sample.abs()))
+    }
+
+    #[test]
+    fn test_overdrive_only_while_engaged() {
+        let mut rock_out = RockOut::new(48000);
+        rock_out.set_effect(Some(RockOutEffect::Overdrive));
+        let mut buffer = sine(220.0, 0.2, 4800);
+        rock_out.process(&mut buffer);
+        assert_eq!(buffer, sine(220.0, 0.2, 4800));
+
+        rock_out.set_engaged(true);
+        rock_out.process(&mut buffer);
+        // Past the fade, the quiet sine is driven close to full scale
+        assert!(peak(&buffer[2 * 2400..]) > 0.8);
+
+        // Disengaged, it fades back out to the dry signal
+        rock_out.set_engaged(false);
+        let mut buffer = sine(220.0, 0.2, 4800);
+        rock_out.process(&mut buffer);
+        assert!((peak(&buffer[2 * 2400..]) - 0.2).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_filter_sweep_damps_highs() {
+        let mut rock_out = RockOut::new(48000);
+        rock_out.set_effect(Some(RockOutEffect::FilterSweep));
+        rock_out.set_engaged(true);
+        // The sweep starts at its lowest cutoff, far under 6 kHz
+        let mut buffer = sine(6000.0, 0.5, 9600);
+        rock_out.process(&mut buffer);
+        assert!(peak(&buffer[2 * 4800..]) < 0.1);
+
+        rock_out.set_effect(None);
+        let mut buffer = sine(6000.0, 0.5, 960);
+        rock_out.process(&mut buffer);
+        assert_eq!(buffer, sine(6000.0, 0.5, 960));
+    }
+}
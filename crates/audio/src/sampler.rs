@@ -0,0 +1,631 @@
+//! Sample-based instrument playback
+//! Zones map key/velocity ranges to samples, with round-robin groups so repeated
+//! strums of the same chord don't sound machine-gun identical.
+//! Sample instruments are folders of WAV files named `<root key>[_v<top velocity>][_rr<n>]`,
+//! or SFZ files (see `sfz`).
+
+use std::path::Path;
+use std::sync::Arc;
+use anyhow::{Context, Result};
+
+const MAX_SAMPLER_VOICES: usize = 32;
+const DEFAULT_RELEASE_TIME: f32 = 0.25; // 250ms release
+
+/// Mono sample data shared between zones and voices
+#[derive(Debug, Clone)]
+pub struct SampleData {
+    pub frames: Vec<f32>,
+    pub sample_rate: u32,
+}
+
+/// How zones inside a round-robin group are picked
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoundRobinMode {
+    /// Cycle through the group in order (SFZ `seq_position`)
+    #[default]
+    Sequential,
+    /// Pick a random zone from the group, never the same one twice in a row
+    Random,
+}
+
+/// How a zone's sample loops (SFZ `loop_mode`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LoopMode {
+    /// Play the sample once, stopping early on release
+    #[default]
+    NoLoop,
+    /// Play the whole sample, ignoring note off
+    OneShot,
+    /// Loop between the loop points, through the release too
+    Continuous,
+    /// Loop while the note is held, play out the rest on release
+    Sustain,
+}
+
+/// A sample mapped onto a key and velocity range
+#[derive(Debug, Clone)]
+pub struct SampleZone {
+    pub sample: Arc<SampleData>,
+    pub key_low: u8,
+    pub key_high: u8,
+    pub velocity_low: u8,
+    pub velocity_high: u8,
+    /// Key at which the sample plays back unpitched
+    pub root_key: u8,
+    /// Per-layer gain in decibels
+    pub gain_db: f32,
+    /// Per-layer pitch offset in cents
+    pub tune_cents: f32,
+    /// Release time in seconds
+    pub release_time: f32,
+    /// Round-robin group length (1 = no round-robin)
+    pub seq_length: u32,
+    /// 1-based position inside the round-robin group
+    pub seq_position: u32,
+    pub loop_mode: LoopMode,
+    /// Loop points in frames (end inclusive)
+    pub loop_start: usize,
+    pub loop_end: usize,
+}
+
+impl SampleZone {
+    /// Create a zone covering every key and velocity
+    pub fn new(sample: Arc<SampleData>, root_key: u8) -> Self {
+        Self {
+            sample,
+            key_low: 0,
+            key_high: 127,
+            velocity_low: 0,
+            velocity_high: 127,
+            root_key,
+            gain_db: 0.0,
+            tune_cents: 0.0,
+            release_time: DEFAULT_RELEASE_TIME,
+            seq_length: 1,
+            seq_position: 1,
+            loop_mode: LoopMode::NoLoop,
+            loop_start: 0,
+            loop_end: 0,
+        }
+    }
+
+    pub fn matches(&self, note: u8, velocity: u8) -> bool {
+        (self.key_low..=self.key_high).contains(&note)
+            && (self.velocity_low..=self.velocity_high).contains(&velocity)
+    }
+
+    /// Linear gain from `gain_db`
+    pub fn gain(&self) -> f32 {
+        10.0_f32.powf(self.gain_db / 20.0)
+    }
+
+    /// Loop points the voice wraps at, while it is held or not
+    fn active_loop(&self, held: bool) -> Option<(usize, usize)> {
+        let looping = match self.loop_mode {
+            LoopMode::Continuous => true,
+            LoopMode::Sustain => held,
+            LoopMode::NoLoop | LoopMode::OneShot => false,
+        };
+        let end = self.loop_end.min(self.sample.frames.len().saturating_sub(1));
+        (looping && self.loop_start < end).then_some((self.loop_start, end))
+    }
+
+    /// Playback rate for a note, including tuning and sample rate conversion
+    pub fn playback_rate(&self, note: u8, output_sample_rate: u32) -> f64 {
+        let semitones = note as f64 - self.root_key as f64 + self.tune_cents as f64 / 100.0;
+        let resample = self.sample.sample_rate as f64 / output_sample_rate.max(1) as f64;
+        2.0_f64.powf(semitones / 12.0) * resample
+    }
+}
+
+/// Picks zones for incoming notes, tracking round-robin position per key
+#[derive(Debug, Clone)]
+pub struct ZoneSelector {
+    mode: RoundRobinMode,
+    counters: [u32; 128],
+}
+
+impl ZoneSelector {
+    pub fn new(mode: RoundRobinMode) -> Self {
+        Self { mode, counters: [0; 128] }
+    }
+
+    pub fn set_mode(&mut self, mode: RoundRobinMode) {
+        self.mode = mode;
+    }
+
+    /// Select the zone index for a note (RT-safe, no allocation).
+    /// Velocity layers are resolved first, then round-robin within the layer.
+    pub fn select(&mut self, zones: &[SampleZone], note: u8, velocity: u8) -> Option<usize> {
+        let note = note.min(127);
+        let seq_length = zones.iter()
+            .filter(|z| z.matches(note, velocity))
+            .map(|z| z.seq_length.max(1))
+            .max()?;
+
+        if seq_length <= 1 {
+            return zones.iter().position(|z| z.matches(note, velocity));
+        }
+
+        let counter = &mut self.counters[note as usize];
+        let position = match self.mode {
+            RoundRobinMode::Sequential => *counter % seq_length + 1,
+            RoundRobinMode::Random => {
+                // Shift by 1..seq_length so the previous zone is never repeated
+                let offset = fastrand::u32(1..seq_length);
+                (counter.saturating_sub(1) + offset) % seq_length + 1
+            }
+        };
+        *counter = position;
+
+        zones.iter()
+            .position(|z| z.matches(note, velocity) && z.seq_position == position)
+            .or_else(|| zones.iter().position(|z| z.matches(note, velocity)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum SamplerStage {
+    Off,
+    Playing,
+    Release,
+}
+
+/// A single playing sample
+#[derive(Debug, Clone)]
+struct SamplerVoice {
+    zone: usize,
+    note: u8,
+    position: f64,
+    rate: f64,
+    gain: f32,
+    envelope: f32,
+    release_step: f32,
+    stage: SamplerStage,
+}
+
+impl SamplerVoice {
+    fn new() -> Self {
+        Self {
+            zone: 0,
+            note: 0,
+            position: 0.0,
+            rate: 1.0,
+            gain: 0.0,
+            envelope: 0.0,
+            release_step: 0.0,
+            stage: SamplerStage::Off,
+        }
+    }
+
+    fn is_active(&self) -> bool {
+        self.stage != SamplerStage::Off
+    }
+
+    fn render_sample(&mut self, zone: &SampleZone, pitch_ratio: f64) -> f32 {
+        let frames = &zone.sample.frames;
+        let active_loop = zone.active_loop(self.stage == SamplerStage::Playing);
+        let index = self.position as usize;
+        // The frame after the loop end is the loop start
+        let next = match active_loop {
+            Some((start, end)) if index >= end => start,
+            _ => index + 1,
+        };
+        if next >= frames.len() || index >= frames.len() {
+            self.stage = SamplerStage::Off;
+            return 0.0;
+        }
+
+        // Linear interpolation between neighbouring frames
+        let frac = (self.position - index as f64) as f32;
+        let value = frames[index] * (1.0 - frac) + frames[next] * frac;
+        self.position += self.rate * pitch_ratio;
+        if let Some((start, end)) = active_loop {
+            let length = (end - start + 1) as f64;
+            while self.position >= end as f64 + 1.0 {
+                self.position -= length;
+            }
+        }
+
+        if self.stage == SamplerStage::Release {
+            self.envelope -= self.release_step;
+            if self.envelope <= 0.0 {
+                self.envelope = 0.0;
+                self.stage = SamplerStage::Off;
+            }
+        }
+
+        value * self.gain * self.envelope
+    }
+}
+
+/// Polyphonic sample player
+pub struct Sampler {
+    zones: Vec<SampleZone>,
+    selector: ZoneSelector,
+    voices: Vec<SamplerVoice>,
+    sample_rate: u32,
+    pitch_bend: f32, // In semitones (-2 to +2)
+    tuning_cents: f32,
+}
+
+impl Sampler {
+    pub fn new(sample_rate: u32, zones: Vec<SampleZone>) -> Self {
+        Self {
+            zones,
+            selector: ZoneSelector::new(RoundRobinMode::default()),
+            voices: vec![SamplerVoice::new(); MAX_SAMPLER_VOICES],
+            sample_rate,
+            pitch_bend: 0.0,
+            tuning_cents: 0.0,
+        }
+    }
+
+    pub fn zones(&self) -> &[SampleZone] {
+        &self.zones
+    }
+
+    pub fn set_round_robin_mode(&mut self, mode: RoundRobinMode) {
+        self.selector.set_mode(mode);
+    }
+
+    pub fn note_on(&mut self, note: u8, velocity: u8) {
+        let Some(zone_index) = self.selector.select(&self.zones, note, velocity) else {
+            return;
+        };
+        let zone = &self.zones[zone_index];
+        let velocity_gain = velocity as f32 / 127.0;
+        let release_step = 1.0 / (zone.release_time.max(0.001) * self.sample_rate as f32);
+
+        let voice_index = self.voices.iter()
+            .position(|v| !v.is_active())
+            .unwrap_or(0); // Voice stealing: take the first voice
+        let voice = &mut self.voices[voice_index];
+        voice.zone = zone_index;
+        voice.note = note;
+        voice.position = 0.0;
+        voice.rate = zone.playback_rate(note, self.sample_rate);
+        voice.gain = zone.gain() * velocity_gain;
+        voice.envelope = 1.0;
+        voice.release_step = release_step;
+        voice.stage = SamplerStage::Playing;
+    }
+
+    pub fn note_off(&mut self, note: u8) {
+        for voice in &mut self.voices {
+            // One-shot samples play out
+            if voice.note == note && voice.stage == SamplerStage::Playing
+                && self.zones[voice.zone].loop_mode != LoopMode::OneShot
+            {
+                voice.stage = SamplerStage::Release;
+            }
+        }
+    }
+
+    pub fn all_notes_off(&mut self) {
+        for voice in &mut self.voices {
+            if voice.stage == SamplerStage::Playing {
+                voice.stage = SamplerStage::Release;
+            }
+        }
+    }
+
+    pub fn set_pitch_bend(&mut self, amount: i16) {
+        // Convert -8192 to +8191 to -2 to +2 semitones
+        self.pitch_bend = (amount as f32 / 8192.0) * 2.0;
+    }
+
+    /// Master tuning offset in cents (A4 reference away from 440 Hz)
+    pub fn set_tuning_cents(&mut self, cents: f32) {
+        self.tuning_cents = cents;
+    }
+
+    /// Render interleaved stereo audio (RT-safe)
+    pub fn render(&mut self, buffer: &mut [f32]) {
+        for sample in buffer.iter_mut() {
+            *sample = 0.0;
+        }
+
+        let semitones = self.pitch_bend as f64 + self.tuning_cents as f64 / 100.0;
+        let pitch_ratio = 2.0_f64.powf(semitones / 12.0);
+        for voice in &mut self.voices {
+            if !voice.is_active() {
+                continue;
+            }
+            let zone = &self.zones[voice.zone];
+            for frame in buffer.chunks_mut(2) {
+                let value = voice.render_sample(zone, pitch_ratio);
+                for out in frame.iter_mut() {
+                    *out += value;
+                }
+                if !voice.is_active() {
+                    break;
+                }
+            }
+        }
+
+        for sample in buffer.iter_mut() {
+            *sample = sample.clamp(-1.0, 1.0);
+        }
+    }
+
+    pub fn active_voice_count(&self) -> usize {
+        self.voices.iter().filter(|v| v.is_active()).count()
+    }
+}
+
+/// Decode a PCM (16/24/32-bit) or float WAV file, mixing channels down to mono
+pub fn decode_wav(bytes: &[u8]) -> Result<SampleData> {
+    if bytes.len() < 12 || &bytes[0..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+        anyhow::bail!("Not a WAV file");
+    }
+
+    let mut format = None;
+    let mut data = None;
+    let mut offset = 12;
+    while offset + 8 <= bytes.len() {
+        let id = &bytes[offset..offset + 4];
+        let len = u32::from_le_bytes(bytes[offset + 4..offset + 8].try_into()?) as usize;
+        let body = &bytes[offset + 8..(offset + 8 + len).min(bytes.len())];
+        match id {
+            b"fmt " if body.len() >= 16 => {
+                let mut tag = u16::from_le_bytes([body[0], body[1]]);
+                // WAVE_FORMAT_EXTENSIBLE carries the real format in its subformat GUID
+                if tag == 0xFFFE && body.len() >= 26 {
+                    tag = u16::from_le_bytes([body[24], body[25]]);
+                }
+                let channels = u16::from_le_bytes([body[2], body[3]]).max(1) as usize;
+                let sample_rate = u32::from_le_bytes(body[4..8].try_into()?);
+                let bits = u16::from_le_bytes([body[14], body[15]]);
+                format = Some((tag, channels, sample_rate, bits));
+            }
+            b"data" => data = Some(body),
+            _ => {}
+        }
+        // Chunks are padded to an even length
+        offset += 8 + len + (len & 1);
+    }
+
+    let (tag, channels, sample_rate, bits) = format.context("WAV file has no fmt chunk")?;
+    let data = data.context("WAV file has no data chunk")?;
+    let decode: fn(&[u8]) -> f32 = match (tag == 3, bits) {
+        (false, 16) => |b| i16::from_le_bytes([b[0], b[1]]) as f32 / 32768.0,
+        (false, 24) => |b| (i32::from_le_bytes([0, b[0], b[1], b[2]]) >> 8) as f32 / 8_388_608.0,
+        (false, 32) => |b| i32::from_le_bytes([b[0], b[1], b[2], b[3]]) as f32 / 2_147_483_648.0,
+        (true, 32) => |b| f32::from_le_bytes([b[0], b[1], b[2], b[3]]),
+        _ => anyhow::bail!("Unsupported WAV format (tag {}, {} bits)", tag, bits),
+    };
+
+    let sample_bytes = bits as usize / 8;
+    let frames = data.chunks_exact(sample_bytes * channels)
+        .map(|frame| frame.chunks_exact(sample_bytes).map(decode).sum::<f32>() / channels as f32)
+        .collect();
+    Ok(SampleData { frames, sample_rate })
+}
+
+/// Parse a sample file name into (root key, top velocity, round-robin position)
+fn parse_sample_name(stem: &str) -> Option<(u8, u8, u32)> {
+    let mut parts = stem.split('_');
+    let root = parts.next()?.parse::<u8>().ok().filter(|key| *key <= 127)?;
+    let mut velocity = 127;
+    let mut round_robin = 1;
+    for part in parts {
+        if let Some(value) = part.strip_prefix("rr") {
+            round_robin = value.parse().ok().filter(|n| *n >= 1)?;
+        } else if let Some(value) = part.strip_prefix('v') {
+            velocity = value.parse::<u8>().ok().filter(|v| *v <= 127)?;
+        } else {
+            return None;
+        }
+    }
+    Some((root, velocity, round_robin))
+}
+
+/// Lay samples out as zones: each root covers the keys up to the next root,
+/// each velocity layer starts above the previous one, and repeated samples
+/// in a layer form a round-robin group
+pub fn zones_from_samples(mut samples: Vec<(u8, u8, u32, Arc<SampleData>)>) -> Vec<SampleZone> {
+    samples.sort_by_key(|(root, velocity, round_robin, _)| (*root, *velocity, *round_robin));
+    let mut roots: Vec<u8> = samples.iter().map(|(root, ..)| *root).collect();
+    roots.dedup();
+
+    samples.iter().map(|(root, velocity, round_robin, sample)| {
+        let root_index = roots.iter().position(|r| r == root).unwrap_or(0);
+        let mut layers: Vec<u8> = samples.iter()
+            .filter(|(r, ..)| r == root)
+            .map(|(_, v, ..)| *v)
+            .collect();
+        layers.dedup();
+        let layer_index = layers.iter().position(|v| v == velocity).unwrap_or(0);
+        let group: Vec<u32> = samples.iter()
+            .filter(|(r, v, ..)| r == root && v == velocity)
+            .map(|(.., rr, _)| *rr)
+            .collect();
+
+        let mut zone = SampleZone::new(Arc::clone(sample), *root);
+        zone.key_low = if root_index == 0 { 0 } else { *root };
+        zone.key_high = roots.get(root_index + 1).map_or(127, |next| next - 1);
+        zone.velocity_low = if layer_index == 0 { 0 } else { layers[layer_index - 1] + 1 };
+        zone.velocity_high = *velocity;
+        zone.seq_length = group.len() as u32;
+        zone.seq_position = group.iter().position(|rr| rr == round_robin).unwrap_or(0) as u32 + 1;
+        zone
+    }).collect()
+}
+
+/// Load every sample in a sample instrument folder as zones
+pub fn load_sample_folder(dir: &Path) -> Result<Vec<SampleZone>> {
+    let mut samples = Vec::new();
+    for entry in std::fs::read_dir(dir).with_context(|| format!("Failed to read sample folder: {:?}", dir))? {
+        let path = entry?.path();
+        if !path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("wav")) {
+            continue;
+        }
+        let Some((root, velocity, round_robin)) = path.file_stem().and_then(|s| s.to_str()).and_then(parse_sample_name) else {
+            log::warn!("Skipping sample with unrecognized name: {:?}", path);
+            continue;
+        };
+        let bytes = std::fs::read(&path).with_context(|| format!("Failed to read sample: {:?}", path))?;
+        let sample = decode_wav(&bytes).with_context(|| format!("Failed to decode sample: {:?}", path))?;
+        samples.push((root, velocity, round_robin, Arc::new(sample)));
+    }
+    if samples.is_empty() {
+        anyhow::bail!("No samples found in {:?}", dir);
+    }
+    Ok(zones_from_samples(samples))
+}
+
+/// Load a sample instrument: an SFZ file or a folder of named samples
+pub fn load_sample_instrument(path: &Path) -> Result<Vec<SampleZone>> {
+    if path.is_file() {
+        crate::sfz::load_sfz(path)
+    } else {
+        load_sample_folder(path)
+    }
+}
+
+/// Whether a folder looks like a sample instrument (holds at least one named sample)
+pub fn is_sample_folder(dir: &Path) -> bool {
+    std::fs::read_dir(dir).map(|entries| entries.flatten().any(|entry| {
+        let path = entry.path();
+        path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("wav"))
+            && path.file_stem().and_then(|s| s.to_str()).and_then(parse_sample_name).is_some()
+    })).unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample(len: usize) -> Arc<SampleData> {
+        Arc::new(SampleData { frames: vec![0.5; len], sample_rate: 48000 })
+    }
+
+    fn zone(velocity: (u8, u8), seq: (u32, u32)) -> SampleZone {
+        let mut zone = SampleZone::new(sample(4800), 60);
+        zone.velocity_low = velocity.0;
+        zone.velocity_high = velocity.1;
+        zone.seq_length = seq.0;
+        zone.seq_position = seq.1;
+        zone
+    }
+
+    #[test]
+    fn test_velocity_layers() {
+        let zones = vec![zone((0, 63), (1, 1)), zone((64, 127), (1, 1))];
+        let mut selector = ZoneSelector::new(RoundRobinMode::Sequential);
+        assert_eq!(selector.select(&zones, 60, 30), Some(0));
+        assert_eq!(selector.select(&zones, 60, 100), Some(1));
+    }
+
+    #[test]
+    fn test_round_robin_cycles() {
+        let zones = vec![zone((0, 127), (3, 1)), zone((0, 127), (3, 2)), zone((0, 127), (3, 3))];
+        let mut selector = ZoneSelector::new(RoundRobinMode::Sequential);
+        let picks: Vec<_> = (0..4).map(|_| selector.select(&zones, 60, 100).unwrap()).collect();
+        assert_eq!(picks, vec![0, 1, 2, 0]);
+    }
+
+    #[test]
+    fn test_random_round_robin_never_repeats() {
+        let zones = vec![zone((0, 127), (2, 1)), zone((0, 127), (2, 2))];
+        let mut selector = ZoneSelector::new(RoundRobinMode::Random);
+        let mut last = selector.select(&zones, 60, 100);
+        for _ in 0..10 {
+            let next = selector.select(&zones, 60, 100);
+            assert_ne!(next, last);
+            last = next;
+        }
+    }
+
+    #[test]
+    fn test_gain_and_pitch_offsets() {
+        let mut zone = SampleZone::new(sample(16), 60);
+        zone.gain_db = -6.0;
+        zone.tune_cents = 1200.0;
+        assert!((zone.gain() - 0.501).abs() < 0.01);
+        assert!((zone.playback_rate(60, 48000) - 2.0).abs() < 1e-9);
+        assert!((zone.playback_rate(48, 24000) - 2.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_decode_wav_roundtrip() {
+        let wav = crate::preview::encode_wav(&[0.0, 0.5, -0.5], 44100);
+        let sample = decode_wav(&wav).unwrap();
+        assert_eq!(sample.sample_rate, 44100);
+        assert_eq!(sample.frames.len(), 3);
+        assert!((sample.frames[1] - 0.5).abs() < 0.001);
+        assert!(decode_wav(b"not a wav file").is_err());
+    }
+
+    #[test]
+    fn test_zones_from_sample_names() {
+        assert_eq!(parse_sample_name("40"), Some((40, 127, 1)));
+        assert_eq!(parse_sample_name("52_v80_rr2"), Some((52, 80, 2)));
+        assert_eq!(parse_sample_name("E2"), None);
+
+        let names = ["40_v80", "40_rr1", "40_rr2", "52"];
+        let zones = zones_from_samples(names.iter()
+            .map(|name| {
+                let (root, velocity, rr) = parse_sample_name(name).unwrap();
+                (root, velocity, rr, sample(16))
+            })
+            .collect());
+        let layout: Vec<_> = zones.iter()
+            .map(|z| (z.key_low, z.key_high, z.velocity_low, z.velocity_high, z.seq_length, z.seq_position))
+            .collect();
+        assert_eq!(layout, vec![
+            (0, 51, 0, 80, 1, 1),
+            (0, 51, 81, 127, 2, 1),
+            (0, 51, 81, 127, 2, 2),
+            (52, 127, 0, 127, 1, 1),
+        ]);
+    }
+
+    #[test]
+    fn test_loop_modes() {
+        let ramp = Arc::new(SampleData { frames: (0..100).map(|i| i as f32 / 100.0).collect(), sample_rate: 48000 });
+        let mut zone = SampleZone::new(ramp, 60);
+        zone.loop_mode = LoopMode::Sustain;
+        zone.loop_start = 20;
+        zone.loop_end = 39;
+
+        // Held: plays on well past the end of the sample
+        let mut sampler = Sampler::new(48000, vec![zone.clone()]);
+        sampler.note_on(60, 127);
+        let mut buffer = vec![0.0f32; 2000];
+        sampler.render(&mut buffer);
+        assert_eq!(sampler.active_voice_count(), 1);
+        assert!(buffer[1000..].iter().all(|s| (0.19..0.41).contains(s)));
+
+        // Released: leaves the loop and stops at the end of the sample
+        sampler.note_off(60);
+        sampler.render(&mut buffer);
+        assert_eq!(sampler.active_voice_count(), 0);
+
+        // One-shot samples ignore note off
+        zone.loop_mode = LoopMode::OneShot;
+        let mut sampler = Sampler::new(48000, vec![zone]);
+        sampler.note_on(60, 127);
+        sampler.note_off(60);
+        let mut short = vec![0.0f32; 20];
+        sampler.render(&mut short);
+        assert!((short[18] - 0.09).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_sampler_voice_lifecycle() {
+        let mut sampler = Sampler::new(48000, vec![SampleZone::new(sample(4800), 60)]);
+        sampler.note_on(60, 127);
+        assert_eq!(sampler.active_voice_count(), 1);
+
+        let mut buffer = vec![0.0f32; 256];
+        sampler.render(&mut buffer);
+        assert!(buffer.iter().any(|s| *s > 0.0));
+
+        sampler.note_off(60);
+        let mut long = vec![0.0f32; 48000];
+        sampler.render(&mut long);
+        assert_eq!(sampler.active_voice_count(), 0);
+    }
+}
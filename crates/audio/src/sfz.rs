@@ -0,0 +1,357 @@
+//! SFZ instruments
+//! Most free guitar sample libraries ship as SFZ: a text file of `<region>`s
+//! mapping WAV samples onto key and velocity ranges, with opcodes inherited
+//! from enclosing `<global>`, `<master>` and `<group>` headers. The regions
+//! are turned into `SampleZone`s for the `Sampler`. Supported opcodes:
+//! `sample`, `key`, `lokey`/`hikey`, `pitch_keycenter`, `lovel`/`hivel`,
+//! `volume`, `tune`, `transpose`, `ampeg_release`, `loop_mode`,
+//! `loop_start`/`loop_end` (and `loopstart`/`loopend`), `seq_length`,
+//! `seq_position` and `default_path` under `<control>`. Other opcodes are
+//! ignored, so a library still loads, just with less nuance.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use anyhow::{Context, Result};
+
+use crate::sampler::{decode_wav, LoopMode, SampleData, SampleZone};
+
+/// Opcodes of one header level
+type Opcodes = HashMap<String, String>;
+
+/// A region with the opcodes it inherits folded in
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SfzRegion {
+    pub opcodes: Opcodes,
+}
+
+impl SfzRegion {
+    fn get(&self, opcode: &str) -> Option<&str> {
+        self.opcodes.get(opcode).map(String::as_str)
+    }
+
+    fn number<T: std::str::FromStr>(&self, opcode: &str) -> Option<T> {
+        self.get(opcode).and_then(|value| value.parse().ok())
+    }
+
+    fn key(&self, opcode: &str) -> Option<u8> {
+        self.get(opcode).and_then(parse_key)
+    }
+}
+
+/// Parsed SFZ file: its regions and `<control>` settings
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct SfzFile {
+    pub regions: Vec<SfzRegion>,
+    /// Folder sample paths are relative to (within the file's folder)
+    pub default_path: String,
+}
+
+/// Parse SFZ text
+pub fn parse_sfz(text: &str) -> SfzFile {
+    let mut file = SfzFile::default();
+    // Opcodes of <global>, <master> and <group>; a region starts from their union
+    let mut levels: [Opcodes; 3] = Default::default();
+    let mut header = String::new();
+    let mut region: Option<Opcodes> = None;
+
+    for line in strip_block_comments(text).lines() {
+        let line = line.split("//").next().unwrap_or("").trim();
+        // #define and #include are not supported
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        for token in tokenize(line) {
+            match token {
+                Token::Header(name) => {
+                    finish_region(&mut region, &mut file);
+                    match name.as_str() {
+                        "global" => levels = Default::default(),
+                        "master" => {
+                            levels[1].clear();
+                            levels[2].clear();
+                        }
+                        "group" => levels[2].clear(),
+                        "region" => {
+                            let mut opcodes = Opcodes::new();
+                            for level in &levels {
+                                opcodes.extend(level.iter().map(|(k, v)| (k.clone(), v.clone())));
+                            }
+                            region = Some(opcodes);
+                        }
+                        _ => {}
+                    }
+                    header = name;
+                }
+                Token::Opcode(opcode, value) => match header.as_str() {
+                    "region" => {
+                        if let Some(region) = region.as_mut() {
+                            insert_opcode(region, opcode, value);
+                        }
+                    }
+                    "group" => insert_opcode(&mut levels[2], opcode, value),
+                    "master" => insert_opcode(&mut levels[1], opcode, value),
+                    "global" => insert_opcode(&mut levels[0], opcode, value),
+                    "control" if opcode == "default_path" => file.default_path = value,
+                    _ => {}
+                },
+            }
+        }
+    }
+    finish_region(&mut region, &mut file);
+    file
+}
+
+fn finish_region(region: &mut Option<Opcodes>, file: &mut SfzFile) {
+    if let Some(opcodes) = region.take() {
+        file.regions.push(SfzRegion { opcodes });
+    }
+}
+
+/// `key` is shorthand for the key range and the root key
+fn insert_opcode(opcodes: &mut Opcodes, opcode: String, value: String) {
+    if opcode == "key" {
+        for opcode in ["lokey", "hikey", "pitch_keycenter"] {
+            opcodes.insert(opcode.to_string(), value.clone());
+        }
+    } else {
+        opcodes.insert(opcode, value);
+    }
+}
+
+enum Token {
+    Header(String),
+    Opcode(String, String),
+}
+
+/// Split a line into headers and `opcode=value` pairs. Values run up to the
+/// next opcode or header, so sample paths may contain spaces.
+fn tokenize(line: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut rest = line.trim_start();
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('<') {
+            let Some(end) = after.find('>') else { break };
+            tokens.push(Token::Header(after[..end].trim().to_lowercase()));
+            rest = after[end + 1..].trim_start();
+            continue;
+        }
+        let Some(equals) = rest.find('=') else { break };
+        let opcode = rest[..equals].trim().to_lowercase();
+        let value_text = &rest[equals + 1..];
+        let end = next_token_start(value_text);
+        tokens.push(Token::Opcode(opcode, value_text[..end].trim().to_string()));
+        rest = value_text[end..].trim_start();
+    }
+    tokens
+}
+
+/// Byte offset where the next header or `opcode=` begins in a value
+fn next_token_start(value: &str) -> usize {
+    let bytes = value.as_bytes();
+    for (i, &byte) in bytes.iter().enumerate() {
+        if byte == b'<' {
+            return i;
+        }
+        if byte.is_ascii_whitespace() {
+            let word = value[i..].trim_start();
+            let word_len = word.bytes().take_while(|b| b.is_ascii_alphanumeric() || *b == b'_').count();
+            if word_len > 0 && word.as_bytes().get(word_len) == Some(&b'=') {
+                return i;
+            }
+        }
+    }
+    value.len()
+}
+
+fn strip_block_comments(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("/*") {
+        out.push_str(&rest[..start]);
+        match rest[start + 2..].find("*/") {
+            Some(end) => rest = &rest[start + 2 + end + 2..],
+            None => return out,
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// MIDI key from a number or a note name (c4 = 60, c#4, db4)
+pub fn parse_key(value: &str) -> Option<u8> {
+    if let Ok(key) = value.parse::<i32>() {
+        return u8::try_from(key).ok().filter(|key| *key <= 127);
+    }
+    let value = value.to_lowercase();
+    let mut chars = value.chars();
+    let semitone = match chars.next()? {
+        'c' => 0,
+        'd' => 2,
+        'e' => 4,
+        'f' => 5,
+        'g' => 7,
+        'a' => 9,
+        'b' => 11,
+        _ => return None,
+    };
+    let rest = chars.as_str();
+    let (accidental, octave) = match rest.chars().next() {
+        Some('#') => (1, &rest[1..]),
+        Some('b') if rest.len() > 1 => (-1, &rest[1..]),
+        _ => (0, rest),
+    };
+    let octave: i32 = octave.parse().ok()?;
+    u8::try_from((octave + 1) * 12 + semitone + accidental).ok().filter(|key| *key <= 127)
+}
+
+fn parse_loop_mode(value: &str) -> Option<LoopMode> {
+    match value {
+        "no_loop" => Some(LoopMode::NoLoop),
+        "one_shot" => Some(LoopMode::OneShot),
+        "loop_continuous" => Some(LoopMode::Continuous),
+        "loop_sustain" => Some(LoopMode::Sustain),
+        _ => None,
+    }
+}
+
+/// Zone of a region, with its sample already loaded
+fn region_zone(region: &SfzRegion, sample: Arc<SampleData>) -> SampleZone {
+    let mut zone = SampleZone::new(sample, region.key("pitch_keycenter").unwrap_or(60));
+    zone.key_low = region.key("lokey").unwrap_or(0);
+    zone.key_high = region.key("hikey").unwrap_or(127);
+    zone.velocity_low = region.number("lovel").unwrap_or(0);
+    zone.velocity_high = region.number("hivel").unwrap_or(127);
+    zone.gain_db = region.number("volume").unwrap_or(0.0);
+    zone.tune_cents = region.number("tune").unwrap_or(0.0) + region.number::<f32>("transpose").unwrap_or(0.0) * 100.0;
+    if let Some(release) = region.number("ampeg_release") {
+        zone.release_time = release;
+    }
+    zone.seq_length = region.number("seq_length").unwrap_or(1).max(1);
+    zone.seq_position = region.number("seq_position").unwrap_or(1).max(1);
+
+    let loop_start = region.number("loop_start").or_else(|| region.number("loopstart"));
+    let loop_end = region.number("loop_end").or_else(|| region.number("loopend"));
+    let frames = zone.sample.frames.len();
+    zone.loop_start = loop_start.unwrap_or(0);
+    zone.loop_end = loop_end.unwrap_or(frames.saturating_sub(1));
+    // Without a loop_mode, regions with loop points loop while held
+    zone.loop_mode = match region.get("loop_mode").and_then(parse_loop_mode) {
+        Some(mode) => mode,
+        None if loop_end.is_some() => LoopMode::Sustain,
+        None => LoopMode::NoLoop,
+    };
+    zone
+}
+
+/// Load an SFZ file and its samples as zones (each sample is decoded once)
+pub fn load_sfz(path: &Path) -> Result<Vec<SampleZone>> {
+    let text = std::fs::read_to_string(path).with_context(|| format!("Failed to read SFZ file: {:?}", path))?;
+    let file = parse_sfz(&text);
+    let base = path.parent().unwrap_or(Path::new(".")).join(file.default_path.replace('\\', "/"));
+
+    let mut samples: HashMap<PathBuf, Arc<SampleData>> = HashMap::new();
+    let mut zones = Vec::new();
+    for region in &file.regions {
+        let Some(sample_name) = region.get("sample") else {
+            continue;
+        };
+        // Libraries made on Windows use backslashes
+        let sample_path = base.join(sample_name.replace('\\', "/"));
+        let sample = match samples.get(&sample_path) {
+            Some(sample) => Arc::clone(sample),
+            None => {
+                let bytes = match std::fs::read(&sample_path) {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        log::warn!("Skipping SFZ region, can't read {:?}: {}", sample_path, e);
+                        continue;
+                    }
+                };
+                let sample = Arc::new(decode_wav(&bytes).with_context(|| format!("Failed to decode sample: {:?}", sample_path))?);
+                samples.insert(sample_path, Arc::clone(&sample));
+                sample
+            }
+        };
+        zones.push(region_zone(region, sample));
+    }
+    if zones.is_empty() {
+        anyhow::bail!("No playable regions in {:?}", path);
+    }
+    Ok(zones)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const GUITAR: &str = r"
+// Two velocity layers over one key range
+<control> default_path=samples\
+<global> ampeg_release=0.5
+<group> lokey=c2 hikey=b2 pitch_keycenter=e2 lovel=0 hivel=63
+<region> sample=soft E2.wav volume=-3
+<group> lokey=c2 hikey=b2 pitch_keycenter=e2 lovel=64
+<region> sample=hard E2.wav loop_mode=loop_continuous loop_start=10 loop_end=90 /* looped */
+<region> key=64 sample=e4.wav tune=-5 transpose=1 seq_length=2 seq_position=2
+";
+
+    #[test]
+    fn test_parse_inherits_opcodes() {
+        let file = parse_sfz(GUITAR);
+        assert_eq!(file.default_path, "samples\\");
+        assert_eq!(file.regions.len(), 3);
+        assert_eq!(file.regions[0].get("sample"), Some("soft E2.wav"));
+        assert_eq!(file.regions[0].get("hivel"), Some("63"));
+        assert_eq!(file.regions[0].get("ampeg_release"), Some("0.5"));
+        // The second group replaced the first one's opcodes
+        assert_eq!(file.regions[1].get("hivel"), None);
+        assert_eq!(file.regions[1].get("loop_end"), Some("90"));
+    }
+
+    #[test]
+    fn test_regions_become_zones() {
+        let sample = Arc::new(SampleData { frames: vec![0.0; 100], sample_rate: 44100 });
+        let file = parse_sfz(GUITAR);
+        let zones: Vec<_> = file.regions.iter().map(|region| region_zone(region, Arc::clone(&sample))).collect();
+
+        assert_eq!((zones[0].key_low, zones[0].key_high, zones[0].root_key), (36, 47, 40));
+        assert_eq!((zones[0].velocity_low, zones[0].velocity_high), (0, 63));
+        assert_eq!(zones[0].gain_db, -3.0);
+        assert_eq!(zones[0].release_time, 0.5);
+        assert_eq!(zones[0].loop_mode, LoopMode::NoLoop);
+
+        assert_eq!(zones[1].velocity_low, 64);
+        assert_eq!((zones[1].loop_mode, zones[1].loop_start, zones[1].loop_end), (LoopMode::Continuous, 10, 90));
+
+        assert_eq!((zones[2].key_low, zones[2].key_high, zones[2].root_key), (64, 64, 64));
+        assert_eq!(zones[2].tune_cents, 95.0);
+        assert_eq!((zones[2].seq_length, zones[2].seq_position), (2, 2));
+    }
+
+    #[test]
+    fn test_key_names() {
+        assert_eq!(parse_key("60"), Some(60));
+        assert_eq!(parse_key("c4"), Some(60));
+        assert_eq!(parse_key("C#4"), Some(61));
+        assert_eq!(parse_key("eb3"), Some(51));
+        assert_eq!(parse_key("b"), None);
+        assert_eq!(parse_key("200"), None);
+    }
+
+    #[test]
+    fn test_load_sfz_reads_samples_once() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        std::fs::create_dir_all(dir.join("samples")).unwrap();
+        std::fs::write(dir.join("samples").join("e2.wav"), crate::preview::encode_wav(&[0.0, 0.5, -0.5], 44100)).unwrap();
+        let sfz = dir.join("guitar.sfz");
+        std::fs::write(&sfz, "<control> default_path=samples/\n<region> sample=e2.wav hivel=63\n<region> sample=e2.wav lovel=64\n<region> sample=missing.wav").unwrap();
+
+        let zones = load_sfz(&sfz).unwrap();
+        assert_eq!(zones.len(), 2);
+        assert!(Arc::ptr_eq(&zones[0].sample, &zones[1].sample));
+        assert!(crate::sampler::load_sample_instrument(&sfz).is_ok());
+    }
+}
@@ -10,7 +10,7 @@ use oxisynth::{SoundFont, Synth, SynthDescriptor};
 use std::io::BufReader;
 use std::fs::File;
 
-/// Information about an instrument (SoundFont or Virtual)
+/// Information about an instrument (SoundFont, sample folder, SFZ or Virtual)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct InstrumentInfo {
     pub name: String,
@@ -26,21 +26,7 @@ impl InstrumentInfo {
             return None;
         }
         
-        match self.name.as_str() {
-            "Clean Electric Guitar" => Some(SynthInstrumentType::CleanElectricGuitar),
-            "Distorted Guitar" => Some(SynthInstrumentType::DistortedGuitar),
-            "Acoustic Guitar" => Some(SynthInstrumentType::AcousticGuitar),
-            "Classical Guitar" => Some(SynthInstrumentType::ClassicalGuitar),
-            "Electric Bass" => Some(SynthInstrumentType::ElectricBass),
-            "Acoustic Bass" => Some(SynthInstrumentType::AcousticBass),
-            "Piano" => Some(SynthInstrumentType::Piano),
-            "Organ" => Some(SynthInstrumentType::Organ),
-            "Strings" => Some(SynthInstrumentType::Strings),
-            "Synth Lead" => Some(SynthInstrumentType::SynthLead),
-            "Synth Pad" => Some(SynthInstrumentType::SynthPad),
-            "Brass Section" => Some(SynthInstrumentType::BrassSection),
-            _ => None,
-        }
+        SynthInstrumentType::from_name(&self.name)
     }
 }
 
@@ -48,6 +34,10 @@ impl InstrumentInfo {
 pub enum InstrumentType {
     SoundFont,
     Virtual,
+    /// Folder of WAV samples played by the sampler
+    Sampler,
+    /// SFZ file played by the sampler
+    Sfz,
 }
 
 /// Legacy SoundFont-only structure for backwards compatibility
@@ -170,6 +160,26 @@ impl SoundFontManager {
                     });
 
                     log::info!("Found SoundFont: {:?}", path);
+                } else if is_sfz_file(&path) {
+                    self.add_sfz(&path)?;
+                } else if path.is_dir() && crate::sampler::is_sample_folder(&path) {
+                    let name = path.file_name()
+                        .and_then(|n| n.to_str())
+                        .unwrap_or("Unknown")
+                        .to_string();
+                    self.instruments.push(InstrumentInfo {
+                        name,
+                        path: Some(path.clone()),
+                        size_bytes: None,
+                        instrument_type: InstrumentType::Sampler,
+                    });
+
+                    log::info!("Found sample instrument: {:?}", path);
+                } else if path.is_dir() {
+                    // SFZ libraries keep the .sfz next to their sample folders
+                    for sfz in fs::read_dir(&path)?.flatten().map(|entry| entry.path()).filter(|p| is_sfz_file(p)) {
+                        self.add_sfz(&sfz)?;
+                    }
                 }
             }
         }
@@ -178,6 +188,23 @@ impl SoundFontManager {
         Ok(())
     }
 
+    fn add_sfz(&mut self, path: &Path) -> Result<()> {
+        let metadata = fs::metadata(path)?;
+        let name = path.file_stem()
+            .and_then(|n| n.to_str())
+            .unwrap_or("Unknown")
+            .to_string();
+        self.instruments.push(InstrumentInfo {
+            name,
+            path: Some(path.to_path_buf()),
+            size_bytes: Some(metadata.len()),
+            instrument_type: InstrumentType::Sfz,
+        });
+
+        log::info!("Found SFZ instrument: {:?}", path);
+        Ok(())
+    }
+
     /// Get list of all instruments (SoundFonts + Virtual)
     pub fn list_instruments(&self) -> &[InstrumentInfo] {
         &self.instruments
@@ -213,11 +240,16 @@ impl SoundFontManager {
     }
 }
 
+fn is_sfz_file(path: &Path) -> bool {
+    path.is_file() && path.extension().and_then(|e| e.to_str()).is_some_and(|e| e.eq_ignore_ascii_case("sfz"))
+}
+
 #[cfg(feature = "soundfont")]
 /// SoundFont-based synthesizer
 pub struct SoundFontSynth {
     synth: Synth,
     active_soundfont: Option<String>,
+    tuning_cents: f32,
 }
 
 #[cfg(feature = "soundfont")]
@@ -236,6 +268,7 @@ impl SoundFontSynth {
         Ok(Self {
             synth,
             active_soundfont: None,
+            tuning_cents: 0.0,
         })
     }
 
@@ -257,6 +290,9 @@ impl SoundFontSynth {
             .and_then(|s| s.to_str())
             .map(|s| s.to_string());
 
+        // Loading a font resets the channels, re-send the master tuning
+        self.apply_tuning();
+
         log::info!("SoundFont loaded successfully");
         Ok(())
     }
@@ -308,6 +344,26 @@ impl SoundFontSynth {
         }
     }
 
+    /// Set master fine tuning in cents (-100..100, e.g. from the A4 reference)
+    pub fn set_tuning_cents(&mut self, cents: f32) {
+        self.tuning_cents = cents.clamp(-100.0, 100.0);
+        self.apply_tuning();
+    }
+
+    /// Send the fine tuning as RPN 0x0001 (channel fine tune)
+    fn apply_tuning(&mut self) {
+        // 14-bit value, 8192 = centered, +/-8192 = +/-100 cents
+        let value = (8192.0 + self.tuning_cents / 100.0 * 8192.0).round().clamp(0.0, 16383.0) as u16;
+        for channel in 0..16 {
+            self.control_change(channel, 101, 0); // RPN MSB
+            self.control_change(channel, 100, 1); // RPN LSB: fine tuning
+            self.control_change(channel, 6, (value >> 7) as u8); // Data entry MSB
+            self.control_change(channel, 38, (value & 0x7F) as u8); // Data entry LSB
+            self.control_change(channel, 101, 127); // RPN null
+            self.control_change(channel, 100, 127);
+        }
+    }
+
     /// Set pitch bend
     pub fn set_pitch_bend(&mut self, amount: f32) {
         // Convert -1.0..1.0 to 0..16383 (14-bit MIDI pitch bend)
@@ -350,6 +406,86 @@ impl SoundFontSynth {
     }
 }
 
+/// A SoundFont loading on a background thread. The engine keeps playing the
+/// current instrument until it is ready, so the first strum after startup or
+/// an instrument change never waits on the disk.
+#[cfg(feature = "soundfont")]
+#[derive(Clone)]
+pub struct SoundFontLoad {
+    path: PathBuf,
+    state: std::sync::Arc<std::sync::Mutex<SoundFontLoadState>>,
+}
+
+#[cfg(feature = "soundfont")]
+pub(crate) enum SoundFontLoadState {
+    Loading,
+    Ready(Box<SoundFontSynth>),
+    Failed,
+    /// Handed to the engine
+    Taken,
+}
+
+#[cfg(feature = "soundfont")]
+impl SoundFontLoad {
+    /// Start loading a SoundFont; `done` is called on the loader thread once
+    /// it is ready (or failed)
+    pub fn spawn(path: PathBuf, sample_rate: f32, done: impl FnOnce(Result<()>) + Send + 'static) -> Self {
+        let load = Self {
+            path,
+            state: std::sync::Arc::new(std::sync::Mutex::new(SoundFontLoadState::Loading)),
+        };
+        let (path, state) = (load.path.clone(), std::sync::Arc::clone(&load.state));
+        std::thread::spawn(move || {
+            let started = std::time::Instant::now();
+            let synth = SoundFontSynth::new(sample_rate).and_then(|mut synth| {
+                synth.load_soundfont(&path)?;
+                Ok(synth)
+            });
+            let result = match synth {
+                Ok(synth) => {
+                    log::info!("🎹 SoundFont ready in {:.0} ms: {}", started.elapsed().as_secs_f64() * 1000.0, path.display());
+                    *state.lock().unwrap() = SoundFontLoadState::Ready(Box::new(synth));
+                    Ok(())
+                }
+                Err(e) => {
+                    log::error!("Failed to load soundfont {}: {:#}", path.display(), e);
+                    *state.lock().unwrap() = SoundFontLoadState::Failed;
+                    Err(e)
+                }
+            };
+            done(result);
+        });
+        load
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether loading has finished, successfully or not
+    pub fn is_finished(&self) -> bool {
+        !matches!(*self.state.lock().unwrap(), SoundFontLoadState::Loading)
+    }
+
+    /// Take the result without blocking (RT-safe); `Loading` while the
+    /// loader thread is still busy
+    pub(crate) fn take(&self) -> SoundFontLoadState {
+        match self.state.try_lock() {
+            Ok(mut state) if !matches!(*state, SoundFontLoadState::Loading) => {
+                std::mem::replace(&mut *state, SoundFontLoadState::Taken)
+            }
+            _ => SoundFontLoadState::Loading,
+        }
+    }
+}
+
+#[cfg(feature = "soundfont")]
+impl std::fmt::Debug for SoundFontLoad {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SoundFontLoad").field("path", &self.path).finish_non_exhaustive()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -364,4 +500,21 @@ mod tests {
             assert!(default.is_some(), "Expected to find a default guitar soundfont");
         }
     }
+
+    #[test]
+    fn test_scan_finds_sfz_instruments() {
+        let temp = tempfile::tempdir().unwrap();
+        let dir = temp.path();
+        fs::create_dir_all(dir.join("Nylon Guitar").join("samples")).unwrap();
+        fs::write(dir.join("steel.sfz"), "<region> sample=steel.wav").unwrap();
+        fs::write(dir.join("Nylon Guitar").join("nylon.sfz"), "<region> sample=samples/e2.wav").unwrap();
+
+        let manager = SoundFontManager::new(dir).unwrap();
+        let mut sfz: Vec<_> = manager.list_instruments().iter()
+            .filter(|instrument| instrument.instrument_type == InstrumentType::Sfz)
+            .map(|instrument| instrument.name.as_str())
+            .collect();
+        sfz.sort_unstable();
+        assert_eq!(sfz, vec!["nylon", "steel"]);
+    }
 }
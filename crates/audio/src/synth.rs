@@ -1,9 +1,36 @@
 //! Fallback polyphonic synthesizer
 //! Simple but musical synth that works without external dependencies
 
-const MAX_VOICES: usize = 16;
+use crate::denormal::flush_denormal;
+use crate::temperament::TuningTable;
+
+/// Polyphony until the config sets its own
+pub const DEFAULT_MAX_VOICES: usize = 16;
+/// Polyphony range `set_max_voices` accepts
+pub const MIN_VOICES: usize = 4;
+pub const MAX_VOICES: usize = 64;
 const ATTACK_TIME: f32 = 0.01;  // 10ms attack
 const RELEASE_TIME: f32 = 0.3;  // 300ms release
+/// Delay line length for plucked strings (covers ~12Hz at 48kHz, ~23Hz at 96kHz)
+const PLUCK_DELAY_SIZE: usize = 4096;
+
+/// Palm mute: release and filter scaling for the damped variant
+const PALM_MUTE_RELEASE_SCALE: f32 = 0.15;
+const PALM_MUTE_FILTER_SCALE: f32 = 0.35;
+/// Seconds for a held palm-muted note to fade by 60dB
+const PALM_MUTE_HOLD_DECAY: f32 = 0.5;
+/// Pick thump: the pitch starts this many semitones sharp and settles quickly
+const PALM_MUTE_THUMP_SEMITONES: f32 = 0.35;
+const PALM_MUTE_THUMP_TIME: f32 = 0.03;
+/// Vibrato at full modulation: depth either side and LFO rate
+const MAX_VIBRATO_SEMITONES: f32 = 0.5;
+const VIBRATO_RATE_HZ: f32 = 5.5;
+/// Whammy filter fully closed: cutoff relative to the instrument's own
+const MIN_WHAMMY_FILTER_SCALE: f32 = 0.05;
+/// Frames over which the whammy smoothing factor applies once (about one controller update)
+const WHAMMY_SMOOTHING_FRAMES: f32 = 256.0;
+/// Whammy smoothing until a genre sets its own (as in `WhammyDefaults`)
+pub const DEFAULT_WHAMMY_SMOOTHING: f32 = 0.8;
 
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum InstrumentType {
@@ -21,6 +48,27 @@ pub enum InstrumentType {
     BrassSection,
 }
 
+impl InstrumentType {
+    /// Instrument for its display name (as listed with the virtual instruments)
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "Clean Electric Guitar" => Some(Self::CleanElectricGuitar),
+            "Distorted Guitar" => Some(Self::DistortedGuitar),
+            "Acoustic Guitar" => Some(Self::AcousticGuitar),
+            "Classical Guitar" => Some(Self::ClassicalGuitar),
+            "Electric Bass" => Some(Self::ElectricBass),
+            "Acoustic Bass" => Some(Self::AcousticBass),
+            "Piano" => Some(Self::Piano),
+            "Organ" => Some(Self::Organ),
+            "Strings" => Some(Self::Strings),
+            "Synth Lead" => Some(Self::SynthLead),
+            "Synth Pad" => Some(Self::SynthPad),
+            "Brass Section" => Some(Self::BrassSection),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq)]
 enum WaveType {
     Sine,
@@ -28,6 +76,9 @@ enum WaveType {
     Square,
     Triangle,
     Noise,
+    /// Karplus-Strong plucked string; `decay` is the time (seconds) for a
+    /// held note to fade by 60dB
+    Pluck { decay: f32 },
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -60,6 +111,23 @@ struct Voice {
     filter_state: f32,
     sustain_enabled: bool,
     sustain_release_time: f32,
+    /// Left/right gains from the voice's pan position
+    pan_gains: [f32; 2],
+    /// Karplus-Strong delay line (allocated once, reused per note)
+    pluck_delay: Vec<f32>,
+    pluck_write: usize,
+    pluck_last: f32,
+    /// Feedback gain per trip round the delay line
+    pluck_feedback: f32,
+    /// Per-sample envelope fade while held (1.0 = none, palm-muted notes die out)
+    hold_decay: f32,
+    /// Pitch offset in semitones for the palm-mute thump, and its per-sample decay
+    thump: f32,
+    thump_decay: f32,
+    /// Pitch bend of this note alone in semitones (on top of the global bend)
+    note_bend: f32,
+    /// When the note started, counted in notes (smaller = older)
+    started: u64,
 }
 
 impl Voice {
@@ -75,6 +143,16 @@ impl Voice {
             filter_state: 0.0,
             sustain_enabled: false,
             sustain_release_time: 0.5,
+            pan_gains: [1.0, 1.0],
+            pluck_delay: vec![0.0; PLUCK_DELAY_SIZE],
+            pluck_write: 0,
+            pluck_last: 0.0,
+            pluck_feedback: 0.0,
+            hold_decay: 1.0,
+            thump: 0.0,
+            thump_decay: 0.0,
+            note_bend: 0.0,
+            started: 0,
         }
     }
 
@@ -82,10 +160,11 @@ impl Voice {
         self.envelope_stage != EnvelopeStage::Off
     }
 
-    fn trigger(&mut self, note: u8, velocity: u8, _sample_rate: u32, settings: InstrumentSettings, sustain_enabled: bool, sustain_release_time: f32) {
+    #[allow(clippy::too_many_arguments)]
+    fn trigger(&mut self, note: u8, velocity: u8, sample_rate: u32, settings: InstrumentSettings, sustain_enabled: bool, sustain_release_time: f32, frequency: f32) {
         self.note = note;
         self.velocity = velocity as f32 / 127.0;
-        self.frequency = midi_to_frequency(note);
+        self.frequency = frequency;
         self.phase = 0.0;
         self.envelope_stage = EnvelopeStage::Attack;
         self.envelope_value = 0.0;
@@ -93,6 +172,57 @@ impl Voice {
         self.filter_state = 0.0;
         self.sustain_enabled = sustain_enabled;
         self.sustain_release_time = sustain_release_time;
+        self.note_bend = 0.0;
+        if let WaveType::Pluck { decay } = settings.wave_type {
+            self.pluck(sample_rate, decay);
+        }
+    }
+
+    /// Damp a freshly triggered note (fades while held, short pitch thump)
+    fn set_palm_mute(&mut self, muted: bool, sample_rate: u32) {
+        let seconds_per_sample = 1.0 / sample_rate.max(1) as f32;
+        if muted {
+            self.hold_decay = 0.001_f32.powf(seconds_per_sample / PALM_MUTE_HOLD_DECAY);
+            self.thump = PALM_MUTE_THUMP_SEMITONES;
+            self.thump_decay = (-seconds_per_sample / PALM_MUTE_THUMP_TIME).exp();
+        } else {
+            self.hold_decay = 1.0;
+            self.thump = 0.0;
+        }
+    }
+
+    /// Fill the delay line with a noise burst one period long. Softer notes
+    /// get a smoothed burst so they sound darker, like a gentler pick.
+    fn pluck(&mut self, sample_rate: u32, decay: f32) {
+        let period = ((sample_rate as f32 / self.frequency.max(1.0)) as usize).clamp(2, PLUCK_DELAY_SIZE - 1);
+        self.pluck_delay.fill(0.0);
+        let smoothing = 0.2 + 0.8 * self.velocity;
+        let mut previous = 0.0;
+        for sample in &mut self.pluck_delay[..period] {
+            previous += ((fastrand::f32() * 2.0 - 1.0) - previous) * smoothing;
+            *sample = previous;
+        }
+        self.pluck_write = period;
+        self.pluck_last = 0.0;
+        self.pluck_feedback = pluck_feedback(self.frequency, decay);
+    }
+
+    /// One Karplus-Strong step: read the delay line one (bent) period back,
+    /// average with the previous read (the damping low-pass) and feed it back
+    fn render_pluck(&mut self, sample_rate: u32, frequency: f32) -> f32 {
+        // The averaging filter adds half a sample of delay
+        let delay = (sample_rate as f32 / frequency.max(1.0) - 0.5).clamp(2.0, (PLUCK_DELAY_SIZE - 2) as f32);
+        let read = (self.pluck_write as f32 - delay).rem_euclid(PLUCK_DELAY_SIZE as f32);
+        let index = read as usize % PLUCK_DELAY_SIZE;
+        let next = (index + 1) % PLUCK_DELAY_SIZE;
+        let fraction = read.fract();
+        let delayed = self.pluck_delay[index] + (self.pluck_delay[next] - self.pluck_delay[index]) * fraction;
+
+        let output = 0.5 * (delayed + self.pluck_last);
+        self.pluck_last = delayed;
+        self.pluck_delay[self.pluck_write] = flush_denormal(output * self.pluck_feedback);
+        self.pluck_write = (self.pluck_write + 1) % PLUCK_DELAY_SIZE;
+        output
     }
 
     fn release(&mut self) {
@@ -101,7 +231,7 @@ impl Voice {
         }
     }
 
-    fn render_sample(&mut self, sample_rate: u32, pitch_bend: f32) -> f32 {
+    fn render_sample(&mut self, sample_rate: u32, pitch_bend: f32, brightness: f32) -> f32 {
         if !self.is_active() {
             return 0.0;
         }
@@ -117,7 +247,12 @@ impl Voice {
                 }
             }
             EnvelopeStage::Sustain => {
-                // Hold at 1.0
+                // Hold (palm-muted notes keep fading)
+                self.envelope_value *= self.hold_decay;
+                if self.envelope_value <= 1e-4 {
+                    self.envelope_value = 0.0;
+                    self.envelope_stage = EnvelopeStage::Off;
+                }
             }
             EnvelopeStage::Release => {
                 // Use sustain release time if sustain is enabled, otherwise use instrument release
@@ -135,8 +270,9 @@ impl Voice {
             EnvelopeStage::Off => return 0.0,
         }
 
-        // Apply pitch bend (in semitones)
-        let bent_frequency = self.frequency * 2.0_f32.powf(pitch_bend / 12.0);
+        // Apply pitch bend, per-note bend and palm-mute thump (in semitones)
+        let bent_frequency = self.frequency * 2.0_f32.powf((pitch_bend + self.note_bend + self.thump) / 12.0);
+        self.thump *= self.thump_decay;
 
         // Generate waveform based on instrument type
         let phase_increment = bent_frequency / sample_rate as f32;
@@ -157,11 +293,12 @@ impl Voice {
                 }
             }
             WaveType::Noise => (fastrand::f32() * 2.0) - 1.0,
+            WaveType::Pluck { .. } => self.render_pluck(sample_rate, bent_frequency),
         };
 
         // Apply simple low-pass filter
-        let cutoff = self.settings.filter_cutoff;
-        self.filter_state += (sample - self.filter_state) * cutoff;
+        let cutoff = self.settings.filter_cutoff * brightness;
+        self.filter_state = flush_denormal(self.filter_state + (sample - self.filter_state) * cutoff);
         sample = self.filter_state;
 
         // Apply distortion if specified
@@ -175,27 +312,74 @@ impl Voice {
     }
 }
 
+/// A whammy controller (0.0-1.0) gliding towards its target, so the steps
+/// between controller updates don't zipper
+#[derive(Debug, Clone, Copy)]
+struct SmoothedControl {
+    value: f32,
+    target: f32,
+}
+
+impl SmoothedControl {
+    fn new(value: f32) -> Self {
+        Self { value, target: value }
+    }
+
+    /// Value `frames` from now, keeping `coefficient` of the distance per frame
+    fn at(&self, coefficient: f32, frames: usize) -> f32 {
+        self.target + (self.value - self.target) * coefficient.powi(frames as i32)
+    }
+
+    fn advance(&mut self, coefficient: f32, frames: usize) {
+        self.value = self.at(coefficient, frames);
+    }
+}
+
 /// Simple polyphonic synthesizer
 pub struct FallbackSynth {
-    voices: [Voice; MAX_VOICES],
+    voices: Vec<Voice>,
+    /// Notes started so far (orders voices by age)
+    notes_started: u64,
+    /// Notes that took over a sounding voice since the last `take_stolen_voices`
+    voices_stolen: u64,
     sample_rate: u32,
     pitch_bend: f32, // In semitones (-2 to +2)
     current_instrument: InstrumentType,
     release_multiplier: f32, // Multiplier for all release times
     sustain_enabled: bool, // Whether sustain mode is enabled
     sustain_release_time: f32, // Custom release time for sustain mode (in seconds)
+    a4_frequency: f32, // Tuning reference (Hz)
+    tuning: TuningTable, // Temperament (cent offsets per pitch class)
+    stereo_spread: f32, // Chord spread width (0.0 = mono, 1.0 = full stereo)
+    palm_mute: bool, // Damped articulation for new notes
+    vibrato_depth: f32, // In semitones, from the modulation wheel
+    vibrato_phase: f32, // LFO phase (0.0-1.0)
+    filter_control: SmoothedControl, // Whammy filter (CC74, 1.0 = open)
+    expression: SmoothedControl, // Whammy volume (CC11, 1.0 = full)
+    control_coefficient: f32, // Per-frame smoothing of the whammy controls
 }
 
 impl FallbackSynth {
     pub fn new(sample_rate: u32) -> Self {
         Self {
-            voices: std::array::from_fn(|_| Voice::new()),
+            voices: (0..DEFAULT_MAX_VOICES).map(|_| Voice::new()).collect(),
+            notes_started: 0,
+            voices_stolen: 0,
             sample_rate,
             pitch_bend: 0.0,
             current_instrument: InstrumentType::CleanElectricGuitar,
             release_multiplier: 1.0,
             sustain_enabled: false,
             sustain_release_time: 0.5,
+            a4_frequency: DEFAULT_A4_FREQUENCY,
+            tuning: TuningTable::equal(),
+            stereo_spread: 0.0,
+            palm_mute: false,
+            vibrato_depth: 0.0,
+            vibrato_phase: 0.0,
+            filter_control: SmoothedControl::new(1.0),
+            expression: SmoothedControl::new(1.0),
+            control_coefficient: control_coefficient(DEFAULT_WHAMMY_SMOOTHING),
         }
     }
 
@@ -220,25 +404,87 @@ impl FallbackSynth {
         self.sustain_release_time = time_seconds.clamp(0.05, 10.0); // 50ms to 10s
     }
 
+    /// Set the A4 reference frequency (applies to new notes)
+    pub fn set_a4_frequency(&mut self, frequency: f32) {
+        self.a4_frequency = clamp_a4_frequency(frequency);
+    }
+
+    /// Set the temperament (applies to new notes)
+    pub fn set_tuning(&mut self, tuning: TuningTable) {
+        self.tuning = tuning;
+    }
+
+    /// Set how wide chord notes are spread across the stereo field (applies to new notes)
+    pub fn set_stereo_spread(&mut self, width: f32) {
+        self.stereo_spread = clamp_stereo_spread(width);
+    }
+
+    /// Set how many notes can sound at once (clamped to `MIN_VOICES`-`MAX_VOICES`);
+    /// lowering it cuts the notes beyond the new limit
+    pub fn set_max_voices(&mut self, voices: usize) {
+        self.voices.resize_with(clamp_max_voices(voices), Voice::new);
+    }
+
+    pub fn max_voices(&self) -> usize {
+        self.voices.len()
+    }
+
+    /// Palm mute: new notes use the damped variant of the instrument
+    pub fn set_palm_mute(&mut self, enabled: bool) {
+        self.palm_mute = enabled;
+    }
+
+    pub fn palm_mute(&self) -> bool {
+        self.palm_mute
+    }
+
     pub fn note_on(&mut self, note: u8, velocity: u8) {
-        // Find a free voice or steal the oldest
         let sample_rate = self.sample_rate;
         let mut settings = get_instrument_settings(self.current_instrument);
         // Apply release multiplier (only when sustain is disabled)
         if !self.sustain_enabled {
             settings.release_time *= self.release_multiplier;
         }
+        if self.palm_mute {
+            settings = palm_muted(settings);
+        }
         
         // Store sustain settings to avoid borrowing issues
         let sustain_enabled = self.sustain_enabled;
         let sustain_release_time = self.sustain_release_time;
+        let frequency = self.tuning.frequency(note, self.a4_frequency);
+        let pan_gains = pan_gains(note_pan(note, self.stereo_spread));
         
-        if let Some(voice) = self.find_free_voice() {
-            voice.trigger(note, velocity, sample_rate, settings, sustain_enabled, sustain_release_time);
-        } else if let Some(voice) = self.voices.first_mut() {
-            // Voice stealing: take the first voice
-            voice.trigger(note, velocity, sample_rate, settings, sustain_enabled, sustain_release_time);
-        }
+        let index = self.free_voice().unwrap_or_else(|| {
+            self.voices_stolen += 1;
+            self.voice_to_steal()
+        });
+        self.notes_started += 1;
+        let voice = &mut self.voices[index];
+        voice.started = self.notes_started;
+        voice.trigger(note, velocity, sample_rate, settings, sustain_enabled, sustain_release_time, frequency);
+        voice.set_palm_mute(self.palm_mute, sample_rate);
+        voice.pan_gains = pan_gains;
+    }
+
+    fn free_voice(&self) -> Option<usize> {
+        self.voices.iter().position(|v| !v.is_active())
+    }
+
+    /// Voice a new note takes over when all are sounding: the quietest
+    /// released one (the oldest on a tie), otherwise the oldest held one
+    fn voice_to_steal(&self) -> usize {
+        let released = self.voices.iter().enumerate()
+            .filter(|(_, v)| v.envelope_stage == EnvelopeStage::Release)
+            .min_by(|(_, a), (_, b)| a.envelope_value.total_cmp(&b.envelope_value).then(a.started.cmp(&b.started)));
+        released
+            .or_else(|| self.voices.iter().enumerate().min_by_key(|(_, v)| v.started))
+            .map_or(0, |(index, _)| index)
+    }
+
+    /// Notes that took over a sounding voice since the last call
+    pub fn take_stolen_voices(&mut self) -> u64 {
+        std::mem::take(&mut self.voices_stolen)
     }
 
     pub fn note_off(&mut self, note: u8) {
@@ -260,26 +506,81 @@ impl FallbackSynth {
         self.pitch_bend = (amount as f32 / 8192.0) * 2.0;
     }
 
+    /// Bend one sounding note (same range as the global bend); the note's
+    /// bend resets when its voice is retriggered
+    pub fn set_note_pitch_bend(&mut self, note: u8, amount: i16) {
+        let semitones = (amount as f32 / 8192.0) * 2.0;
+        for voice in &mut self.voices {
+            if voice.note == note && voice.is_active() {
+                voice.note_bend = semitones;
+            }
+        }
+    }
+
+    /// Modulation wheel (0-127): vibrato depth on all sounding notes
+    pub fn set_modulation(&mut self, value: u8) {
+        self.vibrato_depth = value.min(127) as f32 / 127.0 * MAX_VIBRATO_SEMITONES;
+    }
+
+    /// Filter cutoff controller (0-127, 127 = the instrument's own cutoff)
+    pub fn set_filter_cutoff(&mut self, value: u8) {
+        self.filter_control.target = value.min(127) as f32 / 127.0;
+    }
+
+    /// Expression controller (0-127): volume under the instrument's own
+    pub fn set_expression(&mut self, value: u8) {
+        self.expression.target = value.min(127) as f32 / 127.0;
+    }
+
+    /// How much of the way to a new filter or expression value is left
+    /// after one controller update (0.0 = jump, up to 0.99 = slow glide)
+    pub fn set_whammy_smoothing(&mut self, factor: f32) {
+        self.control_coefficient = control_coefficient(factor);
+    }
+
     pub fn render(&mut self, buffer: &mut [f32]) {
         // Clear buffer first
         for sample in buffer.iter_mut() {
             *sample = 0.0;
         }
 
+        // The vibrato LFO is shared, so every voice follows the same phase
+        let vibrato_step = VIBRATO_RATE_HZ / self.sample_rate.max(1) as f32;
+        let (vibrato_depth, vibrato_phase) = (self.vibrato_depth, self.vibrato_phase);
+        let vibrato = |frame: usize| {
+            if vibrato_depth > 0.0 {
+                vibrato_depth * (std::f32::consts::TAU * (vibrato_phase + frame as f32 * vibrato_step)).sin()
+            } else {
+                0.0
+            }
+        };
+
+        let (coefficient, filter_control, expression) = (self.control_coefficient, self.filter_control, self.expression);
+        let brightness = |frame: usize| {
+            MIN_WHAMMY_FILTER_SCALE + (1.0 - MIN_WHAMMY_FILTER_SCALE) * filter_control.at(coefficient, frame)
+        };
+
         // Render each active voice
         for voice in &mut self.voices {
             if voice.is_active() {
                 for i in (0..buffer.len()).step_by(2) {
-                    let sample = voice.render_sample(self.sample_rate, self.pitch_bend);
-                    // Stereo output (same signal to both channels)
-                    buffer[i] += sample;
+                    let frame = i / 2;
+                    let sample = voice.render_sample(self.sample_rate, self.pitch_bend + vibrato(frame), brightness(frame))
+                        * expression.at(coefficient, frame);
+                    // Stereo output, panned per voice
+                    buffer[i] += sample * voice.pan_gains[0];
                     if i + 1 < buffer.len() {
-                        buffer[i + 1] += sample;
+                        buffer[i + 1] += sample * voice.pan_gains[1];
                     }
                 }
             }
         }
 
+        let frames = buffer.len().div_ceil(2);
+        self.vibrato_phase = (vibrato_phase + frames as f32 * vibrato_step).fract();
+        self.filter_control.advance(coefficient, frames);
+        self.expression.advance(coefficient, frames);
+
         // Soft limiter to prevent clipping
         for sample in buffer.iter_mut() {
             *sample = sample.clamp(-1.0, 1.0);
@@ -289,22 +590,93 @@ impl FallbackSynth {
     pub fn active_voice_count(&self) -> usize {
         self.voices.iter().filter(|v| v.is_active()).count()
     }
+}
+
+/// Per-frame smoothing coefficient for a smoothing factor per controller update
+fn control_coefficient(factor: f32) -> f32 {
+    factor.clamp(0.0, 0.99).powf(1.0 / WHAMMY_SMOOTHING_FRAMES)
+}
+
+/// Notes at or below this pan hard left at full spread (low E on a guitar)
+const SPREAD_LOW_NOTE: f32 = 40.0;
+/// Notes at or above this pan hard right at full spread
+const SPREAD_HIGH_NOTE: f32 = 76.0;
+
+/// Clamp a polyphony to `MIN_VOICES`-`MAX_VOICES`
+pub fn clamp_max_voices(voices: usize) -> usize {
+    voices.clamp(MIN_VOICES, MAX_VOICES)
+}
+
+/// Clamp a stereo spread width to 0.0-1.0
+pub fn clamp_stereo_spread(width: f32) -> f32 {
+    if width.is_finite() {
+        width.clamp(0.0, 1.0)
+    } else {
+        0.0
+    }
+}
+
+/// Pan position (-1.0 left to 1.0 right) for a note: low strings left, high strings right
+pub fn note_pan(note: u8, width: f32) -> f32 {
+    let position = (note as f32 - SPREAD_LOW_NOTE) / (SPREAD_HIGH_NOTE - SPREAD_LOW_NOTE);
+    (position.clamp(0.0, 1.0) * 2.0 - 1.0) * clamp_stereo_spread(width)
+}
+
+/// Left/right gains for a pan position. Centered notes stay at full level in
+/// both channels, so a width of 0.0 matches the old mono output.
+fn pan_gains(pan: f32) -> [f32; 2] {
+    [(1.0 - pan).min(1.0), (1.0 + pan).min(1.0)]
+}
 
-    fn find_free_voice(&mut self) -> Option<&mut Voice> {
-        self.voices.iter_mut().find(|v| !v.is_active())
+/// Standard concert pitch
+pub const DEFAULT_A4_FREQUENCY: f32 = 440.0;
+/// Supported range for the A4 reference
+pub const MIN_A4_FREQUENCY: f32 = 432.0;
+pub const MAX_A4_FREQUENCY: f32 = 446.0;
+
+/// Clamp an A4 reference to the supported range
+pub fn clamp_a4_frequency(frequency: f32) -> f32 {
+    if frequency.is_finite() {
+        frequency.clamp(MIN_A4_FREQUENCY, MAX_A4_FREQUENCY)
+    } else {
+        DEFAULT_A4_FREQUENCY
     }
 }
 
-/// Convert MIDI note number to frequency in Hz
-fn midi_to_frequency(note: u8) -> f32 {
-    440.0 * 2.0_f32.powf((note as f32 - 69.0) / 12.0)
+/// Offset in cents of an A4 reference from A440
+pub fn a4_offset_cents(frequency: f32) -> f32 {
+    1200.0 * (clamp_a4_frequency(frequency) / DEFAULT_A4_FREQUENCY).log2()
+}
+
+/// Feedback gain that fades a string at `frequency` by 60dB over `decay`
+/// seconds (the damping filter still takes the upper harmonics out sooner)
+fn pluck_feedback(frequency: f32, decay: f32) -> f32 {
+    let trips = frequency.max(1.0) * decay.max(0.01);
+    0.001_f32.powf(1.0 / trips)
+}
+
+/// Damped (palm-muted) variant of an instrument: short release, darker
+/// filter and plucked strings that die out quickly
+fn palm_muted(settings: InstrumentSettings) -> InstrumentSettings {
+    let mut muted = settings;
+    muted.release_time = (settings.release_time * PALM_MUTE_RELEASE_SCALE).max(0.02);
+    muted.filter_cutoff = settings.filter_cutoff * PALM_MUTE_FILTER_SCALE;
+    if let WaveType::Pluck { decay } = settings.wave_type {
+        muted.wave_type = WaveType::Pluck { decay: decay.min(PALM_MUTE_HOLD_DECAY) };
+    }
+    muted
+}
+
+/// Convert MIDI note number to frequency in Hz for a given A4 reference
+pub(crate) fn midi_to_frequency(note: u8, a4_frequency: f32) -> f32 {
+    a4_frequency * 2.0_f32.powf((note as f32 - 69.0) / 12.0)
 }
 
 /// Get settings for different instrument types
 fn get_instrument_settings(instrument: InstrumentType) -> InstrumentSettings {
     match instrument {
         InstrumentType::CleanElectricGuitar => InstrumentSettings {
-            wave_type: WaveType::Saw,
+            wave_type: WaveType::Pluck { decay: 3.0 },
             attack_time: 0.005,
             release_time: 1.0,
             filter_cutoff: 0.8,
@@ -322,7 +694,7 @@ fn get_instrument_settings(instrument: InstrumentType) -> InstrumentSettings {
             volume: 0.35,
         },
         InstrumentType::AcousticGuitar => InstrumentSettings {
-            wave_type: WaveType::Triangle,
+            wave_type: WaveType::Pluck { decay: 4.0 },
             attack_time: 0.02,
             release_time: 2.0,
             filter_cutoff: 0.7,
@@ -331,7 +703,7 @@ fn get_instrument_settings(instrument: InstrumentType) -> InstrumentSettings {
             volume: 0.45,
         },
         InstrumentType::ClassicalGuitar => InstrumentSettings {
-            wave_type: WaveType::Triangle,
+            wave_type: WaveType::Pluck { decay: 3.5 },
             attack_time: 0.03,
             release_time: 2.5,
             filter_cutoff: 0.6,
@@ -418,15 +790,98 @@ fn get_instrument_settings(instrument: InstrumentType) -> InstrumentSettings {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_voice_stealing_prefers_released_voices() {
+        let mut synth = FallbackSynth::new(48000);
+        synth.set_max_voices(2);
+        assert_eq!(synth.max_voices(), MIN_VOICES);
+        for note in 60..64 {
+            synth.note_on(note, 100);
+        }
+        assert_eq!(synth.take_stolen_voices(), 0);
+
+        // The released note goes first, even though 60 is older
+        synth.note_off(62);
+        synth.note_on(70, 100);
+        let sounding = |synth: &FallbackSynth| {
+            let mut notes: Vec<u8> = synth.voices.iter().filter(|v| v.is_active()).map(|v| v.note).collect();
+            notes.sort();
+            notes
+        };
+        assert_eq!(sounding(&synth), vec![60, 61, 63, 70]);
+
+        // With every note held the oldest goes
+        synth.note_on(71, 100);
+        assert_eq!(sounding(&synth), vec![61, 63, 70, 71]);
+        assert_eq!(synth.take_stolen_voices(), 2);
+        assert_eq!(synth.take_stolen_voices(), 0);
+    }
+
     #[test]
     fn test_midi_to_frequency() {
-        let a440 = midi_to_frequency(69);
+        let a440 = midi_to_frequency(69, DEFAULT_A4_FREQUENCY);
         assert!((a440 - 440.0).abs() < 0.1);
         
-        let middle_c = midi_to_frequency(60);
+        let middle_c = midi_to_frequency(60, DEFAULT_A4_FREQUENCY);
         assert!((middle_c - 261.63).abs() < 1.0);
     }
 
+    #[test]
+    fn test_a4_reference() {
+        assert!((midi_to_frequency(69, 432.0) - 432.0).abs() < 0.01);
+        assert!((a4_offset_cents(432.0) + 31.77).abs() < 0.05);
+        assert_eq!(clamp_a4_frequency(400.0), MIN_A4_FREQUENCY);
+        assert_eq!(clamp_a4_frequency(f32::NAN), DEFAULT_A4_FREQUENCY);
+    }
+
+    #[test]
+    fn test_stereo_spread() {
+        assert_eq!(note_pan(40, 1.0), -1.0);
+        assert_eq!(note_pan(76, 0.5), 0.5);
+        assert_eq!(note_pan(58, 1.0), 0.0);
+        assert_eq!(pan_gains(note_pan(40, 0.0)), [1.0, 1.0]);
+
+        // A low note at full spread only sounds in the left channel
+        let mut synth = FallbackSynth::new(48000);
+        synth.set_stereo_spread(1.0);
+        synth.note_on(40, 100);
+        let mut buffer = vec![0.0; 256];
+        synth.render(&mut buffer);
+        assert!(buffer.iter().step_by(2).any(|&s| s.abs() > 0.001));
+        assert!(buffer.iter().skip(1).step_by(2).all(|&s| s == 0.0));
+    }
+
+    #[test]
+    fn test_pluck_is_pitched_and_decays() {
+        let mut synth = FallbackSynth::new(48000);
+        synth.set_instrument(InstrumentType::AcousticGuitar);
+        synth.note_on(69, 127);
+
+        let rms = |synth: &mut FallbackSynth| {
+            let mut buffer = vec![0.0; 4800 * 2];
+            synth.render(&mut buffer);
+            (buffer.iter().map(|s| s * s).sum::<f32>() / buffer.len() as f32).sqrt()
+        };
+        let early = rms(&mut synth);
+        for _ in 0..9 {
+            rms(&mut synth);
+        }
+        let late = rms(&mut synth);
+        assert!(early > 0.01);
+        assert!(late < early * 0.5);
+
+        // The delay line repeats once per period (48000 / 440 ≈ 109 samples)
+        let mut voice = Voice::new();
+        voice.frequency = 440.0;
+        voice.velocity = 1.0;
+        voice.pluck(48000, 4.0);
+        let samples: Vec<f32> = (0..2000).map(|_| voice.render_pluck(48000, 440.0)).collect();
+        let error: f32 = (1500..1600).map(|i| (samples[i] - samples[i + 109]).abs()).sum::<f32>();
+        let energy: f32 = (1500..1600).map(|i| samples[i].abs()).sum::<f32>();
+        assert!(energy > 0.0);
+        assert!(error < energy * 0.5);
+    }
+
     #[test]
     fn test_synth_note_on() {
         let mut synth = FallbackSynth::new(48000);
@@ -448,4 +903,77 @@ mod tests {
         let has_signal = buffer.iter().any(|&s| s.abs() > 0.001);
         assert!(has_signal);
     }
+
+    #[test]
+    fn test_note_pitch_bend_only_moves_that_note() {
+        let mut synth = FallbackSynth::new(48000);
+        synth.note_on(52, 100);
+        synth.note_on(64, 100);
+        synth.set_note_pitch_bend(64, 4096);
+        let bend = |synth: &FallbackSynth, note: u8| {
+            synth.voices.iter().find(|voice| voice.note == note && voice.is_active()).map(|voice| voice.note_bend)
+        };
+        assert_eq!(bend(&synth, 52), Some(0.0));
+        assert_eq!(bend(&synth, 64), Some(1.0));
+
+        // Restriking the note starts it in tune
+        synth.note_off(64);
+        synth.note_on(64, 100);
+        assert!(synth.voices.iter().filter(|voice| voice.note == 64 && voice.envelope_stage == EnvelopeStage::Attack).all(|voice| voice.note_bend == 0.0));
+    }
+
+    #[test]
+    fn test_modulation_wobbles_pitch() {
+        let mut synth = FallbackSynth::new(48000);
+        synth.set_modulation(127);
+        assert_eq!(synth.vibrato_depth, MAX_VIBRATO_SEMITONES);
+        synth.note_on(69, 100);
+        let mut buffer = vec![0.0; 4800 * 2];
+        synth.render(&mut buffer);
+        // The LFO keeps running between buffers (100ms at 5.5Hz)
+        assert!((synth.vibrato_phase - 0.55).abs() < 1e-3);
+
+        synth.set_modulation(0);
+        assert_eq!(synth.vibrato_depth, 0.0);
+    }
+
+    #[test]
+    fn test_whammy_controls_glide() {
+        let mut synth = FallbackSynth::new(48000);
+        synth.set_whammy_smoothing(0.5);
+        synth.set_expression(0);
+        synth.set_filter_cutoff(0);
+        let mut buffer = vec![0.0; 256 * 2];
+        synth.render(&mut buffer);
+        // Half of the way is left after one controller update
+        assert!((synth.expression.value - 0.5).abs() < 1e-3);
+        assert!((synth.filter_control.value - 0.5).abs() < 1e-3);
+
+        // Without smoothing the value jumps
+        synth.set_whammy_smoothing(0.0);
+        synth.set_expression(127);
+        synth.render(&mut buffer);
+        assert_eq!(synth.expression.value, 1.0);
+    }
+
+    #[test]
+    fn test_palm_mute_damps_held_notes() {
+        let settings = get_instrument_settings(InstrumentType::CleanElectricGuitar);
+        let muted = palm_muted(settings);
+        assert!(muted.release_time < settings.release_time);
+        assert!(muted.filter_cutoff < settings.filter_cutoff);
+
+        // A held palm-muted note dies out; an open one keeps ringing
+        let mut open = FallbackSynth::new(48000);
+        let mut damped = FallbackSynth::new(48000);
+        damped.set_palm_mute(true);
+        open.note_on(52, 100);
+        damped.note_on(52, 100);
+        let mut buffer = vec![0.0; 48000 * 2];
+        open.render(&mut buffer);
+        damped.render(&mut buffer);
+        assert_eq!(open.active_voice_count(), 1);
+        assert_eq!(damped.active_voice_count(), 0);
+    }
 }
+
@@ -0,0 +1,166 @@
+//! Alternate temperaments for the fallback synth
+//! A tuning table holds a cent offset from 12-TET for each pitch class,
+//! counted from a root note, so non-equal temperaments can be played in any key.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+use crate::synth::midi_to_frequency;
+
+/// Largest offset allowed for a custom pitch class
+pub const MAX_CUSTOM_OFFSET_CENTS: f32 = 100.0;
+
+/// 5-limit just intonation ratios for the 12 intervals above the root
+const JUST_RATIOS: [(f32, f32); 12] = [
+    (1.0, 1.0),
+    (16.0, 15.0),
+    (9.0, 8.0),
+    (6.0, 5.0),
+    (5.0, 4.0),
+    (4.0, 3.0),
+    (45.0, 32.0),
+    (3.0, 2.0),
+    (8.0, 5.0),
+    (5.0, 3.0),
+    (9.0, 5.0),
+    (15.0, 8.0),
+];
+
+/// Built-in temperament presets
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Temperament {
+    /// Standard 12-tone equal temperament
+    #[serde(rename = "equal")]
+    Equal,
+    /// 5-limit just intonation relative to the root
+    #[serde(rename = "just")]
+    Just,
+    /// Each key mapped to the nearest step of 19-tone equal temperament
+    #[serde(rename = "19-tet")]
+    Edo19,
+    /// User-defined cent offsets per pitch class
+    #[serde(rename = "custom")]
+    Custom,
+}
+
+impl Temperament {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Temperament::Equal => "equal",
+            Temperament::Just => "just",
+            Temperament::Edo19 => "19-tet",
+            Temperament::Custom => "custom",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name.to_lowercase().as_str() {
+            "equal" | "12-tet" => Ok(Temperament::Equal),
+            "just" => Ok(Temperament::Just),
+            "19-tet" | "19tet" => Ok(Temperament::Edo19),
+            "custom" => Ok(Temperament::Custom),
+            _ => anyhow::bail!("Unknown temperament: {}", name),
+        }
+    }
+
+    pub fn all() -> [Temperament; 4] {
+        [Temperament::Equal, Temperament::Just, Temperament::Edo19, Temperament::Custom]
+    }
+}
+
+/// Cent offsets from 12-TET per interval above the root pitch class
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TuningTable {
+    /// Root pitch class (0 = C)
+    pub root: u8,
+    pub offsets: [f32; 12],
+}
+
+impl TuningTable {
+    pub fn equal() -> Self {
+        Self { root: 0, offsets: [0.0; 12] }
+    }
+
+    /// Table for a preset temperament (`Custom` yields equal temperament)
+    pub fn preset(temperament: Temperament, root: u8) -> Self {
+        let mut offsets = [0.0; 12];
+        for (interval, offset) in offsets.iter_mut().enumerate() {
+            let equal_cents = interval as f32 * 100.0;
+            *offset = match temperament {
+                Temperament::Equal | Temperament::Custom => 0.0,
+                Temperament::Just => {
+                    let (num, den) = JUST_RATIOS[interval];
+                    1200.0 * (num / den).log2() - equal_cents
+                }
+                Temperament::Edo19 => {
+                    let step = (interval as f32 * 19.0 / 12.0).round();
+                    step * 1200.0 / 19.0 - equal_cents
+                }
+            };
+        }
+        Self { root: root % 12, offsets }
+    }
+
+    /// Table from user-defined offsets (12 values, within +/-100 cents)
+    pub fn custom(root: u8, cents: &[f32]) -> Result<Self> {
+        let offsets: [f32; 12] = cents.try_into()
+            .map_err(|_| anyhow::anyhow!("Expected 12 cent offsets, got {}", cents.len()))?;
+        if let Some(bad) = offsets.iter().find(|c| !c.is_finite() || c.abs() > MAX_CUSTOM_OFFSET_CENTS) {
+            anyhow::bail!("Cent offset {} out of range (+/-{})", bad, MAX_CUSTOM_OFFSET_CENTS);
+        }
+        Ok(Self { root: root % 12, offsets })
+    }
+
+    /// Offset in cents for a MIDI note
+    pub fn offset_cents(&self, note: u8) -> f32 {
+        let interval = (note as i32 - self.root as i32).rem_euclid(12) as usize;
+        self.offsets[interval]
+    }
+
+    /// Frequency of a MIDI note for the given A4 reference
+    pub fn frequency(&self, note: u8, a4_frequency: f32) -> f32 {
+        midi_to_frequency(note, a4_frequency) * 2.0_f32.powf(self.offset_cents(note) / 1200.0)
+    }
+}
+
+impl Default for TuningTable {
+    fn default() -> Self {
+        Self::equal()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_just_major_third() {
+        // D major root: F#4 is a pure 5/4 above D4
+        let table = TuningTable::preset(Temperament::Just, 2);
+        let ratio = table.frequency(66, 440.0) / table.frequency(62, 440.0);
+        assert!((ratio - 1.25).abs() < 1e-4);
+        assert!((table.offset_cents(66) + 13.69).abs() < 0.01);
+        assert_eq!(table.offset_cents(62), 0.0);
+    }
+
+    #[test]
+    fn test_19_tet_fifth() {
+        let table = TuningTable::preset(Temperament::Edo19, 0);
+        // The fifth is step 11 of 19: 694.74 cents
+        assert!((table.offset_cents(7) + 5.26).abs() < 0.01);
+        assert_eq!(TuningTable::equal().frequency(69, 440.0), 440.0);
+    }
+
+    #[test]
+    fn test_custom_table_validation() {
+        assert!(TuningTable::custom(0, &[0.0; 11]).is_err());
+        let mut cents = [0.0; 12];
+        cents[4] = -14.0;
+        let table = TuningTable::custom(12, &cents).unwrap();
+        assert_eq!(table.root, 0);
+        assert_eq!(table.offset_cents(64), -14.0);
+        cents[4] = 150.0;
+        assert!(TuningTable::custom(0, &cents).is_err());
+        assert_eq!(Temperament::from_name("19-TET").unwrap(), Temperament::Edo19);
+    }
+}
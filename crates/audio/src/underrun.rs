@@ -0,0 +1,136 @@
+//! Buffer underrun detection
+//! cpal doesn't report underruns directly, so the callback flags one when
+//! rendering took longer than the buffer lasts, or when the gap since the
+//! previous callback shows the device ran dry waiting for us.
+
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Callback gap (in buffer durations) that counts as a missed deadline
+const CALLBACK_GAP_TOLERANCE: f64 = 1.9;
+
+/// Underruns within `UNDERRUN_WINDOW` that trigger an alert
+pub const UNDERRUN_ALERT_THRESHOLD: u64 = 5;
+const UNDERRUN_WINDOW: Duration = Duration::from_secs(10);
+/// Minimum time between alerts
+const UNDERRUN_ALERT_COOLDOWN: Duration = Duration::from_secs(30);
+
+/// Largest buffer size suggested to the user
+const MAX_SUGGESTED_BUFFER: u32 = 2048;
+
+/// Per-callback underrun check (runs on the audio thread, no allocations)
+pub struct UnderrunDetector {
+    sample_rate: u32,
+}
+
+impl UnderrunDetector {
+    pub fn new(sample_rate: u32) -> Self {
+        Self { sample_rate }
+    }
+
+    fn buffer_duration(&self, frames: usize) -> Duration {
+        Duration::from_secs_f64(frames as f64 / self.sample_rate.max(1) as f64)
+    }
+
+    /// Check one callback: `render_time` is how long it took to fill `frames`,
+    /// `callback_gap` the time since the previous callback (from cpal timestamps)
+    pub fn check(&self, frames: usize, render_time: Duration, callback_gap: Option<Duration>) -> bool {
+        if frames == 0 {
+            return false;
+        }
+        let budget = self.buffer_duration(frames);
+        let late = callback_gap.is_some_and(|gap| gap.as_secs_f64() > budget.as_secs_f64() * CALLBACK_GAP_TOLERANCE);
+        render_time > budget || late
+    }
+}
+
+/// Sent to the frontend when underruns pile up
+#[derive(Debug, Clone, Serialize)]
+pub struct UnderrunAlert {
+    /// Underruns within the last window
+    pub recent: u64,
+    pub total: u64,
+    pub buffer_size: u32,
+    pub suggested_buffer_size: u32,
+}
+
+/// Watches the underrun counter and raises an alert when too many happen in a short window
+pub struct UnderrunMonitor {
+    history: VecDeque<(Instant, u64)>,
+    last_alert: Option<Instant>,
+}
+
+impl UnderrunMonitor {
+    pub fn new() -> Self {
+        Self {
+            history: VecDeque::new(),
+            last_alert: None,
+        }
+    }
+
+    /// Feed the current total underrun count
+    pub fn update(&mut self, total: u64, buffer_size: u32, now: Instant) -> Option<UnderrunAlert> {
+        self.history.push_back((now, total));
+        while let Some(&(at, _)) = self.history.front() {
+            if now.duration_since(at) > UNDERRUN_WINDOW {
+                self.history.pop_front();
+            } else {
+                break;
+            }
+        }
+
+        let oldest = self.history.front().map(|&(_, count)| count).unwrap_or(total);
+        let recent = total.saturating_sub(oldest);
+        let cooling_down = self.last_alert.is_some_and(|at| now.duration_since(at) < UNDERRUN_ALERT_COOLDOWN);
+        if recent < UNDERRUN_ALERT_THRESHOLD || cooling_down {
+            return None;
+        }
+
+        self.last_alert = Some(now);
+        Some(UnderrunAlert {
+            recent,
+            total,
+            buffer_size,
+            suggested_buffer_size: (buffer_size.max(64) * 2).min(MAX_SUGGESTED_BUFFER),
+        })
+    }
+}
+
+impl Default for UnderrunMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detector_flags_slow_render_and_late_callback() {
+        // 256 frames at 48kHz last 5.33ms
+        let detector = UnderrunDetector::new(48000);
+        assert!(!detector.check(256, Duration::from_millis(2), Some(Duration::from_micros(5333))));
+        assert!(detector.check(256, Duration::from_millis(6), None));
+        assert!(detector.check(256, Duration::from_millis(1), Some(Duration::from_millis(12))));
+        assert!(!detector.check(0, Duration::from_millis(6), None));
+    }
+
+    #[test]
+    fn test_monitor_threshold_and_cooldown() {
+        let start = Instant::now();
+        let mut monitor = UnderrunMonitor::new();
+        assert!(monitor.update(0, 256, start).is_none());
+        assert!(monitor.update(3, 256, start + Duration::from_secs(1)).is_none());
+
+        let alert = monitor.update(6, 256, start + Duration::from_secs(2)).unwrap();
+        assert_eq!(alert.recent, 6);
+        assert_eq!(alert.suggested_buffer_size, 512);
+
+        // More underruns during the cooldown stay quiet
+        assert!(monitor.update(20, 256, start + Duration::from_secs(3)).is_none());
+        // Old underruns fall out of the window
+        assert!(monitor.update(20, 256, start + Duration::from_secs(40)).is_none());
+    }
+}
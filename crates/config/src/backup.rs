@@ -0,0 +1,228 @@
+//! User data backup
+//! Everything worth carrying to another machine (config, mapping and config
+//! profiles, genre preset overrides, the song library and its stats) is
+//! bundled into one deflated zip archive with a `manifest.json` describing
+//! it. Files are streamed into and out of the archive one at a time. The
+//! manifest is versioned: a backup from a newer build is refused rather than
+//! half understood. Uploaded SoundFonts are left out, they are large and
+//! easy to add again. Importing overwrites files with the same name and
+//! leaves everything else alone.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+use crate::paths::AppPaths;
+
+/// Manifest layout this build writes and reads
+pub const BACKUP_FORMAT_VERSION: u32 = 1;
+const MANIFEST_NAME: &str = "manifest.json";
+
+/// Describes a backup archive
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BackupManifest {
+    pub format_version: u32,
+    /// Build that exported the backup
+    pub app_version: String,
+    /// Seconds since the Unix epoch
+    pub created_at: u64,
+    /// Archive paths of the files, e.g. "songs/Blues in A.mitychart.json"
+    pub files: Vec<String>,
+}
+
+/// Outcome of an import
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ImportReport {
+    pub format_version: u32,
+    pub app_version: String,
+    pub restored: Vec<String>,
+    /// Archive entries that don't belong to any known location
+    pub skipped: Vec<String>,
+}
+
+/// Where each part of a backup lives on this machine
+#[derive(Debug, Clone)]
+pub struct BackupLocations {
+    config_file: PathBuf,
+    stats_file: PathBuf,
+    /// Archive folder name and local directory
+    dirs: Vec<(&'static str, PathBuf)>,
+}
+
+impl BackupLocations {
+    /// The app data layout, plus the genre preset directory when there is one
+    pub fn new(paths: &AppPaths, presets_dir: Option<PathBuf>) -> Self {
+        let mut dirs = vec![
+            ("mapping_profiles", paths.profiles_dir.clone()),
+            ("config_profiles", paths.config_profiles_dir.clone()),
+            ("songs", paths.songs_dir.clone()),
+        ];
+        dirs.extend(presets_dir.map(|dir| ("presets", dir)));
+        Self {
+            config_file: paths.config_file.clone(),
+            stats_file: paths.stats_file.clone(),
+            dirs,
+        }
+    }
+
+    /// Local file for an archive path (None = unknown or unsafe path)
+    fn local_path(&self, name: &str) -> Option<PathBuf> {
+        match name {
+            "config.json" => return Some(self.config_file.clone()),
+            "song_stats.json" => return Some(self.stats_file.clone()),
+            _ => {}
+        }
+        let (folder, rest) = name.split_once('/')?;
+        let relative = Path::new(rest);
+        // Never write outside the data folders
+        if rest.is_empty() || !relative.components().all(|component| matches!(component, Component::Normal(_))) {
+            return None;
+        }
+        self.dirs.iter().find(|(name, _)| *name == folder).map(|(_, dir)| dir.join(relative))
+    }
+}
+
+/// Bundle the user data into a zip archive at `path`
+pub fn export_user_data(locations: &BackupLocations, path: &Path) -> Result<BackupManifest> {
+    let mut files = Vec::new();
+    for (name, file) in [("config.json", &locations.config_file), ("song_stats.json", &locations.stats_file)] {
+        if file.is_file() {
+            files.push((name.to_string(), file.clone()));
+        }
+    }
+    for (folder, dir) in &locations.dirs {
+        collect_files(dir, folder, &mut files);
+    }
+
+    let manifest = BackupManifest {
+        format_version: BACKUP_FORMAT_VERSION,
+        app_version: env!("CARGO_PKG_VERSION").to_string(),
+        created_at: std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs()),
+        files: files.iter().map(|(name, _)| name.clone()).collect(),
+    };
+
+    let archive = File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut zip = ZipWriter::new(archive);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file(MANIFEST_NAME, options)?;
+    serde_json::to_writer_pretty(&mut zip, &manifest).context("Failed to write backup manifest")?;
+    for (name, file) in &files {
+        let mut source = File::open(file).with_context(|| format!("Failed to read {}", file.display()))?;
+        zip.start_file(name.as_str(), options)?;
+        io::copy(&mut source, &mut zip).with_context(|| format!("Failed to add {} to the backup", file.display()))?;
+    }
+    zip.finish().with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(manifest)
+}
+
+/// Restore the user data from a backup archive
+pub fn import_user_data(locations: &BackupLocations, path: &Path) -> Result<ImportReport> {
+    let archive = File::open(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    let mut zip = ZipArchive::new(archive).with_context(|| format!("{} is not a zip archive", path.display()))?;
+    let manifest: BackupManifest = match zip.by_name(MANIFEST_NAME) {
+        Ok(entry) => serde_json::from_reader(entry).context("Failed to parse backup manifest")?,
+        Err(zip::result::ZipError::FileNotFound) => anyhow::bail!("Not a mITyGuitar backup (no manifest)"),
+        Err(e) => return Err(e).context("Failed to read backup manifest"),
+    };
+    if manifest.format_version > BACKUP_FORMAT_VERSION {
+        anyhow::bail!(
+            "Backup was made by mITyGuitar {} (format {}), this build reads format {} and older",
+            manifest.app_version, manifest.format_version, BACKUP_FORMAT_VERSION
+        );
+    }
+
+    let mut report = ImportReport {
+        format_version: manifest.format_version,
+        app_version: manifest.app_version,
+        ..Default::default()
+    };
+    for index in 0..zip.len() {
+        let mut entry = zip.by_index(index)?;
+        let name = entry.name().to_string();
+        if entry.is_dir() || name == MANIFEST_NAME {
+            continue;
+        }
+        let Some(destination) = locations.local_path(&name) else {
+            report.skipped.push(name);
+            continue;
+        };
+        if let Some(parent) = destination.parent() {
+            fs::create_dir_all(parent).with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let mut file = File::create(&destination).with_context(|| format!("Failed to write {}", destination.display()))?;
+        // The entry's checksum is verified as the last bytes are read
+        io::copy(&mut entry, &mut file).with_context(|| format!("Failed to restore {}", name))?;
+        report.restored.push(name);
+    }
+    Ok(report)
+}
+
+/// Add the files under `dir` as `(<folder>/<relative path>, local path)`
+fn collect_files(dir: &Path, folder: &str, files: &mut Vec<(String, PathBuf)>) {
+    let Ok(dir_entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = dir_entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+    for path in paths {
+        let Some(file_name) = path.file_name().and_then(|name| name.to_str()) else {
+            continue;
+        };
+        let name = format!("{}/{}", folder, file_name);
+        if path.is_dir() {
+            collect_files(&path, &name, files);
+        } else {
+            files.push((name, path));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_backup_round_trip_between_machines() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        let from = AppPaths::at(dir.join("from"));
+        from.ensure_dirs().unwrap();
+        fs::write(&from.config_file, "{\"version\": 1}").unwrap();
+        fs::create_dir_all(from.profiles_dir.join("backups")).unwrap();
+        fs::write(from.profiles_dir.join("Guitar.json"), "profile").unwrap();
+        fs::write(from.profiles_dir.join("backups").join("Guitar.1.json"), "older").unwrap();
+        fs::write(from.songs_dir.join("Blues.mitychart.json"), "song").unwrap();
+        let presets = dir.join("from-presets");
+        fs::create_dir_all(&presets).unwrap();
+        fs::write(presets.join("rock.json"), "preset").unwrap();
+
+        let archive = dir.join("backup.zip");
+        let manifest = export_user_data(&BackupLocations::new(&from, Some(presets)), &archive).unwrap();
+        assert_eq!(manifest.format_version, BACKUP_FORMAT_VERSION);
+        assert_eq!(manifest.files.len(), 5);
+        let mut zip = ZipArchive::new(File::open(&archive).unwrap()).unwrap();
+        assert_eq!(zip.by_name("songs/Blues.mitychart.json").unwrap().compression(), CompressionMethod::Deflated);
+
+        let to = AppPaths::at(dir.join("to"));
+        let to_presets = dir.join("to-presets");
+        let report = import_user_data(&BackupLocations::new(&to, Some(to_presets.clone())), &archive).unwrap();
+        assert_eq!(report.restored.len(), 5);
+        assert_eq!(fs::read_to_string(to.profiles_dir.join("backups").join("Guitar.1.json")).unwrap(), "older");
+        assert_eq!(fs::read_to_string(&to.config_file).unwrap(), "{\"version\": 1}");
+        assert_eq!(fs::read_to_string(to_presets.join("rock.json")).unwrap(), "preset");
+
+        // Paths escaping the data folders are never written
+        let locations = BackupLocations::new(&to, None);
+        assert!(locations.local_path("songs/../../evil.json").is_none());
+        assert!(locations.local_path("presets/rock.json").is_none());
+        fs::write(dir.join("not-a-backup.zip"), "not a zip").unwrap();
+        assert!(import_user_data(&locations, &dir.join("not-a-backup.zip")).is_err());
+    }
+}
@@ -1,10 +1,29 @@
 use anyhow::{Context, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+pub mod backup;
+pub mod migration;
+pub mod paths;
+pub mod profiles;
+pub mod watch;
+pub use backup::{export_user_data, import_user_data, BackupLocations, BackupManifest, ImportReport};
+pub use paths::{AppPaths, MigrationReport};
+pub use profiles::{ConfigProfile, ConfigProfileStore};
+pub use watch::FileWatcher;
 
 const CONFIG_FILE_NAME: &str = "mityguitar_config.json";
-const CONFIG_VERSION: u32 = 1;
+const CONFIG_VERSION: u32 = 2;
+
+/// Fields of a config section this build doesn't know (e.g. from a newer
+/// build). Flattened into the section, so they are kept on load and written
+/// back on save.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct UnknownFields(pub Map<String, Value>);
 
 /// Main application configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -14,12 +33,27 @@ pub struct AppConfig {
     pub audio: AudioConfig,
     pub soundfonts: SoundFontConfig,
     pub mapping: MappingConfig,
+    #[serde(default)]
+    pub song: SongConfig,
+    /// Stream overlay feed
+    #[serde(default)]
+    pub overlay: OverlayConfig,
+    /// Config profile applied last (None = settings changed by hand)
+    #[serde(default)]
+    pub active_profile: Option<String>,
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ControllerConfig {
     pub device_id: String,
     pub simulator_mode: bool,
+    /// Rumble on missed notes and star power (gamepads with force feedback)
+    #[serde(default)]
+    pub rumble: bool,
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,12 +61,123 @@ pub struct AudioConfig {
     pub sample_rate: u32,
     pub buffer_size: u32,
     pub backend: String,
+    /// Output device name (None = system default)
+    #[serde(default)]
+    pub output_device: Option<String>,
     #[serde(default = "default_release_multiplier")]
     pub release_time_multiplier: f32,
     #[serde(default)]
     pub sustain_enabled: bool,
     #[serde(default = "default_sustain_release_time")]
     pub sustain_release_time_ms: f32,
+    /// A4 reference frequency in Hz (master tuning)
+    #[serde(default = "default_a4_frequency")]
+    pub a4_frequency: f32,
+    /// Temperament for the virtual instruments
+    #[serde(default)]
+    pub temperament: TemperamentConfig,
+    /// Chord stereo spread width (0.0 = mono, 1.0 = full stereo)
+    #[serde(default)]
+    pub stereo_spread: f32,
+    /// Notes each virtual instrument plays at once before stealing voices
+    #[serde(default = "default_max_voices")]
+    pub max_voices: usize,
+    /// Monitoring of a guitar/mic input through the output
+    #[serde(default)]
+    pub input_monitor: InputMonitorConfig,
+    /// Mixer fader gains
+    #[serde(default)]
+    pub mixer: MixerConfig,
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+/// Mixer fader gains (1.0 = unity, up to 2.0)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MixerConfig {
+    pub master: f32,
+    pub synth: f32,
+    pub backing_track: f32,
+    pub metronome: f32,
+    pub drums: f32,
+}
+
+impl Default for MixerConfig {
+    fn default() -> Self {
+        Self { master: 1.0, synth: 1.0, backing_track: 1.0, metronome: 1.0, drums: 1.0 }
+    }
+}
+
+impl MixerConfig {
+    /// Gain of a fader by name ("master", "synth", "backing_track", "metronome" or "drums")
+    pub fn level(&self, channel: &str) -> Option<f32> {
+        match channel {
+            "master" => Some(self.master),
+            "synth" => Some(self.synth),
+            "backing_track" => Some(self.backing_track),
+            "metronome" => Some(self.metronome),
+            "drums" => Some(self.drums),
+            _ => None,
+        }
+    }
+
+    pub fn level_mut(&mut self, channel: &str) -> Option<&mut f32> {
+        match channel {
+            "master" => Some(&mut self.master),
+            "synth" => Some(&mut self.synth),
+            "backing_track" => Some(&mut self.backing_track),
+            "metronome" => Some(&mut self.metronome),
+            "drums" => Some(&mut self.drums),
+            _ => None,
+        }
+    }
+}
+
+/// Temperament selection ("equal", "just", "19-tet" or "custom")
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemperamentConfig {
+    pub preset: String,
+    /// Root pitch class the table is relative to (0 = C)
+    #[serde(default)]
+    pub root: u8,
+    /// Cent offsets per interval above the root, for "custom"
+    #[serde(default)]
+    pub custom_cents: Vec<f32>,
+}
+
+impl Default for TemperamentConfig {
+    fn default() -> Self {
+        Self {
+            preset: "equal".to_string(),
+            root: 0,
+            custom_cents: Vec::new(),
+        }
+    }
+}
+
+/// Input monitoring settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InputMonitorConfig {
+    pub enabled: bool,
+    /// Input device name (None = system default input)
+    #[serde(default)]
+    pub device: Option<String>,
+    /// Monitored channel, 0-based (None = all channels mixed)
+    #[serde(default)]
+    pub channel: Option<u16>,
+    #[serde(default = "default_monitor_gain")]
+    pub gain: f32,
+}
+
+impl Default for InputMonitorConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            device: None,
+            channel: None,
+            gain: default_monitor_gain(),
+        }
+    }
 }
 
 fn default_release_multiplier() -> f32 {
@@ -43,6 +188,70 @@ fn default_sustain_release_time() -> f32 {
     500.0
 }
 
+fn default_a4_frequency() -> f32 {
+    440.0
+}
+
+fn default_max_voices() -> usize {
+    16
+}
+
+fn default_monitor_gain() -> f32 {
+    1.0
+}
+
+fn default_strum_spread_ms() -> f32 {
+    8.0
+}
+
+fn default_dynamics_mode() -> String {
+    "fixed".to_string()
+}
+
+fn default_palm_mute_trigger() -> String {
+    "off".to_string()
+}
+
+fn default_bend_target() -> String {
+    "all_notes".to_string()
+}
+
+fn default_whammy_pull_effect() -> String {
+    "vibrato".to_string()
+}
+
+fn default_change_quantize() -> String {
+    "off".to_string()
+}
+
+fn default_transpose_range() -> u8 {
+    7
+}
+
+fn default_auto_instrument() -> bool {
+    true
+}
+
+fn default_count_in_click() -> bool {
+    true
+}
+
+fn default_difficulty() -> String {
+    "expert".to_string()
+}
+
+fn default_fret_leds() -> String {
+    "next_chord".to_string()
+}
+
+fn default_tilt_threshold() -> f32 {
+    0.7
+}
+
+fn default_tilt_layer_instrument() -> String {
+    "Strings".to_string()
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SoundFontConfig {
     pub current: Option<String>,
@@ -60,9 +269,183 @@ pub struct PresetInfo {
 pub struct MappingConfig {
     pub genre: String,
     pub pattern_index: usize,
+    /// Play through the legacy pattern mapper instead of the genre presets
+    #[serde(default)]
+    pub legacy_mapper: bool,
+    /// Correct preset chord qualities to the key and mode (genre presets only)
+    #[serde(default)]
+    pub diatonic_chords: bool,
+    /// What the whammy controls: "pitch_bend", "vibrato", "filter_cutoff" or "volume_swell"
     pub whammy_mode: String,
     pub fx_switch_mode: String,
+    /// What raising the neck does: "star_power", "filter_sweep", "overdrive"
+    /// or "layer" (fades in `tilt_layer_instrument`)
     pub tilt_mode: String,
+    /// Tilt (0.0-1.0) that counts as raising the neck
+    #[serde(default = "default_tilt_threshold")]
+    pub tilt_threshold: f32,
+    /// Virtual instrument faded in under the guitar when `tilt_mode` is "layer"
+    #[serde(default = "default_tilt_layer_instrument")]
+    pub tilt_layer_instrument: String,
+    /// Load the instrument a genre preset or song asks for when switching to it
+    /// (off = the instrument stays as picked by hand)
+    #[serde(default = "default_auto_instrument")]
+    pub auto_instrument: bool,
+    /// Per-genre voice leading overrides (genre name -> enabled)
+    #[serde(default)]
+    pub voice_leading: HashMap<String, bool>,
+    /// Play chords as six-string guitar shapes, strummed string by string
+    #[serde(default)]
+    pub guitar_voicing: bool,
+    /// Stagger stacked chord notes in strum direction
+    #[serde(default)]
+    pub strum_stagger: bool,
+    /// Delay between successive notes of a strummed chord (ms)
+    #[serde(default = "default_strum_spread_ms")]
+    pub strum_spread_ms: f32,
+    /// What sets strum velocity ("fixed", "whammy" or "strum_speed")
+    #[serde(default = "default_dynamics_mode")]
+    pub dynamics_mode: String,
+    /// Input that holds palm mute ("off", "select_held" or "tilt_down")
+    #[serde(default = "default_palm_mute_trigger")]
+    pub palm_mute_trigger: String,
+    /// Notes the whammy bends ("all_notes" or "top_note")
+    #[serde(default = "default_bend_target")]
+    pub bend_target: String,
+    /// Split the whammy at its center: push bends down, pull applies the pull effect
+    #[serde(default)]
+    pub whammy_split: bool,
+    /// What pulling a split whammy does ("vibrato", "bend_up" or "off")
+    #[serde(default = "default_whammy_pull_effect")]
+    pub whammy_pull_effect: String,
+    /// Defer genre/key/instrument changes while a song plays ("off", "beat" or "bar")
+    #[serde(default = "default_change_quantize")]
+    pub change_quantize: String,
+    /// Semitones the D-pad capo may move the key either way
+    #[serde(default = "default_transpose_range")]
+    pub transpose_range: u8,
+    /// Players (0-based) whose frets play single bass roots instead of chords (genre presets only)
+    #[serde(default)]
+    pub bass_players: Vec<usize>,
+    /// Players (0-based) whose solo frets play the key's scale (genre presets only)
+    #[serde(default)]
+    pub lead_players: Vec<usize>,
+    /// Mapper plugin playing for a player (0-based) in place of the chord mappers
+    #[serde(default)]
+    pub player_plugins: HashMap<usize, String>,
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+/// Song mode settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SongConfig {
+    /// Count-in bars before a song starts (None = as the chart says)
+    #[serde(default)]
+    pub count_in_bars: Option<u32>,
+    /// Click the count-in through the audio engine
+    #[serde(default = "default_count_in_click")]
+    pub count_in_click: bool,
+    /// Difficulty songs are played at (easy, medium, hard, expert)
+    #[serde(default = "default_difficulty")]
+    pub difficulty: String,
+    /// Song hit-detection timing windows
+    #[serde(default)]
+    pub hit_windows: HitWindowConfig,
+    /// Latency taken off every strum in song mode (ms, set by calibration)
+    #[serde(default)]
+    pub input_offset_ms: f64,
+    /// What the fret LEDs show in song mode unless a chart section says
+    /// (off, next_chord, held_chord)
+    #[serde(default = "default_fret_leds")]
+    pub fret_leds: String,
+    /// Speed trainer ramp for practice loops
+    #[serde(default)]
+    pub speed_trainer: SpeedTrainerConfig,
+    /// Misses don't break the combo (play-throughs don't set high scores)
+    #[serde(default)]
+    pub no_fail: bool,
+    #[serde(flatten)]
+    pub unknown: UnknownFields,
+}
+
+impl Default for SongConfig {
+    fn default() -> Self {
+        Self {
+            count_in_bars: None,
+            count_in_click: default_count_in_click(),
+            difficulty: default_difficulty(),
+            hit_windows: HitWindowConfig::default(),
+            input_offset_ms: 0.0,
+            fret_leds: default_fret_leds(),
+            speed_trainer: SpeedTrainerConfig::default(),
+            no_fail: false,
+            unknown: UnknownFields::default(),
+        }
+    }
+}
+
+/// Stream overlay feed settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OverlayConfig {
+    /// Serve the overlay state to browser sources
+    #[serde(default)]
+    pub enabled: bool,
+    /// Local port of the feed (http://127.0.0.1:<port>/)
+    #[serde(default = "default_overlay_port")]
+    pub port: u16,
+}
+
+impl Default for OverlayConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            port: default_overlay_port(),
+        }
+    }
+}
+
+fn default_overlay_port() -> u16 {
+    7878
+}
+
+/// Timing windows either side of a note (ms)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HitWindowConfig {
+    pub perfect_ms: f64,
+    pub good_ms: f64,
+    pub miss_ms: f64,
+}
+
+impl Default for HitWindowConfig {
+    fn default() -> Self {
+        Self {
+            perfect_ms: 60.0,
+            good_ms: 120.0,
+            miss_ms: 250.0,
+        }
+    }
+}
+
+/// Speed trainer ramp: the loop starts at `start_speed` and every pass at or
+/// above `accuracy_threshold` (%) adds `increment`, up to `target_speed`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct SpeedTrainerConfig {
+    pub start_speed: f64,
+    pub increment: f64,
+    pub target_speed: f64,
+    pub accuracy_threshold: f64,
+}
+
+impl Default for SpeedTrainerConfig {
+    fn default() -> Self {
+        Self {
+            start_speed: 0.6,
+            increment: 0.05,
+            target_speed: 1.0,
+            accuracy_threshold: 90.0,
+        }
+    }
 }
 
 impl Default for AppConfig {
@@ -72,14 +455,24 @@ impl Default for AppConfig {
             controller: ControllerConfig {
                 device_id: "auto".to_string(),
                 simulator_mode: true, // Default to simulator for development
+                rumble: false,
+                unknown: UnknownFields::default(),
             },
             audio: AudioConfig {
                 sample_rate: 48000,
                 buffer_size: 256,
-                backend: "fallback".to_string(),
+                backend: "default".to_string(),
+                output_device: None,
                 release_time_multiplier: 1.0,
                 sustain_enabled: false,
                 sustain_release_time_ms: 500.0,
+                a4_frequency: 440.0,
+                temperament: TemperamentConfig::default(),
+                stereo_spread: 0.0,
+                max_voices: default_max_voices(),
+                input_monitor: InputMonitorConfig::default(),
+                mixer: MixerConfig::default(),
+                unknown: UnknownFields::default(),
             },
             soundfonts: SoundFontConfig {
                 current: Some("Electric_guitar.sf2".to_string()),
@@ -92,10 +485,34 @@ impl Default for AppConfig {
             mapping: MappingConfig {
                 genre: "rock".to_string(),
                 pattern_index: 0,
+                legacy_mapper: false,
+                diatonic_chords: false,
                 whammy_mode: "pitch_bend".to_string(),
                 fx_switch_mode: "effects".to_string(),
-                tilt_mode: "filter_cutoff".to_string(),
+                tilt_mode: "star_power".to_string(),
+                tilt_threshold: default_tilt_threshold(),
+                tilt_layer_instrument: default_tilt_layer_instrument(),
+                auto_instrument: default_auto_instrument(),
+                voice_leading: HashMap::new(),
+                guitar_voicing: false,
+                strum_stagger: false,
+                strum_spread_ms: default_strum_spread_ms(),
+                dynamics_mode: default_dynamics_mode(),
+                palm_mute_trigger: default_palm_mute_trigger(),
+                bend_target: default_bend_target(),
+                whammy_split: false,
+                whammy_pull_effect: default_whammy_pull_effect(),
+                change_quantize: default_change_quantize(),
+                transpose_range: default_transpose_range(),
+                bass_players: Vec::new(),
+                lead_players: Vec::new(),
+                player_plugins: HashMap::new(),
+                unknown: UnknownFields::default(),
             },
+            song: SongConfig::default(),
+            overlay: OverlayConfig::default(),
+            active_profile: None,
+            unknown: UnknownFields::default(),
         }
     }
 }
@@ -106,17 +523,7 @@ impl AppConfig {
         let path = Self::config_path()?;
         
         if path.exists() {
-            let data = fs::read_to_string(&path)
-                .context("Failed to read config file")?;
-            let mut config: AppConfig = serde_json::from_str(&data)
-                .context("Failed to parse config file")?;
-            
-            // Migrate if needed
-            if config.version < CONFIG_VERSION {
-                config = Self::migrate(config)?;
-            }
-            
-            Ok(config)
+            Self::load_from(&path)
         } else {
             // Create default config
             let config = Self::default();
@@ -125,10 +532,32 @@ impl AppConfig {
         }
     }
 
+    /// Read a config file. Files of an older version are migrated, saved and
+    /// backed up first (see `migration`).
+    pub fn load_from(path: &Path) -> Result<Self> {
+        let data = fs::read_to_string(path)
+            .context("Failed to read config file")?;
+        let mut value: Value = serde_json::from_str(&data)
+            .context("Failed to parse config file")?;
+        
+        let migrated_from = migration::migrate(&mut value)?;
+        let config: AppConfig = serde_json::from_value(value)
+            .context("Failed to parse config file")?;
+        if let Some(version) = migrated_from {
+            migration::backup_before_migration(path, version)?;
+            config.save_to(path)?;
+        }
+        
+        Ok(config)
+    }
+
     /// Save config to disk
     pub fn save(&self) -> Result<()> {
-        let path = Self::config_path()?;
-        
+        self.save_to(&Self::config_path()?)
+    }
+
+    /// Save config to a file
+    pub fn save_to(&self, path: &Path) -> Result<()> {
         // Ensure parent directory exists
         if let Some(parent) = path.parent() {
             fs::create_dir_all(parent)
@@ -138,7 +567,7 @@ impl AppConfig {
         let data = serde_json::to_string_pretty(self)
             .context("Failed to serialize config")?;
         
-        fs::write(&path, data)
+        fs::write(path, data)
             .context("Failed to write config file")?;
         
         Ok(())
@@ -146,17 +575,7 @@ impl AppConfig {
 
     /// Get the path to the config file
     fn config_path() -> Result<PathBuf> {
-        let config_dir = dirs::config_dir()
-            .context("Could not determine config directory")?;
-        
-        Ok(config_dir.join("mityguitar").join(CONFIG_FILE_NAME))
-    }
-
-    /// Migrate from older config versions
-    fn migrate(mut config: AppConfig) -> Result<Self> {
-        // Handle version migrations here
-        config.version = CONFIG_VERSION;
-        Ok(config)
+        Ok(AppPaths::resolve()?.config_file)
     }
 
     /// Add a SoundFont to recent list
@@ -191,6 +610,23 @@ mod tests {
         assert_eq!(parsed.version, config.version);
     }
 
+    #[test]
+    fn test_load_migrates_and_backs_up_old_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE_NAME);
+        let mut old = serde_json::to_value(AppConfig::default()).unwrap();
+        old["version"] = Value::from(1);
+        old["audio"]["backend"] = Value::from("fallback");
+        fs::write(&path, old.to_string()).unwrap();
+
+        let config = AppConfig::load_from(&path).unwrap();
+        assert_eq!(config.version, CONFIG_VERSION);
+        assert_eq!(config.audio.backend, "default");
+        let backup = migration::backup_path(&path, 1);
+        assert!(fs::read_to_string(backup).unwrap().contains("fallback"));
+        assert_eq!(AppConfig::load_from(&path).unwrap().version, CONFIG_VERSION);
+    }
+
     #[test]
     fn test_recent_soundfonts() {
         let mut config = AppConfig::default();
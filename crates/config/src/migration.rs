@@ -0,0 +1,119 @@
+//! Config schema migrations
+//! The config file carries a `version`. When an older file is loaded its
+//! JSON runs through one transform per version step (v1→v2, v2→v3, ...)
+//! before it is parsed, so a renamed or reshaped setting is carried over
+//! instead of falling back to its default. The file as it was is kept next
+//! to the config (`mityguitar_config.v1.json.bak`) in case a migration gets
+//! something wrong. Fields a build doesn't know are kept in each section's
+//! `unknown` fields and written back, so a newer build's settings survive a
+//! round trip through an older one.
+//!
+//! To change the schema: bump `CONFIG_VERSION`, append the transform from
+//! the previous version to `MIGRATIONS` and add a test with a file as the
+//! previous version wrote it.
+
+use anyhow::{Context, Result};
+use serde_json::{Map, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::CONFIG_VERSION;
+
+/// Turns the JSON of one config version into the next
+type Migration = fn(&mut Map<String, Value>);
+
+/// `MIGRATIONS[n - 1]` migrates version `n` to `n + 1`
+const MIGRATIONS: &[Migration] = &[v1_to_v2];
+
+/// Version of a config file's JSON (files from before versioning count as 1)
+pub fn config_version(config: &Value) -> u32 {
+    config.get("version").and_then(Value::as_u64).map_or(1, |version| version as u32)
+}
+
+/// Bring config JSON up to `CONFIG_VERSION`. Returns the version it had
+/// when something was migrated. Files from newer builds are left as they are.
+pub fn migrate(config: &mut Value) -> Result<Option<u32>> {
+    let from = config_version(config);
+    if from >= CONFIG_VERSION {
+        return Ok(None);
+    }
+    let fields = config.as_object_mut().context("Config file is not a JSON object")?;
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(from.saturating_sub(1) as usize) {
+        migration(fields);
+        fields.insert("version".to_string(), Value::from(index as u32 + 2));
+    }
+    Ok(Some(from))
+}
+
+/// Where the file of an older version is kept after migrating
+pub fn backup_path(config_file: &Path, version: u32) -> PathBuf {
+    let stem = config_file.file_stem().and_then(|stem| stem.to_str()).unwrap_or("config");
+    config_file.with_file_name(format!("{}.v{}.json.bak", stem, version))
+}
+
+/// Keep a copy of the config file before it's overwritten by a migration
+/// (an existing backup of that version is never replaced)
+pub fn backup_before_migration(config_file: &Path, version: u32) -> Result<PathBuf> {
+    let backup = backup_path(config_file, version);
+    if !backup.exists() {
+        fs::copy(config_file, &backup).with_context(|| format!("Failed to back up config to {}", backup.display()))?;
+    }
+    Ok(backup)
+}
+
+/// v2: voice leading is looked up by lowercase genre name, so entries stored
+/// as "Rock" were ignored; the audio backend placeholder "fallback" became "default"
+fn v1_to_v2(config: &mut Map<String, Value>) {
+    if let Some(Value::Object(voice_leading)) = config.get_mut("mapping").and_then(|mapping| mapping.get_mut("voice_leading")) {
+        let entries = std::mem::take(voice_leading);
+        for (genre, enabled) in entries {
+            let lowercase = genre.to_lowercase();
+            // An entry already in lowercase was written by a current build, it wins
+            if genre == lowercase || !voice_leading.contains_key(&lowercase) {
+                voice_leading.insert(lowercase, enabled);
+            }
+        }
+    }
+    if let Some(backend) = config.get_mut("audio").and_then(|audio| audio.get_mut("backend")) {
+        if backend.as_str().is_some_and(|name| name.eq_ignore_ascii_case("fallback")) {
+            *backend = Value::from("default");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AppConfig;
+
+    #[test]
+    fn test_v1_config_migrates_and_keeps_unknown_fields() {
+        let mut config = serde_json::json!({
+            "version": 1,
+            "controller": { "device_id": "auto", "simulator_mode": false, "led_brightness": 3 },
+            "audio": { "sample_rate": 48000, "buffer_size": 128, "backend": "fallback" },
+            "soundfonts": { "current": null, "preset": { "bank": 0, "program": 0 }, "recent": [] },
+            "mapping": {
+                "genre": "punk", "pattern_index": 0, "whammy_mode": "vibrato",
+                "fx_switch_mode": "effects", "tilt_mode": "overdrive",
+                "voice_leading": { "Rock": true, "punk": false }
+            },
+            "practice_goals": { "minutes": 20 }
+        });
+        assert_eq!(migrate(&mut config).unwrap(), Some(1));
+        assert_eq!(config_version(&config), CONFIG_VERSION);
+        assert_eq!(migrate(&mut config).unwrap(), None);
+
+        let parsed: AppConfig = serde_json::from_value(config).unwrap();
+        assert_eq!(parsed.audio.backend, "default");
+        assert_eq!(parsed.audio.buffer_size, 128);
+        assert_eq!(parsed.mapping.voice_leading.get("rock"), Some(&true));
+        assert_eq!(parsed.mapping.voice_leading.get("punk"), Some(&false));
+        assert_eq!(parsed.mapping.tilt_mode, "overdrive");
+
+        // Settings this build doesn't know are written back
+        let saved = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(saved["practice_goals"]["minutes"], 20);
+        assert_eq!(saved["controller"]["led_brightness"], 3);
+    }
+}
@@ -0,0 +1,253 @@
+//! App data layout
+//! Everything mITyGuitar persists lives under one per-user directory:
+//!
+//! ```text
+//! <data dir>/mityguitar/
+//!     mityguitar_config.json
+//!     song_stats.json     (high scores and play history)
+//!     library_index.json  (song library listing cache)
+//!     session_recovery.json (song session autosave, gone after a clean exit)
+//!     mapping_profiles/   (with backups/)
+//!     config_profiles/    (named audio/mapping setups)
+//!     songs/
+//!     soundfonts/         (uploaded .sf2 files)
+//!     plugins/            (mapper plugin libraries)
+//! ```
+//!
+//! Older builds scattered these over the config dir, the working directory,
+//! a path relative to the executable and Tauri's app data dir, which broke
+//! installed builds. On first start the legacy locations are searched and
+//! their files moved in; a marker file keeps this from running again. Files
+//! already present in the new layout are never overwritten.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::CONFIG_FILE_NAME;
+
+/// Folder name under the platform data dir
+const APP_DIR_NAME: &str = "mityguitar";
+/// Tauri bundle identifier (older builds kept uploaded soundfonts under it)
+const BUNDLE_IDENTIFIER: &str = "com.mityguitar.app";
+/// Per-song high scores and play history
+const STATS_FILE_NAME: &str = "song_stats.json";
+/// Listing entries of the song library, kept between runs
+const LIBRARY_INDEX_FILE_NAME: &str = "library_index.json";
+/// Autosaved song session, removed on a clean exit
+const RECOVERY_FILE_NAME: &str = "session_recovery.json";
+/// Written once the legacy migration has run
+const MIGRATION_MARKER: &str = ".migrated";
+
+/// Resolved persistence paths
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AppPaths {
+    pub root: PathBuf,
+    pub config_file: PathBuf,
+    pub stats_file: PathBuf,
+    pub library_index_file: PathBuf,
+    pub recovery_file: PathBuf,
+    pub profiles_dir: PathBuf,
+    pub config_profiles_dir: PathBuf,
+    pub songs_dir: PathBuf,
+    pub soundfonts_dir: PathBuf,
+    pub plugins_dir: PathBuf,
+}
+
+/// A place older builds stored files
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LegacyLocation {
+    pub source: PathBuf,
+    pub destination: PathBuf,
+    /// Copy instead of move (e.g. the song folder of a source checkout)
+    pub keep_source: bool,
+}
+
+/// A file brought over from a legacy location
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MigratedFile {
+    pub from: PathBuf,
+    pub to: PathBuf,
+}
+
+/// Outcome of the legacy migration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MigrationReport {
+    /// The migration ran on an earlier start
+    pub already_migrated: bool,
+    pub migrated: Vec<MigratedFile>,
+    /// Legacy files left alone because the new layout already has them
+    pub skipped: Vec<PathBuf>,
+    /// Files that couldn't be moved
+    pub errors: Vec<String>,
+}
+
+impl AppPaths {
+    /// Layout under the platform data dir
+    pub fn resolve() -> Result<Self> {
+        let data_dir = dirs::data_dir().context("Could not determine app data directory")?;
+        Ok(Self::at(data_dir.join(APP_DIR_NAME)))
+    }
+
+    /// Layout under a given root
+    pub fn at(root: PathBuf) -> Self {
+        Self {
+            config_file: root.join(CONFIG_FILE_NAME),
+            stats_file: root.join(STATS_FILE_NAME),
+            library_index_file: root.join(LIBRARY_INDEX_FILE_NAME),
+            recovery_file: root.join(RECOVERY_FILE_NAME),
+            profiles_dir: root.join("mapping_profiles"),
+            config_profiles_dir: root.join("config_profiles"),
+            songs_dir: root.join("songs"),
+            soundfonts_dir: root.join("soundfonts"),
+            plugins_dir: root.join("plugins"),
+            root,
+        }
+    }
+
+    /// Create the directories of the layout
+    pub fn ensure_dirs(&self) -> Result<()> {
+        for dir in [&self.root, &self.profiles_dir, &self.config_profiles_dir, &self.songs_dir, &self.soundfonts_dir, &self.plugins_dir] {
+            fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Where older builds kept their files
+    pub fn legacy_locations(&self) -> Vec<LegacyLocation> {
+        let mut locations = Vec::new();
+        let mut add = |source: PathBuf, destination: &Path, keep_source: bool| {
+            locations.push(LegacyLocation { source, destination: destination.to_path_buf(), keep_source });
+        };
+
+        if let Some(config_dir) = dirs::config_dir() {
+            add(config_dir.join(APP_DIR_NAME).join(CONFIG_FILE_NAME), &self.config_file, false);
+        }
+        if let Ok(cwd) = std::env::current_dir() {
+            add(cwd.join("mapping_profiles"), &self.profiles_dir, false);
+        }
+        add(std::env::temp_dir().join("mityguitar_profiles"), &self.profiles_dir, false);
+        if let Some(exe_dir) = std::env::current_exe().ok().and_then(|exe| exe.parent().map(Path::to_path_buf)) {
+            // Development builds read songs from the workspace; leave the checkout intact
+            add(exe_dir.join("../../../../assets/songs"), &self.songs_dir, true);
+        }
+        if let Some(data_dir) = dirs::data_dir() {
+            add(data_dir.join(BUNDLE_IDENTIFIER).join("soundfonts"), &self.soundfonts_dir, false);
+        }
+        locations
+    }
+
+    /// Move legacy files into the layout (once)
+    pub fn migrate(&self) -> Result<MigrationReport> {
+        self.migrate_from(&self.legacy_locations())
+    }
+
+    /// Move files from the given legacy locations into the layout (once)
+    pub fn migrate_from(&self, locations: &[LegacyLocation]) -> Result<MigrationReport> {
+        let marker = self.root.join(MIGRATION_MARKER);
+        if marker.exists() {
+            return Ok(MigrationReport { already_migrated: true, ..Default::default() });
+        }
+        self.ensure_dirs()?;
+
+        let mut report = MigrationReport::default();
+        for location in locations {
+            let Ok(source) = location.source.canonicalize() else {
+                continue;
+            };
+            // Already inside the layout (e.g. started from the data dir)
+            if source.starts_with(self.root.canonicalize()?) {
+                continue;
+            }
+            migrate_path(&source, &location.destination, location.keep_source, &mut report);
+        }
+
+        fs::write(&marker, format!("{}\n", report.migrated.len())).context("Failed to write migration marker")?;
+        Ok(report)
+    }
+}
+
+/// Move (or copy) a file or directory tree, never overwriting
+fn migrate_path(source: &Path, destination: &Path, keep_source: bool, report: &mut MigrationReport) {
+    if source.is_dir() {
+        let Ok(entries) = fs::read_dir(source) else {
+            report.errors.push(format!("Can't read {}", source.display()));
+            return;
+        };
+        for entry in entries.flatten() {
+            migrate_path(&entry.path(), &destination.join(entry.file_name()), keep_source, report);
+        }
+        if !keep_source {
+            // Only succeeds once everything inside was moved
+            let _ = fs::remove_dir(source);
+        }
+        return;
+    }
+
+    if destination.exists() {
+        report.skipped.push(source.to_path_buf());
+        return;
+    }
+    let result = destination.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| {
+        if keep_source {
+            fs::copy(source, destination).map(|_| ())
+        } else {
+            // Rename fails across drives; fall back to copy + delete
+            fs::rename(source, destination)
+                .or_else(|_| fs::copy(source, destination).and_then(|_| fs::remove_file(source)))
+        }
+    });
+    match result {
+        Ok(()) => report.migrated.push(MigratedFile { from: source.to_path_buf(), to: destination.to_path_buf() }),
+        Err(e) => report.errors.push(format!("{}: {}", source.display(), e)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_migration_moves_once_without_overwriting() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+        let paths = AppPaths::at(dir.join("data"));
+        let legacy_profiles = dir.join("cwd").join("mapping_profiles");
+        fs::create_dir_all(legacy_profiles.join("backups")).unwrap();
+        fs::write(legacy_profiles.join("Guitar.json"), "old").unwrap();
+        fs::write(legacy_profiles.join("backups").join("Guitar.1.json"), "older").unwrap();
+        fs::write(legacy_profiles.join("Kept.json"), "legacy").unwrap();
+        fs::create_dir_all(&paths.profiles_dir).unwrap();
+        fs::write(paths.profiles_dir.join("Kept.json"), "current").unwrap();
+        let legacy_songs = dir.join("assets").join("songs");
+        fs::create_dir_all(&legacy_songs).unwrap();
+        fs::write(legacy_songs.join("Demo.mitychart.json"), "{}").unwrap();
+
+        let locations = vec![
+            LegacyLocation { source: legacy_profiles.clone(), destination: paths.profiles_dir.clone(), keep_source: false },
+            LegacyLocation { source: legacy_songs.clone(), destination: paths.songs_dir.clone(), keep_source: true },
+            LegacyLocation { source: dir.join("missing"), destination: paths.songs_dir.clone(), keep_source: false },
+        ];
+        let report = paths.migrate_from(&locations).unwrap();
+        assert_eq!(report.migrated.len(), 3);
+        assert_eq!(report.skipped.len(), 1);
+        assert!(report.errors.is_empty());
+        assert_eq!(fs::read_to_string(paths.profiles_dir.join("backups").join("Guitar.1.json")).unwrap(), "older");
+        assert_eq!(fs::read_to_string(paths.profiles_dir.join("Kept.json")).unwrap(), "current");
+        assert!(!legacy_profiles.join("Guitar.json").exists());
+        // Copied sources stay in place
+        assert!(legacy_songs.join("Demo.mitychart.json").exists());
+        assert!(paths.songs_dir.join("Demo.mitychart.json").exists());
+
+        assert!(paths.migrate_from(&locations).unwrap().already_migrated);
+    }
+
+    #[test]
+    fn test_layout() {
+        let paths = AppPaths::at(PathBuf::from("/data/mityguitar"));
+        assert_eq!(paths.config_file, PathBuf::from("/data/mityguitar").join(CONFIG_FILE_NAME));
+        assert_eq!(paths.songs_dir, PathBuf::from("/data/mityguitar/songs"));
+    }
+}
@@ -0,0 +1,198 @@
+//! Configuration profiles
+//! A profile is a named snapshot of the audio, mapping and instrument
+//! settings ("Practice", "Live low-latency", "Streaming"), so a player can
+//! switch a whole setup at once instead of touching every setting. Profiles
+//! are stored one JSON file each in `config_profiles/`. The audio backend and
+//! devices belong to the machine, not the setup: applying a profile keeps
+//! the ones in use.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use crate::{AppConfig, AudioConfig, MappingConfig, PresetInfo};
+
+/// Profiles written when the store is empty
+pub const DEFAULT_PROFILE_NAMES: [&str; 3] = ["Practice", "Live low-latency", "Streaming"];
+
+/// Named audio, mapping and instrument settings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigProfile {
+    pub name: String,
+    pub audio: AudioConfig,
+    pub mapping: MappingConfig,
+    /// Instrument or SoundFont name (None = keep the current one)
+    #[serde(default)]
+    pub instrument: Option<String>,
+    pub preset: PresetInfo,
+}
+
+impl ConfigProfile {
+    /// Snapshot of a config's settings
+    pub fn capture(name: &str, config: &AppConfig) -> Self {
+        Self {
+            name: name.to_string(),
+            audio: config.audio.clone(),
+            mapping: config.mapping.clone(),
+            instrument: config.soundfonts.current.clone(),
+            preset: config.soundfonts.preset.clone(),
+        }
+    }
+
+    /// Copy the profile's settings into a config, keeping its backend and devices
+    pub fn apply_to(&self, config: &mut AppConfig) {
+        let backend = std::mem::take(&mut config.audio.backend);
+        let output_device = config.audio.output_device.take();
+        let monitor_device = config.audio.input_monitor.device.take();
+        config.audio = AudioConfig {
+            backend,
+            output_device,
+            ..self.audio.clone()
+        };
+        config.audio.input_monitor.device = monitor_device;
+        config.mapping = self.mapping.clone();
+        if self.instrument.is_some() {
+            config.soundfonts.current = self.instrument.clone();
+        }
+        config.soundfonts.preset = self.preset.clone();
+        config.active_profile = Some(self.name.clone());
+    }
+
+    /// One of the built-in profiles, starting from a config's settings
+    pub fn preset(name: &str, config: &AppConfig) -> Option<Self> {
+        let mut profile = Self::capture(name, config);
+        match name {
+            // Roomy buffer and sustain to let chords ring out
+            "Practice" => {
+                profile.audio.buffer_size = 512;
+                profile.audio.sustain_enabled = true;
+            }
+            // Smallest buffer, shorter tails and fewer voices for a tight feel
+            "Live low-latency" => {
+                profile.audio.buffer_size = 64;
+                profile.audio.sustain_enabled = false;
+                profile.audio.release_time_multiplier = 0.75;
+                profile.audio.max_voices = profile.audio.max_voices.min(16);
+            }
+            // Wide stereo and a safe buffer, the stream adds its own delay anyway
+            "Streaming" => {
+                profile.audio.buffer_size = 256;
+                profile.audio.stereo_spread = 0.6;
+            }
+            _ => return None,
+        }
+        Some(profile)
+    }
+}
+
+/// Config profiles on disk, one JSON file each
+pub struct ConfigProfileStore {
+    dir: PathBuf,
+}
+
+impl ConfigProfileStore {
+    pub fn new(dir: PathBuf) -> Result<Self> {
+        fs::create_dir_all(&dir).context("Failed to create config profiles directory")?;
+        Ok(Self { dir })
+    }
+
+    /// Write the built-in profiles if there are none yet
+    pub fn seed_defaults(&self, config: &AppConfig) -> Result<()> {
+        if !self.list()?.is_empty() {
+            return Ok(());
+        }
+        for name in DEFAULT_PROFILE_NAMES {
+            if let Some(profile) = ConfigProfile::preset(name, config) {
+                self.save(&profile)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Names of the stored profiles, sorted
+    pub fn list(&self) -> Result<Vec<String>> {
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.dir).context("Failed to read config profiles directory")?.flatten() {
+            let path = entry.path();
+            if path.extension().is_some_and(|ext| ext == "json") {
+                // The name inside the file, file names are sanitized
+                if let Ok(profile) = Self::read(&path) {
+                    names.push(profile.name);
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+
+    pub fn load(&self, name: &str) -> Result<ConfigProfile> {
+        Self::read(&self.profile_path(name)).with_context(|| format!("Config profile not found: {}", name))
+    }
+
+    pub fn save(&self, profile: &ConfigProfile) -> Result<()> {
+        if profile.name.trim().is_empty() {
+            anyhow::bail!("Config profile needs a name");
+        }
+        let json = serde_json::to_string_pretty(profile).context("Failed to serialize config profile")?;
+        fs::write(self.profile_path(&profile.name), json).context("Failed to write config profile")
+    }
+
+    pub fn delete(&self, name: &str) -> Result<()> {
+        fs::remove_file(self.profile_path(name)).with_context(|| format!("Failed to delete config profile: {}", name))
+    }
+
+    /// The profile after `current` in name order, wrapping around (the first
+    /// one when `current` is None or gone)
+    pub fn next_after(&self, current: Option<&str>) -> Result<Option<String>> {
+        let names = self.list()?;
+        let next = current
+            .and_then(|current| names.iter().position(|name| name == current))
+            .map_or(0, |index| (index + 1) % names.len().max(1));
+        Ok(names.get(next).cloned())
+    }
+
+    fn read(path: &std::path::Path) -> Result<ConfigProfile> {
+        let data = fs::read_to_string(path)?;
+        serde_json::from_str(&data).context("Failed to parse config profile")
+    }
+
+    fn profile_path(&self, name: &str) -> PathBuf {
+        let file_name: String = name
+            .trim()
+            .chars()
+            .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+            .collect();
+        self.dir.join(format!("{}.json", file_name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_profiles_round_trip_and_cycle() {
+        let dir = TempDir::new().unwrap();
+        let store = ConfigProfileStore::new(dir.path().to_path_buf()).unwrap();
+        let mut config = AppConfig::default();
+        config.audio.output_device = Some("USB Interface".to_string());
+        store.seed_defaults(&config).unwrap();
+        assert_eq!(store.list().unwrap(), vec!["Live low-latency", "Practice", "Streaming"]);
+
+        let live = store.load("Live low-latency").unwrap();
+        assert_eq!(live.audio.buffer_size, 64);
+        config.audio.output_device = Some("Laptop Speakers".to_string());
+        live.apply_to(&mut config);
+        assert_eq!(config.audio.buffer_size, 64);
+        assert_eq!(config.audio.output_device.as_deref(), Some("Laptop Speakers"));
+        assert_eq!(config.active_profile.as_deref(), Some("Live low-latency"));
+
+        assert_eq!(store.next_after(Some("Streaming")).unwrap().as_deref(), Some("Live low-latency"));
+        assert_eq!(store.next_after(None).unwrap().as_deref(), Some("Live low-latency"));
+        store.delete("Practice").unwrap();
+        assert_eq!(store.next_after(Some("Practice")).unwrap().as_deref(), Some("Live low-latency"));
+        assert!(store.load("Practice").is_err());
+    }
+}
@@ -0,0 +1,89 @@
+//! External edit watching
+//! The config file and the genre presets can change behind the app's back:
+//! edited by hand or written by a sync tool. `FileWatcher` polls the
+//! modification time and size of a few files and directories (their direct
+//! children) and reports the ones that changed since the last poll. Polling
+//! a handful of small files once a second is cheap and behaves the same on
+//! every platform and file system, network shares included.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+/// Modification time and size of each file under a watched path
+type Stamp = Vec<(PathBuf, Option<SystemTime>, u64)>;
+
+/// Reports watched files and directories that changed on disk
+pub struct FileWatcher {
+    stamps: HashMap<PathBuf, Stamp>,
+}
+
+impl FileWatcher {
+    /// Watch files and directories, starting from their current state
+    pub fn new(paths: impl IntoIterator<Item = PathBuf>) -> Self {
+        let stamps = paths.into_iter().map(|path| {
+            let stamp = stamp(&path);
+            (path, stamp)
+        }).collect();
+        Self { stamps }
+    }
+
+    /// Watched paths that changed (written, created, removed) since the last poll
+    pub fn poll(&mut self) -> Vec<PathBuf> {
+        let mut changed = Vec::new();
+        for (path, last) in self.stamps.iter_mut() {
+            let current = stamp(path);
+            if current != *last {
+                *last = current;
+                changed.push(path.clone());
+            }
+        }
+        changed.sort();
+        changed
+    }
+}
+
+fn file_stamp(path: &Path) -> Option<(PathBuf, Option<SystemTime>, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((path.to_path_buf(), metadata.modified().ok(), metadata.len()))
+}
+
+fn stamp(path: &Path) -> Stamp {
+    if !path.is_dir() {
+        return file_stamp(path).into_iter().collect();
+    }
+    let mut files: Stamp = fs::read_dir(path)
+        .map(|entries| entries.flatten().filter_map(|entry| file_stamp(&entry.path())).collect())
+        .unwrap_or_default();
+    files.sort_by(|a, b| a.0.cmp(&b.0));
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_watcher_reports_changed_paths() {
+        let dir = TempDir::new().unwrap();
+        let presets = dir.path().join("chordmaps");
+        fs::create_dir_all(&presets).unwrap();
+        let config = dir.path().join("config.json");
+        fs::write(&config, "{}").unwrap();
+        fs::write(presets.join("rock.json"), "{}").unwrap();
+
+        let mut watcher = FileWatcher::new([config.clone(), presets.clone()]);
+        assert!(watcher.poll().is_empty());
+
+        fs::write(&config, "{\"version\": 1}").unwrap();
+        assert_eq!(watcher.poll(), vec![config.clone()]);
+        assert!(watcher.poll().is_empty());
+
+        fs::write(presets.join("punk.json"), "{}").unwrap();
+        assert_eq!(watcher.poll(), vec![presets.clone()]);
+        fs::remove_file(&config).unwrap();
+        assert_eq!(watcher.poll(), vec![config]);
+    }
+}
@@ -0,0 +1,97 @@
+//! Debounce for chattering switches (mostly worn strum bars)
+//! Uses a lockout window: the first edge is accepted immediately (no added
+//! latency), then further changes are ignored until the window has passed.
+
+use std::time::{Duration, Instant};
+use crate::mapping_profile::AppAction;
+
+/// Actions that go through the debounce layer, in poll order
+/// (frets green..orange, then strum up/down)
+pub const DEBOUNCED_ACTIONS: [AppAction; 7] = [
+    AppAction::FretGreen,
+    AppAction::FretRed,
+    AppAction::FretYellow,
+    AppAction::FretBlue,
+    AppAction::FretOrange,
+    AppAction::StrumUp,
+    AppAction::StrumDown,
+];
+
+/// Lockout debouncer for one boolean input
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DebounceFilter {
+    window: Duration,
+    stable: bool,
+    last_change: Option<Instant>,
+    /// A change is currently being held back (counted once)
+    rejecting: bool,
+}
+
+impl DebounceFilter {
+    pub fn new(window_ms: u32) -> Self {
+        Self {
+            window: Duration::from_millis(window_ms as u64),
+            ..Default::default()
+        }
+    }
+
+    pub fn set_window_ms(&mut self, window_ms: u32) {
+        self.window = Duration::from_millis(window_ms as u64);
+    }
+
+    /// Forget the input's state (released, no lockout), keeping the window
+    pub fn reset(&mut self) {
+        *self = Self { window: self.window, ..Default::default() };
+    }
+
+    /// Feed a raw reading. Returns the debounced state and whether this call
+    /// started filtering a new bounce.
+    pub fn update(&mut self, raw: bool, now: Instant) -> (bool, bool) {
+        if raw == self.stable {
+            self.rejecting = false;
+            return (self.stable, false);
+        }
+
+        let locked = self.last_change
+            .map(|last| now.saturating_duration_since(last) < self.window)
+            .unwrap_or(false);
+        if locked {
+            let newly_filtered = !self.rejecting;
+            self.rejecting = true;
+            return (self.stable, newly_filtered);
+        }
+
+        self.stable = raw;
+        self.last_change = Some(now);
+        self.rejecting = false;
+        (self.stable, false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_chatter_is_filtered() {
+        let start = Instant::now();
+        let ms = |n: u64| start + Duration::from_millis(n);
+        let mut filter = DebounceFilter::new(20);
+
+        assert_eq!(filter.update(true, ms(0)), (true, false));
+        // Contact bounces open 2ms later: held back and counted once
+        assert_eq!(filter.update(false, ms(2)), (true, true));
+        assert_eq!(filter.update(false, ms(3)), (true, false));
+        assert_eq!(filter.update(true, ms(4)), (true, false));
+        // Real release after the window passes
+        assert_eq!(filter.update(false, ms(30)), (false, false));
+    }
+
+    #[test]
+    fn test_zero_window_passes_through() {
+        let start = Instant::now();
+        let mut filter = DebounceFilter::new(0);
+        assert_eq!(filter.update(true, start), (true, false));
+        assert_eq!(filter.update(false, start), (false, false));
+    }
+}
@@ -0,0 +1,147 @@
+//! Scripted demo performances
+//! A demo script is a timed list of controller states that the app plays
+//! through its normal input pipeline, as if a guitar were plugged in. It is
+//! used to show the app off, as a smoke test after installing and to check
+//! that audio works on a new machine.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use crate::high_performance::ControllerStateSnapshot;
+
+/// Controls a step can hold, as named in scripts
+pub const DEMO_CONTROLS: [&str; 13] = [
+    "green", "red", "yellow", "blue", "orange",
+    "strum_up", "strum_down",
+    "dpad_up", "dpad_down", "dpad_left", "dpad_right",
+    "start", "select",
+];
+
+/// Controller state from `at_ms` until the next step
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DemoStep {
+    /// Time from the start of the demo (ms)
+    pub at_ms: u64,
+    /// Controls held down (see `DEMO_CONTROLS`)
+    #[serde(default)]
+    pub held: Vec<String>,
+    #[serde(default)]
+    pub whammy: f32,
+    #[serde(default)]
+    pub tilt: f32,
+}
+
+impl DemoStep {
+    fn new(at_ms: u64, held: &[&str]) -> Self {
+        Self { at_ms, held: held.iter().map(|name| name.to_string()).collect(), whammy: 0.0, tilt: 0.0 }
+    }
+
+    /// Controller snapshot for player 1 holding this step's controls
+    pub fn snapshot(&self) -> ControllerStateSnapshot {
+        let held = |name: &str| self.held.iter().any(|control| control == name);
+        ControllerStateSnapshot {
+            fret_green: held("green"),
+            fret_red: held("red"),
+            fret_yellow: held("yellow"),
+            fret_blue: held("blue"),
+            fret_orange: held("orange"),
+            strum_up: held("strum_up"),
+            strum_down: held("strum_down"),
+            dpad_up: held("dpad_up"),
+            dpad_down: held("dpad_down"),
+            dpad_left: held("dpad_left"),
+            dpad_right: held("dpad_right"),
+            start: held("start"),
+            select: held("select"),
+            whammy_bar: self.whammy.clamp(-1.0, 1.0),
+            tilt: self.tilt.clamp(-1.0, 1.0),
+            connected: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// A timed sequence of controller states
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DemoScript {
+    pub name: String,
+    pub steps: Vec<DemoStep>,
+}
+
+impl DemoScript {
+    /// Built-in demo: four chords strummed in time, the last one bent with the whammy
+    pub fn builtin() -> Self {
+        const BEAT_MS: u64 = 500;
+        const STRUM_MS: u64 = 120;
+        let mut steps = Vec::new();
+        for (bar, fret) in ["green", "red", "yellow", "blue"].iter().enumerate() {
+            for beat in 0..4u64 {
+                let at_ms = (bar as u64 * 4 + beat) * BEAT_MS;
+                let strum = if beat % 2 == 0 { "strum_down" } else { "strum_up" };
+                steps.push(DemoStep::new(at_ms, &[fret, strum]));
+                let mut ring = DemoStep::new(at_ms + STRUM_MS, &[fret]);
+                if bar == 3 {
+                    ring.whammy = (beat + 1) as f32 / 4.0;
+                }
+                steps.push(ring);
+            }
+        }
+        steps.push(DemoStep::new(16 * BEAT_MS, &[]));
+        Self { name: "Built-in demo".to_string(), steps }
+    }
+
+    /// Check the script can be played: steps in time order, known controls
+    pub fn validate(&self) -> Result<()> {
+        if self.steps.is_empty() {
+            bail!("Demo script '{}' has no steps", self.name);
+        }
+        if self.steps.windows(2).any(|pair| pair[1].at_ms < pair[0].at_ms) {
+            bail!("Demo script '{}' has steps out of time order", self.name);
+        }
+        if let Some(unknown) = self.steps.iter()
+            .flat_map(|step| step.held.iter())
+            .find(|name| !DEMO_CONTROLS.contains(&name.as_str()))
+        {
+            bail!("Unknown control '{}' in demo script '{}'", unknown, self.name);
+        }
+        Ok(())
+    }
+
+    /// Time of the last step (ms)
+    pub fn duration_ms(&self) -> u64 {
+        self.steps.last().map(|step| step.at_ms).unwrap_or(0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builtin_script_is_valid_and_ends_released() {
+        let script = DemoScript::builtin();
+        script.validate().unwrap();
+        assert_eq!(script.duration_ms(), 8000);
+        let last = script.steps.last().unwrap().snapshot();
+        assert!(!last.fret_green && !last.strum_down && last.whammy_bar == 0.0);
+    }
+
+    #[test]
+    fn test_script_from_json() {
+        let json = r#"{"name":"Test","steps":[
+            {"at_ms":0,"held":["green","strum_down"]},
+            {"at_ms":100,"held":["green"],"whammy":0.5}
+        ]}"#;
+        let script: DemoScript = serde_json::from_str(json).unwrap();
+        script.validate().unwrap();
+        let first = script.steps[0].snapshot();
+        assert!(first.fret_green && first.strum_down && first.connected);
+        assert_eq!(script.steps[1].snapshot().whammy_bar, 0.5);
+
+        let mut bad = script.clone();
+        bad.steps[0].held.push("purple".to_string());
+        assert!(bad.validate().is_err());
+        bad = script;
+        bad.steps.reverse();
+        assert!(bad.validate().is_err());
+    }
+}
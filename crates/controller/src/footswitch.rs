@@ -0,0 +1,175 @@
+//! USB foot switch input (sustain pedal)
+//! Generic USB foot switches are cheap HID devices, most of them posing as a
+//! keyboard that "types" a key while the pedal is down. They are found by
+//! known VID/PIDs or a product name mentioning a foot switch or pedal. A
+//! reader thread turns their reports into pressed/released and publishes the
+//! result in the player's atomic state, so the polling loop never blocks on
+//! the device. Some systems (notably Windows) don't let apps open keyboard
+//! class devices; binding the SustainPedal action to a guitar button works
+//! there instead.
+
+use anyhow::{Context, Result};
+use hidapi::HidApi;
+use serde::{Deserialize, Serialize};
+use std::ffi::CString;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread;
+
+use crate::high_performance::AtomicControllerState;
+
+/// Known USB foot switches (VID, PID)
+pub const FOOTSWITCH_DEVICES: &[(u16, u16)] = &[
+    (0x0c45, 0x7403), // PCsensor FootSwitch
+    (0x0c45, 0x7404), // PCsensor FootSwitch3
+    (0x413d, 0x2107), // PCsensor FootSwitch (2019+)
+    (0x1a86, 0xe026), // iKKEGOL single pedal
+    (0x3553, 0xb001), // Generic USB foot pedal
+];
+
+/// Product name fragments of foot switches not in the list
+const FOOTSWITCH_NAMES: &[&str] = &["footswitch", "foot switch", "foot pedal", "pedal"];
+
+/// Read timeout so the reader notices when it should stop
+const READ_TIMEOUT_MS: i32 = 50;
+
+/// A foot switch found on the HID bus
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FootswitchInfo {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub name: String,
+    /// Platform device path (used to open the device)
+    pub path: String,
+}
+
+/// Whether a HID device looks like a foot switch
+pub fn is_footswitch(vendor_id: u16, product_id: u16, name: &str) -> bool {
+    let name = name.to_lowercase();
+    FOOTSWITCH_DEVICES.contains(&(vendor_id, product_id))
+        || FOOTSWITCH_NAMES.iter().any(|fragment| name.contains(fragment))
+}
+
+/// Foot switches currently connected
+pub fn find_footswitches(api: &HidApi) -> Vec<FootswitchInfo> {
+    let mut found: Vec<FootswitchInfo> = Vec::new();
+    for device in api.device_list() {
+        let name = device.product_string().unwrap_or("Unknown");
+        if !is_footswitch(device.vendor_id(), device.product_id(), name) {
+            continue;
+        }
+        let path = device.path().to_string_lossy().to_string();
+        // Composite devices list one entry per interface; keep the first
+        if found.iter().any(|info| info.vendor_id == device.vendor_id() && info.product_id == device.product_id()) {
+            continue;
+        }
+        found.push(FootswitchInfo {
+            vendor_id: device.vendor_id(),
+            product_id: device.product_id(),
+            name: name.to_string(),
+            path,
+        });
+    }
+    found
+}
+
+/// Scan the HID bus for foot switches
+pub fn list_footswitches() -> Result<Vec<FootswitchInfo>> {
+    let api = HidApi::new().context("Failed to initialize HID API")?;
+    Ok(find_footswitches(&api))
+}
+
+/// Pedal state from an input report. Keyboard reports are 8 bytes (modifiers,
+/// reserved, key codes) and all zero when nothing is held; a 9th leading byte
+/// is a report id and is ignored.
+fn report_pressed(report: &[u8]) -> bool {
+    let data = if report.len() > 8 { &report[1..] } else { report };
+    data.iter().any(|&byte| byte != 0)
+}
+
+/// Open foot switch feeding a player's sustain pedal state (closed on drop)
+pub struct Footswitch {
+    info: FootswitchInfo,
+    should_stop: Arc<AtomicBool>,
+    state: Arc<AtomicControllerState>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl Footswitch {
+    /// Open a foot switch and start reading it into `state`
+    pub fn open(api: &HidApi, info: FootswitchInfo, state: Arc<AtomicControllerState>) -> Result<Self> {
+        let path = CString::new(info.path.clone()).context("Invalid device path")?;
+        let device = api.open_path(&path)
+            .with_context(|| format!("Failed to open foot switch '{}'", info.name))?;
+
+        let should_stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = Arc::clone(&should_stop);
+        let thread_state = Arc::clone(&state);
+        let name = info.name.clone();
+        let thread = thread::spawn(move || {
+            let mut report = [0u8; 64];
+            while !thread_stop.load(Ordering::Relaxed) {
+                match device.read_timeout(&mut report, READ_TIMEOUT_MS) {
+                    Ok(0) => {}
+                    Ok(len) => thread_state.footswitch.store(report_pressed(&report[..len]), Ordering::Relaxed),
+                    Err(e) => {
+                        log::warn!("🦶 Foot switch '{}' stopped: {}", name, e);
+                        break;
+                    }
+                }
+            }
+            thread_state.footswitch.store(false, Ordering::Relaxed);
+        });
+
+        log::info!("🦶 Foot switch connected: {} (VID:{:04x} PID:{:04x})", info.name, info.vendor_id, info.product_id);
+        Ok(Self {
+            info,
+            should_stop,
+            state,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn info(&self) -> &FootswitchInfo {
+        &self.info
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.state.footswitch.load(Ordering::Relaxed)
+    }
+}
+
+impl Drop for Footswitch {
+    fn drop(&mut self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+        self.state.footswitch.store(false, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_footswitch_detection() {
+        assert!(is_footswitch(0x0c45, 0x7403, "Unknown"));
+        assert!(is_footswitch(0x1234, 0x5678, "USB FootSwitch"));
+        assert!(is_footswitch(0x1234, 0x5678, "Sustain Pedal"));
+        assert!(!is_footswitch(0x1bad, 0x0004, "Harmonix Guitar"));
+    }
+
+    #[test]
+    fn test_report_pressed() {
+        // Keyboard report holding "b"
+        assert!(report_pressed(&[0, 0, 0x05, 0, 0, 0, 0, 0]));
+        assert!(!report_pressed(&[0; 8]));
+        // Leading report id is ignored
+        assert!(!report_pressed(&[1, 0, 0, 0, 0, 0, 0, 0, 0]));
+        assert!(report_pressed(&[1, 0x02, 0, 0, 0, 0, 0, 0, 0]));
+        // Vendor-specific single byte switches
+        assert!(report_pressed(&[1]));
+    }
+}
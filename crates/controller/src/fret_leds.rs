@@ -0,0 +1,198 @@
+//! Fret LED output
+//! Some Rock Band 3 guitars have a light under each fret that the host can
+//! switch through a HID output report. `FretLedOutput` owns the device and a
+//! writer thread: callers only store the wanted `FretLeds` in an atomic, and
+//! the thread sends a report when it changes, so neither the polling loop nor
+//! the UI ever waits on the USB bus. The lights go dark when the output is
+//! closed.
+
+use anyhow::{Context, Result};
+use hidapi::HidApi;
+use serde::{Deserialize, Serialize};
+use std::ffi::CString;
+use std::sync::atomic::{AtomicBool, AtomicU8, Ordering};
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+/// Guitars with fret LEDs (VID, PID)
+pub const FRET_LED_DEVICES: &[(u16, u16)] = &[
+    (0x1bad, 0x3110), // Harmonix Rock Band 3 Mustang Guitar (Wii)
+    (0x1bad, 0x3430), // Harmonix Rock Band 3 Squier Guitar (Wii)
+    (0x12ba, 0x2430), // Harmonix Rock Band 3 Mustang Guitar (PS3)
+    (0x12ba, 0x2530), // Harmonix Rock Band 3 Squier Guitar (PS3)
+];
+
+/// Output report command that sets the fret lights (value = `FretLeds` bits)
+const FRET_LED_COMMAND: u8 = 0x40;
+
+/// How often the writer checks for a change
+const WRITE_INTERVAL: Duration = Duration::from_millis(10);
+
+/// Lit frets, one bit each from green (bit 0) to orange (bit 4)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FretLeds(u8);
+
+impl FretLeds {
+    pub const NONE: Self = Self(0);
+    pub const GREEN: Self = Self(1 << 0);
+    pub const RED: Self = Self(1 << 1);
+    pub const YELLOW: Self = Self(1 << 2);
+    pub const BLUE: Self = Self(1 << 3);
+    pub const ORANGE: Self = Self(1 << 4);
+
+    /// Lights for fret names as charts write them ("GREEN", "RED", ...);
+    /// unknown names are ignored
+    pub fn from_fret_names<S: AsRef<str>>(names: &[S]) -> Self {
+        let bits = names.iter().fold(0, |bits, name| {
+            bits | match name.as_ref().to_uppercase().as_str() {
+                "GREEN" => Self::GREEN.0,
+                "RED" => Self::RED.0,
+                "YELLOW" => Self::YELLOW.0,
+                "BLUE" => Self::BLUE.0,
+                "ORANGE" => Self::ORANGE.0,
+                _ => 0,
+            }
+        });
+        Self(bits)
+    }
+
+    pub fn bits(&self) -> u8 {
+        self.0
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+}
+
+/// A guitar with fret LEDs found on the HID bus
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FretLedInfo {
+    pub vendor_id: u16,
+    pub product_id: u16,
+    pub name: String,
+    /// Platform device path (used to open the device)
+    pub path: String,
+}
+
+/// Guitars with fret LEDs currently connected
+pub fn find_led_guitars(api: &HidApi) -> Vec<FretLedInfo> {
+    let mut found: Vec<FretLedInfo> = Vec::new();
+    for device in api.device_list() {
+        if !FRET_LED_DEVICES.contains(&(device.vendor_id(), device.product_id())) {
+            continue;
+        }
+        // Composite devices list one entry per interface; keep the first
+        if found.iter().any(|info| info.vendor_id == device.vendor_id() && info.product_id == device.product_id()) {
+            continue;
+        }
+        found.push(FretLedInfo {
+            vendor_id: device.vendor_id(),
+            product_id: device.product_id(),
+            name: device.product_string().unwrap_or("Unknown").to_string(),
+            path: device.path().to_string_lossy().to_string(),
+        });
+    }
+    found
+}
+
+/// Scan the HID bus for guitars with fret LEDs
+pub fn list_led_guitars() -> Result<Vec<FretLedInfo>> {
+    let api = HidApi::new().context("Failed to initialize HID API")?;
+    Ok(find_led_guitars(&api))
+}
+
+/// Output report setting the fret lights. It follows the Harmonix instrument
+/// layout: report id 0, two header bytes, the command and its value, padded
+/// to 9 bytes.
+fn fret_led_report(leds: FretLeds) -> [u8; 9] {
+    [0x00, 0x00, 0x08, FRET_LED_COMMAND, leds.bits(), 0x00, 0x00, 0x00, 0x00]
+}
+
+/// Open guitar whose fret LEDs follow `set` (switched off on drop)
+pub struct FretLedOutput {
+    info: FretLedInfo,
+    leds: Arc<AtomicU8>,
+    should_stop: Arc<AtomicBool>,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl FretLedOutput {
+    /// Open a guitar's LED output and start the writer
+    pub fn open(api: &HidApi, info: FretLedInfo) -> Result<Self> {
+        let path = CString::new(info.path.clone()).context("Invalid device path")?;
+        let device = api.open_path(&path)
+            .with_context(|| format!("Failed to open fret LEDs of '{}'", info.name))?;
+
+        let leds = Arc::new(AtomicU8::new(0));
+        let should_stop = Arc::new(AtomicBool::new(false));
+        let thread_leds = Arc::clone(&leds);
+        let thread_stop = Arc::clone(&should_stop);
+        let name = info.name.clone();
+        let thread = thread::spawn(move || {
+            // Nothing sent yet, so the first pass always writes
+            let mut sent = None;
+            while !thread_stop.load(Ordering::Relaxed) {
+                let wanted = FretLeds(thread_leds.load(Ordering::Relaxed));
+                if sent != Some(wanted) {
+                    if let Err(e) = device.write(&fret_led_report(wanted)) {
+                        log::warn!("💡 Fret LEDs of '{}' stopped: {}", name, e);
+                        return;
+                    }
+                    sent = Some(wanted);
+                }
+                thread::sleep(WRITE_INTERVAL);
+            }
+            let _ = device.write(&fret_led_report(FretLeds::NONE));
+        });
+
+        log::info!("💡 Fret LEDs connected: {} (VID:{:04x} PID:{:04x})", info.name, info.vendor_id, info.product_id);
+        Ok(Self {
+            info,
+            leds,
+            should_stop,
+            thread: Some(thread),
+        })
+    }
+
+    pub fn info(&self) -> &FretLedInfo {
+        &self.info
+    }
+
+    /// Light these frets (sent by the writer thread)
+    pub fn set(&self, leds: FretLeds) {
+        self.leds.store(leds.bits(), Ordering::Relaxed);
+    }
+
+    pub fn get(&self) -> FretLeds {
+        FretLeds(self.leds.load(Ordering::Relaxed))
+    }
+}
+
+impl Drop for FretLedOutput {
+    fn drop(&mut self) {
+        self.should_stop.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fret_leds_from_names() {
+        let leds = FretLeds::from_fret_names(&["GREEN", "yellow", "WHAMMY"]);
+        assert_eq!(leds.bits(), FretLeds::GREEN.bits() | FretLeds::YELLOW.bits());
+        assert!(FretLeds::from_fret_names::<&str>(&[]).is_empty());
+
+        let report = fret_led_report(FretLeds::from_fret_names(&["ORANGE"]));
+        assert_eq!(report.len(), 9);
+        assert_eq!(report[0], 0x00);
+        assert_eq!(report[3], FRET_LED_COMMAND);
+        assert_eq!(report[4], 1 << 4);
+    }
+}
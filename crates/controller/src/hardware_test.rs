@@ -0,0 +1,331 @@
+//! Guided hardware test
+//! Walks through each control (frets, strum directions, whammy sweep, tilt) and
+//! records whether it responded before a timeout, so a used guitar can be
+//! checked in under a minute. Fed from the polling thread like the whammy
+//! calibrator, so short strum taps between UI polls aren't missed.
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::high_performance::ControllerStateSnapshot;
+
+/// Time allowed per control before it counts as failed
+pub const DEFAULT_STEP_TIMEOUT_MS: u64 = 5000;
+/// Travel needed for the whammy and tilt sweeps
+const WHAMMY_SWEEP_RANGE: f32 = 0.5;
+const TILT_SWEEP_RANGE: f32 = 0.5;
+
+/// Controls checked by the hardware test, in order
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TestControl {
+    FretGreen,
+    FretRed,
+    FretYellow,
+    FretBlue,
+    FretOrange,
+    StrumUp,
+    StrumDown,
+    WhammySweep,
+    Tilt,
+}
+
+impl TestControl {
+    pub fn all() -> [TestControl; 9] {
+        [
+            TestControl::FretGreen,
+            TestControl::FretRed,
+            TestControl::FretYellow,
+            TestControl::FretBlue,
+            TestControl::FretOrange,
+            TestControl::StrumUp,
+            TestControl::StrumDown,
+            TestControl::WhammySweep,
+            TestControl::Tilt,
+        ]
+    }
+
+    /// Instruction shown to the user
+    pub fn prompt(&self) -> &'static str {
+        match self {
+            TestControl::FretGreen => "Press the green fret",
+            TestControl::FretRed => "Press the red fret",
+            TestControl::FretYellow => "Press the yellow fret",
+            TestControl::FretBlue => "Press the blue fret",
+            TestControl::FretOrange => "Press the orange fret",
+            TestControl::StrumUp => "Strum up",
+            TestControl::StrumDown => "Strum down",
+            TestControl::WhammySweep => "Push the whammy bar all the way and release it",
+            TestControl::Tilt => "Tilt the guitar neck up",
+        }
+    }
+
+    /// Pressed state for button controls (None for analog sweeps)
+    fn pressed(&self, snapshot: &ControllerStateSnapshot) -> Option<bool> {
+        match self {
+            TestControl::FretGreen => Some(snapshot.fret_green),
+            TestControl::FretRed => Some(snapshot.fret_red),
+            TestControl::FretYellow => Some(snapshot.fret_yellow),
+            TestControl::FretBlue => Some(snapshot.fret_blue),
+            TestControl::FretOrange => Some(snapshot.fret_orange),
+            TestControl::StrumUp => Some(snapshot.strum_up),
+            TestControl::StrumDown => Some(snapshot.strum_down),
+            TestControl::WhammySweep | TestControl::Tilt => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TestOutcome {
+    Pending,
+    Running,
+    Passed,
+    Failed,
+    Skipped,
+}
+
+/// Result for one control
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlTestResult {
+    pub control: TestControl,
+    pub outcome: TestOutcome,
+    /// Time from the prompt to the response
+    pub response_ms: Option<u64>,
+    pub detail: Option<String>,
+}
+
+/// Progress and results of a hardware test, for the UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HardwareTestReport {
+    pub active: bool,
+    pub current: Option<TestControl>,
+    pub prompt: Option<String>,
+    /// Time left for the current control
+    pub remaining_ms: u64,
+    pub results: Vec<ControlTestResult>,
+    pub passed: usize,
+    pub failed: usize,
+}
+
+/// One run through all controls
+#[derive(Debug)]
+pub struct HardwareTest {
+    results: Vec<ControlTestResult>,
+    current: usize,
+    step_started: Instant,
+    timeout: Duration,
+    /// Whether the current button was seen released (a stuck button can't pass)
+    seen_released: bool,
+    /// Analog range seen during the current sweep
+    range: Option<(f32, f32)>,
+}
+
+impl HardwareTest {
+    pub fn new(timeout_ms: u64, now: Instant) -> Self {
+        let mut results: Vec<ControlTestResult> = TestControl::all()
+            .iter()
+            .map(|&control| ControlTestResult {
+                control,
+                outcome: TestOutcome::Pending,
+                response_ms: None,
+                detail: None,
+            })
+            .collect();
+        results[0].outcome = TestOutcome::Running;
+        Self {
+            results,
+            current: 0,
+            step_started: now,
+            timeout: Duration::from_millis(timeout_ms.max(500)),
+            seen_released: false,
+            range: None,
+        }
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current >= self.results.len()
+    }
+
+    fn finish_step(&mut self, outcome: TestOutcome, detail: Option<String>, now: Instant) {
+        let Some(result) = self.results.get_mut(self.current) else {
+            return;
+        };
+        result.outcome = outcome;
+        result.detail = detail;
+        if outcome == TestOutcome::Passed {
+            result.response_ms = Some(now.duration_since(self.step_started).as_millis() as u64);
+        }
+        log::info!("🔧 Hardware test {:?}: {:?}", result.control, outcome);
+
+        self.current += 1;
+        self.step_started = now;
+        self.seen_released = false;
+        self.range = None;
+        if let Some(next) = self.results.get_mut(self.current) {
+            next.outcome = TestOutcome::Running;
+        }
+    }
+
+    /// Feed the current controller state (and tilt, when the backend reports it)
+    pub fn update(&mut self, snapshot: &ControllerStateSnapshot, tilt: Option<f32>, now: Instant) {
+        let Some(result) = self.results.get(self.current) else {
+            return;
+        };
+        let control = result.control;
+        let timed_out = now.duration_since(self.step_started) >= self.timeout;
+
+        if let Some(pressed) = control.pressed(snapshot) {
+            if !pressed {
+                self.seen_released = true;
+            } else if self.seen_released {
+                self.finish_step(TestOutcome::Passed, None, now);
+                return;
+            }
+            if timed_out {
+                let detail = if self.seen_released { "No press detected" } else { "Stuck pressed" };
+                self.finish_step(TestOutcome::Failed, Some(detail.to_string()), now);
+            }
+            return;
+        }
+
+        let (value, needed) = match control {
+            TestControl::Tilt => match tilt {
+                Some(value) => (value, TILT_SWEEP_RANGE),
+                None => {
+                    self.finish_step(TestOutcome::Skipped, Some("Tilt is not reported by this controller backend".to_string()), now);
+                    return;
+                }
+            },
+            _ => (snapshot.whammy_bar, WHAMMY_SWEEP_RANGE),
+        };
+        let (min, max) = self.range.unwrap_or((value, value));
+        let (min, max) = (min.min(value), max.max(value));
+        self.range = Some((min, max));
+        if max - min >= needed {
+            self.finish_step(TestOutcome::Passed, Some(format!("Range {:.2} to {:.2}", min, max)), now);
+        } else if timed_out {
+            self.finish_step(TestOutcome::Failed, Some(format!("Only moved {:.2} (range {:.2} to {:.2})", max - min, min, max)), now);
+        }
+    }
+
+    /// Mark the current control as skipped and move on
+    pub fn skip(&mut self, now: Instant) {
+        self.finish_step(TestOutcome::Skipped, Some("Skipped by user".to_string()), now);
+    }
+
+    pub fn report(&self, now: Instant) -> HardwareTestReport {
+        let current = self.results.get(self.current).map(|result| result.control);
+        HardwareTestReport {
+            active: !self.is_finished(),
+            current,
+            prompt: current.map(|control| control.prompt().to_string()),
+            remaining_ms: if self.is_finished() {
+                0
+            } else {
+                self.timeout.saturating_sub(now.duration_since(self.step_started)).as_millis() as u64
+            },
+            results: self.results.clone(),
+            passed: self.results.iter().filter(|r| r.outcome == TestOutcome::Passed).count(),
+            failed: self.results.iter().filter(|r| r.outcome == TestOutcome::Failed).count(),
+        }
+    }
+}
+
+/// Holds the running hardware test, fed by the polling thread (player 1)
+#[derive(Debug, Default)]
+pub struct HardwareTester {
+    run: Mutex<Option<HardwareTest>>,
+}
+
+impl HardwareTester {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a test (restarts any test in progress)
+    pub fn start(&self, timeout_ms: u64) {
+        log::info!("🔧 Hardware test started ({} ms per control)", timeout_ms);
+        *self.run.lock().unwrap() = Some(HardwareTest::new(timeout_ms, Instant::now()));
+    }
+
+    /// Feed controller state (called from the polling thread)
+    pub fn record(&self, snapshot: &ControllerStateSnapshot, tilt: Option<f32>) {
+        // Never block the polling thread on the UI
+        let Ok(mut run) = self.run.try_lock() else {
+            return;
+        };
+        if let Some(test) = run.as_mut() {
+            test.update(snapshot, tilt, Instant::now());
+        }
+    }
+
+    /// Whether a test is waiting for input
+    pub fn is_active(&self) -> bool {
+        self.run.lock().unwrap().as_ref().is_some_and(|test| !test.is_finished())
+    }
+
+    pub fn skip(&self) {
+        if let Some(test) = self.run.lock().unwrap().as_mut() {
+            test.skip(Instant::now());
+        }
+    }
+
+    pub fn cancel(&self) {
+        *self.run.lock().unwrap() = None;
+    }
+
+    pub fn report(&self) -> Option<HardwareTestReport> {
+        self.run.lock().unwrap().as_ref().map(|test| test.report(Instant::now()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_button_steps_pass_and_time_out() {
+        let start = Instant::now();
+        let at = |ms: u64| start + Duration::from_millis(ms);
+        let mut test = HardwareTest::new(1000, start);
+        // Green held from the start counts as stuck
+        let mut snapshot = ControllerStateSnapshot { fret_green: true, ..Default::default() };
+        test.update(&snapshot, None, at(10));
+        test.update(&snapshot, None, at(1000));
+        assert_eq!(test.results[0].outcome, TestOutcome::Failed);
+        assert_eq!(test.results[0].detail.as_deref(), Some("Stuck pressed"));
+
+        // Red released then pressed passes
+        snapshot.fret_green = false;
+        test.update(&snapshot, None, at(1100));
+        snapshot.fret_red = true;
+        test.update(&snapshot, None, at(1300));
+        assert_eq!(test.results[1].outcome, TestOutcome::Passed);
+        assert_eq!(test.results[1].response_ms, Some(300));
+        assert_eq!(test.report(at(1300)).current, Some(TestControl::FretYellow));
+    }
+
+    #[test]
+    fn test_whammy_sweep_and_missing_tilt() {
+        let start = Instant::now();
+        let mut test = HardwareTest::new(1000, start);
+        for _ in 0..7 {
+            test.skip(start);
+        }
+        let mut snapshot = ControllerStateSnapshot::default();
+        test.update(&snapshot, None, start);
+        snapshot.whammy_bar = 0.3;
+        test.update(&snapshot, None, start);
+        assert_eq!(test.results[7].outcome, TestOutcome::Running);
+        snapshot.whammy_bar = 0.8;
+        test.update(&snapshot, None, start);
+        assert_eq!(test.results[7].outcome, TestOutcome::Passed);
+
+        test.update(&snapshot, None, start);
+        assert_eq!(test.results[8].outcome, TestOutcome::Skipped);
+        let report = test.report(start);
+        assert!(!report.active);
+        assert_eq!(report.passed, 1);
+    }
+}
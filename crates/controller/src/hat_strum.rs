@@ -0,0 +1,120 @@
+//! Strum decoding for guitars that report the strum bar as a hat/axis
+//! Some guitars send strum as the D-pad hat (gilrs `DPadY`) instead of buttons,
+//! and a few of those report intermediate positions while the bar travels.
+//! For those the time from leaving rest to reaching the press threshold gives
+//! a strum velocity; digital hats jump straight to +/-1 and get the default.
+
+use std::time::{Duration, Instant};
+
+/// Hat value needed to count as a strum
+pub const HAT_PRESS_THRESHOLD: f32 = 0.5;
+/// Hysteresis below the threshold before the strum releases
+const HAT_RELEASE_HYSTERESIS: f32 = 0.1;
+/// Values inside this zone count as the bar resting
+const HAT_REST_ZONE: f32 = 0.15;
+
+/// Velocity used when the travel time can't be measured
+pub const DEFAULT_STRUM_VELOCITY: u8 = 100;
+/// Travel time (and velocity) of the hardest strum
+const FASTEST_STRUM: Duration = Duration::from_millis(2);
+const MAX_STRUM_VELOCITY: f32 = 127.0;
+/// Travel time (and velocity) of the softest strum
+const SLOWEST_STRUM: Duration = Duration::from_millis(40);
+const MIN_STRUM_VELOCITY: f32 = 40.0;
+
+/// Map strum travel time to MIDI velocity (faster = louder)
+pub fn velocity_from_travel(travel: Duration) -> u8 {
+    let fastest = FASTEST_STRUM.as_secs_f32();
+    let slowest = SLOWEST_STRUM.as_secs_f32();
+    let t = ((travel.as_secs_f32() - fastest) / (slowest - fastest)).clamp(0.0, 1.0);
+    (MAX_STRUM_VELOCITY + (MIN_STRUM_VELOCITY - MAX_STRUM_VELOCITY) * t).round() as u8
+}
+
+/// Turns hat/axis readings into strum up/down with velocity.
+/// Positive values strum up, negative values strum down.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HatStrumDecoder {
+    /// 1 = up, -1 = down, 0 = released
+    direction: i8,
+    /// When (and in which direction) the bar last left the rest zone
+    left_rest: Option<(Instant, i8)>,
+    velocity: u8,
+}
+
+impl HatStrumDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Feed a hat reading, returns (strum up, strum down)
+    pub fn update(&mut self, value: f32, now: Instant) -> (bool, bool) {
+        let side = if value > 0.0 { 1 } else { -1 };
+
+        if value.abs() <= HAT_REST_ZONE {
+            self.left_rest = None;
+        } else if self.left_rest.map(|(_, s)| s) != Some(side) {
+            self.left_rest = Some((now, side));
+        }
+
+        let held = self.direction != 0
+            && side == self.direction
+            && value.abs() >= HAT_PRESS_THRESHOLD - HAT_RELEASE_HYSTERESIS;
+        if !held {
+            let pressed = value.abs() >= HAT_PRESS_THRESHOLD;
+            let was_released = self.direction == 0 || side != self.direction;
+            self.direction = if pressed { side } else { 0 };
+            if pressed && was_released {
+                self.velocity = match self.left_rest {
+                    // Reached the threshold in the same poll it left rest: digital hat
+                    Some((started, _)) if now > started => velocity_from_travel(now - started),
+                    _ => DEFAULT_STRUM_VELOCITY,
+                };
+            }
+        }
+
+        (self.direction == 1, self.direction == -1)
+    }
+
+    /// Velocity of the current (or last) strum
+    pub fn velocity(&self) -> u8 {
+        if self.velocity == 0 {
+            DEFAULT_STRUM_VELOCITY
+        } else {
+            self.velocity
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_digital_hat_strums() {
+        let now = Instant::now();
+        let mut decoder = HatStrumDecoder::new();
+        assert_eq!(decoder.update(1.0, now), (true, false));
+        assert_eq!(decoder.velocity(), DEFAULT_STRUM_VELOCITY);
+        assert_eq!(decoder.update(0.0, now), (false, false));
+        assert_eq!(decoder.update(-1.0, now), (false, true));
+    }
+
+    #[test]
+    fn test_analog_travel_sets_velocity() {
+        let start = Instant::now();
+        let ms = |n: u64| start + Duration::from_millis(n);
+
+        let mut fast = HatStrumDecoder::new();
+        fast.update(0.3, ms(0));
+        assert_eq!(fast.update(0.9, ms(2)), (true, false));
+        assert_eq!(fast.velocity(), 127);
+        // Wobble just under the threshold stays pressed (hysteresis)
+        assert_eq!(fast.update(0.45, ms(3)), (true, false));
+
+        let mut slow = HatStrumDecoder::new();
+        slow.update(-0.2, ms(0));
+        slow.update(-0.4, ms(20));
+        assert_eq!(slow.update(-0.6, ms(30)), (false, true));
+        assert!(slow.velocity() < 80 && slow.velocity() > 40);
+    }
+}
@@ -1,11 +1,22 @@
 use std::sync::Arc;
-use std::sync::atomic::{AtomicBool, AtomicU64, AtomicI32, Ordering};
+use std::sync::atomic::{AtomicBool, AtomicU8, AtomicU64, AtomicI32, Ordering};
 use std::thread;
 use std::time::{Duration, Instant};
 use anyhow::Result;
 use gilrs::{Gilrs, GamepadId, Button, Axis};
+use crate::{ControlId, ControllerState};
 use crate::raw_diagnostics::RawDiagnostics;
 use crate::mapping_wizard::MappingWizard;
+use crate::hotplug::{ControllerDeviceInfo, ControllerEvent, ControllerEventBus};
+use crate::mapping_profile::{AppAction, AxisBinding, AxisButtonState, MappingProfile, ResponseCurve};
+use crate::whammy_calibration::{WhammyCalibration, WhammyCalibrator};
+use crate::whammy_filter::WhammyFilter;
+use crate::debounce::{DebounceFilter, DEBOUNCED_ACTIONS};
+use crate::hat_strum::{HatStrumDecoder, DEFAULT_STRUM_VELOCITY};
+use crate::hardware_test::HardwareTester;
+use crate::footswitch::{Footswitch, FootswitchInfo};
+use crate::fret_leds::{FretLedInfo, FretLedOutput, FretLeds};
+use crate::rumble::{Rumble, RumbleCue};
 
 /// High-performance atomic controller state for zero-latency access
 /// All fields are atomic for lock-free access from multiple threads
@@ -35,11 +46,38 @@ pub struct AtomicControllerState {
     // Whammy bar (atomic i32 storing f32 bits)
     pub whammy_bar: AtomicI32,
     
+    // Tilt sensor (atomic i32 storing f32 bits)
+    pub tilt: AtomicI32,
+    
     // Connection state
     pub connected: AtomicBool,
     
     // Last update timestamp (nanoseconds since epoch)
     pub last_update: AtomicU64,
+    
+    // Last fret/strum change (nanoseconds since epoch), for latency measurement
+    pub last_input: AtomicU64,
+    
+    // Velocity of the current/last strum (0 = unknown)
+    pub strum_velocity: AtomicU8,
+    
+    // Tap tempo action (bound in the mapping profile)
+    pub tap_tempo: AtomicBool,
+    
+    // Last tap tempo press (nanoseconds since epoch)
+    pub last_tap_tempo: AtomicU64,
+    
+    // Sustain pedal action (bound in the mapping profile)
+    pub sustain_pedal: AtomicBool,
+    
+    // Config profile quick-switch action (bound in the mapping profile)
+    pub next_config_profile: AtomicBool,
+    
+    // USB foot switch held (written by the foot switch reader)
+    pub footswitch: AtomicBool,
+    
+    // Battery charge in percent + 1 (0 = unknown or wired)
+    pub battery: AtomicU8,
 }
 
 impl AtomicControllerState {
@@ -57,14 +95,90 @@ impl AtomicControllerState {
         self.whammy_bar.store(value.to_bits() as i32, Ordering::Relaxed);
     }
     
+    /// Get tilt sensor value as f32
+    pub fn get_tilt(&self) -> f32 {
+        f32::from_bits(self.tilt.load(Ordering::Relaxed) as u32)
+    }
+    
+    /// Set tilt sensor value from f32
+    pub fn set_tilt(&self, value: f32) {
+        self.tilt.store(value.to_bits() as i32, Ordering::Relaxed);
+    }
+    
+    /// Mark the guitar unplugged and release everything it held, so no
+    /// fret, strum or whammy stays down after the cable is pulled
+    pub fn disconnect(&self) {
+        self.connected.store(false, Ordering::Relaxed);
+        self.set_battery(None);
+        for input in [
+            &self.fret_green, &self.fret_red, &self.fret_blue, &self.fret_yellow, &self.fret_orange,
+            &self.strum_up, &self.strum_down,
+            &self.dpad_up, &self.dpad_down, &self.dpad_left, &self.dpad_right,
+            &self.start, &self.select, &self.tap_tempo, &self.sustain_pedal,
+            &self.next_config_profile,
+        ] {
+            input.store(false, Ordering::Relaxed);
+        }
+        self.set_whammy(0.0);
+        self.set_tilt(0.0);
+        self.strum_velocity.store(0, Ordering::Relaxed);
+        self.update_timestamp();
+    }
+    
+    /// Battery charge in percent (None = unknown or wired)
+    pub fn get_battery(&self) -> Option<u8> {
+        self.battery.load(Ordering::Relaxed).checked_sub(1)
+    }
+    
+    /// Set battery charge in percent
+    pub fn set_battery(&self, percent: Option<u8>) {
+        self.battery.store(percent.map_or(0, |percent| percent.min(100) + 1), Ordering::Relaxed);
+    }
+    
     /// Update timestamp to current time
     pub fn update_timestamp(&self) {
-        let now = std::time::SystemTime::now()
-            .duration_since(std::time::UNIX_EPOCH)
-            .unwrap()
-            .as_nanos() as u64;
-        self.last_update.store(now, Ordering::Relaxed);
+        self.last_update.store(timestamp_nanos(), Ordering::Relaxed);
     }
+    
+    /// Read the current state (lock-free)
+    pub fn snapshot(&self, player_index: usize) -> ControllerStateSnapshot {
+        ControllerStateSnapshot {
+            player_index,
+            fret_green: self.fret_green.load(Ordering::Relaxed),
+            fret_red: self.fret_red.load(Ordering::Relaxed),
+            fret_blue: self.fret_blue.load(Ordering::Relaxed),
+            fret_yellow: self.fret_yellow.load(Ordering::Relaxed),
+            fret_orange: self.fret_orange.load(Ordering::Relaxed),
+            strum_up: self.strum_up.load(Ordering::Relaxed),
+            strum_down: self.strum_down.load(Ordering::Relaxed),
+            dpad_up: self.dpad_up.load(Ordering::Relaxed),
+            dpad_down: self.dpad_down.load(Ordering::Relaxed),
+            dpad_left: self.dpad_left.load(Ordering::Relaxed),
+            dpad_right: self.dpad_right.load(Ordering::Relaxed),
+            start: self.start.load(Ordering::Relaxed),
+            select: self.select.load(Ordering::Relaxed),
+            whammy_bar: self.get_whammy(),
+            tilt: self.get_tilt(),
+            connected: self.connected.load(Ordering::Relaxed),
+            timestamp: self.last_update.load(Ordering::Relaxed),
+            input_timestamp: self.last_input.load(Ordering::Relaxed),
+            strum_velocity: self.strum_velocity.load(Ordering::Relaxed),
+            tap_tempo: self.tap_tempo.load(Ordering::Relaxed),
+            tap_tempo_timestamp: self.last_tap_tempo.load(Ordering::Relaxed),
+            sustain_pedal: self.sustain_pedal.load(Ordering::Relaxed) || self.footswitch.load(Ordering::Relaxed),
+            next_config_profile: self.next_config_profile.load(Ordering::Relaxed),
+            battery: self.get_battery(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Current time in nanoseconds since the Unix epoch (shared clock for latency measurement)
+pub fn timestamp_nanos() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos() as u64
 }
 
 /// Audio callback trait for instant sound triggering
@@ -79,16 +193,399 @@ pub trait AudioCallback: Send + Sync {
     fn on_whammy_change(&self, value: f32);
 }
 
+/// Fire audio callbacks for fret and strum edges between two polls
+pub(crate) fn dispatch_edges(
+    callback: &dyn AudioCallback,
+    frets: &[bool; 5],
+    prev_frets: &[bool; 5],
+    strum: &[bool; 2],
+    prev_strum: &[bool; 2],
+) {
+    // Detect fret button press/release edges
+    for (i, (&current, &previous)) in frets.iter().zip(prev_frets.iter()).enumerate() {
+        if current && !previous {
+            // Button pressed - instant audio trigger (non-blocking)
+            callback.on_fret_press(i as u8, 1.0);
+        } else if !current && previous {
+            // Button released (non-blocking)
+            callback.on_fret_release(i as u8);
+        }
+    }
+
+    // Detect strum edges (non-blocking)
+    for (i, (&current, &previous)) in strum.iter().zip(prev_strum.iter()).enumerate() {
+        if current && !previous {
+            // Strum - instant audio trigger (non-blocking)
+            callback.on_strum(i == 0, 1.0); // true = up, false = down
+        }
+    }
+}
+
+/// Input backend feeding the atomic controller state
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControllerBackend {
+    /// gilrs gamepad API (HID / SDL mappings)
+    Gilrs,
+    /// Windows XInput, for Xbox 360 wireless guitars
+    XInput { user_index: u32 },
+}
+
+impl ControllerBackend {
+    /// Select a backend from `ControllerConfig.device_id` ("auto", "xinput", "xinput:1", ...)
+    pub fn from_device_id(device_id: &str) -> Self {
+        match crate::xinput::parse_device_id(device_id) {
+            Some(user_index) => ControllerBackend::XInput { user_index },
+            None => ControllerBackend::Gilrs,
+        }
+    }
+}
+
+/// Maximum number of guitars tracked simultaneously (one per player)
+pub const MAX_PLAYERS: usize = 2;
+
+/// How often wireless guitars are asked for their battery level
+const BATTERY_POLL_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Battery charge in percent from gilrs (None = unknown or wired)
+fn battery_percent(power: gilrs::PowerInfo) -> Option<u8> {
+    match power {
+        gilrs::PowerInfo::Discharging(percent) | gilrs::PowerInfo::Charging(percent) => Some(percent),
+        gilrs::PowerInfo::Charged => Some(100),
+        gilrs::PowerInfo::Unknown | gilrs::PowerInfo::Wired => None,
+    }
+}
+
+/// Assign an id to a player slot, reusing its slot if already assigned.
+/// Returns `None` if all slots are taken.
+pub(crate) fn assign_slot<T: PartialEq + Copy>(slots: &mut [Option<T>], id: T) -> Option<usize> {
+    if let Some(index) = slots.iter().position(|slot| *slot == Some(id)) {
+        return Some(index);
+    }
+    let index = slots.iter().position(|slot| slot.is_none())?;
+    slots[index] = Some(id);
+    Some(index)
+}
+
+/// Release the slot holding an id, returning its index
+pub(crate) fn release_slot<T: PartialEq + Copy>(slots: &mut [Option<T>], id: T) -> Option<usize> {
+    let index = slots.iter().position(|slot| *slot == Some(id))?;
+    slots[index] = None;
+    Some(index)
+}
+
+/// Release a disconnected gamepad's player slot and input state and announce it
+fn disconnect_gamepad(
+    gilrs: &Gilrs,
+    id: GamepadId,
+    slots: &mut [Option<GamepadId>],
+    players: &[Arc<AtomicControllerState>],
+    events: &ControllerEventBus,
+) {
+    log::info!("🎮 Guitar disconnected (ID: {:?})", id);
+    let player_index = release_slot(slots, id);
+    if let Some(player) = player_index {
+        players[player].disconnect();
+    }
+    events.publish(ControllerEvent::Disconnected {
+        device: gilrs_device_info(&gilrs.gamepad(id), id),
+        player_index,
+    });
+}
+
+/// Record a gilrs event for raw diagnostics and the mapping wizard
+fn record_raw_event(gilrs: &Gilrs, event: &gilrs::Event, raw_diagnostics: &RawDiagnostics, mapping_wizard: &MappingWizard) {
+    let gamepad_name = match gilrs.connected_gamepad(event.id) {
+        Some(gamepad) => gamepad.name().to_string(),
+        None => "Unknown".to_string(),
+    };
+    raw_diagnostics.record_event(event, &gamepad_name);
+    let raw_event = crate::raw_diagnostics::RawInputEvent::from_gilrs_event(event, &gamepad_name);
+    mapping_wizard.record_event(&raw_event);
+}
+
+/// Axis binding driving a button action, with threshold state per player
+#[derive(Debug, Clone)]
+struct AxisButtonBinding {
+    action: AppAction,
+    axis: Axis,
+    binding: AxisBinding,
+    states: [AxisButtonState; MAX_PLAYERS],
+}
+
+/// Mapping profile settings applied inside the polling loop
+#[derive(Debug, Default)]
+pub(crate) struct ProfileBindings {
+    axis_buttons: Vec<AxisButtonBinding>,
+    /// Profile button bindings for app-level actions (tap tempo)
+    tool_buttons: Vec<(AppAction, Button)>,
+    whammy_calibration: Option<WhammyCalibration>,
+    /// Response curves of the profile's whammy and tilt axes
+    whammy_curve: ResponseCurve,
+    tilt_curve: ResponseCurve,
+    /// Debounce per player, indexed like `DEBOUNCED_ACTIONS`
+    pub(crate) debounce: [[DebounceFilter; DEBOUNCED_ACTIONS.len()]; MAX_PLAYERS],
+    /// Whammy deadzone and smoothing per player
+    pub(crate) whammy_filter: [WhammyFilter; MAX_PLAYERS],
+}
+
+/// Parse a gilrs axis from its Debug name as stored in mapping profiles
+fn parse_gilrs_axis(name: &str) -> Option<Axis> {
+    match name {
+        "LeftStickX" => Some(Axis::LeftStickX),
+        "LeftStickY" => Some(Axis::LeftStickY),
+        "LeftZ" => Some(Axis::LeftZ),
+        "RightStickX" => Some(Axis::RightStickX),
+        "RightStickY" => Some(Axis::RightStickY),
+        "RightZ" => Some(Axis::RightZ),
+        "DPadX" => Some(Axis::DPadX),
+        "DPadY" => Some(Axis::DPadY),
+        _ => None,
+    }
+}
+
+/// Parse a gilrs button from its Debug name as stored in mapping profiles
+fn parse_gilrs_button(name: &str) -> Option<Button> {
+    match name {
+        "South" => Some(Button::South),
+        "East" => Some(Button::East),
+        "North" => Some(Button::North),
+        "West" => Some(Button::West),
+        "C" => Some(Button::C),
+        "Z" => Some(Button::Z),
+        "LeftTrigger" => Some(Button::LeftTrigger),
+        "LeftTrigger2" => Some(Button::LeftTrigger2),
+        "RightTrigger" => Some(Button::RightTrigger),
+        "RightTrigger2" => Some(Button::RightTrigger2),
+        "Select" => Some(Button::Select),
+        "Start" => Some(Button::Start),
+        "Mode" => Some(Button::Mode),
+        "LeftThumb" => Some(Button::LeftThumb),
+        "RightThumb" => Some(Button::RightThumb),
+        "DPadUp" => Some(Button::DPadUp),
+        "DPadDown" => Some(Button::DPadDown),
+        "DPadLeft" => Some(Button::DPadLeft),
+        "DPadRight" => Some(Button::DPadRight),
+        _ => None,
+    }
+}
+
+/// Button and axis reads shared by the gilrs and XInput backends, so both
+/// run through the same polling logic
+pub(crate) trait GuitarInput {
+    fn is_pressed(&self, button: Button) -> bool;
+    fn value(&self, axis: Axis) -> f32;
+}
+
+impl GuitarInput for gilrs::Gamepad<'_> {
+    fn is_pressed(&self, button: Button) -> bool {
+        gilrs::Gamepad::is_pressed(self, button)
+    }
+    
+    fn value(&self, axis: Axis) -> f32 {
+        gilrs::Gamepad::value(self, axis)
+    }
+}
+
+/// Read a guitar into the atomic state, returning (frets, strum) for edge detection
+pub(crate) fn poll_gamepad(
+    gamepad: &impl GuitarInput,
+    state: &AtomicControllerState,
+    bindings: &mut ProfileBindings,
+    hat_strum: &mut HatStrumDecoder,
+    whammy_calibrator: &WhammyCalibrator,
+    raw_diagnostics: &RawDiagnostics,
+    player: usize,
+) -> ([bool; 5], [bool; 2]) {
+    // Read all button states (fastest possible)
+    let mut frets = [
+        gamepad.is_pressed(Button::South),  // Green
+        gamepad.is_pressed(Button::East),   // Red  
+        gamepad.is_pressed(Button::North),  // Yellow (was West - swapped)
+        gamepad.is_pressed(Button::West),   // Blue (was North - swapped)
+        gamepad.is_pressed(Button::LeftTrigger) || gamepad.is_pressed(Button::LeftTrigger2), // Orange
+    ];
+    
+    // Check if we have a real strum bar (RightTrigger buttons)
+    let has_strum_bar = gamepad.is_pressed(Button::RightTrigger) || gamepad.is_pressed(Button::RightTrigger2);
+    
+    let mut strum = if has_strum_bar {
+        // Use RightTrigger buttons for strum if available
+        [
+            gamepad.is_pressed(Button::RightTrigger),
+            gamepad.is_pressed(Button::RightTrigger2),
+        ]
+    } else {
+        // Fall back to D-pad for strum if no RightTrigger
+        [
+            gamepad.is_pressed(Button::DPadUp),
+            gamepad.is_pressed(Button::DPadDown),
+        ]
+    };
+    
+    // D-pad is ONLY read if we're NOT using it for strum
+    let mut dpad = if has_strum_bar {
+        [
+            gamepad.is_pressed(Button::DPadUp),
+            gamepad.is_pressed(Button::DPadDown),
+        ]
+    } else {
+        [false, false] // Don't report d-pad if it's being used for strum
+    };
+    
+    // Guitars reporting strum as a hat axis instead of buttons
+    let now = Instant::now();
+    if !has_strum_bar {
+        let (hat_up, hat_down) = hat_strum.update(gamepad.value(Axis::DPadY), now);
+        if hat_up || hat_down {
+            state.strum_velocity.store(hat_strum.velocity(), Ordering::Relaxed);
+        } else if strum[0] || strum[1] {
+            state.strum_velocity.store(DEFAULT_STRUM_VELOCITY, Ordering::Relaxed);
+        }
+        strum[0] |= hat_up;
+        strum[1] |= hat_down;
+    } else if strum[0] || strum[1] {
+        state.strum_velocity.store(DEFAULT_STRUM_VELOCITY, Ordering::Relaxed);
+    }
+    
+    let mut dpad_left = gamepad.is_pressed(Button::DPadLeft);
+    let mut dpad_right = gamepad.is_pressed(Button::DPadRight);
+    let mut start = gamepad.is_pressed(Button::Start);
+    let mut select = gamepad.is_pressed(Button::Select);
+    let tool_pressed = |tool: AppAction| bindings.tool_buttons.iter()
+        .any(|(action, button)| *action == tool && gamepad.is_pressed(*button));
+    let mut tap_tempo = tool_pressed(AppAction::TapTempo);
+    let mut sustain_pedal = tool_pressed(AppAction::SustainPedal);
+    let mut next_config_profile = tool_pressed(AppAction::NextConfigProfile);
+    
+    // Axis bindings for button actions (guitars reporting strum/frets as axes)
+    for axis_button in bindings.axis_buttons.iter_mut() {
+        let raw = gamepad.value(axis_button.axis);
+        let pressed = axis_button.states[player].update(&axis_button.binding, raw);
+        let target = match axis_button.action {
+            AppAction::FretGreen => &mut frets[0],
+            AppAction::FretRed => &mut frets[1],
+            AppAction::FretYellow => &mut frets[2],
+            AppAction::FretBlue => &mut frets[3],
+            AppAction::FretOrange => &mut frets[4],
+            AppAction::StrumUp => &mut strum[0],
+            AppAction::StrumDown => &mut strum[1],
+            AppAction::DPadUp => &mut dpad[0],
+            AppAction::DPadDown => &mut dpad[1],
+            AppAction::DPadLeft => &mut dpad_left,
+            AppAction::DPadRight => &mut dpad_right,
+            AppAction::Start => &mut start,
+            AppAction::Select => &mut select,
+            AppAction::TapTempo => &mut tap_tempo,
+            AppAction::SustainPedal => &mut sustain_pedal,
+            AppAction::NextConfigProfile => &mut next_config_profile,
+            _ => continue,
+        };
+        *target |= pressed;
+    }
+    
+    // Debounce frets and strum before edge detection
+    for (i, action) in DEBOUNCED_ACTIONS.iter().enumerate() {
+        let value = if i < frets.len() { &mut frets[i] } else { &mut strum[i - frets.len()] };
+        let (debounced, filtered) = bindings.debounce[player][i].update(*value, now);
+        if filtered {
+            raw_diagnostics.record_filtered(*action);
+        }
+        *value = debounced;
+    }
+    
+    // Timestamp fret/strum changes as close to the raw event as possible
+    let previous = [
+        state.fret_green.load(Ordering::Relaxed),
+        state.fret_red.load(Ordering::Relaxed),
+        state.fret_yellow.load(Ordering::Relaxed),
+        state.fret_blue.load(Ordering::Relaxed),
+        state.fret_orange.load(Ordering::Relaxed),
+        state.strum_up.load(Ordering::Relaxed),
+        state.strum_down.load(Ordering::Relaxed),
+    ];
+    if frets.iter().chain(strum.iter()).zip(previous.iter()).any(|(now, before)| now != before) {
+        state.last_input.store(timestamp_nanos(), Ordering::Relaxed);
+    }
+    
+    // Update atomic state (lock-free)
+    state.fret_green.store(frets[0], Ordering::Relaxed);
+    state.fret_red.store(frets[1], Ordering::Relaxed);
+    state.fret_yellow.store(frets[2], Ordering::Relaxed);
+    state.fret_blue.store(frets[3], Ordering::Relaxed);
+    state.fret_orange.store(frets[4], Ordering::Relaxed);
+    
+    state.strum_up.store(strum[0], Ordering::Relaxed);
+    state.strum_down.store(strum[1], Ordering::Relaxed);
+    
+    state.dpad_up.store(dpad[0], Ordering::Relaxed);
+    state.dpad_down.store(dpad[1], Ordering::Relaxed);
+    
+    // D-pad and other controls
+    state.dpad_left.store(dpad_left, Ordering::Relaxed);
+    state.dpad_right.store(dpad_right, Ordering::Relaxed);
+    state.start.store(start, Ordering::Relaxed);
+    state.select.store(select, Ordering::Relaxed);
+    state.sustain_pedal.store(sustain_pedal, Ordering::Relaxed);
+    state.next_config_profile.store(next_config_profile, Ordering::Relaxed);
+    
+    // Timestamp tap tempo presses here so taps keep 1ms precision
+    if tap_tempo && !state.tap_tempo.swap(true, Ordering::Relaxed) {
+        state.last_tap_tempo.store(timestamp_nanos(), Ordering::Relaxed);
+    } else if !tap_tempo {
+        state.tap_tempo.store(false, Ordering::Relaxed);
+    }
+    
+    // Whammy bar (calibrated profiles normalize to 0.0-1.0, split ones pull to -1.0)
+    let raw_whammy = gamepad.value(Axis::RightStickX);
+    if player == 0 {
+        whammy_calibrator.record(raw_whammy);
+    }
+    let whammy = bindings.whammy_curve.apply(match &bindings.whammy_calibration {
+        Some(calibration) => calibration.normalize(raw_whammy),
+        None => raw_whammy,
+    });
+    state.set_whammy(bindings.whammy_filter[player].apply(whammy));
+    state.set_tilt(bindings.tilt_curve.apply(gamepad.value(Axis::RightStickY)));
+    
+    // Update timestamp
+    state.update_timestamp();
+    
+    (frets, strum)
+}
+
+/// Device metadata for a gilrs gamepad
+fn gilrs_device_info(gamepad: &gilrs::Gamepad<'_>, id: GamepadId) -> ControllerDeviceInfo {
+    ControllerDeviceInfo {
+        device_id: usize::from(id).to_string(),
+        name: gamepad.name().to_string(),
+        vendor_id: gamepad.vendor_id(),
+        product_id: gamepad.product_id(),
+        backend: "gilrs".to_string(),
+    }
+}
+
 /// High-performance controller with 1000Hz polling and direct audio callbacks
 pub struct PerformanceController {
-    state: Arc<AtomicControllerState>,
+    /// One atomic state per player slot (index = player_index)
+    players: Vec<Arc<AtomicControllerState>>,
+    backend: ControllerBackend,
     audio_callback: Option<Arc<dyn AudioCallback>>,
     polling_thread: Option<thread::JoinHandle<()>>,
     should_stop: Arc<AtomicBool>,
     gilrs: Arc<std::sync::Mutex<Gilrs>>,
-    active_gamepad: Arc<std::sync::Mutex<Option<GamepadId>>>, // Store GamepadId directly
+    active_gamepads: Arc<std::sync::Mutex<[Option<GamepadId>; MAX_PLAYERS]>>, // Gamepad per player slot
     raw_diagnostics: Arc<RawDiagnostics>,
     mapping_wizard: Arc<MappingWizard>,
+    events: Arc<ControllerEventBus>,
+    profile_bindings: Arc<std::sync::Mutex<ProfileBindings>>,
+    whammy_calibrator: Arc<WhammyCalibrator>,
+    hardware_tester: Arc<HardwareTester>,
+    /// USB foot switch driving player 1's sustain pedal
+    footswitch: Option<Footswitch>,
+    /// Player 1's guitar fret LEDs, when it has them
+    fret_leds: Option<FretLedOutput>,
+    /// Force feedback cues (off until enabled)
+    rumble: std::sync::Mutex<Rumble>,
 }
 
 impl PerformanceController {
@@ -99,14 +596,22 @@ impl PerformanceController {
         log::info!("🎮 Gilrs initialized successfully");
         
         Ok(Self {
-            state: Arc::new(AtomicControllerState::new()),
+            players: (0..MAX_PLAYERS).map(|_| Arc::new(AtomicControllerState::new())).collect(),
+            backend: ControllerBackend::Gilrs,
             audio_callback: None,
             polling_thread: None,
             should_stop: Arc::new(AtomicBool::new(false)),
             gilrs: Arc::new(std::sync::Mutex::new(gilrs)),
-            active_gamepad: Arc::new(std::sync::Mutex::new(None)), // None = no gamepad
+            active_gamepads: Arc::new(std::sync::Mutex::new([None; MAX_PLAYERS])), // None = no gamepad
             raw_diagnostics: Arc::new(RawDiagnostics::new()),
             mapping_wizard: Arc::new(MappingWizard::new()),
+            events: Arc::new(ControllerEventBus::new()),
+            profile_bindings: Arc::new(std::sync::Mutex::new(ProfileBindings::default())),
+            whammy_calibrator: Arc::new(WhammyCalibrator::new()),
+            hardware_tester: Arc::new(HardwareTester::new()),
+            footswitch: None,
+            fret_leds: None,
+            rumble: std::sync::Mutex::new(Rumble::new(false)),
         })
     }
     
@@ -120,7 +625,91 @@ impl PerformanceController {
         Arc::clone(&self.mapping_wizard)
     }
     
-    /// Set audio callback for instant sound triggering
+    /// Subscribe to hot-plug events (connect/disconnect) from the polling thread
+    pub fn subscribe_events(&self) -> std::sync::mpsc::Receiver<ControllerEvent> {
+        self.events.subscribe()
+    }
+    
+    /// Select the input backend (takes effect on the next `start_polling`)
+    pub fn set_backend(&mut self, backend: ControllerBackend) {
+        log::info!("🎮 Controller backend: {:?}", backend);
+        self.backend = backend;
+    }
+
+    /// Currently selected input backend
+    pub fn backend(&self) -> ControllerBackend {
+        self.backend
+    }
+    
+    /// Set the whammy smoothing factor and deadzone for all players
+    /// (from the genre's `WhammyDefaults`)
+    pub fn set_whammy_filter(&self, smoothing: f32, deadzone: f32) {
+        let mut profile_bindings = self.profile_bindings.lock().unwrap();
+        for filter in profile_bindings.whammy_filter.iter_mut() {
+            filter.configure(smoothing, deadzone);
+        }
+    }
+    
+    /// Apply a mapping profile's axis bindings that drive button actions
+    /// (e.g. a strum bar reported as an axis), its tool button bindings (tap
+    /// tempo), its debounce windows, its whammy calibration and the response
+    /// curves of its whammy and tilt axes.
+    /// Pass `None` to clear them.
+    pub fn set_mapping_profile(&self, profile: Option<&MappingProfile>) {
+        let bindings: Vec<AxisButtonBinding> = profile
+            .map(|profile| profile.axis_button_bindings())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(action, binding)| {
+                let Some(axis) = parse_gilrs_axis(&binding.logical_axis) else {
+                    log::warn!("🎮 Unsupported axis '{}' for {:?}", binding.logical_axis, action);
+                    return None;
+                };
+                Some(AxisButtonBinding { action, axis, binding, states: Default::default() })
+            })
+            .collect();
+        log::info!("🎮 {} axis button binding(s) active", bindings.len());
+        let tool_buttons: Vec<(AppAction, Button)> = profile
+            .map(|profile| profile.tool_button_bindings())
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|(action, name)| {
+                let Some(button) = parse_gilrs_button(&name) else {
+                    log::warn!("🎮 Unsupported button '{}' for {:?}", name, action);
+                    return None;
+                };
+                Some((action, button))
+            })
+            .collect();
+        let mut profile_bindings = self.profile_bindings.lock().unwrap();
+        profile_bindings.axis_buttons = bindings;
+        profile_bindings.tool_buttons = tool_buttons;
+        profile_bindings.whammy_calibration = profile.and_then(|profile| profile.whammy_calibration);
+        profile_bindings.whammy_curve = profile
+            .map(|profile| profile.axis_curve(AppAction::WhammyAxis))
+            .unwrap_or_default();
+        profile_bindings.tilt_curve = profile
+            .map(|profile| profile.axis_curve(AppAction::TiltAxis))
+            .unwrap_or_default();
+        for (i, action) in DEBOUNCED_ACTIONS.iter().enumerate() {
+            let window_ms = profile.map(|profile| profile.debounce_window_ms(*action)).unwrap_or(0);
+            for player in profile_bindings.debounce.iter_mut() {
+                player[i] = DebounceFilter::new(window_ms);
+            }
+        }
+    }
+    
+    /// Whammy calibrator fed by the polling thread (player 1)
+    pub fn whammy_calibrator(&self) -> Arc<WhammyCalibrator> {
+        Arc::clone(&self.whammy_calibrator)
+    }
+    
+    /// Guided hardware test fed by the polling thread (player 1)
+    pub fn hardware_tester(&self) -> Arc<HardwareTester> {
+        Arc::clone(&self.hardware_tester)
+    }
+    
+    /// Set audio callback for instant sound triggering (player 1 only)
     pub fn set_audio_callback(&mut self, callback: Arc<dyn AudioCallback>) {
         self.audio_callback = Some(callback);
     }
@@ -134,22 +723,45 @@ impl PerformanceController {
             return Ok(()); // Already running
         }
         
-        let state = Arc::clone(&self.state);
+        if let ControllerBackend::XInput { user_index } = self.backend {
+            self.should_stop.store(false, Ordering::Relaxed);
+            let thread = crate::xinput::spawn_polling_thread(
+                user_index,
+                Arc::clone(&self.players[0]),
+                self.audio_callback.clone(),
+                Arc::clone(&self.should_stop),
+                Arc::clone(&self.events),
+                Arc::clone(&self.hardware_tester),
+                Arc::clone(&self.profile_bindings),
+                Arc::clone(&self.whammy_calibrator),
+                Arc::clone(&self.raw_diagnostics),
+            )?;
+            self.polling_thread = Some(thread);
+            return Ok(());
+        }
+        
+        let players = self.players.clone();
         let audio_callback = self.audio_callback.clone();
         let should_stop = Arc::clone(&self.should_stop);
         let gilrs = Arc::clone(&self.gilrs);
-        let active_gamepad = Arc::clone(&self.active_gamepad);
+        let active_gamepads = Arc::clone(&self.active_gamepads);
         let raw_diagnostics = Arc::clone(&self.raw_diagnostics);
         let mapping_wizard = Arc::clone(&self.mapping_wizard);
+        let events = Arc::clone(&self.events);
+        let profile_bindings = Arc::clone(&self.profile_bindings);
+        let whammy_calibrator = Arc::clone(&self.whammy_calibrator);
+        let hardware_tester = Arc::clone(&self.hardware_tester);
         
         self.should_stop.store(false, Ordering::Relaxed);
         
         let thread = thread::spawn(move || {
             log::info!("🚀 High-performance polling thread started (1000Hz)");
             
-            // Previous state for edge detection
-            let mut prev_frets = [false; 5];  // green, red, yellow, blue, orange
-            let mut prev_strum = [false; 2];  // up, down
+            // Previous state per player for edge detection
+            let mut prev_frets = [[false; 5]; MAX_PLAYERS];  // green, red, yellow, blue, orange
+            let mut prev_strum = [[false; 2]; MAX_PLAYERS];  // up, down
+            let mut hat_strum = [HatStrumDecoder::new(); MAX_PLAYERS];
+            let mut last_battery_poll: Option<Instant> = None;
             
             while !should_stop.load(Ordering::Relaxed) {
                 let start_time = Instant::now();
@@ -163,135 +775,75 @@ impl PerformanceController {
                     
                     // Process connection events AND record for raw diagnostics
                     while let Some(event) = gilrs.next_event() {
-                        // Get gamepad name for diagnostics
-                        let gamepad_name = if let Some(gp) = gilrs.connected_gamepad(event.id) {
-                            gp.name().to_string()
-                        } else {
-                            "Unknown".to_string()
-                        };
-                        
-                        // Record raw event for diagnostics
-                        raw_diagnostics.record_event(&event, &gamepad_name);
-                        
-                        // Create RawInputEvent for mapping wizard directly
-                        let raw_event = crate::raw_diagnostics::RawInputEvent::from_gilrs_event(&event, &gamepad_name);
-                        mapping_wizard.record_event(&raw_event);
+                        record_raw_event(&gilrs, &event, &raw_diagnostics, &mapping_wizard);
                         
                         match event.event {
                             gilrs::EventType::Connected => {
                                 let gamepad = gilrs.gamepad(event.id);
-                                log::info!("🎮 Guitar connected: {} (ID: {:?})", gamepad.name(), event.id);
-                                *active_gamepad.lock().unwrap() = Some(event.id);
-                                state.connected.store(true, Ordering::Relaxed);
+                                let player_index = assign_slot(&mut *active_gamepads.lock().unwrap(), event.id);
+                                match player_index {
+                                    Some(player) => {
+                                        log::info!("🎮 Guitar connected: {} (ID: {:?}) as player {}", gamepad.name(), event.id, player + 1);
+                                        players[player].connected.store(true, Ordering::Relaxed);
+                                        last_battery_poll = None;
+                                    }
+                                    None => {
+                                        log::warn!("🎮 Guitar connected: {} (ID: {:?}) but all {} player slots are taken", gamepad.name(), event.id, MAX_PLAYERS);
+                                    }
+                                }
+                                events.publish(ControllerEvent::Connected {
+                                    device: gilrs_device_info(&gamepad, event.id),
+                                    player_index,
+                                });
                             }
                             gilrs::EventType::Disconnected => {
-                                log::info!("🎮 Guitar disconnected (ID: {:?})", event.id);
-                                let mut guard = active_gamepad.lock().unwrap();
-                                if *guard == Some(event.id) {
-                                    *guard = None;
-                                    state.connected.store(false, Ordering::Relaxed);
-                                }
+                                disconnect_gamepad(&gilrs, event.id, &mut *active_gamepads.lock().unwrap(), &players, &events);
                             }
                             _ => {}
                         }
                     }
                     
-                    // Poll active gamepad if connected (get gamepad_id once to avoid double lock)
-                    let current_gamepad_id = {
-                        let guard = active_gamepad.lock().unwrap();
-                        *guard
-                    };
+                    // Poll every assigned gamepad (copy slots once to avoid holding the lock)
+                    let current_gamepads = *active_gamepads.lock().unwrap();
+                    let mut profile_bindings = profile_bindings.lock().unwrap();
+                    let poll_battery = !matches!(last_battery_poll, Some(polled) if polled.elapsed() < BATTERY_POLL_INTERVAL);
+                    if poll_battery {
+                        last_battery_poll = Some(start_time);
+                    }
                     
-                    if let Some(gamepad_id) = current_gamepad_id {
-                        let gamepad = gilrs.gamepad(gamepad_id);
-                        
-                        // Read all button states (fastest possible)
-                        let frets = [
-                            gamepad.is_pressed(Button::South),  // Green
-                            gamepad.is_pressed(Button::East),   // Red  
-                            gamepad.is_pressed(Button::North),  // Yellow (was West - swapped)
-                            gamepad.is_pressed(Button::West),   // Blue (was North - swapped)
-                            gamepad.is_pressed(Button::LeftTrigger) || gamepad.is_pressed(Button::LeftTrigger2), // Orange
-                        ];
-                        
-                        // Check if we have a real strum bar (RightTrigger buttons)
-                        let has_strum_bar = gamepad.is_pressed(Button::RightTrigger) || gamepad.is_pressed(Button::RightTrigger2);
-                        
-                        let strum = if has_strum_bar {
-                            // Use RightTrigger buttons for strum if available
-                            [
-                                gamepad.is_pressed(Button::RightTrigger),
-                                gamepad.is_pressed(Button::RightTrigger2),
-                            ]
-                        } else {
-                            // Fall back to D-pad for strum if no RightTrigger
-                            [
-                                gamepad.is_pressed(Button::DPadUp),
-                                gamepad.is_pressed(Button::DPadDown),
-                            ]
-                        };
-                        
-                        // D-pad is ONLY read if we're NOT using it for strum
-                        let dpad = if has_strum_bar {
-                            [
-                                gamepad.is_pressed(Button::DPadUp),
-                                gamepad.is_pressed(Button::DPadDown),
-                            ]
-                        } else {
-                            [false, false] // Don't report d-pad if it's being used for strum
-                        };
-                        
-                        // Update atomic state (lock-free)
-                        state.fret_green.store(frets[0], Ordering::Relaxed);
-                        state.fret_red.store(frets[1], Ordering::Relaxed);
-                        state.fret_yellow.store(frets[2], Ordering::Relaxed);
-                        state.fret_blue.store(frets[3], Ordering::Relaxed);
-                        state.fret_orange.store(frets[4], Ordering::Relaxed);
-                        
-                        state.strum_up.store(strum[0], Ordering::Relaxed);
-                        state.strum_down.store(strum[1], Ordering::Relaxed);
-                        
-                        state.dpad_up.store(dpad[0], Ordering::Relaxed);
-                        state.dpad_down.store(dpad[1], Ordering::Relaxed);
-                        
-                        // D-pad and other controls
-                        state.dpad_left.store(gamepad.is_pressed(Button::DPadLeft), Ordering::Relaxed);
-                        state.dpad_right.store(gamepad.is_pressed(Button::DPadRight), Ordering::Relaxed);
-                        state.start.store(gamepad.is_pressed(Button::Start), Ordering::Relaxed);
-                        state.select.store(gamepad.is_pressed(Button::Select), Ordering::Relaxed);
-                        
-                        // Whammy bar
-                        let whammy = gamepad.value(Axis::RightStickX);
-                        state.set_whammy(whammy);
-                        
-                        // Update timestamp
-                        state.update_timestamp();
-                        
-                        // Instant audio callbacks on button press edges (non-blocking)
-                        if let Some(ref callback) = audio_callback {
-                            // Detect fret button press/release edges
-                            for (i, (&current, &previous)) in frets.iter().zip(prev_frets.iter()).enumerate() {
-                                if current && !previous {
-                                    // Button pressed - instant audio trigger (non-blocking)
-                                    callback.on_fret_press(i as u8, 1.0);
-                                } else if !current && previous {
-                                    // Button released (non-blocking)
-                                    callback.on_fret_release(i as u8);
+                    for (player, gamepad_id) in current_gamepads.iter().enumerate() {
+                        let Some(gamepad_id) = *gamepad_id else {
+                            // Freed slot (unplugged here or in `process_events`): release
+                            // what the guitar held so the next one starts clean
+                            if prev_frets[player] != [false; 5] || prev_strum[player] != [false; 2] {
+                                if let (0, Some(callback)) = (player, &audio_callback) {
+                                    dispatch_edges(callback.as_ref(), &[false; 5], &prev_frets[player], &[false; 2], &prev_strum[player]);
                                 }
+                                prev_frets[player] = [false; 5];
+                                prev_strum[player] = [false; 2];
+                                hat_strum[player] = HatStrumDecoder::new();
+                                profile_bindings.debounce[player].iter_mut().for_each(DebounceFilter::reset);
+                                profile_bindings.whammy_filter[player].reset();
                             }
-                            
-                            // Detect strum edges (non-blocking)
-                            for (i, (&current, &previous)) in strum.iter().zip(prev_strum.iter()).enumerate() {
-                                if current && !previous {
-                                    // Strum - instant audio trigger (non-blocking)
-                                    callback.on_strum(i == 0, 1.0); // true = up, false = down
-                                }
+                            continue;
+                        };
+                        let gamepad = gilrs.gamepad(gamepad_id);
+                        if poll_battery {
+                            players[player].set_battery(battery_percent(gamepad.power_info()));
+                        }
+                        let (frets, strum) = poll_gamepad(&gamepad, &players[player], &mut profile_bindings, &mut hat_strum[player], &whammy_calibrator, &raw_diagnostics, player);
+                        
+                        // Instant audio callbacks on button press edges (non-blocking, player 1 only)
+                        if player == 0 {
+                            hardware_tester.record(&players[0].snapshot(0), None);
+                            if let Some(ref callback) = audio_callback {
+                                dispatch_edges(callback.as_ref(), &frets, &prev_frets[player], &strum, &prev_strum[player]);
                             }
                         }
                         
                         // Update previous state for next edge detection
-                        prev_frets.copy_from_slice(&frets);
-                        prev_strum.copy_from_slice(&strum);
+                        prev_frets[player] = frets;
+                        prev_strum[player] = strum;
                     }
                 } // Release gilrs lock
                 
@@ -309,6 +861,94 @@ impl PerformanceController {
         Ok(())
     }
     
+    /// Open a USB foot switch as player 1's sustain pedal (by device path, or
+    /// the first one found). Returns None when no foot switch is connected.
+    pub fn connect_footswitch(&mut self, path: Option<&str>) -> Result<Option<FootswitchInfo>> {
+        self.footswitch = None;
+        let api = hidapi::HidApi::new().map_err(|e| anyhow::anyhow!("Failed to initialize HID API: {}", e))?;
+        let found = crate::footswitch::find_footswitches(&api);
+        let info = match path {
+            Some(path) => found.into_iter().find(|info| info.path == path)
+                .ok_or_else(|| anyhow::anyhow!("Foot switch not found: {}", path))?,
+            None => match found.into_iter().next() {
+                Some(info) => info,
+                None => return Ok(None),
+            },
+        };
+        let footswitch = Footswitch::open(&api, info.clone(), Arc::clone(&self.players[0]))?;
+        self.footswitch = Some(footswitch);
+        Ok(Some(info))
+    }
+
+    /// Close the foot switch (releases the pedal)
+    pub fn disconnect_footswitch(&mut self) {
+        if self.footswitch.take().is_some() {
+            log::info!("🦶 Foot switch disconnected");
+        }
+    }
+
+    /// The open foot switch, if any
+    pub fn footswitch(&self) -> Option<&FootswitchInfo> {
+        self.footswitch.as_ref().map(|footswitch| footswitch.info())
+    }
+
+    /// Open the fret LEDs of a guitar (by device path, or the first one found).
+    /// Returns None when no guitar with fret LEDs is connected.
+    pub fn connect_fret_leds(&mut self, path: Option<&str>) -> Result<Option<FretLedInfo>> {
+        self.fret_leds = None;
+        let api = hidapi::HidApi::new().map_err(|e| anyhow::anyhow!("Failed to initialize HID API: {}", e))?;
+        let found = crate::fret_leds::find_led_guitars(&api);
+        let info = match path {
+            Some(path) => found.into_iter().find(|info| info.path == path)
+                .ok_or_else(|| anyhow::anyhow!("Guitar with fret LEDs not found: {}", path))?,
+            None => match found.into_iter().next() {
+                Some(info) => info,
+                None => return Ok(None),
+            },
+        };
+        self.fret_leds = Some(FretLedOutput::open(&api, info.clone())?);
+        Ok(Some(info))
+    }
+
+    /// Close the fret LEDs (switching them off)
+    pub fn disconnect_fret_leds(&mut self) {
+        if self.fret_leds.take().is_some() {
+            log::info!("💡 Fret LEDs disconnected");
+        }
+    }
+
+    /// The guitar whose fret LEDs are in use, if any
+    pub fn fret_leds(&self) -> Option<&FretLedInfo> {
+        self.fret_leds.as_ref().map(|output| output.info())
+    }
+
+    /// Light frets on the connected guitar (ignored without one)
+    pub fn set_fret_leds(&self, leds: FretLeds) {
+        if let Some(output) = &self.fret_leds {
+            output.set(leds);
+        }
+    }
+
+    /// Switch rumble cues on or off
+    pub fn set_rumble_enabled(&self, enabled: bool) {
+        self.rumble.lock().unwrap().set_enabled(enabled);
+    }
+
+    pub fn rumble_enabled(&self) -> bool {
+        self.rumble.lock().unwrap().enabled()
+    }
+
+    /// Rumble a player's gamepad for a cue (skipped when rumble is off, no
+    /// gamepad is in the slot or it has no force feedback)
+    pub fn rumble(&self, player_index: usize, cue: RumbleCue) -> Result<()> {
+        let gamepad = self.active_gamepads.lock().unwrap().get(player_index).copied().flatten();
+        let Some(gamepad) = gamepad else {
+            return Ok(());
+        };
+        let mut gilrs = self.gilrs.lock().unwrap();
+        self.rumble.lock().unwrap().play(&mut gilrs, gamepad, cue)
+    }
+
     /// Stop the polling thread
     pub fn stop_polling(&mut self) {
         self.should_stop.store(true, Ordering::Relaxed);
@@ -318,28 +958,24 @@ impl PerformanceController {
         }
     }
     
-    /// Get current controller state (lock-free read)
+    /// Get current controller state of player 1 (lock-free read)
     pub fn get_state(&self) -> ControllerStateSnapshot {
-        let state = &self.state;
-        
-        ControllerStateSnapshot {
-            fret_green: state.fret_green.load(Ordering::Relaxed),
-            fret_red: state.fret_red.load(Ordering::Relaxed),
-            fret_blue: state.fret_blue.load(Ordering::Relaxed),
-            fret_yellow: state.fret_yellow.load(Ordering::Relaxed),
-            fret_orange: state.fret_orange.load(Ordering::Relaxed),
-            strum_up: state.strum_up.load(Ordering::Relaxed),
-            strum_down: state.strum_down.load(Ordering::Relaxed),
-            dpad_up: state.dpad_up.load(Ordering::Relaxed),
-            dpad_down: state.dpad_down.load(Ordering::Relaxed),
-            dpad_left: state.dpad_left.load(Ordering::Relaxed),
-            dpad_right: state.dpad_right.load(Ordering::Relaxed),
-            start: state.start.load(Ordering::Relaxed),
-            select: state.select.load(Ordering::Relaxed),
-            whammy_bar: state.get_whammy(),
-            connected: state.connected.load(Ordering::Relaxed),
-            timestamp: state.last_update.load(Ordering::Relaxed),
-        }
+        self.get_player_state(0).unwrap_or_default()
+    }
+    
+    /// Get current controller state for a player slot (lock-free read)
+    pub fn get_player_state(&self, player_index: usize) -> Option<ControllerStateSnapshot> {
+        let state = self.players.get(player_index)?;
+        Some(state.snapshot(player_index))
+    }
+    
+    /// Player slots that currently have a connected guitar
+    pub fn connected_players(&self) -> Vec<usize> {
+        self.players.iter()
+            .enumerate()
+            .filter(|(_, state)| state.connected.load(Ordering::Relaxed))
+            .map(|(index, _)| index)
+            .collect()
     }
     
     /// Force connection scan (non-blocking)
@@ -349,71 +985,100 @@ impl PerformanceController {
     
     /// Process events (compatibility method - same as scan_for_controllers)
     pub fn process_events(&self) -> Result<bool> {
+        // The XInput thread tracks connection state itself
+        if matches!(self.backend, ControllerBackend::XInput { .. }) {
+            return Ok(self.players[0].connected.load(Ordering::Relaxed));
+        }
+        
         let Ok(mut gilrs) = self.gilrs.try_lock() else {
             return Ok(false); // Locked, try later
         };
         
+        let mut found = false;
+        let mut slots = self.active_gamepads.lock().unwrap();
+        
         // Process any pending connection events
         while let Some(event) = gilrs.next_event() {
-            match event.event {
-                gilrs::EventType::Connected => {
-                    let gamepad = gilrs.gamepad(event.id);
-                    log::info!("🎮 Guitar found during scan: {} (ID: {:?})", gamepad.name(), event.id);
-                    *self.active_gamepad.lock().unwrap() = Some(event.id);
-                    self.state.connected.store(true, Ordering::Relaxed);
-                    return Ok(true);
+            record_raw_event(&gilrs, &event, &self.raw_diagnostics, &self.mapping_wizard);
+            if let gilrs::EventType::Disconnected = event.event {
+                disconnect_gamepad(&gilrs, event.id, &mut *slots, &self.players, &self.events);
+            }
+            if let gilrs::EventType::Connected = event.event {
+                let gamepad = gilrs.gamepad(event.id);
+                let is_new = !slots.contains(&Some(event.id));
+                if let Some(player) = assign_slot(&mut *slots, event.id) {
+                    log::info!("🎮 Guitar found during scan: {} (ID: {:?}) as player {}", gamepad.name(), event.id, player + 1);
+                    self.players[player].connected.store(true, Ordering::Relaxed);
+                    found = true;
+                    if is_new {
+                        self.events.publish(ControllerEvent::Connected {
+                            device: gilrs_device_info(&gamepad, event.id),
+                            player_index: Some(player),
+                        });
+                    }
                 }
-                _ => {}
             }
         }
         
         // Check for existing gamepads
         for (id, gamepad) in gilrs.gamepads() {
-            log::info!("🎮 Existing gamepad found: {} (ID: {:?})", gamepad.name(), id);
-            *self.active_gamepad.lock().unwrap() = Some(id);
-            self.state.connected.store(true, Ordering::Relaxed);
-            return Ok(true);
+            let is_new = !slots.contains(&Some(id));
+            if let Some(player) = assign_slot(&mut *slots, id) {
+                if is_new {
+                    log::info!("🎮 Existing gamepad found: {} (ID: {:?}) as player {}", gamepad.name(), id, player + 1);
+                    self.events.publish(ControllerEvent::Connected {
+                        device: gilrs_device_info(&gamepad, id),
+                        player_index: Some(player),
+                    });
+                }
+                self.players[player].connected.store(true, Ordering::Relaxed);
+                found = true;
+            }
         }
         
-        Ok(false)
+        Ok(found)
     }
     
     /// Check if any device is connected (compatibility with old interface)
     pub fn find_device(&self) -> Result<bool> {
-        Ok(self.state.connected.load(Ordering::Relaxed))
+        Ok(self.players.iter().any(|state| state.connected.load(Ordering::Relaxed)))
     }
     
     /// Get debug information (compatibility with old interface)
     pub fn get_debug_info(&self) -> String {
         let mut info = String::new();
         info.push_str("Type: PerformanceController\n");
-        info.push_str(&format!("Connected: {}\n", self.state.connected.load(Ordering::Relaxed)));
+        info.push_str(&format!("Backend: {:?}\n", self.backend));
         info.push_str(&format!("Polling active: {}\n", self.polling_thread.is_some()));
-        info.push_str(&format!("Last update: {}\n", self.state.last_update.load(Ordering::Relaxed)));
         
-        let gamepad_lock = self.active_gamepad.lock().unwrap();
-        info.push_str(&format!("Active gamepad: {:?}\n", 
-            gamepad_lock.as_ref().map_or("None".to_string(), |id| format!("{:?}", id))));
-        info.push_str(&format!("Fret Green: {}\n", 
-            self.state.fret_green.load(Ordering::Relaxed)));
-        info.push_str(&format!("Fret Red: {}\n", 
-            self.state.fret_red.load(Ordering::Relaxed)));
-        info.push_str(&format!("Fret Yellow: {}\n", 
-            self.state.fret_yellow.load(Ordering::Relaxed)));
-        info.push_str(&format!("Fret Blue: {}\n", 
-            self.state.fret_blue.load(Ordering::Relaxed)));
-        info.push_str(&format!("Fret Orange: {}\n", 
-            self.state.fret_orange.load(Ordering::Relaxed)));
-        info.push_str(&format!("Strum Up: {}\n", 
-            self.state.strum_up.load(Ordering::Relaxed)));
-        info.push_str(&format!("Strum Down: {}\n", 
-            self.state.strum_down.load(Ordering::Relaxed)));
-        info.push_str(&format!("Start: {}\n", 
-            self.state.start.load(Ordering::Relaxed)));
-        info.push_str(&format!("Select: {}\n", 
-            self.state.select.load(Ordering::Relaxed)));
-        info.push_str(&format!("Whammy Bar: {}\n", 
-            self.state.get_whammy()));
+        let gamepads = *self.active_gamepads.lock().unwrap();
+        for (player, state) in self.players.iter().enumerate() {
+            info.push_str(&format!("--- Player {} ---\n", player + 1));
+            info.push_str(&format!("Connected: {}\n", state.connected.load(Ordering::Relaxed)));
+            info.push_str(&format!("Last update: {}\n", state.last_update.load(Ordering::Relaxed)));
+            info.push_str(&format!("Active gamepad: {:?}\n", 
+                gamepads[player].map_or("None".to_string(), |id| format!("{:?}", id))));
+            info.push_str(&format!("Fret Green: {}\n", 
+                state.fret_green.load(Ordering::Relaxed)));
+            info.push_str(&format!("Fret Red: {}\n", 
+                state.fret_red.load(Ordering::Relaxed)));
+            info.push_str(&format!("Fret Yellow: {}\n", 
+                state.fret_yellow.load(Ordering::Relaxed)));
+            info.push_str(&format!("Fret Blue: {}\n", 
+                state.fret_blue.load(Ordering::Relaxed)));
+            info.push_str(&format!("Fret Orange: {}\n", 
+                state.fret_orange.load(Ordering::Relaxed)));
+            info.push_str(&format!("Strum Up: {}\n", 
+                state.strum_up.load(Ordering::Relaxed)));
+            info.push_str(&format!("Strum Down: {}\n", 
+                state.strum_down.load(Ordering::Relaxed)));
+            info.push_str(&format!("Start: {}\n", 
+                state.start.load(Ordering::Relaxed)));
+            info.push_str(&format!("Select: {}\n", 
+                state.select.load(Ordering::Relaxed)));
+            info.push_str(&format!("Whammy Bar: {}\n", 
+                state.get_whammy()));
+        }
         info
     }
 }
@@ -425,8 +1090,11 @@ impl Drop for PerformanceController {
 }
 
 /// Snapshot of controller state for display/logic
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
 pub struct ControllerStateSnapshot {
+    /// Player slot this snapshot belongs to (0 = player 1)
+    #[serde(default)]
+    pub player_index: usize,
     pub fret_green: bool,
     pub fret_red: bool,
     pub fret_blue: bool,
@@ -441,6 +1109,134 @@ pub struct ControllerStateSnapshot {
     pub start: bool,
     pub select: bool,
     pub whammy_bar: f32,
+    /// Tilt sensor (-1.0..1.0, 0 when the backend does not report it)
+    #[serde(default)]
+    pub tilt: f32,
     pub connected: bool,
     pub timestamp: u64,
-}
\ No newline at end of file
+    /// Time of the last fret/strum change (nanoseconds since epoch, 0 = none)
+    #[serde(default)]
+    pub input_timestamp: u64,
+    /// Velocity of the current/last strum (0 = unknown, hat-switch guitars only)
+    #[serde(default)]
+    pub strum_velocity: u8,
+    /// Tap tempo action held
+    #[serde(default)]
+    pub tap_tempo: bool,
+    /// Time of the last tap tempo press (nanoseconds since epoch, 0 = none)
+    #[serde(default)]
+    pub tap_tempo_timestamp: u64,
+    /// Sustain pedal held (foot switch or bound action)
+    #[serde(default)]
+    pub sustain_pedal: bool,
+    /// Config profile quick-switch action held
+    #[serde(default)]
+    pub next_config_profile: bool,
+    /// Battery charge in percent (None = unknown or wired)
+    #[serde(default)]
+    pub battery: Option<u8>,
+    /// Solo frets held (keyboard simulator only)
+    #[serde(default)]
+    pub solo_green: bool,
+    #[serde(default)]
+    pub solo_red: bool,
+    #[serde(default)]
+    pub solo_yellow: bool,
+    #[serde(default)]
+    pub solo_blue: bool,
+    #[serde(default)]
+    pub solo_orange: bool,
+}
+
+impl ControllerStateSnapshot {
+    /// Convert to the state the mappers take
+    pub fn to_controller_state(&self) -> ControllerState {
+        let mut state = ControllerState::default();
+        
+        // Map buttons
+        state.buttons.insert(ControlId::FretGreen, self.fret_green);
+        state.buttons.insert(ControlId::FretRed, self.fret_red);
+        state.buttons.insert(ControlId::FretBlue, self.fret_blue);
+        state.buttons.insert(ControlId::FretYellow, self.fret_yellow);
+        state.buttons.insert(ControlId::FretOrange, self.fret_orange);
+        state.buttons.insert(ControlId::StrumUp, self.strum_up);
+        state.buttons.insert(ControlId::StrumDown, self.strum_down);
+        // Skip dpad_up and dpad_down as they're not in the original ControlId enum
+        state.buttons.insert(ControlId::DPadLeft, self.dpad_left);
+        state.buttons.insert(ControlId::DPadRight, self.dpad_right);
+        state.buttons.insert(ControlId::Start, self.start);
+        state.buttons.insert(ControlId::Select, self.select);
+        state.buttons.insert(ControlId::SustainPedal, self.sustain_pedal);
+        state.buttons.insert(ControlId::SoloGreen, self.solo_green);
+        state.buttons.insert(ControlId::SoloRed, self.solo_red);
+        state.buttons.insert(ControlId::SoloYellow, self.solo_yellow);
+        state.buttons.insert(ControlId::SoloBlue, self.solo_blue);
+        state.buttons.insert(ControlId::SoloOrange, self.solo_orange);
+        
+        // Map axes
+        state.axes.insert(ControlId::WhammyBar, self.whammy_bar);
+        state.axes.insert(ControlId::TiltSensor, self.tilt);
+        if self.strum_velocity > 0 {
+            state.axes.insert(ControlId::StrumVelocity, self.strum_velocity as f32 / 127.0);
+        }
+        
+        state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_assign_slot() {
+        let mut slots: [Option<u32>; MAX_PLAYERS] = [None; MAX_PLAYERS];
+        assert_eq!(assign_slot(&mut slots, 7), Some(0));
+        assert_eq!(assign_slot(&mut slots, 9), Some(1));
+        // Already assigned ids keep their slot
+        assert_eq!(assign_slot(&mut slots, 7), Some(0));
+        // No free slots left
+        assert_eq!(assign_slot(&mut slots, 11), None);
+    }
+
+    #[test]
+    fn test_release_slot() {
+        let mut slots: [Option<u32>; MAX_PLAYERS] = [None; MAX_PLAYERS];
+        assign_slot(&mut slots, 7);
+        assign_slot(&mut slots, 9);
+        assert_eq!(release_slot(&mut slots, 7), Some(0));
+        assert_eq!(release_slot(&mut slots, 7), None);
+        // Freed slot is reused by the next guitar
+        assert_eq!(assign_slot(&mut slots, 11), Some(0));
+        assert_eq!(slots, [Some(11), Some(9)]);
+    }
+
+    #[test]
+    fn test_battery_level() {
+        let state = AtomicControllerState::new();
+        assert_eq!(state.snapshot(0).battery, None);
+        state.set_battery(battery_percent(gilrs::PowerInfo::Discharging(0)));
+        assert_eq!(state.snapshot(0).battery, Some(0));
+        state.set_battery(battery_percent(gilrs::PowerInfo::Charged));
+        assert_eq!(state.get_battery(), Some(100));
+        state.set_battery(battery_percent(gilrs::PowerInfo::Wired));
+        assert_eq!(state.get_battery(), None);
+    }
+
+    #[test]
+    fn test_disconnect_releases_held_inputs() {
+        let state = AtomicControllerState::new();
+        state.connected.store(true, Ordering::Relaxed);
+        state.fret_green.store(true, Ordering::Relaxed);
+        state.strum_down.store(true, Ordering::Relaxed);
+        state.set_whammy(0.7);
+        state.set_battery(Some(40));
+
+        state.disconnect();
+        let snapshot = state.snapshot(0);
+        assert!(!snapshot.connected);
+        assert!(!snapshot.fret_green && !snapshot.strum_down);
+        assert_eq!(snapshot.whammy_bar, 0.0);
+        assert_eq!(snapshot.battery, None);
+    }
+}
@@ -0,0 +1,117 @@
+//! Hot-plug notifications from the polling thread
+//! Subscribers receive connect/disconnect events over a channel, so the UI no
+//! longer has to poll for controllers.
+
+use std::sync::mpsc::{channel, Receiver, Sender};
+use std::sync::Mutex;
+use serde::{Deserialize, Serialize};
+
+/// Metadata about a connected device
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControllerDeviceInfo {
+    /// Backend specific device id (gilrs id or XInput slot)
+    pub device_id: String,
+    pub name: String,
+    pub vendor_id: Option<u16>,
+    pub product_id: Option<u16>,
+    /// Backend that reported the device ("gilrs" or "xinput")
+    pub backend: String,
+}
+
+/// Connection change reported by the polling thread
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum ControllerEvent {
+    Connected {
+        device: ControllerDeviceInfo,
+        /// Player slot the device was assigned to (None if all slots are taken)
+        player_index: Option<usize>,
+    },
+    Disconnected {
+        device: ControllerDeviceInfo,
+        player_index: Option<usize>,
+    },
+}
+
+impl ControllerEvent {
+    /// Event name used when forwarding to the frontend
+    pub fn event_name(&self) -> &'static str {
+        match self {
+            ControllerEvent::Connected { .. } => "controller-connected",
+            ControllerEvent::Disconnected { .. } => "controller-disconnected",
+        }
+    }
+}
+
+/// Fan-out of controller events to any number of subscribers
+#[derive(Debug, Default)]
+pub struct ControllerEventBus {
+    subscribers: Mutex<Vec<Sender<ControllerEvent>>>,
+}
+
+impl ControllerEventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register a new subscriber
+    pub fn subscribe(&self) -> Receiver<ControllerEvent> {
+        let (tx, rx) = channel();
+        self.subscribers.lock().unwrap().push(tx);
+        rx
+    }
+
+    /// Send an event to all subscribers, dropping the ones that hung up
+    pub fn publish(&self, event: ControllerEvent) {
+        log::debug!("🎮 Controller event: {:?}", event);
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|tx| tx.send(event.clone()).is_ok());
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.subscribers.lock().unwrap().len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn device() -> ControllerDeviceInfo {
+        ControllerDeviceInfo {
+            device_id: "0".to_string(),
+            name: "Harmonix Guitar".to_string(),
+            vendor_id: Some(0x1bad),
+            product_id: Some(0x3010),
+            backend: "gilrs".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_publish_to_subscribers() {
+        let bus = ControllerEventBus::new();
+        let rx = bus.subscribe();
+        bus.publish(ControllerEvent::Connected { device: device(), player_index: Some(0) });
+
+        let event = rx.try_recv().unwrap();
+        assert_eq!(event.event_name(), "controller-connected");
+    }
+
+    #[test]
+    fn test_dropped_subscribers_are_removed() {
+        let bus = ControllerEventBus::new();
+        let rx = bus.subscribe();
+        drop(rx);
+        bus.publish(ControllerEvent::Disconnected { device: device(), player_index: None });
+        assert_eq!(bus.subscriber_count(), 0);
+    }
+
+    #[test]
+    fn test_event_serialization() {
+        let event = ControllerEvent::Connected { device: device(), player_index: Some(1) };
+        let json = serde_json::to_value(&event).unwrap();
+        assert_eq!(json["kind"], "connected");
+        assert_eq!(json["player_index"], 1);
+        assert_eq!(json["device"]["name"], "Harmonix Guitar");
+    }
+}
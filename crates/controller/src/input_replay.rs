@@ -0,0 +1,279 @@
+//! Replay of captured raw input without hardware
+//! Feeds a raw diagnostics capture through the same translation the polling
+//! loop applies (default button layout, profile axis bindings, hat strum and
+//! whammy calibration) and reports the resulting actions and controller state
+//! per event. Debounce is not applied, so bounces in the capture show up as-is.
+
+use std::collections::{HashMap, HashSet};
+use std::time::{Duration, Instant};
+use serde::{Deserialize, Serialize};
+
+use crate::hat_strum::HatStrumDecoder;
+use crate::mapping_profile::{AppAction, AxisBinding, AxisButtonState, MappingProfile, ResponseCurve};
+use crate::raw_diagnostics::RawInputEvent;
+use crate::whammy_calibration::WhammyCalibration;
+use crate::{ControlId, ControllerState};
+
+/// Button actions tracked during replay, in report order
+const REPLAYED_ACTIONS: [AppAction; 13] = [
+    AppAction::FretGreen,
+    AppAction::FretRed,
+    AppAction::FretYellow,
+    AppAction::FretBlue,
+    AppAction::FretOrange,
+    AppAction::StrumUp,
+    AppAction::StrumDown,
+    AppAction::DPadUp,
+    AppAction::DPadDown,
+    AppAction::DPadLeft,
+    AppAction::DPadRight,
+    AppAction::Start,
+    AppAction::Select,
+];
+
+/// An action that changed state on a replayed event
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReplayedAction {
+    pub action: AppAction,
+    pub pressed: bool,
+}
+
+/// Result of replaying one raw event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayStep {
+    pub timestamp_ms: u64,
+    pub raw_code: String,
+    /// Actions pressed or released by this event
+    pub actions: Vec<ReplayedAction>,
+    /// Whammy value after this event
+    pub whammy: f32,
+    /// Controller state after this event, as the mapper sees it
+    pub state: ControllerState,
+}
+
+/// Controller state button driven by an action
+pub(crate) fn control_for(action: AppAction) -> Option<ControlId> {
+    match action {
+        AppAction::FretGreen => Some(ControlId::FretGreen),
+        AppAction::FretRed => Some(ControlId::FretRed),
+        AppAction::FretYellow => Some(ControlId::FretYellow),
+        AppAction::FretBlue => Some(ControlId::FretBlue),
+        AppAction::FretOrange => Some(ControlId::FretOrange),
+        AppAction::StrumUp => Some(ControlId::StrumUp),
+        AppAction::StrumDown => Some(ControlId::StrumDown),
+        AppAction::DPadUp => Some(ControlId::DPadUp),
+        AppAction::DPadDown => Some(ControlId::DPadDown),
+        AppAction::DPadLeft => Some(ControlId::DPadLeft),
+        AppAction::DPadRight => Some(ControlId::DPadRight),
+        AppAction::Start => Some(ControlId::Start),
+        AppAction::Select => Some(ControlId::Select),
+        AppAction::SustainPedal => Some(ControlId::SustainPedal),
+        _ => None,
+    }
+}
+
+/// Replays raw events through the controller translation layer
+pub struct InputReplayer {
+    held_buttons: HashSet<String>,
+    axes: HashMap<String, f32>,
+    axis_buttons: Vec<(AppAction, AxisBinding, AxisButtonState)>,
+    whammy_calibration: Option<WhammyCalibration>,
+    whammy_curve: ResponseCurve,
+    hat_strum: HatStrumDecoder,
+    pressed: HashSet<AppAction>,
+    started: Instant,
+}
+
+impl InputReplayer {
+    /// Create a replayer using a mapping profile's axis bindings and whammy calibration
+    pub fn new(profile: Option<&MappingProfile>) -> Self {
+        Self {
+            held_buttons: HashSet::new(),
+            axes: HashMap::new(),
+            axis_buttons: profile
+                .map(|profile| profile.axis_button_bindings())
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(action, binding)| (action, binding, AxisButtonState::default()))
+                .collect(),
+            whammy_calibration: profile.and_then(|profile| profile.whammy_calibration),
+            whammy_curve: profile
+                .map(|profile| profile.axis_curve(AppAction::WhammyAxis))
+                .unwrap_or_default(),
+            hat_strum: HatStrumDecoder::new(),
+            pressed: HashSet::new(),
+            started: Instant::now(),
+        }
+    }
+
+    fn button(&self, name: &str) -> bool {
+        self.held_buttons.contains(name)
+    }
+
+    fn axis(&self, name: &str) -> f32 {
+        self.axes.get(name).copied().unwrap_or(0.0)
+    }
+
+    /// Apply one raw event and return what changed
+    pub fn step(&mut self, event: &RawInputEvent) -> ReplayStep {
+        match (event.event_type.as_str(), &event.button, &event.axis) {
+            ("ButtonPressed", Some(button), _) => {
+                self.held_buttons.insert(button.clone());
+            }
+            ("ButtonReleased", Some(button), _) => {
+                self.held_buttons.remove(button);
+            }
+            ("AxisChanged", _, Some(axis)) => {
+                self.axes.insert(axis.clone(), event.value.unwrap_or(0.0));
+            }
+            ("Disconnected", _, _) => {
+                self.held_buttons.clear();
+                self.axes.clear();
+            }
+            _ => {}
+        }
+
+        // Same layout as the polling loop
+        let has_strum_bar = self.button("RightTrigger") || self.button("RightTrigger2");
+        let mut pressed: HashMap<AppAction, bool> = HashMap::from([
+            (AppAction::FretGreen, self.button("South")),
+            (AppAction::FretRed, self.button("East")),
+            (AppAction::FretYellow, self.button("North")),
+            (AppAction::FretBlue, self.button("West")),
+            (AppAction::FretOrange, self.button("LeftTrigger") || self.button("LeftTrigger2")),
+            (AppAction::DPadLeft, self.button("DPadLeft")),
+            (AppAction::DPadRight, self.button("DPadRight")),
+            (AppAction::Start, self.button("Start")),
+            (AppAction::Select, self.button("Select")),
+        ]);
+        let mut velocity = None;
+        if has_strum_bar {
+            pressed.insert(AppAction::StrumUp, self.button("RightTrigger"));
+            pressed.insert(AppAction::StrumDown, self.button("RightTrigger2"));
+            pressed.insert(AppAction::DPadUp, self.button("DPadUp"));
+            pressed.insert(AppAction::DPadDown, self.button("DPadDown"));
+        } else {
+            let now = self.started + Duration::from_millis(event.timestamp_ms);
+            let hat = self.axis("DPadY");
+            let (hat_up, hat_down) = self.hat_strum.update(hat, now);
+            if hat_up || hat_down {
+                velocity = Some(self.hat_strum.velocity());
+            }
+            pressed.insert(AppAction::StrumUp, self.button("DPadUp") || hat_up);
+            pressed.insert(AppAction::StrumDown, self.button("DPadDown") || hat_down);
+        }
+
+        for (action, binding, axis_state) in self.axis_buttons.iter_mut() {
+            let raw = self.axes.get(&binding.logical_axis).copied().unwrap_or(0.0);
+            if axis_state.update(binding, raw) {
+                pressed.insert(*action, true);
+            }
+        }
+
+        let raw_whammy = self.axis("RightStickX");
+        let whammy = self.whammy_curve.apply(match &self.whammy_calibration {
+            Some(calibration) => calibration.normalize(raw_whammy),
+            None => raw_whammy,
+        });
+
+        let mut actions = Vec::new();
+        let mut state = ControllerState::default();
+        for action in REPLAYED_ACTIONS {
+            let is_pressed = pressed.get(&action).copied().unwrap_or(false);
+            if is_pressed != self.pressed.contains(&action) {
+                actions.push(ReplayedAction { action, pressed: is_pressed });
+                if is_pressed {
+                    self.pressed.insert(action);
+                } else {
+                    self.pressed.remove(&action);
+                }
+            }
+            if let Some(control) = control_for(action) {
+                state.set_button(control, is_pressed);
+            }
+        }
+        state.set_axis(ControlId::WhammyBar, whammy);
+        if let Some(velocity) = velocity {
+            state.set_axis(ControlId::StrumVelocity, velocity as f32 / 127.0);
+        }
+        state.timestamp = event.timestamp_ms as f64 / 1000.0;
+
+        ReplayStep {
+            timestamp_ms: event.timestamp_ms,
+            raw_code: event.raw_code.clone(),
+            actions,
+            whammy,
+            state,
+        }
+    }
+
+    /// Replay a whole capture in timestamp order
+    pub fn replay(events: &[RawInputEvent], profile: Option<&MappingProfile>) -> Vec<ReplayStep> {
+        let mut ordered: Vec<&RawInputEvent> = events.iter().collect();
+        ordered.sort_by_key(|event| event.timestamp_ms);
+        let mut replayer = Self::new(profile);
+        ordered.into_iter().map(|event| replayer.step(event)).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(timestamp_ms: u64, event_type: &str, button: Option<&str>, axis: Option<&str>, value: Option<f32>) -> RawInputEvent {
+        RawInputEvent {
+            timestamp_ms,
+            unix_timestamp_ms: timestamp_ms,
+            gamepad_id: 0,
+            gamepad_name: "Test Guitar".to_string(),
+            event_type: event_type.to_string(),
+            button: button.map(str::to_string),
+            axis: axis.map(str::to_string),
+            value,
+            raw_code: "test".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_replay_default_layout() {
+        // Captures are exported newest first
+        let events = vec![
+            event(30, "ButtonReleased", Some("DPadDown"), None, None),
+            event(20, "ButtonPressed", Some("DPadDown"), None, None),
+            event(10, "ButtonPressed", Some("South"), None, None),
+        ];
+        let steps = InputReplayer::replay(&events, None);
+        assert_eq!(steps[0].actions, vec![ReplayedAction { action: AppAction::FretGreen, pressed: true }]);
+        assert_eq!(steps[1].actions, vec![ReplayedAction { action: AppAction::StrumDown, pressed: true }]);
+        assert!(steps[1].state.is_strumming());
+        assert_eq!(steps[1].state.pressed_frets(), vec![ControlId::FretGreen]);
+        assert_eq!(steps[2].actions, vec![ReplayedAction { action: AppAction::StrumDown, pressed: false }]);
+    }
+
+    #[test]
+    fn test_replay_profile_axis_binding() {
+        let mut profile = MappingProfile::new("Test".to_string(), crate::ControllerId {
+            name: "Test Guitar".to_string(),
+            label: None,
+            vendor_id: None,
+            product_id: None,
+        });
+        profile.add_mapping(AppAction::StrumUp, crate::RawBinding::Axis(AxisBinding {
+            code: None,
+            logical_axis: "RightStickY".to_string(),
+            min: -1.0,
+            max: 1.0,
+            deadzone: 0.05,
+            invert: false,
+            threshold: 0.5,
+            curve: crate::ResponseCurve::Linear,
+        }));
+        let events = vec![
+            event(0, "AxisChanged", None, Some("RightStickY"), Some(0.8)),
+            event(5, "AxisChanged", None, Some("RightStickY"), Some(0.0)),
+        ];
+        let steps = InputReplayer::replay(&events, Some(&profile));
+        assert_eq!(steps[0].actions, vec![ReplayedAction { action: AppAction::StrumUp, pressed: true }]);
+        assert_eq!(steps[1].actions, vec![ReplayedAction { action: AppAction::StrumUp, pressed: false }]);
+    }
+}
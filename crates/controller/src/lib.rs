@@ -11,15 +11,75 @@ pub mod simulator;
 
 // New high-performance controller module
 pub mod high_performance;
-pub use high_performance::{PerformanceController, ControllerStateSnapshot, AtomicControllerState, AudioCallback};
+pub use high_performance::{PerformanceController, ControllerStateSnapshot, AtomicControllerState, AudioCallback, ControllerBackend, MAX_PLAYERS};
+
+// Hot-plug events
+pub mod hotplug;
+pub use hotplug::{ControllerEvent, ControllerEventBus, ControllerDeviceInfo};
+
+// XInput backend (Xbox 360 wireless guitars)
+pub mod xinput;
 
 // Raw diagnostics module
 pub mod raw_diagnostics;
-pub use raw_diagnostics::{RawDiagnostics, RawInputEvent};
+pub use raw_diagnostics::{RawDiagnostics, RawInputEvent, FilteredEventStats};
+
+// Replay of raw captures through the translation layer
+pub mod input_replay;
+pub use input_replay::{InputReplayer, ReplayStep, ReplayedAction};
+
+// Macros (button combos replaying recorded steps)
+pub mod macros;
+pub use macros::{ControllerMacro, MacroAction, MacroPlayer, MacroRecorder, MacroStep};
+
+// Strum/fret debounce
+pub mod debounce;
+
+// USB foot switch (sustain pedal)
+pub mod footswitch;
+pub use footswitch::{Footswitch, FootswitchInfo, list_footswitches};
+
+// Fret LEDs of Rock Band 3 guitars
+pub mod fret_leds;
+pub use fret_leds::{FretLedInfo, FretLedOutput, FretLeds, list_led_guitars};
+
+// Force feedback cues from song mode
+pub mod rumble;
+pub use rumble::{Rumble, RumbleCue};
+
+// Hat-switch strum decoding
+pub mod hat_strum;
+
+// Scripted demo performances
+pub mod demo;
+pub use demo::{DemoScript, DemoStep};
+
+// Guitar-driven menu navigation
+pub mod menu_navigation;
+pub use menu_navigation::{MenuNavigator, NavAction};
 
 // Mapping profile and wizard modules
 pub mod mapping_profile;
-pub use mapping_profile::{AppAction, RawBinding, ButtonBinding, AxisBinding, MappingProfile, MappingProfileManager, ControllerId};
+pub use mapping_profile::{AppAction, RawBinding, ButtonBinding, AxisBinding, MappingProfile, MappingProfileManager, ControllerId, ResponseCurve};
+
+// Backups of profile files before they are overwritten
+pub mod profile_backup;
+pub use profile_backup::BackupVersion;
+
+// Guided hardware test
+pub mod hardware_test;
+pub use hardware_test::{HardwareTester, HardwareTestReport, ControlTestResult, TestControl, TestOutcome};
+
+pub mod whammy_calibration;
+pub use whammy_calibration::{WhammyCalibration, WhammyCalibrator, CalibrationStatus};
+
+// Whammy deadzone and smoothing in the polling loop
+pub mod whammy_filter;
+pub use whammy_filter::{WhammyFilter, DEFAULT_WHAMMY_DEADZONE};
+
+// Tilt threshold with hysteresis
+pub mod tilt_trigger;
+pub use tilt_trigger::TiltTrigger;
 
 pub mod mapping_wizard;
 pub use mapping_wizard::{MappingWizard, CaptureState, CaptureResult, CapturedEventSummary};
@@ -59,6 +119,10 @@ pub enum ControlId {
     DPadRight,
     WhammyBar,
     TiltSensor,
+    /// Strum velocity (0.0-1.0), only present when the guitar reports it
+    StrumVelocity,
+    /// Sustain pedal (foot switch or a button bound to the SustainPedal action)
+    SustainPedal,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,6 +156,7 @@ impl Default for ControllerState {
         buttons.insert(ControlId::DPadDown, false);
         buttons.insert(ControlId::DPadLeft, false);
         buttons.insert(ControlId::DPadRight, false);
+        buttons.insert(ControlId::SustainPedal, false);
 
         let mut axes = HashMap::new();
         axes.insert(ControlId::WhammyBar, 0.0);
@@ -114,6 +179,19 @@ impl ControllerState {
         self.axes.insert(control, value);
     }
 
+    /// Button held (false if not found)
+    pub fn button(&self, control: ControlId) -> bool {
+        self.buttons.get(&control).copied().unwrap_or(false)
+    }
+
+    /// MIDI velocity of the current strum (falls back to `default`)
+    pub fn strum_velocity(&self, default: u8) -> u8 {
+        match self.axes.get(&ControlId::StrumVelocity) {
+            Some(&value) if value > 0.0 => (value * 127.0).round().clamp(1.0, 127.0) as u8,
+            _ => default,
+        }
+    }
+
     /// Get list of currently pressed fret buttons
     pub fn pressed_frets(&self) -> Vec<ControlId> {
         let fret_buttons = [
@@ -0,0 +1,263 @@
+//! Controller macros
+//! A macro is a timed sequence of steps (notes or app actions) fired by a
+//! button combo, e.g. a signature riff or "panic + switch to clean guitar".
+//! Each step carries its offset from the trigger, so a recorded riff replays
+//! with its original timing as long as the player is updated often (the app
+//! runs it every millisecond). Macros live in the mapping profile next to the
+//! bindings they are triggered with.
+
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+use crate::input_replay::control_for;
+use crate::mapping_profile::AppAction;
+use crate::{ControlId, ControllerState};
+
+/// Most macros that can play at the same time
+const MAX_RUNNING_MACROS: usize = 8;
+
+/// Something a macro does
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum MacroAction {
+    NoteOn { note: u8, velocity: u8 },
+    NoteOff { note: u8 },
+    /// Pitch bend (-8192 to 8191)
+    PitchBend { amount: i16 },
+    /// Stop all notes
+    Panic,
+    /// Switch instrument (by name, as listed in the instrument picker)
+    SetInstrument { name: String },
+    /// Switch genre
+    SetGenre { genre: String },
+}
+
+/// A macro action at an offset from the trigger
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MacroStep {
+    pub at_ms: u32,
+    pub action: MacroAction,
+}
+
+/// Named step sequence fired by a button combo
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ControllerMacro {
+    pub name: String,
+    /// Buttons held together to fire the macro (e.g. Select + Start)
+    pub trigger: Vec<AppAction>,
+    pub steps: Vec<MacroStep>,
+}
+
+impl ControllerMacro {
+    /// Whether every trigger button is held (empty triggers never fire)
+    fn is_triggered(&self, state: &ControllerState) -> bool {
+        let controls: Vec<ControlId> = self.trigger.iter().filter_map(|action| control_for(*action)).collect();
+        !controls.is_empty()
+            && controls.len() == self.trigger.len()
+            && controls.iter().all(|control| state.buttons.get(control).copied().unwrap_or(false))
+    }
+
+    /// Length of the macro in milliseconds
+    pub fn duration_ms(&self) -> u32 {
+        self.steps.iter().map(|step| step.at_ms).max().unwrap_or(0)
+    }
+}
+
+/// A macro being played
+#[derive(Debug, Clone)]
+struct RunningMacro {
+    index: usize,
+    started: Instant,
+    next_step: usize,
+    /// Notes turned on and not yet off (released if the macro is cut short)
+    notes_on: Vec<u8>,
+}
+
+/// Fires macros on their trigger combos and releases their steps on time
+#[derive(Debug, Default)]
+pub struct MacroPlayer {
+    macros: Vec<ControllerMacro>,
+    /// Trigger combo held at the last update, per macro
+    trigger_held: Vec<bool>,
+    running: Vec<RunningMacro>,
+}
+
+impl MacroPlayer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the macro set (stops running macros; returns their note-offs)
+    pub fn set_macros(&mut self, mut macros: Vec<ControllerMacro>) -> Vec<MacroAction> {
+        let released = self.stop_all();
+        for controller_macro in &mut macros {
+            controller_macro.steps.sort_by_key(|step| step.at_ms);
+        }
+        self.trigger_held = vec![false; macros.len()];
+        self.macros = macros;
+        released
+    }
+
+    pub fn macros(&self) -> &[ControllerMacro] {
+        &self.macros
+    }
+
+    /// Nothing to do until a macro is set up
+    pub fn is_idle(&self) -> bool {
+        self.macros.is_empty() && self.running.is_empty()
+    }
+
+    /// Start a macro by name (restarts it if already playing).
+    /// Returns the note-offs of the cut-off run, or None if there is no such macro.
+    pub fn trigger(&mut self, name: &str, now: Instant) -> Option<Vec<MacroAction>> {
+        let index = self.macros.iter().position(|controller_macro| controller_macro.name == name)?;
+        Some(self.start(index, now))
+    }
+
+    fn start(&mut self, index: usize, now: Instant) -> Vec<MacroAction> {
+        let mut released = Vec::new();
+        if let Some(position) = self.running.iter().position(|running| running.index == index) {
+            released = Self::release(self.running.remove(position));
+        }
+        if self.running.len() >= MAX_RUNNING_MACROS {
+            released.extend(Self::release(self.running.remove(0)));
+        }
+        self.running.push(RunningMacro { index, started: now, next_step: 0, notes_on: Vec::new() });
+        released
+    }
+
+    /// Check triggers and return the steps due by `now`, in order
+    pub fn update(&mut self, state: &ControllerState, now: Instant) -> Vec<MacroAction> {
+        let mut actions = Vec::new();
+        for index in 0..self.macros.len() {
+            let held = self.macros[index].is_triggered(state);
+            if held && !self.trigger_held[index] {
+                actions.extend(self.start(index, now));
+            }
+            self.trigger_held[index] = held;
+        }
+
+        let macros = &self.macros;
+        for running in &mut self.running {
+            let elapsed_ms = now.saturating_duration_since(running.started).as_millis();
+            let steps = &macros[running.index].steps;
+            while let Some(step) = steps.get(running.next_step).filter(|step| u128::from(step.at_ms) <= elapsed_ms) {
+                match step.action {
+                    MacroAction::NoteOn { note, .. } => running.notes_on.push(note),
+                    MacroAction::NoteOff { note } => running.notes_on.retain(|&on| on != note),
+                    MacroAction::Panic => running.notes_on.clear(),
+                    _ => {}
+                }
+                actions.push(step.action.clone());
+                running.next_step += 1;
+            }
+        }
+        self.running.retain(|running| running.next_step < macros[running.index].steps.len());
+        actions
+    }
+
+    /// Stop every running macro, returning note-offs for notes it left on
+    pub fn stop_all(&mut self) -> Vec<MacroAction> {
+        self.running.drain(..).flat_map(Self::release).collect()
+    }
+
+    fn release(running: RunningMacro) -> Vec<MacroAction> {
+        running.notes_on.into_iter().map(|note| MacroAction::NoteOff { note }).collect()
+    }
+}
+
+/// Records actions with their time since recording started
+#[derive(Debug, Clone)]
+pub struct MacroRecorder {
+    started: Instant,
+    steps: Vec<MacroStep>,
+}
+
+impl MacroRecorder {
+    pub fn new(now: Instant) -> Self {
+        Self { started: now, steps: Vec::new() }
+    }
+
+    /// Record an action happening at `now` plus `delay_ms`
+    pub fn record(&mut self, action: MacroAction, now: Instant, delay_ms: f32) {
+        let at_ms = now.saturating_duration_since(self.started).as_millis() as f32 + delay_ms.max(0.0);
+        self.steps.push(MacroStep { at_ms: at_ms.round() as u32, action });
+    }
+
+    pub fn step_count(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Finish recording. Silence before the first step is dropped and notes
+    /// still held are released at the end.
+    pub fn finish(mut self, name: String, trigger: Vec<AppAction>) -> ControllerMacro {
+        self.steps.sort_by_key(|step| step.at_ms);
+        let offset = self.steps.first().map(|step| step.at_ms).unwrap_or(0);
+        let mut notes_on = Vec::new();
+        for step in &mut self.steps {
+            step.at_ms -= offset;
+            match step.action {
+                MacroAction::NoteOn { note, .. } => notes_on.push(note),
+                MacroAction::NoteOff { note } => notes_on.retain(|&on| on != note),
+                _ => {}
+            }
+        }
+        let end = self.steps.last().map(|step| step.at_ms).unwrap_or(0);
+        self.steps.extend(notes_on.into_iter().map(|note| MacroStep { at_ms: end, action: MacroAction::NoteOff { note } }));
+        ControllerMacro { name, trigger, steps: self.steps }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn riff() -> ControllerMacro {
+        ControllerMacro {
+            name: "Riff".to_string(),
+            trigger: vec![AppAction::Select, AppAction::Start],
+            steps: vec![
+                MacroStep { at_ms: 100, action: MacroAction::NoteOff { note: 40 } },
+                MacroStep { at_ms: 0, action: MacroAction::NoteOn { note: 40, velocity: 100 } },
+                MacroStep { at_ms: 100, action: MacroAction::NoteOn { note: 43, velocity: 100 } },
+                MacroStep { at_ms: 300, action: MacroAction::NoteOff { note: 43 } },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_combo_triggers_and_steps_follow_timing() {
+        let start = Instant::now();
+        let mut player = MacroPlayer::new();
+        player.set_macros(vec![riff()]);
+        let mut state = ControllerState::default();
+        state.buttons.insert(ControlId::Select, true);
+        assert!(player.update(&state, start).is_empty());
+
+        state.buttons.insert(ControlId::Start, true);
+        assert_eq!(player.update(&state, start), vec![MacroAction::NoteOn { note: 40, velocity: 100 }]);
+        // Holding the combo doesn't retrigger
+        let due = player.update(&state, start + Duration::from_millis(150));
+        assert_eq!(due, vec![MacroAction::NoteOff { note: 40 }, MacroAction::NoteOn { note: 43, velocity: 100 }]);
+
+        // Cutting the macro short releases the note it left on
+        assert_eq!(player.stop_all(), vec![MacroAction::NoteOff { note: 43 }]);
+        assert!(player.update(&state, start + Duration::from_millis(400)).is_empty());
+    }
+
+    #[test]
+    fn test_recorder_trims_lead_in_and_closes_notes() {
+        let start = Instant::now();
+        let mut recorder = MacroRecorder::new(start);
+        recorder.record(MacroAction::NoteOn { note: 40, velocity: 90 }, start + Duration::from_millis(500), 0.0);
+        recorder.record(MacroAction::NoteOn { note: 47, velocity: 90 }, start + Duration::from_millis(500), 8.0);
+        recorder.record(MacroAction::NoteOff { note: 40 }, start + Duration::from_millis(700), 0.0);
+
+        let recorded = recorder.finish("Lick".to_string(), vec![AppAction::Select]);
+        let offsets: Vec<u32> = recorded.steps.iter().map(|step| step.at_ms).collect();
+        assert_eq!(offsets, vec![0, 8, 200, 200]);
+        assert_eq!(recorded.steps[3].action, MacroAction::NoteOff { note: 47 });
+        assert_eq!(recorded.duration_ms(), 200);
+    }
+}
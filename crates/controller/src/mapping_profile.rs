@@ -3,10 +3,19 @@ use std::collections::HashMap;
 use std::fs;
 use std::path::{Path, PathBuf};
 use anyhow::{Result, Context};
+use crate::profile_backup::{self, BackupVersion, DEFAULT_BACKUP_VERSIONS};
+use crate::whammy_calibration::WhammyCalibration;
+use crate::macros::ControllerMacro;
 
 /// Version for mapping profile schema
 const MAPPING_PROFILE_VERSION: u32 = 1;
 
+/// Default press threshold for axis-driven button actions
+const DEFAULT_AXIS_THRESHOLD: f32 = 0.5;
+
+/// Hysteresis below the threshold before an axis-driven button releases
+const AXIS_RELEASE_HYSTERESIS: f32 = 0.1;
+
 /// App-level action that can be triggered by controller input
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AppAction {
@@ -39,6 +48,11 @@ pub enum AppAction {
     Select,
     System,
     
+    // Tools
+    TapTempo,
+    SustainPedal,
+    NextConfigProfile,
+    
     // Analog axes
     WhammyAxis,
     TiltAxis,
@@ -68,6 +82,9 @@ impl AppAction {
             Self::Start => "Start",
             Self::Select => "Select",
             Self::System => "System",
+            Self::TapTempo => "Tap Tempo",
+            Self::SustainPedal => "Sustain Pedal",
+            Self::NextConfigProfile => "Next Config Profile",
             Self::WhammyAxis => "Whammy Bar",
             Self::TiltAxis => "Tilt Sensor",
             Self::GenericAxis1 => "Generic Axis 1",
@@ -82,10 +99,16 @@ impl AppAction {
             Self::StrumUp | Self::StrumDown => "Strum",
             Self::DPadUp | Self::DPadDown | Self::DPadLeft | Self::DPadRight => "D-Pad",
             Self::Start | Self::Select | Self::System => "Menu",
+            Self::TapTempo | Self::SustainPedal | Self::NextConfigProfile => "Tools",
             Self::WhammyAxis | Self::TiltAxis | Self::GenericAxis1 | Self::GenericAxis2 => "Analog",
         }
     }
 
+    /// Whether the action takes a continuous value rather than pressed/released
+    pub fn is_analog(&self) -> bool {
+        matches!(self, Self::WhammyAxis | Self::TiltAxis | Self::GenericAxis1 | Self::GenericAxis2)
+    }
+
     pub fn all_actions() -> Vec<Self> {
         vec![
             // Main frets
@@ -98,6 +121,8 @@ impl AppAction {
             Self::DPadUp, Self::DPadDown, Self::DPadLeft, Self::DPadRight,
             // Menu
             Self::Start, Self::Select, Self::System,
+            // Tools
+            Self::TapTempo, Self::SustainPedal, Self::NextConfigProfile,
             // Analog
             Self::WhammyAxis, Self::TiltAxis, Self::GenericAxis1, Self::GenericAxis2,
         ]
@@ -128,6 +153,113 @@ pub struct AxisBinding {
     pub deadzone: f32,
     /// Invert axis direction
     pub invert: bool,
+    /// Press threshold when the axis drives a button action (0.0-1.0)
+    #[serde(default = "default_axis_threshold")]
+    pub threshold: f32,
+    /// Response curve from axis travel to app value
+    #[serde(default)]
+    pub curve: ResponseCurve,
+}
+
+fn default_axis_threshold() -> f32 {
+    DEFAULT_AXIS_THRESHOLD
+}
+
+impl AxisBinding {
+    /// Apply invert, deadzone and the response curve to a raw axis value (-1.0 to 1.0)
+    pub fn normalize(&self, raw: f32) -> f32 {
+        let value = if self.invert { -raw } else { raw };
+        if value.abs() < self.deadzone {
+            0.0
+        } else {
+            self.curve.apply(value.clamp(-1.0, 1.0))
+        }
+    }
+}
+
+/// Response curve shaping an axis. Applied to the distance from center, so
+/// both directions of a centered axis bend the same way.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ResponseCurve {
+    /// Value as read
+    #[default]
+    Linear,
+    /// `travel ^ exponent`: below 1.0 the start of the travel is more
+    /// sensitive, above 1.0 less
+    Exponential { exponent: f32 },
+    /// Breakpoints as (travel, value) pairs in 0.0-1.0, joined by straight lines
+    Table { points: Vec<(f32, f32)> },
+}
+
+impl ResponseCurve {
+    /// Shape an axis value (-1.0 to 1.0)
+    pub fn apply(&self, value: f32) -> f32 {
+        let travel = value.abs().min(1.0);
+        let shaped = match self {
+            Self::Linear => travel,
+            Self::Exponential { exponent } if *exponent > 0.0 => travel.powf(*exponent),
+            Self::Exponential { .. } => travel,
+            Self::Table { points } => interpolate_table(points, travel),
+        };
+        shaped.clamp(0.0, 1.0).copysign(value)
+    }
+}
+
+/// Piecewise-linear lookup; travel outside the table holds the end values
+/// and an empty table is linear
+fn interpolate_table(points: &[(f32, f32)], travel: f32) -> f32 {
+    let mut sorted: Vec<(f32, f32)> = points.to_vec();
+    sorted.sort_by(|a, b| a.0.total_cmp(&b.0));
+    let (Some(first), Some(last)) = (sorted.first(), sorted.last()) else {
+        return travel;
+    };
+    if travel <= first.0 {
+        return first.1;
+    }
+    if travel >= last.0 {
+        return last.1;
+    }
+    sorted.windows(2)
+        .find(|pair| travel <= pair[1].0)
+        .map(|pair| {
+            let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+            if x1 - x0 <= f32::EPSILON {
+                y1
+            } else {
+                y0 + (y1 - y0) * (travel - x0) / (x1 - x0)
+            }
+        })
+        .unwrap_or(last.1)
+}
+
+/// Pressed state of an axis driving a button action.
+/// Presses when the normalized value crosses `threshold` and releases once it
+/// drops below `threshold - AXIS_RELEASE_HYSTERESIS`, so a noisy strum axis
+/// resting near the threshold doesn't chatter.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AxisButtonState {
+    pressed: bool,
+}
+
+impl AxisButtonState {
+    /// Feed a raw axis value, returning the new pressed state
+    pub fn update(&mut self, binding: &AxisBinding, raw: f32) -> bool {
+        let value = binding.normalize(raw);
+        let threshold = binding.threshold.clamp(0.0, 1.0).max(binding.deadzone);
+        if self.pressed {
+            if value < threshold - AXIS_RELEASE_HYSTERESIS {
+                self.pressed = false;
+            }
+        } else if value >= threshold {
+            self.pressed = true;
+        }
+        self.pressed
+    }
+
+    pub fn is_pressed(&self) -> bool {
+        self.pressed
+    }
 }
 
 /// Raw event binding (button or axis)
@@ -164,6 +296,15 @@ pub struct MappingProfile {
     pub controller: ControllerId,
     /// Mappings from AppAction to RawBinding
     pub mappings: HashMap<AppAction, RawBinding>,
+    /// Debounce window per action in milliseconds (missing = no debounce)
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+    pub debounce_ms: HashMap<AppAction, u32>,
+    /// Calibrated whammy range (None = use raw values)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub whammy_calibration: Option<WhammyCalibration>,
+    /// Macros fired by button combos
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub macros: Vec<ControllerMacro>,
     /// Creation timestamp
     pub created_at: u64,
     /// Last modified timestamp
@@ -182,6 +323,9 @@ impl MappingProfile {
             name,
             controller,
             mappings: HashMap::new(),
+            debounce_ms: HashMap::new(),
+            whammy_calibration: None,
+            macros: Vec::new(),
             created_at: now,
             modified_at: now,
         }
@@ -208,6 +352,89 @@ impl MappingProfile {
             .as_secs();
     }
 
+    /// Debounce window for an action (0 = disabled)
+    pub fn debounce_window_ms(&self, action: AppAction) -> u32 {
+        self.debounce_ms.get(&action).copied().unwrap_or(0)
+    }
+
+    /// Set the debounce window for an action (0 removes it)
+    pub fn set_debounce_ms(&mut self, action: AppAction, window_ms: u32) {
+        if window_ms == 0 {
+            self.debounce_ms.remove(&action);
+        } else {
+            self.debounce_ms.insert(action, window_ms);
+        }
+        self.update_modified_time();
+    }
+
+    /// Store a whammy calibration
+    pub fn set_whammy_calibration(&mut self, calibration: Option<WhammyCalibration>) {
+        self.whammy_calibration = calibration;
+        self.update_modified_time();
+    }
+
+    /// Add a macro (replaces the macro with the same name)
+    pub fn set_macro(&mut self, controller_macro: ControllerMacro) {
+        self.macros.retain(|existing| existing.name != controller_macro.name);
+        self.macros.push(controller_macro);
+        self.update_modified_time();
+    }
+
+    /// Remove a macro by name (returns whether it existed)
+    pub fn remove_macro(&mut self, name: &str) -> bool {
+        let count = self.macros.len();
+        self.macros.retain(|existing| existing.name != name);
+        self.update_modified_time();
+        self.macros.len() != count
+    }
+
+    /// Response curve of the axis bound to an analog action (linear if unbound)
+    pub fn axis_curve(&self, action: AppAction) -> ResponseCurve {
+        match self.mappings.get(&action) {
+            Some(RawBinding::Axis(axis)) => axis.curve.clone(),
+            _ => ResponseCurve::Linear,
+        }
+    }
+
+    /// Set the response curve of the axis bound to an action
+    /// (false if the action isn't bound to an axis)
+    pub fn set_axis_curve(&mut self, action: AppAction, curve: ResponseCurve) -> bool {
+        let Some(RawBinding::Axis(axis)) = self.mappings.get_mut(&action) else {
+            return false;
+        };
+        axis.curve = curve;
+        self.update_modified_time();
+        true
+    }
+
+    /// Axis bindings that drive button actions (frets, strum, d-pad, menu)
+    pub fn axis_button_bindings(&self) -> Vec<(AppAction, AxisBinding)> {
+        let mut bindings: Vec<(AppAction, AxisBinding)> = self.mappings.iter()
+            .filter(|(action, _)| !action.is_analog())
+            .filter_map(|(action, binding)| match binding {
+                RawBinding::Axis(axis) => Some((*action, axis.clone())),
+                RawBinding::Button(_) => None,
+            })
+            .collect();
+        bindings.sort_by_key(|(action, _)| format!("{:?}", action));
+        bindings
+    }
+
+    /// Button bindings (by logical button name) for app-level actions such as
+    /// tap tempo, which the polling loop reads from the profile rather than the
+    /// default layout
+    pub fn tool_button_bindings(&self) -> Vec<(AppAction, String)> {
+        let mut bindings: Vec<(AppAction, String)> = self.mappings.iter()
+            .filter(|(action, _)| action.category() == "Tools")
+            .filter_map(|(action, binding)| match binding {
+                RawBinding::Button(button) => button.logical_button.clone().map(|name| (*action, name)),
+                RawBinding::Axis(_) => None,
+            })
+            .collect();
+        bindings.sort_by_key(|(action, _)| format!("{:?}", action));
+        bindings
+    }
+
     /// Find which action is bound to a given raw signature
     pub fn find_action_for_signature(&self, signature: &str) -> Option<AppAction> {
         for (action, binding) in &self.mappings {
@@ -293,6 +520,8 @@ impl MappingProfileManager {
         let json = serde_json::to_string_pretty(profile)
             .context("Failed to serialize profile")?;
         
+        // Keep the previous version in case this save breaks a working mapping
+        profile_backup::backup_before_write(&path, DEFAULT_BACKUP_VERSIONS)?;
         fs::write(&path, json)
             .context("Failed to write profile file")?;
         
@@ -300,6 +529,21 @@ impl MappingProfileManager {
         Ok(())
     }
 
+    /// Previous versions of a profile, newest first
+    pub fn list_profile_versions(&self, name: &str) -> Result<Vec<BackupVersion>> {
+        profile_backup::list_versions(&self.get_profile_path(name))
+    }
+
+    /// Restore a previous version of a profile (reloaded if it's the active one)
+    pub fn restore_profile_version(&mut self, name: &str, timestamp: u64) -> Result<()> {
+        profile_backup::restore_version(&self.get_profile_path(name), timestamp, DEFAULT_BACKUP_VERSIONS)?;
+        log::info!("⏪ Restored mapping profile {} from version {}", name, timestamp);
+        if self.active_profile.as_ref().is_some_and(|profile| profile.name == name) {
+            self.load_profile(name)?;
+        }
+        Ok(())
+    }
+
     /// Delete a profile
     pub fn delete_profile(&self, name: &str) -> Result<()> {
         let path = self.get_profile_path(name);
@@ -348,3 +592,89 @@ pub fn generate_axis_signature(logical_axis: &str, code: Option<&str>) -> String
         format!("axis:{}", logical_axis)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn axis(invert: bool) -> AxisBinding {
+        AxisBinding {
+            code: None,
+            logical_axis: "RightStickY".to_string(),
+            min: -1.0,
+            max: 1.0,
+            deadzone: 0.1,
+            invert,
+            threshold: 0.5,
+            curve: ResponseCurve::Linear,
+        }
+    }
+
+    #[test]
+    fn test_normalize_deadzone_and_invert() {
+        assert_eq!(axis(false).normalize(0.05), 0.0);
+        assert_eq!(axis(false).normalize(0.8), 0.8);
+        assert_eq!(axis(true).normalize(-0.8), 0.8);
+    }
+
+    #[test]
+    fn test_response_curves() {
+        let soft_start = ResponseCurve::Exponential { exponent: 0.5 };
+        assert!((soft_start.apply(0.25) - 0.5).abs() < 1e-6);
+        assert!((soft_start.apply(-0.25) + 0.5).abs() < 1e-6);
+        assert_eq!(soft_start.apply(1.0), 1.0);
+
+        // Unsorted breakpoints still interpolate in travel order
+        let table = ResponseCurve::Table { points: vec![(1.0, 1.0), (0.0, 0.0), (0.2, 0.6)] };
+        assert!((table.apply(0.1) - 0.3).abs() < 1e-6);
+        assert!((table.apply(0.6) - 0.8).abs() < 1e-6);
+        assert!((table.apply(-0.6) + 0.8).abs() < 1e-6);
+        assert_eq!(ResponseCurve::Table { points: Vec::new() }.apply(0.4), 0.4);
+    }
+
+    #[test]
+    fn test_normalize_applies_curve() {
+        let mut binding = axis(false);
+        binding.curve = ResponseCurve::Exponential { exponent: 2.0 };
+        assert_eq!(binding.normalize(0.05), 0.0);
+        assert!((binding.normalize(0.5) - 0.25).abs() < 1e-6);
+
+        // Profiles saved before curves existed stay linear
+        let json = r#"{"code":null,"logical_axis":"RightStickX","min":-1.0,"max":1.0,"deadzone":0.05,"invert":false}"#;
+        let binding: AxisBinding = serde_json::from_str(json).unwrap();
+        assert_eq!(binding.curve, ResponseCurve::Linear);
+    }
+
+    #[test]
+    fn test_axis_button_hysteresis() {
+        let binding = axis(false);
+        let mut state = AxisButtonState::default();
+        assert!(!state.update(&binding, 0.45));
+        assert!(state.update(&binding, 0.55));
+        // Still pressed inside the hysteresis band
+        assert!(state.update(&binding, 0.45));
+        assert!(!state.update(&binding, 0.3));
+    }
+
+    #[test]
+    fn test_axis_button_bindings_skip_analog_actions() {
+        let controller = ControllerId { name: "Guitar".to_string(), label: None, vendor_id: None, product_id: None };
+        let mut profile = MappingProfile::new("Test".to_string(), controller);
+        profile.add_mapping(AppAction::StrumUp, RawBinding::Axis(axis(true)));
+        profile.add_mapping(AppAction::StrumDown, RawBinding::Axis(axis(false)));
+        profile.add_mapping(AppAction::WhammyAxis, RawBinding::Axis(axis(false)));
+
+        let bindings = profile.axis_button_bindings();
+        assert_eq!(bindings.len(), 2);
+        assert!(bindings.iter().all(|(action, _)| !action.is_analog()));
+    }
+
+    #[test]
+    fn test_threshold_defaults_when_missing() {
+        let json = r#"{"kind":"axis","code":null,"logical_axis":"LeftZ","min":0.0,"max":1.0,"deadzone":0.05,"invert":false}"#;
+        let RawBinding::Axis(binding) = serde_json::from_str(json).unwrap() else {
+            panic!("expected axis binding");
+        };
+        assert_eq!(binding.threshold, DEFAULT_AXIS_THRESHOLD);
+    }
+}
@@ -3,7 +3,7 @@ use std::time::{Duration, Instant};
 use gilrs::Event;
 use serde::{Deserialize, Serialize};
 use crate::raw_diagnostics::RawInputEvent;
-use crate::mapping_profile::{AppAction, RawBinding, ButtonBinding, AxisBinding};
+use crate::mapping_profile::{AppAction, RawBinding, ButtonBinding, AxisBinding, ResponseCurve};
 
 /// Capture state for the mapping wizard
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -201,13 +201,19 @@ impl MappingWizard {
 
             // Only consider it a valid axis if there was movement
             if (max - min).abs() > 0.1 {
+                // Button actions on an axis fire in the direction the control moved
+                let invert = state.target_action
+                    .map(|action| !action.is_analog() && min.abs() > max.abs())
+                    .unwrap_or(false);
                 let binding = RawBinding::Axis(AxisBinding {
                     code: None,
                     logical_axis: axis_name.clone(),
                     min,
                     max,
                     deadzone: 0.05,
-                    invert: false,
+                    invert,
+                    threshold: 0.5,
+                    curve: ResponseCurve::Linear,
                 });
 
                 state.captured_events.clear();
@@ -0,0 +1,106 @@
+//! Guitar-driven menu navigation
+//! While the frontend has a menu open, the D-pad, strum bar, Start/Select and
+//! the green/red frets become navigation actions instead of notes, so the app
+//! can be used from the guitar without a keyboard.
+
+use serde::{Deserialize, Serialize};
+use crate::high_performance::ControllerStateSnapshot;
+
+/// Navigation action sent to the frontend
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NavAction {
+    Up,
+    Down,
+    Select,
+    Back,
+}
+
+const NAV_ACTIONS: [NavAction; 4] = [NavAction::Up, NavAction::Down, NavAction::Select, NavAction::Back];
+
+/// Which navigation inputs a snapshot holds, indexed like `NAV_ACTIONS`
+fn held(snapshot: &ControllerStateSnapshot) -> [bool; 4] {
+    [
+        snapshot.dpad_up || snapshot.strum_up,
+        snapshot.dpad_down || snapshot.strum_down,
+        snapshot.start || snapshot.fret_green,
+        snapshot.select || snapshot.fret_red,
+    ]
+}
+
+/// Turns controller presses into navigation actions while a menu is open
+#[derive(Debug, Default)]
+pub struct MenuNavigator {
+    open: bool,
+    prev: [bool; 4],
+}
+
+impl MenuNavigator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.open
+    }
+
+    /// Enter menu mode. Inputs already held don't fire until pressed again.
+    pub fn open(&mut self, snapshot: &ControllerStateSnapshot) {
+        self.open = true;
+        self.prev = held(snapshot);
+    }
+
+    pub fn close(&mut self) {
+        self.open = false;
+    }
+
+    /// Actions for inputs pressed since the last update (none while closed)
+    pub fn update(&mut self, snapshot: &ControllerStateSnapshot) -> Vec<NavAction> {
+        let now = held(snapshot);
+        let prev = std::mem::replace(&mut self.prev, now);
+        if !self.open {
+            return Vec::new();
+        }
+        NAV_ACTIONS.iter()
+            .zip(now.iter().zip(prev.iter()))
+            .filter(|(_, (now, before))| **now && !**before)
+            .map(|(action, _)| *action)
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_presses_become_actions_only_while_open() {
+        let mut navigator = MenuNavigator::new();
+        let mut snapshot = ControllerStateSnapshot { strum_down: true, ..Default::default() };
+        assert!(navigator.update(&snapshot).is_empty());
+
+        snapshot.strum_down = false;
+        navigator.open(&snapshot);
+        snapshot.dpad_down = true;
+        snapshot.fret_green = true;
+        assert_eq!(navigator.update(&snapshot), vec![NavAction::Down, NavAction::Select]);
+        // Holding doesn't repeat
+        assert!(navigator.update(&snapshot).is_empty());
+
+        navigator.close();
+        snapshot.fret_red = true;
+        assert!(navigator.update(&snapshot).is_empty());
+    }
+
+    #[test]
+    fn test_inputs_held_when_opening_are_ignored() {
+        let mut navigator = MenuNavigator::new();
+        let snapshot = ControllerStateSnapshot { start: true, ..Default::default() };
+        navigator.open(&snapshot);
+        assert!(navigator.update(&snapshot).is_empty());
+
+        let released = ControllerStateSnapshot::default();
+        navigator.update(&released);
+        assert_eq!(navigator.update(&snapshot), vec![NavAction::Select]);
+    }
+}
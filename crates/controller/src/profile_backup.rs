@@ -0,0 +1,137 @@
+//! Backup rotation for user-edited JSON files (mapping profiles, chord presets)
+//! Before a file is overwritten, the previous version is copied into a
+//! `backups` folder next to it as `<name>.<unix ms>.<ext>`, keeping the newest
+//! `keep` versions per file.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Reverse;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Versions kept per file
+pub const DEFAULT_BACKUP_VERSIONS: usize = 10;
+
+const BACKUP_DIR: &str = "backups";
+
+/// A stored previous version of a file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupVersion {
+    /// Unix timestamp (milliseconds) when the version was replaced
+    pub timestamp: u64,
+    pub file_name: String,
+}
+
+fn backup_dir(path: &Path) -> PathBuf {
+    path.parent().unwrap_or(Path::new(".")).join(BACKUP_DIR)
+}
+
+fn name_parts(path: &Path) -> Result<(String, String)> {
+    let stem = path.file_stem().context("File has no name")?.to_string_lossy().to_string();
+    let ext = path.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default();
+    Ok((stem, ext))
+}
+
+fn backup_path(path: &Path, timestamp: u64) -> Result<PathBuf> {
+    let (stem, ext) = name_parts(path)?;
+    Ok(backup_dir(path).join(format!("{}.{}.{}", stem, timestamp, ext)))
+}
+
+/// Copy the current contents of `path` into the backups folder (no-op if the
+/// file doesn't exist yet) and drop versions beyond `keep`
+pub fn backup_before_write(path: &Path, keep: usize) -> Result<Option<PathBuf>> {
+    if !path.exists() {
+        return Ok(None);
+    }
+    fs::create_dir_all(backup_dir(path)).context("Failed to create backups directory")?;
+
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64;
+    // Saves within the same millisecond still sort after every stored version
+    let newest = list_versions(path)?.first().map(|version| version.timestamp);
+    let timestamp = newest.map_or(now, |newest| now.max(newest + 1));
+    let backup = backup_path(path, timestamp)?;
+    fs::copy(path, &backup).context("Failed to write backup")?;
+
+    for old in list_versions(path)?.into_iter().skip(keep) {
+        let _ = fs::remove_file(backup_dir(path).join(&old.file_name));
+    }
+    Ok(Some(backup))
+}
+
+/// Stored versions of `path`, newest first
+pub fn list_versions(path: &Path) -> Result<Vec<BackupVersion>> {
+    let (stem, ext) = name_parts(path)?;
+    let prefix = format!("{}.", stem);
+    let suffix = format!(".{}", ext);
+
+    let mut versions = Vec::new();
+    if let Ok(entries) = fs::read_dir(backup_dir(path)) {
+        for entry in entries.flatten() {
+            let file_name = entry.file_name().to_string_lossy().to_string();
+            let timestamp = file_name
+                .strip_prefix(&prefix)
+                .and_then(|rest| rest.strip_suffix(&suffix))
+                .and_then(|middle| middle.parse::<u64>().ok());
+            if let Some(timestamp) = timestamp {
+                versions.push(BackupVersion { timestamp, file_name });
+            }
+        }
+    }
+    versions.sort_by_key(|version| Reverse(version.timestamp));
+    Ok(versions)
+}
+
+/// Put a stored version back in place (the current file is backed up first)
+pub fn restore_version(path: &Path, timestamp: u64, keep: usize) -> Result<()> {
+    let backup = backup_path(path, timestamp)?;
+    let content = fs::read(&backup).context(format!("Backup version {} not found", timestamp))?;
+    backup_before_write(path, keep)?;
+    fs::write(path, content).context("Failed to restore backup")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rotation_keeps_newest_versions() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("Guitar.json");
+        assert!(backup_before_write(&path, 2).unwrap().is_none());
+
+        for version in 0..4 {
+            fs::write(&path, format!("{{\"v\": {}}}", version)).unwrap();
+            backup_before_write(&path, 2).unwrap();
+        }
+        // A profile whose name extends this one isn't mixed in
+        fs::write(dir.path().join("backups").join("Guitar.2.123.json"), "{}").unwrap();
+
+        let versions = list_versions(&path).unwrap();
+        assert_eq!(versions.len(), 2);
+        assert!(versions[0].timestamp > versions[1].timestamp);
+        let newest = fs::read_to_string(dir.path().join("backups").join(&versions[0].file_name)).unwrap();
+        assert_eq!(newest, "{\"v\": 3}");
+    }
+
+    #[test]
+    fn test_restore_version() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("Guitar.json");
+        fs::write(&path, "working").unwrap();
+        backup_before_write(&path, 5).unwrap();
+        fs::write(&path, "broken").unwrap();
+
+        let working = list_versions(&path).unwrap()[0].timestamp;
+        restore_version(&path, working, 5).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "working");
+        // The overwritten version is kept too
+        assert_eq!(list_versions(&path).unwrap().len(), 2);
+        assert!(restore_version(&path, 1, 5).is_err());
+    }
+}
@@ -1,8 +1,11 @@
-use std::collections::VecDeque;
+use std::cmp::Reverse;
+use std::collections::{HashMap, VecDeque};
 use std::sync::{Arc, Mutex};
 use std::time::{Instant, SystemTime, UNIX_EPOCH};
 use gilrs::{Event, EventType, Button, Axis};
 use serde::{Deserialize, Serialize};
+use crate::input_replay::{InputReplayer, ReplayStep};
+use crate::mapping_profile::{AppAction, MappingProfile};
 
 /// Maximum number of raw events to keep in memory
 const MAX_RAW_EVENTS: usize = 500;
@@ -111,10 +114,20 @@ impl RawInputEvent {
     }
 }
 
+/// Number of bounces dropped by the debounce layer for one action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilteredEventStats {
+    pub action: AppAction,
+    pub count: u64,
+    /// Milliseconds since diagnostics start of the last filtered bounce
+    pub last_filtered_ms: u64,
+}
+
 /// Raw diagnostics recorder for guitar controller
 pub struct RawDiagnostics {
     enabled: Arc<Mutex<bool>>,
     events: Arc<Mutex<VecDeque<RawInputEvent>>>,
+    filtered: Arc<Mutex<HashMap<AppAction, FilteredEventStats>>>,
     start_time: Instant,
     max_events: usize,
 }
@@ -124,6 +137,7 @@ impl RawDiagnostics {
         Self {
             enabled: Arc::new(Mutex::new(false)),
             events: Arc::new(Mutex::new(VecDeque::with_capacity(MAX_RAW_EVENTS))),
+            filtered: Arc::new(Mutex::new(HashMap::new())),
             start_time: Instant::now(),
             max_events: MAX_RAW_EVENTS,
         }
@@ -236,9 +250,36 @@ impl RawDiagnostics {
         events.iter().rev().cloned().collect()
     }
 
+    /// Count a bounce dropped by the debounce layer (always recorded)
+    pub fn record_filtered(&self, action: AppAction) {
+        let timestamp_ms = self.start_time.elapsed().as_millis() as u64;
+        let mut filtered = self.filtered.lock().unwrap();
+        let stats = filtered.entry(action).or_insert(FilteredEventStats {
+            action,
+            count: 0,
+            last_filtered_ms: 0,
+        });
+        stats.count += 1;
+        stats.last_filtered_ms = timestamp_ms;
+    }
+
+    /// Debounce statistics per action (most filtered first)
+    pub fn filtered_stats(&self) -> Vec<FilteredEventStats> {
+        let mut stats: Vec<_> = self.filtered.lock().unwrap().values().cloned().collect();
+        stats.sort_by_key(|stat| Reverse(stat.count));
+        stats
+    }
+
+    /// Replay the recorded events through the translation layer
+    pub fn replay(&self, profile: Option<&MappingProfile>) -> Vec<ReplayStep> {
+        let events: Vec<RawInputEvent> = self.events.lock().unwrap().iter().cloned().collect();
+        InputReplayer::replay(&events, profile)
+    }
+
     /// Clear all recorded events
     pub fn clear(&self) {
         self.events.lock().unwrap().clear();
+        self.filtered.lock().unwrap().clear();
         log::info!("🔍 Raw diagnostics cleared");
     }
 
@@ -0,0 +1,131 @@
+//! Rumble cues
+//! Guitars and gamepads with force feedback shake as feedback from song
+//! mode: a short buzz on a missed note and a long one when star power kicks
+//! in. `Rumble` builds the gilrs effects and keeps each one alive until it
+//! has played (dropping a gilrs effect stops it). Bursts of misses are
+//! thinned out so fast strumming doesn't turn into one long rumble, and
+//! misses never cut into a star power rumble.
+
+use anyhow::Result;
+use gilrs::ff::{BaseEffect, BaseEffectType, Effect, EffectBuilder, Repeat, Replay, Ticks};
+use gilrs::{GamepadId, Gilrs};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Misses closer together than this rumble once
+const MIN_MISS_GAP: Duration = Duration::from_millis(150);
+
+/// What the rumble tells the player
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RumbleCue {
+    /// Short buzz on a missed note
+    MissedNote,
+    /// Long rumble when star power activates
+    StarPower,
+}
+
+impl RumbleCue {
+    pub fn duration(&self) -> Duration {
+        match self {
+            Self::MissedNote => Duration::from_millis(120),
+            Self::StarPower => Duration::from_millis(700),
+        }
+    }
+
+    /// Motor strength (0-65535)
+    pub fn magnitude(&self) -> u16 {
+        match self {
+            Self::MissedNote => 30_000,
+            Self::StarPower => 60_000,
+        }
+    }
+}
+
+/// Plays rumble cues on gamepads that support force feedback
+#[derive(Default)]
+pub struct Rumble {
+    enabled: bool,
+    /// Effects still playing, with when they end
+    playing: Vec<(RumbleCue, Instant, Effect)>,
+    last_miss: Option<Instant>,
+}
+
+impl Rumble {
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled, ..Default::default() }
+    }
+
+    pub fn enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Switch rumble on or off (off stops what is playing)
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        if !enabled {
+            self.playing.clear();
+        }
+    }
+
+    /// Whether a cue should play now: rumble is on, a miss isn't right after
+    /// another one, and a miss doesn't cut into a star power rumble
+    fn should_play(&mut self, cue: RumbleCue, now: Instant) -> bool {
+        if !self.enabled {
+            return false;
+        }
+        self.playing.retain(|(_, until, _)| *until > now);
+        if cue == RumbleCue::MissedNote {
+            let star_power_playing = self.playing.iter().any(|(playing, _, _)| *playing == RumbleCue::StarPower);
+            let too_soon = self.last_miss.is_some_and(|last| now.saturating_duration_since(last) < MIN_MISS_GAP);
+            if star_power_playing || too_soon {
+                return false;
+            }
+            self.last_miss = Some(now);
+        }
+        true
+    }
+
+    /// Rumble a gamepad for a cue. Gamepads without force feedback are skipped.
+    pub fn play(&mut self, gilrs: &mut Gilrs, gamepad: GamepadId, cue: RumbleCue) -> Result<()> {
+        let now = Instant::now();
+        if !gilrs.connected_gamepad(gamepad).is_some_and(|pad| pad.is_ff_supported()) || !self.should_play(cue, now) {
+            return Ok(());
+        }
+        let duration = Ticks::from_ms(cue.duration().as_millis() as u32);
+        let effect = EffectBuilder::new()
+            .add_effect(BaseEffect {
+                kind: BaseEffectType::Strong { magnitude: cue.magnitude() },
+                scheduling: Replay { play_for: duration, ..Default::default() },
+                envelope: Default::default(),
+            })
+            .repeat(Repeat::For(duration))
+            .gamepads(&[gamepad])
+            .finish(gilrs)
+            .map_err(|e| anyhow::anyhow!("Failed to create rumble effect: {}", e))?;
+        effect.play().map_err(|e| anyhow::anyhow!("Failed to play rumble effect: {}", e))?;
+        self.playing.push((cue, now + cue.duration(), effect));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rumble_cue_scheduling() {
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        let mut rumble = Rumble::new(false);
+        assert!(!rumble.should_play(RumbleCue::MissedNote, at(0)));
+
+        rumble.set_enabled(true);
+        assert!(rumble.should_play(RumbleCue::MissedNote, at(0)));
+        // A burst of misses rumbles once
+        assert!(!rumble.should_play(RumbleCue::MissedNote, at(50)));
+        assert!(rumble.should_play(RumbleCue::MissedNote, at(300)));
+        assert!(rumble.should_play(RumbleCue::StarPower, at(310)));
+        assert!(RumbleCue::StarPower.duration() > RumbleCue::MissedNote.duration());
+    }
+}
@@ -1,12 +1,90 @@
 //! Controller simulator for development and testing without hardware
+//! Keys pressed in the app, or played back from a timed input script, hold
+//! controls of player 1's guitar alongside any hardware. Holding an axis key
+//! ramps the whammy or tilt up and letting go brings it back; the mouse
+//! wheel sets where the whammy rests.
 
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use crate::high_performance::ControllerStateSnapshot;
 use crate::{ControlId, ControllerState};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::{Duration, Instant};
+
+/// Time an axis key takes to ramp the axis all the way (ms)
+pub const AXIS_RAMP_MS: f32 = 300.0;
+/// Whammy travel per mouse wheel notch
+pub const WHEEL_STEP: f32 = 0.1;
+
+/// Whether a scripted key goes down or comes back up
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyAction {
+    Down,
+    Up,
+}
+
+/// One key event of an input script
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScriptedKey {
+    /// Time from the start of the script (ms)
+    pub at_ms: u64,
+    /// Key as the app reports it (e.g. "1", "ArrowDown", "Space")
+    pub key: String,
+    pub action: KeyAction,
+}
+
+/// A timed list of key events, stored as a JSON list:
+/// `[{"at_ms": 0, "key": "1", "action": "down"}, ...]`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct InputScript {
+    pub events: Vec<ScriptedKey>,
+}
+
+impl InputScript {
+    pub fn from_json(json: &str) -> Result<Self> {
+        serde_json::from_str(json).context("Failed to parse input script")
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read input script {}", path.display()))?;
+        Self::from_json(&json).with_context(|| format!("Invalid input script {}", path.display()))
+    }
+
+    /// Check the script can be played: events in time order, keys the simulator knows
+    pub fn validate(&self, simulator: &ControllerSimulator) -> Result<()> {
+        if self.events.is_empty() {
+            bail!("Input script has no events");
+        }
+        if self.events.windows(2).any(|pair| pair[1].at_ms < pair[0].at_ms) {
+            bail!("Input script has events out of time order");
+        }
+        if let Some(event) = self.events.iter().find(|event| !simulator.is_bound(&event.key)) {
+            bail!("Unknown key '{}' at {} ms in input script", event.key, event.at_ms);
+        }
+        Ok(())
+    }
+
+    /// Time of the last event (ms)
+    pub fn duration_ms(&self) -> u64 {
+        self.events.last().map(|event| event.at_ms).unwrap_or(0)
+    }
+}
 
 /// Maps keyboard keys to controller inputs for simulation
 pub struct ControllerSimulator {
     state: ControllerState,
     key_bindings: HashMap<String, ControlId>,
+    /// Keys that ramp an axis while held
+    axis_bindings: HashMap<String, ControlId>,
+    held_axes: HashSet<ControlId>,
+    /// Whammy position with no axis key held (set with the mouse wheel)
+    whammy_rest: f32,
+    last_tick: Option<Instant>,
 }
 
 impl ControllerSimulator {
@@ -32,18 +110,36 @@ impl ControllerSimulator {
         key_bindings.insert("t".to_string(), ControlId::SoloOrange);
         key_bindings.insert("T".to_string(), ControlId::SoloOrange);
         
-        // Strum (Arrow Up/Down or Space)
+        // Strum (Arrow Up/Down or Space; scripts can name it "Space")
         key_bindings.insert("ArrowUp".to_string(), ControlId::StrumUp);
         key_bindings.insert("ArrowDown".to_string(), ControlId::StrumDown);
         key_bindings.insert(" ".to_string(), ControlId::StrumDown); // Space key
+        key_bindings.insert("Space".to_string(), ControlId::StrumDown);
         
         // Standard buttons
         key_bindings.insert("Enter".to_string(), ControlId::Start);
         key_bindings.insert("Escape".to_string(), ControlId::Select);
         
+        // D-pad (Arrow Left/Right, Page Up/Down)
+        key_bindings.insert("ArrowLeft".to_string(), ControlId::DPadLeft);
+        key_bindings.insert("ArrowRight".to_string(), ControlId::DPadRight);
+        key_bindings.insert("PageUp".to_string(), ControlId::DPadUp);
+        key_bindings.insert("PageDown".to_string(), ControlId::DPadDown);
+        
+        // Whammy (X) and tilt (Z) ramp while held
+        let mut axis_bindings = HashMap::new();
+        axis_bindings.insert("x".to_string(), ControlId::WhammyBar);
+        axis_bindings.insert("X".to_string(), ControlId::WhammyBar);
+        axis_bindings.insert("z".to_string(), ControlId::TiltSensor);
+        axis_bindings.insert("Z".to_string(), ControlId::TiltSensor);
+        
         Self {
             state: ControllerState::default(),
             key_bindings,
+            axis_bindings,
+            held_axes: HashSet::new(),
+            whammy_rest: 0.0,
+            last_tick: None,
         }
     }
 
@@ -53,6 +149,9 @@ impl ControllerSimulator {
         if let Some(control) = self.key_bindings.get(key) {
             log::debug!("  ➜ Mapped to: {:?}", control);
             self.state.set_button(*control, true);
+        } else if let Some(axis) = self.axis_bindings.get(key) {
+            log::debug!("  ➜ Ramping: {:?}", axis);
+            self.held_axes.insert(*axis);
         } else {
             log::trace!("  ➜ No mapping found for key: {:?}", key);
         }
@@ -64,6 +163,88 @@ impl ControllerSimulator {
         if let Some(control) = self.key_bindings.get(key) {
             log::debug!("  ➜ Unmapped from: {:?}", control);
             self.state.set_button(*control, false);
+        } else if let Some(axis) = self.axis_bindings.get(key) {
+            self.held_axes.remove(axis);
+        }
+    }
+
+    /// Move the whammy's resting position by mouse wheel notches (positive = pushed further)
+    pub fn wheel(&mut self, notches: f32) {
+        self.whammy_rest = (self.whammy_rest + notches * WHEEL_STEP).clamp(0.0, 1.0);
+        if !self.held_axes.contains(&ControlId::WhammyBar) {
+            self.state.set_axis(ControlId::WhammyBar, self.whammy_rest);
+        }
+    }
+
+    /// Ramp the axes for the time since the last tick
+    pub fn tick(&mut self, now: Instant) {
+        if let Some(last) = self.last_tick {
+            self.advance(now.saturating_duration_since(last));
+        }
+        self.last_tick = Some(now);
+    }
+
+    /// Ramp held axes up and released ones back to rest
+    pub fn advance(&mut self, elapsed: Duration) {
+        let step = elapsed.as_secs_f32() * 1000.0 / AXIS_RAMP_MS;
+        for (axis, rest) in [(ControlId::WhammyBar, self.whammy_rest), (ControlId::TiltSensor, 0.0)] {
+            let target = if self.held_axes.contains(&axis) { 1.0 } else { rest };
+            let value = self.state.axis(axis);
+            let moved = if value < target { (value + step).min(target) } else { (value - step).max(target) };
+            self.state.set_axis(axis, moved);
+        }
+    }
+
+    /// Play a scripted key event, exactly as if the key was pressed or released
+    pub fn apply(&mut self, event: &ScriptedKey) {
+        match event.action {
+            KeyAction::Down => self.key_down(&event.key),
+            KeyAction::Up => self.key_up(&event.key),
+        }
+    }
+
+    /// Let go of every key (e.g. when a script ends or is cancelled)
+    pub fn release_all(&mut self) {
+        for pressed in self.state.buttons.values_mut() {
+            *pressed = false;
+        }
+        self.held_axes.clear();
+    }
+
+    /// The key holds a control or ramps an axis
+    pub fn is_bound(&self, key: &str) -> bool {
+        self.key_bindings.contains_key(key) || self.axis_bindings.contains_key(key)
+    }
+
+    /// Add the held keys to a guitar's snapshot (controls held on either count)
+    pub fn merge_into(&self, snapshot: &mut ControllerStateSnapshot) {
+        let held = |control| self.state.button(control);
+        snapshot.fret_green |= held(ControlId::FretGreen);
+        snapshot.fret_red |= held(ControlId::FretRed);
+        snapshot.fret_yellow |= held(ControlId::FretYellow);
+        snapshot.fret_blue |= held(ControlId::FretBlue);
+        snapshot.fret_orange |= held(ControlId::FretOrange);
+        snapshot.strum_up |= held(ControlId::StrumUp);
+        snapshot.strum_down |= held(ControlId::StrumDown);
+        snapshot.dpad_up |= held(ControlId::DPadUp);
+        snapshot.dpad_down |= held(ControlId::DPadDown);
+        snapshot.dpad_left |= held(ControlId::DPadLeft);
+        snapshot.dpad_right |= held(ControlId::DPadRight);
+        snapshot.start |= held(ControlId::Start);
+        snapshot.select |= held(ControlId::Select);
+        snapshot.solo_green |= held(ControlId::SoloGreen);
+        snapshot.solo_red |= held(ControlId::SoloRed);
+        snapshot.solo_yellow |= held(ControlId::SoloYellow);
+        snapshot.solo_blue |= held(ControlId::SoloBlue);
+        snapshot.solo_orange |= held(ControlId::SoloOrange);
+        // A simulated axis away from rest takes over from the guitar's
+        let whammy = self.state.axis(ControlId::WhammyBar);
+        if whammy != 0.0 {
+            snapshot.whammy_bar = whammy;
+        }
+        let tilt = self.state.axis(ControlId::TiltSensor);
+        if tilt != 0.0 {
+            snapshot.tilt = tilt;
         }
     }
 
@@ -86,6 +267,11 @@ impl ControllerSimulator {
     pub fn get_bindings(&self) -> &HashMap<String, ControlId> {
         &self.key_bindings
     }
+
+    /// Keys that ramp an axis, for UI display
+    pub fn get_axis_bindings(&self) -> &HashMap<String, ControlId> {
+        &self.axis_bindings
+    }
 }
 
 impl Default for ControllerSimulator {
@@ -116,4 +302,70 @@ mod tests {
         sim.key_down("Space");
         assert!(sim.get_state().button(ControlId::StrumDown));
     }
+
+    #[test]
+    fn test_input_script_playback() {
+        let json = r#"[
+            {"at_ms": 0, "key": "1", "action": "down"},
+            {"at_ms": 0, "key": "3", "action": "down"},
+            {"at_ms": 250, "key": "ArrowDown", "action": "down"},
+            {"at_ms": 300, "key": "ArrowDown", "action": "up"}
+        ]"#;
+        let script = InputScript::from_json(json).unwrap();
+        let mut sim = ControllerSimulator::new();
+        script.validate(&sim).unwrap();
+        assert_eq!(script.duration_ms(), 300);
+
+        for event in &script.events[..3] {
+            sim.apply(event);
+        }
+        let mut snapshot = ControllerStateSnapshot { fret_blue: true, ..Default::default() };
+        sim.merge_into(&mut snapshot);
+        assert!(snapshot.fret_green && snapshot.fret_yellow && snapshot.strum_down);
+        // Hardware input is kept
+        assert!(snapshot.fret_blue && !snapshot.fret_red);
+
+        sim.apply(&script.events[3]);
+        assert!(!sim.get_state().button(ControlId::StrumDown));
+        sim.release_all();
+        assert!(sim.get_state().pressed_frets().is_empty());
+
+        let mut bad = script.clone();
+        bad.events[1].key = "F13".to_string();
+        assert!(bad.validate(&sim).is_err());
+        bad = script;
+        bad.events.reverse();
+        assert!(bad.validate(&sim).is_err());
+    }
+
+    #[test]
+    fn test_axis_ramps_wheel_and_solo_frets() {
+        let mut sim = ControllerSimulator::new();
+        sim.key_down("x");
+        sim.advance(Duration::from_millis(150));
+        assert!((sim.get_state().axis(ControlId::WhammyBar) - 0.5).abs() < 1e-4);
+        sim.advance(Duration::from_millis(500));
+        assert_eq!(sim.get_state().axis(ControlId::WhammyBar), 1.0);
+
+        // Letting go springs back to where the wheel left it
+        sim.wheel(2.0);
+        sim.key_up("x");
+        sim.advance(Duration::from_secs(1));
+        assert!((sim.get_state().axis(ControlId::WhammyBar) - 0.2).abs() < 1e-4);
+        sim.wheel(-5.0);
+        assert_eq!(sim.get_state().axis(ControlId::WhammyBar), 0.0);
+
+        sim.key_down("Z");
+        sim.advance(Duration::from_millis(60));
+        sim.key_down("w");
+        sim.key_down("ArrowLeft");
+        let mut snapshot = ControllerStateSnapshot { whammy_bar: 0.3, ..Default::default() };
+        sim.merge_into(&mut snapshot);
+        assert!((snapshot.tilt - 0.2).abs() < 1e-4);
+        // The guitar's whammy is kept while the simulated one rests
+        assert_eq!(snapshot.whammy_bar, 0.3);
+        assert!(snapshot.solo_red && !snapshot.solo_green);
+        assert!(snapshot.dpad_left);
+        assert!(snapshot.to_controller_state().buttons[&ControlId::SoloRed]);
+    }
 }
@@ -0,0 +1,81 @@
+//! Tilt trigger
+//! Turns the tilt axis into an on/off gesture: the neck has to come up past
+//! one threshold to engage and back down under a lower one to let go, so a
+//! guitar held near the threshold doesn't flicker between the two.
+
+/// Tilt (-1.0..1.0) where raising the neck engages
+pub const DEFAULT_TILT_ON: f32 = 0.7;
+
+/// Gap between engaging and letting go
+pub const DEFAULT_TILT_HYSTERESIS: f32 = 0.2;
+
+/// Threshold and hysteresis detector for one player's tilt
+#[derive(Debug, Clone, Copy)]
+pub struct TiltTrigger {
+    on: f32,
+    off: f32,
+    engaged: bool,
+}
+
+impl Default for TiltTrigger {
+    fn default() -> Self {
+        Self::new(DEFAULT_TILT_ON, DEFAULT_TILT_HYSTERESIS)
+    }
+}
+
+impl TiltTrigger {
+    pub fn new(threshold: f32, hysteresis: f32) -> Self {
+        let on = threshold.clamp(0.1, 1.0);
+        Self { on, off: on - hysteresis.clamp(0.0, on), engaged: false }
+    }
+
+    /// Feed a tilt reading; returns the new state when it changes
+    pub fn update(&mut self, tilt: f32) -> Option<bool> {
+        let engaged = if self.engaged { tilt > self.off } else { tilt >= self.on };
+        if engaged == self.engaged {
+            return None;
+        }
+        self.engaged = engaged;
+        Some(engaged)
+    }
+
+    pub fn is_engaged(&self) -> bool {
+        self.engaged
+    }
+
+    /// Let go without waiting for the neck to come down (the guitar was unplugged)
+    pub fn reset(&mut self) {
+        self.engaged = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engages_past_threshold_and_releases_under_hysteresis() {
+        let mut trigger = TiltTrigger::new(0.7, 0.2);
+        assert_eq!(trigger.update(0.0), None);
+        assert_eq!(trigger.update(0.69), None);
+        assert_eq!(trigger.update(0.7), Some(true));
+        // Wobbling around the threshold doesn't let go
+        assert_eq!(trigger.update(0.6), None);
+        assert_eq!(trigger.update(0.75), None);
+        assert_eq!(trigger.update(0.5), Some(false));
+        assert!(!trigger.is_engaged());
+    }
+
+    #[test]
+    fn test_thresholds_are_clamped() {
+        let mut trigger = TiltTrigger::new(5.0, 3.0);
+        assert_eq!(trigger.update(1.0), Some(true));
+        // The release point can't go under flat
+        assert_eq!(trigger.update(0.01), None);
+        assert_eq!(trigger.update(0.0), Some(false));
+
+        trigger.update(1.0);
+        trigger.reset();
+        assert!(!trigger.is_engaged());
+    }
+}
@@ -0,0 +1,260 @@
+//! Whammy bar calibration
+//! Records raw whammy values for a few seconds (leave the bar at rest first,
+//! then push it all the way a few times) and derives the rest/min/max range
+//! used to normalize the whammy to 0.0-1.0 in the polling loop.
+//!
+//! Bars that travel both ways (push and pull) can be calibrated split: the
+//! resting position becomes the center, pushing reads 0.0 to 1.0 and pulling
+//! 0.0 to -1.0, so the mapper can give each direction its own effect.
+
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+
+/// Default length of a calibration run
+pub const DEFAULT_CALIBRATION_MS: u64 = 5000;
+
+/// Initial part of the run used to measure the resting position
+const REST_WINDOW_MS: u64 = 500;
+
+/// Minimum travel between rest and full press for a usable calibration
+const MIN_WHAMMY_TRAVEL: f32 = 0.05;
+
+/// Calibrated whammy range, stored in the mapping profile
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct WhammyCalibration {
+    /// Raw value with the bar released
+    pub rest: f32,
+    /// Lowest raw value seen
+    pub min: f32,
+    /// Highest raw value seen
+    pub max: f32,
+    /// Rest is the center of a two-way bar (normalizes to -1.0 to 1.0)
+    #[serde(default)]
+    pub split: bool,
+}
+
+impl WhammyCalibration {
+    /// Raw value at full press (the extreme furthest from rest)
+    pub fn full(&self) -> f32 {
+        if (self.max - self.rest).abs() >= (self.rest - self.min).abs() {
+            self.max
+        } else {
+            self.min
+        }
+    }
+
+    /// Raw value at full pull (the other extreme)
+    pub fn pulled(&self) -> f32 {
+        if self.full() == self.max {
+            self.min
+        } else {
+            self.max
+        }
+    }
+
+    /// Map a raw value onto 0.0 (rest) to 1.0 (fully pressed). Split
+    /// calibrations map pulling onto 0.0 to -1.0.
+    pub fn normalize(&self, raw: f32) -> f32 {
+        let travel = self.full() - self.rest;
+        if travel.abs() < f32::EPSILON {
+            return 0.0;
+        }
+        let pushed = (raw - self.rest) / travel;
+        if pushed >= 0.0 || !self.split {
+            return pushed.clamp(0.0, 1.0);
+        }
+        let pull_travel = self.rest - self.pulled();
+        if pull_travel.abs() < f32::EPSILON {
+            return 0.0;
+        }
+        -((self.rest - raw) / pull_travel).clamp(0.0, 1.0)
+    }
+}
+
+/// Progress of a calibration run, for the UI
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalibrationStatus {
+    pub active: bool,
+    pub elapsed_ms: u64,
+    pub duration_ms: u64,
+    pub samples: usize,
+    pub rest: Option<f32>,
+    pub min: Option<f32>,
+    pub max: Option<f32>,
+}
+
+#[derive(Debug)]
+struct CalibrationRun {
+    started: Instant,
+    duration: Duration,
+    rest_sum: f64,
+    rest_samples: usize,
+    min: f32,
+    max: f32,
+    samples: usize,
+}
+
+impl CalibrationRun {
+    fn rest(&self) -> Option<f32> {
+        (self.rest_samples > 0).then(|| (self.rest_sum / self.rest_samples as f64) as f32)
+    }
+}
+
+/// Collects whammy samples from the polling thread while a calibration runs
+#[derive(Debug, Default)]
+pub struct WhammyCalibrator {
+    run: Mutex<Option<CalibrationRun>>,
+}
+
+impl WhammyCalibrator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a calibration run (restarts any run in progress)
+    pub fn start(&self, duration_ms: u64) {
+        log::info!("🎸 Whammy calibration started ({} ms)", duration_ms);
+        *self.run.lock().unwrap() = Some(CalibrationRun {
+            started: Instant::now(),
+            duration: Duration::from_millis(duration_ms.max(REST_WINDOW_MS * 2)),
+            rest_sum: 0.0,
+            rest_samples: 0,
+            min: f32::MAX,
+            max: f32::MIN,
+            samples: 0,
+        });
+    }
+
+    /// Feed a raw whammy value (called from the polling thread)
+    pub fn record(&self, raw: f32) {
+        // Never block the polling thread on the UI
+        let Ok(mut run) = self.run.try_lock() else {
+            return;
+        };
+        let Some(run) = run.as_mut() else {
+            return;
+        };
+        let elapsed = run.started.elapsed();
+        if elapsed > run.duration {
+            return;
+        }
+        if elapsed < Duration::from_millis(REST_WINDOW_MS) {
+            run.rest_sum += raw as f64;
+            run.rest_samples += 1;
+        }
+        run.min = run.min.min(raw);
+        run.max = run.max.max(raw);
+        run.samples += 1;
+    }
+
+    pub fn status(&self) -> CalibrationStatus {
+        let run = self.run.lock().unwrap();
+        match run.as_ref() {
+            Some(run) => CalibrationStatus {
+                active: run.started.elapsed() <= run.duration,
+                elapsed_ms: (run.started.elapsed().min(run.duration)).as_millis() as u64,
+                duration_ms: run.duration.as_millis() as u64,
+                samples: run.samples,
+                rest: run.rest(),
+                min: (run.samples > 0).then_some(run.min),
+                max: (run.samples > 0).then_some(run.max),
+            },
+            None => CalibrationStatus {
+                active: false,
+                elapsed_ms: 0,
+                duration_ms: 0,
+                samples: 0,
+                rest: None,
+                min: None,
+                max: None,
+            },
+        }
+    }
+
+    /// End the run and compute the calibration
+    pub fn finish(&self) -> Result<WhammyCalibration> {
+        self.finish_with(false)
+    }
+
+    /// End the run and compute a split calibration (the resting position
+    /// is the center; push and pull the bar all the way during the run)
+    pub fn finish_split(&self) -> Result<WhammyCalibration> {
+        self.finish_with(true)
+    }
+
+    fn finish_with(&self, split: bool) -> Result<WhammyCalibration> {
+        let run = self.run.lock().unwrap().take()
+            .ok_or_else(|| anyhow::anyhow!("No whammy calibration in progress"))?;
+        let rest = run.rest()
+            .ok_or_else(|| anyhow::anyhow!("No whammy input received - is the guitar connected?"))?;
+        let calibration = WhammyCalibration { rest, min: run.min, max: run.max, split };
+        if (calibration.full() - rest).abs() < MIN_WHAMMY_TRAVEL {
+            anyhow::bail!("Whammy barely moved during calibration - push it all the way down a few times");
+        }
+        if split && (rest - calibration.pulled()).abs() < MIN_WHAMMY_TRAVEL {
+            anyhow::bail!("Whammy was never pulled during calibration - split needs a bar that moves both ways");
+        }
+        log::info!("🎸 Whammy calibrated: rest={:.3} min={:.3} max={:.3} split={}", rest, run.min, run.max, split);
+        Ok(calibration)
+    }
+
+    /// Abort without producing a calibration
+    pub fn cancel(&self) {
+        *self.run.lock().unwrap() = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_partial_range() {
+        // Whammy that only reports 0.3 (rest) to 0.8 (full)
+        let calibration = WhammyCalibration { rest: 0.3, min: 0.3, max: 0.8, split: false };
+        assert_eq!(calibration.normalize(0.3), 0.0);
+        assert!((calibration.normalize(0.55) - 0.5).abs() < 1e-6);
+        assert_eq!(calibration.normalize(0.9), 1.0);
+    }
+
+    #[test]
+    fn test_normalize_inverted_range() {
+        // Rest at the top of the range, pressing lowers the value
+        let calibration = WhammyCalibration { rest: 1.0, min: -1.0, max: 1.0, split: false };
+        assert_eq!(calibration.full(), -1.0);
+        assert!((calibration.normalize(0.0) - 0.5).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_normalize_split_around_center() {
+        // Rest at 0.1, pushing reaches 0.9 and pulling -0.5
+        let calibration = WhammyCalibration { rest: 0.1, min: -0.5, max: 0.9, split: true };
+        assert_eq!(calibration.normalize(0.1), 0.0);
+        assert!((calibration.normalize(0.5) - 0.5).abs() < 1e-6);
+        assert!((calibration.normalize(-0.2) + 0.5).abs() < 1e-6);
+        assert_eq!(calibration.normalize(-0.9), -1.0);
+
+        // A one-way calibration ignores the other direction
+        let one_way = WhammyCalibration { split: false, ..calibration };
+        assert_eq!(one_way.normalize(-0.2), 0.0);
+    }
+
+    #[test]
+    fn test_calibrator_collects_range() {
+        let calibrator = WhammyCalibrator::new();
+        calibrator.start(DEFAULT_CALIBRATION_MS);
+        for raw in [0.3, 0.31, 0.5, 0.8, 0.6] {
+            calibrator.record(raw);
+        }
+        let status = calibrator.status();
+        assert!(status.active);
+        assert_eq!(status.samples, 5);
+
+        let calibration = calibrator.finish().unwrap();
+        assert_eq!(calibration.min, 0.3);
+        assert_eq!(calibration.max, 0.8);
+        assert!(calibrator.finish().is_err());
+    }
+}
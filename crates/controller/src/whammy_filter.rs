@@ -0,0 +1,96 @@
+//! Whammy smoothing and deadzone
+//! Raw whammy readings jitter around rest and snap back when the bar is let
+//! go. The polling loop runs them through a deadzone and a one-pole smoother
+//! (the genre's `smoothing_factor`) so pitch bends glide instead of zippering.
+
+/// Deadzone until a genre sets its own
+pub const DEFAULT_WHAMMY_DEADZONE: f32 = 0.05;
+
+/// Smoothing until a genre sets its own (0.0 = raw, towards 1.0 = slower)
+pub const DEFAULT_WHAMMY_SMOOTHING: f32 = 0.8;
+
+/// Closer than this to the target, the smoother lands on it exactly
+const SETTLE_EPSILON: f32 = 0.001;
+
+/// Deadzone + smoothing for one player's whammy bar
+#[derive(Debug, Clone, Copy)]
+pub struct WhammyFilter {
+    smoothing: f32,
+    deadzone: f32,
+    value: f32,
+}
+
+impl Default for WhammyFilter {
+    fn default() -> Self {
+        Self::new(DEFAULT_WHAMMY_SMOOTHING, DEFAULT_WHAMMY_DEADZONE)
+    }
+}
+
+impl WhammyFilter {
+    pub fn new(smoothing: f32, deadzone: f32) -> Self {
+        let mut filter = Self { smoothing: 0.0, deadzone: 0.0, value: 0.0 };
+        filter.configure(smoothing, deadzone);
+        filter
+    }
+
+    /// Change the settings, keeping the current position
+    pub fn configure(&mut self, smoothing: f32, deadzone: f32) {
+        // At 1.0 the output would never move
+        self.smoothing = smoothing.clamp(0.0, 0.99);
+        self.deadzone = deadzone.clamp(0.0, 0.5);
+    }
+
+    /// Filter a raw reading (-1.0..1.0), called once per poll
+    pub fn apply(&mut self, raw: f32) -> f32 {
+        let magnitude = raw.abs();
+        let target = if magnitude <= self.deadzone {
+            0.0
+        } else {
+            // Rescale so the bar still reaches full travel past the deadzone
+            raw.signum() * ((magnitude - self.deadzone) / (1.0 - self.deadzone)).min(1.0)
+        };
+        self.value = self.value * self.smoothing + target * (1.0 - self.smoothing);
+        if (self.value - target).abs() < SETTLE_EPSILON {
+            self.value = target;
+        }
+        self.value
+    }
+
+    /// Forget the position (the guitar was unplugged)
+    pub fn reset(&mut self) {
+        self.value = 0.0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadzone_rescales_travel() {
+        let mut filter = WhammyFilter::new(0.0, 0.1);
+        assert_eq!(filter.apply(0.08), 0.0);
+        assert_eq!(filter.apply(-0.05), 0.0);
+        assert!((filter.apply(0.55) - 0.5).abs() < 1e-6);
+        assert_eq!(filter.apply(1.0), 1.0);
+        assert_eq!(filter.apply(-1.0), -1.0);
+    }
+
+    #[test]
+    fn test_release_glides_back_and_settles() {
+        let mut filter = WhammyFilter::new(0.8, 0.0);
+        for _ in 0..200 {
+            filter.apply(1.0);
+        }
+        assert_eq!(filter.apply(1.0), 1.0);
+
+        // Letting go doesn't snap straight to rest
+        let first = filter.apply(0.0);
+        assert!(first > 0.5 && first < 1.0);
+        let mut value = first;
+        for _ in 0..100 {
+            value = filter.apply(0.0);
+        }
+        assert_eq!(value, 0.0);
+    }
+}
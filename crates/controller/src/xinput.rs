@@ -0,0 +1,383 @@
+//! XInput backend for Xbox 360 (wireless) Rock Band / Guitar Hero guitars.
+//!
+//! The Xbox 360 wireless receiver is not a HID device, so gilrs does not always
+//! pick up guitars connected through it. On Windows we talk to XInput directly
+//! and feed the same `AtomicControllerState` used by the gilrs polling loop.
+
+use std::sync::atomic::AtomicBool;
+#[cfg(windows)]
+use std::sync::atomic::Ordering;
+use std::sync::{Arc, Mutex};
+use std::thread;
+#[cfg(windows)]
+use std::time::{Duration, Instant};
+use anyhow::Result;
+use gilrs::{Axis, Button};
+use crate::high_performance::{AtomicControllerState, AudioCallback, GuitarInput, ProfileBindings};
+use crate::hotplug::ControllerEventBus;
+use crate::hardware_test::HardwareTester;
+use crate::raw_diagnostics::RawDiagnostics;
+use crate::whammy_calibration::WhammyCalibrator;
+#[cfg(windows)]
+use crate::hotplug::{ControllerDeviceInfo, ControllerEvent};
+#[cfg(windows)]
+use crate::high_performance::{dispatch_edges, poll_gamepad};
+#[cfg(windows)]
+use crate::hat_strum::HatStrumDecoder;
+#[cfg(windows)]
+use crate::debounce::DebounceFilter;
+
+/// Device id prefix selecting this backend (`"xinput"` or `"xinput:<slot>"`)
+pub const XINPUT_DEVICE_PREFIX: &str = "xinput";
+
+/// XInput supports at most four user slots
+pub const XINPUT_MAX_USERS: u32 = 4;
+
+// XINPUT_GAMEPAD button bits
+pub const XINPUT_DPAD_UP: u16 = 0x0001;
+pub const XINPUT_DPAD_DOWN: u16 = 0x0002;
+pub const XINPUT_DPAD_LEFT: u16 = 0x0004;
+pub const XINPUT_DPAD_RIGHT: u16 = 0x0008;
+pub const XINPUT_START: u16 = 0x0010;
+pub const XINPUT_BACK: u16 = 0x0020;
+pub const XINPUT_LEFT_THUMB: u16 = 0x0040;
+pub const XINPUT_LEFT_SHOULDER: u16 = 0x0100;
+pub const XINPUT_A: u16 = 0x1000;
+pub const XINPUT_B: u16 = 0x2000;
+pub const XINPUT_X: u16 = 0x4000;
+pub const XINPUT_Y: u16 = 0x8000;
+
+/// Parse a `ControllerConfig.device_id` into an XInput user slot.
+/// Returns `None` if the id does not select the XInput backend.
+pub fn parse_device_id(device_id: &str) -> Option<u32> {
+    let id = device_id.trim().to_ascii_lowercase();
+    if id == XINPUT_DEVICE_PREFIX {
+        return Some(0);
+    }
+    let slot = id.strip_prefix(XINPUT_DEVICE_PREFIX)?.strip_prefix(':')?;
+    slot.parse::<u32>().ok().filter(|s| *s < XINPUT_MAX_USERS)
+}
+
+/// Decoded XINPUT_GAMEPAD report
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct XInputReport {
+    pub buttons: u16,
+    pub left_trigger: u8,
+    pub right_trigger: u8,
+    pub thumb_rx: i16,
+    pub thumb_ry: i16,
+}
+
+impl XInputReport {
+    fn pressed(&self, mask: u16) -> bool {
+        self.buttons & mask != 0
+    }
+
+    /// Fret states in green, red, yellow, blue, orange order
+    pub fn frets(&self) -> [bool; 5] {
+        [
+            self.pressed(XINPUT_A),
+            self.pressed(XINPUT_B),
+            self.pressed(XINPUT_Y),
+            self.pressed(XINPUT_X),
+            self.pressed(XINPUT_LEFT_SHOULDER),
+        ]
+    }
+
+    /// Strum bar (up, down) - reported on the D-pad by 360 guitars
+    pub fn strum(&self) -> [bool; 2] {
+        [self.pressed(XINPUT_DPAD_UP), self.pressed(XINPUT_DPAD_DOWN)]
+    }
+
+    /// Solo frets set the left thumb bit alongside the fret button
+    pub fn solo(&self) -> bool {
+        self.pressed(XINPUT_LEFT_THUMB)
+    }
+
+    /// Whammy bar normalized to -1.0..1.0 (right stick X)
+    pub fn whammy(&self) -> f32 {
+        (self.thumb_rx as f32 / i16::MAX as f32).clamp(-1.0, 1.0)
+    }
+
+    /// Tilt normalized to -1.0..1.0 (right stick Y)
+    pub fn tilt(&self) -> f32 {
+        (self.thumb_ry as f32 / i16::MAX as f32).clamp(-1.0, 1.0)
+    }
+}
+
+/// 360 guitars report through the standard gamepad layout, so the report is
+/// read like a gilrs gamepad by the shared polling logic
+impl GuitarInput for XInputReport {
+    fn is_pressed(&self, button: Button) -> bool {
+        match button {
+            Button::South => self.pressed(XINPUT_A),
+            Button::East => self.pressed(XINPUT_B),
+            Button::North => self.pressed(XINPUT_Y),
+            Button::West => self.pressed(XINPUT_X),
+            Button::LeftTrigger => self.pressed(XINPUT_LEFT_SHOULDER),
+            Button::LeftThumb => self.pressed(XINPUT_LEFT_THUMB),
+            Button::DPadUp => self.pressed(XINPUT_DPAD_UP),
+            Button::DPadDown => self.pressed(XINPUT_DPAD_DOWN),
+            Button::DPadLeft => self.pressed(XINPUT_DPAD_LEFT),
+            Button::DPadRight => self.pressed(XINPUT_DPAD_RIGHT),
+            Button::Start => self.pressed(XINPUT_START),
+            Button::Select => self.pressed(XINPUT_BACK),
+            _ => false,
+        }
+    }
+
+    fn value(&self, axis: Axis) -> f32 {
+        match axis {
+            Axis::RightStickX => self.whammy(),
+            Axis::RightStickY => self.tilt(),
+            Axis::LeftZ => self.left_trigger as f32 / u8::MAX as f32,
+            Axis::RightZ => self.right_trigger as f32 / u8::MAX as f32,
+            _ => 0.0,
+        }
+    }
+}
+
+#[cfg(windows)]
+#[allow(non_camel_case_types, non_snake_case, dead_code)]
+mod ffi {
+    use super::XInputReport;
+
+    pub const ERROR_SUCCESS: u32 = 0;
+    pub const XINPUT_DEVSUBTYPE_GUITAR: u8 = 0x06;
+    pub const XINPUT_DEVSUBTYPE_GUITAR_ALTERNATE: u8 = 0x07;
+    pub const XINPUT_DEVSUBTYPE_GUITAR_BASS: u8 = 0x0B;
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct XINPUT_GAMEPAD {
+        pub wButtons: u16,
+        pub bLeftTrigger: u8,
+        pub bRightTrigger: u8,
+        pub sThumbLX: i16,
+        pub sThumbLY: i16,
+        pub sThumbRX: i16,
+        pub sThumbRY: i16,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct XINPUT_STATE {
+        pub dwPacketNumber: u32,
+        pub Gamepad: XINPUT_GAMEPAD,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct XINPUT_VIBRATION {
+        pub wLeftMotorSpeed: u16,
+        pub wRightMotorSpeed: u16,
+    }
+
+    #[repr(C)]
+    #[derive(Default)]
+    pub struct XINPUT_CAPABILITIES {
+        pub Type: u8,
+        pub SubType: u8,
+        pub Flags: u16,
+        pub Gamepad: XINPUT_GAMEPAD,
+        pub Vibration: XINPUT_VIBRATION,
+    }
+
+    #[link(name = "xinput")]
+    extern "system" {
+        fn XInputGetState(dwUserIndex: u32, pState: *mut XINPUT_STATE) -> u32;
+        fn XInputGetCapabilities(dwUserIndex: u32, dwFlags: u32, pCapabilities: *mut XINPUT_CAPABILITIES) -> u32;
+    }
+
+    /// Read the current report for a user slot, `None` if nothing is connected
+    pub fn read_state(user_index: u32) -> Option<(u32, XInputReport)> {
+        let mut state = XINPUT_STATE::default();
+        // SAFETY: `state` is a valid, properly laid out XINPUT_STATE
+        let result = unsafe { XInputGetState(user_index, &mut state) };
+        if result != ERROR_SUCCESS {
+            return None;
+        }
+        let pad = &state.Gamepad;
+        Some((state.dwPacketNumber, XInputReport {
+            buttons: pad.wButtons,
+            left_trigger: pad.bLeftTrigger,
+            right_trigger: pad.bRightTrigger,
+            thumb_rx: pad.sThumbRX,
+            thumb_ry: pad.sThumbRY,
+        }))
+    }
+
+    /// Whether the device in a user slot reports a guitar subtype
+    pub fn is_guitar(user_index: u32) -> bool {
+        let mut caps = XINPUT_CAPABILITIES::default();
+        // SAFETY: `caps` is a valid, properly laid out XINPUT_CAPABILITIES
+        let result = unsafe { XInputGetCapabilities(user_index, 0, &mut caps) };
+        result == ERROR_SUCCESS
+            && matches!(
+                caps.SubType,
+                XINPUT_DEVSUBTYPE_GUITAR | XINPUT_DEVSUBTYPE_GUITAR_ALTERNATE | XINPUT_DEVSUBTYPE_GUITAR_BASS
+            )
+    }
+}
+
+/// Device metadata for an XInput user slot
+#[cfg(windows)]
+fn device_info(user_index: u32, kind: &str) -> ControllerDeviceInfo {
+    ControllerDeviceInfo {
+        device_id: format!("{}:{}", XINPUT_DEVICE_PREFIX, user_index),
+        name: format!("XInput {} (slot {})", kind, user_index),
+        vendor_id: None,
+        product_id: None,
+        backend: XINPUT_DEVICE_PREFIX.to_string(),
+    }
+}
+
+/// Spawn the XInput polling thread (1000Hz) for the given user slot
+#[cfg(windows)]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn spawn_polling_thread(
+    user_index: u32,
+    state: Arc<AtomicControllerState>,
+    audio_callback: Option<Arc<dyn AudioCallback>>,
+    should_stop: Arc<AtomicBool>,
+    events: Arc<ControllerEventBus>,
+    hardware_tester: Arc<HardwareTester>,
+    profile_bindings: Arc<Mutex<ProfileBindings>>,
+    whammy_calibrator: Arc<WhammyCalibrator>,
+    raw_diagnostics: Arc<RawDiagnostics>,
+) -> Result<thread::JoinHandle<()>> {
+    if user_index >= XINPUT_MAX_USERS {
+        anyhow::bail!("Invalid XInput user index: {}", user_index);
+    }
+
+    let thread = thread::spawn(move || {
+        log::info!("🚀 XInput polling thread started (slot {}, 1000Hz)", user_index);
+
+        let mut prev_frets = [false; 5];
+        let mut prev_strum = [false; 2];
+        let mut hat_strum = HatStrumDecoder::new();
+
+        while !should_stop.load(Ordering::Relaxed) {
+            let start_time = Instant::now();
+
+            match ffi::read_state(user_index) {
+                Some((_, report)) => {
+                    if !state.connected.load(Ordering::Relaxed) {
+                        let kind = if ffi::is_guitar(user_index) { "guitar" } else { "gamepad" };
+                        log::info!("🎮 XInput {} connected (slot {})", kind, user_index);
+                        state.connected.store(true, Ordering::Relaxed);
+                        events.publish(ControllerEvent::Connected {
+                            device: device_info(user_index, kind),
+                            player_index: Some(0),
+                        });
+                    }
+
+                    // Poll every tick (not only on new packets) so debounce windows expire
+                    let (frets, strum) = {
+                        let mut bindings = profile_bindings.lock().unwrap();
+                        poll_gamepad(&report, &state, &mut bindings, &mut hat_strum, &whammy_calibrator, &raw_diagnostics, 0)
+                    };
+                    if let Some(ref callback) = audio_callback {
+                        dispatch_edges(callback.as_ref(), &frets, &prev_frets, &strum, &prev_strum);
+                    }
+                    prev_frets = frets;
+                    prev_strum = strum;
+                    hardware_tester.record(&state.snapshot(0), Some(report.tilt()));
+                }
+                None => {
+                    if state.connected.load(Ordering::Relaxed) {
+                        log::info!("🎮 XInput device disconnected (slot {})", user_index);
+                        state.disconnect();
+                        events.publish(ControllerEvent::Disconnected {
+                            device: device_info(user_index, "device"),
+                            player_index: Some(0),
+                        });
+                        if let Some(ref callback) = audio_callback {
+                            dispatch_edges(callback.as_ref(), &[false; 5], &prev_frets, &[false; 2], &prev_strum);
+                        }
+                        prev_frets = [false; 5];
+                        prev_strum = [false; 2];
+                        hat_strum = HatStrumDecoder::new();
+                        let mut bindings = profile_bindings.lock().unwrap();
+                        bindings.debounce[0].iter_mut().for_each(DebounceFilter::reset);
+                        bindings.whammy_filter[0].reset();
+                    }
+                }
+            }
+
+            let elapsed = start_time.elapsed();
+            if elapsed < Duration::from_millis(1) {
+                thread::sleep(Duration::from_millis(1) - elapsed);
+            }
+        }
+
+        log::info!("🛑 XInput polling thread stopped");
+    });
+
+    Ok(thread)
+}
+
+/// XInput is only available on Windows
+#[cfg(not(windows))]
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn spawn_polling_thread(
+    _user_index: u32,
+    _state: Arc<AtomicControllerState>,
+    _audio_callback: Option<Arc<dyn AudioCallback>>,
+    _should_stop: Arc<AtomicBool>,
+    _events: Arc<ControllerEventBus>,
+    _hardware_tester: Arc<HardwareTester>,
+    _profile_bindings: Arc<Mutex<ProfileBindings>>,
+    _whammy_calibrator: Arc<WhammyCalibrator>,
+    _raw_diagnostics: Arc<RawDiagnostics>,
+) -> Result<thread::JoinHandle<()>> {
+    anyhow::bail!("XInput backend is only available on Windows")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::Ordering;
+    use crate::high_performance::poll_gamepad;
+    use crate::hat_strum::HatStrumDecoder;
+
+    #[test]
+    fn test_parse_device_id() {
+        assert_eq!(parse_device_id("xinput"), Some(0));
+        assert_eq!(parse_device_id("XInput:2"), Some(2));
+        assert_eq!(parse_device_id("xinput:4"), None);
+        assert_eq!(parse_device_id("auto"), None);
+        assert_eq!(parse_device_id("xinputfoo"), None);
+    }
+
+    #[test]
+    fn test_report_decoding() {
+        let report = XInputReport {
+            buttons: XINPUT_A | XINPUT_Y | XINPUT_LEFT_SHOULDER | XINPUT_DPAD_DOWN,
+            thumb_rx: i16::MAX,
+            ..Default::default()
+        };
+        assert_eq!(report.frets(), [true, false, true, false, true]);
+        assert_eq!(report.strum(), [false, true]);
+        assert_eq!(report.whammy(), 1.0);
+        assert_eq!(XInputReport { thumb_rx: i16::MIN, ..Default::default() }.whammy(), -1.0);
+    }
+
+    #[test]
+    fn test_report_through_polling() {
+        let state = AtomicControllerState::new();
+        let report = XInputReport {
+            buttons: XINPUT_B | XINPUT_DPAD_UP | XINPUT_START,
+            thumb_ry: i16::MAX,
+            ..Default::default()
+        };
+        let (frets, strum) = poll_gamepad(&report, &state, &mut ProfileBindings::default(), &mut HatStrumDecoder::new(), &WhammyCalibrator::new(), &RawDiagnostics::new(), 0);
+        assert_eq!(frets, [false, true, false, false, false]);
+        assert_eq!(strum, [true, false]);
+        assert!(state.fret_red.load(Ordering::Relaxed));
+        assert!(state.strum_up.load(Ordering::Relaxed));
+        assert!(!state.dpad_up.load(Ordering::Relaxed));
+        assert!(state.start.load(Ordering::Relaxed));
+        assert_eq!(state.get_tilt(), 1.0);
+    }
+}
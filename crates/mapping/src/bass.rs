@@ -0,0 +1,142 @@
+//! Bass mode
+//! Turns the guitar into a bass for a second player: each main fret plays the
+//! root of the chord the genre preset puts on it, as a single note an octave
+//! under the guitar chords. Strumming down plays the low octave, strumming up
+//! the one above. The note rings while its fret is held.
+
+use std::collections::HashMap;
+
+use controller::{ControlId, ControllerState};
+
+use crate::harmonic::{ChordSpec, FretButton};
+use crate::MusicEvent;
+
+/// Octave passed to `Note::to_midi` for strum-down roots (A1 for A, an octave under the chords)
+const BASS_OCTAVE: i8 = -2;
+
+/// Velocity of bass notes
+const BASS_VELOCITY: u8 = 110;
+
+/// Main frets, highest first (the highest held fret plays, like single notes in rhythm games)
+const FRETS: [(ControlId, FretButton); 5] = [
+    (ControlId::FretOrange, FretButton::Orange),
+    (ControlId::FretBlue, FretButton::Blue),
+    (ControlId::FretYellow, FretButton::Yellow),
+    (ControlId::FretRed, FretButton::Red),
+    (ControlId::FretGreen, FretButton::Green),
+];
+
+/// Single-note bass lines from the fret chords
+#[derive(Debug, Clone, Default)]
+pub struct BassMapper {
+    /// Note sounding and the fret that plays it
+    active: Option<(u8, ControlId)>,
+    strum_up: bool,
+    strum_down: bool,
+}
+
+impl BassMapper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Bass note for a chord (`up` = the octave above)
+    pub fn note_for(chord: &ChordSpec, up: bool) -> u8 {
+        chord.root.to_midi(BASS_OCTAVE) + if up { 12 } else { 0 }
+    }
+
+    /// Process controller state against the fret chords of the main row
+    pub fn process(&mut self, state: &ControllerState, chords: &HashMap<FretButton, ChordSpec>) -> Vec<MusicEvent> {
+        let mut events = Vec::new();
+        let held = |control: ControlId| state.buttons.get(&control).copied().unwrap_or(false);
+
+        let (up, down) = (held(ControlId::StrumUp), held(ControlId::StrumDown));
+        let strummed_up = up && !self.strum_up;
+        let strummed_down = down && !self.strum_down;
+        self.strum_up = up;
+        self.strum_down = down;
+
+        // Lifting the fret ends the note
+        if self.active.is_some_and(|(_, control)| !held(control)) {
+            self.release(&mut events);
+        }
+
+        if strummed_up || strummed_down {
+            let fret = FRETS.iter().find(|(control, _)| held(*control));
+            if let Some((control, chord)) = fret.and_then(|(control, button)| chords.get(button).map(|chord| (*control, chord))) {
+                self.release(&mut events);
+                let note = Self::note_for(chord, strummed_up && !strummed_down);
+                events.push(MusicEvent::NoteOn { note, velocity: BASS_VELOCITY });
+                self.active = Some((note, control));
+            }
+        }
+        events
+    }
+
+    fn release(&mut self, events: &mut Vec<MusicEvent>) {
+        if let Some((note, _)) = self.active.take() {
+            events.push(MusicEvent::NoteOff { note });
+        }
+    }
+
+    /// Stop the sounding note
+    pub fn panic(&mut self) -> Vec<MusicEvent> {
+        let mut events = Vec::new();
+        self.release(&mut events);
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::harmonic::{ChordQuality, Note};
+
+    fn chords() -> HashMap<FretButton, ChordSpec> {
+        HashMap::from([
+            (FretButton::Green, ChordSpec::new(Note::A, ChordQuality::Major)),
+            (FretButton::Red, ChordSpec::new(Note::D, ChordQuality::Major)),
+        ])
+    }
+
+    fn input(frets: &[ControlId], up: bool, down: bool) -> ControllerState {
+        let mut state = ControllerState::default();
+        for &fret in frets {
+            state.buttons.insert(fret, true);
+        }
+        state.buttons.insert(ControlId::StrumUp, up);
+        state.buttons.insert(ControlId::StrumDown, down);
+        state
+    }
+
+    #[test]
+    fn test_frets_play_single_roots_with_strum_octave() {
+        let mut bass = BassMapper::new();
+        let chords = chords();
+
+        // Strum down: A1
+        let events = bass.process(&input(&[ControlId::FretGreen], false, true), &chords);
+        assert!(matches!(events[..], [MusicEvent::NoteOn { note: 33, .. }]));
+        // Holding the strum doesn't retrigger
+        assert!(bass.process(&input(&[ControlId::FretGreen], false, true), &chords).is_empty());
+
+        // The highest held fret plays; strum up is the octave above (D2 rather than D1)
+        let events = bass.process(&input(&[ControlId::FretGreen, ControlId::FretRed], true, false), &chords);
+        assert!(matches!(events[..], [MusicEvent::NoteOff { note: 33 }, MusicEvent::NoteOn { note: 38, .. }]));
+    }
+
+    #[test]
+    fn test_note_ends_with_its_fret() {
+        let mut bass = BassMapper::new();
+        let chords = chords();
+        bass.process(&input(&[ControlId::FretRed], false, true), &chords);
+        // Still held after the strum
+        assert!(bass.process(&input(&[ControlId::FretRed], false, false), &chords).is_empty());
+        let events = bass.process(&input(&[], false, false), &chords);
+        assert!(matches!(events[..], [MusicEvent::NoteOff { note: 26 }]));
+        // No fret, no note; a strum on a fret without a chord does nothing either
+        assert!(bass.process(&input(&[], false, true), &chords).is_empty());
+        assert!(bass.process(&input(&[ControlId::FretOrange], true, false), &chords).is_empty());
+        assert!(bass.panic().is_empty());
+    }
+}
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use controller::ControlId;
 
+use crate::guitar_voicing::{voice_guitar_chord, GuitarVoicing};
+
 /// Chord quality/type
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ChordQuality {
@@ -16,6 +18,26 @@ pub enum ChordQuality {
     Augmented,
 }
 
+impl ChordQuality {
+    /// Chord symbol suffix ("" for major, "m", "7", ...)
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            ChordQuality::Major => "",
+            ChordQuality::Minor => "m",
+            ChordQuality::Power => "5",
+            ChordQuality::Major7 => "maj7",
+            ChordQuality::Minor7 => "m7",
+            ChordQuality::Dominant7 => "7",
+            ChordQuality::Sus2 => "sus2",
+            ChordQuality::Sus4 => "sus4",
+            ChordQuality::Diminished => "dim",
+            ChordQuality::Augmented => "aug",
+        }
+    }
+}
+
+const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
 /// A musical chord
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Chord {
@@ -50,6 +72,18 @@ impl Chord {
         intervals.iter().map(|&interval| root + interval).collect()
     }
 
+    /// Chord symbol (e.g. "Am") with the root relative to `base_note`
+    pub fn name(&self, base_note: u8) -> String {
+        let root = (base_note as i16 + self.root as i16).rem_euclid(12) as usize;
+        format!("{}{}", NOTE_NAMES[root], self.quality.suffix())
+    }
+
+    /// Six-string guitar voicing of this chord (root relative to `base_note`)
+    pub fn guitar_voicing(&self, base_note: u8) -> GuitarVoicing {
+        let root = (base_note as i16 + self.root as i16).rem_euclid(12) as u8;
+        voice_guitar_chord(root, &self.get_intervals())
+    }
+
     /// Get the intervals for this chord quality
     fn get_intervals(&self) -> Vec<u8> {
         match self.quality {
@@ -0,0 +1,152 @@
+//! Strum dynamics
+//! Chords used to be struck at a constant velocity of 100. A `DynamicsMode`
+//! turns how the strum was played into its velocity instead: the whammy
+//! position at the moment of the strum (at rest = full, pushed down = softer)
+//! or how quickly it followed the previous strum (fast strumming digs in
+//! harder). Controllers that report a strum velocity of their own (MIDI
+//! guitars, replayed input) keep it in every mode.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use controller::{ControlId, ControllerState};
+
+/// Velocity of a strum without dynamics
+pub const DEFAULT_VELOCITY: u8 = 100;
+
+/// Softest velocity the dynamics play
+pub const MIN_DYNAMICS_VELOCITY: u8 = 40;
+
+/// Strums this close together play at full velocity
+const FAST_STRUM: Duration = Duration::from_millis(100);
+
+/// Strums this far apart play at `MIN_DYNAMICS_VELOCITY`
+const SLOW_STRUM: Duration = Duration::from_millis(800);
+
+/// A strum after a longer pause starts a new phrase at `DEFAULT_VELOCITY`
+const PHRASE_GAP: Duration = Duration::from_millis(1500);
+
+/// What sets the velocity of a strum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DynamicsMode {
+    /// Every strum at `DEFAULT_VELOCITY`
+    #[default]
+    Fixed,
+    /// Whammy position at the strum (at rest = loudest)
+    Whammy,
+    /// Time since the previous strum (faster = louder)
+    StrumSpeed,
+}
+
+impl DynamicsMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "fixed" => Some(Self::Fixed),
+            "whammy" => Some(Self::Whammy),
+            "strum_speed" => Some(Self::StrumSpeed),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Fixed => "fixed",
+            Self::Whammy => "whammy",
+            Self::StrumSpeed => "strum_speed",
+        }
+    }
+}
+
+/// Picks strum velocities for one player
+#[derive(Debug, Clone, Default)]
+pub struct Dynamics {
+    mode: DynamicsMode,
+    /// When the previous strum happened (for `StrumSpeed`)
+    last_strum: Option<Instant>,
+}
+
+impl Dynamics {
+    pub fn new(mode: DynamicsMode) -> Self {
+        Self { mode, last_strum: None }
+    }
+
+    pub fn mode(&self) -> DynamicsMode {
+        self.mode
+    }
+
+    pub fn set_mode(&mut self, mode: DynamicsMode) {
+        self.mode = mode;
+        self.last_strum = None;
+    }
+
+    /// Velocity of a strum happening now (call once per strum)
+    pub fn strum_velocity(&mut self, state: &ControllerState) -> u8 {
+        self.velocity_at(state, Instant::now())
+    }
+
+    fn velocity_at(&mut self, state: &ControllerState, now: Instant) -> u8 {
+        let previous = self.last_strum.replace(now);
+        let velocity = match self.mode {
+            DynamicsMode::Fixed => DEFAULT_VELOCITY,
+            DynamicsMode::Whammy => scale(state.axis(ControlId::WhammyBar)),
+            DynamicsMode::StrumSpeed => match previous.map(|previous| now.saturating_duration_since(previous)) {
+                Some(gap) if gap < PHRASE_GAP => {
+                    let slowness = (gap.saturating_sub(FAST_STRUM)).as_secs_f32() / (SLOW_STRUM - FAST_STRUM).as_secs_f32();
+                    scale(slowness)
+                }
+                _ => DEFAULT_VELOCITY,
+            },
+        };
+        state.strum_velocity(velocity)
+    }
+}
+
+/// Velocity from full (0.0) down to `MIN_DYNAMICS_VELOCITY` (1.0)
+fn scale(softness: f32) -> u8 {
+    let softness = softness.clamp(0.0, 1.0);
+    (127.0 - softness * (127 - MIN_DYNAMICS_VELOCITY) as f32).round() as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_whammy_dynamics() {
+        let mut dynamics = Dynamics::new(DynamicsMode::Whammy);
+        let mut state = ControllerState::default();
+        let now = Instant::now();
+        assert_eq!(dynamics.velocity_at(&state, now), 127);
+        state.set_axis(ControlId::WhammyBar, 0.5);
+        assert_eq!(dynamics.velocity_at(&state, now), 84);
+        state.set_axis(ControlId::WhammyBar, 1.0);
+        assert_eq!(dynamics.velocity_at(&state, now), MIN_DYNAMICS_VELOCITY);
+
+        // A velocity from the controller wins
+        state.set_axis(ControlId::StrumVelocity, 1.0);
+        assert_eq!(dynamics.velocity_at(&state, now), 127);
+
+        dynamics.set_mode(DynamicsMode::Fixed);
+        state.set_axis(ControlId::StrumVelocity, 0.0);
+        assert_eq!(dynamics.velocity_at(&state, now), DEFAULT_VELOCITY);
+        assert_eq!(DynamicsMode::from_name("strum_speed"), Some(DynamicsMode::StrumSpeed));
+    }
+
+    #[test]
+    fn test_strum_speed_dynamics() {
+        let mut dynamics = Dynamics::new(DynamicsMode::StrumSpeed);
+        let state = ControllerState::default();
+        let start = Instant::now();
+        let at = |ms| start + Duration::from_millis(ms);
+        // The first strum has nothing to compare with
+        assert_eq!(dynamics.velocity_at(&state, at(0)), DEFAULT_VELOCITY);
+        assert_eq!(dynamics.velocity_at(&state, at(80)), 127);
+        assert_eq!(dynamics.velocity_at(&state, at(880)), MIN_DYNAMICS_VELOCITY);
+        let velocity = dynamics.velocity_at(&state, at(1330));
+        assert!(velocity > MIN_DYNAMICS_VELOCITY && velocity < 127);
+        // After a pause the phrase starts over
+        assert_eq!(dynamics.velocity_at(&state, at(5000)), DEFAULT_VELOCITY);
+    }
+}
@@ -31,6 +31,11 @@ impl Genre {
         }
     }
 
+    /// Whether voice leading is on by default (chordal genres played on pads/keys)
+    pub fn voice_leading_default(&self) -> bool {
+        matches!(self, Genre::Edm | Genre::Folk | Genre::Pop)
+    }
+
     /// Get chord patterns for this genre
     pub fn get_patterns(&self) -> Vec<ChordPattern> {
         match self {
@@ -261,6 +266,19 @@ impl Genre {
     }
 }
 
+impl From<Genre> for crate::harmonic::Genre {
+    fn from(genre: Genre) -> Self {
+        match genre {
+            Genre::Punk => Self::Punk,
+            Genre::Rock => Self::Rock,
+            Genre::Edm => Self::Edm,
+            Genre::Metal => Self::Metal,
+            Genre::Folk => Self::Folk,
+            Genre::Pop => Self::Pop,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,270 @@
+//! Six-string guitar voicings
+//! Maps a chord to the notes a guitarist would actually fret: open-position
+//! shapes where they exist, otherwise E- or A-shape barre chords, listed
+//! string by string so a strum can sweep across them with a small delay.
+
+use serde::{Deserialize, Serialize};
+
+/// Standard tuning, low E to high e
+pub const STANDARD_TUNING: [u8; 6] = [40, 45, 50, 55, 59, 64];
+/// Default delay between adjacent strings in a strum
+pub const DEFAULT_STRUM_SPREAD_MS: f32 = 8.0;
+/// Shortest delay between notes when strum stagger is on
+pub const MIN_STRUM_STAGGER_MS: f32 = 5.0;
+/// Longest per-string delay accepted (slower sounds like an arpeggio)
+pub const MAX_STRUM_SPREAD_MS: f32 = 20.0;
+
+/// Frets per string, low E to high e (None = muted)
+type Frets = [Option<u8>; 6];
+
+const X: Option<u8> = None;
+const fn f(fret: u8) -> Option<u8> {
+    Some(fret)
+}
+
+// Chord tones as pitch-class sets (sorted semitones from the root)
+const MAJOR: &[u8] = &[0, 4, 7];
+const MINOR: &[u8] = &[0, 3, 7];
+const DOM7: &[u8] = &[0, 4, 7, 10];
+const MIN7: &[u8] = &[0, 3, 7, 10];
+const MAJ7: &[u8] = &[0, 4, 7, 11];
+const SUS2: &[u8] = &[0, 2, 7];
+const SUS4: &[u8] = &[0, 5, 7];
+const ADD9: &[u8] = &[0, 2, 4, 7];
+const POWER: &[u8] = &[0, 7];
+const DIM: &[u8] = &[0, 3, 6];
+const AUG: &[u8] = &[0, 4, 8];
+
+/// Movable shapes relative to the barre fret: (chord tones, E-shape, A-shape)
+const MOVABLE_SHAPES: &[(&[u8], Frets, Frets)] = &[
+    (MAJOR, [f(0), f(2), f(2), f(1), f(0), f(0)], [X, f(0), f(2), f(2), f(2), f(0)]),
+    (MINOR, [f(0), f(2), f(2), f(0), f(0), f(0)], [X, f(0), f(2), f(2), f(1), f(0)]),
+    (DOM7, [f(0), f(2), f(0), f(1), f(0), f(0)], [X, f(0), f(2), f(0), f(2), f(0)]),
+    (MIN7, [f(0), f(2), f(0), f(0), f(0), f(0)], [X, f(0), f(2), f(0), f(1), f(0)]),
+    (MAJ7, [f(0), f(2), f(1), f(1), f(0), f(0)], [X, f(0), f(2), f(1), f(2), f(0)]),
+    (SUS2, [f(0), f(2), f(4), f(4), f(0), f(0)], [X, f(0), f(2), f(2), f(0), f(0)]),
+    (SUS4, [f(0), f(2), f(2), f(2), f(0), f(0)], [X, f(0), f(2), f(2), f(3), f(0)]),
+    (ADD9, [f(0), f(2), f(2), f(1), f(0), f(2)], [X, f(0), f(2), f(4), f(2), f(0)]),
+    (POWER, [f(0), f(2), f(2), X, X, X], [X, f(0), f(2), f(2), X, X]),
+    (DIM, [f(0), f(1), f(2), f(0), X, X], [X, f(0), f(1), f(2), f(1), X]),
+    (AUG, [f(0), f(3), f(2), f(1), f(1), f(0)], [X, f(0), f(3), f(2), f(2), f(1)]),
+];
+
+/// Open-position shapes that aren't just an E or A shape at fret 0: (root, chord tones, frets)
+const OPEN_SHAPES: &[(u8, &[u8], Frets)] = &[
+    (0, MAJOR, [X, f(3), f(2), f(0), f(1), f(0)]),
+    (0, DOM7, [X, f(3), f(2), f(3), f(1), f(0)]),
+    (0, MAJ7, [X, f(3), f(2), f(0), f(0), f(0)]),
+    (0, ADD9, [X, f(3), f(2), f(0), f(3), f(0)]),
+    (2, MAJOR, [X, X, f(0), f(2), f(3), f(2)]),
+    (2, MINOR, [X, X, f(0), f(2), f(3), f(1)]),
+    (2, DOM7, [X, X, f(0), f(2), f(1), f(2)]),
+    (2, MIN7, [X, X, f(0), f(2), f(1), f(1)]),
+    (2, MAJ7, [X, X, f(0), f(2), f(2), f(2)]),
+    (2, SUS2, [X, X, f(0), f(2), f(3), f(0)]),
+    (2, SUS4, [X, X, f(0), f(2), f(3), f(3)]),
+    (7, MAJOR, [f(3), f(2), f(0), f(0), f(0), f(3)]),
+    (7, DOM7, [f(3), f(2), f(0), f(0), f(0), f(1)]),
+    (11, DOM7, [X, f(2), f(1), f(2), f(0), f(2)]),
+];
+
+/// Which fingering a voicing uses
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ShapeKind {
+    /// Open-position chord (uses open strings)
+    Open,
+    /// Barre with the root on the low E string
+    EShape,
+    /// Barre with the root on the A string
+    AShape,
+    /// No known shape; chord tones picked string by string
+    Generic,
+}
+
+/// Strum direction (down = low E first)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StrumDirection {
+    #[default]
+    Down,
+    Up,
+}
+
+/// One sounding string
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StringNote {
+    /// String index, 0 = low E
+    pub string: u8,
+    pub fret: u8,
+    pub note: u8,
+}
+
+/// A chord as played on a six-string guitar
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GuitarVoicing {
+    pub shape: ShapeKind,
+    /// Barre fret (0 for open shapes)
+    pub base_fret: u8,
+    /// Sounding strings, low to high
+    pub strings: Vec<StringNote>,
+}
+
+impl GuitarVoicing {
+    fn from_frets(shape: ShapeKind, base_fret: u8, frets: &Frets) -> Self {
+        let strings = frets
+            .iter()
+            .enumerate()
+            .filter_map(|(string, fret)| {
+                fret.map(|fret| StringNote {
+                    string: string as u8,
+                    fret,
+                    note: STANDARD_TUNING[string] + fret,
+                })
+            })
+            .collect();
+        Self { shape, base_fret, strings }
+    }
+
+    /// MIDI notes, low to high
+    pub fn notes(&self) -> Vec<u8> {
+        self.strings.iter().map(|s| s.note).collect()
+    }
+
+    /// Move every note by whole octaves (for chords voiced outside guitar range)
+    pub fn shift_octaves(&mut self, octaves: i8) {
+        for string in &mut self.strings {
+            string.note = (string.note as i16 + octaves as i16 * 12).clamp(0, 127) as u8;
+        }
+    }
+
+    /// Notes in strum order with the delay (ms) before each one sounds
+    pub fn strum(&self, direction: StrumDirection, spread_ms: f32) -> Vec<(u8, f32)> {
+        strum_order(&self.notes(), direction, spread_ms)
+    }
+}
+
+/// Order chord notes for a strum (down = low to high) with the delay (ms)
+/// before each one sounds; works for any chord, not just guitar shapes
+pub fn strum_order(notes: &[u8], direction: StrumDirection, spread_ms: f32) -> Vec<(u8, f32)> {
+    let spread_ms = spread_ms.clamp(0.0, MAX_STRUM_SPREAD_MS);
+    let mut notes = notes.to_vec();
+    notes.sort_unstable();
+    if direction == StrumDirection::Up {
+        notes.reverse();
+    }
+    notes
+        .into_iter()
+        .enumerate()
+        .map(|(i, note)| (note, i as f32 * spread_ms))
+        .collect()
+}
+
+/// Reduce intervals to a sorted pitch-class set
+fn pitch_classes(intervals: &[u8]) -> Vec<u8> {
+    let mut classes: Vec<u8> = intervals.iter().map(|i| i % 12).collect();
+    classes.sort_unstable();
+    classes.dedup();
+    classes
+}
+
+/// Voice a chord (root pitch class 0-11 = C-B, intervals in semitones) for guitar
+pub fn voice_guitar_chord(root: u8, intervals: &[u8]) -> GuitarVoicing {
+    let root = root % 12;
+    let classes = pitch_classes(intervals);
+
+    if let Some((_, _, frets)) = OPEN_SHAPES.iter().find(|(r, tones, _)| *r == root && *tones == classes.as_slice()) {
+        return GuitarVoicing::from_frets(ShapeKind::Open, 0, frets);
+    }
+
+    let Some((_, e_shape, a_shape)) = MOVABLE_SHAPES.iter().find(|(tones, _, _)| *tones == classes.as_slice()) else {
+        return generic_voicing(root, &classes);
+    };
+
+    // Root fret on the low E and A strings
+    let e_fret = (root + 12 - 4) % 12;
+    let a_fret = (root + 12 - 9) % 12;
+    let (shape, base_fret, template) = if e_fret <= a_fret {
+        (ShapeKind::EShape, e_fret, e_shape)
+    } else {
+        (ShapeKind::AShape, a_fret, a_shape)
+    };
+    let frets = template.map(|fret| fret.map(|fret| fret + base_fret));
+    let shape = if base_fret == 0 { ShapeKind::Open } else { shape };
+    GuitarVoicing::from_frets(shape, base_fret, &frets)
+}
+
+/// Root on the low E or A string (whichever is lower on the neck), then the
+/// lowest chord tone within a four-fret box on each higher string
+fn generic_voicing(root: u8, classes: &[u8]) -> GuitarVoicing {
+    let (root_string, base_fret) = (0..2)
+        .map(|string| (string, (root + 12 - STANDARD_TUNING[string] % 12) % 12))
+        .min_by_key(|(_, fret)| *fret)
+        .unwrap_or((0, 0));
+
+    let mut frets: Frets = [None; 6];
+    frets[root_string] = Some(base_fret);
+    for (string, open) in STANDARD_TUNING.iter().enumerate().skip(root_string + 1) {
+        frets[string] = (base_fret..=base_fret + 4).find(|fret| {
+            let interval = (open + fret + 12 - root % 12) % 12;
+            classes.contains(&interval)
+        });
+    }
+    GuitarVoicing::from_frets(ShapeKind::Generic, base_fret, &frets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_open_and_barre_shapes() {
+        // Open C
+        let c = voice_guitar_chord(0, MAJOR);
+        assert_eq!(c.shape, ShapeKind::Open);
+        assert_eq!(c.notes(), vec![48, 52, 55, 60, 64]);
+
+        // E minor is the E shape at fret 0, so it's open
+        let em = voice_guitar_chord(4, MINOR);
+        assert_eq!(em.shape, ShapeKind::Open);
+        assert_eq!(em.notes(), vec![40, 47, 52, 55, 59, 64]);
+
+        // F is a first-fret E-shape barre
+        let f_major = voice_guitar_chord(5, MAJOR);
+        assert_eq!(f_major.shape, ShapeKind::EShape);
+        assert_eq!(f_major.base_fret, 1);
+        assert_eq!(f_major.notes(), vec![41, 48, 53, 57, 60, 65]);
+
+        // Bm is a second-fret A-shape barre with the low E muted
+        let bm = voice_guitar_chord(11, MINOR);
+        assert_eq!(bm.shape, ShapeKind::AShape);
+        assert_eq!(bm.strings.len(), 5);
+        assert_eq!(bm.strings[0], StringNote { string: 1, fret: 2, note: 47 });
+    }
+
+    #[test]
+    fn test_every_shape_plays_only_chord_tones() {
+        for root in 0..12u8 {
+            for (tones, _, _) in MOVABLE_SHAPES {
+                let voicing = voice_guitar_chord(root, tones);
+                assert!(voicing.strings.len() >= 3, "{} {:?}", root, tones);
+                assert_eq!(voicing.notes()[0] % 12, root, "bass note should be the root");
+                for note in voicing.notes() {
+                    assert!(tones.contains(&((note + 12 - root) % 12)), "{} {:?} has {}", root, tones, note);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_strum_order_and_delay() {
+        let g = voice_guitar_chord(7, MAJOR);
+        let down = g.strum(StrumDirection::Down, 10.0);
+        assert_eq!(down.first(), Some(&(43, 0.0)));
+        assert_eq!(down.last(), Some(&(67, 50.0)));
+
+        let up = g.strum(StrumDirection::Up, 100.0);
+        assert_eq!(up[0].0, 67);
+        assert_eq!(up[1].1, MAX_STRUM_SPREAD_MS);
+    }
+}
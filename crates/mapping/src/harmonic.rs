@@ -79,6 +79,16 @@ pub enum Mode {
     Minor,
 }
 
+impl Mode {
+    /// Scale degrees in semitones from the tonic (natural minor for Minor)
+    pub fn scale(&self) -> [u8; 7] {
+        match self {
+            Mode::Major => [0, 2, 4, 5, 7, 9, 11],
+            Mode::Minor => [0, 2, 3, 5, 7, 8, 10],
+        }
+    }
+}
+
 /// Chord qualities/types
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ChordQuality {
@@ -94,9 +104,80 @@ pub enum ChordQuality {
     Sus4,        // Suspended 4th
     #[serde(rename = "add9")]
     Add9,        // Add 9th
+    #[serde(rename = "dim")]
+    Diminished,  // Diminished triad
+    #[serde(rename = "aug")]
+    Augmented,   // Augmented triad
+    #[serde(rename = "maj7")]
+    Major7,      // Major seventh
+    #[serde(rename = "min7")]
+    Minor7,      // Minor seventh
+    #[serde(rename = "dom7")]
+    Dominant7,   // Dominant seventh
+    #[serde(rename = "maj9")]
+    Major9,      // Major ninth
+    #[serde(rename = "min9")]
+    Minor9,      // Minor ninth
+    #[serde(rename = "dom9")]
+    Dominant9,   // Dominant ninth
 }
 
 impl ChordQuality {
+    /// This quality corrected to the scale of `mode`, for a chord rooted
+    /// `interval` semitones above the tonic: triads, sevenths and ninths take
+    /// the third, fifth and seventh the scale gives that degree (ii is minor,
+    /// vii is diminished, V7 is dominant). Power and sus chords keep their
+    /// color unless the fifth is flat. None when the root isn't in the scale
+    /// (borrowed chords like bVII in major keep their quality).
+    pub fn diatonic(self, interval: u8, mode: Mode) -> Option<ChordQuality> {
+        let scale = mode.scale();
+        let degree = scale.iter().position(|&step| step == interval % 12)?;
+        let above = |steps: usize| (scale[(degree + steps) % 7] + 12 - scale[degree]) % 12;
+        let (third, fifth, seventh) = (above(2), above(4), above(6));
+        if fifth == 6 {
+            return Some(ChordQuality::Diminished);
+        }
+        let major = third == 4;
+        let quality = match self {
+            ChordQuality::Power5 | ChordQuality::Sus2 | ChordQuality::Sus4 => self,
+            ChordQuality::Major | ChordQuality::Minor | ChordQuality::Diminished | ChordQuality::Augmented => {
+                if major { ChordQuality::Major } else { ChordQuality::Minor }
+            }
+            ChordQuality::Add9 => if major { ChordQuality::Add9 } else { ChordQuality::Minor },
+            ChordQuality::Major7 | ChordQuality::Minor7 | ChordQuality::Dominant7 => match (major, seventh) {
+                (true, 11) => ChordQuality::Major7,
+                (true, _) => ChordQuality::Dominant7,
+                (false, _) => ChordQuality::Minor7,
+            },
+            ChordQuality::Major9 | ChordQuality::Minor9 | ChordQuality::Dominant9 => match (major, seventh) {
+                (true, 11) => ChordQuality::Major9,
+                (true, _) => ChordQuality::Dominant9,
+                (false, _) => ChordQuality::Minor9,
+            },
+        };
+        Some(quality)
+    }
+
+    /// All qualities, in menu order
+    pub fn all() -> &'static [ChordQuality] {
+        &[
+            ChordQuality::Power5,
+            ChordQuality::Major,
+            ChordQuality::Minor,
+            ChordQuality::Sus2,
+            ChordQuality::Sus4,
+            ChordQuality::Add9,
+            ChordQuality::Diminished,
+            ChordQuality::Augmented,
+            ChordQuality::Major7,
+            ChordQuality::Minor7,
+            ChordQuality::Dominant7,
+            ChordQuality::Major9,
+            ChordQuality::Minor9,
+            ChordQuality::Dominant9,
+        ]
+    }
+
     /// Get intervals for this chord quality (in semitones from root)
     pub fn intervals(&self) -> Vec<u8> {
         match self {
@@ -106,8 +187,58 @@ impl ChordQuality {
             ChordQuality::Sus2 => vec![0, 2, 7],                   // Root, second, fifth
             ChordQuality::Sus4 => vec![0, 5, 7],                   // Root, fourth, fifth
             ChordQuality::Add9 => vec![0, 4, 7, 14],               // Root, major third, fifth, ninth
+            ChordQuality::Diminished => vec![0, 3, 6],             // Root, minor third, flat fifth
+            ChordQuality::Augmented => vec![0, 4, 8],              // Root, major third, sharp fifth
+            ChordQuality::Major7 => vec![0, 4, 7, 11],             // Major triad, major seventh
+            ChordQuality::Minor7 => vec![0, 3, 7, 10],             // Minor triad, minor seventh
+            ChordQuality::Dominant7 => vec![0, 4, 7, 10],          // Major triad, minor seventh
+            ChordQuality::Major9 => vec![0, 4, 7, 11, 14],         // Major seventh, ninth
+            ChordQuality::Minor9 => vec![0, 3, 7, 10, 14],         // Minor seventh, ninth
+            ChordQuality::Dominant9 => vec![0, 4, 7, 10, 14],      // Dominant seventh, ninth
+        }
+    }
+
+    /// Chord symbol suffix ("" for major, "m", "maj7", ...)
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            ChordQuality::Power5 => "5",
+            ChordQuality::Major => "",
+            ChordQuality::Minor => "m",
+            ChordQuality::Sus2 => "sus2",
+            ChordQuality::Sus4 => "sus4",
+            ChordQuality::Add9 => "add9",
+            ChordQuality::Diminished => "dim",
+            ChordQuality::Augmented => "aug",
+            ChordQuality::Major7 => "maj7",
+            ChordQuality::Minor7 => "m7",
+            ChordQuality::Dominant7 => "7",
+            ChordQuality::Major9 => "maj9",
+            ChordQuality::Minor9 => "m9",
+            ChordQuality::Dominant9 => "9",
         }
     }
+
+    /// Parse a chord symbol suffix (accepts common spellings like "min7", "°", "+")
+    pub fn from_suffix(suffix: &str) -> Option<ChordQuality> {
+        let quality = match suffix {
+            "5" => ChordQuality::Power5,
+            "" | "maj" => ChordQuality::Major,
+            "m" | "min" | "-" => ChordQuality::Minor,
+            "sus2" => ChordQuality::Sus2,
+            "sus4" | "sus" => ChordQuality::Sus4,
+            "add9" => ChordQuality::Add9,
+            "dim" | "°" => ChordQuality::Diminished,
+            "aug" | "+" => ChordQuality::Augmented,
+            "maj7" | "M7" => ChordQuality::Major7,
+            "m7" | "min7" | "-7" => ChordQuality::Minor7,
+            "7" | "dom7" => ChordQuality::Dominant7,
+            "maj9" | "M9" => ChordQuality::Major9,
+            "m9" | "min9" | "-9" => ChordQuality::Minor9,
+            "9" | "dom9" => ChordQuality::Dominant9,
+            _ => return None,
+        };
+        Some(quality)
+    }
 }
 
 /// Musical notes
@@ -119,7 +250,12 @@ pub enum Note {
 impl Note {
     /// Get MIDI note number for this note in octave 4 (middle C = 60)
     pub fn to_midi(self, octave: i8) -> u8 {
-        let base = match self {
+        ((octave + 4) * 12 + self.semitone() as i8) as u8
+    }
+
+    /// Pitch class (0 = C)
+    pub fn semitone(self) -> u8 {
+        match self {
             Note::C => 0,
             Note::Cs => 1,
             Note::D => 2,
@@ -132,8 +268,25 @@ impl Note {
             Note::A => 9,
             Note::As => 10,
             Note::B => 11,
-        };
-        ((octave + 4) * 12 + base as i8) as u8
+        }
+    }
+
+    /// Note for a pitch class (0 = C, wraps above 11)
+    pub fn from_semitone(semitone: u8) -> Note {
+        match semitone % 12 {
+            0 => Note::C,
+            1 => Note::Cs,
+            2 => Note::D,
+            3 => Note::Ds,
+            4 => Note::E,
+            5 => Note::F,
+            6 => Note::Fs,
+            7 => Note::G,
+            8 => Note::Gs,
+            9 => Note::A,
+            10 => Note::As,
+            _ => Note::B,
+        }
     }
 
     /// Get display name
@@ -207,17 +360,25 @@ impl ChordSpec {
             .collect()
     }
 
+    /// Six-string guitar voicing (octave_offset shifts the whole shape)
+    pub fn guitar_voicing(&self) -> crate::guitar_voicing::GuitarVoicing {
+        let mut voicing = crate::guitar_voicing::voice_guitar_chord(self.root.to_midi(-4), &self.quality.intervals());
+        voicing.shift_octaves(self.octave_offset);
+        voicing
+    }
+
     /// Get display name for this chord
     pub fn display_name(&self) -> String {
-        let quality_suffix = match self.quality {
-            ChordQuality::Power5 => "5",
-            ChordQuality::Major => "",
-            ChordQuality::Minor => "m",
-            ChordQuality::Sus2 => "sus2",
-            ChordQuality::Sus4 => "sus4",
-            ChordQuality::Add9 => "add9",
-        };
-        format!("{}{}", self.root.name(), quality_suffix)
+        format!("{}{}", self.root.name(), self.quality.suffix())
+    }
+
+    /// Parse a chord symbol such as "C#m7", "Bbmaj9" or "E5"
+    pub fn parse(symbol: &str) -> Option<ChordSpec> {
+        let symbol = symbol.trim();
+        let root_len = if matches!(symbol.as_bytes().get(1), Some(b'#' | b'b')) { 2 } else { 1 };
+        let root = Note::from_str(symbol.get(..root_len)?)?;
+        let quality = ChordQuality::from_suffix(symbol.get(root_len..)?)?;
+        Some(ChordSpec::new(root, quality))
     }
 }
 
@@ -239,6 +400,16 @@ pub struct GenrePreset {
     pub role_to_chord_quality: std::collections::HashMap<HarmonicRole, ChordQuality>,
     pub whammy_defaults: WhammyDefaults,
     pub sustain_defaults: SustainDefaults,
+    /// Choose inversions that minimize movement from the previous chord
+    #[serde(default)]
+    pub voice_leading: bool,
+    /// Second instrument played under the guitar (None = guitar only)
+    #[serde(default)]
+    pub layer: Option<LayerDefaults>,
+    /// Instrument loaded when switching to the genre, e.g. "Distorted Guitar"
+    /// (None = keep the current one)
+    #[serde(default)]
+    pub instrument: Option<String>,
 }
 
 /// Whammy bar effect configuration
@@ -249,6 +420,13 @@ pub struct WhammyDefaults {
     pub vibrato_depth: f32,
     pub filter_cutoff_enabled: bool,
     pub smoothing_factor: f32,
+    /// Travel around rest ignored as noise (0.0-0.5)
+    #[serde(default = "default_whammy_deadzone")]
+    pub deadzone: f32,
+}
+
+fn default_whammy_deadzone() -> f32 {
+    controller::DEFAULT_WHAMMY_DEADZONE
 }
 
 impl Default for WhammyDefaults {
@@ -259,6 +437,7 @@ impl Default for WhammyDefaults {
             vibrato_depth: 0.0,
             filter_cutoff_enabled: false,
             smoothing_factor: 0.8,
+            deadzone: default_whammy_deadzone(),
         }
     }
 }
@@ -279,6 +458,23 @@ impl Default for SustainDefaults {
     }
 }
 
+/// Instrument layered under the guitar, e.g. a strings pad
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LayerDefaults {
+    /// Virtual instrument name, as listed with the virtual instruments
+    pub instrument: String,
+    /// Layer level relative to the guitar (0.0-1.0)
+    #[serde(default = "default_layer_gain")]
+    pub gain: f32,
+    /// Octaves the layer plays above (positive) or below (negative) the guitar
+    #[serde(default)]
+    pub octave: i8,
+}
+
+fn default_layer_gain() -> f32 {
+    0.5
+}
+
 /// Pattern-level chord overrides
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PatternChordOverride {
@@ -292,4 +488,51 @@ pub struct PatternChordOverride {
 pub enum FretRow {
     Main,
     Solo,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extended_quality_serde_names() {
+        let json = r#"{"I": "maj7", "IV": "min9", "V": "dom7", "II": "dim"}"#;
+        let map: std::collections::HashMap<HarmonicRole, ChordQuality> = serde_json::from_str(json).unwrap();
+        assert_eq!(map[&HarmonicRole::I], ChordQuality::Major7);
+        assert_eq!(map[&HarmonicRole::IV], ChordQuality::Minor9);
+        assert_eq!(serde_json::to_string(&ChordQuality::Augmented).unwrap(), "\"aug\"");
+    }
+
+    #[test]
+    fn test_diatonic_qualities() {
+        // Triads of C major: I ii iii IV V vi vii°
+        let triads: Vec<ChordQuality> = Mode::Major.scale().iter()
+            .filter_map(|&interval| ChordQuality::Major.diatonic(interval, Mode::Major))
+            .collect();
+        use ChordQuality::*;
+        assert_eq!(triads, vec![Major, Minor, Minor, Major, Major, Minor, Diminished]);
+
+        assert_eq!(Major7.diatonic(7, Mode::Major), Some(Dominant7));
+        assert_eq!(Dominant9.diatonic(0, Mode::Major), Some(Major9));
+        assert_eq!(Major.diatonic(0, Mode::Minor), Some(Minor));
+        assert_eq!(Minor.diatonic(10, Mode::Minor), Some(Major));
+        assert_eq!(Power5.diatonic(2, Mode::Minor), Some(Diminished));
+        assert_eq!(Sus4.diatonic(5, Mode::Major), Some(Sus4));
+        // bVII is borrowed in major
+        assert_eq!(Major.diatonic(10, Mode::Major), None);
+    }
+
+    #[test]
+    fn test_chord_symbols_round_trip() {
+        for &quality in ChordQuality::all() {
+            let spec = ChordSpec::new(Note::Fs, quality);
+            let parsed = ChordSpec::parse(&spec.display_name()).unwrap();
+            assert_eq!((parsed.root, parsed.quality), (Note::Fs, quality));
+        }
+        let spec = ChordSpec::parse("Bbmaj9").unwrap();
+        assert_eq!((spec.root, spec.quality), (Note::As, ChordQuality::Major9));
+        // Octave 0 starts at C3
+        assert_eq!(ChordSpec::new(Note::C, ChordQuality::Dominant9).to_midi_notes(0), vec![48, 52, 55, 58, 62]);
+        assert!(ChordSpec::parse("Hmaj7").is_none());
+    }
+}
@@ -0,0 +1,415 @@
+//! Harmonic mapping pipeline
+//! Plays the genre presets: the ChordResolver turns each fret into a chord
+//! from the preset's harmonic roles (in the preset's key and mode unless one
+//! is chosen), the PerformanceEngine decides when chords start, sustain and
+//! stop, and this adapter voices the chords and turns whammy effects into
+//! pitch bend and modulation. Switching genre applies that preset's whammy
+//! and sustain defaults. Palm mute and the sustain pedal work as in the
+//! legacy Mapper. In bass mode the frets play single root notes instead
+//! (see `BassMapper`); in lead mode the solo frets play the key's scale over
+//! the chords (see `LeadMapper`).
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use controller::{ControlId, ControllerState};
+
+use crate::bass::BassMapper;
+use crate::dynamics::{Dynamics, DynamicsMode};
+use crate::lead::LeadMapper;
+use crate::harmonic::{ChordSpec, FretButton, FretRow, Genre, LayerDefaults, Mode, Note, WhammyDefaults};
+use crate::performance::{PerformanceEngine, PerformanceEvent};
+use crate::resolution::ChordResolver;
+use crate::{send_whammy_control, MusicEvent, PalmMuteTrigger, PlayedChord, WhammyMode, MODULATION_CC, SUSTAIN_PEDAL_CC};
+
+/// Octave passed to `ChordSpec::to_midi_notes` (roots from E2, like the legacy mapper)
+const CHORD_OCTAVE: i8 = -1;
+
+/// Pitch bend range of the synths in semitones (full MIDI bend)
+const SYNTH_BEND_RANGE_SEMITONES: f32 = 2.0;
+
+/// Maps controller state to musical events through the genre presets
+#[derive(Debug)]
+pub struct HarmonicMapper {
+    resolver: Arc<ChordResolver>,
+    engine: PerformanceEngine,
+    genre: Genre,
+    /// Key and mode chosen by the player (None = the preset's default)
+    key_root: Option<Note>,
+    mode: Option<Mode>,
+    /// Correct the preset's chord qualities to the key and mode
+    diatonic: bool,
+    main_chords: HashMap<FretButton, ChordSpec>,
+    solo_chords: HashMap<FretButton, ChordSpec>,
+    active_notes: Vec<u8>,
+    /// Velocity of each strum (in place of the engine's constant one)
+    dynamics: Dynamics,
+    palm_mute_trigger: PalmMuteTrigger,
+    palm_muted: bool,
+    /// Sustain pedal state last sent (as CC64)
+    sustain_pedal: bool,
+    /// Whammy pitch bend and modulation last sent
+    bend: i16,
+    modulation: u8,
+    /// What the whammy controls, and the controller value it last sent
+    whammy_mode: WhammyMode,
+    whammy_control: Option<(WhammyMode, u8)>,
+    /// Single-note bass lines in place of chords (None = chords)
+    bass: Option<BassMapper>,
+    /// Scale notes on the solo frets (None = the solo frets play chords too)
+    lead: Option<LeadMapper>,
+    /// Chord struck since the last `take_played_chord`
+    played_chord: Option<PlayedChord>,
+}
+
+impl HarmonicMapper {
+    pub fn new(resolver: Arc<ChordResolver>, genre: Genre) -> Self {
+        let mut mapper = Self {
+            resolver,
+            engine: PerformanceEngine::new(),
+            genre,
+            key_root: None,
+            mode: None,
+            diatonic: false,
+            main_chords: HashMap::new(),
+            solo_chords: HashMap::new(),
+            active_notes: Vec::new(),
+            dynamics: Dynamics::default(),
+            palm_mute_trigger: PalmMuteTrigger::Off,
+            palm_muted: false,
+            sustain_pedal: false,
+            bend: 0,
+            modulation: 0,
+            whammy_mode: WhammyMode::PitchBend,
+            whammy_control: None,
+            bass: None,
+            lead: None,
+            played_chord: None,
+        };
+        mapper.set_genre(genre);
+        mapper
+    }
+
+    /// Take over reloaded genre presets (the genre's defaults and chords are reapplied)
+    pub fn set_resolver(&mut self, resolver: Arc<ChordResolver>) {
+        self.resolver = resolver;
+        self.set_genre(self.genre);
+    }
+
+    /// Switch genre, applying its preset's whammy and sustain defaults
+    pub fn set_genre(&mut self, genre: Genre) {
+        self.genre = genre;
+        if let Some(preset) = self.resolver.get_preset(genre) {
+            self.engine.update_whammy_config(preset.whammy_defaults.clone());
+            self.engine.update_sustain_config(preset.sustain_defaults.clone());
+        }
+        self.resolve_chords();
+    }
+
+    pub fn genre(&self) -> Genre {
+        self.genre
+    }
+
+    /// Choose key and mode (None keeps the preset's default)
+    pub fn set_key(&mut self, key_root: Option<Note>, mode: Option<Mode>) {
+        self.key_root = key_root;
+        self.mode = mode;
+        self.resolve_chords();
+    }
+
+    /// Key and mode being played
+    pub fn key(&self) -> (Note, Mode) {
+        let preset = self.resolver.get_preset(self.genre);
+        let key_root = self.key_root.or(preset.map(|preset| preset.default_key)).unwrap_or(self.genre.default_key_root());
+        let mode = self.mode.or(preset.map(|preset| preset.default_mode)).unwrap_or(self.genre.default_mode());
+        (key_root, mode)
+    }
+
+    /// Make every chord diatonic to the key and mode (ii minor, vii
+    /// diminished, ...) instead of playing the preset's qualities as written
+    pub fn set_diatonic(&mut self, enabled: bool) {
+        self.diatonic = enabled;
+        self.resolve_chords();
+    }
+
+    pub fn diatonic(&self) -> bool {
+        self.diatonic
+    }
+
+    /// Play root notes as a bass instead of chords; returns the note-offs of
+    /// what was sounding
+    pub fn set_bass_mode(&mut self, enabled: bool) -> Vec<MusicEvent> {
+        if enabled == self.bass.is_some() {
+            return Vec::new();
+        }
+        let mut events = Vec::new();
+        if let Some(bass) = &mut self.bass {
+            events.extend(bass.panic());
+        }
+        self.release_notes(&mut events);
+        self.bass = enabled.then(BassMapper::new);
+        events
+    }
+
+    pub fn bass_mode(&self) -> bool {
+        self.bass.is_some()
+    }
+
+    /// Play the key's scale on the solo frets (ignored in bass mode); returns
+    /// the note-off of a lead note that was sounding
+    pub fn set_lead_mode(&mut self, enabled: bool) -> Vec<MusicEvent> {
+        if enabled == self.lead.is_some() {
+            return Vec::new();
+        }
+        let mut events = Vec::new();
+        if let Some(lead) = &mut self.lead {
+            events.extend(lead.panic());
+            self.send_whammy(self.bend, 0, &mut events);
+        }
+        self.lead = enabled.then(LeadMapper::new);
+        events
+    }
+
+    pub fn lead_mode(&self) -> bool {
+        self.lead.is_some()
+    }
+
+    /// Chords on a fret row
+    pub fn chords(&self, row: FretRow) -> &HashMap<FretButton, ChordSpec> {
+        match row {
+            FretRow::Main => &self.main_chords,
+            FretRow::Solo => &self.solo_chords,
+        }
+    }
+
+    fn resolve_chords(&mut self) {
+        let resolve = |row| {
+            self.resolver.resolve_chord_map_with(self.genre, self.key_root, self.mode, row, &[], self.diatonic).unwrap_or_else(|e| {
+                log::warn!("{}", e);
+                HashMap::new()
+            })
+        };
+        let (main_chords, solo_chords) = (resolve(FretRow::Main), resolve(FretRow::Solo));
+        self.main_chords = main_chords;
+        self.solo_chords = solo_chords;
+    }
+
+    /// Choose the input that holds palm mute (picked up on the next update)
+    pub fn set_palm_mute_trigger(&mut self, trigger: PalmMuteTrigger) {
+        self.palm_mute_trigger = trigger;
+    }
+
+    /// Choose what sets the velocity of each strum
+    pub fn set_dynamics_mode(&mut self, mode: DynamicsMode) {
+        self.dynamics.set_mode(mode);
+    }
+
+    pub fn dynamics_mode(&self) -> DynamicsMode {
+        self.dynamics.mode()
+    }
+
+    /// Choose what the whammy controls (other modes ignore the preset's bend and vibrato)
+    pub fn set_whammy_mode(&mut self, mode: WhammyMode) {
+        self.whammy_mode = mode;
+    }
+
+    /// Whammy settings of the current genre's preset
+    pub fn whammy_defaults(&self) -> &WhammyDefaults {
+        &self.engine.state().whammy_config
+    }
+
+    /// Instrument the current genre's preset layers under the guitar
+    pub fn layer_defaults(&self) -> Option<&LayerDefaults> {
+        self.resolver.get_preset(self.genre).and_then(|preset| preset.layer.as_ref())
+    }
+
+    /// Instrument the current genre's preset switches to
+    pub fn preset_instrument(&self) -> Option<&str> {
+        self.resolver.get_preset(self.genre).and_then(|preset| preset.instrument.as_deref())
+    }
+
+    /// Process controller state and generate musical events
+    pub fn process(&mut self, state: &ControllerState) -> Vec<MusicEvent> {
+        let mut events = Vec::new();
+
+        // Palm mute goes first so a chord strummed in the same update is damped
+        let palm_muted = self.palm_mute_trigger.is_engaged(state);
+        if palm_muted != self.palm_muted {
+            self.palm_muted = palm_muted;
+            events.push(MusicEvent::PalmMute(palm_muted));
+        }
+
+        let sustain_pedal = state.buttons.get(&ControlId::SustainPedal).copied().unwrap_or(false);
+        if sustain_pedal != self.sustain_pedal {
+            self.sustain_pedal = sustain_pedal;
+            events.push(MusicEvent::ControlChange { cc: SUSTAIN_PEDAL_CC, value: if sustain_pedal { 127 } else { 0 } });
+        }
+
+        if let Some(bass) = &mut self.bass {
+            events.extend(bass.process(state, &self.main_chords));
+            return events;
+        }
+
+        let lead_input = self.lead.is_some().then(|| self.key());
+        let chord_input = match (&mut self.lead, lead_input) {
+            (Some(lead), Some((key_root, mode))) => {
+                let was_sounding = lead.is_sounding();
+                events.extend(lead.process(state, key_root, mode));
+                let vibrato = lead.vibrato(state);
+                let chord_input = lead.chord_input(state);
+                match vibrato {
+                    Some(vibrato) => self.send_whammy(self.bend, vibrato, &mut events),
+                    // The lead note ended: take its vibrato off
+                    None if was_sounding => self.send_whammy(self.bend, 0, &mut events),
+                    None => {}
+                }
+                Some(chord_input)
+            }
+            _ => None,
+        };
+        let state = chord_input.as_ref().unwrap_or(state);
+
+        for event in self.engine.process_input(state, &self.main_chords, &self.solo_chords) {
+            match event {
+                PerformanceEvent::ChordTrigger { chord_spec, .. } => {
+                    self.release_notes(&mut events);
+                    let velocity = self.dynamics.strum_velocity(state);
+                    let mut notes = chord_spec.to_midi_notes(CHORD_OCTAVE);
+                    for &note in &notes {
+                        events.push(MusicEvent::NoteOn { note, velocity });
+                        self.active_notes.push(note);
+                    }
+                    notes.sort_unstable();
+                    self.played_chord = Some(PlayedChord { name: chord_spec.display_name(), notes });
+                }
+                // Release fades come from the instrument's release time
+                PerformanceEvent::ChordRelease { .. } => self.release_notes(&mut events),
+                // The preset's filter cutoff is left out; the filter is a whammy mode of its own
+                PerformanceEvent::WhammyEffect { normalized_value, pitch_bend_semitones, vibrato_depth, .. } => {
+                    if self.whammy_mode == WhammyMode::PitchBend {
+                        let bend = ((pitch_bend_semitones / SYNTH_BEND_RANGE_SEMITONES).clamp(-1.0, 1.0) * 8191.0) as i16;
+                        let modulation = (vibrato_depth.clamp(0.0, 1.0) * 127.0).round() as u8;
+                        self.send_whammy(bend, modulation, &mut events);
+                    } else {
+                        send_whammy_control(self.whammy_mode, normalized_value, &mut self.whammy_control, &mut events);
+                    }
+                }
+                PerformanceEvent::WhammyStop => self.send_whammy(0, 0, &mut events),
+                PerformanceEvent::Panic => {
+                    self.active_notes.clear();
+                    events.push(MusicEvent::PanicAllNotesOff);
+                }
+            }
+        }
+
+        // Without sustain the engine never releases; stop with the strum bar like the legacy mapper
+        let strumming = state.buttons.get(&ControlId::StrumUp).copied().unwrap_or(false)
+            || state.buttons.get(&ControlId::StrumDown).copied().unwrap_or(false);
+        if !self.engine.state().sustain_config.enabled && !strumming {
+            self.release_notes(&mut events);
+        }
+
+        events
+    }
+
+    /// Chord struck since the previous call (None when no chord was)
+    pub fn take_played_chord(&mut self) -> Option<PlayedChord> {
+        self.played_chord.take()
+    }
+
+    /// Note-offs for the sounding chord, straightening the whammy
+    /// (the engine only follows the whammy while a chord sustains)
+    fn release_notes(&mut self, events: &mut Vec<MusicEvent>) {
+        events.extend(self.active_notes.drain(..).map(|note| MusicEvent::NoteOff { note }));
+        self.send_whammy(0, 0, events);
+    }
+
+    /// Pitch bend and modulation from the preset; a zero bend also returns
+    /// the whammy mode's controller to rest
+    fn send_whammy(&mut self, bend: i16, modulation: u8, events: &mut Vec<MusicEvent>) {
+        if bend == 0 && modulation == 0 {
+            send_whammy_control(self.whammy_mode, 0.0, &mut self.whammy_control, events);
+        }
+        if bend != self.bend {
+            self.bend = bend;
+            events.push(MusicEvent::PitchBend(bend));
+        }
+        if modulation != self.modulation {
+            self.modulation = modulation;
+            events.push(MusicEvent::ControlChange { cc: MODULATION_CC, value: modulation });
+        }
+    }
+
+    /// Stop everything
+    pub fn panic(&mut self) -> Vec<MusicEvent> {
+        let mut events: Vec<MusicEvent> = self.active_notes.drain(..).map(|note| MusicEvent::NoteOff { note }).collect();
+        if let Some(bass) = &mut self.bass {
+            events.extend(bass.panic());
+        }
+        if let Some(lead) = &mut self.lead {
+            events.extend(lead.panic());
+        }
+        self.engine.panic();
+        self.send_whammy(0, 0, &mut events);
+        events.push(MusicEvent::PanicAllNotesOff);
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::PresetLoader;
+
+    fn strum(frets: &[ControlId], down: bool) -> ControllerState {
+        let mut state = ControllerState::default();
+        for &fret in frets {
+            state.buttons.insert(fret, true);
+        }
+        state.buttons.insert(ControlId::StrumDown, down);
+        state
+    }
+
+    fn notes_on(events: &[MusicEvent]) -> Vec<u8> {
+        events.iter().filter_map(|event| match event {
+            MusicEvent::NoteOn { note, .. } => Some(*note),
+            _ => None,
+        }).collect()
+    }
+
+    #[test]
+    fn test_plays_preset_chords_in_preset_key() {
+        let mut mapper = HarmonicMapper::new(Arc::new(PresetLoader::default_resolver()), Genre::Rock);
+        assert_eq!(mapper.key(), (Note::A, Mode::Major));
+
+        // Green is the I chord: A major from A2
+        let events = mapper.process(&strum(&[ControlId::FretGreen], true));
+        assert_eq!(notes_on(&events), vec![45, 49, 52]);
+
+        // Sustains after the strum; a new chord releases the old one first
+        assert!(mapper.process(&strum(&[ControlId::FretGreen], false)).is_empty());
+        let events = mapper.process(&strum(&[ControlId::FretRed], true));
+        assert!(matches!(events[0], MusicEvent::NoteOff { note: 45 }));
+        assert_eq!(notes_on(&events), vec![38, 42, 45]);
+
+        // Lifting the fret releases the chord
+        let events = mapper.process(&strum(&[], false));
+        assert_eq!(events.iter().filter(|event| matches!(event, MusicEvent::NoteOff { .. })).count(), 3);
+    }
+
+    #[test]
+    fn test_key_override_and_panic() {
+        let mut mapper = HarmonicMapper::new(Arc::new(PresetLoader::default_resolver()), Genre::Punk);
+        mapper.set_key(Some(Note::G), None);
+        assert_eq!(mapper.chords(FretRow::Main)[&FretButton::Green].root, Note::G);
+
+        mapper.process(&strum(&[ControlId::FretGreen], true));
+        let events = mapper.panic();
+        assert_eq!(events.iter().filter(|event| matches!(event, MusicEvent::NoteOff { .. })).count(), 2);
+        assert!(matches!(events.last(), Some(MusicEvent::PanicAllNotesOff)));
+
+        // Switching genre keeps the chosen key
+        mapper.set_genre(Genre::Metal);
+        assert_eq!(mapper.key(), (Note::G, Mode::Minor));
+    }
+}
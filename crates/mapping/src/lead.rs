@@ -0,0 +1,222 @@
+//! Lead mode
+//! Solos over the chords: the solo frets play single notes of the key's scale
+//! (green the root, up to orange on the fifth degree), and so do the main frets
+//! while Select is held, for guitars without a solo row. A strum plays the
+//! highest held fret, strumming up an octave higher; changing frets while the
+//! note rings hammers on or pulls off without a strum. The whammy adds vibrato
+//! to the lead note. Everything else still plays chords.
+
+use controller::{ControlId, ControllerState};
+
+use crate::harmonic::{Mode, Note};
+use crate::MusicEvent;
+
+/// Held to play lead on the main frets
+pub const LEAD_MODIFIER: ControlId = ControlId::Select;
+
+/// Octave passed to `Note::to_midi` for the scale root (E4 for E, two octaves over the chords)
+const LEAD_OCTAVE: i8 = 1;
+
+/// Velocity of strummed lead notes (hammer-ons and pull-offs are softer)
+const LEAD_VELOCITY: u8 = 110;
+const LEGATO_VELOCITY: u8 = 90;
+
+/// Fret rows highest first, by scale degree (0 = root)
+const SOLO_FRETS: [(ControlId, usize); 5] = [
+    (ControlId::SoloOrange, 4),
+    (ControlId::SoloBlue, 3),
+    (ControlId::SoloYellow, 2),
+    (ControlId::SoloRed, 1),
+    (ControlId::SoloGreen, 0),
+];
+const MAIN_FRETS: [(ControlId, usize); 5] = [
+    (ControlId::FretOrange, 4),
+    (ControlId::FretBlue, 3),
+    (ControlId::FretYellow, 2),
+    (ControlId::FretRed, 1),
+    (ControlId::FretGreen, 0),
+];
+
+/// A lead note that is sounding
+#[derive(Debug, Clone, Copy)]
+struct LeadNote {
+    note: u8,
+    fret: ControlId,
+    /// Strummed up (an octave higher); hammer-ons keep the octave
+    octave_up: bool,
+}
+
+/// Single scale notes from the solo frets
+#[derive(Debug, Clone, Default)]
+pub struct LeadMapper {
+    active: Option<LeadNote>,
+    strum_up: bool,
+    strum_down: bool,
+}
+
+impl LeadMapper {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// MIDI note of a scale degree (0 = root; degrees past the seventh go up an octave)
+    pub fn scale_note(key_root: Note, mode: Mode, degree: usize, octave_up: bool) -> u8 {
+        let octaves = degree / 7 + usize::from(octave_up);
+        key_root.to_midi(LEAD_OCTAVE) + mode.scale()[degree % 7] + 12 * octaves as u8
+    }
+
+    /// Whether the controller is playing lead (a solo fret, or the modifier, is held)
+    pub fn is_engaged(state: &ControllerState) -> bool {
+        held(state, LEAD_MODIFIER) || SOLO_FRETS.iter().any(|(control, _)| held(state, *control))
+    }
+
+    /// Whether a lead note is sounding
+    pub fn is_sounding(&self) -> bool {
+        self.active.is_some()
+    }
+
+    /// Highest held lead fret and its scale degree
+    fn held_fret(state: &ControllerState) -> Option<(ControlId, usize)> {
+        let main_frets: &[(ControlId, usize)] = if held(state, LEAD_MODIFIER) { &MAIN_FRETS } else { &[] };
+        SOLO_FRETS.iter().chain(main_frets).find(|(control, _)| held(state, *control)).copied()
+    }
+
+    /// Process controller state in a key
+    pub fn process(&mut self, state: &ControllerState, key_root: Note, mode: Mode) -> Vec<MusicEvent> {
+        let mut events = Vec::new();
+        let (up, down) = (held(state, ControlId::StrumUp), held(state, ControlId::StrumDown));
+        let strummed_up = up && !self.strum_up;
+        let strummed_down = down && !self.strum_down;
+        self.strum_up = up;
+        self.strum_down = down;
+
+        let fret = Self::held_fret(state);
+        if strummed_up || strummed_down {
+            if let Some((control, degree)) = fret {
+                let octave_up = strummed_up && !strummed_down;
+                self.play(Self::scale_note(key_root, mode, degree, octave_up), control, octave_up, LEAD_VELOCITY, &mut events);
+            }
+        } else if let Some(active) = self.active.filter(|active| fret.map(|(control, _)| control) != Some(active.fret)) {
+            match fret {
+                // Hammer-on or pull-off to the fret now on top
+                Some((control, degree)) => {
+                    let note = Self::scale_note(key_root, mode, degree, active.octave_up);
+                    self.play(note, control, active.octave_up, LEGATO_VELOCITY, &mut events);
+                }
+                None => self.release(&mut events),
+            }
+        }
+        events
+    }
+
+    fn play(&mut self, note: u8, fret: ControlId, octave_up: bool, velocity: u8, events: &mut Vec<MusicEvent>) {
+        self.release(events);
+        events.push(MusicEvent::NoteOn { note, velocity });
+        self.active = Some(LeadNote { note, fret, octave_up });
+    }
+
+    fn release(&mut self, events: &mut Vec<MusicEvent>) {
+        if let Some(active) = self.active.take() {
+            events.push(MusicEvent::NoteOff { note: active.note });
+        }
+    }
+
+    /// Vibrato depth (modulation wheel) from the whammy while a lead note sounds
+    pub fn vibrato(&self, state: &ControllerState) -> Option<u8> {
+        self.active?;
+        let raw = state.axis(ControlId::WhammyBar);
+        // Same ranges as the PerformanceEngine: -1..1 or 0..1
+        let normalized = if raw < 0.0 { (raw + 1.0) * 0.5 } else { raw };
+        Some((normalized.clamp(0.0, 1.0) * 127.0).round() as u8)
+    }
+
+    /// The input left for the chords: without the solo frets, without the
+    /// main frets and strum bar while the modifier is held, and with the
+    /// whammy at rest while it gives the lead vibrato
+    pub fn chord_input(&self, state: &ControllerState) -> ControllerState {
+        let mut input = state.clone();
+        for (control, _) in SOLO_FRETS {
+            input.buttons.insert(control, false);
+        }
+        if held(state, LEAD_MODIFIER) {
+            for control in MAIN_FRETS.iter().map(|(control, _)| *control).chain([ControlId::StrumUp, ControlId::StrumDown]) {
+                input.buttons.insert(control, false);
+            }
+        }
+        if self.active.is_some() {
+            input.axes.insert(ControlId::WhammyBar, 0.0);
+        }
+        input
+    }
+
+    /// Stop the sounding note
+    pub fn panic(&mut self) -> Vec<MusicEvent> {
+        let mut events = Vec::new();
+        self.release(&mut events);
+        events
+    }
+}
+
+fn held(state: &ControllerState, control: ControlId) -> bool {
+    state.buttons.get(&control).copied().unwrap_or(false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn input(controls: &[ControlId]) -> ControllerState {
+        let mut state = ControllerState::default();
+        for &control in controls {
+            state.buttons.insert(control, true);
+        }
+        state
+    }
+
+    #[test]
+    fn test_solo_frets_play_scale_notes_with_legato() {
+        let mut lead = LeadMapper::new();
+        // A minor: A4 on green, C5 on yellow
+        let events = lead.process(&input(&[ControlId::SoloGreen, ControlId::StrumDown]), Note::A, Mode::Minor);
+        assert!(matches!(events[..], [MusicEvent::NoteOn { note: 69, velocity: LEAD_VELOCITY }]));
+
+        // Hammer on to yellow without strumming, then pull off back to green
+        let events = lead.process(&input(&[ControlId::SoloGreen, ControlId::SoloYellow]), Note::A, Mode::Minor);
+        assert!(matches!(events[..], [MusicEvent::NoteOff { note: 69 }, MusicEvent::NoteOn { note: 72, velocity: LEGATO_VELOCITY }]));
+        let events = lead.process(&input(&[ControlId::SoloGreen]), Note::A, Mode::Minor);
+        assert!(matches!(events[..], [MusicEvent::NoteOff { note: 72 }, MusicEvent::NoteOn { note: 69, .. }]));
+
+        // Letting go ends the note
+        let events = lead.process(&input(&[]), Note::A, Mode::Minor);
+        assert!(matches!(events[..], [MusicEvent::NoteOff { note: 69 }]));
+        assert!(!lead.is_sounding());
+
+        // Strumming up plays an octave higher, and so do degrees past the seventh
+        assert_eq!(LeadMapper::scale_note(Note::C, Mode::Major, 4, true), 60 + 7 + 12);
+        assert_eq!(LeadMapper::scale_note(Note::C, Mode::Major, 7, false), 72);
+    }
+
+    #[test]
+    fn test_modifier_takes_main_frets_from_the_chords() {
+        let mut lead = LeadMapper::new();
+        let mut state = input(&[LEAD_MODIFIER, ControlId::FretRed, ControlId::StrumUp]);
+        state.axes.insert(ControlId::WhammyBar, 0.5);
+        assert!(LeadMapper::is_engaged(&state));
+        // E major second degree, strummed up: F#5
+        let events = lead.process(&state, Note::E, Mode::Major);
+        assert!(matches!(events[..], [MusicEvent::NoteOn { note: 78, .. }]));
+        assert_eq!(lead.vibrato(&state), Some(64));
+
+        // The chords don't see the frets, strum or whammy the lead is using
+        let chords = lead.chord_input(&state);
+        assert!(!chords.buttons[&ControlId::FretRed] && !chords.buttons[&ControlId::StrumUp]);
+        assert_eq!(chords.axis(ControlId::WhammyBar), 0.0);
+
+        // Without the modifier the main frets are left to the chords
+        let state = input(&[ControlId::FretRed]);
+        assert!(!LeadMapper::is_engaged(&state));
+        assert!(lead.chord_input(&state).buttons[&ControlId::FretRed]);
+        assert_eq!(lead.panic().len(), 1);
+        assert_eq!(lead.vibrato(&state), None);
+    }
+}
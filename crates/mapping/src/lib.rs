@@ -1,11 +1,23 @@
 use serde::{Deserialize, Serialize};
 
+pub mod bass;
 pub mod chord;
+pub mod dynamics;
 pub mod genre;
+pub mod guitar_voicing;
 pub mod harmonic;
+pub mod harmonic_mapper;
+pub mod lead;
 pub mod resolution;
 pub mod performance;
+pub mod plugin;
 pub mod presets;
+pub mod tilt_layer;
+pub mod transpose;
+pub mod voicing;
+
+#[cfg(test)]
+mod proptests;
 
 // Re-export legacy types for compatibility
 pub use chord::{Chord, ChordQuality, ChordPattern};
@@ -14,13 +26,23 @@ pub use genre::Genre as LegacyGenre;
 // New genre-based chord mapping API
 pub use harmonic::{
     FretButton, HarmonicRole, Genre, Mode, Note, ChordQuality as NewChordQuality, 
-    ChordSpec, GenrePreset, PatternChordOverride, FretRow, WhammyDefaults, SustainDefaults
+    ChordSpec, GenrePreset, PatternChordOverride, FretRow, WhammyDefaults, SustainDefaults,
+    LayerDefaults
 };
 pub use resolution::ChordResolver;
+pub use harmonic_mapper::HarmonicMapper;
+pub use bass::BassMapper;
+pub use dynamics::{Dynamics, DynamicsMode};
+pub use lead::{LeadMapper, LEAD_MODIFIER};
 pub use performance::{PerformanceEngine, PerformanceEvent, PerformanceState};
+pub use plugin::{MapperPlugin, PluginInfo, PluginRegistry};
 pub use presets::PresetLoader;
+pub use tilt_layer::TiltLayer;
+pub use transpose::{Capo, DEFAULT_TRANSPOSE_RANGE};
+pub use voicing::VoiceLeader;
+pub use guitar_voicing::{GuitarVoicing, ShapeKind, StringNote, StrumDirection};
 
-use controller::{ControlId, ControllerState};
+use controller::{ControlId, ControllerState, MacroAction};
 
 /// Musical event generated from controller input
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,11 +50,17 @@ pub enum MusicEvent {
     /// Start playing a note
     NoteOn { note: u8, velocity: u8 },
     
+    /// Start playing a note after a short delay (one string of a strummed chord)
+    StrumNoteOn { note: u8, velocity: u8, delay_ms: f32 },
+    
     /// Stop playing a note
     NoteOff { note: u8 },
     
     /// Pitch bend (-8192 to 8191, 0 = center)
     PitchBend(i16),
+
+    /// Pitch bend of one sounding note only (-8192 to 8191, 0 = center)
+    NotePitchBend { note: u8, amount: i16 },
     
     /// Control change (CC number, value 0-127)
     ControlChange { cc: u8, value: u8 },
@@ -42,6 +70,300 @@ pub enum MusicEvent {
     
     /// Stop all notes immediately
     PanicAllNotesOff,
+
+    /// Palm mute on/off (applies to notes started afterwards)
+    PalmMute(bool),
+}
+
+impl MusicEvent {
+    /// Macro step for a played event, with its delay in milliseconds
+    /// (None for events a macro doesn't record)
+    pub fn to_macro_action(&self) -> Option<(MacroAction, f32)> {
+        match *self {
+            MusicEvent::NoteOn { note, velocity } => Some((MacroAction::NoteOn { note, velocity }, 0.0)),
+            MusicEvent::StrumNoteOn { note, velocity, delay_ms } => Some((MacroAction::NoteOn { note, velocity }, delay_ms)),
+            MusicEvent::NoteOff { note } => Some((MacroAction::NoteOff { note }, 0.0)),
+            MusicEvent::PitchBend(amount) => Some((MacroAction::PitchBend { amount }, 0.0)),
+            MusicEvent::PanicAllNotesOff => Some((MacroAction::Panic, 0.0)),
+            _ => None,
+        }
+    }
+
+    /// Event for a macro step (None for app actions like instrument changes)
+    pub fn from_macro_action(action: &MacroAction) -> Option<Self> {
+        match *action {
+            MacroAction::NoteOn { note, velocity } => Some(MusicEvent::NoteOn { note, velocity }),
+            MacroAction::NoteOff { note } => Some(MusicEvent::NoteOff { note }),
+            MacroAction::PitchBend { amount } => Some(MusicEvent::PitchBend(amount)),
+            MacroAction::Panic => Some(MusicEvent::PanicAllNotesOff),
+            MacroAction::SetInstrument { .. } | MacroAction::SetGenre { .. } => None,
+        }
+    }
+}
+
+/// MIDI CC number of the sustain pedal
+pub const SUSTAIN_PEDAL_CC: u8 = 64;
+
+/// MIDI CC number of the modulation wheel (vibrato depth)
+pub const MODULATION_CC: u8 = 1;
+
+/// MIDI CC number of expression (volume swells)
+pub const EXPRESSION_CC: u8 = 11;
+
+/// MIDI CC number of filter cutoff (brightness)
+pub const FILTER_CUTOFF_CC: u8 = 74;
+
+/// MIDI CC number of the tilt layer level (effect control 1)
+pub const LAYER_CC: u8 = 12;
+
+/// MIDI CC number of the tilt rock-out effect (effect control 2, >= 64 = engaged)
+pub const ROCK_OUT_CC: u8 = 13;
+
+/// Tilt below this (neck pointing down) engages palm mute
+const PALM_MUTE_TILT_THRESHOLD: f32 = 0.5;
+
+/// Input that holds palm mute
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PalmMuteTrigger {
+    #[default]
+    Off,
+    /// Mute while Select is held
+    SelectHeld,
+    /// Mute while the neck is tilted down
+    TiltDown,
+}
+
+impl PalmMuteTrigger {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(Self::Off),
+            "select_held" => Some(Self::SelectHeld),
+            "tilt_down" => Some(Self::TiltDown),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::SelectHeld => "select_held",
+            Self::TiltDown => "tilt_down",
+        }
+    }
+
+    /// Whether the controller is holding palm mute
+    pub fn is_engaged(&self, state: &ControllerState) -> bool {
+        match self {
+            Self::Off => false,
+            Self::SelectHeld => state.buttons.get(&ControlId::Select).copied().unwrap_or(false),
+            Self::TiltDown => state.axis(ControlId::TiltSensor) < -PALM_MUTE_TILT_THRESHOLD,
+        }
+    }
+}
+
+/// Notes the whammy bar bends
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BendTarget {
+    /// Bend the whole chord
+    #[default]
+    AllNotes,
+    /// Bend only the highest note of the chord (lead bend)
+    TopNote,
+}
+
+impl BendTarget {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "all_notes" => Some(Self::AllNotes),
+            "top_note" => Some(Self::TopNote),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::AllNotes => "all_notes",
+            Self::TopNote => "top_note",
+        }
+    }
+}
+
+/// What pulling a split whammy bar does (pushing always bends down)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhammyPullEffect {
+    /// Vibrato as deep as the bar is pulled (sent as the modulation wheel)
+    #[default]
+    Vibrato,
+    /// Bend up
+    BendUp,
+    /// Pulling does nothing
+    Off,
+}
+
+impl WhammyPullEffect {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "vibrato" => Some(Self::Vibrato),
+            "bend_up" => Some(Self::BendUp),
+            "off" => Some(Self::Off),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Vibrato => "vibrato",
+            Self::BendUp => "bend_up",
+            Self::Off => "off",
+        }
+    }
+}
+
+/// What raising the neck does (`tilt_mode` in the config)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TiltAction {
+    /// Release star power in a song
+    #[default]
+    StarPower,
+    /// Sweep a resonant filter over the guitars while raised
+    FilterSweep,
+    /// Overdrive boost while raised
+    Overdrive,
+    /// Fade a second instrument in as the neck rises (see `TiltLayer`)
+    Layer,
+}
+
+impl TiltAction {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "star_power" => Some(Self::StarPower),
+            // "filter_cutoff" is what older configs call the sweep
+            "filter_sweep" | "filter_cutoff" => Some(Self::FilterSweep),
+            "overdrive" => Some(Self::Overdrive),
+            "layer" => Some(Self::Layer),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::StarPower => "star_power",
+            Self::FilterSweep => "filter_sweep",
+            Self::Overdrive => "overdrive",
+            Self::Layer => "layer",
+        }
+    }
+}
+
+/// What the whammy bar controls
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WhammyMode {
+    /// Bend the pitch (sent as pitch bend)
+    #[default]
+    PitchBend,
+    /// Vibrato as deep as the bar is pressed (modulation wheel)
+    Vibrato,
+    /// Pressing closes the filter, darkening the tone (CC74)
+    FilterCutoff,
+    /// Pressing turns the volume down, so releasing the bar swells in (CC11)
+    VolumeSwell,
+}
+
+impl WhammyMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "pitch_bend" => Some(Self::PitchBend),
+            "vibrato" => Some(Self::Vibrato),
+            "filter_cutoff" => Some(Self::FilterCutoff),
+            "volume_swell" => Some(Self::VolumeSwell),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::PitchBend => "pitch_bend",
+            Self::Vibrato => "vibrato",
+            Self::FilterCutoff => "filter_cutoff",
+            Self::VolumeSwell => "volume_swell",
+        }
+    }
+
+    /// Controller the mode sends (None = pitch bend)
+    pub fn cc(&self) -> Option<u8> {
+        match self {
+            Self::PitchBend => None,
+            Self::Vibrato => Some(MODULATION_CC),
+            Self::FilterCutoff => Some(FILTER_CUTOFF_CC),
+            Self::VolumeSwell => Some(EXPRESSION_CC),
+        }
+    }
+
+    /// Controller value for how far the bar is pressed (0.0 = at rest, 1.0 = fully)
+    pub fn cc_value(&self, depth: f32) -> u8 {
+        let depth = depth.abs().clamp(0.0, 1.0);
+        let value = match self {
+            Self::PitchBend | Self::Vibrato => depth,
+            // At rest the filter is open and the volume full
+            Self::FilterCutoff | Self::VolumeSwell => 1.0 - depth,
+        };
+        (value * 127.0).round() as u8
+    }
+
+    /// Controller event for a bar depth (None = pitch bend)
+    pub fn control_change(&self, depth: f32) -> Option<MusicEvent> {
+        self.cc().map(|cc| MusicEvent::ControlChange { cc, value: self.cc_value(depth) })
+    }
+}
+
+/// Send the whammy's controller for a bar depth unless `sent` already holds
+/// that value; after a mode change the previous controller is returned to rest
+pub(crate) fn send_whammy_control(
+    mode: WhammyMode,
+    depth: f32,
+    sent: &mut Option<(WhammyMode, u8)>,
+    events: &mut Vec<MusicEvent>,
+) {
+    let control = mode.cc().map(|_| (mode, mode.cc_value(depth)));
+    if control == *sent {
+        return;
+    }
+    if let Some((previous, _)) = sent.filter(|&(previous, _)| previous != mode) {
+        events.extend(previous.control_change(0.0));
+    }
+    events.extend(mode.control_change(depth));
+    *sent = control;
+}
+
+/// `PlayedChord` with its notes sorted low to high
+fn played_chord(name: String, notes: &[u8]) -> PlayedChord {
+    let mut notes = notes.to_vec();
+    notes.sort_unstable();
+    PlayedChord { name, notes }
+}
+
+/// A fret combination whose chord differs between two mappings
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChordChange {
+    pub frets: Vec<ControlId>,
+    /// Chord played now (None = nothing)
+    pub from: Option<String>,
+    /// Chord played after the change
+    pub to: Option<String>,
+}
+
+/// A chord a mapper just struck, for the UI
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PlayedChord {
+    /// Display name ("Am7")
+    pub name: String,
+    /// MIDI notes, low to high
+    pub notes: Vec<u8>,
 }
 
 /// Maps controller state to musical events (Legacy - for compatibility)
@@ -55,6 +377,34 @@ pub struct Mapper {
     key_root: u8,
     /// Current mode (true = major, false = minor)
     is_major: bool,
+    /// Inversion choice for smooth voice leading between chords
+    voice_leader: VoiceLeader,
+    /// Play chords as six-string guitar shapes instead of stacked intervals
+    guitar_voicing: bool,
+    /// Stagger stacked chord notes in strum direction (guitar shapes always are)
+    strum_stagger: bool,
+    /// Delay between successive notes of a strummed chord
+    strum_spread_ms: f32,
+    /// Velocity of each strum
+    dynamics: Dynamics,
+    /// Input that holds palm mute, and whether it is engaged
+    palm_mute_trigger: PalmMuteTrigger,
+    palm_muted: bool,
+    /// Sustain pedal state last sent (as CC64)
+    sustain_pedal: bool,
+    /// Notes the whammy bends, and the note last bent alone (reset on release)
+    bend_target: BendTarget,
+    bent_note: Option<u8>,
+    /// Split whammy: push bends down, pull applies `whammy_pull`
+    whammy_split: bool,
+    whammy_pull: WhammyPullEffect,
+    /// Modulation wheel value last sent (vibrato from a pulled whammy)
+    modulation: u8,
+    /// What the whammy controls, and the controller value it last sent
+    whammy_mode: WhammyMode,
+    whammy_control: Option<(WhammyMode, u8)>,
+    /// Chord struck since the last `take_played_chord`
+    played_chord: Option<PlayedChord>,
 }
 
 impl Mapper {
@@ -67,6 +417,22 @@ impl Mapper {
             active_notes: Vec::new(),
             key_root: 4, // Default to E
             is_major: true, // Default to Major
+            voice_leader: VoiceLeader::new(genre.voice_leading_default()),
+            guitar_voicing: false,
+            strum_stagger: false,
+            strum_spread_ms: guitar_voicing::DEFAULT_STRUM_SPREAD_MS,
+            dynamics: Dynamics::default(),
+            palm_mute_trigger: PalmMuteTrigger::Off,
+            palm_muted: false,
+            sustain_pedal: false,
+            bend_target: BendTarget::AllNotes,
+            bent_note: None,
+            whammy_split: false,
+            whammy_pull: WhammyPullEffect::Vibrato,
+            modulation: 0,
+            whammy_mode: WhammyMode::PitchBend,
+            whammy_control: None,
+            played_chord: None,
         }
     }
     
@@ -80,6 +446,22 @@ impl Mapper {
             active_notes: Vec::new(),
             key_root: key_root % 12,
             is_major,
+            voice_leader: VoiceLeader::new(genre.voice_leading_default()),
+            guitar_voicing: false,
+            strum_stagger: false,
+            strum_spread_ms: guitar_voicing::DEFAULT_STRUM_SPREAD_MS,
+            dynamics: Dynamics::default(),
+            palm_mute_trigger: PalmMuteTrigger::Off,
+            palm_muted: false,
+            sustain_pedal: false,
+            bend_target: BendTarget::AllNotes,
+            bent_note: None,
+            whammy_split: false,
+            whammy_pull: WhammyPullEffect::Vibrato,
+            modulation: 0,
+            whammy_mode: WhammyMode::PitchBend,
+            whammy_control: None,
+            played_chord: None,
         }
     }
 
@@ -87,6 +469,20 @@ impl Mapper {
     pub fn process(&mut self, state: &ControllerState) -> Vec<MusicEvent> {
         let mut events = Vec::new();
 
+        // Palm mute goes first so a chord strummed in the same update is damped
+        let palm_muted = self.palm_mute_trigger.is_engaged(state);
+        if palm_muted != self.palm_muted {
+            self.palm_muted = palm_muted;
+            events.push(MusicEvent::PalmMute(palm_muted));
+        }
+
+        // Sustain pedal goes to the engine as CC64, which holds note-offs while down
+        let sustain_pedal = state.buttons.get(&ControlId::SustainPedal).copied().unwrap_or(false);
+        if sustain_pedal != self.sustain_pedal {
+            self.sustain_pedal = sustain_pedal;
+            events.push(MusicEvent::ControlChange { cc: SUSTAIN_PEDAL_CC, value: if sustain_pedal { 127 } else { 0 } });
+        }
+
         // Get current fret combination
         let frets = state.pressed_frets();
         
@@ -112,20 +508,34 @@ impl Mapper {
                 // The chord.root is an offset from E (which is 0 in the chord system)
                 // We need to add our key_root to transpose it
                 let base_note = 40 + self.key_root; // E2 (40) + key_root offset
-                let notes = chord.to_midi_notes(base_note);
-                let velocity = 100; // TODO: Calculate from strum velocity
+                let velocity = self.dynamics.strum_velocity(state);
                 
-                for note in &notes {
-                    events.push(MusicEvent::NoteOn {
-                        note: *note,
-                        velocity,
+                let notes = if self.guitar_voicing {
+                    chord.guitar_voicing(base_note).notes()
+                } else {
+                    self.voice_leader.voice(chord.to_midi_notes(base_note))
+                };
+                // Guitar shapes always sweep across the strings; stacked chords only with stagger on
+                let spread_ms = if self.guitar_voicing || self.strum_stagger { self.strum_spread_ms } else { 0.0 };
+                let direction = if state.buttons.get(&ControlId::StrumUp).copied().unwrap_or(false) {
+                    StrumDirection::Up
+                } else {
+                    StrumDirection::Down
+                };
+                for (note, delay_ms) in guitar_voicing::strum_order(&notes, direction, spread_ms) {
+                    events.push(if delay_ms > 0.0 {
+                        MusicEvent::StrumNoteOn { note, velocity, delay_ms }
+                    } else {
+                        MusicEvent::NoteOn { note, velocity }
                     });
-                    self.active_notes.push(*note);
+                    self.active_notes.push(note);
                 }
+                self.played_chord = Some(played_chord(chord.name(base_note), &notes));
             } else {
                 // No frets pressed or invalid combo - play single note
                 let note = 40 + self.key_root;
-                events.push(MusicEvent::NoteOn { note, velocity: 100 });
+                let velocity = self.dynamics.strum_velocity(state);
+                events.push(MusicEvent::NoteOn { note, velocity });
                 self.active_notes.push(note);
             }
             
@@ -148,7 +558,11 @@ impl Mapper {
             // Play new chord immediately
             if let Some(chord) = self.fret_combo_to_chord(&frets) {
                 let base_note = 40 + self.key_root;
-                let notes = chord.to_midi_notes(base_note);
+                let notes = if self.guitar_voicing {
+                    chord.guitar_voicing(base_note).notes()
+                } else {
+                    self.voice_leader.voice(chord.to_midi_notes(base_note))
+                };
                 let velocity = 100;
                 
                 for note in &notes {
@@ -158,6 +572,7 @@ impl Mapper {
                     });
                     self.active_notes.push(*note);
                 }
+                self.played_chord = Some(played_chord(chord.name(base_note), &notes));
             } else {
                 let note = 40 + self.key_root;
                 events.push(MusicEvent::NoteOn { note, velocity: 100 });
@@ -168,15 +583,69 @@ impl Mapper {
         }
 
         // Handle whammy bar for pitch bend
-        let whammy = state.axis(ControlId::WhammyBar);
-        if whammy.abs() > 0.01 {
-            let bend_amount = (whammy * 8191.0) as i16;
-            events.push(MusicEvent::PitchBend(bend_amount));
+        let mut whammy = state.axis(ControlId::WhammyBar);
+        if self.whammy_mode != WhammyMode::PitchBend && self.modulation != 0 {
+            // The mode changed while a split whammy was pulled
+            self.modulation = 0;
+            events.push(MusicEvent::ControlChange { cc: MODULATION_CC, value: 0 });
+        }
+        send_whammy_control(self.whammy_mode, whammy, &mut self.whammy_control, &mut events);
+        if self.whammy_mode != WhammyMode::PitchBend {
+            return events;
+        }
+        if self.whammy_split {
+            whammy = self.split_whammy(whammy, &mut events);
+        } else if self.modulation != 0 {
+            // Split was just switched off while pulled
+            self.modulation = 0;
+            events.push(MusicEvent::ControlChange { cc: MODULATION_CC, value: 0 });
+        }
+        match self.bend_target {
+            BendTarget::AllNotes => {
+                if whammy.abs() > 0.01 {
+                    let bend_amount = (whammy * 8191.0) as i16;
+                    events.push(MusicEvent::PitchBend(bend_amount));
+                }
+            }
+            BendTarget::TopNote => {
+                let top_note = self.active_notes.iter().copied().max();
+                if let Some(note) = top_note.filter(|_| whammy.abs() > 0.01) {
+                    // A new chord may have a different top note; straighten the old one
+                    if let Some(previous) = self.bent_note.filter(|&previous| previous != note) {
+                        events.push(MusicEvent::NotePitchBend { note: previous, amount: 0 });
+                    }
+                    events.push(MusicEvent::NotePitchBend { note, amount: (whammy * 8191.0) as i16 });
+                    self.bent_note = Some(note);
+                } else if let Some(note) = self.bent_note.take() {
+                    if self.active_notes.contains(&note) {
+                        events.push(MusicEvent::NotePitchBend { note, amount: 0 });
+                    }
+                }
+            }
         }
 
         events
     }
 
+    /// Split a signed whammy value (push > 0, pull < 0) into a downward bend
+    /// and the pull effect; returns the bend to apply
+    fn split_whammy(&mut self, whammy: f32, events: &mut Vec<MusicEvent>) -> f32 {
+        let push = whammy.max(0.0);
+        let pull = (-whammy).max(0.0);
+        let modulation = match self.whammy_pull {
+            WhammyPullEffect::Vibrato => (pull * 127.0).round().min(127.0) as u8,
+            _ => 0,
+        };
+        if modulation != self.modulation {
+            self.modulation = modulation;
+            events.push(MusicEvent::ControlChange { cc: MODULATION_CC, value: modulation });
+        }
+        match self.whammy_pull {
+            WhammyPullEffect::BendUp => pull - push,
+            _ => -push,
+        }
+    }
+
     /// Map fret combination to a chord
     fn fret_combo_to_chord(&self, frets: &[ControlId]) -> Option<Chord> {
         if frets.is_empty() {
@@ -205,10 +674,85 @@ impl Mapper {
         Some(chord)
     }
 
+    /// Fresh mapper with the same genre, key, pattern and voicing settings
+    /// but no notes playing (used to replay captured input)
+    pub fn replay_copy(&self) -> Self {
+        let mut mapper = Self::new_with_key_mode(self.genre, self.key_root, self.is_major);
+        mapper.pattern_index = self.pattern_index;
+        mapper.set_voice_leading(self.voice_leading());
+        mapper.set_guitar_voicing(self.guitar_voicing, self.strum_spread_ms);
+        mapper.strum_stagger = self.strum_stagger;
+        mapper.dynamics.set_mode(self.dynamics.mode());
+        mapper.palm_mute_trigger = self.palm_mute_trigger;
+        mapper.bend_target = self.bend_target;
+        mapper.whammy_split = self.whammy_split;
+        mapper.whammy_pull = self.whammy_pull;
+        mapper.whammy_mode = self.whammy_mode;
+        mapper
+    }
+
+    /// Chord struck since the previous call (None when no chord was)
+    pub fn take_played_chord(&mut self) -> Option<PlayedChord> {
+        self.played_chord.take()
+    }
+
+    /// Chord symbol played by a fret combination (None when nothing is mapped)
+    pub fn chord_name(&self, frets: &[ControlId]) -> Option<String> {
+        self.fret_combo_to_chord(frets).map(|chord| chord.name(40 + self.key_root))
+    }
+
+    /// Fret combinations mapped by the current pattern with their chord symbols
+    pub fn chord_names(&self) -> Vec<(Vec<ControlId>, String)> {
+        self.mapped_combos()
+            .into_iter()
+            .filter_map(|frets| self.chord_name(&frets).map(|name| (frets, name)))
+            .collect()
+    }
+
+    fn mapped_combos(&self) -> Vec<Vec<ControlId>> {
+        let patterns = self.genre.get_patterns();
+        if patterns.is_empty() {
+            return Vec::new();
+        }
+        let pattern = &patterns[self.pattern_index % patterns.len()];
+        pattern.mappings.iter().map(|(frets, _)| frets.clone()).collect()
+    }
+
+    /// Copy with another genre, key and mode, for previewing a change before applying it
+    /// (keeps the pattern when the genre stays the same)
+    pub fn with_settings(&self, genre: LegacyGenre, key_root: u8, is_major: bool) -> Self {
+        let mut mapper = self.replay_copy();
+        if genre != self.genre {
+            mapper.set_genre(genre);
+        }
+        mapper.set_key_root(key_root);
+        mapper.set_mode(is_major);
+        mapper
+    }
+
+    /// Fret combinations (mapped by either side) that play a different chord under `other`
+    pub fn diff(&self, other: &Mapper) -> Vec<ChordChange> {
+        let same_combo = |a: &[ControlId], b: &[ControlId]| a.len() == b.len() && a.iter().all(|f| b.contains(f));
+        let mut combos: Vec<Vec<ControlId>> = Vec::new();
+        for frets in self.mapped_combos().into_iter().chain(other.mapped_combos()) {
+            if !combos.iter().any(|existing| same_combo(existing, &frets)) {
+                combos.push(frets);
+            }
+        }
+        combos
+            .into_iter()
+            .filter_map(|frets| {
+                let (from, to) = (self.chord_name(&frets), other.chord_name(&frets));
+                (from != to).then_some(ChordChange { frets, from, to })
+            })
+            .collect()
+    }
+
     /// Change genre
     pub fn set_genre(&mut self, genre: LegacyGenre) {
         self.genre = genre;
         self.pattern_index = 0;
+        self.voice_leader.set_enabled(genre.voice_leading_default());
     }
 
     /// Get current genre
@@ -236,6 +780,92 @@ impl Mapper {
         self.is_major
     }
 
+    /// Enable or disable voice leading (smooth inversions between chords)
+    pub fn set_voice_leading(&mut self, enabled: bool) {
+        self.voice_leader.set_enabled(enabled);
+    }
+    
+    /// Whether voice leading is enabled
+    pub fn voice_leading(&self) -> bool {
+        self.voice_leader.is_enabled()
+    }
+
+    /// Play chords as guitar shapes, strummed string by string `strum_spread_ms` apart
+    pub fn set_guitar_voicing(&mut self, enabled: bool, strum_spread_ms: f32) {
+        self.guitar_voicing = enabled;
+        self.strum_spread_ms = strum_spread_ms.clamp(0.0, guitar_voicing::MAX_STRUM_SPREAD_MS);
+    }
+
+    /// Whether guitar voicings are enabled
+    pub fn guitar_voicing(&self) -> bool {
+        self.guitar_voicing
+    }
+
+    /// Stagger the notes of strummed chords `spread_ms` apart (5-20ms), low to
+    /// high on a down strum and high to low on an up strum
+    pub fn set_strum_stagger(&mut self, enabled: bool, spread_ms: f32) {
+        self.strum_stagger = enabled;
+        self.strum_spread_ms = spread_ms.clamp(guitar_voicing::MIN_STRUM_STAGGER_MS, guitar_voicing::MAX_STRUM_SPREAD_MS);
+    }
+
+    /// Whether strum stagger is enabled for stacked chords
+    pub fn strum_stagger(&self) -> bool {
+        self.strum_stagger
+    }
+
+    /// Delay between successive notes when strumming
+    pub fn strum_spread_ms(&self) -> f32 {
+        self.strum_spread_ms
+    }
+
+    /// Choose what sets the velocity of each strum
+    pub fn set_dynamics_mode(&mut self, mode: DynamicsMode) {
+        self.dynamics.set_mode(mode);
+    }
+
+    pub fn dynamics_mode(&self) -> DynamicsMode {
+        self.dynamics.mode()
+    }
+
+    /// Choose the input that holds palm mute (picked up on the next update)
+    pub fn set_palm_mute_trigger(&mut self, trigger: PalmMuteTrigger) {
+        self.palm_mute_trigger = trigger;
+    }
+
+    pub fn palm_mute_trigger(&self) -> PalmMuteTrigger {
+        self.palm_mute_trigger
+    }
+
+    /// Choose whether the whammy bends the whole chord or just its top note
+    pub fn set_bend_target(&mut self, target: BendTarget) {
+        self.bend_target = target;
+        self.bent_note = None;
+    }
+
+    pub fn bend_target(&self) -> BendTarget {
+        self.bend_target
+    }
+
+    /// Split the whammy at its center (needs a split calibration or a
+    /// bar reporting -1.0 to 1.0): push bends down, pull applies `pull`
+    pub fn set_whammy_split(&mut self, enabled: bool, pull: WhammyPullEffect) {
+        self.whammy_split = enabled;
+        self.whammy_pull = pull;
+    }
+
+    pub fn whammy_split(&self) -> (bool, WhammyPullEffect) {
+        (self.whammy_split, self.whammy_pull)
+    }
+
+    /// Choose what the whammy controls (the split and bend target only apply to pitch bend)
+    pub fn set_whammy_mode(&mut self, mode: WhammyMode) {
+        self.whammy_mode = mode;
+    }
+
+    pub fn whammy_mode(&self) -> WhammyMode {
+        self.whammy_mode
+    }
+
     /// Cycle to next pattern
     pub fn next_pattern(&mut self) {
         let patterns = self.genre.get_patterns();
@@ -269,6 +899,7 @@ impl Mapper {
             events.push(MusicEvent::NoteOff { note: *note });
         }
         self.active_notes.clear();
+        self.voice_leader.reset();
         
         events.push(MusicEvent::PanicAllNotesOff);
         events
@@ -287,7 +918,7 @@ mod tests {
 
     #[test]
     fn test_mapper_creation() {
-        let mapper = Mapper::new(Genre::Rock);
+        let mapper = Mapper::new(LegacyGenre::Rock);
         assert_eq!(mapper.pattern_index(), 0);
     }
 
@@ -302,4 +933,188 @@ mod tests {
         mapper.prev_pattern();
         assert_eq!(mapper.pattern_index(), initial);
     }
+
+    #[test]
+    fn test_mapping_diff_lists_changed_frets() {
+        let mapper = Mapper::new_with_key_mode(LegacyGenre::Rock, 0, true);
+        assert!(mapper.diff(&mapper.with_settings(LegacyGenre::Rock, 0, true)).is_empty());
+
+        // Moving up a whole step changes every mapped chord
+        let up = mapper.with_settings(LegacyGenre::Rock, 2, true);
+        let changes = mapper.diff(&up);
+        assert_eq!(changes.len(), mapper.chord_names().len());
+        let green = changes.iter().find(|change| change.frets == vec![ControlId::FretGreen]).unwrap();
+        assert_eq!(green.from, mapper.chord_name(&[ControlId::FretGreen]));
+        assert_eq!(green.to, up.chord_name(&[ControlId::FretGreen]));
+        assert_ne!(green.from, green.to);
+    }
+
+    #[test]
+    fn test_struck_chord_is_reported_once() {
+        let mut mapper = Mapper::new_with_key_mode(LegacyGenre::Rock, 0, true);
+        let mut state = ControllerState::default();
+        state.buttons.insert(ControlId::FretGreen, true);
+        mapper.process(&state);
+        assert_eq!(mapper.take_played_chord(), None);
+
+        state.buttons.insert(ControlId::StrumDown, true);
+        let events = mapper.process(&state);
+        let played = mapper.take_played_chord().unwrap();
+        assert_eq!(Some(played.name), mapper.chord_name(&[ControlId::FretGreen]));
+        let mut notes: Vec<u8> = events.iter().filter_map(|event| match event {
+            MusicEvent::NoteOn { note, .. } | MusicEvent::StrumNoteOn { note, .. } => Some(*note),
+            _ => None,
+        }).collect();
+        notes.sort_unstable();
+        assert_eq!(played.notes, notes);
+        assert_eq!(mapper.take_played_chord(), None);
+    }
+
+    #[test]
+    fn test_guitar_voicing_strums_strings() {
+        let mut mapper = Mapper::new(LegacyGenre::Rock);
+        mapper.set_guitar_voicing(true, 10.0);
+
+        let mut state = ControllerState::default();
+        state.buttons.insert(ControlId::FretGreen, true);
+        state.buttons.insert(ControlId::StrumDown, true);
+        let events = mapper.process(&state);
+
+        let delays: Vec<f32> = events.iter().filter_map(|event| match event {
+            MusicEvent::NoteOn { .. } => Some(0.0),
+            MusicEvent::StrumNoteOn { delay_ms, .. } => Some(*delay_ms),
+            _ => None,
+        }).collect();
+        assert!(delays.len() >= 3);
+        assert_eq!(delays[0], 0.0);
+        assert_eq!(delays[1], 10.0);
+        assert!(delays.windows(2).all(|pair| pair[1] > pair[0]));
+    }
+
+    #[test]
+    fn test_strum_stagger_follows_direction() {
+        let mut mapper = Mapper::new(LegacyGenre::Rock);
+        mapper.set_voice_leading(false);
+        let mut state = ControllerState::default();
+        state.buttons.insert(ControlId::FretGreen, true);
+        state.buttons.insert(ControlId::StrumUp, true);
+
+        // Off: the whole chord starts at once
+        assert!(mapper.process(&state).iter().all(|event| !matches!(event, MusicEvent::StrumNoteOn { .. })));
+
+        mapper.set_strum_stagger(true, 50.0);
+        assert_eq!(mapper.strum_spread_ms(), guitar_voicing::MAX_STRUM_SPREAD_MS);
+        state.buttons.insert(ControlId::StrumUp, false);
+        mapper.process(&state);
+        state.buttons.insert(ControlId::StrumUp, true);
+        let onsets: Vec<(u8, f32)> = mapper.process(&state).iter().filter_map(|event| match event {
+            MusicEvent::NoteOn { note, .. } => Some((*note, 0.0)),
+            MusicEvent::StrumNoteOn { note, delay_ms, .. } => Some((*note, *delay_ms)),
+            _ => None,
+        }).collect();
+        // Up strum: highest note first
+        assert!(onsets.len() >= 2);
+        assert!(onsets.windows(2).all(|pair| pair[0].0 > pair[1].0 && pair[1].1 > pair[0].1));
+    }
+
+    #[test]
+    fn test_palm_mute_precedes_strum() {
+        let mut mapper = Mapper::new(LegacyGenre::Rock);
+        let mut state = ControllerState::default();
+        state.buttons.insert(ControlId::Select, true);
+        assert!(mapper.process(&state).is_empty());
+
+        mapper.set_palm_mute_trigger(PalmMuteTrigger::SelectHeld);
+        state.buttons.insert(ControlId::FretGreen, true);
+        state.buttons.insert(ControlId::StrumDown, true);
+        let events = mapper.process(&state);
+        assert!(matches!(events[0], MusicEvent::PalmMute(true)));
+        assert!(events[1..].iter().any(|event| matches!(event, MusicEvent::NoteOn { .. })));
+
+        // Only changes are reported
+        assert!(!mapper.process(&state).iter().any(|event| matches!(event, MusicEvent::PalmMute(_))));
+        state.buttons.insert(ControlId::Select, false);
+        assert!(matches!(mapper.process(&state)[0], MusicEvent::PalmMute(false)));
+
+        mapper.set_palm_mute_trigger(PalmMuteTrigger::TiltDown);
+        state.axes.insert(ControlId::TiltSensor, -0.8);
+        assert!(matches!(mapper.process(&state)[0], MusicEvent::PalmMute(true)));
+        assert_eq!(PalmMuteTrigger::from_name("tilt_down"), Some(PalmMuteTrigger::TiltDown));
+    }
+
+    #[test]
+    fn test_top_note_bend_leaves_chord_in_tune() {
+        let mut mapper = Mapper::new(LegacyGenre::Rock);
+        mapper.set_bend_target(BendTarget::TopNote);
+        let mut state = ControllerState::default();
+        state.buttons.insert(ControlId::FretGreen, true);
+        state.buttons.insert(ControlId::StrumDown, true);
+        mapper.process(&state);
+        let top = *mapper.active_notes.iter().max().unwrap();
+
+        state.axes.insert(ControlId::WhammyBar, 0.5);
+        let events = mapper.process(&state);
+        assert!(!events.iter().any(|event| matches!(event, MusicEvent::PitchBend(_))));
+        assert!(matches!(events[..], [MusicEvent::NotePitchBend { note, amount }] if note == top && amount > 0));
+
+        // Releasing the whammy straightens the bent note once
+        state.axes.insert(ControlId::WhammyBar, 0.0);
+        assert!(matches!(mapper.process(&state)[..], [MusicEvent::NotePitchBend { note, amount: 0 }] if note == top));
+        assert!(mapper.process(&state).is_empty());
+        assert_eq!(BendTarget::from_name("top_note"), Some(BendTarget::TopNote));
+    }
+
+    #[test]
+    fn test_split_whammy_push_bends_down_pull_adds_vibrato() {
+        let mut mapper = Mapper::new(LegacyGenre::Rock);
+        mapper.set_whammy_split(true, WhammyPullEffect::Vibrato);
+        let mut state = ControllerState::default();
+        state.axes.insert(ControlId::WhammyBar, 0.5);
+        assert!(matches!(mapper.process(&state)[..], [MusicEvent::PitchBend(amount)] if amount < 0));
+
+        state.axes.insert(ControlId::WhammyBar, -1.0);
+        assert!(matches!(mapper.process(&state)[..], [MusicEvent::ControlChange { cc: MODULATION_CC, value: 127 }]));
+        // Back at the center the vibrato is switched off once
+        state.axes.insert(ControlId::WhammyBar, 0.0);
+        assert!(matches!(mapper.process(&state)[..], [MusicEvent::ControlChange { cc: MODULATION_CC, value: 0 }]));
+        assert!(mapper.process(&state).is_empty());
+
+        mapper.set_whammy_split(true, WhammyPullEffect::BendUp);
+        state.axes.insert(ControlId::WhammyBar, -0.5);
+        assert!(matches!(mapper.process(&state)[..], [MusicEvent::PitchBend(amount)] if amount > 0));
+    }
+
+    #[test]
+    fn test_whammy_mode_sends_controller() {
+        let mut mapper = Mapper::new(LegacyGenre::Rock);
+        mapper.set_whammy_mode(WhammyMode::FilterCutoff);
+        let mut state = ControllerState::default();
+        // The filter starts open, and pressing closes it instead of bending
+        assert!(matches!(mapper.process(&state)[..], [MusicEvent::ControlChange { cc: FILTER_CUTOFF_CC, value: 127 }]));
+        state.axes.insert(ControlId::WhammyBar, 1.0);
+        assert!(matches!(mapper.process(&state)[..], [MusicEvent::ControlChange { cc: FILTER_CUTOFF_CC, value: 0 }]));
+        assert!(mapper.process(&state).is_empty());
+
+        // Switching mode opens the filter again before the new controller
+        mapper.set_whammy_mode(WhammyMode::Vibrato);
+        let events = mapper.process(&state);
+        assert!(matches!(events[..], [
+            MusicEvent::ControlChange { cc: FILTER_CUTOFF_CC, value: 127 },
+            MusicEvent::ControlChange { cc: MODULATION_CC, value: 127 },
+        ]));
+        assert_eq!(WhammyMode::from_name("volume_swell").and_then(|mode| mode.cc()), Some(EXPRESSION_CC));
+    }
+
+    #[test]
+    fn test_sustain_pedal_sends_cc64() {
+        let mut mapper = Mapper::new(LegacyGenre::Rock);
+        let mut state = ControllerState::default();
+        state.buttons.insert(ControlId::SustainPedal, true);
+        let events = mapper.process(&state);
+        assert!(matches!(events[..], [MusicEvent::ControlChange { cc: SUSTAIN_PEDAL_CC, value: 127 }]));
+        assert!(mapper.process(&state).is_empty());
+
+        state.buttons.insert(ControlId::SustainPedal, false);
+        assert!(matches!(mapper.process(&state)[..], [MusicEvent::ControlChange { cc: SUSTAIN_PEDAL_CC, value: 0 }]));
+    }
 }
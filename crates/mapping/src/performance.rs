@@ -268,6 +268,7 @@ impl PerformanceEngine {
         self.whammy_smooth_buffer.push(normalized_whammy);
         let smoothed_whammy = self.whammy_smooth_buffer.average();
         
+        let was_active = self.state.whammy_value > 0.01;
         self.state.whammy_value = smoothed_whammy;
 
         let mut events = Vec::new();
@@ -288,7 +289,7 @@ impl PerformanceEngine {
                 vibrato_depth,
                 filter_cutoff,
             });
-        } else if self.state.whammy_value > 0.01 {
+        } else if was_active {
             // Whammy was active, now stopped
             events.push(PerformanceEvent::WhammyStop);
         }
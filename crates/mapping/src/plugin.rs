@@ -0,0 +1,230 @@
+//! Mapper plugins
+//! Alternative note generation (a generative ambient mode, an arpeggiator,
+//! ...) without forking the app: a `MapperPlugin` gets player input as
+//! snapshots and returns the events to play, taking the player's frets over
+//! from the chord mappers. Plugins are registered by name, either linked in
+//! with `PluginRegistry::register` or, with the `plugins` feature, loaded
+//! from dynamic libraries in a folder.
+//!
+//! A dynamic plugin is a `cdylib` depending on this crate that exports its
+//! constructor with `declare_mapper_plugin!`. It is called through the Rust
+//! ABI, so it must be built with the same toolchain and `mapping` version as
+//! the app; `PLUGIN_ABI_VERSION` rejects libraries built against an older
+//! trait.
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+use serde::Serialize;
+
+use controller::ControllerStateSnapshot;
+
+use crate::MusicEvent;
+
+/// Bumped whenever `MapperPlugin` changes
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+
+/// Symbol returning the `PLUGIN_ABI_VERSION` a library was built against
+pub const ABI_VERSION_SYMBOL: &[u8] = b"mityguitar_plugin_abi_version";
+/// Symbol of the `PluginFactory`
+pub const FACTORY_SYMBOL: &[u8] = b"mityguitar_create_mapper";
+
+/// Note generation that replaces the chord mappers for a player
+pub trait MapperPlugin: Send {
+    /// Name the plugin is registered and chosen by
+    fn name(&self) -> &str;
+
+    /// Events for the player's current input (called on every poll)
+    fn process(&mut self, snapshot: &ControllerStateSnapshot) -> Vec<MusicEvent>;
+
+    /// Events that silence whatever the plugin left sounding (when it is switched off)
+    fn panic(&mut self) -> Vec<MusicEvent> {
+        vec![MusicEvent::PanicAllNotesOff]
+    }
+}
+
+/// Creates a plugin instance (one per player using it)
+pub type PluginFactory = fn() -> Box<dyn MapperPlugin>;
+
+/// Export a dynamic plugin's constructor, e.g.
+/// `declare_mapper_plugin!(AmbientMapper::new);`
+#[macro_export]
+macro_rules! declare_mapper_plugin {
+    ($constructor:path) => {
+        #[no_mangle]
+        pub extern "C" fn mityguitar_plugin_abi_version() -> u32 {
+            $crate::plugin::PLUGIN_ABI_VERSION
+        }
+
+        #[no_mangle]
+        pub fn mityguitar_create_mapper() -> Box<dyn $crate::plugin::MapperPlugin> {
+            Box::new($constructor())
+        }
+    };
+}
+
+/// A registered plugin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PluginInfo {
+    pub name: String,
+    /// Library it was loaded from (None = linked in)
+    pub path: Option<PathBuf>,
+}
+
+/// Plugins available by name
+#[derive(Default)]
+pub struct PluginRegistry {
+    plugins: BTreeMap<String, (PluginFactory, Option<PathBuf>)>,
+}
+
+impl PluginRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a linked-in plugin under the name its instances report
+    pub fn register(&mut self, factory: PluginFactory) -> String {
+        self.insert(factory, None)
+    }
+
+    fn insert(&mut self, factory: PluginFactory, path: Option<PathBuf>) -> String {
+        let name = factory().name().to_string();
+        if self.plugins.insert(name.clone(), (factory, path)).is_some() {
+            log::warn!("🧩 Plugin '{}' registered twice, keeping the last one", name);
+        }
+        name
+    }
+
+    /// New instance of a plugin
+    pub fn create(&self, name: &str) -> Option<Box<dyn MapperPlugin>> {
+        self.plugins.get(name).map(|(factory, _)| factory())
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.plugins.contains_key(name)
+    }
+
+    /// Registered plugins by name
+    pub fn list(&self) -> Vec<PluginInfo> {
+        self.plugins.iter()
+            .map(|(name, (_, path))| PluginInfo { name: name.clone(), path: path.clone() })
+            .collect()
+    }
+
+    /// Load the dynamic libraries in a folder that aren't loaded yet; returns
+    /// the names of the plugins loaded (libraries that fail are logged and skipped)
+    pub fn load_dir(&mut self, dir: &Path) -> Vec<String> {
+        let Ok(entries) = std::fs::read_dir(dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+            .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some(std::env::consts::DLL_EXTENSION))
+            .filter(|path| !self.plugins.values().any(|(_, loaded)| loaded.as_ref() == Some(path)))
+            .collect();
+        paths.sort();
+
+        let mut loaded = Vec::new();
+        for path in paths {
+            match self.load_library(&path) {
+                Ok(name) => {
+                    log::info!("🧩 Loaded mapper plugin '{}' from {}", name, path.display());
+                    loaded.push(name);
+                }
+                Err(e) => log::warn!("Failed to load plugin {}: {}", path.display(), e),
+            }
+        }
+        loaded
+    }
+
+    /// Load one dynamic library and register its plugin
+    #[cfg(feature = "plugins")]
+    pub fn load_library(&mut self, path: &Path) -> anyhow::Result<String> {
+        // SAFETY: loading runs the library's initialisers; plugins are trusted
+        // code the user put in the plugin folder
+        let library = unsafe { libloading::Library::new(path)? };
+        // SAFETY: the symbols are the ones `declare_mapper_plugin!` exports, and
+        // the ABI version is checked before the factory's signature is relied on
+        let factory = unsafe {
+            let version = library.get::<extern "C" fn() -> u32>(ABI_VERSION_SYMBOL)?();
+            if version != PLUGIN_ABI_VERSION {
+                anyhow::bail!("built for plugin ABI {}, the app uses {}", version, PLUGIN_ABI_VERSION);
+            }
+            *library.get::<PluginFactory>(FACTORY_SYMBOL)?
+        };
+        // Plugin instances can outlive the registry, so the library is never unloaded
+        std::mem::forget(library);
+        Ok(self.insert(factory, Some(path.to_path_buf())))
+    }
+
+    /// Load one dynamic library and register its plugin
+    #[cfg(not(feature = "plugins"))]
+    pub fn load_library(&mut self, _path: &Path) -> anyhow::Result<String> {
+        anyhow::bail!("built without dynamic plugin support (the `plugins` feature)")
+    }
+}
+
+impl std::fmt::Debug for PluginRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.plugins.keys()).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Plays middle C while green is held
+    struct Drone {
+        sounding: bool,
+    }
+
+    impl Drone {
+        fn new() -> Self {
+            Self { sounding: false }
+        }
+    }
+
+    impl MapperPlugin for Drone {
+        fn name(&self) -> &str {
+            "drone"
+        }
+
+        fn process(&mut self, snapshot: &ControllerStateSnapshot) -> Vec<MusicEvent> {
+            if snapshot.fret_green == self.sounding {
+                return Vec::new();
+            }
+            self.sounding = snapshot.fret_green;
+            vec![if self.sounding { MusicEvent::NoteOn { note: 60, velocity: 100 } } else { MusicEvent::NoteOff { note: 60 } }]
+        }
+    }
+
+    #[test]
+    fn test_registry_creates_independent_instances() {
+        let mut registry = PluginRegistry::new();
+        assert_eq!(registry.register(|| Box::new(Drone::new())), "drone");
+        assert!(registry.contains("drone"));
+        assert!(registry.create("ambient").is_none());
+        assert_eq!(registry.list(), vec![PluginInfo { name: "drone".to_string(), path: None }]);
+
+        let mut first = registry.create("drone").unwrap();
+        let mut second = registry.create("drone").unwrap();
+        let held = ControllerStateSnapshot { fret_green: true, ..Default::default() };
+        assert!(matches!(first.process(&held)[..], [MusicEvent::NoteOn { note: 60, .. }]));
+        assert!(first.process(&held).is_empty());
+        // The second player's instance has its own state
+        assert_eq!(second.process(&held).len(), 1);
+        assert!(matches!(first.process(&ControllerStateSnapshot::default())[..], [MusicEvent::NoteOff { note: 60 }]));
+        assert!(matches!(first.panic()[..], [MusicEvent::PanicAllNotesOff]));
+    }
+
+    #[test]
+    fn test_load_dir_skips_missing_and_foreign_files() {
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join("readme.txt"), "not a plugin").unwrap();
+        let mut registry = PluginRegistry::new();
+        assert!(registry.load_dir(dir.path()).is_empty());
+        assert!(registry.load_dir(&dir.path().join("missing")).is_empty());
+        assert!(registry.list().is_empty());
+    }
+}
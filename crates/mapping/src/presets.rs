@@ -4,7 +4,7 @@ use anyhow::{Result, Context};
 
 use crate::harmonic::{
     Genre, GenrePreset, HarmonicRole, ChordQuality,
-    WhammyDefaults, SustainDefaults
+    WhammyDefaults, SustainDefaults, LayerDefaults
 };
 use crate::resolution::ChordResolver;
 
@@ -37,6 +37,15 @@ impl PresetLoader {
         Ok(resolver)
     }
 
+    /// Resolver with the built-in preset of every genre (no files needed)
+    pub fn default_resolver() -> ChordResolver {
+        let mut resolver = ChordResolver::new();
+        for genre in Genre::all() {
+            resolver.load_preset(*genre, Self::create_default_preset(*genre));
+        }
+        resolver
+    }
+
     /// Load a specific genre preset from JSON file
     async fn load_preset(&self, genre: Genre) -> Result<GenrePreset> {
         let filename = format!("{}.json", genre.name().to_lowercase());
@@ -62,6 +71,9 @@ impl PresetLoader {
         
         let json = serde_json::to_string_pretty(preset)
             .with_context(|| format!("Failed to serialize preset for {}", genre.name()))?;
+        
+        // Chord overrides live in these files, keep the previous versions
+        controller::profile_backup::backup_before_write(&preset_path, controller::profile_backup::DEFAULT_BACKUP_VERSIONS)?;
             
         tokio::fs::write(&preset_path, json)
             .await
@@ -132,6 +144,7 @@ impl PresetLoader {
                 vibrato_depth: 0.0,
                 filter_cutoff_enabled: false,
                 smoothing_factor: 0.8,
+                deadzone: 0.05,
             },
             Genre::Rock => WhammyDefaults {
                 enabled: true,
@@ -139,6 +152,7 @@ impl PresetLoader {
                 vibrato_depth: 0.1,
                 filter_cutoff_enabled: true,
                 smoothing_factor: 0.7,
+                deadzone: 0.05,
             },
             Genre::Pop => WhammyDefaults {
                 enabled: true,
@@ -146,6 +160,7 @@ impl PresetLoader {
                 vibrato_depth: 0.05,
                 filter_cutoff_enabled: false,
                 smoothing_factor: 0.9,
+                deadzone: 0.08,
             },
             Genre::Folk => WhammyDefaults {
                 enabled: true,
@@ -153,6 +168,7 @@ impl PresetLoader {
                 vibrato_depth: 0.2,
                 filter_cutoff_enabled: false,
                 smoothing_factor: 0.85,
+                deadzone: 0.08,
             },
             Genre::Edm => WhammyDefaults {
                 enabled: true,
@@ -160,6 +176,7 @@ impl PresetLoader {
                 vibrato_depth: 0.0,
                 filter_cutoff_enabled: true,
                 smoothing_factor: 0.6,
+                deadzone: 0.03,
             },
             Genre::Metal => WhammyDefaults {
                 enabled: true,
@@ -167,6 +184,7 @@ impl PresetLoader {
                 vibrato_depth: 0.0,
                 filter_cutoff_enabled: false,
                 smoothing_factor: 0.75,
+                deadzone: 0.04,
             },
         };
 
@@ -177,6 +195,18 @@ impl PresetLoader {
             role_to_chord_quality,
             whammy_defaults,
             sustain_defaults: SustainDefaults::default(),
+            voice_leading: matches!(genre, Genre::Edm | Genre::Folk | Genre::Pop),
+            // A pad an octave down fills out the thin EDM chords
+            layer: (genre == Genre::Edm).then(|| LayerDefaults {
+                instrument: "Synth Pad".to_string(),
+                gain: 0.4,
+                octave: -1,
+            }),
+            instrument: match genre {
+                Genre::Metal => Some("Distorted Guitar".to_string()),
+                Genre::Folk => Some("Acoustic Guitar".to_string()),
+                _ => None,
+            },
         }
     }
 
@@ -222,6 +252,28 @@ mod tests {
         let loaded_preset = loader.load_preset(Genre::Punk).await.unwrap();
         assert_eq!(preset.name, loaded_preset.name);
         assert_eq!(preset.default_key, loaded_preset.default_key);
+        assert_eq!(preset.layer, loaded_preset.layer);
+    }
+
+    #[test]
+    fn test_layer_is_optional_in_preset_files() {
+        let json = r#"{
+            "name": "Pop", "default_mode": "Major", "default_key": "C",
+            "role_to_chord_quality": {"I": "major"},
+            "whammy_defaults": {"enabled": true, "pitch_bend_range_semitones": 1.0, "vibrato_depth": 0.0,
+                "filter_cutoff_enabled": false, "smoothing_factor": 0.8},
+            "sustain_defaults": {"enabled": true, "release_time_ms": 500.0}
+        }"#;
+        let preset: GenrePreset = serde_json::from_str(json).unwrap();
+        assert!(preset.layer.is_none());
+        assert!(preset.instrument.is_none());
+
+        let json = json.replacen('{', r#"{"layer": {"instrument": "Strings", "octave": 1}, "instrument": "Piano","#, 1);
+        let preset: GenrePreset = serde_json::from_str(&json).unwrap();
+        assert_eq!(preset.instrument.as_deref(), Some("Piano"));
+        let layer = preset.layer.unwrap();
+        assert_eq!((layer.instrument.as_str(), layer.gain, layer.octave), ("Strings", 0.5, 1));
+        assert_eq!(PresetLoader::create_default_preset(Genre::Metal).instrument.as_deref(), Some("Distorted Guitar"));
     }
 
     #[tokio::test]
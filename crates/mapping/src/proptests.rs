@@ -0,0 +1,237 @@
+//! Property tests for the input-to-note state machines
+//! Random sequences of fret, strum, whammy and tilt states are fed through the
+//! Mapper and the PerformanceEngine, checking the invariants stuck notes would
+//! break: note-offs only for sounding notes, the mapper's active notes matching
+//! what actually sounds, everything released once the strum bar is let go, and
+//! panic always silencing.
+
+use proptest::prelude::*;
+use std::collections::HashMap;
+
+use crate::{ChordSpec, FretButton, LegacyGenre, Mapper, MusicEvent, NewChordQuality, Note, PalmMuteTrigger, PerformanceEngine, PerformanceEvent};
+use controller::{ControlId, ControllerState};
+
+const FRETS: [ControlId; 5] = [
+    ControlId::FretGreen,
+    ControlId::FretRed,
+    ControlId::FretYellow,
+    ControlId::FretBlue,
+    ControlId::FretOrange,
+];
+const SOLO_FRETS: [ControlId; 5] = [
+    ControlId::SoloGreen,
+    ControlId::SoloRed,
+    ControlId::SoloYellow,
+    ControlId::SoloBlue,
+    ControlId::SoloOrange,
+];
+
+/// One controller poll
+#[derive(Debug, Clone)]
+struct Input {
+    frets: [bool; 5],
+    solo_frets: [bool; 5],
+    strum_up: bool,
+    strum_down: bool,
+    whammy: f32,
+    tilt: f32,
+    select: bool,
+}
+
+impl Input {
+    fn state(&self) -> ControllerState {
+        let mut state = ControllerState::default();
+        for (control, &pressed) in FRETS.iter().zip(&self.frets).chain(SOLO_FRETS.iter().zip(&self.solo_frets)) {
+            state.buttons.insert(*control, pressed);
+        }
+        state.buttons.insert(ControlId::StrumUp, self.strum_up);
+        state.buttons.insert(ControlId::StrumDown, self.strum_down);
+        state.buttons.insert(ControlId::Select, self.select);
+        state.axes.insert(ControlId::WhammyBar, self.whammy);
+        state.axes.insert(ControlId::TiltSensor, self.tilt);
+        state
+    }
+}
+
+/// Mostly held strum bars and fret changes, like real playing
+fn input() -> impl Strategy<Value = Input> {
+    (
+        prop::array::uniform5(any::<bool>()),
+        prop::array::uniform5(prop::bool::weighted(0.1)),
+        prop::bool::weighted(0.3),
+        prop::bool::weighted(0.3),
+        prop_oneof![Just(0.0f32), -1.0f32..1.0],
+        -1.0f32..1.0,
+        prop::bool::weighted(0.2),
+    )
+        .prop_map(|(frets, solo_frets, strum_up, strum_down, whammy, tilt, select)| Input {
+            frets,
+            solo_frets,
+            strum_up,
+            strum_down,
+            whammy,
+            tilt,
+            select,
+        })
+}
+
+/// Mapper settings to test with
+#[derive(Debug, Clone)]
+struct MapperConfig {
+    genre: LegacyGenre,
+    key_root: u8,
+    is_major: bool,
+    voice_leading: bool,
+    guitar_voicing: bool,
+    strum_stagger: bool,
+    pattern: usize,
+}
+
+impl MapperConfig {
+    fn mapper(&self) -> Mapper {
+        let mut mapper = Mapper::new_with_key_mode(self.genre, self.key_root, self.is_major);
+        mapper.set_voice_leading(self.voice_leading);
+        mapper.set_guitar_voicing(self.guitar_voicing, 12.0);
+        mapper.set_strum_stagger(self.strum_stagger, 12.0);
+        mapper.set_palm_mute_trigger(PalmMuteTrigger::SelectHeld);
+        for _ in 0..self.pattern {
+            mapper.next_pattern();
+        }
+        mapper
+    }
+}
+
+fn mapper_config() -> impl Strategy<Value = MapperConfig> {
+    (
+        prop::sample::select(LegacyGenre::all()),
+        0u8..12,
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        any::<bool>(),
+        0usize..4,
+    )
+        .prop_map(|(genre, key_root, is_major, voice_leading, guitar_voicing, strum_stagger, pattern)| MapperConfig {
+            genre,
+            key_root,
+            is_major,
+            voice_leading,
+            guitar_voicing,
+            strum_stagger,
+            pattern,
+        })
+}
+
+/// Notes sounding according to the events sent so far (note -> voices)
+#[derive(Debug, Default)]
+struct Sounding(HashMap<u8, usize>);
+
+impl Sounding {
+    /// Apply events, failing on a note-off for a note that isn't sounding
+    fn apply(&mut self, events: &[MusicEvent]) -> Result<(), TestCaseError> {
+        for event in events {
+            match *event {
+                MusicEvent::NoteOn { note, .. } | MusicEvent::StrumNoteOn { note, .. } => {
+                    *self.0.entry(note).or_default() += 1;
+                }
+                MusicEvent::NoteOff { note } => {
+                    let voices = self.0.get_mut(&note).filter(|voices| **voices > 0);
+                    prop_assert!(voices.is_some(), "NoteOff for silent note {}", note);
+                    if let Some(voices) = voices {
+                        *voices -= 1;
+                    }
+                }
+                MusicEvent::PanicAllNotesOff => self.0.clear(),
+                _ => {}
+            }
+        }
+        self.0.retain(|_, voices| *voices > 0);
+        Ok(())
+    }
+
+    fn notes(&self) -> Vec<u8> {
+        let mut notes: Vec<u8> = self.0.iter().flat_map(|(&note, &voices)| vec![note; voices]).collect();
+        notes.sort_unstable();
+        notes
+    }
+}
+
+fn full_chord_map(root: Note) -> HashMap<FretButton, ChordSpec> {
+    [FretButton::Green, FretButton::Red, FretButton::Yellow, FretButton::Blue, FretButton::Orange]
+        .into_iter()
+        .map(|fret| (fret, ChordSpec::new(root, NewChordQuality::Power5)))
+        .collect()
+}
+
+proptest! {
+    #[test]
+    fn mapper_never_leaks_notes(config in mapper_config(), inputs in prop::collection::vec(input(), 1..64)) {
+        let mut mapper = config.mapper();
+        let mut sounding = Sounding::default();
+        for input in &inputs {
+            let events = mapper.process(&input.state());
+            sounding.apply(&events)?;
+
+            let mut active = mapper.active_notes.clone();
+            active.sort_unstable();
+            prop_assert_eq!(&active, &sounding.notes());
+            if !input.strum_up && !input.strum_down {
+                prop_assert!(active.is_empty(), "notes left sounding with the strum bar released: {:?}", active);
+            }
+        }
+
+        // Letting go of everything releases every note
+        let release = Input { frets: [false; 5], solo_frets: [false; 5], strum_up: false, strum_down: false, whammy: 0.0, tilt: 0.0, select: false };
+        sounding.apply(&mapper.process(&release.state()))?;
+        prop_assert!(sounding.notes().is_empty());
+    }
+
+    #[test]
+    fn mapper_panic_always_silences(config in mapper_config(), inputs in prop::collection::vec(input(), 1..32)) {
+        let mut mapper = config.mapper();
+        let mut sounding = Sounding::default();
+        for input in &inputs {
+            sounding.apply(&mapper.process(&input.state()))?;
+        }
+        let events = mapper.panic();
+        prop_assert!(matches!(events.last(), Some(MusicEvent::PanicAllNotesOff)));
+        // The explicit note-offs alone already release everything
+        sounding.apply(&events[..events.len() - 1])?;
+        prop_assert!(sounding.notes().is_empty());
+        prop_assert!(mapper.active_notes.is_empty());
+    }
+
+    #[test]
+    fn performance_engine_balances_chords(inputs in prop::collection::vec(input(), 1..64), panic_at in 0usize..64) {
+        let mut engine = PerformanceEngine::new();
+        let (main, solo) = (full_chord_map(Note::E), full_chord_map(Note::A));
+        let mut sounding = false;
+        for (step, input) in inputs.iter().enumerate() {
+            if step == panic_at {
+                prop_assert!(matches!(engine.panic().as_slice(), [PerformanceEvent::Panic]));
+                prop_assert!(!engine.state().is_sustaining);
+                sounding = false;
+                continue;
+            }
+            let events = engine.process_input(&input.state(), &main, &solo);
+            for event in &events {
+                match event {
+                    PerformanceEvent::ChordTrigger { .. } => {
+                        prop_assert!(!sounding, "chord triggered over a sounding chord");
+                        sounding = true;
+                    }
+                    PerformanceEvent::ChordRelease { .. } => {
+                        prop_assert!(sounding, "release without a sounding chord");
+                        sounding = false;
+                    }
+                    PerformanceEvent::WhammyEffect { .. } => prop_assert!(sounding),
+                    _ => {}
+                }
+            }
+            prop_assert_eq!(engine.state().is_sustaining, sounding);
+            if !input.frets.iter().chain(&input.solo_frets).any(|&pressed| pressed) {
+                prop_assert!(!sounding, "chord sounding with no fret held");
+            }
+        }
+    }
+}
@@ -23,6 +23,7 @@ struct ResolutionKey {
     key_root: Note,
     mode: Mode,
     row: FretRow,
+    diatonic: bool,
 }
 
 impl ChordResolver {
@@ -46,6 +47,20 @@ impl ChordResolver {
         mode: Option<Mode>,
         row: FretRow,
         overrides: &[PatternChordOverride]
+    ) -> Result<ChordMap, String> {
+        self.resolve_chord_map_with(genre, key_root, mode, row, overrides, false)
+    }
+
+    /// Resolve chord map, optionally correcting the preset's qualities to be
+    /// diatonic to the key and mode (overrides are always kept as written)
+    pub fn resolve_chord_map_with(
+        &self,
+        genre: Genre,
+        key_root: Option<Note>,
+        mode: Option<Mode>,
+        row: FretRow,
+        overrides: &[PatternChordOverride],
+        diatonic: bool,
     ) -> Result<ChordMap, String> {
         let preset = self.presets.get(&genre)
             .ok_or_else(|| format!("No preset found for genre: {}", genre.name()))?;
@@ -58,6 +73,7 @@ impl ChordResolver {
             key_root,
             mode,
             row,
+            diatonic,
         };
 
         // Check cache first
@@ -77,7 +93,8 @@ impl ChordResolver {
                 key_root, 
                 mode, 
                 preset,
-                row
+                row,
+                diatonic,
             ) {
                 chord_map.insert(fret_button, chord_spec);
             }
@@ -100,11 +117,16 @@ impl ChordResolver {
         mode: Mode,
         preset: &GenrePreset,
         row: FretRow,
+        diatonic: bool,
     ) -> Option<ChordSpec> {
-        let quality = preset.role_to_chord_quality.get(&role)?;
+        let mut quality = *preset.role_to_chord_quality.get(&role)?;
         let chord_root = self.get_chord_root_for_role(role, key_root, mode);
+        if diatonic {
+            let interval = (chord_root.to_midi(0) + 12 - key_root.to_midi(0)) % 12;
+            quality = quality.diatonic(interval, mode).unwrap_or(quality);
+        }
         
-        let mut chord_spec = ChordSpec::new(chord_root, *quality);
+        let mut chord_spec = ChordSpec::new(chord_root, quality);
         
         // Adjust octave for solo row
         if row == FretRow::Solo {
@@ -130,22 +152,7 @@ impl ChordResolver {
             (HarmonicRole::VI, Mode::Minor) => 8,   // Minor sixth (VI in minor)
         };
         
-        let target_note = (root_semitones + interval) % 12;
-        match target_note {
-            0 => Note::C,
-            1 => Note::Cs,
-            2 => Note::D,
-            3 => Note::Ds,
-            4 => Note::E,
-            5 => Note::F,
-            6 => Note::Fs,
-            7 => Note::G,
-            8 => Note::Gs,
-            9 => Note::A,
-            10 => Note::As,
-            11 => Note::B,
-            _ => unreachable!(),
-        }
+        Note::from_semitone(root_semitones + interval)
     }
 
     /// Apply pattern overrides to the resolved chord map
@@ -169,6 +176,11 @@ impl ChordResolver {
         cache.clear();
     }
 
+    /// Whether a genre's preset asks for voice leading between chords
+    pub fn voice_leading_enabled(&self, genre: Genre) -> bool {
+        self.presets.get(&genre).map(|preset| preset.voice_leading).unwrap_or(false)
+    }
+
     /// Get preset for a genre
     pub fn get_preset(&self, genre: Genre) -> Option<&GenrePreset> {
         self.presets.get(&genre)
@@ -201,6 +213,9 @@ mod tests {
             role_to_chord_quality,
             whammy_defaults: WhammyDefaults::default(),
             sustain_defaults: SustainDefaults::default(),
+            voice_leading: false,
+            layer: None,
+            instrument: None,
         }
     }
 
@@ -226,6 +241,35 @@ mod tests {
         assert_eq!(green_chord.quality, ChordQuality::Power5);
     }
 
+    #[test]
+    fn test_diatonic_resolution() {
+        let mut resolver = ChordResolver::new();
+        let mut preset = create_test_preset();
+        preset.role_to_chord_quality = [HarmonicRole::I, HarmonicRole::IV, HarmonicRole::V, HarmonicRole::bVII, HarmonicRole::II]
+            .into_iter()
+            .map(|role| (role, ChordQuality::Major))
+            .collect();
+        resolver.load_preset(Genre::Rock, preset);
+
+        let resolve = |mode, diatonic| {
+            resolver.resolve_chord_map_with(Genre::Rock, Some(Note::C), Some(mode), FretRow::Main, &[], diatonic).unwrap()
+        };
+        let quality = |map: &ChordMap, fret| map[&fret].quality;
+
+        // The preset as written
+        let plain = resolve(Mode::Major, false);
+        assert_eq!(quality(&plain, FretButton::Orange), ChordQuality::Major);
+
+        let major = resolve(Mode::Major, true);
+        assert_eq!(quality(&major, FretButton::Orange), ChordQuality::Minor); // ii
+        assert_eq!(quality(&major, FretButton::Blue), ChordQuality::Major); // borrowed bVII
+
+        let minor = resolve(Mode::Minor, true);
+        assert_eq!(quality(&minor, FretButton::Green), ChordQuality::Minor); // i
+        assert_eq!(quality(&minor, FretButton::Orange), ChordQuality::Diminished); // ii°
+        assert_eq!(quality(&minor, FretButton::Blue), ChordQuality::Major); // VII
+    }
+
     #[test]
     fn test_pattern_overrides() {
         let mut resolver = ChordResolver::new();
@@ -0,0 +1,90 @@
+//! Tilt-driven instrument layer
+//! Raising the neck fades a second instrument (e.g. strings) in under the
+//! guitar. The tilt is turned into a level on `LAYER_CC` that the audio engine
+//! uses as a continuous crossfade, so the layer swells in and out with the
+//! guitar rather than switching on and off.
+
+use controller::{ControlId, ControllerState};
+use crate::{MusicEvent, LAYER_CC};
+
+/// Tilt where the layer starts to fade in
+pub const DEFAULT_LAYER_TILT_START: f32 = 0.2;
+
+/// Tilt where the layer reaches full level
+pub const DEFAULT_LAYER_TILT_FULL: f32 = 0.8;
+
+/// Turns the tilt sensor into layer level changes
+#[derive(Debug, Clone)]
+pub struct TiltLayer {
+    start: f32,
+    full: f32,
+    /// Last level sent, so a steady tilt sends nothing
+    sent: Option<u8>,
+}
+
+impl Default for TiltLayer {
+    fn default() -> Self {
+        Self::new(DEFAULT_LAYER_TILT_START, DEFAULT_LAYER_TILT_FULL)
+    }
+}
+
+impl TiltLayer {
+    pub fn new(start: f32, full: f32) -> Self {
+        let start = start.clamp(0.0, 0.95);
+        Self { start, full: full.clamp(start + 0.05, 1.0), sent: None }
+    }
+
+    /// Layer level (0.0..1.0) for a tilt reading, eased so the fade starts gently
+    pub fn level(&self, tilt: f32) -> f32 {
+        let ramp = ((tilt - self.start) / (self.full - self.start)).clamp(0.0, 1.0);
+        ramp * ramp * (3.0 - 2.0 * ramp)
+    }
+
+    /// Layer level change for the current controller state, if any
+    pub fn process(&mut self, state: &ControllerState) -> Option<MusicEvent> {
+        let value = (self.level(state.axis(ControlId::TiltSensor)) * 127.0).round() as u8;
+        if self.sent == Some(value) {
+            return None;
+        }
+        self.sent = Some(value);
+        Some(MusicEvent::ControlChange { cc: LAYER_CC, value })
+    }
+
+    /// Forget the last level so the next update is always sent
+    pub fn reset(&mut self) {
+        self.sent = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tilted(tilt: f32) -> ControllerState {
+        let mut state = ControllerState::default();
+        state.axes.insert(ControlId::TiltSensor, tilt);
+        state
+    }
+
+    #[test]
+    fn test_level_fades_in_between_start_and_full() {
+        let layer = TiltLayer::new(0.2, 0.8);
+        assert_eq!(layer.level(-1.0), 0.0);
+        assert_eq!(layer.level(0.2), 0.0);
+        assert!((layer.level(0.5) - 0.5).abs() < 1e-6);
+        assert!(layer.level(0.3) < layer.level(0.4));
+        assert_eq!(layer.level(0.8), 1.0);
+        assert_eq!(layer.level(1.0), 1.0);
+    }
+
+    #[test]
+    fn test_only_changes_are_sent() {
+        let mut layer = TiltLayer::default();
+        assert!(matches!(layer.process(&tilted(0.0)), Some(MusicEvent::ControlChange { cc: LAYER_CC, value: 0 })));
+        assert!(layer.process(&tilted(0.0)).is_none());
+        assert!(matches!(layer.process(&tilted(1.0)), Some(MusicEvent::ControlChange { cc: LAYER_CC, value: 127 })));
+
+        layer.reset();
+        assert!(layer.process(&tilted(1.0)).is_some());
+    }
+}
@@ -0,0 +1,91 @@
+//! Capo / transpose
+//! The D-pad nudges the key a semitone at a time while playing, like moving a
+//! capo, instead of opening the settings. The capo only tracks how far the key
+//! has been moved from the one chosen in the settings and keeps that within a
+//! range; the mappers are moved by the returned steps, so choosing a new key
+//! in the settings simply starts again from zero.
+
+use serde::{Deserialize, Serialize};
+
+/// Semitones the capo may move the key either way by default
+pub const DEFAULT_TRANSPOSE_RANGE: u8 = 7;
+
+/// Semitone offset from the chosen key, limited to +/- `range`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Capo {
+    offset: i8,
+    range: u8,
+}
+
+impl Default for Capo {
+    fn default() -> Self {
+        Self::new(DEFAULT_TRANSPOSE_RANGE)
+    }
+}
+
+impl Capo {
+    pub fn new(range: u8) -> Self {
+        Self { offset: 0, range: range.min(12) }
+    }
+
+    /// Semitones from the chosen key
+    pub fn offset(&self) -> i8 {
+        self.offset
+    }
+
+    pub fn range(&self) -> u8 {
+        self.range
+    }
+
+    /// Move by `semitones`, stopping at the range. Returns the semitones actually moved.
+    pub fn shift(&mut self, semitones: i8) -> i8 {
+        let range = self.range as i8;
+        let offset = (self.offset + semitones).clamp(-range, range);
+        let moved = offset - self.offset;
+        self.offset = offset;
+        moved
+    }
+
+    /// Change the range, pulling the offset inside it. Returns the semitones moved.
+    pub fn set_range(&mut self, range: u8) -> i8 {
+        self.range = range.min(12);
+        self.shift(0)
+    }
+
+    /// Back to the chosen key. Returns the semitones moved.
+    pub fn reset(&mut self) -> i8 {
+        self.shift(-self.offset)
+    }
+}
+
+/// Pitch class `semitones` away from `root` (0 = C, wraps around the octave)
+pub fn transpose_root(root: u8, semitones: i8) -> u8 {
+    (root as i16 + semitones as i16).rem_euclid(12) as u8
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_capo_stays_in_range() {
+        let mut capo = Capo::new(2);
+        assert_eq!(capo.shift(1), 1);
+        assert_eq!(capo.shift(1), 1);
+        // At the top of the range nothing moves
+        assert_eq!(capo.shift(1), 0);
+        assert_eq!(capo.offset(), 2);
+
+        // Narrowing the range pulls the offset back
+        assert_eq!(capo.set_range(1), -1);
+        assert_eq!(capo.reset(), -1);
+        assert_eq!(capo.offset(), 0);
+    }
+
+    #[test]
+    fn test_transpose_root_wraps() {
+        assert_eq!(transpose_root(9, 2), 11);
+        assert_eq!(transpose_root(11, 1), 0);
+        assert_eq!(transpose_root(0, -1), 11);
+    }
+}
@@ -0,0 +1,135 @@
+//! Voice leading between successive chords
+//! Picks the inversion/octave of each new chord that moves the least from the
+//! previously played chord, so pad and piano progressions glide instead of jump.
+
+/// Lowest note a voiced chord may use
+const MIN_VOICED_NOTE: u8 = 28;
+/// Highest note a voiced chord may use
+const MAX_VOICED_NOTE: u8 = 96;
+
+/// Rotate a chord `inversion` times (lowest note moves up an octave)
+fn invert(notes: &[u8], inversion: usize) -> Vec<u8> {
+    let mut voiced: Vec<u8> = notes.to_vec();
+    voiced.sort_unstable();
+    for _ in 0..inversion {
+        let lowest = voiced.remove(0);
+        voiced.push(lowest.saturating_add(12));
+    }
+    voiced
+}
+
+/// Total movement between two voicings: each note's distance to the nearest
+/// note of the other chord, in both directions (handles different sizes)
+pub fn voice_distance(a: &[u8], b: &[u8]) -> u32 {
+    let nearest = |note: u8, other: &[u8]| {
+        other.iter()
+            .map(|&o| (note as i32 - o as i32).unsigned_abs())
+            .min()
+            .unwrap_or(0)
+    };
+    a.iter().map(|&n| nearest(n, b)).sum::<u32>() + b.iter().map(|&n| nearest(n, a)).sum::<u32>()
+}
+
+/// Choose the inversion and octave of `notes` closest to `previous`.
+/// Returns `notes` unchanged if there is no previous chord.
+pub fn voice_lead(notes: &[u8], previous: &[u8]) -> Vec<u8> {
+    if notes.len() < 2 || previous.is_empty() {
+        return notes.to_vec();
+    }
+
+    let mut best: Option<(u32, u32, Vec<u8>)> = None;
+    for inversion in 0..notes.len() {
+        let inverted = invert(notes, inversion);
+        for octave in -2i32..=2 {
+            let candidate: Vec<i32> = inverted.iter().map(|&n| n as i32 + octave * 12).collect();
+            if candidate.iter().any(|&n| n < MIN_VOICED_NOTE as i32 || n > MAX_VOICED_NOTE as i32) {
+                continue;
+            }
+            let candidate: Vec<u8> = candidate.into_iter().map(|n| n as u8).collect();
+            let cost = voice_distance(&candidate, previous);
+            // Prefer the least movement, then the voicing closest to the original register
+            let register_shift = inversion as u32 + octave.unsigned_abs();
+            let better = match &best {
+                Some((best_cost, best_shift, _)) => (cost, register_shift) < (*best_cost, *best_shift),
+                None => true,
+            };
+            if better {
+                best = Some((cost, register_shift, candidate));
+            }
+        }
+    }
+
+    best.map(|(_, _, voiced)| voiced).unwrap_or_else(|| notes.to_vec())
+}
+
+/// Remembers the last voiced chord and voice-leads the next one from it
+#[derive(Debug, Clone, Default)]
+pub struct VoiceLeader {
+    enabled: bool,
+    previous: Vec<u8>,
+}
+
+impl VoiceLeader {
+    pub fn new(enabled: bool) -> Self {
+        Self {
+            enabled,
+            previous: Vec::new(),
+        }
+    }
+
+    pub fn set_enabled(&mut self, enabled: bool) {
+        self.enabled = enabled;
+        self.previous.clear();
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+
+    /// Voice a chord (root-position MIDI notes), returning the notes to play
+    pub fn voice(&mut self, notes: Vec<u8>) -> Vec<u8> {
+        if !self.enabled {
+            return notes;
+        }
+        let voiced = voice_lead(&notes, &self.previous);
+        self.previous = voiced.clone();
+        voiced
+    }
+
+    /// Forget the previous chord (next chord plays in root position)
+    pub fn reset(&mut self) {
+        self.previous.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_c_to_f_uses_second_inversion() {
+        // C major (C4 E4 G4) -> F major: C4 F4 A4 keeps the common tone
+        let voiced = voice_lead(&[65, 69, 72], &[60, 64, 67]);
+        assert_eq!(voiced, vec![60, 65, 69]);
+    }
+
+    #[test]
+    fn test_voice_leading_reduces_movement() {
+        let previous = [60, 64, 67];
+        let g_major = [67, 71, 74];
+        let voiced = voice_lead(&g_major, &previous);
+        assert!(voice_distance(&voiced, &previous) < voice_distance(&g_major, &previous));
+        assert!(voiced.contains(&67));
+    }
+
+    #[test]
+    fn test_voice_leader_disabled_passes_through() {
+        let mut leader = VoiceLeader::new(false);
+        leader.voice(vec![60, 64, 67]);
+        assert_eq!(leader.voice(vec![65, 69, 72]), vec![65, 69, 72]);
+
+        let mut leader = VoiceLeader::new(true);
+        assert_eq!(leader.voice(vec![60, 64, 67]), vec![60, 64, 67]);
+        assert_eq!(leader.voice(vec![65, 69, 72]), vec![60, 65, 69]);
+    }
+}
@@ -0,0 +1,84 @@
+//! MIDI to chart converter for chart authors
+//!
+//! Usage: cargo run -p song --bin midi2chart -- [options] <input.mid> [output.mitychart.json]
+//! Options: --title <t>, --artist <a>, --track <index>, --grid <beats>, --min-spacing <beats>, --max-chords <n>
+
+use song::{convert_midi_file, MidiConvertOptions};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "Usage: midi2chart [--title <t>] [--artist <a>] [--track <index>] [--grid <beats>] [--min-spacing <beats>] [--max-chords <n>] <input.mid> [output.mitychart.json]";
+
+fn main() -> ExitCode {
+    let mut options = MidiConvertOptions::default();
+    let mut paths = Vec::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| match args.next() {
+            Some(value) => Ok(value),
+            None => Err(format!("{} needs a value", name)),
+        };
+        let result = match arg.as_str() {
+            "--title" => value("--title").map(|v| options.title = Some(v)),
+            "--artist" => value("--artist").map(|v| options.artist = v),
+            "--track" => value("--track").and_then(|v| parse(&v).map(|n| options.track = Some(n))),
+            "--grid" => value("--grid").and_then(|v| parse(&v).map(|n| options.grid = n)),
+            "--min-spacing" => value("--min-spacing").and_then(|v| parse(&v).map(|n| options.min_spacing = n)),
+            "--max-chords" => value("--max-chords").and_then(|v| parse(&v).map(|n| options.max_chords = n)),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            _ => {
+                paths.push(arg);
+                Ok(())
+            }
+        };
+        if let Err(e) = result {
+            eprintln!("error: {}", e);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    let Some(input) = paths.first().map(PathBuf::from) else {
+        eprintln!("{}", USAGE);
+        return ExitCode::FAILURE;
+    };
+    let output = paths.get(1).map(PathBuf::from).unwrap_or_else(|| default_output(&input));
+
+    let chart = match convert_midi_file(&input, &options) {
+        Ok(chart) => chart,
+        Err(e) => {
+            eprintln!("error: {}: {:#}", input.display(), e);
+            return ExitCode::FAILURE;
+        }
+    };
+    let json = match serde_json::to_string_pretty(&chart) {
+        Ok(json) => json,
+        Err(e) => {
+            eprintln!("error: failed to serialize chart: {}", e);
+            return ExitCode::FAILURE;
+        }
+    };
+    if let Err(e) = std::fs::write(&output, json) {
+        eprintln!("error: failed to write {}: {}", output.display(), e);
+        return ExitCode::FAILURE;
+    }
+
+    let events: usize = chart.lanes.iter().map(|lane| lane.events.len()).sum();
+    println!(
+        "Wrote {}: {} chord(s), {} event(s), {} BPM",
+        output.display(), chart.mapping.chords.len(), events, chart.clock.bpm
+    );
+    ExitCode::SUCCESS
+}
+
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value.parse().map_err(|_| format!("invalid number '{}'", value))
+}
+
+fn default_output(input: &Path) -> PathBuf {
+    let stem = input.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_else(|| "chart".to_string());
+    input.with_file_name(format!("{}.mitychart.json", stem))
+}
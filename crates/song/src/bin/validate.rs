@@ -0,0 +1,78 @@
+//! Chart validation tool for chart authors and CI
+//!
+//! Usage: cargo run -p song --bin validate -- [--json] [--strict] <dir-or-file>...
+//! Exits with status 1 if any chart has errors (or warnings with --strict).
+
+use song::{validate_chart_directory, validate_chart_file, ChartReport, Severity};
+use std::path::Path;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    let mut json_output = false;
+    let mut strict = false;
+    let mut targets = Vec::new();
+
+    for arg in std::env::args().skip(1) {
+        match arg.as_str() {
+            "--json" => json_output = true,
+            "--strict" => strict = true,
+            "-h" | "--help" => {
+                println!("Usage: validate [--json] [--strict] <dir-or-file>...");
+                return ExitCode::SUCCESS;
+            }
+            _ => targets.push(arg),
+        }
+    }
+    if targets.is_empty() {
+        targets.push("assets/songs".to_string());
+    }
+
+    let mut reports: Vec<ChartReport> = Vec::new();
+    for target in &targets {
+        let path = Path::new(target);
+        if path.is_dir() {
+            match validate_chart_directory(path) {
+                Ok(dir_reports) => reports.extend(dir_reports),
+                Err(e) => {
+                    eprintln!("error: failed to read directory {}: {}", path.display(), e);
+                    return ExitCode::FAILURE;
+                }
+            }
+        } else {
+            reports.push(validate_chart_file(path));
+        }
+    }
+
+    let errors: usize = reports.iter().map(|r| r.error_count()).sum();
+    let warnings: usize = reports.iter().map(|r| r.warning_count()).sum();
+
+    if json_output {
+        match serde_json::to_string_pretty(&reports) {
+            Ok(json) => println!("{}", json),
+            Err(e) => {
+                eprintln!("error: failed to serialize report: {}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+    } else {
+        for report in &reports {
+            for issue in &report.issues {
+                let level = match issue.severity {
+                    Severity::Error => "error",
+                    Severity::Warning => "warning",
+                };
+                println!("{}: {}: {}: {}", report.file.display(), level, issue.path, issue.message);
+            }
+        }
+        println!(
+            "Checked {} chart(s): {} error(s), {} warning(s)",
+            reports.len(), errors, warnings
+        );
+    }
+
+    if errors > 0 || (strict && warnings > 0) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
@@ -0,0 +1,123 @@
+//! Latency calibration
+//! The player strums along to a steady click; how far the strums land from
+//! the clicks on average is the latency of the whole loop (audio out, hand,
+//! controller in). Song hit detection takes that off every strum. The median
+//! is used so a few stray strums don't skew the result.
+
+use anyhow::{bail, Result};
+
+/// Fewest strums a calibration needs to be trusted
+pub const MIN_CALIBRATION_STRUMS: usize = 4;
+
+/// Clicks a calibration plays when none is given
+pub const DEFAULT_CALIBRATION_BEATS: u32 = 16;
+
+/// Tempo of the calibration clicks when none is given
+pub const DEFAULT_CALIBRATION_BPM: f64 = 100.0;
+
+/// Collects strum offsets against a click track starting at time 0. Clicks
+/// fall on every beat from one beat in (the first beat is a lead-in).
+#[derive(Debug, Clone)]
+pub struct LatencyCalibration {
+    beat_ms: f64,
+    beats: u32,
+    offsets_ms: Vec<f64>,
+    strumming: bool,
+}
+
+impl LatencyCalibration {
+    pub fn new(bpm: f64, beats: u32) -> Result<Self> {
+        if bpm <= 0.0 || beats == 0 {
+            bail!("Calibration needs a positive tempo and at least one click");
+        }
+        Ok(Self {
+            beat_ms: 60_000.0 / bpm,
+            beats,
+            offsets_ms: Vec::new(),
+            strumming: false,
+        })
+    }
+
+    /// Time of click `index` (0-based) in ms from the start
+    pub fn click_ms(&self, index: u32) -> f64 {
+        (index + 1) as f64 * self.beat_ms
+    }
+
+    pub fn beats(&self) -> u32 {
+        self.beats
+    }
+
+    /// When the last strum can still land (half a beat after the last click)
+    pub fn duration_ms(&self) -> f64 {
+        self.click_ms(self.beats - 1) + self.beat_ms / 2.0
+    }
+
+    /// Feed the strum bar at `at_ms` from the start; a new strum is measured
+    /// against the nearest click
+    pub fn process(&mut self, at_ms: f64, strum: bool) {
+        let strummed = strum && !self.strumming;
+        self.strumming = strum;
+        if !strummed {
+            return;
+        }
+        let nearest = (at_ms / self.beat_ms).round();
+        if nearest < 1.0 || nearest > self.beats as f64 {
+            return;
+        }
+        self.offsets_ms.push(at_ms - nearest * self.beat_ms);
+    }
+
+    pub fn strum_count(&self) -> usize {
+        self.offsets_ms.len()
+    }
+
+    /// Median strum offset in ms (positive = strums land late)
+    pub fn result(&self) -> Result<f64> {
+        if self.offsets_ms.len() < MIN_CALIBRATION_STRUMS {
+            bail!("Only {} strums were heard, strum along with at least {} clicks",
+                self.offsets_ms.len(), MIN_CALIBRATION_STRUMS);
+        }
+        let mut offsets = self.offsets_ms.clone();
+        offsets.sort_by(f64::total_cmp);
+        let middle = offsets.len() / 2;
+        Ok(if offsets.len().is_multiple_of(2) {
+            (offsets[middle - 1] + offsets[middle]) / 2.0
+        } else {
+            offsets[middle]
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_median_offset_of_strums() {
+        // 120 BPM: clicks every 500 ms from 500 ms
+        let mut calibration = LatencyCalibration::new(120.0, 8).unwrap();
+        assert_eq!(calibration.click_ms(0), 500.0);
+        for at_ms in [540.0, 1045.0, 1530.0, 2050.0, 2700.0] {
+            calibration.process(at_ms, true);
+            calibration.process(at_ms + 20.0, false);
+        }
+        // A held strum bar counts once
+        calibration.process(3040.0, true);
+        calibration.process(3060.0, true);
+        assert_eq!(calibration.strum_count(), 6);
+        // Offsets 40, 45, 30, 50, 200 (stray), 40: median 42.5
+        assert_eq!(calibration.result().unwrap(), 42.5);
+    }
+
+    #[test]
+    fn test_needs_enough_strums() {
+        let mut calibration = LatencyCalibration::new(100.0, 4).unwrap();
+        // Before the lead-in is over and after the last click don't count
+        calibration.process(100.0, true);
+        calibration.process(150.0, false);
+        calibration.process(3000.0, true);
+        assert_eq!(calibration.strum_count(), 0);
+        assert!(calibration.result().is_err());
+        assert!(LatencyCalibration::new(0.0, 4).is_err());
+    }
+}
@@ -29,6 +29,9 @@ pub struct SongChart {
     pub lanes: Vec<Lane>,
     pub lyrics: Vec<LyricEvent>,
     pub sections: Vec<Section>,
+    /// Phrases that fill the star power meter when every note is hit
+    #[serde(rename = "starPhrases", default, skip_serializing_if = "Vec::is_empty")]
+    pub star_phrases: Vec<StarPhrase>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -39,6 +42,23 @@ pub struct SongMeta {
     pub youtube: Option<String>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub spotify: Option<String>,
+    /// Recording to play along to
+    #[serde(rename = "backingTrack", default, skip_serializing_if = "Option::is_none")]
+    pub backing_track: Option<BackingTrackRef>,
+    /// Instrument loaded with the song, e.g. "Distorted Guitar" (None = the genre preset's)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instrument: Option<String>,
+}
+
+/// Audio file played in sync with the transport
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BackingTrackRef {
+    /// File path (relative paths are looked up in the song library)
+    pub path: String,
+    /// Time in the file where beat 0 falls (seconds)
+    #[serde(default)]
+    pub offset_seconds: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -48,6 +68,82 @@ pub struct ClockSettings {
     pub time_sig: [u32; 2],
     #[serde(rename = "countInBars")]
     pub count_in_bars: u32,
+    /// Accent grouping for odd/compound meters, e.g. [2, 2, 3] for 7/8
+    #[serde(rename = "accentGrouping", default, skip_serializing_if = "Option::is_none")]
+    pub accent_grouping: Option<Vec<u32>>,
+}
+
+impl ClockSettings {
+    /// Meter for this clock, using the default grouping if none is given
+    pub fn meter(&self) -> anyhow::Result<crate::meter::Meter> {
+        match &self.accent_grouping {
+            Some(grouping) => crate::meter::Meter::with_grouping(self.time_sig, grouping.clone()),
+            None => Ok(crate::meter::Meter::new(self.time_sig)),
+        }
+    }
+}
+
+/// Tempo changes of a tick-based source (MIDI, .chart), used to place its
+/// ticks on a chart clock that keeps the first tempo
+#[derive(Debug, Clone, PartialEq)]
+pub struct TempoMap {
+    ticks_per_quarter: f64,
+    /// (tick, quarter notes per minute, quarter notes of the first tempo before the tick)
+    changes: Vec<(u64, f64, f64)>,
+}
+
+impl TempoMap {
+    /// Build from (tick, quarter notes per minute) changes in any order. A
+    /// later change on the same tick wins; `default_bpm` holds until the first.
+    pub fn new(ticks_per_quarter: u32, changes: &[(u64, f64)], default_bpm: f64) -> Self {
+        let mut sorted: Vec<(u64, f64)> = changes.iter().copied().filter(|(_, bpm)| *bpm > 0.0).collect();
+        sorted.sort_by_key(|(tick, _)| *tick);
+        if sorted.first().is_none_or(|(tick, _)| *tick > 0) {
+            sorted.insert(0, (0, default_bpm));
+        }
+
+        let ticks_per_quarter = ticks_per_quarter.max(1) as f64;
+        let first_bpm = sorted.iter().rev().find(|(tick, _)| *tick == 0).map_or(default_bpm, |(_, bpm)| *bpm);
+        let mut map: Vec<(u64, f64, f64)> = Vec::new();
+        for (tick, bpm) in sorted {
+            match map.last_mut() {
+                Some(last) if last.0 == tick => last.1 = bpm,
+                Some(&mut (last_tick, last_bpm, last_quarters)) => {
+                    let quarters = last_quarters + (tick - last_tick) as f64 / ticks_per_quarter * first_bpm / last_bpm;
+                    map.push((tick, bpm, quarters));
+                }
+                None => map.push((tick, bpm, 0.0)),
+            }
+        }
+        Self { ticks_per_quarter, changes: map }
+    }
+
+    /// Tempo at the start (quarter notes per minute)
+    pub fn first_bpm(&self) -> f64 {
+        self.changes[0].1
+    }
+
+    /// Tempo in effect at a tick (quarter notes per minute)
+    pub fn bpm_at(&self, tick: u64) -> f64 {
+        self.change_at(tick).1
+    }
+
+    /// Quarter notes at the first tempo from the start to a tick, i.e. where
+    /// the tick falls on a clock that never changes tempo
+    pub fn quarters_at(&self, tick: u64) -> f64 {
+        let (change_tick, bpm, quarters) = self.change_at(tick);
+        quarters + (tick - change_tick) as f64 / self.ticks_per_quarter * self.first_bpm() / bpm
+    }
+
+    /// Seconds from the start to a tick
+    pub fn seconds_at(&self, tick: u64) -> f64 {
+        self.quarters_at(tick) * 60.0 / self.first_bpm()
+    }
+
+    fn change_at(&self, tick: u64) -> (u64, f64, f64) {
+        let index = self.changes.partition_point(|(change_tick, _, _)| *change_tick <= tick);
+        self.changes[index.saturating_sub(1)]
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -72,6 +168,53 @@ pub struct MappingSettings {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub preset: Option<String>,
     pub chords: HashMap<String, ChordMapping>,
+    /// Key free play switches to while the song is loaded ("E", "F#m", "Bb minor")
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub key: Option<String>,
+    /// Genre free play switches to while the song is loaded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub genre: Option<String>,
+}
+
+impl MappingSettings {
+    /// The declared key, if any and valid
+    pub fn song_key(&self) -> Option<SongKey> {
+        self.key.as_deref().and_then(SongKey::parse)
+    }
+}
+
+/// Key declared by a chart
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SongKey {
+    /// Root note name ("E", "F#", "Bb")
+    pub root: String,
+    pub is_major: bool,
+}
+
+impl SongKey {
+    /// Parse "E", "Em", "F# major", "Bb minor"
+    pub fn parse(key: &str) -> Option<Self> {
+        let key = key.trim();
+        let mut chars = key.chars();
+        let letter = chars.next().filter(|c| ('A'..='G').contains(&c.to_ascii_uppercase()))?;
+        let rest = chars.as_str();
+        let (accidental, quality) = match rest.chars().next() {
+            Some(c @ ('#' | 'b')) => (Some(c), &rest[1..]),
+            _ => (None, rest),
+        };
+        let is_major = match quality.trim().to_lowercase().as_str() {
+            "" | "maj" | "major" => true,
+            "m" | "min" | "minor" => false,
+            _ => return None,
+        };
+        let mut root = letter.to_ascii_uppercase().to_string();
+        root.extend(accidental);
+        Some(Self { root, is_major })
+    }
+
+    pub fn mode_name(&self) -> &'static str {
+        if self.is_major { "major" } else { "minor" }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -112,6 +255,14 @@ pub struct LyricEvent {
     pub annotations: Option<Vec<WordAnnotation>>,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StarPhrase {
+    #[serde(rename = "fromBeat")]
+    pub from_beat: f64,
+    #[serde(rename = "toBeat")]
+    pub to_beat: f64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Section {
     pub name: String,
@@ -119,6 +270,39 @@ pub struct Section {
     pub from_beat: f64,
     #[serde(rename = "toBeat")]
     pub to_beat: f64,
+    /// What the guitar's fret LEDs show in this section (None = the player's setting)
+    #[serde(rename = "fretLeds", default, skip_serializing_if = "Option::is_none")]
+    pub fret_leds: Option<FretLedMode>,
+}
+
+/// What fret LEDs show on guitars that have them
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FretLedMode {
+    Off,
+    /// Frets of the chord coming up next
+    NextChord,
+    /// Held frets, lit while they play the chord due now
+    HeldChord,
+}
+
+impl FretLedMode {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(Self::Off),
+            "next_chord" => Some(Self::NextChord),
+            "held_chord" => Some(Self::HeldChord),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::NextChord => "next_chord",
+            Self::HeldChord => "held_chord",
+        }
+    }
 }
 
 impl SongChart {
@@ -137,6 +321,15 @@ impl SongChart {
         if self.clock.time_sig[1] == 0 {
             anyhow::bail!("Time signature denominator cannot be zero");
         }
+        if self.clock.time_sig[0] == 0 {
+            anyhow::bail!("Time signature numerator cannot be zero");
+        }
+        self.clock.meter()?;
+        if let Some(key) = &self.mapping.key {
+            if SongKey::parse(key).is_none() {
+                anyhow::bail!("Invalid key '{}'", key);
+            }
+        }
         
         // Validate chord events reference valid chords
         for lane in &self.lanes {
@@ -187,6 +380,13 @@ impl SongChart {
             .find(|s| beat >= s.from_beat && beat < s.to_beat)
     }
 
+    /// Fret LED mode at a beat: the section's own, else `default`
+    pub fn fret_led_mode_at(&self, beat: f64, default: FretLedMode) -> FretLedMode {
+        self.get_section_at_beat(beat)
+            .and_then(|section| section.fret_leds)
+            .unwrap_or(default)
+    }
+
     /// Calculate total song duration in beats
     pub fn total_beats(&self) -> f64 {
         let max_chord_beat = self.lanes
@@ -263,5 +463,51 @@ mod tests {
         let chart = SongChart::from_json(json).unwrap();
         assert_eq!(chart.meta.title, "Test Song");
         assert_eq!(chart.clock.bpm, 120.0);
+        assert!(chart.meta.instrument.is_none());
+
+        let chart = SongChart::from_json(&json.replacen("\"Test Artist\"", "\"Test Artist\", \"instrument\": \"Distorted Guitar\"", 1)).unwrap();
+        assert_eq!(chart.meta.instrument.as_deref(), Some("Distorted Guitar"));
+    }
+
+    #[test]
+    fn test_parse_song_key() {
+        assert_eq!(SongKey::parse("E"), Some(SongKey { root: "E".to_string(), is_major: true }));
+        assert_eq!(SongKey::parse("f#m"), Some(SongKey { root: "F#".to_string(), is_major: false }));
+        assert_eq!(SongKey::parse("Bb minor"), Some(SongKey { root: "Bb".to_string(), is_major: false }));
+        assert_eq!(SongKey::parse("A major").map(|key| key.is_major), Some(true));
+        assert_eq!(SongKey::parse("H"), None);
+        assert_eq!(SongKey::parse("C dorian"), None);
+    }
+
+    #[test]
+    fn test_tempo_map() {
+        // 120 BPM for one bar, then 60 BPM; a later change on tick 0 wins
+        let map = TempoMap::new(480, &[(1920, 60.0), (0, 90.0), (0, 120.0)], 100.0);
+        assert_eq!(map.first_bpm(), 120.0);
+        assert_eq!(map.bpm_at(1919), 120.0);
+        assert_eq!(map.bpm_at(1920), 60.0);
+        assert_eq!(map.seconds_at(960), 1.0);
+        assert_eq!(map.seconds_at(1920), 2.0);
+        assert_eq!(map.seconds_at(2400), 3.0);
+        // Half as many quarters pass per second after the tempo halves
+        assert_eq!(map.quarters_at(2400), 6.0);
+
+        let default = TempoMap::new(480, &[(960, 60.0)], 120.0);
+        assert_eq!(default.first_bpm(), 120.0);
+        assert_eq!(default.seconds_at(1440), 2.0);
+    }
+
+    #[test]
+    fn test_section_fret_led_mode() {
+        let sections: Vec<Section> = serde_json::from_str(r#"[
+            { "name": "Verse", "fromBeat": 0, "toBeat": 8 },
+            { "name": "Solo", "fromBeat": 8, "toBeat": 16, "fretLeds": "held_chord" }
+        ]"#).unwrap();
+        let mut chart = SongChart::new("Test", "Me", 120.0, [4, 4]).unwrap();
+        chart.sections = sections;
+        assert_eq!(chart.fret_led_mode_at(2.0, FretLedMode::NextChord), FretLedMode::NextChord);
+        assert_eq!(chart.fret_led_mode_at(9.0, FretLedMode::NextChord), FretLedMode::HeldChord);
+        assert_eq!(chart.fret_led_mode_at(20.0, FretLedMode::Off), FretLedMode::Off);
+        assert!(!serde_json::to_string(&chart.sections[0]).unwrap().contains("fretLeds"));
     }
 }
@@ -0,0 +1,305 @@
+//! Clone Hero .chart import
+//! Reads the text `.chart` format used by Guitar Hero / Clone Hero community
+//! charts and turns one difficulty of the lead guitar part into a chart lane.
+//! Notes struck together become a chord named after its frets (single notes
+//! are one-fret chords); open notes have no fret to hold and are skipped.
+//! Tempo changes are followed through a tempo map, so notes keep their time
+//! on a chart clock at the first tempo, which also keeps the first time
+//! signature; section events become sections.
+
+use anyhow::{anyhow, bail, Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+use crate::chart::*;
+use crate::editor::DEFAULT_LANE;
+
+/// Difficulties of the lead guitar part, hardest first
+pub const CLONE_HERO_DIFFICULTIES: [&str; 4] = ["Expert", "Hard", "Medium", "Easy"];
+
+/// Chart fret names for note numbers 0-4
+const FRET_NAMES: [&str; 5] = ["GREEN", "RED", "YELLOW", "BLUE", "ORANGE"];
+const DEFAULT_RESOLUTION: u32 = 192;
+const DEFAULT_BPM: f64 = 120.0;
+/// Longest a note without a sustain is held, in beats
+const MAX_TAP_BEATS: f64 = 1.0;
+
+/// A note with its position and sustain in ticks
+#[derive(Debug, Clone, PartialEq)]
+pub struct CloneHeroNote {
+    pub tick: u64,
+    /// 0-4 = green..orange, 5 = forced, 6 = tap, 7 = open
+    pub fret: u8,
+    pub length: u64,
+}
+
+/// The parts of a .chart file the import uses
+#[derive(Debug, Clone, Default)]
+pub struct CloneHeroChart {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    /// Ticks per quarter note
+    pub resolution: u32,
+    /// Tempo changes (tick, quarter notes per minute), in file order
+    pub tempos: Vec<(u64, f64)>,
+    /// Time signature changes (tick, signature), in file order
+    pub time_sigs: Vec<(u64, [u32; 2])>,
+    pub sections: Vec<(u64, String)>,
+    /// Notes by track name (e.g. "ExpertSingle")
+    pub tracks: HashMap<String, Vec<CloneHeroNote>>,
+}
+
+/// Parse the text of a .chart file
+pub fn parse_clone_hero_chart(text: &str) -> Result<CloneHeroChart> {
+    let mut chart = CloneHeroChart { resolution: DEFAULT_RESOLUTION, ..Default::default() };
+    let mut section: Option<String> = None;
+
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim().trim_start_matches('\u{feff}');
+        if line.is_empty() || line == "{" || line == "}" {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|rest| rest.strip_suffix(']')) {
+            section = Some(name.to_string());
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            bail!("Line {}: expected 'key = value'", number + 1);
+        };
+        let (key, value) = (key.trim(), value.trim());
+        let context = || format!("Line {}: invalid entry '{}'", number + 1, line);
+
+        match section.as_deref() {
+            Some("Song") => match key {
+                "Name" => chart.title = Some(unquote(value)),
+                "Artist" => chart.artist = Some(unquote(value)),
+                "Resolution" => chart.resolution = value.parse().with_context(context)?,
+                _ => {}
+            },
+            Some("SyncTrack") => {
+                let tick: u64 = key.parse().with_context(context)?;
+                let fields: Vec<&str> = value.split_whitespace().collect();
+                match fields.as_slice() {
+                    ["B", tempo] => {
+                        chart.tempos.push((tick, tempo.parse::<f64>().with_context(context)? / 1000.0));
+                    }
+                    ["TS", numerator, rest @ ..] => {
+                        // The denominator is written as a power of two (default 2 = quarter notes)
+                        let exponent: u32 = rest.first().map(|e| e.parse()).transpose().with_context(context)?.unwrap_or(2);
+                        chart.time_sigs.push((tick, [numerator.parse().with_context(context)?, 1 << exponent.min(6)]));
+                    }
+                    _ => {}
+                }
+            }
+            Some("Events") => {
+                let tick: u64 = key.parse().with_context(context)?;
+                if let Some(name) = value.strip_prefix('E').map(|rest| unquote(rest.trim())) {
+                    if let Some(name) = name.strip_prefix("section ") {
+                        chart.sections.push((tick, name.trim().to_string()));
+                    }
+                }
+            }
+            Some(track) => {
+                let fields: Vec<&str> = value.split_whitespace().collect();
+                if let ["N", fret, length] = fields.as_slice() {
+                    chart.tracks.entry(track.to_string()).or_default().push(CloneHeroNote {
+                        tick: key.parse().with_context(context)?,
+                        fret: fret.parse().with_context(context)?,
+                        length: length.parse().with_context(context)?,
+                    });
+                }
+            }
+            None => bail!("Line {}: entry outside a section", number + 1),
+        }
+    }
+
+    if chart.resolution == 0 {
+        bail!("Resolution must be positive");
+    }
+    Ok(chart)
+}
+
+impl CloneHeroChart {
+    /// Tempo map over the chart's ticks (120 BPM until the first tempo)
+    pub fn tempo_map(&self) -> TempoMap {
+        TempoMap::new(self.resolution, &self.tempos, DEFAULT_BPM)
+    }
+
+    /// Time signature at the start (4/4 if none is given there)
+    pub fn first_time_sig(&self) -> [u32; 2] {
+        let mut time_sigs = self.time_sigs.clone();
+        time_sigs.sort_by_key(|(tick, _)| *tick);
+        time_sigs.iter().take_while(|(tick, _)| *tick == 0).last().map_or([4, 4], |(_, time_sig)| *time_sig)
+    }
+}
+
+/// Convert one difficulty of the lead guitar part (the hardest charted if none is named)
+pub fn clone_hero_to_chart(source: &CloneHeroChart, difficulty: Option<&str>) -> Result<SongChart> {
+    let track_name = |difficulty: &str| format!("{}Single", difficulty);
+    let (difficulty, notes) = match difficulty {
+        Some(difficulty) => {
+            let difficulty = CLONE_HERO_DIFFICULTIES.iter()
+                .find(|name| name.eq_ignore_ascii_case(difficulty))
+                .ok_or_else(|| anyhow!("Unknown difficulty '{}'", difficulty))?;
+            let notes = source.tracks.get(&track_name(difficulty))
+                .ok_or_else(|| anyhow!("Chart has no {} guitar part", difficulty))?;
+            (*difficulty, notes)
+        }
+        None => CLONE_HERO_DIFFICULTIES.iter()
+            .find_map(|difficulty| source.tracks.get(&track_name(difficulty)).map(|notes| (*difficulty, notes)))
+            .ok_or_else(|| anyhow!("Chart has no lead guitar part"))?,
+    };
+
+    let time_sig = source.first_time_sig();
+    // Chart beats count the time signature's denominator unit
+    let beat_unit = time_sig[1].max(1) as f64 / 4.0;
+    // Ticks after a tempo change land where they sound on the first tempo's clock
+    let tempo_map = source.tempo_map();
+    let to_beat = |tick: u64| tempo_map.quarters_at(tick) * beat_unit;
+
+    // Group fretted notes struck on the same tick
+    let mut onsets: Vec<(u64, [bool; 5], u64)> = Vec::new();
+    let mut notes: Vec<&CloneHeroNote> = notes.iter().filter(|note| (note.fret as usize) < FRET_NAMES.len()).collect();
+    notes.sort_by_key(|note| note.tick);
+    for note in notes {
+        match onsets.last_mut() {
+            Some((tick, frets, length)) if *tick == note.tick => {
+                frets[note.fret as usize] = true;
+                *length = (*length).max(note.length);
+            }
+            _ => {
+                let mut frets = [false; 5];
+                frets[note.fret as usize] = true;
+                onsets.push((note.tick, frets, note.length));
+            }
+        }
+    }
+    if onsets.is_empty() {
+        bail!("The {} guitar part has no fretted notes", difficulty);
+    }
+
+    let title = source.title.clone().filter(|title| !title.is_empty()).unwrap_or_else(|| "Untitled".to_string());
+    let artist = source.artist.clone().unwrap_or_else(|| "Unknown".to_string());
+    let bpm = tempo_map.first_bpm();
+    let mut chart = SongChart::new(&title, &artist, (bpm * beat_unit * 100.0).round() / 100.0, time_sig)?;
+
+    let mut events = Vec::new();
+    for (index, (tick, frets, length)) in onsets.iter().enumerate() {
+        let frets: Vec<String> = FRET_NAMES.iter().zip(frets)
+            .filter(|(_, held)| **held)
+            .map(|(name, _)| name.to_string())
+            .collect();
+        let name = frets.join("+");
+        let beat = to_beat(*tick);
+        let dur = if *length > 0 {
+            to_beat(tick + length) - beat
+        } else {
+            onsets.get(index + 1).map(|(next, _, _)| to_beat(*next) - beat).unwrap_or(MAX_TAP_BEATS).min(MAX_TAP_BEATS)
+        };
+        chart.mapping.chords.entry(name.clone()).or_insert(ChordMapping { frets });
+        events.push(ChordEvent { beat, dur, chord: name, section: None });
+    }
+
+    let song_end = events.iter().map(|event| event.beat + event.dur).fold(0.0, f64::max);
+    let mut sections: Vec<(u64, String)> = source.sections.clone();
+    sections.sort_by_key(|(tick, _)| *tick);
+    chart.sections = sections.iter().enumerate()
+        .map(|(index, (tick, name))| Section {
+            name: name.clone(),
+            from_beat: to_beat(*tick),
+            to_beat: sections.get(index + 1).map(|(next, _)| to_beat(*next)).unwrap_or(song_end),
+            fret_leds: None,
+        })
+        .filter(|section| section.to_beat > section.from_beat)
+        .collect();
+    for event in &mut events {
+        event.section = chart.get_section_at_beat(event.beat).map(|section| section.name.clone());
+    }
+
+    chart.lanes = vec![Lane { name: DEFAULT_LANE.to_string(), events }];
+    chart.validate()?;
+    Ok(chart)
+}
+
+/// Read and convert a .chart file
+pub fn convert_clone_hero_file(path: &Path, difficulty: Option<&str>) -> Result<SongChart> {
+    let text = std::fs::read_to_string(path).context(format!("Failed to read {}", path.display()))?;
+    let mut source = parse_clone_hero_chart(&text)?;
+    if source.title.as_deref().is_none_or(str::is_empty) {
+        source.title = path.file_stem().map(|stem| stem.to_string_lossy().to_string());
+    }
+    clone_hero_to_chart(&source, difficulty)
+}
+
+fn unquote(value: &str) -> String {
+    value.trim().trim_matches('"').to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHART: &str = r#"[Song]
+{
+  Name = "Test Song"
+  Artist = "Band"
+  Resolution = 192
+}
+[SyncTrack]
+{
+  0 = TS 4
+  0 = B 150000
+  1536 = TS 3
+  1536 = B 75000
+}
+[Events]
+{
+  0 = E "section Intro"
+  768 = E "section Verse"
+}
+[ExpertSingle]
+{
+  0 = N 0 0
+  0 = N 1 0
+  96 = N 2 0
+  384 = N 7 0
+  768 = N 4 768
+  768 = N 5 0
+  1728 = N 3 192
+}
+[EasySingle]
+{
+  0 = N 0 0
+}
+"#;
+
+    #[test]
+    fn test_parse_chart() {
+        let chart = parse_clone_hero_chart(CHART).unwrap();
+        assert_eq!(chart.title.as_deref(), Some("Test Song"));
+        assert_eq!(chart.resolution, 192);
+        assert_eq!(chart.tempos, vec![(0, 150.0), (1536, 75.0)]);
+        assert_eq!(chart.time_sigs, vec![(0, [4, 4]), (1536, [3, 4])]);
+        assert_eq!(chart.sections, vec![(0, "Intro".to_string()), (768, "Verse".to_string())]);
+        assert_eq!(chart.tracks["ExpertSingle"].len(), 7);
+        assert!(parse_clone_hero_chart("[Song]\n{\n  Resolution\n}").is_err());
+    }
+
+    #[test]
+    fn test_convert_groups_chords_and_skips_open_notes() {
+        let source = parse_clone_hero_chart(CHART).unwrap();
+        let chart = clone_hero_to_chart(&source, None).unwrap();
+        assert_eq!(chart.clock.bpm, 150.0);
+
+        let events: Vec<(f64, f64, &str)> = chart.lanes[0].events.iter()
+            .map(|e| (e.beat, e.dur, e.chord.as_str()))
+            .collect();
+        // The tempo halves at tick 1536, so the last beat lands twice as far on the chart clock
+        assert_eq!(events, vec![(0.0, 0.5, "GREEN+RED"), (0.5, 1.0, "YELLOW"), (4.0, 4.0, "ORANGE"), (10.0, 2.0, "BLUE")]);
+        assert_eq!(chart.lanes[0].events[2].section.as_deref(), Some("Verse"));
+        assert_eq!(chart.mapping.chords["GREEN+RED"].frets, vec!["GREEN", "RED"]);
+
+        let easy = clone_hero_to_chart(&source, Some("easy")).unwrap();
+        assert_eq!(easy.lanes[0].events.len(), 1);
+        assert!(clone_hero_to_chart(&source, Some("Hard")).is_err());
+    }
+}
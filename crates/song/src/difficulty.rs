@@ -0,0 +1,156 @@
+//! Difficulty levels
+//! Easier versions of a chart are derived from the one as written instead of
+//! being charted separately: notes that follow too closely on the previous one
+//! are dropped, chords are cut down to fewer frets (single frets on Easy) and
+//! the hit window is widened. Expert plays the chart unchanged.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use crate::chart::*;
+
+/// Chart fret order, lowest first (chords keep their lowest frets)
+const FRET_ORDER: [&str; 5] = ["GREEN", "RED", "YELLOW", "BLUE", "ORANGE"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+    /// The chart as written
+    #[default]
+    Expert,
+}
+
+impl Difficulty {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "easy" => Some(Self::Easy),
+            "medium" => Some(Self::Medium),
+            "hard" => Some(Self::Hard),
+            "expert" => Some(Self::Expert),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Easy => "easy",
+            Self::Medium => "medium",
+            Self::Hard => "hard",
+            Self::Expert => "expert",
+        }
+    }
+
+    /// Shortest gap kept between two notes of a lane, in beats
+    pub fn min_note_gap(&self) -> f64 {
+        match self {
+            Self::Easy => 2.0,
+            Self::Medium => 1.0,
+            Self::Hard => 0.5,
+            Self::Expert => 0.0,
+        }
+    }
+
+    /// Most frets a chord keeps
+    pub fn max_frets(&self) -> usize {
+        match self {
+            Self::Easy => 1,
+            Self::Medium => 2,
+            Self::Hard => 3,
+            Self::Expert => FRET_ORDER.len(),
+        }
+    }
+
+    /// Hit window relative to Expert's
+    pub fn hit_window_scale(&self) -> f64 {
+        match self {
+            Self::Easy => 1.6,
+            Self::Medium => 1.3,
+            Self::Hard => 1.1,
+            Self::Expert => 1.0,
+        }
+    }
+}
+
+/// Derive the chart played at a difficulty
+pub fn reduce_chart(chart: &SongChart, difficulty: Difficulty) -> Result<SongChart> {
+    let mut reduced = chart.clone();
+    if difficulty == Difficulty::Expert {
+        return Ok(reduced);
+    }
+
+    let min_gap = difficulty.min_note_gap();
+    for lane in &mut reduced.lanes {
+        lane.events.sort_by(|a, b| a.beat.total_cmp(&b.beat));
+        let mut last_beat: Option<f64> = None;
+        lane.events.retain(|event| {
+            // Small tolerance so notes exactly on the gap survive rounding
+            let keep = last_beat.is_none_or(|last| event.beat - last >= min_gap - 1e-6);
+            if keep {
+                last_beat = Some(event.beat);
+            }
+            keep
+        });
+    }
+
+    let max_frets = difficulty.max_frets();
+    for mapping in reduced.mapping.chords.values_mut() {
+        if mapping.frets.len() > max_frets {
+            let position = |fret: &String| FRET_ORDER.iter().position(|name| name == fret).unwrap_or(FRET_ORDER.len());
+            mapping.frets.sort_by_key(position);
+            mapping.frets.truncate(max_frets);
+        }
+    }
+
+    reduced.validate()?;
+    Ok(reduced)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::editor::DEFAULT_LANE;
+
+    fn frets(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    fn test_chart() -> SongChart {
+        let mut chart = SongChart::new("Test", "Band", 120.0, [4, 4]).unwrap();
+        chart.set_chord("C", frets(&["YELLOW", "GREEN", "RED"])).unwrap();
+        chart.set_chord("G", frets(&["BLUE"])).unwrap();
+        for (beat, chord) in [(0.0, "C"), (0.5, "G"), (1.0, "C"), (2.0, "G"), (2.5, "C"), (4.0, "G")] {
+            chart.add_event(DEFAULT_LANE, ChordEvent { beat, dur: 0.5, chord: chord.to_string(), section: None }).unwrap();
+        }
+        chart
+    }
+
+    fn beats(chart: &SongChart) -> Vec<f64> {
+        chart.lanes[0].events.iter().map(|event| event.beat).collect()
+    }
+
+    #[test]
+    fn test_easier_levels_drop_close_notes() {
+        let chart = test_chart();
+        assert_eq!(beats(&reduce_chart(&chart, Difficulty::Expert).unwrap()), vec![0.0, 0.5, 1.0, 2.0, 2.5, 4.0]);
+        assert_eq!(beats(&reduce_chart(&chart, Difficulty::Hard).unwrap()), vec![0.0, 0.5, 1.0, 2.0, 2.5, 4.0]);
+        assert_eq!(beats(&reduce_chart(&chart, Difficulty::Medium).unwrap()), vec![0.0, 1.0, 2.0, 4.0]);
+        assert_eq!(beats(&reduce_chart(&chart, Difficulty::Easy).unwrap()), vec![0.0, 2.0, 4.0]);
+    }
+
+    #[test]
+    fn test_chords_keep_their_lowest_frets() {
+        let chart = test_chart();
+        let easy = reduce_chart(&chart, Difficulty::Easy).unwrap();
+        assert_eq!(easy.mapping.chords["C"].frets, frets(&["GREEN"]));
+        let medium = reduce_chart(&chart, Difficulty::Medium).unwrap();
+        assert_eq!(medium.mapping.chords["C"].frets, frets(&["GREEN", "RED"]));
+        assert_eq!(medium.mapping.chords["G"].frets, frets(&["BLUE"]));
+        // The chart as written is untouched
+        assert_eq!(chart.mapping.chords["C"].frets.len(), 3);
+
+        assert_eq!(Difficulty::from_name("Medium"), Some(Difficulty::Medium));
+        assert!(Difficulty::Easy.hit_window_scale() > Difficulty::Expert.hit_window_scale());
+    }
+}
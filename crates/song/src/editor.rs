@@ -0,0 +1,273 @@
+//! Chart editing
+//! Builds and changes charts programmatically so the frontend can host a
+//! chart editor. Chord events stay sorted by beat within their lane and are
+//! addressed by lane name and index; every edit leaves a chart that passes
+//! `SongChart::validate`.
+
+use anyhow::{anyhow, bail, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use crate::chart::*;
+
+/// Lane new events go to when none is named
+pub const DEFAULT_LANE: &str = "Main";
+
+/// Chart header fields to change (None = leave as is)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChartMetaEdit {
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub bpm: Option<f64>,
+    pub time_sig: Option<[u32; 2]>,
+    pub count_in_bars: Option<u32>,
+    /// Key free play follows while the song is loaded ("" clears it)
+    pub key: Option<String>,
+    /// Genre free play follows while the song is loaded ("" clears it)
+    pub genre: Option<String>,
+}
+
+impl SongChart {
+    /// Empty chart with a single lane, ready for chords to be added
+    pub fn new(title: &str, artist: &str, bpm: f64, time_sig: [u32; 2]) -> Result<Self> {
+        let chart = Self {
+            meta: SongMeta {
+                title: title.to_string(),
+                artist: artist.to_string(),
+                youtube: None,
+                spotify: None,
+                backing_track: None,
+                instrument: None,
+            },
+            clock: ClockSettings {
+                bpm,
+                time_sig,
+                count_in_bars: 1,
+                accent_grouping: None,
+            },
+            playback: PlaybackSettings {
+                default_instrument: InstrumentRef {
+                    instrument_type: "virtual".to_string(),
+                    label: "Clean Electric Guitar".to_string(),
+                },
+                fallback_instrument: InstrumentRef {
+                    instrument_type: "virtual".to_string(),
+                    label: "Basic Guitar".to_string(),
+                },
+                allow_user_override_instrument: true,
+            },
+            mapping: MappingSettings {
+                preset: None,
+                chords: HashMap::new(),
+                key: None,
+                genre: None,
+            },
+            lanes: vec![Lane { name: DEFAULT_LANE.to_string(), events: Vec::new() }],
+            lyrics: Vec::new(),
+            sections: Vec::new(),
+            star_phrases: Vec::new(),
+        };
+        chart.validate()?;
+        Ok(chart)
+    }
+
+    /// Apply header changes, leaving the chart untouched if the result is invalid
+    pub fn apply_meta(&mut self, edit: &ChartMetaEdit) -> Result<()> {
+        let mut edited = self.clone();
+        if let Some(title) = &edit.title {
+            edited.meta.title = title.clone();
+        }
+        if let Some(artist) = &edit.artist {
+            edited.meta.artist = artist.clone();
+        }
+        if let Some(bpm) = edit.bpm {
+            edited.clock.bpm = bpm;
+        }
+        if let Some(time_sig) = edit.time_sig {
+            // A grouping for the old meter wouldn't add up any more
+            if time_sig != edited.clock.time_sig {
+                edited.clock.accent_grouping = None;
+            }
+            edited.clock.time_sig = time_sig;
+        }
+        if let Some(count_in_bars) = edit.count_in_bars {
+            edited.clock.count_in_bars = count_in_bars;
+        }
+        if let Some(key) = &edit.key {
+            edited.mapping.key = Some(key.trim().to_string()).filter(|key| !key.is_empty());
+        }
+        if let Some(genre) = &edit.genre {
+            edited.mapping.genre = Some(genre.trim().to_string()).filter(|genre| !genre.is_empty());
+        }
+        edited.validate()?;
+        *self = edited;
+        Ok(())
+    }
+
+    /// Map a chord name to the frets that play it
+    pub fn set_chord(&mut self, chord: &str, frets: Vec<String>) -> Result<()> {
+        if chord.trim().is_empty() {
+            bail!("Chord name cannot be empty");
+        }
+        if frets.is_empty() {
+            bail!("Chord '{}' needs at least one fret", chord);
+        }
+        self.mapping.chords.insert(chord.to_string(), ChordMapping { frets });
+        Ok(())
+    }
+
+    /// Insert a chord event in beat order, creating the lane if needed.
+    /// Returns the event's index in its lane.
+    pub fn add_event(&mut self, lane: &str, event: ChordEvent) -> Result<usize> {
+        if !self.mapping.chords.contains_key(&event.chord) {
+            bail!("Chord '{}' not found in mapping", event.chord);
+        }
+        if event.dur <= 0.0 {
+            bail!("Chord duration must be positive");
+        }
+        if !event.beat.is_finite() || event.beat < 0.0 {
+            bail!("Chord beat must be zero or later");
+        }
+        let lane_index = match self.lanes.iter().position(|existing| existing.name == lane) {
+            Some(index) => index,
+            None => {
+                self.lanes.push(Lane { name: lane.to_string(), events: Vec::new() });
+                self.lanes.len() - 1
+            }
+        };
+        let events = &mut self.lanes[lane_index].events;
+        let index = events.partition_point(|existing| existing.beat <= event.beat);
+        events.insert(index, event);
+        Ok(index)
+    }
+
+    /// Remove a chord event by lane and index
+    pub fn remove_event(&mut self, lane: &str, index: usize) -> Result<ChordEvent> {
+        let events = self.lane_events_mut(lane)?;
+        if index >= events.len() {
+            bail!("No event {} in lane '{}'", index, lane);
+        }
+        Ok(events.remove(index))
+    }
+
+    /// Move a chord event to another beat, returning its new index
+    pub fn move_event(&mut self, lane: &str, index: usize, beat: f64) -> Result<usize> {
+        if !beat.is_finite() || beat < 0.0 {
+            bail!("Chord beat must be zero or later");
+        }
+        let mut event = self.remove_event(lane, index)?;
+        event.beat = beat;
+        self.add_event(lane, event)
+    }
+
+    fn lane_events_mut(&mut self, lane: &str) -> Result<&mut Vec<ChordEvent>> {
+        self.lanes.iter_mut()
+            .find(|existing| existing.name == lane)
+            .map(|lane| &mut lane.events)
+            .ok_or_else(|| anyhow!("No lane named '{}'", lane))
+    }
+
+    /// Add a section, or replace the one with the same name
+    pub fn set_section(&mut self, section: Section) -> Result<()> {
+        if section.to_beat <= section.from_beat {
+            bail!("Section '{}' must end after it starts", section.name);
+        }
+        self.sections.retain(|existing| existing.name != section.name);
+        let index = self.sections.partition_point(|existing| existing.from_beat <= section.from_beat);
+        self.sections.insert(index, section);
+        Ok(())
+    }
+
+    /// Remove a section by name (false if there was none)
+    pub fn remove_section(&mut self, name: &str) -> bool {
+        let count = self.sections.len();
+        self.sections.retain(|existing| existing.name != name);
+        self.sections.len() != count
+    }
+
+    /// Add a lyric line, or replace the one starting on the same beat
+    pub fn set_lyric(&mut self, lyric: LyricEvent) -> Result<()> {
+        if !lyric.beat.is_finite() || lyric.beat < 0.0 {
+            bail!("Lyric beat must be zero or later");
+        }
+        self.lyrics.retain(|existing| existing.beat != lyric.beat);
+        let index = self.lyrics.partition_point(|existing| existing.beat <= lyric.beat);
+        self.lyrics.insert(index, lyric);
+        Ok(())
+    }
+
+    /// Remove the lyric line starting on a beat (false if there was none)
+    pub fn remove_lyric(&mut self, beat: f64) -> bool {
+        let count = self.lyrics.len();
+        self.lyrics.retain(|existing| existing.beat != beat);
+        self.lyrics.len() != count
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(beat: f64, chord: &str) -> ChordEvent {
+        ChordEvent { beat, dur: 2.0, chord: chord.to_string(), section: None }
+    }
+
+    fn chart() -> SongChart {
+        let mut chart = SongChart::new("Test", "Me", 100.0, [4, 4]).unwrap();
+        chart.set_chord("E", vec!["GREEN".to_string()]).unwrap();
+        chart.set_chord("A", vec!["RED".to_string()]).unwrap();
+        chart
+    }
+
+    #[test]
+    fn test_events_stay_in_beat_order() {
+        let mut chart = chart();
+        assert_eq!(chart.add_event(DEFAULT_LANE, event(4.0, "A")).unwrap(), 0);
+        assert_eq!(chart.add_event(DEFAULT_LANE, event(0.0, "E")).unwrap(), 0);
+        assert_eq!(chart.add_event(DEFAULT_LANE, event(8.0, "E")).unwrap(), 2);
+        assert!(chart.add_event(DEFAULT_LANE, event(2.0, "G")).is_err());
+
+        assert_eq!(chart.move_event(DEFAULT_LANE, 0, 6.0).unwrap(), 1);
+        let beats: Vec<f64> = chart.lanes[0].events.iter().map(|e| e.beat).collect();
+        assert_eq!(beats, vec![4.0, 6.0, 8.0]);
+
+        assert_eq!(chart.remove_event(DEFAULT_LANE, 0).unwrap().chord, "A");
+        assert!(chart.remove_event(DEFAULT_LANE, 5).is_err());
+        assert!(chart.remove_event("Lead", 0).is_err());
+        chart.validate().unwrap();
+    }
+
+    #[test]
+    fn test_meta_edits_are_validated() {
+        let mut chart = chart();
+        chart.apply_meta(&ChartMetaEdit { bpm: Some(0.0), title: Some("Bad".to_string()), ..Default::default() })
+            .unwrap_err();
+        assert_eq!(chart.meta.title, "Test");
+
+        chart.apply_meta(&ChartMetaEdit { bpm: Some(140.0), key: Some("Em".to_string()), ..Default::default() })
+            .unwrap();
+        assert_eq!(chart.clock.bpm, 140.0);
+        assert_eq!(chart.mapping.key.as_deref(), Some("Em"));
+        assert!(chart.apply_meta(&ChartMetaEdit { key: Some("H".to_string()), ..Default::default() }).is_err());
+    }
+
+    #[test]
+    fn test_sections_and_lyrics_replace_by_name_and_beat() {
+        let mut chart = chart();
+        chart.set_section(Section { name: "Verse".to_string(), from_beat: 8.0, to_beat: 16.0, fret_leds: None }).unwrap();
+        chart.set_section(Section { name: "Intro".to_string(), from_beat: 0.0, to_beat: 8.0, fret_leds: None }).unwrap();
+        chart.set_section(Section { name: "Verse".to_string(), from_beat: 8.0, to_beat: 24.0, fret_leds: None }).unwrap();
+        assert_eq!(chart.sections.len(), 2);
+        assert_eq!(chart.sections[1].to_beat, 24.0);
+        assert!(chart.set_section(Section { name: "Empty".to_string(), from_beat: 4.0, to_beat: 4.0, fret_leds: None }).is_err());
+        assert!(chart.remove_section("Intro"));
+
+        let line = |text: &str| LyricEvent { beat: 8.0, text: Some(text.to_string()), annotations: None };
+        chart.set_lyric(line("first")).unwrap();
+        chart.set_lyric(line("second")).unwrap();
+        assert_eq!(chart.lyrics.len(), 1);
+        assert_eq!(chart.lyrics[0].text.as_deref(), Some("second"));
+        assert!(chart.remove_lyric(8.0));
+        assert!(!chart.remove_lyric(8.0));
+    }
+}
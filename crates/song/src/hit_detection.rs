@@ -1,13 +1,96 @@
 use crate::chart::{ChordEvent, ChordMapping};
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-/// Hit window tolerance in beats
-pub const HIT_WINDOW: f64 = 0.5;
+/// Tempo the windows are converted at until the detector is told otherwise
+const DEFAULT_MS_PER_BEAT: f64 = 500.0;
+
+/// Timing windows either side of a note, in milliseconds
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HitWindows {
+    /// Full accuracy within this
+    pub perfect_ms: f64,
+    pub good_ms: f64,
+    /// Strums further off than this don't hit the note at all
+    pub miss_ms: f64,
+}
+
+impl Default for HitWindows {
+    fn default() -> Self {
+        Self {
+            perfect_ms: 60.0,
+            good_ms: 120.0,
+            miss_ms: 250.0,
+        }
+    }
+}
+
+impl HitWindows {
+    /// Windows must be positive and nested (perfect <= good <= miss)
+    pub fn validate(&self) -> Result<()> {
+        if self.perfect_ms <= 0.0 || self.good_ms < self.perfect_ms || self.miss_ms < self.good_ms {
+            bail!("Hit windows must be positive with perfect <= good <= miss (got {}/{}/{} ms)",
+                self.perfect_ms, self.good_ms, self.miss_ms);
+        }
+        Ok(())
+    }
+
+    /// Timing grade of a strum `offset_ms` away from its note (None = outside)
+    pub fn timing(&self, offset_ms: f64) -> Option<HitTiming> {
+        let offset_ms = offset_ms.abs();
+        if offset_ms <= self.perfect_ms {
+            Some(HitTiming::Perfect)
+        } else if offset_ms <= self.good_ms {
+            Some(HitTiming::Good)
+        } else if offset_ms <= self.miss_ms {
+            Some(HitTiming::Ok)
+        } else {
+            None
+        }
+    }
+
+    /// Accuracy of a strum: 1.0 within the perfect window, falling to 0.0 at the miss window
+    pub fn accuracy(&self, offset_ms: f64) -> f64 {
+        let offset_ms = offset_ms.abs();
+        if offset_ms <= self.perfect_ms {
+            return 1.0;
+        }
+        let span = self.miss_ms - self.perfect_ms;
+        if span <= 0.0 {
+            return 0.0;
+        }
+        (1.0 - (offset_ms - self.perfect_ms) / span).clamp(0.0, 1.0)
+    }
+
+    fn scaled(&self, scale: f64) -> Self {
+        Self {
+            perfect_ms: self.perfect_ms * scale,
+            good_ms: self.good_ms * scale,
+            miss_ms: self.miss_ms * scale,
+        }
+    }
+}
+
+/// How close to the note a hit was
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HitTiming {
+    Perfect,
+    Good,
+    Ok,
+}
 
 /// Result of a strum attempt
 #[derive(Debug, Clone, PartialEq)]
 pub enum HitResult {
-    Hit { event: ChordEventHit, accuracy: f64 },
+    Hit {
+        event: ChordEventHit,
+        accuracy: f64,
+        timing: HitTiming,
+        /// Strum time relative to the note (ms, negative = early)
+        offset_ms: f64,
+    },
     Miss { reason: MissReason },
 }
 
@@ -30,6 +113,13 @@ pub struct HitDetector {
     chord_mappings: HashMap<String, Vec<String>>,
     hit_events: Vec<HitEvent>,
     sustaining_event: Option<SustainingEvent>,
+    windows: HitWindows,
+    /// Widens or narrows the windows (difficulty)
+    window_scale: f64,
+    /// Real time of a beat at the current tempo and speed
+    ms_per_beat: f64,
+    /// Input and audio latency taken off every strum
+    input_offset_ms: f64,
 }
 
 #[derive(Debug, Clone)]
@@ -58,9 +148,52 @@ impl HitDetector {
             chord_mappings: mappings,
             hit_events: Vec::new(),
             sustaining_event: None,
+            windows: HitWindows::default(),
+            window_scale: 1.0,
+            ms_per_beat: DEFAULT_MS_PER_BEAT,
+            input_offset_ms: 0.0,
+        }
+    }
+
+    pub fn set_windows(&mut self, windows: HitWindows) {
+        self.windows = windows;
+    }
+
+    /// Scale every window (e.g. widened on easier difficulties)
+    pub fn set_window_scale(&mut self, scale: f64) {
+        if scale > 0.0 {
+            self.window_scale = scale;
         }
     }
 
+    /// Tempo the windows are measured against (speed = practice multiplier)
+    pub fn set_tempo(&mut self, bpm: f64, speed: f64) {
+        if bpm > 0.0 && speed > 0.0 {
+            self.ms_per_beat = 60_000.0 / bpm / speed;
+        }
+    }
+
+    /// Latency to take off strums (positive = strums register late)
+    pub fn set_input_offset_ms(&mut self, offset_ms: f64) {
+        self.input_offset_ms = offset_ms;
+    }
+
+    /// Windows in effect (after scaling)
+    pub fn windows(&self) -> HitWindows {
+        self.windows.scaled(self.window_scale)
+    }
+
+    /// Miss window in beats at the current tempo
+    pub fn hit_window(&self) -> f64 {
+        self.windows().miss_ms / self.ms_per_beat
+    }
+
+    /// Beats of the notes a strum at `current_beat` can hit (after the input offset)
+    pub fn strum_range(&self, current_beat: f64) -> (f64, f64) {
+        let beat = current_beat - self.input_offset_ms / self.ms_per_beat;
+        (beat - self.hit_window(), beat + self.hit_window())
+    }
+
     /// Reset hit detection state
     pub fn reset(&mut self) {
         self.hit_events.clear();
@@ -74,12 +207,16 @@ impl HitDetector {
         pressed_frets: &[String],
         events: &[&ChordEvent],
     ) -> HitResult {
+        let current_beat = current_beat - self.input_offset_ms / self.ms_per_beat;
+        let windows = self.windows();
+        let hit_window = self.hit_window();
+
         // Find events within hit window
         let candidates: Vec<&ChordEvent> = events
             .iter()
             .filter(|e| {
                 let diff = (e.beat - current_beat).abs();
-                diff <= HIT_WINDOW && !self.is_already_hit(e.beat, &e.chord)
+                diff <= hit_window && !self.is_already_hit(e.beat, &e.chord)
             })
             .copied()
             .collect();
@@ -94,7 +231,9 @@ impl HitDetector {
         for event in candidates {
             if let Some(required_frets) = self.chord_mappings.get(&event.chord) {
                 if self.frets_match(pressed_frets, required_frets) {
-                    let accuracy = 1.0 - ((event.beat - current_beat).abs() / HIT_WINDOW);
+                    let offset_ms = (current_beat - event.beat) * self.ms_per_beat;
+                    let accuracy = windows.accuracy(offset_ms);
+                    let timing = windows.timing(offset_ms).unwrap_or(HitTiming::Ok);
                     
                     // Register hit
                     self.hit_events.push(HitEvent {
@@ -121,6 +260,8 @@ impl HitDetector {
                             is_sustain,
                         },
                         accuracy,
+                        timing,
+                        offset_ms,
                     };
                 }
             }
@@ -153,6 +294,11 @@ impl HitDetector {
         false
     }
 
+    /// The event can still be hit: its chord has frets and it wasn't hit yet
+    pub fn is_pending(&self, event: &ChordEvent) -> bool {
+        self.chord_mappings.contains_key(&event.chord) && !self.is_already_hit(event.beat, &event.chord)
+    }
+
     /// Check if an event was already hit
     fn is_already_hit(&self, beat: f64, chord: &str) -> bool {
         self.hit_events
@@ -229,7 +375,7 @@ mod tests {
         let result = detector.check_strum(10.1, &["GREEN".to_string()], &[&event]);
 
         match result {
-            HitResult::Hit { event: hit, accuracy } => {
+            HitResult::Hit { event: hit, accuracy, .. } => {
                 assert_eq!(hit.chord, "C");
                 assert!(accuracy > 0.9);
             }
@@ -280,4 +426,36 @@ mod tests {
             _ => panic!("Expected miss"),
         }
     }
+
+    #[test]
+    fn test_windows_follow_tempo_and_input_offset() {
+        let mappings = create_test_mappings();
+        let mut detector = HitDetector::new(&mappings);
+        detector.set_windows(HitWindows { perfect_ms: 30.0, good_ms: 60.0, miss_ms: 100.0 });
+        detector.set_tempo(60.0, 1.0);
+        let event = ChordEvent { beat: 4.0, dur: 1.0, chord: "C".to_string(), section: None };
+
+        // 80 ms late at 60 BPM
+        match detector.check_strum(4.08, &["GREEN".to_string()], &[&event]) {
+            HitResult::Hit { timing, offset_ms, accuracy, .. } => {
+                assert_eq!(timing, HitTiming::Ok);
+                assert!((offset_ms - 80.0).abs() < 1e-6);
+                assert!(accuracy > 0.0 && accuracy < 0.5);
+            }
+            _ => panic!("Expected hit"),
+        }
+
+        // The same strum is perfect once 70 ms of latency are taken off
+        detector.reset();
+        detector.set_input_offset_ms(70.0);
+        match detector.check_strum(4.08, &["GREEN".to_string()], &[&event]) {
+            HitResult::Hit { timing, accuracy, .. } => {
+                assert_eq!(timing, HitTiming::Perfect);
+                assert_eq!(accuracy, 1.0);
+            }
+            _ => panic!("Expected hit"),
+        }
+
+        assert!(HitWindows { perfect_ms: 50.0, good_ms: 40.0, miss_ms: 100.0 }.validate().is_err());
+    }
 }
@@ -1,11 +1,45 @@
 pub mod chart;
+pub mod editor;
 pub mod transport;
 pub mod hit_detection;
 pub mod scoring;
 pub mod instrument_resolver;
+pub mod validation;
+pub mod meter;
+pub mod midi_import;
+pub mod clone_hero;
+pub mod tap_tempo;
+pub mod quantize;
+pub mod recorder;
+pub mod difficulty;
+pub mod calibration;
+pub mod stats;
+pub mod practice;
+pub mod trainer;
+pub mod setlist;
+pub mod library;
+pub mod recovery;
+pub mod speed_trainer;
 
 pub use chart::*;
+pub use editor::*;
 pub use transport::*;
 pub use hit_detection::*;
 pub use scoring::*;
 pub use instrument_resolver::*;
+pub use validation::*;
+pub use meter::*;
+pub use midi_import::*;
+pub use clone_hero::*;
+pub use tap_tempo::*;
+pub use quantize::*;
+pub use recorder::*;
+pub use difficulty::*;
+pub use calibration::*;
+pub use stats::*;
+pub use practice::*;
+pub use trainer::*;
+pub use setlist::*;
+pub use library::*;
+pub use recovery::*;
+pub use speed_trainer::*;
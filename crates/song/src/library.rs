@@ -0,0 +1,333 @@
+//! Song library index
+//! Listing the library used to parse every chart each time. The index keeps
+//! the listing entry of each chart together with the file's modification
+//! time and size, so a rescan only parses charts that are new or changed and
+//! drops the ones that are gone. It is saved between runs, so the first
+//! listing after a start doesn't parse the whole library either. Searching,
+//! filtering and sorting run over the index without touching the files.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+use crate::chart::SongChart;
+use crate::difficulty::Difficulty;
+
+/// File name ending of a chart in the library
+pub const CHART_EXTENSION: &str = ".mitychart.json";
+/// Layout of the saved index (an index in another layout is rebuilt)
+const INDEX_VERSION: u32 = 1;
+
+/// A chart as listed in the library
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LibrarySong {
+    /// Same as the file name
+    pub id: String,
+    pub title: String,
+    pub artist: String,
+    pub filename: String,
+    /// Length at full speed (seconds)
+    pub duration_seconds: f64,
+    pub bpm: f64,
+    /// Genre the chart declares
+    #[serde(default)]
+    pub genre: Option<String>,
+    /// Rough rating from how many chords are struck per second
+    pub difficulty: Difficulty,
+    /// Different chords the song uses
+    pub chord_count: usize,
+}
+
+impl LibrarySong {
+    pub fn from_chart(filename: &str, chart: &SongChart) -> Self {
+        let duration_seconds = chart.beat_to_seconds(chart.total_beats(), 1.0);
+        let events = chart.lanes.iter().flat_map(|lane| &lane.events);
+        let strums = events.clone().count();
+        let chords: HashSet<&str> = events.map(|event| event.chord.as_str()).collect();
+        Self {
+            id: filename.to_string(),
+            title: chart.meta.title.clone(),
+            artist: chart.meta.artist.clone(),
+            filename: filename.to_string(),
+            duration_seconds,
+            bpm: chart.clock.bpm,
+            genre: chart.mapping.genre.clone(),
+            difficulty: rate_difficulty(strums as f64 / duration_seconds.max(1.0)),
+            chord_count: chords.len(),
+        }
+    }
+}
+
+/// Difficulty a song feels like at a number of strums per second
+fn rate_difficulty(strums_per_second: f64) -> Difficulty {
+    match strums_per_second {
+        rate if rate < 0.75 => Difficulty::Easy,
+        rate if rate < 1.5 => Difficulty::Medium,
+        rate if rate < 3.0 => Difficulty::Hard,
+        _ => Difficulty::Expert,
+    }
+}
+
+/// What a chart file looked like when it was indexed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+struct FileStamp {
+    /// Nanoseconds since the Unix epoch
+    modified: u128,
+    size: u64,
+}
+
+impl FileStamp {
+    fn of(path: &Path) -> Option<Self> {
+        let metadata = fs::metadata(path).ok()?;
+        let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?.as_nanos();
+        Some(Self { modified, size: metadata.len() })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedChart {
+    stamp: FileStamp,
+    /// None = the file isn't a valid chart (not parsed again until it changes)
+    song: Option<LibrarySong>,
+}
+
+/// Order of search results
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SongSort {
+    #[default]
+    Title,
+    Artist,
+    Bpm,
+    Duration,
+    Difficulty,
+    ChordCount,
+}
+
+impl SongSort {
+    fn compare(&self, a: &LibrarySong, b: &LibrarySong) -> Ordering {
+        match self {
+            Self::Title => Ordering::Equal,
+            Self::Artist => a.artist.to_lowercase().cmp(&b.artist.to_lowercase()),
+            Self::Bpm => a.bpm.total_cmp(&b.bpm),
+            Self::Duration => a.duration_seconds.total_cmp(&b.duration_seconds),
+            Self::Difficulty => a.difficulty.cmp(&b.difficulty),
+            Self::ChordCount => a.chord_count.cmp(&b.chord_count),
+        }
+        // Songs that tie are listed by title
+        .then_with(|| a.title.to_lowercase().cmp(&b.title.to_lowercase()))
+    }
+}
+
+/// What a library search keeps and how it orders it (all filters optional)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SongFilters {
+    /// Genre the chart declares (any case)
+    pub genre: Option<String>,
+    pub min_bpm: Option<f64>,
+    pub max_bpm: Option<f64>,
+    /// Difficulty ratings to keep (empty = any)
+    pub difficulties: Vec<Difficulty>,
+    pub sort: SongSort,
+    pub descending: bool,
+}
+
+impl SongFilters {
+    fn keeps(&self, song: &LibrarySong) -> bool {
+        let genre_matches = self.genre.as_ref().is_none_or(|genre| {
+            song.genre.as_ref().is_some_and(|song_genre| song_genre.eq_ignore_ascii_case(genre))
+        });
+        genre_matches
+            && self.min_bpm.is_none_or(|min| song.bpm >= min)
+            && self.max_bpm.is_none_or(|max| song.bpm <= max)
+            && (self.difficulties.is_empty() || self.difficulties.contains(&song.difficulty))
+    }
+}
+
+/// Listing entries of the charts in the songs directory, by file name
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LibraryIndex {
+    #[serde(default)]
+    version: u32,
+    charts: BTreeMap<String, IndexedChart>,
+}
+
+impl Default for LibraryIndex {
+    fn default() -> Self {
+        Self { version: INDEX_VERSION, charts: BTreeMap::new() }
+    }
+}
+
+impl LibraryIndex {
+    /// The saved index (missing or unreadable = empty, it's rebuilt on the next rescan)
+    pub fn load(path: &Path) -> Self {
+        fs::read_to_string(path).ok()
+            .and_then(|json| serde_json::from_str::<Self>(&json).ok())
+            .filter(|index| index.version == INDEX_VERSION)
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string(self)?)
+            .with_context(|| format!("Failed to write library index {}", path.display()))
+    }
+
+    /// Bring the index up to date with the songs directory, parsing only new
+    /// and changed charts. Returns whether anything changed.
+    pub fn rescan(&mut self, songs_dir: &Path) -> Result<bool> {
+        let dir_entries = fs::read_dir(songs_dir)
+            .with_context(|| format!("Failed to read songs directory {}", songs_dir.display()))?;
+        let mut changed = false;
+        let mut present = HashSet::new();
+        for entry in dir_entries.flatten() {
+            let path = entry.path();
+            let Some(filename) = path.file_name().and_then(|name| name.to_str()) else {
+                continue;
+            };
+            if !filename.ends_with(CHART_EXTENSION) || !path.is_file() {
+                continue;
+            }
+            let Some(stamp) = FileStamp::of(&path) else {
+                continue;
+            };
+            present.insert(filename.to_string());
+            if self.charts.get(filename).is_some_and(|indexed| indexed.stamp == stamp) {
+                continue;
+            }
+            let song = fs::read_to_string(&path).ok()
+                .and_then(|json| serde_json::from_str::<SongChart>(&json).ok())
+                .map(|chart| LibrarySong::from_chart(filename, &chart));
+            self.charts.insert(filename.to_string(), IndexedChart { stamp, song });
+            changed = true;
+        }
+
+        let count = self.charts.len();
+        self.charts.retain(|filename, _| present.contains(filename));
+        Ok(changed || self.charts.len() != count)
+    }
+
+    /// The valid charts, by title
+    pub fn songs(&self) -> Vec<LibrarySong> {
+        let mut songs: Vec<LibrarySong> = self.charts.values()
+            .filter_map(|indexed| indexed.song.clone())
+            .collect();
+        songs.sort_by(|a, b| a.title.cmp(&b.title));
+        songs
+    }
+
+    /// Songs whose title or artist contains `query` (any case, blank = all)
+    /// and that pass the filters, in the filters' order
+    pub fn search(&self, query: &str, filters: &SongFilters) -> Vec<LibrarySong> {
+        let query = query.trim().to_lowercase();
+        let mut songs: Vec<LibrarySong> = self.charts.values()
+            .filter_map(|indexed| indexed.song.as_ref())
+            .filter(|song| query.is_empty()
+                || song.title.to_lowercase().contains(&query)
+                || song.artist.to_lowercase().contains(&query))
+            .filter(|song| filters.keeps(song))
+            .cloned()
+            .collect();
+        songs.sort_by(|a, b| filters.sort.compare(a, b));
+        if filters.descending {
+            songs.reverse();
+        }
+        songs
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chart::ChordEvent;
+    use crate::editor::DEFAULT_LANE;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_rescan_only_parses_changed_charts() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+
+        let mut chart = SongChart::new("Blues", "Me", 120.0, [4, 4]).unwrap();
+        chart.set_chord("E", vec!["GREEN".to_string()]).unwrap();
+        chart.set_chord("A", vec!["RED".to_string()]).unwrap();
+        for bar in 0..8 {
+            let chord = if bar % 2 == 0 { "E" } else { "A" };
+            let event = ChordEvent { beat: bar as f64 * 2.0, dur: 2.0, chord: chord.to_string(), section: None };
+            chart.add_event(DEFAULT_LANE, event).unwrap();
+        }
+        fs::write(dir.join("blues.mitychart.json"), serde_json::to_string(&chart).unwrap()).unwrap();
+        fs::write(dir.join("broken.mitychart.json"), "not a chart").unwrap();
+        fs::write(dir.join("notes.txt"), "ignored").unwrap();
+
+        let mut index = LibraryIndex::default();
+        assert!(index.rescan(dir).unwrap());
+        let songs = index.songs();
+        assert_eq!(songs.len(), 1);
+        assert_eq!((songs[0].duration_seconds, songs[0].bpm, songs[0].chord_count), (8.0, 120.0, 2));
+        assert_eq!(songs[0].difficulty, Difficulty::Medium);
+        assert!(!index.rescan(dir).unwrap());
+
+        // The saved index is picked up without parsing again
+        let index_file = dir.join("index").join("library_index.json");
+        index.save(&index_file).unwrap();
+        let mut index = LibraryIndex::load(&index_file);
+        assert!(!index.rescan(dir).unwrap());
+
+        fs::remove_file(dir.join("blues.mitychart.json")).unwrap();
+        assert!(index.rescan(dir).unwrap());
+        assert!(index.songs().is_empty());
+    }
+
+    fn indexed(title: &str, artist: &str, bpm: f64, genre: Option<&str>, difficulty: Difficulty) -> (String, IndexedChart) {
+        let filename = format!("{}.mitychart.json", title.to_lowercase());
+        let song = LibrarySong {
+            id: filename.clone(),
+            title: title.to_string(),
+            artist: artist.to_string(),
+            filename: filename.clone(),
+            duration_seconds: 180.0,
+            bpm,
+            genre: genre.map(str::to_string),
+            difficulty,
+            chord_count: 4,
+        };
+        (filename, IndexedChart { stamp: FileStamp { modified: 0, size: 0 }, song: Some(song) })
+    }
+
+    #[test]
+    fn test_search_filters_and_sorts() {
+        let index = LibraryIndex {
+            charts: [
+                indexed("Paranoid", "Black Sabbath", 164.0, Some("metal"), Difficulty::Hard),
+                indexed("Iron Man", "Black Sabbath", 76.0, Some("Metal"), Difficulty::Medium),
+                indexed("Greensleeves", "Traditional", 90.0, Some("folk"), Difficulty::Easy),
+                indexed("Blues", "Me", 120.0, None, Difficulty::Medium),
+            ].into_iter().collect(),
+            ..Default::default()
+        };
+        let titles = |songs: Vec<LibrarySong>| songs.into_iter().map(|song| song.title).collect::<Vec<_>>();
+
+        assert_eq!(titles(index.search("sabbath", &SongFilters::default())), ["Iron Man", "Paranoid"]);
+        assert_eq!(titles(index.search(" GREEN", &SongFilters::default())), ["Greensleeves"]);
+
+        let metal = SongFilters { genre: Some("METAL".to_string()), sort: SongSort::Bpm, descending: true, ..Default::default() };
+        assert_eq!(titles(index.search("", &metal)), ["Paranoid", "Iron Man"]);
+
+        let filters = SongFilters { min_bpm: Some(80.0), max_bpm: Some(150.0), ..Default::default() };
+        assert_eq!(titles(index.search("", &filters)), ["Blues", "Greensleeves"]);
+
+        let filters = SongFilters { difficulties: vec![Difficulty::Easy, Difficulty::Medium], sort: SongSort::Difficulty, ..Default::default() };
+        assert_eq!(titles(index.search("", &filters)), ["Greensleeves", "Blues", "Iron Man"]);
+
+        let filters: SongFilters = serde_json::from_str(r#"{"sort": "artist", "difficulties": ["hard"]}"#).unwrap();
+        assert_eq!(titles(index.search("", &filters)), ["Paranoid"]);
+    }
+}
@@ -0,0 +1,231 @@
+use serde::{Deserialize, Serialize};
+
+/// Accent strength of a metronome click
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AccentLevel {
+    /// First pulse of the bar
+    Downbeat,
+    /// First pulse of an accent group (e.g. the "3" in 2+2+3)
+    Group,
+    /// Any other pulse
+    Weak,
+}
+
+/// Time signature with accent grouping.
+/// Beats are counted in the denominator unit, so 7/8 has 7 pulses per bar.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Meter {
+    pub time_sig: [u32; 2],
+    /// Pulses per accent group, summing to the numerator (e.g. [2, 2, 3])
+    pub grouping: Vec<u32>,
+}
+
+impl Meter {
+    /// Create a meter with the default grouping for the time signature
+    pub fn new(time_sig: [u32; 2]) -> Self {
+        Self {
+            time_sig,
+            grouping: default_grouping(time_sig),
+        }
+    }
+
+    /// Create a meter with an explicit grouping
+    pub fn with_grouping(time_sig: [u32; 2], grouping: Vec<u32>) -> anyhow::Result<Self> {
+        validate_grouping(time_sig, &grouping)?;
+        Ok(Self { time_sig, grouping })
+    }
+
+    pub fn beats_per_bar(&self) -> u32 {
+        self.time_sig[0].max(1)
+    }
+
+    /// Compound meters (6/8, 9/8, 12/8) are felt in groups of three
+    pub fn is_compound(&self) -> bool {
+        is_compound(self.time_sig)
+    }
+
+    /// Accent for a pulse index inside the bar (0-indexed)
+    pub fn accent_at(&self, pulse_in_bar: u32) -> AccentLevel {
+        let pulse = pulse_in_bar % self.beats_per_bar();
+        if pulse == 0 {
+            return AccentLevel::Downbeat;
+        }
+        let mut group_start = 0;
+        for size in &self.grouping {
+            if pulse == group_start {
+                return AccentLevel::Group;
+            }
+            group_start += size;
+        }
+        AccentLevel::Weak
+    }
+}
+
+fn is_compound(time_sig: [u32; 2]) -> bool {
+    time_sig[1] >= 8 && time_sig[0] > 3 && time_sig[0].is_multiple_of(3)
+}
+
+/// Default accent grouping for a time signature
+pub fn default_grouping(time_sig: [u32; 2]) -> Vec<u32> {
+    let numerator = time_sig[0].max(1);
+    if is_compound(time_sig) {
+        return vec![3; (numerator / 3) as usize];
+    }
+    match numerator {
+        5 => vec![3, 2],
+        7 => vec![2, 2, 3],
+        _ => vec![numerator],
+    }
+}
+
+/// Parse an accent grouping like "2+2+3"
+pub fn parse_grouping(text: &str) -> anyhow::Result<Vec<u32>> {
+    text.split('+')
+        .map(|part| {
+            part.trim()
+                .parse::<u32>()
+                .map_err(|_| anyhow::anyhow!("Invalid accent group '{}' in '{}'", part.trim(), text))
+        })
+        .collect()
+}
+
+/// Check that a grouping fills exactly one bar
+pub fn validate_grouping(time_sig: [u32; 2], grouping: &[u32]) -> anyhow::Result<()> {
+    if grouping.is_empty() || grouping.contains(&0) {
+        anyhow::bail!("Accent groups must be non-empty and positive");
+    }
+    let total: u32 = grouping.iter().sum();
+    if total != time_sig[0] {
+        anyhow::bail!(
+            "Accent grouping adds up to {} but the bar has {} beats",
+            total,
+            time_sig[0]
+        );
+    }
+    Ok(())
+}
+
+/// A metronome click at an integer beat
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MetronomeClick {
+    /// Absolute beat (negative during count-in)
+    pub beat: i64,
+    /// Bar number (negative during count-in)
+    pub bar: i64,
+    pub pulse_in_bar: u32,
+    pub accent: AccentLevel,
+}
+
+/// A beat of the count-in, for the click and the on-screen count
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CountInBeat {
+    pub click: MetronomeClick,
+    /// Beats left before the song starts, counting this one (the last is 1)
+    pub beats_left: u32,
+}
+
+impl CountInBeat {
+    /// Count-in beat for a click before beat 0 (None once the song has started)
+    pub fn from_click(click: MetronomeClick) -> Option<Self> {
+        (click.beat < 0).then(|| Self { click, beats_left: click.beat.unsigned_abs() as u32 })
+    }
+}
+
+/// Emits one click per beat crossed by the transport
+#[derive(Debug, Clone)]
+pub struct Metronome {
+    pub enabled: bool,
+    last_beat: Option<i64>,
+}
+
+impl Metronome {
+    pub fn new() -> Self {
+        Self {
+            enabled: true,
+            last_beat: None,
+        }
+    }
+
+    /// Forget the last clicked beat (after seek/stop)
+    pub fn reset(&mut self) {
+        self.last_beat = None;
+    }
+
+    /// Click for the most recent beat crossed since the previous call
+    pub fn tick(&mut self, meter: &Meter, current_beat: f64) -> Option<MetronomeClick> {
+        let beat = current_beat.floor() as i64;
+        if !self.enabled || self.last_beat == Some(beat) {
+            return None;
+        }
+        // Jumping backwards (seek) re-arms without clicking for skipped beats
+        self.last_beat = Some(beat);
+
+        let beats_per_bar = meter.beats_per_bar() as i64;
+        let pulse_in_bar = beat.rem_euclid(beats_per_bar) as u32;
+        Some(MetronomeClick {
+            beat,
+            bar: beat.div_euclid(beats_per_bar),
+            pulse_in_bar,
+            accent: meter.accent_at(pulse_in_bar),
+        })
+    }
+}
+
+impl Default for Metronome {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_groupings() {
+        assert_eq!(default_grouping([4, 4]), vec![4]);
+        assert_eq!(default_grouping([7, 8]), vec![2, 2, 3]);
+        assert_eq!(default_grouping([5, 4]), vec![3, 2]);
+        assert_eq!(default_grouping([12, 8]), vec![3, 3, 3, 3]);
+        assert!(Meter::new([6, 8]).is_compound());
+        assert!(!Meter::new([3, 4]).is_compound());
+    }
+
+    #[test]
+    fn test_accents_follow_grouping() {
+        let meter = Meter::with_grouping([7, 8], parse_grouping("2+2+3").unwrap()).unwrap();
+        let accents: Vec<_> = (0..7).map(|p| meter.accent_at(p)).collect();
+        assert_eq!(accents, vec![
+            AccentLevel::Downbeat, AccentLevel::Weak,
+            AccentLevel::Group, AccentLevel::Weak,
+            AccentLevel::Group, AccentLevel::Weak, AccentLevel::Weak,
+        ]);
+        assert!(Meter::with_grouping([7, 8], vec![3, 3]).is_err());
+    }
+
+    #[test]
+    fn test_metronome_clicks_once_per_beat() {
+        let meter = Meter::new([5, 4]);
+        let mut metronome = Metronome::new();
+
+        // Count-in bar starts at beat -5 and is accented like any other bar
+        let click = metronome.tick(&meter, -5.0).unwrap();
+        assert_eq!((click.bar, click.pulse_in_bar, click.accent), (-1, 0, AccentLevel::Downbeat));
+        assert!(metronome.tick(&meter, -4.5).is_none());
+
+        let click = metronome.tick(&meter, -2.0).unwrap();
+        assert_eq!(click.accent, AccentLevel::Group);
+    }
+
+    #[test]
+    fn test_count_in_beats_count_down() {
+        let meter = Meter::new([4, 4]);
+        let mut metronome = Metronome::new();
+        let first = CountInBeat::from_click(metronome.tick(&meter, -3.9).unwrap()).unwrap();
+        assert_eq!((first.beats_left, first.click.accent), (4, AccentLevel::Downbeat));
+        let last = CountInBeat::from_click(metronome.tick(&meter, -0.5).unwrap()).unwrap();
+        assert_eq!(last.beats_left, 1);
+        assert!(CountInBeat::from_click(metronome.tick(&meter, 0.0).unwrap()).is_none());
+    }
+}
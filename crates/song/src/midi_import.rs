@@ -0,0 +1,671 @@
+//! MIDI to chart conversion
+//! Reads a standard MIDI file (e.g. a guitar part exported from a DAW),
+//! quantizes note onsets to a grid, names the chord at each onset and assigns
+//! chords to five-lane fret combinations (most used chords on single frets).
+//! Tempo changes are followed through a tempo map, so notes keep their time
+//! on a chart clock at the first tempo. Only the first time signature is used;
+//! markers become sections.
+
+use crate::chart::*;
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// General MIDI percussion channel (0-indexed), skipped during conversion
+const DRUM_CHANNEL: u8 = 9;
+const DEFAULT_MICROS_PER_QUARTER: u32 = 500_000;
+
+/// Fret combinations handed out to chords, easiest first
+const FRET_COMBOS: &[&[&str]] = &[
+    &["GREEN"],
+    &["RED"],
+    &["YELLOW"],
+    &["BLUE"],
+    &["ORANGE"],
+    &["GREEN", "RED"],
+    &["RED", "YELLOW"],
+    &["YELLOW", "BLUE"],
+    &["BLUE", "ORANGE"],
+    &["GREEN", "YELLOW"],
+    &["RED", "BLUE"],
+    &["YELLOW", "ORANGE"],
+    &["GREEN", "BLUE"],
+    &["RED", "ORANGE"],
+    &["GREEN", "ORANGE"],
+    &["GREEN", "RED", "YELLOW"],
+    &["RED", "YELLOW", "BLUE"],
+    &["YELLOW", "BLUE", "ORANGE"],
+];
+
+/// Chord templates (intervals from the root) and their name suffixes,
+/// simplest first so ties go to the plainer chord
+const CHORD_TEMPLATES: &[(&[u8], &str)] = &[
+    (&[0, 4, 7], ""),
+    (&[0, 3, 7], "m"),
+    (&[0, 7], "5"),
+    (&[0, 4, 7, 10], "7"),
+    (&[0, 3, 7, 10], "m7"),
+    (&[0, 4, 7, 11], "maj7"),
+    (&[0, 2, 7], "sus2"),
+    (&[0, 5, 7], "sus4"),
+    (&[0, 2, 4, 7], "add9"),
+    (&[0, 3, 6], "dim"),
+    (&[0, 4, 8], "aug"),
+];
+
+const NOTE_NAMES: [&str; 12] = ["C", "C#", "D", "D#", "E", "F", "F#", "G", "G#", "A", "A#", "B"];
+
+/// A note with its start and end in MIDI ticks
+#[derive(Debug, Clone, PartialEq)]
+pub struct MidiNote {
+    pub start_tick: u64,
+    pub end_tick: u64,
+    pub key: u8,
+    pub velocity: u8,
+    pub channel: u8,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct MidiTrack {
+    pub name: Option<String>,
+    pub notes: Vec<MidiNote>,
+}
+
+/// The parts of a standard MIDI file the converter needs
+#[derive(Debug, Clone)]
+pub struct MidiFile {
+    pub ticks_per_quarter: u16,
+    /// Tempo at the start (microseconds per quarter note)
+    pub micros_per_quarter: u32,
+    /// Every tempo event (tick, microseconds per quarter note), by tick
+    pub tempos: Vec<(u64, u32)>,
+    pub time_sig: [u32; 2],
+    /// Marker meta events (tick, text)
+    pub markers: Vec<(u64, String)>,
+    pub tracks: Vec<MidiTrack>,
+}
+
+/// Conversion options
+#[derive(Debug, Clone)]
+pub struct MidiConvertOptions {
+    pub title: Option<String>,
+    pub artist: String,
+    /// Track to convert (index into `MidiFile::tracks`); None takes every track
+    pub track: Option<usize>,
+    /// Quantization grid in beats (0.5 = eighth notes in 4/4)
+    pub grid: f64,
+    /// Thinning: drop onsets closer than this (in beats) to the previous kept one
+    pub min_spacing: f64,
+    /// Thinning: at most this many distinct chords; rarer ones are folded into
+    /// the chord before them
+    pub max_chords: usize,
+    pub instrument: String,
+}
+
+impl Default for MidiConvertOptions {
+    fn default() -> Self {
+        Self {
+            title: None,
+            artist: "Unknown".to_string(),
+            track: None,
+            grid: 0.5,
+            min_spacing: 0.0,
+            max_chords: FRET_COMBOS.len(),
+            instrument: "Clean Guitar".to_string(),
+        }
+    }
+}
+
+impl MidiFile {
+    /// Tempo map over the file's ticks
+    pub fn tempo_map(&self) -> TempoMap {
+        let changes: Vec<(u64, f64)> = self.tempos.iter()
+            .map(|(tick, micros)| (*tick, 60_000_000.0 / *micros as f64))
+            .collect();
+        TempoMap::new(self.ticks_per_quarter as u32, &changes, 60_000_000.0 / DEFAULT_MICROS_PER_QUARTER as f64)
+    }
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8]) -> Self {
+        Self { data, pos: 0 }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pos >= self.data.len()
+    }
+
+    fn bytes(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos.checked_add(len).filter(|&end| end <= self.data.len())
+            .context("Unexpected end of MIDI data")?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn u8(&mut self) -> Result<u8> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16> {
+        let b = self.bytes(2)?;
+        Ok(u16::from_be_bytes([b[0], b[1]]))
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        let b = self.bytes(4)?;
+        Ok(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+    }
+
+    /// Variable-length quantity (at most 4 bytes)
+    fn vlq(&mut self) -> Result<u32> {
+        let mut value = 0u32;
+        for _ in 0..4 {
+            let byte = self.u8()?;
+            value = (value << 7) | (byte & 0x7f) as u32;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        bail!("Invalid variable-length quantity")
+    }
+}
+
+/// Parse a standard MIDI file (format 0 or 1)
+pub fn parse_midi(data: &[u8]) -> Result<MidiFile> {
+    let mut reader = Reader::new(data);
+    if reader.bytes(4)? != b"MThd" {
+        bail!("Not a MIDI file (missing MThd header)");
+    }
+    let header_len = reader.u32()? as usize;
+    let _format = reader.u16()?;
+    let track_count = reader.u16()?;
+    let division = reader.u16()?;
+    reader.bytes(header_len.saturating_sub(6))?;
+    if division & 0x8000 != 0 {
+        bail!("SMPTE time division is not supported");
+    }
+
+    let mut file = MidiFile {
+        ticks_per_quarter: division.max(1),
+        micros_per_quarter: 0,
+        tempos: Vec::new(),
+        time_sig: [0, 0],
+        markers: Vec::new(),
+        tracks: Vec::new(),
+    };
+
+    while file.tracks.len() < track_count as usize && !reader.is_empty() {
+        let id = reader.bytes(4)?;
+        let len = reader.u32()? as usize;
+        let chunk = reader.bytes(len)?;
+        if id == b"MTrk" {
+            let track = parse_track(chunk, &mut file)?;
+            file.tracks.push(track);
+        }
+    }
+
+    // Tempo events can sit in any track of a format 1 file
+    file.tempos.sort_by_key(|(tick, _)| *tick);
+    file.micros_per_quarter = file.tempos.iter()
+        .take_while(|(tick, _)| *tick == 0)
+        .last()
+        .map_or(DEFAULT_MICROS_PER_QUARTER, |(_, micros)| *micros);
+    if file.time_sig[0] == 0 {
+        file.time_sig = [4, 4];
+    }
+    file.markers.sort_by_key(|(tick, _)| *tick);
+    Ok(file)
+}
+
+fn parse_track(chunk: &[u8], file: &mut MidiFile) -> Result<MidiTrack> {
+    let mut reader = Reader::new(chunk);
+    let mut track = MidiTrack::default();
+    let mut tick = 0u64;
+    let mut running_status = None;
+    // Open notes per (channel, key), oldest first
+    let mut open: HashMap<(u8, u8), Vec<(u64, u8)>> = HashMap::new();
+
+    while !reader.is_empty() {
+        tick += reader.vlq()? as u64;
+        let mut status = reader.u8()?;
+        let first_data = if status < 0x80 {
+            let data = status;
+            status = running_status.context("Data byte without running status")?;
+            Some(data)
+        } else {
+            None
+        };
+
+        match status {
+            0xff => {
+                let kind = reader.u8()?;
+                let len = reader.vlq()? as usize;
+                let data = reader.bytes(len)?;
+                match kind {
+                    0x03 if track.name.is_none() => {
+                        track.name = Some(String::from_utf8_lossy(data).trim().to_string());
+                    }
+                    0x06 => file.markers.push((tick, String::from_utf8_lossy(data).trim().to_string())),
+                    0x51 if len == 3 => {
+                        let micros = u32::from_be_bytes([0, data[0], data[1], data[2]]);
+                        if micros > 0 {
+                            file.tempos.push((tick, micros));
+                        }
+                    }
+                    0x58 if len >= 2 && file.time_sig[0] == 0 => {
+                        file.time_sig = [data[0] as u32, 1u32 << data[1].min(5)];
+                    }
+                    0x2f => break,
+                    _ => {}
+                }
+            }
+            0xf0 | 0xf7 => {
+                let len = reader.vlq()? as usize;
+                reader.bytes(len)?;
+            }
+            0x80..=0xef => {
+                running_status = Some(status);
+                let kind = status & 0xf0;
+                let channel = status & 0x0f;
+                let data1 = match first_data {
+                    Some(data) => data,
+                    None => reader.u8()?,
+                };
+                let data2 = if kind == 0xc0 || kind == 0xd0 { 0 } else { reader.u8()? };
+
+                if kind == 0x90 && data2 > 0 {
+                    open.entry((channel, data1)).or_default().push((tick, data2));
+                } else if kind == 0x80 || kind == 0x90 {
+                    let started = open.get_mut(&(channel, data1))
+                        .filter(|starts| !starts.is_empty())
+                        .map(|starts| starts.remove(0));
+                    if let Some((start_tick, velocity)) = started {
+                        track.notes.push(MidiNote { start_tick, end_tick: tick, key: data1, velocity, channel });
+                    }
+                }
+            }
+            _ => bail!("Unsupported MIDI status byte 0x{:02x}", status),
+        }
+    }
+
+    // Notes never released end with the track
+    for ((channel, key), starts) in open {
+        for (start_tick, velocity) in starts {
+            track.notes.push(MidiNote { start_tick, end_tick: tick.max(start_tick), key, velocity, channel });
+        }
+    }
+    track.notes.sort_by_key(|note| (note.start_tick, note.key));
+    Ok(track)
+}
+
+/// Name the chord formed by a set of MIDI keys (the lowest key is preferred
+/// as root). A single pitch is named as a power chord.
+pub fn recognize_chord(keys: &[u8]) -> Option<String> {
+    let bass = *keys.iter().min()?;
+    let mut pitch_classes = [false; 12];
+    for key in keys {
+        pitch_classes[(key % 12) as usize] = true;
+    }
+
+    let mut best: Option<(i32, String)> = None;
+    for root in 0..12u8 {
+        if !pitch_classes[root as usize] {
+            continue;
+        }
+        for (intervals, suffix) in CHORD_TEMPLATES {
+            let in_template = |pc: usize| intervals.contains(&(((pc + 12 - root as usize) % 12) as u8));
+            let matched = intervals.iter().filter(|&&i| pitch_classes[(root + i) as usize % 12]).count() as i32;
+            let missing = intervals.len() as i32 - matched;
+            let extra = (0..12).filter(|&pc| pitch_classes[pc] && !in_template(pc)).count() as i32;
+            let bass_bonus = if root == bass % 12 { 1 } else { 0 };
+            let score = matched * 2 - missing * 2 - extra * 2 + bass_bonus;
+            let better = match &best {
+                Some((best_score, _)) => score > *best_score,
+                None => true,
+            };
+            if better {
+                best = Some((score, format!("{}{}", NOTE_NAMES[root as usize], suffix)));
+            }
+        }
+    }
+    best.map(|(_, name)| name)
+}
+
+/// Convert a parsed MIDI file into a chart (all non-drum tracks are merged)
+pub fn midi_to_chart(midi: &MidiFile, options: &MidiConvertOptions) -> Result<SongChart> {
+    let grid = if options.grid > 0.0 { options.grid } else { 0.5 };
+    // Chart beats count the time signature's denominator unit
+    let beat_unit = midi.time_sig[1].max(1) as f64 / 4.0;
+    // Ticks after a tempo change land where they sound on the first tempo's clock
+    let tempo_map = midi.tempo_map();
+    let to_beat = |tick: u64| tempo_map.quarters_at(tick) * beat_unit;
+    // Quantize on the written grid, before tempo changes move the ticks
+    let ticks_per_step = midi.ticks_per_quarter as f64 / beat_unit * grid;
+    let quantize = |tick: u64| to_beat(((tick as f64 / ticks_per_step).round() * ticks_per_step).round() as u64);
+
+    let tracks = match options.track {
+        Some(index) => {
+            let track = midi.tracks.get(index)
+                .with_context(|| format!("No track {} (the file has {})", index, midi.tracks.len()))?;
+            std::slice::from_ref(track)
+        }
+        None => midi.tracks.as_slice(),
+    };
+    let mut notes: Vec<&MidiNote> = tracks.iter()
+        .flat_map(|track| &track.notes)
+        .filter(|note| note.channel != DRUM_CHANNEL)
+        .collect();
+    if notes.is_empty() {
+        bail!("MIDI file has no (non-drum) notes");
+    }
+
+    // Group notes by quantized onset
+    let mut onsets: Vec<(f64, Vec<u8>)> = Vec::new();
+    let mut song_end: f64 = 0.0;
+    notes.sort_by_key(|note| note.start_tick);
+    for note in notes {
+        let beat = quantize(note.start_tick);
+        song_end = song_end.max(to_beat(note.end_tick));
+        match onsets.last_mut() {
+            Some((last, keys)) if (*last - beat).abs() < 1e-9 => keys.push(note.key),
+            _ => onsets.push((beat, vec![note.key])),
+        }
+    }
+
+    let mut events: Vec<(f64, String)> = Vec::new();
+    for (beat, keys) in onsets {
+        let Some(chord) = recognize_chord(&keys) else { continue };
+        // Single notes that belong to the current chord are arpeggios, not changes
+        let arpeggio = keys.len() == 1 && events.last().is_some_and(|(_, previous)| {
+            chord_contains(previous, keys[0])
+        });
+        if arpeggio {
+            continue;
+        }
+        // Thinning: skip onsets crowding the previous one
+        if events.last().is_some_and(|(previous, _)| beat - previous < options.min_spacing - 1e-9) {
+            continue;
+        }
+        events.push((beat, chord));
+    }
+
+    // Thinning: fold rare chords into the chord before them
+    let max_chords = options.max_chords.clamp(1, FRET_COMBOS.len());
+    let ranked = rank_chords(&events, song_end);
+    if ranked.len() > max_chords {
+        let kept: Vec<&String> = ranked.iter().take(max_chords).collect();
+        let mut current = ranked[0].clone();
+        for (_, chord) in events.iter_mut() {
+            if kept.contains(&&*chord) {
+                current = chord.clone();
+            } else {
+                *chord = current.clone();
+            }
+        }
+    }
+    let ranked = rank_chords(&events, song_end);
+
+    let mut chords = HashMap::new();
+    for (chord, frets) in ranked.iter().zip(FRET_COMBOS) {
+        chords.insert(chord.clone(), ChordMapping {
+            frets: frets.iter().map(|fret| fret.to_string()).collect(),
+        });
+    }
+
+    let sections = sections_from_markers(midi, &to_beat, song_end);
+    let section_at = |beat: f64| sections.iter()
+        .find(|s| beat >= s.from_beat && beat < s.to_beat)
+        .map(|s| s.name.clone());
+    let lane_events = events.iter().enumerate().map(|(index, (beat, chord))| {
+        let end = events.get(index + 1).map(|(next, _)| *next).unwrap_or(song_end.max(beat + grid));
+        ChordEvent {
+            beat: *beat,
+            dur: (end - beat).max(grid),
+            chord: chord.clone(),
+            section: section_at(*beat),
+        }
+    }).collect();
+
+    let title = options.title.clone()
+        .or_else(|| tracks.iter().find_map(|track| track.name.clone()).filter(|name| !name.is_empty()))
+        .unwrap_or_else(|| "Untitled".to_string());
+    let quarter_bpm = tempo_map.first_bpm();
+
+    let chart = SongChart {
+        meta: SongMeta {
+            title,
+            artist: options.artist.clone(),
+            youtube: None,
+            spotify: None,
+            backing_track: None,
+            instrument: None,
+        },
+        clock: ClockSettings {
+            bpm: (quarter_bpm * beat_unit * 100.0).round() / 100.0,
+            time_sig: midi.time_sig,
+            count_in_bars: 1,
+            accent_grouping: None,
+        },
+        playback: PlaybackSettings {
+            default_instrument: InstrumentRef {
+                instrument_type: "soundfont".to_string(),
+                label: options.instrument.clone(),
+            },
+            fallback_instrument: InstrumentRef {
+                instrument_type: "virtual".to_string(),
+                label: "Basic Guitar".to_string(),
+            },
+            allow_user_override_instrument: true,
+        },
+        mapping: MappingSettings { preset: None, chords, key: None, genre: None },
+        lanes: vec![Lane { name: "Main".to_string(), events: lane_events }],
+        lyrics: Vec::new(),
+        sections,
+        star_phrases: Vec::new(),
+    };
+    chart.validate()?;
+    Ok(chart)
+}
+
+/// Read and convert a .mid file
+pub fn convert_midi_file(path: &Path, options: &MidiConvertOptions) -> Result<SongChart> {
+    let data = std::fs::read(path).context(format!("Failed to read {}", path.display()))?;
+    let midi = parse_midi(&data)?;
+    let mut options = options.clone();
+    if options.title.is_none() {
+        options.title = path.file_stem().map(|stem| stem.to_string_lossy().to_string());
+    }
+    midi_to_chart(&midi, &options)
+}
+
+/// Whether a chord name produced by `recognize_chord` contains the key's pitch class
+fn chord_contains(chord: &str, key: u8) -> bool {
+    let root_len = if chord.as_bytes().get(1) == Some(&b'#') { 2 } else { 1 };
+    let (root, suffix) = chord.split_at(root_len.min(chord.len()));
+    let Some(root) = NOTE_NAMES.iter().position(|name| *name == root) else {
+        return false;
+    };
+    CHORD_TEMPLATES.iter()
+        .find(|(_, name)| *name == suffix)
+        .is_some_and(|(intervals, _)| intervals.contains(&(((key as usize % 12) + 12 - root) as u8 % 12)))
+}
+
+/// Distinct chords, most played (by total length) first
+fn rank_chords(events: &[(f64, String)], song_end: f64) -> Vec<String> {
+    let mut totals: Vec<(String, f64, usize)> = Vec::new();
+    for (index, (beat, chord)) in events.iter().enumerate() {
+        let end = events.get(index + 1).map(|(next, _)| *next).unwrap_or(song_end);
+        match totals.iter_mut().find(|(name, _, _)| name == chord) {
+            Some(entry) => entry.1 += end - beat,
+            None => totals.push((chord.clone(), end - beat, index)),
+        }
+    }
+    // Longest first; first appearance breaks ties
+    totals.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal).then(a.2.cmp(&b.2)));
+    totals.into_iter().map(|(name, _, _)| name).collect()
+}
+
+fn sections_from_markers(midi: &MidiFile, to_beat: &dyn Fn(u64) -> f64, song_end: f64) -> Vec<Section> {
+    let markers: Vec<(f64, &String)> = midi.markers.iter()
+        .filter(|(_, name)| !name.is_empty())
+        .map(|(tick, name)| (to_beat(*tick), name))
+        .collect();
+    markers.iter().enumerate()
+        .map(|(index, (from_beat, name))| Section {
+            name: (*name).clone(),
+            from_beat: *from_beat,
+            to_beat: markers.get(index + 1).map(|(next, _)| *next).unwrap_or(song_end),
+            fret_leds: None,
+        })
+        .filter(|section| section.to_beat > section.from_beat)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Build a format-0 MIDI file with 480 ticks per quarter
+    fn midi_bytes(events: &[(u32, Vec<u8>)]) -> Vec<u8> {
+        let mut track = Vec::new();
+        for (delta, bytes) in events {
+            let mut vlq = vec![(*delta & 0x7f) as u8];
+            let mut rest = *delta >> 7;
+            while rest > 0 {
+                vlq.insert(0, (rest & 0x7f) as u8 | 0x80);
+                rest >>= 7;
+            }
+            track.extend(vlq);
+            track.extend(bytes);
+        }
+        track.extend([0, 0xff, 0x2f, 0]);
+
+        let mut data = b"MThd".to_vec();
+        data.extend(6u32.to_be_bytes());
+        data.extend([0, 0, 0, 1, 0x01, 0xe0]);
+        data.extend(b"MTrk");
+        data.extend((track.len() as u32).to_be_bytes());
+        data.extend(track);
+        data
+    }
+
+    fn chord(delta: u32, keys: &[u8], length: u32) -> Vec<(u32, Vec<u8>)> {
+        let mut events: Vec<(u32, Vec<u8>)> = keys.iter().enumerate()
+            .map(|(i, &key)| (if i == 0 { delta } else { 0 }, vec![0x90, key, 100]))
+            .collect();
+        // Note-offs as note-on with velocity 0, using running status
+        events.extend(keys.iter().enumerate().map(|(i, &key)| (if i == 0 { length } else { 0 }, vec![key, 0])));
+        events
+    }
+
+    #[test]
+    fn test_recognize_chord() {
+        assert_eq!(recognize_chord(&[48, 52, 55]).as_deref(), Some("C"));
+        assert_eq!(recognize_chord(&[57, 60, 64, 69]).as_deref(), Some("Am"));
+        assert_eq!(recognize_chord(&[40, 47, 52]).as_deref(), Some("E5"));
+        assert_eq!(recognize_chord(&[47, 51, 54, 57]).as_deref(), Some("B7"));
+        assert_eq!(recognize_chord(&[43]).as_deref(), Some("G5"));
+        assert_eq!(recognize_chord(&[]), None);
+    }
+
+    #[test]
+    fn test_convert_quantizes_and_maps_frets() {
+        let mut events = vec![
+            (0, vec![0xff, 0x51, 3, 0x07, 0xa1, 0x20]), // 120 BPM
+            (0, vec![0xff, 0x06, 5, b'V', b'e', b'r', b's', b'e']),
+        ];
+        // C for two bars (played slightly late), Am for one, C again
+        events.extend(chord(10, &[48, 52, 55], 1910));
+        events.extend(chord(0, &[48, 52, 55], 1920));
+        events.extend(chord(0, &[45, 48, 52], 1920));
+        events.extend(chord(0, &[48, 52, 55], 1920));
+        let midi = parse_midi(&midi_bytes(&events)).unwrap();
+        assert_eq!(midi.tracks[0].notes.len(), 12);
+
+        let chart = midi_to_chart(&midi, &MidiConvertOptions::default()).unwrap();
+        assert_eq!(chart.clock.bpm, 120.0);
+        let lane = &chart.lanes[0].events;
+        assert_eq!(lane.len(), 4);
+        assert_eq!(lane[0].beat, 0.0);
+        assert_eq!(lane[2].beat, 8.0);
+        assert_eq!(lane[2].chord, "Am");
+        assert_eq!(lane[0].section.as_deref(), Some("Verse"));
+        assert_eq!(chart.mapping.chords["C"].frets, vec!["GREEN"]);
+        assert_eq!(chart.mapping.chords["Am"].frets, vec!["RED"]);
+        assert!(chart.lint().is_empty());
+    }
+
+    #[test]
+    fn test_thinning() {
+        let mut events = Vec::new();
+        // G, then D and a quick passing Em on the off-beat, then G with an arpeggiated B
+        events.extend(chord(0, &[43, 47, 50], 960));
+        events.extend(chord(0, &[50, 54, 57], 240));
+        events.extend(chord(0, &[52, 55, 59], 720));
+        events.extend(chord(0, &[43, 47, 50], 480));
+        events.extend(chord(0, &[59], 480));
+        let midi = parse_midi(&midi_bytes(&events)).unwrap();
+
+        let full = midi_to_chart(&midi, &MidiConvertOptions::default()).unwrap();
+        let names: Vec<&str> = full.lanes[0].events.iter().map(|e| e.chord.as_str()).collect();
+        assert_eq!(names, vec!["G", "D", "Em", "G"]);
+
+        let options = MidiConvertOptions { min_spacing: 1.0, max_chords: 1, ..Default::default() };
+        let easy = midi_to_chart(&midi, &options).unwrap();
+        let names: Vec<&str> = easy.lanes[0].events.iter().map(|e| e.chord.as_str()).collect();
+        assert_eq!(names, vec!["G", "G", "G"]);
+        assert_eq!(easy.mapping.chords.len(), 1);
+    }
+
+    #[test]
+    fn test_track_selection() {
+        let mut events = vec![(0, vec![0xff, 0x03, 6, b'G', b'u', b'i', b't', b'a', b'r'])];
+        events.extend(chord(0, &[48, 52, 55], 1920));
+        let mut midi = parse_midi(&midi_bytes(&events)).unwrap();
+        let bass = parse_midi(&midi_bytes(&chord(0, &[33], 1920))).unwrap().tracks.remove(0);
+        midi.tracks.push(MidiTrack { name: Some("Bass".to_string()), ..bass });
+
+        let guitar = midi_to_chart(&midi, &MidiConvertOptions { track: Some(0), ..Default::default() }).unwrap();
+        assert_eq!(guitar.meta.title, "Guitar");
+        assert_eq!(guitar.lanes[0].events[0].chord, "C");
+        let bass = midi_to_chart(&midi, &MidiConvertOptions { track: Some(1), ..Default::default() }).unwrap();
+        assert_eq!(bass.meta.title, "Bass");
+        assert_eq!(bass.lanes[0].events[0].chord, "A5");
+        assert!(midi_to_chart(&midi, &MidiConvertOptions { track: Some(2), ..Default::default() }).is_err());
+    }
+
+    #[test]
+    fn test_import_follows_tempo_changes() {
+        // Two bars of C and Am at 120 BPM, then G and C after a drop to 60 BPM
+        let mut events = vec![(0, vec![0xff, 0x51, 3, 0x07, 0xa1, 0x20])];
+        events.extend(chord(0, &[48, 52, 55], 1920));
+        events.extend(chord(0, &[45, 48, 52], 1920));
+        events.push((0, vec![0xff, 0x51, 3, 0x0f, 0x42, 0x40]));
+        events.extend(chord(0, &[43, 47, 50], 1920));
+        events.extend(chord(0, &[48, 52, 55], 1920));
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("tempo_change.mid");
+        std::fs::write(&path, midi_bytes(&events)).unwrap();
+
+        let midi = parse_midi(&std::fs::read(&path).unwrap()).unwrap();
+        assert_eq!(midi.tempos, vec![(0, 500_000), (3840, 1_000_000)]);
+        assert_eq!(midi.micros_per_quarter, 500_000);
+        assert_eq!(midi.tempo_map().seconds_at(5760), 8.0);
+
+        let chart = convert_midi_file(&path, &MidiConvertOptions { track: Some(0), ..Default::default() }).unwrap();
+        assert_eq!(chart.meta.title, "tempo_change");
+        // The clock keeps the first tempo, so bars at half speed take twice the beats
+        assert_eq!(chart.clock.bpm, 120.0);
+        let events: Vec<(f64, f64, &str)> = chart.lanes[0].events.iter()
+            .map(|e| (e.beat, e.dur, e.chord.as_str()))
+            .collect();
+        assert_eq!(events, vec![(0.0, 4.0, "C"), (4.0, 4.0, "Am"), (8.0, 8.0, "G"), (16.0, 8.0, "C")]);
+        assert_eq!(chart.beat_to_seconds(16.0, 1.0), 8.0);
+    }
+}
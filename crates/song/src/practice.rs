@@ -0,0 +1,276 @@
+//! Practice analytics
+//! Counts what is played during a session (strums, chords) and how far song
+//! hits land from their notes, per chord. Nothing leaves the machine; the
+//! session only lives as long as the app runs. The report names chords by
+//! scale degree when the key is known, so a tendency like rushing every bVII
+//! shows up whatever key the songs are in.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::chart::SongKey;
+use crate::hit_detection::HitResult;
+
+/// Edges of the timing histogram buckets in ms (negative = early)
+pub const TIMING_BUCKET_EDGES_MS: [f64; 6] = [-100.0, -60.0, -20.0, 20.0, 60.0, 100.0];
+/// Hits of a degree needed before its timing counts as a tendency
+pub const MIN_TENDENCY_HITS: usize = 8;
+/// Average offset that counts as early or late
+pub const TENDENCY_MS: f64 = 25.0;
+/// Share of hits that must land on the same side of the note
+pub const TENDENCY_SHARE: f64 = 0.7;
+
+/// Degree names by semitones above the key root
+const DEGREES: [&str; 12] = ["I", "bII", "II", "bIII", "III", "IV", "#IV", "V", "bVI", "VI", "bVII", "VII"];
+
+#[derive(Debug, Clone, Default)]
+struct ChordPractice {
+    played: u32,
+    offsets_ms: Vec<f64>,
+}
+
+/// Analytics of the running practice session
+#[derive(Debug, Clone)]
+pub struct PracticeSession {
+    /// Seconds since the Unix epoch
+    started_at: u64,
+    strums: u32,
+    hits: u32,
+    misses: u32,
+    chords: BTreeMap<String, ChordPractice>,
+}
+
+/// Hits landing in one timing range
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TimingBucket {
+    /// None = open-ended
+    pub from_ms: Option<f64>,
+    pub to_ms: Option<f64>,
+    pub count: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChordReport {
+    pub chord: String,
+    /// Scale degree in the key ("bVII", "vi"), when the key and root are known
+    pub degree: Option<String>,
+    /// Strums while holding the chord
+    pub played: u32,
+    /// Song notes of the chord that were hit
+    pub hits: u32,
+    /// Average hit offset (positive = late)
+    pub mean_offset_ms: Option<f64>,
+    /// Standard deviation of the hit offsets
+    pub spread_ms: Option<f64>,
+    pub histogram: Vec<TimingBucket>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PracticeReport {
+    pub started_at: u64,
+    pub duration_seconds: u64,
+    pub strums: u32,
+    pub hits: u32,
+    pub misses: u32,
+    /// Most played first
+    pub chords: Vec<ChordReport>,
+    /// Timing tendencies in plain words
+    pub insights: Vec<String>,
+}
+
+impl PracticeSession {
+    pub fn new(started_at: u64) -> Self {
+        Self { started_at, strums: 0, hits: 0, misses: 0, chords: BTreeMap::new() }
+    }
+
+    /// Count a strum and the chord it played (None = not a known chord)
+    pub fn record_strum(&mut self, chord: Option<&str>) {
+        self.strums += 1;
+        if let Some(chord) = chord {
+            self.chords.entry(chord.to_string()).or_default().played += 1;
+        }
+    }
+
+    /// Count a song hit or miss; hits add their offset to the chord's timing
+    pub fn record_hit(&mut self, result: &HitResult) {
+        match result {
+            HitResult::Hit { event, offset_ms, .. } => {
+                self.hits += 1;
+                self.chords.entry(event.chord.clone()).or_default().offsets_ms.push(*offset_ms);
+            }
+            HitResult::Miss { .. } => self.misses += 1,
+        }
+    }
+
+    /// Aggregates so far, with degrees taken from `key`
+    pub fn report(&self, key: Option<&SongKey>, now: u64) -> PracticeReport {
+        let mut chords: Vec<ChordReport> = self.chords.iter()
+            .map(|(chord, practice)| ChordReport {
+                chord: chord.clone(),
+                degree: key.and_then(|key| degree(key, chord)),
+                played: practice.played,
+                hits: practice.offsets_ms.len() as u32,
+                mean_offset_ms: mean(&practice.offsets_ms),
+                spread_ms: spread(&practice.offsets_ms),
+                histogram: histogram(&practice.offsets_ms),
+            })
+            .collect();
+        chords.sort_by_key(|report| std::cmp::Reverse(report.played + report.hits));
+
+        // Chords of the same degree (or name, without a key) are judged together
+        let mut offsets_by_label: BTreeMap<String, Vec<f64>> = BTreeMap::new();
+        for report in &chords {
+            let label = report.degree.clone().unwrap_or_else(|| report.chord.clone());
+            offsets_by_label.entry(label).or_default().extend(&self.chords[&report.chord].offsets_ms);
+        }
+        let insights = offsets_by_label.iter()
+            .filter_map(|(label, offsets)| tendency(offsets).map(|(side, mean)| {
+                format!("You're consistently {} on {} changes ({:+.0} ms on average)", side, label, mean)
+            }))
+            .collect();
+
+        PracticeReport {
+            started_at: self.started_at,
+            duration_seconds: now.saturating_sub(self.started_at),
+            strums: self.strums,
+            hits: self.hits,
+            misses: self.misses,
+            chords,
+            insights,
+        }
+    }
+}
+
+/// Scale degree of a chord symbol in a key; minor chords get lowercase numerals
+fn degree(key: &SongKey, chord: &str) -> Option<String> {
+    let (key_root, _) = pitch_class(&key.root)?;
+    let (chord_root, quality) = pitch_class(chord)?;
+    let degree = DEGREES[((chord_root + 12 - key_root) % 12) as usize];
+    let minor = quality.starts_with('m') && !quality.starts_with("maj") || quality.starts_with("dim");
+    Some(if minor { degree.to_lowercase() } else { degree.to_string() })
+}
+
+/// Pitch class of a note name or chord root, and the rest of the symbol
+fn pitch_class(symbol: &str) -> Option<(u8, &str)> {
+    let mut chars = symbol.chars();
+    let semitone: i8 = match chars.next()?.to_ascii_uppercase() {
+        'C' => 0,
+        'D' => 2,
+        'E' => 4,
+        'F' => 5,
+        'G' => 7,
+        'A' => 9,
+        'B' => 11,
+        _ => return None,
+    };
+    let rest = chars.as_str();
+    let (semitone, rest) = match rest.chars().next() {
+        Some('#') => (semitone + 1, &rest[1..]),
+        Some('b') => (semitone - 1, &rest[1..]),
+        _ => (semitone, rest),
+    };
+    Some((semitone.rem_euclid(12) as u8, rest))
+}
+
+fn mean(values: &[f64]) -> Option<f64> {
+    (!values.is_empty()).then(|| values.iter().sum::<f64>() / values.len() as f64)
+}
+
+fn spread(values: &[f64]) -> Option<f64> {
+    let mean = mean(values)?;
+    Some((values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / values.len() as f64).sqrt())
+}
+
+fn histogram(offsets_ms: &[f64]) -> Vec<TimingBucket> {
+    let edges: Vec<Option<f64>> = std::iter::once(None)
+        .chain(TIMING_BUCKET_EDGES_MS.iter().copied().map(Some))
+        .chain(std::iter::once(None))
+        .collect();
+    edges.windows(2)
+        .map(|edge| TimingBucket {
+            from_ms: edge[0],
+            to_ms: edge[1],
+            count: offsets_ms.iter()
+                .filter(|offset| edge[0].is_none_or(|from| **offset >= from) && edge[1].is_none_or(|to| **offset < to))
+                .count() as u32,
+        })
+        .collect()
+}
+
+/// "early" or "late" with the mean offset, if the offsets lean one way
+fn tendency(offsets_ms: &[f64]) -> Option<(&'static str, f64)> {
+    if offsets_ms.len() < MIN_TENDENCY_HITS {
+        return None;
+    }
+    let mean = mean(offsets_ms)?;
+    if mean.abs() < TENDENCY_MS {
+        return None;
+    }
+    let same_side = offsets_ms.iter().filter(|offset| offset.signum() == mean.signum()).count();
+    if (same_side as f64) < offsets_ms.len() as f64 * TENDENCY_SHARE {
+        return None;
+    }
+    Some((if mean > 0.0 { "late" } else { "early" }, mean))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit_detection::{ChordEventHit, HitTiming, MissReason};
+
+    fn hit(chord: &str, offset_ms: f64) -> HitResult {
+        HitResult::Hit {
+            event: ChordEventHit { beat: 0.0, chord: chord.to_string(), is_sustain: false },
+            accuracy: 1.0,
+            timing: HitTiming::Perfect,
+            offset_ms,
+        }
+    }
+
+    #[test]
+    fn test_report_counts_and_histogram() {
+        let mut session = PracticeSession::new(100);
+        session.record_strum(Some("G"));
+        session.record_strum(Some("G"));
+        session.record_strum(Some("Em"));
+        session.record_strum(None);
+        session.record_hit(&hit("G", -30.0));
+        session.record_hit(&hit("G", 10.0));
+        session.record_hit(&hit("G", 150.0));
+        session.record_hit(&HitResult::Miss { reason: MissReason::WrongFrets });
+
+        let key = SongKey::parse("G major").unwrap();
+        let report = session.report(Some(&key), 160);
+        assert_eq!((report.strums, report.hits, report.misses, report.duration_seconds), (4, 3, 1, 60));
+        let g = &report.chords[0];
+        assert_eq!((g.chord.as_str(), g.degree.as_deref(), g.played, g.hits), ("G", Some("I"), 2, 3));
+        assert!((g.mean_offset_ms.unwrap() - 130.0 / 3.0).abs() < 1e-9);
+        let counts: Vec<u32> = g.histogram.iter().map(|bucket| bucket.count).collect();
+        assert_eq!(counts, vec![0, 0, 1, 1, 0, 0, 1]);
+        assert_eq!(report.chords[1].degree.as_deref(), Some("vi"));
+        assert_eq!(report.chords[1].mean_offset_ms, None);
+        // Too few hits to call it a tendency
+        assert!(report.insights.is_empty());
+    }
+
+    #[test]
+    fn test_consistent_lateness_by_degree() {
+        let mut session = PracticeSession::new(0);
+        // D and D7 are both the bVII of E, and count towards the same degree
+        for offset in [40.0, 55.0, 30.0, 45.0] {
+            session.record_hit(&hit("D", offset));
+            session.record_hit(&hit("D7", offset));
+        }
+        session.record_hit(&hit("E", 5.0));
+        let e = SongKey::parse("E").unwrap();
+        let report = session.report(Some(&e), 10);
+        assert_eq!(report.insights, vec!["You're consistently late on bVII changes (+42 ms on average)".to_string()]);
+
+        // Without a key each chord stands alone, with too few hits for a tendency
+        assert!(session.report(None, 10).insights.is_empty());
+        assert_eq!(degree(&e, "Bbmaj7").as_deref(), Some("#IV"));
+        assert_eq!(degree(&e, "C#m7").as_deref(), Some("vi"));
+    }
+}
@@ -0,0 +1,151 @@
+//! Quantized live changes
+//! Switching genre, key or instrument mid-phrase clashes with the band, so a
+//! change can wait for the next bar (or beat) of the transport instead. Queued
+//! changes carry the beat they become due and are taken once the transport
+//! reaches it. A newer change of the same kind replaces the queued one, so
+//! flicking through genres only applies the last pick.
+
+use serde::{Deserialize, Serialize};
+use std::mem::discriminant;
+
+use crate::meter::Meter;
+
+/// Where deferred changes land
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum QuantizeGrid {
+    /// Apply immediately
+    #[default]
+    Off,
+    /// Next beat
+    Beat,
+    /// Next downbeat
+    Bar,
+}
+
+impl QuantizeGrid {
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "off" => Some(Self::Off),
+            "beat" => Some(Self::Beat),
+            "bar" => Some(Self::Bar),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Off => "off",
+            Self::Beat => "beat",
+            Self::Bar => "bar",
+        }
+    }
+
+    /// First grid line after `beat` (None when off)
+    pub fn next_boundary(&self, meter: &Meter, beat: f64) -> Option<f64> {
+        let step = match self {
+            Self::Off => return None,
+            Self::Beat => 1.0,
+            Self::Bar => meter.beats_per_bar() as f64,
+        };
+        Some(((beat / step).floor() + 1.0) * step)
+    }
+}
+
+/// A change waiting for its beat
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ScheduledChange<T> {
+    pub due_beat: f64,
+    pub change: T,
+}
+
+/// Queue of changes deferred to a beat of the transport
+#[derive(Debug, Clone)]
+pub struct ChangeScheduler<T> {
+    pending: Vec<ScheduledChange<T>>,
+}
+
+impl<T> ChangeScheduler<T> {
+    pub fn new() -> Self {
+        Self { pending: Vec::new() }
+    }
+
+    /// Queue a change (replaces a queued change of the same kind)
+    pub fn schedule(&mut self, change: T, due_beat: f64) {
+        self.pending.retain(|pending| discriminant(&pending.change) != discriminant(&change));
+        self.pending.push(ScheduledChange { due_beat, change });
+    }
+
+    /// Remove and return the changes due at `beat`, in the order they were queued
+    pub fn take_due(&mut self, beat: f64) -> Vec<T> {
+        let (due, waiting) = std::mem::take(&mut self.pending)
+            .into_iter()
+            .partition(|pending| pending.due_beat <= beat);
+        self.pending = waiting;
+        due.into_iter().map(|pending| pending.change).collect()
+    }
+
+    /// Remove and return everything (e.g. when the transport stops)
+    pub fn take_all(&mut self) -> Vec<T> {
+        self.pending.drain(..).map(|pending| pending.change).collect()
+    }
+
+    /// Move every queued change to a new beat (after a seek)
+    pub fn retime(&mut self, due_beat: f64) {
+        for pending in &mut self.pending {
+            pending.due_beat = due_beat;
+        }
+    }
+
+    pub fn pending(&self) -> &[ScheduledChange<T>] {
+        &self.pending
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+    }
+}
+
+impl<T> Default for ChangeScheduler<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    enum Change {
+        Genre(&'static str),
+        Key(u8),
+    }
+
+    #[test]
+    fn test_next_boundary() {
+        let meter = Meter::new([4, 4]);
+        assert_eq!(QuantizeGrid::Bar.next_boundary(&meter, 5.5), Some(8.0));
+        // Exactly on a downbeat waits for the next one
+        assert_eq!(QuantizeGrid::Bar.next_boundary(&meter, 8.0), Some(12.0));
+        assert_eq!(QuantizeGrid::Bar.next_boundary(&meter, -6.0), Some(-4.0));
+        assert_eq!(QuantizeGrid::Beat.next_boundary(&meter, 5.5), Some(6.0));
+        assert_eq!(QuantizeGrid::Off.next_boundary(&meter, 5.5), None);
+        assert_eq!(QuantizeGrid::Bar.next_boundary(&Meter::new([7, 8]), 3.0), Some(7.0));
+    }
+
+    #[test]
+    fn test_scheduler_replaces_same_kind_and_takes_due() {
+        let mut scheduler = ChangeScheduler::new();
+        scheduler.schedule(Change::Genre("rock"), 4.0);
+        scheduler.schedule(Change::Key(2), 4.0);
+        scheduler.schedule(Change::Genre("punk"), 8.0);
+        assert_eq!(scheduler.pending().len(), 2);
+
+        assert!(scheduler.take_due(3.9).is_empty());
+        assert_eq!(scheduler.take_due(4.0), vec![Change::Key(2)]);
+        scheduler.retime(6.0);
+        assert_eq!(scheduler.take_due(6.5), vec![Change::Genre("punk")]);
+        assert!(scheduler.take_all().is_empty());
+    }
+}
@@ -0,0 +1,162 @@
+//! Record-to-chart
+//! Captures a live performance as a new chart. The recorder is fed the frets
+//! held and the strum bar at transport beats; every strum starts a chord event
+//! that lasts until the next strum or until the frets change. Starts and ends
+//! are snapped to a grid so the chart plays back in time.
+
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use crate::chart::*;
+use crate::editor::DEFAULT_LANE;
+
+/// Quantization grid in beats when none is given (eighth notes in 4/4)
+pub const DEFAULT_RECORD_GRID: f64 = 0.5;
+
+/// Chord being held since it was strummed
+#[derive(Debug, Clone)]
+struct HeldChord {
+    beat: f64,
+    frets: Vec<String>,
+    chord: String,
+}
+
+/// Turns strums into quantized chord events
+#[derive(Debug, Clone)]
+pub struct ChartRecorder {
+    grid: f64,
+    events: Vec<ChordEvent>,
+    chords: HashMap<String, ChordMapping>,
+    held: Option<HeldChord>,
+    strumming: bool,
+}
+
+impl ChartRecorder {
+    /// Recorder snapping to `grid` beats (0 or less uses `DEFAULT_RECORD_GRID`)
+    pub fn new(grid: f64) -> Self {
+        Self {
+            grid: if grid > 0.0 { grid } else { DEFAULT_RECORD_GRID },
+            events: Vec::new(),
+            chords: HashMap::new(),
+            held: None,
+            strumming: false,
+        }
+    }
+
+    pub fn grid(&self) -> f64 {
+        self.grid
+    }
+
+    /// Chord events recorded so far (not counting the one being held)
+    pub fn event_count(&self) -> usize {
+        self.events.len()
+    }
+
+    /// Feed the input at a transport beat. `frets` are fret names as charts
+    /// use them ("GREEN", "RED", ...); `chord` names what they play, if known
+    /// (otherwise the chord is named after its frets).
+    pub fn process(&mut self, beat: f64, frets: &[String], strum: bool, chord: Option<&str>) {
+        let strummed = strum && !self.strumming;
+        self.strumming = strum;
+
+        if self.held.as_ref().is_some_and(|held| strummed || held.frets != frets) {
+            self.close(beat);
+        }
+        if strummed && !frets.is_empty() {
+            let chord = self.chord_for(frets, chord);
+            self.held = Some(HeldChord { beat, frets: frets.to_vec(), chord });
+        }
+    }
+
+    /// Build the chart, ending a chord still held at `beat`
+    pub fn finish(mut self, beat: f64, title: &str, artist: &str, bpm: f64, time_sig: [u32; 2]) -> Result<SongChart> {
+        self.close(beat);
+        if self.events.is_empty() {
+            bail!("Nothing was recorded");
+        }
+        let mut chart = SongChart::new(title, artist, bpm, time_sig)?;
+        let events = self.events;
+        // Strums replaced on the grid can leave chords nothing plays
+        self.chords.retain(|name, _| events.iter().any(|event| &event.chord == name));
+        chart.mapping.chords = self.chords;
+        chart.lanes = vec![Lane { name: DEFAULT_LANE.to_string(), events }];
+        chart.validate()?;
+        Ok(chart)
+    }
+
+    fn quantize(&self, beat: f64) -> f64 {
+        ((beat / self.grid).round() * self.grid).max(0.0)
+    }
+
+    /// Chord name for a fret combination, kept unique per combination
+    fn chord_for(&mut self, frets: &[String], chord: Option<&str>) -> String {
+        let combo = frets.join("+");
+        let name = chord.map(str::trim).filter(|name| !name.is_empty()).unwrap_or(&combo);
+        // The same symbol on other frets (e.g. after a key change) gets its own entry
+        let name = match self.chords.get(name) {
+            Some(mapping) if mapping.frets != frets => format!("{} ({})", name, combo),
+            _ => name.to_string(),
+        };
+        self.chords.insert(name.clone(), ChordMapping { frets: frets.to_vec() });
+        name
+    }
+
+    fn close(&mut self, beat: f64) {
+        let Some(held) = self.held.take() else { return };
+        let start = self.quantize(held.beat);
+        let end = self.quantize(beat).max(start + self.grid);
+        // A strum snapped onto the previous one replaces it
+        if self.events.last().is_some_and(|last| last.beat >= start) {
+            self.events.pop();
+        }
+        if let Some(last) = self.events.last_mut() {
+            last.dur = last.dur.min(start - last.beat);
+        }
+        self.events.push(ChordEvent { beat: start, dur: end - start, chord: held.chord, section: None });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn frets(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn test_strums_become_quantized_events() {
+        let mut recorder = ChartRecorder::new(0.5);
+        let green = frets(&["GREEN"]);
+        recorder.process(0.1, &green, true, Some("E"));
+        recorder.process(0.3, &green, false, Some("E"));
+        recorder.process(1.9, &green, true, Some("E"));
+        recorder.process(2.1, &green, false, Some("E"));
+        // Letting go ends the chord
+        recorder.process(3.2, &[], false, None);
+        recorder.process(4.05, &frets(&["RED", "YELLOW"]), true, None);
+
+        let chart = recorder.finish(6.0, "Take 1", "Me", 100.0, [4, 4]).unwrap();
+        let events: Vec<(f64, f64, &str)> = chart.lanes[0].events.iter()
+            .map(|e| (e.beat, e.dur, e.chord.as_str()))
+            .collect();
+        assert_eq!(events, vec![(0.0, 2.0, "E"), (2.0, 1.0, "E"), (4.0, 2.0, "RED+YELLOW")]);
+        assert_eq!(chart.mapping.chords["RED+YELLOW"].frets, frets(&["RED", "YELLOW"]));
+        assert_eq!(chart.clock.bpm, 100.0);
+    }
+
+    #[test]
+    fn test_strums_on_the_same_grid_line_keep_the_last() {
+        let mut recorder = ChartRecorder::new(1.0);
+        recorder.process(1.9, &frets(&["GREEN"]), true, Some("G"));
+        recorder.process(2.0, &frets(&["GREEN"]), false, Some("G"));
+        recorder.process(2.2, &frets(&["RED"]), true, Some("G"));
+        let chart = recorder.finish(3.0, "Take", "", 120.0, [4, 4]).unwrap();
+
+        assert_eq!(chart.lanes[0].events.len(), 1);
+        // Same symbol on other frets doesn't overwrite the first mapping
+        assert_eq!(chart.lanes[0].events[0].chord, "G (RED)");
+        assert_eq!(chart.mapping.chords.len(), 1);
+
+        assert!(ChartRecorder::new(0.5).finish(4.0, "Empty", "", 120.0, [4, 4]).is_err());
+    }
+}
@@ -0,0 +1,123 @@
+//! Session recovery
+//! While a song is loaded, the session is written to a recovery file every
+//! so often: the chart as edited (flagged when the edits weren't saved to the
+//! library), the difficulty, where the transport was and the score so far.
+//! The file is removed on a clean exit, so finding one at start means the
+//! last session crashed and can be picked up where it stopped.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+
+use crate::chart::SongChart;
+use crate::difficulty::Difficulty;
+use crate::scoring::ScoreSnapshot;
+
+/// A song session as it was last saved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SessionSnapshot {
+    /// Seconds since the Unix epoch
+    pub saved_at: u64,
+    /// The chart as written and edited
+    pub chart: SongChart,
+    /// The chart has edits that weren't saved to the library
+    pub unsaved_edits: bool,
+    pub difficulty: Difficulty,
+    /// Transport position (beats)
+    pub beat: f64,
+    pub speed: f64,
+    pub score: ScoreSnapshot,
+}
+
+impl SessionSnapshot {
+    /// The snapshot in a recovery file (None = there is none)
+    pub fn load(path: &Path) -> Result<Option<Self>> {
+        if !path.exists() {
+            return Ok(None);
+        }
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read recovery file {}", path.display()))?;
+        serde_json::from_str(&json)
+            .map(Some)
+            .with_context(|| format!("Failed to parse recovery file {}", path.display()))
+    }
+
+    /// Write the snapshot, replacing the previous one in a single step so a
+    /// crash while saving leaves the last good snapshot
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let partial = path.with_extension("json.tmp");
+        fs::write(&partial, serde_json::to_string(self)?)
+            .with_context(|| format!("Failed to write recovery file {}", partial.display()))?;
+        fs::rename(&partial, path)
+            .with_context(|| format!("Failed to write recovery file {}", path.display()))
+    }
+
+    /// Remove the recovery file (nothing to recover)
+    pub fn clear(path: &Path) -> Result<()> {
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                Err(e).with_context(|| format!("Failed to remove recovery file {}", path.display()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::hit_detection::{ChordEventHit, HitResult, HitTiming};
+    use crate::scoring::Scorer;
+
+    fn hit() -> HitResult {
+        HitResult::Hit {
+            event: ChordEventHit { beat: 0.0, chord: "E".to_string(), is_sustain: false },
+            accuracy: 1.0,
+            timing: HitTiming::Perfect,
+            offset_ms: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_session_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("session_recovery.json");
+        assert!(SessionSnapshot::load(&path).unwrap().is_none());
+        SessionSnapshot::clear(&path).unwrap();
+
+        let mut scorer = Scorer::new();
+        for _ in 0..12 {
+            scorer.register_hit(&hit());
+        }
+        let snapshot = SessionSnapshot {
+            saved_at: 1_700_000_000,
+            chart: SongChart::new("Blues", "Me", 120.0, [4, 4]).unwrap(),
+            unsaved_edits: true,
+            difficulty: Difficulty::Hard,
+            beat: 17.5,
+            speed: 0.75,
+            score: scorer.snapshot(),
+        };
+        snapshot.save(&path).unwrap();
+
+        let loaded = SessionSnapshot::load(&path).unwrap().unwrap();
+        assert_eq!((loaded.beat, loaded.speed, loaded.difficulty), (17.5, 0.75, Difficulty::Hard));
+        assert!(loaded.unsaved_edits);
+        assert_eq!(loaded.chart.meta.title, "Blues");
+
+        // The combo multiplier carries on where it was
+        let mut restored = Scorer::new();
+        restored.restore(&loaded.score);
+        restored.register_hit(&hit());
+        scorer.register_hit(&hit());
+        assert_eq!(restored.snapshot(), scorer.snapshot());
+
+        SessionSnapshot::clear(&path).unwrap();
+        assert!(SessionSnapshot::load(&path).unwrap().is_none());
+    }
+}
@@ -1,4 +1,26 @@
+use crate::chart::StarPhrase;
 use crate::hit_detection::{HitResult, HitStats};
+use serde::{Deserialize, Serialize};
+
+/// Beats of star power a full meter lasts
+pub const STAR_POWER_FULL_BEATS: f64 = 32.0;
+/// Meter a phrase hit all the way through adds
+pub const STAR_PHRASE_FILL: f64 = 0.25;
+/// Meter needed before star power can be activated
+pub const STAR_POWER_MIN_ACTIVATION: f64 = 0.5;
+/// Score multiplier while star power is active
+pub const STAR_POWER_MULTIPLIER: u32 = 2;
+
+/// Score of a play-through in progress, as kept for crash recovery
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScoreSnapshot {
+    pub score: u32,
+    pub combo: u32,
+    pub max_combo: u32,
+    pub hits: u32,
+    pub misses: u32,
+}
 
 /// Scoring system
 #[derive(Debug, Clone)]
@@ -10,6 +32,10 @@ pub struct Scorer {
     pub misses: u32,
     
     combo_multiplier: u32,
+    /// Extra multiplier on top of the combo (star power)
+    bonus_multiplier: u32,
+    /// Misses are counted but don't break the combo
+    no_fail: bool,
 }
 
 impl Scorer {
@@ -21,6 +47,8 @@ impl Scorer {
             hits: 0,
             misses: 0,
             combo_multiplier: 1,
+            bonus_multiplier: 1,
+            no_fail: false,
         }
     }
 
@@ -32,6 +60,37 @@ impl Scorer {
         self.hits = 0;
         self.misses = 0;
         self.combo_multiplier = 1;
+        self.bonus_multiplier = 1;
+    }
+
+    pub fn snapshot(&self) -> ScoreSnapshot {
+        ScoreSnapshot { score: self.score, combo: self.combo, max_combo: self.max_combo, hits: self.hits, misses: self.misses }
+    }
+
+    /// Carry on from a snapshot (star power bonus starts off)
+    pub fn restore(&mut self, snapshot: &ScoreSnapshot) {
+        self.score = snapshot.score;
+        self.combo = snapshot.combo;
+        self.max_combo = snapshot.max_combo;
+        self.hits = snapshot.hits;
+        self.misses = snapshot.misses;
+        self.combo_multiplier = combo_multiplier(self.combo);
+        self.bonus_multiplier = 1;
+    }
+
+    /// Keep the combo going through misses (beginners playing along);
+    /// misses still count against the accuracy
+    pub fn set_no_fail(&mut self, enabled: bool) {
+        self.no_fail = enabled;
+    }
+
+    pub fn no_fail(&self) -> bool {
+        self.no_fail
+    }
+
+    /// Multiply the points of the next hits (1 = off)
+    pub fn set_bonus_multiplier(&mut self, multiplier: u32) {
+        self.bonus_multiplier = multiplier.max(1);
     }
 
     /// Register a hit result
@@ -45,22 +104,18 @@ impl Scorer {
                     self.max_combo = self.combo;
                 }
 
-                // Update combo multiplier
-                self.combo_multiplier = match self.combo {
-                    0..=9 => 1,
-                    10..=19 => 2,
-                    20..=29 => 3,
-                    _ => 4,
-                };
+                self.combo_multiplier = combo_multiplier(self.combo);
 
                 // Calculate points based on accuracy and multiplier
                 let base_points = 100.0 * accuracy;
-                let points = (base_points * self.combo_multiplier as f64) as u32;
+                let points = (base_points * (self.combo_multiplier * self.bonus_multiplier) as f64) as u32;
                 self.score += points;
             }
             HitResult::Miss { .. } => {
-                self.combo = 0;
-                self.combo_multiplier = 1;
+                if !self.no_fail {
+                    self.combo = 0;
+                    self.combo_multiplier = 1;
+                }
                 self.misses += 1;
             }
         }
@@ -68,7 +123,7 @@ impl Scorer {
 
     /// Add sustain bonus points
     pub fn add_sustain_bonus(&mut self, points: u32) {
-        self.score += points * self.combo_multiplier;
+        self.score += points * self.combo_multiplier * self.bonus_multiplier;
     }
 
     /// Get accuracy percentage
@@ -94,6 +149,16 @@ impl Scorer {
     }
 }
 
+/// Points multiplier a combo earns
+fn combo_multiplier(combo: u32) -> u32 {
+    match combo {
+        0..=9 => 1,
+        10..=19 => 2,
+        20..=29 => 3,
+        _ => 4,
+    }
+}
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Grade {
     S,
@@ -117,6 +182,140 @@ impl std::fmt::Display for Grade {
     }
 }
 
+/// Star power meter as shown to the player
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StarPowerState {
+    /// 0.0-1.0, draining while active
+    pub meter: f64,
+    pub active: bool,
+    pub can_activate: bool,
+}
+
+/// Star power was switched on
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StarPowerActivation {
+    pub beat: f64,
+    pub until_beat: f64,
+}
+
+#[derive(Debug, Clone)]
+struct PhraseProgress {
+    phrase: StarPhrase,
+    notes: usize,
+    hits: usize,
+    failed: bool,
+}
+
+/// Star power: hitting every note of a charted phrase fills the meter; once
+/// it is at least half full it can be activated for a score multiplier that
+/// lasts as long as the meter does. Phrases completed while active extend it.
+#[derive(Debug, Clone)]
+pub struct StarPower {
+    phrases: Vec<PhraseProgress>,
+    meter: f64,
+    active_until: Option<f64>,
+}
+
+impl StarPower {
+    /// Phrases of a chart and the beats of the notes they have to hit
+    pub fn new(phrases: &[StarPhrase], note_beats: &[f64]) -> Self {
+        let phrases = phrases.iter()
+            .map(|phrase| PhraseProgress {
+                phrase: *phrase,
+                notes: note_beats.iter().filter(|beat| Self::in_phrase(phrase, **beat)).count(),
+                hits: 0,
+                failed: false,
+            })
+            .filter(|progress| progress.notes > 0)
+            .collect();
+        Self { phrases, meter: 0.0, active_until: None }
+    }
+
+    fn in_phrase(phrase: &StarPhrase, beat: f64) -> bool {
+        beat >= phrase.from_beat && beat < phrase.to_beat
+    }
+
+    /// Empty the meter and start every phrase over
+    pub fn reset(&mut self) {
+        self.meter = 0.0;
+        self.active_until = None;
+        self.reset_phrases();
+    }
+
+    /// Start every phrase over, keeping the meter (e.g. a practice loop repeating)
+    pub fn reset_phrases(&mut self) {
+        for progress in &mut self.phrases {
+            progress.hits = 0;
+            progress.failed = false;
+        }
+    }
+
+    /// Follow a strum result; true if it completed a phrase
+    pub fn register(&mut self, current_beat: f64, result: &HitResult) -> bool {
+        match result {
+            HitResult::Hit { event, .. } => {
+                let Some(progress) = self.phrases.iter_mut().find(|p| Self::in_phrase(&p.phrase, event.beat)) else {
+                    return false;
+                };
+                progress.hits += 1;
+                if progress.failed || progress.hits != progress.notes {
+                    return false;
+                }
+                self.fill(current_beat);
+                true
+            }
+            HitResult::Miss { .. } => {
+                if let Some(progress) = self.phrases.iter_mut().find(|p| Self::in_phrase(&p.phrase, current_beat)) {
+                    progress.failed = true;
+                }
+                false
+            }
+        }
+    }
+
+    fn fill(&mut self, beat: f64) {
+        match self.active_until {
+            Some(until) if beat < until => self.active_until = Some(until + STAR_PHRASE_FILL * STAR_POWER_FULL_BEATS),
+            _ => self.meter = (self.meter + STAR_PHRASE_FILL).min(1.0),
+        }
+    }
+
+    /// Switch star power on if the meter is full enough
+    pub fn activate(&mut self, beat: f64) -> Option<StarPowerActivation> {
+        if self.is_active(beat) || self.meter < STAR_POWER_MIN_ACTIVATION {
+            return None;
+        }
+        let until_beat = beat + self.meter * STAR_POWER_FULL_BEATS;
+        self.meter = 0.0;
+        self.active_until = Some(until_beat);
+        Some(StarPowerActivation { beat, until_beat })
+    }
+
+    pub fn is_active(&self, beat: f64) -> bool {
+        self.active_until.is_some_and(|until| beat < until)
+    }
+
+    pub fn state(&self, beat: f64) -> StarPowerState {
+        let active = self.is_active(beat);
+        let meter = match self.active_until {
+            Some(until) if active => ((until - beat) / STAR_POWER_FULL_BEATS).min(1.0),
+            _ => self.meter,
+        };
+        StarPowerState { meter, active, can_activate: !active && meter >= STAR_POWER_MIN_ACTIVATION }
+    }
+}
+
+/// What happens to the score each time a practice loop repeats
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LoopScoring {
+    /// Keep adding up over every pass
+    #[default]
+    Accumulate,
+    /// Start from zero on every pass
+    Reset,
+}
+
 impl Default for Scorer {
     fn default() -> Self {
         Self::new()
@@ -126,7 +325,7 @@ impl Default for Scorer {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::hit_detection::{ChordEventHit, MissReason};
+    use crate::hit_detection::{ChordEventHit, HitTiming, MissReason};
 
     #[test]
     fn test_scoring_basic() {
@@ -139,6 +338,8 @@ mod tests {
                 is_sustain: false,
             },
             accuracy: 1.0,
+            timing: HitTiming::Perfect,
+            offset_ms: 0.0,
         };
 
         scorer.register_hit(&hit);
@@ -160,6 +361,8 @@ mod tests {
                     is_sustain: false,
                 },
                 accuracy: 1.0,
+                timing: HitTiming::Perfect,
+                offset_ms: 0.0,
             };
             scorer.register_hit(&hit);
         }
@@ -182,6 +385,8 @@ mod tests {
                     is_sustain: false,
                 },
                 accuracy: 1.0,
+                timing: HitTiming::Perfect,
+                offset_ms: 0.0,
             });
         }
 
@@ -192,4 +397,75 @@ mod tests {
         assert_eq!(scorer.get_accuracy(), 75.0);
         assert_eq!(scorer.get_grade(), Grade::C);
     }
+
+    #[test]
+    fn test_no_fail_keeps_the_combo() {
+        let mut scorer = Scorer::new();
+        scorer.set_no_fail(true);
+        for _ in 0..12 {
+            scorer.register_hit(&hit_at(0.0));
+        }
+        scorer.register_hit(&HitResult::Miss { reason: MissReason::WrongFrets });
+        assert_eq!((scorer.combo, scorer.combo_multiplier, scorer.misses), (12, 2, 1));
+
+        // Resetting the score keeps the setting
+        scorer.reset();
+        scorer.register_hit(&hit_at(0.0));
+        scorer.register_hit(&HitResult::Miss { reason: MissReason::NoEventInWindow });
+        assert_eq!(scorer.combo, 1);
+        scorer.set_no_fail(false);
+        scorer.register_hit(&HitResult::Miss { reason: MissReason::NoEventInWindow });
+        assert_eq!(scorer.combo, 0);
+    }
+
+    fn hit_at(beat: f64) -> HitResult {
+        HitResult::Hit {
+            event: ChordEventHit { beat, chord: "C".to_string(), is_sustain: false },
+            accuracy: 1.0,
+            timing: HitTiming::Perfect,
+            offset_ms: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_star_phrases_fill_the_meter() {
+        let phrases = [
+            StarPhrase { from_beat: 0.0, to_beat: 4.0 },
+            StarPhrase { from_beat: 4.0, to_beat: 8.0 },
+            StarPhrase { from_beat: 8.0, to_beat: 12.0 },
+        ];
+        let mut star_power = StarPower::new(&phrases, &[0.0, 2.0, 4.0, 6.0, 8.0, 10.0]);
+
+        assert!(!star_power.register(0.0, &hit_at(0.0)));
+        assert!(star_power.register(2.0, &hit_at(2.0)));
+        // A miss inside a phrase spoils it
+        star_power.register(4.1, &HitResult::Miss { reason: MissReason::WrongFrets });
+        star_power.register(4.2, &hit_at(4.0));
+        assert!(!star_power.register(6.0, &hit_at(6.0)));
+        assert_eq!(star_power.state(7.0).meter, STAR_PHRASE_FILL);
+        assert!(star_power.activate(7.0).is_none());
+
+        star_power.register(8.0, &hit_at(8.0));
+        star_power.register(10.0, &hit_at(10.0));
+        assert!(star_power.state(11.0).can_activate);
+    }
+
+    #[test]
+    fn test_activation_doubles_points_until_the_meter_runs_out() {
+        let phrases = [StarPhrase { from_beat: 0.0, to_beat: 4.0 }, StarPhrase { from_beat: 4.0, to_beat: 8.0 }];
+        let mut star_power = StarPower::new(&phrases, &[0.0, 4.0]);
+        star_power.register(0.0, &hit_at(0.0));
+        star_power.register(4.0, &hit_at(4.0));
+
+        let activation = star_power.activate(8.0).unwrap();
+        assert_eq!(activation.until_beat, 8.0 + 0.5 * STAR_POWER_FULL_BEATS);
+        assert!(star_power.is_active(20.0));
+        assert!((star_power.state(16.0).meter - 0.25).abs() < 1e-9);
+        assert!(!star_power.is_active(24.0));
+
+        let mut scorer = Scorer::new();
+        scorer.set_bonus_multiplier(STAR_POWER_MULTIPLIER);
+        scorer.register_hit(&hit_at(0.0));
+        assert_eq!(scorer.score, 200);
+    }
 }
@@ -0,0 +1,152 @@
+//! Setlists
+//! An ordered list of library songs played one after the other, each entry
+//! optionally slowed down or on its own instrument. Setlists are stored in
+//! the songs directory as `<name>.setlist.json` and refer to songs by their
+//! library file name, so a setlist moves along with the library.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// File name ending of a setlist in the songs directory
+pub const SETLIST_EXTENSION: &str = ".setlist.json";
+
+/// One song of a setlist
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SetlistEntry {
+    /// Library file name, e.g. "blues-in-a.mitychart.json"
+    pub song: String,
+    /// Playback speed multiplier (None = full speed)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speed: Option<f64>,
+    /// Instrument loaded for the song (None = the one the song asks for)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub instrument: Option<String>,
+}
+
+impl SetlistEntry {
+    pub fn new(song: &str) -> Self {
+        Self { song: song.to_string(), speed: None, instrument: None }
+    }
+}
+
+/// Songs played in order
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Setlist {
+    pub name: String,
+    #[serde(default)]
+    pub entries: Vec<SetlistEntry>,
+    /// Load and start the next song when one finishes
+    #[serde(default = "default_auto_advance")]
+    pub auto_advance: bool,
+}
+
+fn default_auto_advance() -> bool {
+    true
+}
+
+impl Setlist {
+    pub fn new(name: &str) -> Self {
+        Self { name: name.to_string(), entries: Vec::new(), auto_advance: default_auto_advance() }
+    }
+
+    /// Move the entry at `from` to `to`, shifting the ones in between
+    pub fn move_entry(&mut self, from: usize, to: usize) -> Result<()> {
+        if from >= self.entries.len() || to >= self.entries.len() {
+            anyhow::bail!("Setlist '{}' has no entry {}", self.name, from.max(to) + 1);
+        }
+        let entry = self.entries.remove(from);
+        self.entries.insert(to, entry);
+        Ok(())
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read setlist {}", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("Failed to parse setlist {}", path.display()))
+    }
+
+    /// Write the setlist to the songs directory, returning its file
+    pub fn save(&self, songs_dir: &Path) -> Result<PathBuf> {
+        fs::create_dir_all(songs_dir)?;
+        let path = setlist_path(songs_dir, &self.name)?;
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write setlist {}", path.display()))?;
+        Ok(path)
+    }
+}
+
+/// File of a setlist ("Friday Gig!" -> "friday-gig.setlist.json")
+pub fn setlist_path(songs_dir: &Path, name: &str) -> Result<PathBuf> {
+    let slug = name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    if slug.is_empty() {
+        anyhow::bail!("Setlist name '{}' has no letters or digits", name);
+    }
+    Ok(songs_dir.join(format!("{}{}", slug, SETLIST_EXTENSION)))
+}
+
+/// Setlists in the songs directory, by name (unreadable files are skipped)
+pub fn list_setlists(songs_dir: &Path) -> Result<Vec<Setlist>> {
+    let Ok(dir_entries) = fs::read_dir(songs_dir) else {
+        return Ok(Vec::new());
+    };
+    let mut setlists: Vec<Setlist> = dir_entries.flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.to_string_lossy().ends_with(SETLIST_EXTENSION))
+        .filter_map(|path| Setlist::load(&path).ok())
+        .collect();
+    setlists.sort_by_key(|setlist| setlist.name.to_lowercase());
+    Ok(setlists)
+}
+
+/// Load a setlist by name
+pub fn load_setlist(songs_dir: &Path, name: &str) -> Result<Setlist> {
+    Setlist::load(&setlist_path(songs_dir, name)?)
+}
+
+pub fn delete_setlist(songs_dir: &Path, name: &str) -> Result<()> {
+    let path = setlist_path(songs_dir, name)?;
+    fs::remove_file(&path).with_context(|| format!("Failed to delete setlist {}", path.display()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_setlist_save_reorder_and_list() {
+        let temp = TempDir::new().unwrap();
+        let dir = temp.path();
+
+        let mut setlist = Setlist::new("Friday Gig!");
+        setlist.entries.push(SetlistEntry::new("intro.mitychart.json"));
+        setlist.entries.push(SetlistEntry { speed: Some(0.75), ..SetlistEntry::new("blues.mitychart.json") });
+        setlist.entries.push(SetlistEntry { instrument: Some("Distorted Guitar".to_string()), ..SetlistEntry::new("metal.mitychart.json") });
+        setlist.move_entry(2, 0).unwrap();
+        assert_eq!(setlist.entries.iter().map(|entry| entry.song.as_str()).collect::<Vec<_>>(),
+            ["metal.mitychart.json", "intro.mitychart.json", "blues.mitychart.json"]);
+        assert!(setlist.move_entry(0, 3).is_err());
+
+        let path = setlist.save(dir).unwrap();
+        assert_eq!(path.file_name().unwrap(), "friday-gig.setlist.json");
+        Setlist::new("Acoustic").save(dir).unwrap();
+        fs::write(dir.join("song.mitychart.json"), "{}").unwrap();
+
+        let setlists = list_setlists(dir).unwrap();
+        assert_eq!(setlists.iter().map(|setlist| setlist.name.as_str()).collect::<Vec<_>>(), ["Acoustic", "Friday Gig!"]);
+        assert_eq!(load_setlist(dir, "friday gig").unwrap(), setlist);
+        assert!(setlist_path(dir, "!!").is_err());
+
+        delete_setlist(dir, "Acoustic").unwrap();
+        assert_eq!(list_setlists(dir).unwrap().len(), 1);
+    }
+}
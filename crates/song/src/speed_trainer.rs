@@ -0,0 +1,133 @@
+//! Speed trainer
+//! Builds a riff up gradually: the practice loop starts slowed down and each
+//! pass played accurately enough raises the speed a step, until the target
+//! speed is reached. A pass that falls short is simply played again at the
+//! same speed.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+
+/// How the speed trainer ramps up
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedTrainerSettings {
+    /// Speed multiplier of the first pass
+    pub start_speed: f64,
+    /// Added to the speed after every successful pass
+    pub increment: f64,
+    /// Speed the trainer stops raising at
+    pub target_speed: f64,
+    /// Accuracy (%) a pass needs to count as successful
+    pub accuracy_threshold: f64,
+}
+
+impl Default for SpeedTrainerSettings {
+    fn default() -> Self {
+        Self {
+            start_speed: 0.6,
+            increment: 0.05,
+            target_speed: 1.0,
+            accuracy_threshold: 90.0,
+        }
+    }
+}
+
+impl SpeedTrainerSettings {
+    pub fn validate(&self) -> Result<()> {
+        if self.start_speed <= 0.0 || self.target_speed < self.start_speed {
+            bail!("Speed trainer needs 0 < start speed <= target speed (got {} - {})", self.start_speed, self.target_speed);
+        }
+        if self.increment <= 0.0 {
+            bail!("Speed trainer increment must be positive (got {})", self.increment);
+        }
+        if !(0.0..=100.0).contains(&self.accuracy_threshold) {
+            bail!("Speed trainer accuracy threshold must be 0-100% (got {})", self.accuracy_threshold);
+        }
+        Ok(())
+    }
+}
+
+/// Outcome of one loop pass
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeedTrainerPass {
+    /// Accuracy of the pass (%)
+    pub accuracy: f64,
+    /// The pass met the threshold
+    pub passed: bool,
+    /// Speed the next pass is played at
+    pub speed: f64,
+    /// The target speed has been reached
+    pub at_target: bool,
+}
+
+/// Speed ramp over the passes of a practice loop
+#[derive(Debug, Clone)]
+pub struct SpeedTrainer {
+    settings: SpeedTrainerSettings,
+    speed: f64,
+}
+
+impl SpeedTrainer {
+    pub fn new(settings: SpeedTrainerSettings) -> Result<Self> {
+        settings.validate()?;
+        Ok(Self { settings, speed: settings.start_speed })
+    }
+
+    pub fn settings(&self) -> SpeedTrainerSettings {
+        self.settings
+    }
+
+    /// Speed of the current pass
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    pub fn at_target(&self) -> bool {
+        self.speed >= self.settings.target_speed
+    }
+
+    /// Finish a pass with its hits and misses; a pass without any notes
+    /// played doesn't count as successful
+    pub fn finish_pass(&mut self, hits: u32, misses: u32) -> SpeedTrainerPass {
+        let total = hits + misses;
+        let accuracy = if total == 0 { 0.0 } else { hits as f64 / total as f64 * 100.0 };
+        let passed = total > 0 && accuracy >= self.settings.accuracy_threshold;
+        if passed {
+            self.speed = (self.speed + self.settings.increment).min(self.settings.target_speed);
+        }
+        SpeedTrainerPass { accuracy, passed, speed: self.speed, at_target: self.at_target() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_speed_trainer_ramp() {
+        let settings = SpeedTrainerSettings { start_speed: 0.6, increment: 0.15, target_speed: 0.8, accuracy_threshold: 90.0 };
+        let mut trainer = SpeedTrainer::new(settings).unwrap();
+        assert_eq!(trainer.speed(), 0.6);
+
+        // Too many misses: the pass is played again at the same speed
+        let pass = trainer.finish_pass(8, 2);
+        assert!(!pass.passed);
+        assert_eq!((pass.accuracy, pass.speed), (80.0, 0.6));
+        assert!(!trainer.finish_pass(0, 0).passed);
+
+        let pass = trainer.finish_pass(9, 1);
+        assert!(pass.passed && !pass.at_target);
+        assert!((pass.speed - 0.75).abs() < 1e-9);
+
+        // The last step stops at the target
+        let pass = trainer.finish_pass(10, 0);
+        assert_eq!(pass.speed, 0.8);
+        assert!(pass.at_target && trainer.at_target());
+        assert_eq!(trainer.finish_pass(10, 0).speed, 0.8);
+
+        assert!(SpeedTrainer::new(SpeedTrainerSettings { target_speed: 0.5, ..settings }).is_err());
+        assert!(SpeedTrainer::new(SpeedTrainerSettings { increment: 0.0, ..settings }).is_err());
+        assert!(SpeedTrainer::new(SpeedTrainerSettings { accuracy_threshold: 120.0, ..settings }).is_err());
+    }
+}
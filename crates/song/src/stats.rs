@@ -0,0 +1,267 @@
+//! Song statistics
+//! Every finished play-through goes into a history, and the best results are
+//! kept per song and difficulty. Songs are told apart by artist and title
+//! rather than by file, so a song keeps its scores when it is moved or renamed.
+
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::Path;
+use crate::chart::{SongChart, SongMeta};
+use crate::difficulty::Difficulty;
+use crate::scoring::Scorer;
+
+/// Play-throughs kept in the history (oldest are dropped first)
+pub const MAX_HISTORY: usize = 500;
+
+/// Stats key of a song ("artist-title", lowercase)
+pub fn song_id(meta: &SongMeta) -> String {
+    format!("{} {}", meta.artist, meta.title)
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// One finished play-through
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlayRecord {
+    pub song_id: String,
+    pub title: String,
+    pub artist: String,
+    pub difficulty: Difficulty,
+    pub score: u32,
+    /// Percentage of notes hit
+    pub accuracy: f64,
+    pub max_combo: u32,
+    pub hits: u32,
+    pub misses: u32,
+    pub grade: String,
+    /// Seconds since the Unix epoch
+    pub played_at: u64,
+    /// Played with no-fail on (misses didn't break the combo)
+    #[serde(default)]
+    pub no_fail: bool,
+}
+
+impl PlayRecord {
+    pub fn new(chart: &SongChart, difficulty: Difficulty, scorer: &Scorer, played_at: u64) -> Self {
+        Self {
+            song_id: song_id(&chart.meta),
+            title: chart.meta.title.clone(),
+            artist: chart.meta.artist.clone(),
+            difficulty,
+            score: scorer.score,
+            accuracy: scorer.get_accuracy(),
+            max_combo: scorer.max_combo,
+            hits: scorer.hits,
+            misses: scorer.misses,
+            grade: scorer.get_grade().to_string(),
+            played_at,
+            no_fail: scorer.no_fail(),
+        }
+    }
+}
+
+/// Best results of a song at one difficulty
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HighScore {
+    pub song_id: String,
+    pub title: String,
+    pub artist: String,
+    pub difficulty: Difficulty,
+    pub best_score: u32,
+    pub best_accuracy: f64,
+    pub max_combo: u32,
+    pub play_count: u32,
+    /// When the best score was set (seconds since the Unix epoch)
+    pub best_played_at: u64,
+    pub last_played_at: u64,
+}
+
+/// Which bests a play-through beat
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NewBests {
+    pub score: bool,
+    pub accuracy: bool,
+    pub combo: bool,
+}
+
+/// A play-through as recorded, with the bests it set
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FinishedPlay {
+    pub play: PlayRecord,
+    pub new_bests: NewBests,
+}
+
+/// High scores and play history
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SongStats {
+    #[serde(default)]
+    high_scores: Vec<HighScore>,
+    /// Oldest first
+    #[serde(default)]
+    history: Vec<PlayRecord>,
+}
+
+impl SongStats {
+    /// Load from a JSON file (empty when it doesn't exist yet)
+    pub fn load(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read song stats {}", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("Failed to parse song stats {}", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write song stats {}", path.display()))
+    }
+
+    /// Add a play-through to the history and the song's bests. No-fail
+    /// play-throughs only go into the history, their combos never broke.
+    pub fn record(&mut self, play: PlayRecord) -> FinishedPlay {
+        let index = self.high_scores.iter()
+            .position(|high| high.song_id == play.song_id && high.difficulty == play.difficulty);
+        let new_bests = match index {
+            _ if play.no_fail => NewBests::default(),
+            Some(index) => {
+                let high = &mut self.high_scores[index];
+                let new_bests = NewBests {
+                    score: play.score > high.best_score,
+                    accuracy: play.accuracy > high.best_accuracy,
+                    combo: play.max_combo > high.max_combo,
+                };
+                if new_bests.score {
+                    high.best_score = play.score;
+                    high.best_played_at = play.played_at;
+                }
+                high.best_accuracy = high.best_accuracy.max(play.accuracy);
+                high.max_combo = high.max_combo.max(play.max_combo);
+                high.play_count += 1;
+                high.last_played_at = play.played_at;
+                // Follow a renamed song's title
+                high.title = play.title.clone();
+                high.artist = play.artist.clone();
+                new_bests
+            }
+            None => {
+                self.high_scores.push(HighScore {
+                    song_id: play.song_id.clone(),
+                    title: play.title.clone(),
+                    artist: play.artist.clone(),
+                    difficulty: play.difficulty,
+                    best_score: play.score,
+                    best_accuracy: play.accuracy,
+                    max_combo: play.max_combo,
+                    play_count: 1,
+                    best_played_at: play.played_at,
+                    last_played_at: play.played_at,
+                });
+                NewBests { score: true, accuracy: true, combo: true }
+            }
+        };
+
+        self.history.push(play.clone());
+        if self.history.len() > MAX_HISTORY {
+            let excess = self.history.len() - MAX_HISTORY;
+            self.history.drain(..excess);
+        }
+        FinishedPlay { play, new_bests }
+    }
+
+    /// Bests of every song, or of one song at each difficulty
+    pub fn high_scores(&self, song_id: Option<&str>) -> Vec<HighScore> {
+        self.high_scores.iter()
+            .filter(|high| song_id.is_none_or(|id| high.song_id == id))
+            .cloned()
+            .collect()
+    }
+
+    /// Play-throughs, newest first
+    pub fn history(&self, song_id: Option<&str>, limit: Option<usize>) -> Vec<PlayRecord> {
+        self.history.iter()
+            .rev()
+            .filter(|play| song_id.is_none_or(|id| play.song_id == id))
+            .take(limit.unwrap_or(usize::MAX))
+            .cloned()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn play(score: u32, accuracy: f64, max_combo: u32, played_at: u64) -> PlayRecord {
+        PlayRecord {
+            song_id: "band-song".to_string(),
+            title: "Song".to_string(),
+            artist: "Band".to_string(),
+            difficulty: Difficulty::Medium,
+            score,
+            accuracy,
+            max_combo,
+            hits: 10,
+            misses: 0,
+            grade: "S".to_string(),
+            played_at,
+            no_fail: false,
+        }
+    }
+
+    #[test]
+    fn test_record_keeps_bests_per_difficulty() {
+        let mut stats = SongStats::default();
+        assert_eq!(stats.record(play(1000, 80.0, 12, 1)).new_bests, NewBests { score: true, accuracy: true, combo: true });
+        let second = stats.record(play(800, 90.0, 5, 2));
+        assert_eq!(second.new_bests, NewBests { score: false, accuracy: true, combo: false });
+        stats.record(PlayRecord { difficulty: Difficulty::Expert, ..play(300, 50.0, 3, 3) });
+        let no_fail = stats.record(PlayRecord { no_fail: true, ..play(5000, 95.0, 40, 4) });
+        assert_eq!(no_fail.new_bests, NewBests::default());
+
+        let medium = &stats.high_scores(Some("band-song"))[0];
+        assert_eq!((medium.best_score, medium.best_accuracy, medium.max_combo), (1000, 90.0, 12));
+        assert_eq!((medium.play_count, medium.best_played_at, medium.last_played_at), (2, 1, 2));
+        assert_eq!(stats.high_scores(None).len(), 2);
+        assert!(stats.high_scores(Some("other")).is_empty());
+
+        let history = stats.history(Some("band-song"), Some(3));
+        assert_eq!(history.iter().map(|play| play.played_at).collect::<Vec<_>>(), vec![4, 3, 2]);
+        assert!(history[0].no_fail);
+    }
+
+    #[test]
+    fn test_stats_round_trip_and_cap_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("stats.json");
+        assert!(SongStats::load(&path).unwrap().history(None, None).is_empty());
+
+        let mut stats = SongStats::default();
+        for played_at in 0..MAX_HISTORY as u64 + 5 {
+            stats.record(play(100, 100.0, 1, played_at));
+        }
+        stats.save(&path).unwrap();
+        let loaded = SongStats::load(&path).unwrap();
+
+        let history = loaded.history(None, None);
+        assert_eq!(history.len(), MAX_HISTORY);
+        assert_eq!(history.last().unwrap().played_at, 5);
+        assert_eq!(loaded.high_scores(None)[0].play_count, MAX_HISTORY as u32 + 5);
+
+        let meta = SongMeta { title: "Smoke on the Water!".to_string(), artist: "Deep Purple".to_string(), youtube: None, spotify: None, backing_track: None, instrument: None };
+        assert_eq!(song_id(&meta), "deep-purple-smoke-on-the-water");
+    }
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+/// Taps averaged for the tempo estimate
+pub const TAP_TEMPO_WINDOW: usize = 8;
+/// A pause longer than this starts a new tap sequence
+const TAP_RESET_AFTER: Duration = Duration::from_secs(2);
+const MIN_TAP_BPM: f64 = 30.0;
+const MAX_TAP_BPM: f64 = 300.0;
+
+/// How a tapped tempo is rounded
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TempoRounding {
+    /// One decimal place
+    None,
+    /// Nearest half BPM
+    Half,
+    /// Nearest whole BPM
+    #[default]
+    Whole,
+}
+
+impl TempoRounding {
+    pub fn apply(&self, bpm: f64) -> f64 {
+        match self {
+            TempoRounding::None => (bpm * 10.0).round() / 10.0,
+            TempoRounding::Half => (bpm * 2.0).round() / 2.0,
+            TempoRounding::Whole => bpm.round(),
+        }
+    }
+}
+
+/// Tap tempo: the BPM is the average interval over the last few taps
+#[derive(Debug, Clone)]
+pub struct TapTempo {
+    taps: VecDeque<Instant>,
+    window: usize,
+    pub rounding: TempoRounding,
+}
+
+impl TapTempo {
+    pub fn new(window: usize, rounding: TempoRounding) -> Self {
+        Self {
+            taps: VecDeque::new(),
+            window: window.max(2),
+            rounding,
+        }
+    }
+
+    /// Register a tap; returns the tempo once at least two taps are in the window
+    pub fn tap(&mut self, now: Instant) -> Option<f64> {
+        if self.taps.back().is_some_and(|&last| now.saturating_duration_since(last) > TAP_RESET_AFTER) {
+            self.taps.clear();
+        }
+        self.taps.push_back(now);
+        while self.taps.len() > self.window {
+            self.taps.pop_front();
+        }
+        self.bpm()
+    }
+
+    /// Current estimate (None until two taps)
+    pub fn bpm(&self) -> Option<f64> {
+        let (first, last) = (self.taps.front()?, self.taps.back()?);
+        let intervals = self.taps.len().checked_sub(1).filter(|&n| n > 0)?;
+        let average = last.saturating_duration_since(*first).as_secs_f64() / intervals as f64;
+        if average <= 0.0 {
+            return None;
+        }
+        Some(self.rounding.apply(60.0 / average).clamp(MIN_TAP_BPM, MAX_TAP_BPM))
+    }
+
+    /// Taps in the current sequence
+    pub fn tap_count(&self) -> usize {
+        self.taps.len()
+    }
+
+    pub fn reset(&mut self) {
+        self.taps.clear();
+    }
+}
+
+impl Default for TapTempo {
+    fn default() -> Self {
+        Self::new(TAP_TEMPO_WINDOW, TempoRounding::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tap_tempo_average_and_rounding() {
+        let start = Instant::now();
+        let mut tap = TapTempo::new(4, TempoRounding::None);
+        assert_eq!(tap.tap(start), None);
+        // Slightly uneven taps around 120 BPM (500ms)
+        for (i, ms) in [490, 1010, 1500, 2004].iter().enumerate() {
+            let bpm = tap.tap(start + Duration::from_millis(*ms)).unwrap();
+            if i == 0 {
+                assert_eq!(bpm, 122.4);
+            }
+        }
+        // Window keeps the last 4 taps: 490..2004 over 3 intervals
+        assert_eq!(tap.tap_count(), 4);
+        assert_eq!(tap.bpm(), Some(118.9));
+
+        tap.rounding = TempoRounding::Half;
+        assert_eq!(tap.bpm(), Some(119.0));
+        tap.rounding = TempoRounding::Whole;
+        assert_eq!(tap.bpm(), Some(119.0));
+    }
+
+    #[test]
+    fn test_tap_tempo_resets_after_pause() {
+        let start = Instant::now();
+        let mut tap = TapTempo::default();
+        tap.tap(start);
+        tap.tap(start + Duration::from_millis(1000));
+        assert_eq!(tap.bpm(), Some(60.0));
+
+        tap.tap(start + Duration::from_millis(5000));
+        assert_eq!(tap.tap_count(), 1);
+        assert_eq!(tap.bpm(), None);
+        assert_eq!(tap.tap(start + Duration::from_millis(5250)), Some(240.0));
+    }
+}
@@ -0,0 +1,250 @@
+//! Chord trainer
+//! Prompts random chords from the fret mapping being played and waits for the
+//! right frets to be strummed. Each level adds a chord to the pool (from the
+//! lowest fret up) and shortens the time allowed; a run of quick answers goes
+//! up a level and repeated misses go back down.
+
+use anyhow::{bail, Result};
+use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+
+/// Quick correct answers in a row that go up a level
+pub const LEVEL_UP_STREAK: u32 = 5;
+/// Misses in a row that go down a level
+pub const LEVEL_DOWN_MISSES: u32 = 2;
+/// Time allowed on the first level
+const START_TIME_LIMIT_MS: u64 = 3000;
+/// Time taken off per level
+const TIME_LIMIT_STEP_MS: u64 = 400;
+const MIN_TIME_LIMIT_MS: u64 = 1000;
+
+/// A chord the trainer can ask for
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TrainerChord {
+    pub name: String,
+    /// Frets as charts name them ("GREEN", "RED", ...)
+    pub frets: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrainerPrompt {
+    /// Counts up from 1 over the session
+    pub number: u32,
+    pub chord: String,
+    pub frets: Vec<String>,
+    pub level: u32,
+    pub time_limit_ms: u64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrainerResult {
+    pub prompt: TrainerPrompt,
+    /// The right frets, in time
+    pub correct: bool,
+    /// The right frets, but too late
+    pub timed_out: bool,
+    pub reaction_ms: u64,
+    /// Level after this answer
+    pub level: u32,
+    pub streak: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TrainerStats {
+    pub attempts: u32,
+    pub correct: u32,
+    /// Average reaction of the correct answers
+    pub average_reaction_ms: Option<f64>,
+    pub level: u32,
+    pub max_level: u32,
+    pub streak: u32,
+}
+
+pub struct ChordTrainer {
+    chords: Vec<TrainerChord>,
+    rng: fastrand::Rng,
+    level: u32,
+    streak: u32,
+    misses: u32,
+    attempts: u32,
+    correct: u32,
+    reaction_total_ms: u64,
+    prompt: Option<(TrainerPrompt, Instant)>,
+    prompts: u32,
+}
+
+impl ChordTrainer {
+    /// Train the given chords, lowest fret first (a seed repeats the same prompts)
+    pub fn new(chords: Vec<TrainerChord>, seed: u64) -> Result<Self> {
+        if chords.len() < 2 {
+            bail!("The chord trainer needs at least two chords to choose from");
+        }
+        Ok(Self {
+            chords,
+            rng: fastrand::Rng::with_seed(seed),
+            level: 1,
+            streak: 0,
+            misses: 0,
+            attempts: 0,
+            correct: 0,
+            reaction_total_ms: 0,
+            prompt: None,
+            prompts: 0,
+        })
+    }
+
+    /// Highest level (every chord in the pool)
+    pub fn max_level(&self) -> u32 {
+        self.chords.len() as u32 - 1
+    }
+
+    pub fn level(&self) -> u32 {
+        self.level
+    }
+
+    /// Time allowed to answer at the current level
+    pub fn time_limit(&self) -> Duration {
+        let ms = START_TIME_LIMIT_MS.saturating_sub(TIME_LIMIT_STEP_MS * (self.level as u64 - 1));
+        Duration::from_millis(ms.max(MIN_TIME_LIMIT_MS))
+    }
+
+    /// Chord being asked for
+    pub fn prompt(&self) -> Option<&TrainerPrompt> {
+        self.prompt.as_ref().map(|(prompt, _)| prompt)
+    }
+
+    /// Ask for a new random chord (never the one just asked for)
+    pub fn next_prompt(&mut self, now: Instant) -> TrainerPrompt {
+        let pool = (self.level as usize + 1).min(self.chords.len());
+        let previous = self.prompt().map(|prompt| prompt.chord.clone());
+        let mut index = self.rng.usize(..pool);
+        if previous.as_deref() == Some(self.chords[index].name.as_str()) {
+            index = (index + 1 + self.rng.usize(..pool - 1)) % pool;
+        }
+        let chord = &self.chords[index];
+        self.prompts += 1;
+        let prompt = TrainerPrompt {
+            number: self.prompts,
+            chord: chord.name.clone(),
+            frets: chord.frets.clone(),
+            level: self.level,
+            time_limit_ms: self.time_limit().as_millis() as u64,
+        };
+        self.prompt = Some((prompt.clone(), now));
+        prompt
+    }
+
+    /// Answer the prompt with the frets held on a strum. Wrong frets keep the
+    /// prompt up; the right ones (in time or not) move on to a new chord.
+    pub fn submit(&mut self, pressed_frets: &[String], now: Instant) -> Option<TrainerResult> {
+        let (prompt, asked_at) = self.prompt.clone()?;
+        let reaction = now.saturating_duration_since(asked_at);
+        let right_frets = same_frets(pressed_frets, &prompt.frets);
+        let correct = right_frets && reaction <= self.time_limit();
+        self.attempts += 1;
+
+        if correct {
+            self.correct += 1;
+            self.reaction_total_ms += reaction.as_millis() as u64;
+            self.streak += 1;
+            self.misses = 0;
+            if self.streak >= LEVEL_UP_STREAK && self.level < self.max_level() {
+                self.level += 1;
+                self.streak = 0;
+            }
+        } else {
+            self.streak = 0;
+            self.misses += 1;
+            if self.misses >= LEVEL_DOWN_MISSES && self.level > 1 {
+                self.level -= 1;
+                self.misses = 0;
+            }
+        }
+
+        let result = TrainerResult {
+            prompt,
+            correct,
+            timed_out: right_frets && !correct,
+            reaction_ms: reaction.as_millis() as u64,
+            level: self.level,
+            streak: self.streak,
+        };
+        if right_frets {
+            self.next_prompt(now);
+        }
+        Some(result)
+    }
+
+    pub fn stats(&self) -> TrainerStats {
+        TrainerStats {
+            attempts: self.attempts,
+            correct: self.correct,
+            average_reaction_ms: (self.correct > 0).then(|| self.reaction_total_ms as f64 / self.correct as f64),
+            level: self.level,
+            max_level: self.max_level(),
+            streak: self.streak,
+        }
+    }
+}
+
+fn same_frets(pressed: &[String], expected: &[String]) -> bool {
+    pressed.len() == expected.len() && expected.iter().all(|fret| pressed.contains(fret))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chords() -> Vec<TrainerChord> {
+        ["GREEN", "RED", "YELLOW", "BLUE"].iter().zip(["E", "A", "B", "C#m"])
+            .map(|(fret, name)| TrainerChord { name: name.to_string(), frets: vec![fret.to_string()] })
+            .collect()
+    }
+
+    #[test]
+    fn test_quick_answers_level_up() {
+        let mut trainer = ChordTrainer::new(chords(), 7).unwrap();
+        let mut now = Instant::now();
+        trainer.next_prompt(now);
+        for _ in 0..LEVEL_UP_STREAK {
+            let prompt = trainer.prompt().unwrap().clone();
+            // Level 1 only asks for the two lowest chords, never twice in a row
+            assert!(prompt.chord == "E" || prompt.chord == "A");
+            now += Duration::from_millis(800);
+            let result = trainer.submit(&prompt.frets, now).unwrap();
+            assert!(result.correct);
+            assert_eq!(result.reaction_ms, 800);
+            assert_ne!(trainer.prompt().unwrap().chord, prompt.chord);
+        }
+        assert_eq!(trainer.level(), 2);
+        assert!(trainer.time_limit() < Duration::from_millis(START_TIME_LIMIT_MS));
+        let stats = trainer.stats();
+        assert_eq!((stats.attempts, stats.correct, stats.average_reaction_ms), (5, 5, Some(800.0)));
+    }
+
+    #[test]
+    fn test_misses_wait_and_level_down() {
+        let mut trainer = ChordTrainer::new(chords(), 3).unwrap();
+        trainer.level = 3;
+        let now = Instant::now();
+        let prompt = trainer.next_prompt(now);
+        assert_eq!(prompt.level, 3);
+
+        // Wrong frets keep the prompt up
+        let wrong = vec!["ORANGE".to_string()];
+        let result = trainer.submit(&wrong, now + Duration::from_millis(300)).unwrap();
+        assert!(!result.correct && !result.timed_out);
+        assert_eq!(trainer.prompt().unwrap().number, prompt.number);
+
+        // The right frets too late move on, and two misses go down a level
+        let result = trainer.submit(&prompt.frets, now + Duration::from_secs(10)).unwrap();
+        assert!(result.timed_out);
+        assert_eq!(result.level, 2);
+        assert_eq!(trainer.prompt().unwrap().number, prompt.number + 1);
+
+        assert!(ChordTrainer::new(chords()[..1].to_vec(), 0).is_err());
+    }
+}
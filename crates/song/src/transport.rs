@@ -1,4 +1,23 @@
+use serde::{Deserialize, Serialize};
 use std::time::Instant;
+use crate::meter::{AccentLevel, Meter};
+
+/// A/B practice loop: playback reaching `end_beat` jumps back to `start_beat`
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LoopRegion {
+    pub start_beat: f64,
+    pub end_beat: f64,
+}
+
+impl LoopRegion {
+    pub fn length(&self) -> f64 {
+        self.end_beat - self.start_beat
+    }
+
+    pub fn contains(&self, beat: f64) -> bool {
+        beat >= self.start_beat && beat < self.end_beat
+    }
+}
 
 /// Transport clock for beat-based playback
 #[derive(Debug, Clone)]
@@ -7,12 +26,19 @@ pub struct Transport {
     pub time_sig: [u32; 2],
     pub count_in_bars: u32,
     pub speed_multiplier: f64,
+    /// Accent grouping for the time signature
+    pub meter: Meter,
     
     pub is_playing: bool,
     pub current_beat: f64,
     
     start_instant: Option<Instant>,
     paused_at_beat: f64,
+    loop_region: Option<LoopRegion>,
+    /// Times playback jumped back to the loop start since the loop was set
+    loop_passes: u32,
+    /// Beat the clock stops at until the wait is moved on (wait-for-note practice)
+    wait_beat: Option<f64>,
 }
 
 impl Transport {
@@ -22,13 +48,80 @@ impl Transport {
             time_sig,
             count_in_bars,
             speed_multiplier: 1.0,
+            meter: Meter::new(time_sig),
             is_playing: false,
             current_beat: 0.0,
             start_instant: None,
             paused_at_beat: 0.0,
+            loop_region: None,
+            loop_passes: 0,
+            wait_beat: None,
+        }
+    }
+
+    /// Set the accent grouping (must add up to the bar length)
+    pub fn set_accent_grouping(&mut self, grouping: Vec<u32>) -> anyhow::Result<()> {
+        self.meter = Meter::with_grouping(self.time_sig, grouping)?;
+        Ok(())
+    }
+
+    /// Number of count-in beats (whole bars in the chart's meter)
+    pub fn count_in_beats(&self) -> f64 {
+        self.count_in_bars as f64 * self.meter.beats_per_bar() as f64
+    }
+
+    /// Change the count-in length; a transport waiting at the start of the
+    /// count-in moves to the start of the new one
+    pub fn set_count_in_bars(&mut self, bars: u32) {
+        let at_start = !self.is_playing && self.current_beat == -self.count_in_beats();
+        self.count_in_bars = bars;
+        if at_start {
+            self.stop();
+        }
+    }
+
+    /// Loop between two beats (the end must come after the start)
+    pub fn set_loop(&mut self, start_beat: f64, end_beat: f64) -> anyhow::Result<()> {
+        if end_beat <= start_beat {
+            anyhow::bail!("Loop end ({}) must be after its start ({})", end_beat, start_beat);
+        }
+        self.loop_region = Some(LoopRegion { start_beat, end_beat });
+        self.loop_passes = 0;
+        Ok(())
+    }
+
+    pub fn clear_loop(&mut self) {
+        self.loop_region = None;
+        self.loop_passes = 0;
+    }
+
+    pub fn loop_region(&self) -> Option<LoopRegion> {
+        self.loop_region
+    }
+
+    /// Times playback has jumped back to the loop start
+    pub fn loop_passes(&self) -> u32 {
+        self.loop_passes
+    }
+
+    /// Stop the clock on reaching `beat` (None = play on). Moving the wait
+    /// on from a beat already reached starts the clock again from there.
+    pub fn wait_at(&mut self, beat: Option<f64>) {
+        if self.wait_beat == beat {
+            return;
+        }
+        self.wait_beat = beat;
+        if self.is_playing && self.start_instant.is_none() {
+            self.start_instant = Some(Instant::now());
+            self.paused_at_beat = self.current_beat;
         }
     }
 
+    /// Playing, but stopped at the wait beat
+    pub fn is_waiting(&self) -> bool {
+        self.is_playing && self.start_instant.is_none()
+    }
+
     /// Start or resume playback
     pub fn play(&mut self) {
         if !self.is_playing {
@@ -51,7 +144,7 @@ impl Transport {
     /// Stop and reset to beginning (including count-in)
     pub fn stop(&mut self) {
         self.is_playing = false;
-        self.current_beat = -(self.count_in_bars as f64 * self.time_sig[0] as f64);
+        self.current_beat = -self.count_in_beats();
         self.paused_at_beat = self.current_beat;
         self.start_instant = None;
     }
@@ -82,6 +175,22 @@ impl Transport {
         }
     }
 
+    /// Change the tempo, keeping the current position
+    pub fn set_bpm(&mut self, bpm: f64) {
+        if bpm <= 0.0 {
+            return;
+        }
+        let was_playing = self.is_playing;
+        if was_playing {
+            self.update_current_beat();
+            self.paused_at_beat = self.current_beat;
+        }
+        self.bpm = bpm;
+        if was_playing {
+            self.start_instant = Some(Instant::now());
+        }
+    }
+
     /// Update current beat based on elapsed time
     pub fn update_current_beat(&mut self) {
         if let Some(start) = self.start_instant {
@@ -89,6 +198,35 @@ impl Transport {
             let beats_elapsed = self.seconds_to_beats(elapsed);
             self.current_beat = self.paused_at_beat + beats_elapsed;
         }
+        self.hold_at_wait();
+        self.wrap_loop();
+    }
+
+    /// Stop the clock at the wait beat once playback reaches it
+    fn hold_at_wait(&mut self) {
+        let Some(wait_beat) = self.wait_beat else { return };
+        // Playback that started past the wait beat isn't pulled back
+        if self.current_beat < wait_beat || self.paused_at_beat > wait_beat {
+            return;
+        }
+        self.current_beat = wait_beat;
+        self.paused_at_beat = wait_beat;
+        self.start_instant = None;
+    }
+
+    /// Jump back into the loop once playback passes its end. The clock keeps
+    /// running from the same instant, so the jump is sample-exact.
+    fn wrap_loop(&mut self) {
+        let Some(region) = self.loop_region else { return };
+        // Playback started past the end (e.g. after a seek) isn't pulled back
+        if self.current_beat < region.end_beat || self.paused_at_beat >= region.end_beat {
+            return;
+        }
+        let wraps = ((self.current_beat - region.end_beat) / region.length()).floor() + 1.0;
+        let jump = wraps * region.length();
+        self.current_beat -= jump;
+        self.paused_at_beat -= jump;
+        self.loop_passes += wraps as u32;
     }
 
     /// Get current beat (updates if playing)
@@ -99,6 +237,11 @@ impl Transport {
         self.current_beat
     }
 
+    /// Song time of a beat at normal speed (seconds), e.g. for a backing track
+    pub fn song_seconds(&self, beat: f64) -> f64 {
+        beat * 60.0 / self.bpm
+    }
+
     /// Convert beats to seconds
     pub fn beats_to_seconds(&self, beats: f64) -> f64 {
         let seconds_per_beat = (60.0 / self.bpm) / self.speed_multiplier;
@@ -118,15 +261,20 @@ impl Transport {
 
     /// Get current bar number
     pub fn get_current_bar(&self) -> i32 {
-        let beats_per_bar = self.time_sig[0] as f64;
+        let beats_per_bar = self.meter.beats_per_bar() as f64;
         (self.current_beat / beats_per_bar).floor() as i32
     }
 
     /// Get current beat within bar (0-indexed)
     pub fn get_beat_in_bar(&self) -> f64 {
-        let beats_per_bar = self.time_sig[0] as f64;
+        let beats_per_bar = self.meter.beats_per_bar() as f64;
         self.current_beat.rem_euclid(beats_per_bar)
     }
+
+    /// Accent of the current beat
+    pub fn get_current_accent(&self) -> AccentLevel {
+        self.meter.accent_at(self.get_beat_in_bar().floor() as u32)
+    }
 }
 
 #[cfg(test)]
@@ -135,6 +283,61 @@ mod tests {
     use std::thread;
     use std::time::Duration;
 
+    #[test]
+    fn test_loop_wraps_back_to_start() {
+        let mut transport = Transport::new(120.0, [4, 4], 0);
+        assert!(transport.set_loop(4.0, 4.0).is_err());
+        transport.set_loop(4.0, 8.0).unwrap();
+
+        // 120 BPM: 2 beats per second, so 0.5 s after beat 7 is beat 8 (wrapped to 4)
+        transport.seek(7.0);
+        transport.play();
+        transport.start_instant = Some(Instant::now() - Duration::from_millis(1500));
+        let beat = transport.get_current_beat();
+        assert!((beat - 6.0).abs() < 0.05, "beat {}", beat);
+        assert_eq!(transport.loop_passes(), 1);
+
+        // Later reads keep counting from the wrapped position
+        transport.start_instant = Some(transport.start_instant.unwrap() - Duration::from_millis(2500));
+        let beat = transport.get_current_beat();
+        assert!((beat - 7.0).abs() < 0.05, "beat {}", beat);
+        assert_eq!(transport.loop_passes(), 2);
+
+        // Starting past the loop end plays on
+        transport.seek(9.0);
+        transport.start_instant = Some(Instant::now() - Duration::from_secs(1));
+        assert!(transport.get_current_beat() > 10.0);
+        transport.clear_loop();
+        assert_eq!(transport.loop_passes(), 0);
+    }
+
+    #[test]
+    fn test_wait_holds_the_clock() {
+        let mut transport = Transport::new(120.0, [4, 4], 0);
+        transport.wait_at(Some(2.0));
+        transport.play();
+        transport.start_instant = Some(Instant::now() - Duration::from_secs(3));
+        assert_eq!(transport.get_current_beat(), 2.0);
+        assert!(transport.is_waiting());
+
+        // Pausing and playing again doesn't get past the wait
+        transport.pause();
+        assert!(!transport.is_waiting());
+        transport.play();
+        transport.start_instant = Some(Instant::now() - Duration::from_secs(1));
+        assert_eq!(transport.get_current_beat(), 2.0);
+
+        // Moving the wait on plays on from the held beat
+        transport.wait_at(Some(6.0));
+        assert!(!transport.is_waiting());
+        transport.start_instant = Some(Instant::now() - Duration::from_millis(500));
+        let beat = transport.get_current_beat();
+        assert!((beat - 3.0).abs() < 0.05, "beat {}", beat);
+        transport.wait_at(None);
+        transport.start_instant = Some(Instant::now() - Duration::from_secs(5));
+        assert!(transport.get_current_beat() > 6.0);
+    }
+
     #[test]
     fn test_transport_basic() {
         let mut transport = Transport::new(120.0, [4, 4], 2);
@@ -147,6 +350,21 @@ mod tests {
         assert_eq!(transport.current_beat, -8.0); // -2 bars * 4 beats
     }
 
+    #[test]
+    fn test_set_count_in_bars() {
+        let mut transport = Transport::new(120.0, [4, 4], 2);
+        transport.stop();
+
+        // Stopped at the start, the count-in follows the new length
+        transport.set_count_in_bars(1);
+        assert_eq!(transport.current_beat, -4.0);
+
+        // Past the count-in the position stays put
+        transport.seek(2.0);
+        transport.set_count_in_bars(3);
+        assert_eq!(transport.current_beat, 2.0);
+    }
+
     #[test]
     fn test_transport_playback() {
         let mut transport = Transport::new(120.0, [4, 4], 0);
@@ -159,6 +377,20 @@ mod tests {
         assert!(beat >= 0.9 && beat <= 1.1);
     }
 
+    #[test]
+    fn test_odd_meter_count_in() {
+        let mut transport = Transport::new(140.0, [7, 8], 1);
+        transport.set_accent_grouping(vec![2, 2, 3]).unwrap();
+        transport.stop();
+        assert_eq!(transport.current_beat, -7.0);
+        assert_eq!(transport.get_current_bar(), -1);
+        assert_eq!(transport.get_current_accent(), AccentLevel::Downbeat);
+
+        transport.seek(4.0);
+        assert_eq!(transport.get_current_accent(), AccentLevel::Group);
+        assert!(transport.set_accent_grouping(vec![4, 4]).is_err());
+    }
+
     #[test]
     fn test_transport_speed_multiplier() {
         let mut transport = Transport::new(120.0, [4, 4], 0);
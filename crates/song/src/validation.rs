@@ -0,0 +1,271 @@
+use crate::chart::{SongChart, SongKey};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+/// Fret names accepted in chord mappings
+pub const VALID_FRETS: &[&str] = &[
+    "GREEN", "RED", "YELLOW", "BLUE", "ORANGE",
+    "SOLO_GREEN", "SOLO_RED", "SOLO_YELLOW", "SOLO_BLUE", "SOLO_ORANGE",
+];
+
+/// Instrument types accepted in playback settings
+pub const VALID_INSTRUMENT_TYPES: &[&str] = &["soundfont", "virtual"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// A single problem found in a chart
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ValidationIssue {
+    pub severity: Severity,
+    /// Location inside the chart (e.g. `lanes[0].events[3]`)
+    pub path: String,
+    pub message: String,
+}
+
+impl ValidationIssue {
+    fn error(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { severity: Severity::Error, path: path.into(), message: message.into() }
+    }
+
+    fn warning(path: impl Into<String>, message: impl Into<String>) -> Self {
+        Self { severity: Severity::Warning, path: path.into(), message: message.into() }
+    }
+}
+
+/// Validation result for one chart file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChartReport {
+    pub file: PathBuf,
+    pub issues: Vec<ValidationIssue>,
+}
+
+impl ChartReport {
+    pub fn error_count(&self) -> usize {
+        self.issues.iter().filter(|i| i.severity == Severity::Error).count()
+    }
+
+    pub fn warning_count(&self) -> usize {
+        self.issues.iter().filter(|i| i.severity == Severity::Warning).count()
+    }
+}
+
+impl SongChart {
+    /// Collect all errors and warnings instead of stopping at the first one
+    pub fn lint(&self) -> Vec<ValidationIssue> {
+        let mut issues = Vec::new();
+
+        if self.meta.title.trim().is_empty() {
+            issues.push(ValidationIssue::warning("meta.title", "Title is empty"));
+        }
+        if self.clock.bpm <= 0.0 {
+            issues.push(ValidationIssue::error("clock.bpm", "BPM must be positive"));
+        }
+        if self.clock.time_sig[0] == 0 {
+            issues.push(ValidationIssue::error("clock.timeSig", "Time signature numerator cannot be zero"));
+        }
+        if self.clock.time_sig[1] == 0 {
+            issues.push(ValidationIssue::error("clock.timeSig", "Time signature denominator cannot be zero"));
+        }
+        if let Some(grouping) = &self.clock.accent_grouping {
+            if let Err(e) = crate::meter::validate_grouping(self.clock.time_sig, grouping) {
+                issues.push(ValidationIssue::error("clock.accentGrouping", e.to_string()));
+            }
+        }
+
+        for (path, instrument) in [
+            ("playback.defaultInstrument", &self.playback.default_instrument),
+            ("playback.fallbackInstrument", &self.playback.fallback_instrument),
+        ] {
+            if !VALID_INSTRUMENT_TYPES.contains(&instrument.instrument_type.as_str()) {
+                issues.push(ValidationIssue::warning(
+                    format!("{}.type", path),
+                    format!("Unknown instrument type '{}'", instrument.instrument_type),
+                ));
+            }
+        }
+
+        if let Some(key) = &self.mapping.key {
+            if SongKey::parse(key).is_none() {
+                issues.push(ValidationIssue::error("mapping.key", format!("Invalid key '{}'", key)));
+            }
+        }
+
+        // Chord mappings: fret names and duplicate fret combinations
+        let mut chord_names: Vec<&String> = self.mapping.chords.keys().collect();
+        chord_names.sort();
+        let mut combos: HashMap<Vec<String>, &String> = HashMap::new();
+        for name in chord_names {
+            let mapping = &self.mapping.chords[name];
+            let path = format!("mapping.chords.{}", name);
+            if mapping.frets.is_empty() {
+                issues.push(ValidationIssue::error(&path, "Chord has no frets"));
+            }
+            for fret in &mapping.frets {
+                if !VALID_FRETS.contains(&fret.as_str()) {
+                    issues.push(ValidationIssue::error(&path, format!("Unknown fret '{}'", fret)));
+                }
+            }
+            let mut combo = mapping.frets.clone();
+            combo.sort();
+            if let Some(other) = combos.insert(combo, name) {
+                issues.push(ValidationIssue::warning(
+                    &path,
+                    format!("Same fret combination as chord '{}'", other),
+                ));
+            }
+        }
+
+        // Lane events
+        let mut used_chords = HashSet::new();
+        for (lane_index, lane) in self.lanes.iter().enumerate() {
+            let mut previous_end: Option<f64> = None;
+            for (event_index, event) in lane.events.iter().enumerate() {
+                let path = format!("lanes[{}].events[{}]", lane_index, event_index);
+                used_chords.insert(event.chord.as_str());
+                if !self.mapping.chords.contains_key(&event.chord) {
+                    issues.push(ValidationIssue::error(&path, format!("Chord '{}' not found in mapping", event.chord)));
+                }
+                if event.dur <= 0.0 {
+                    issues.push(ValidationIssue::error(&path, "Chord duration must be positive"));
+                }
+                if event.beat < 0.0 {
+                    issues.push(ValidationIssue::error(&path, "Event starts before beat 0"));
+                }
+                if let Some(end) = previous_end {
+                    if event.beat < end - 1e-6 {
+                        issues.push(ValidationIssue::warning(&path, format!("Overlaps previous event (ends at beat {})", end)));
+                    }
+                }
+                previous_end = Some(event.beat + event.dur);
+            }
+        }
+
+        let mut unused: Vec<&String> = self.mapping.chords.keys()
+            .filter(|name| !used_chords.contains(name.as_str()))
+            .collect();
+        unused.sort();
+        for name in unused {
+            issues.push(ValidationIssue::warning(format!("mapping.chords.{}", name), "Chord is never used"));
+        }
+
+        for (index, section) in self.sections.iter().enumerate() {
+            if section.to_beat <= section.from_beat {
+                issues.push(ValidationIssue::error(
+                    format!("sections[{}]", index),
+                    format!("Section '{}' ends before it starts", section.name),
+                ));
+            }
+        }
+
+        for (index, phrase) in self.star_phrases.iter().enumerate() {
+            if phrase.to_beat <= phrase.from_beat {
+                issues.push(ValidationIssue::error(
+                    format!("starPhrases[{}]", index),
+                    "Star power phrase ends before it starts",
+                ));
+            }
+        }
+
+        let total_beats = self.total_beats();
+        for (index, lyric) in self.lyrics.iter().enumerate() {
+            if lyric.beat < 0.0 || lyric.beat > total_beats {
+                issues.push(ValidationIssue::warning(
+                    format!("lyrics[{}]", index),
+                    format!("Lyric at beat {} is outside the song (0..{})", lyric.beat, total_beats),
+                ));
+            }
+        }
+
+        issues
+    }
+}
+
+/// Validate chart JSON text, reporting parse errors as issues
+pub fn validate_chart_json(json: &str) -> Vec<ValidationIssue> {
+    match serde_json::from_str::<SongChart>(json) {
+        Ok(chart) => chart.lint(),
+        Err(e) => vec![ValidationIssue::error(
+            format!("line {}, column {}", e.line(), e.column()),
+            format!("Invalid chart JSON: {}", e),
+        )],
+    }
+}
+
+/// Validate a single chart file
+pub fn validate_chart_file(path: &Path) -> ChartReport {
+    let issues = match std::fs::read_to_string(path) {
+        Ok(json) => validate_chart_json(&json),
+        Err(e) => vec![ValidationIssue::error("", format!("Failed to read file: {}", e))],
+    };
+    ChartReport { file: path.to_path_buf(), issues }
+}
+
+/// Validate every `.json` chart in a directory (sorted by file name)
+pub fn validate_chart_directory(dir: &Path) -> anyhow::Result<Vec<ChartReport>> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.extension().map(|ext| ext == "json").unwrap_or(false))
+        .collect();
+    files.sort();
+    Ok(files.iter().map(|path| validate_chart_file(path)).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chart_json(chords: &str, events: &str) -> String {
+        format!(r#"{{
+            "meta": {{ "title": "Test", "artist": "Test" }},
+            "clock": {{ "bpm": 120, "timeSig": [4, 4], "countInBars": 1 }},
+            "playback": {{
+                "defaultInstrument": {{ "type": "soundfont", "label": "Clean Guitar" }},
+                "fallbackInstrument": {{ "type": "virtual", "label": "Basic Guitar" }},
+                "allowUserOverrideInstrument": true
+            }},
+            "mapping": {{ "chords": {{ {} }} }},
+            "lanes": [ {{ "name": "Main", "events": [ {} ] }} ],
+            "lyrics": [],
+            "sections": []
+        }}"#, chords, events)
+    }
+
+    #[test]
+    fn test_valid_chart_has_no_issues() {
+        let json = chart_json(
+            r#""C": { "frets": ["GREEN"] }"#,
+            r#"{ "beat": 0, "dur": 4, "chord": "C" }"#,
+        );
+        assert!(validate_chart_json(&json).is_empty());
+    }
+
+    #[test]
+    fn test_collects_multiple_issues() {
+        let json = chart_json(
+            r#""C": { "frets": ["PURPLE"] }, "G": { "frets": ["RED"] }"#,
+            r#"{ "beat": 0, "dur": 4, "chord": "C" }, { "beat": 2, "dur": 0, "chord": "D" }"#,
+        );
+        let issues = validate_chart_json(&json);
+        let errors: Vec<_> = issues.iter().filter(|i| i.severity == Severity::Error).collect();
+        let warnings: Vec<_> = issues.iter().filter(|i| i.severity == Severity::Warning).collect();
+
+        // Unknown fret, missing chord, zero duration
+        assert_eq!(errors.len(), 3);
+        // Overlap and unused chord G
+        assert_eq!(warnings.len(), 2);
+        assert!(issues.iter().any(|i| i.path == "lanes[0].events[1]"));
+    }
+
+    #[test]
+    fn test_parse_error_is_reported() {
+        let issues = validate_chart_json("{ not json");
+        assert_eq!(issues.len(), 1);
+        assert_eq!(issues[0].severity, Severity::Error);
+    }
+}